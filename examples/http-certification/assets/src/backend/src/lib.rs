@@ -97,41 +97,53 @@ fn certify_all_assets() {
             fallback_for: vec![AssetFallbackConfig {
                 scope: "/".to_string(),
                 status_code: Some(StatusCode::OK),
+                priority: 0,
             }],
             aliased_by: vec!["/".to_string()],
             encodings: encodings.clone(),
+            image_variants: vec![],
         },
         AssetConfig::Pattern {
             pattern: "**/*.js".to_string(),
+            exclude: vec![],
+            exclude_dotfiles: false,
             content_type: Some("text/javascript".to_string()),
             headers: get_asset_headers(vec![(
                 "cache-control".to_string(),
                 IMMUTABLE_ASSET_CACHE_CONTROL.to_string(),
             )]),
             encodings: encodings.clone(),
+            image_variants: vec![],
         },
         AssetConfig::Pattern {
             pattern: "**/*.css".to_string(),
+            exclude: vec![],
+            exclude_dotfiles: false,
             content_type: Some("text/css".to_string()),
             headers: get_asset_headers(vec![(
                 "cache-control".to_string(),
                 IMMUTABLE_ASSET_CACHE_CONTROL.to_string(),
             )]),
             encodings,
+            image_variants: vec![],
         },
         AssetConfig::Pattern {
             pattern: "**/*.ico".to_string(),
+            exclude: vec![],
+            exclude_dotfiles: false,
             content_type: Some("image/x-icon".to_string()),
             headers: get_asset_headers(vec![(
                 "cache-control".to_string(),
                 IMMUTABLE_ASSET_CACHE_CONTROL.to_string(),
             )]),
             encodings: vec![],
+            image_variants: vec![],
         },
         AssetConfig::Redirect {
             from: "/old-url".to_string(),
             to: "/".to_string(),
             kind: AssetRedirectKind::Permanent,
+            append_query: false,
             headers: get_asset_headers(vec![
                 ("content-type".to_string(), "text/plain".to_string()),
                 (
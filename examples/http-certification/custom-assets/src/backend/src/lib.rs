@@ -4,7 +4,7 @@ use ic_cdk::{
     *,
 };
 use ic_http_certification::{
-    utils::add_v2_certificate_header, DefaultCelBuilder, DefaultResponseCertification,
+    header, utils::add_v2_certificate_header, DefaultCelBuilder, DefaultResponseCertification,
     DefaultResponseOnlyCelExpression, HeaderField, HttpCertification, HttpCertificationPath,
     HttpCertificationTree, HttpCertificationTreeEntry, HttpRequest, HttpResponse,
     CERTIFICATE_EXPRESSION_HEADER_NAME,
@@ -299,13 +299,7 @@ fn asset_handler(req: &HttpRequest) -> HttpResponse<'static> {
             };
 
             // extract the content encoding header
-            let content_encoding = req.headers().iter().find_map(|(name, value)| {
-                if name.to_lowercase() == "accept-encoding" {
-                    Some(value)
-                } else {
-                    None
-                }
-            });
+            let content_encoding = req.get_header(header::ACCEPT_ENCODING);
 
             let CertifiedHttpResponse {
                 certification,
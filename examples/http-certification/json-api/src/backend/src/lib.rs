@@ -3,13 +3,11 @@ use ic_cdk::{
     *,
 };
 use ic_http_certification::{
-    utils::add_v2_certificate_header, DefaultCelBuilder, DefaultFullCelExpression,
-    DefaultResponseCertification, DefaultResponseOnlyCelExpression, HttpCertification,
-    HttpCertificationPath, HttpCertificationTree, HttpCertificationTreeEntry, HttpRequest,
-    HttpResponse, Method, StatusCode, CERTIFICATE_EXPRESSION_HEADER_NAME,
+    DefaultCelBuilder, DefaultFullCelExpression, DefaultResponseCertification,
+    DefaultResponseOnlyCelExpression, HttpCertificationRouter, HttpRequest, HttpResponse,
+    HttpRouteParams, Method, StatusCode,
 };
 use lazy_static::lazy_static;
-use matchit::{Params, Router};
 use serde::Deserialize;
 use std::{cell::RefCell, collections::HashMap};
 
@@ -26,8 +24,7 @@ fn init() {
     certify_not_allowed_todo_responses();
     certify_not_found_response();
 
-    // prepare query and update handlers
-    prepare_query_handlers();
+    // prepare update handlers
     prepare_update_handlers();
 }
 
@@ -40,68 +37,44 @@ fn post_upgrade() {
 
 #[query]
 fn http_request(req: HttpRequest) -> HttpResponse<'static> {
-    let req_path = req.get_path().expect("Failed to get req path");
-
-    QUERY_ROUTER.with_borrow(|query_router| {
-        let method_router = query_router
-            .get(&req.method().as_str().to_uppercase())
-            .unwrap();
-        let handler_match = method_router.at(&req_path).unwrap();
-        let handler = handler_match.value;
-
-        handler(&req, &handler_match.params)
+    ROUTER.with_borrow(|router| {
+        router
+            .serve_query(
+                &req,
+                &data_certificate().expect("No data certificate available"),
+            )
+            .expect("Failed to serve query")
     })
 }
 
 #[update]
 fn http_request_update(req: HttpRequest) -> HttpResponse<'static> {
-    let req_path = req.get_path().expect("Failed to get req path");
-
-    UPDATE_ROUTER.with_borrow(|update_router| {
-        let method_router = update_router
-            .get(&req.method().as_str().to_uppercase())
-            .unwrap();
-        let handler_match = method_router.at(&req_path).unwrap();
-        let handler = handler_match.value;
-
-        handler(&req, &handler_match.params)
-    })
+    // release the router before calling the handler, so the handler can certify new responses
+    let update_handler = ROUTER
+        .with_borrow(|router| router.update_handler(&req))
+        .expect("Failed to get req path");
+
+    match update_handler {
+        Some((handler, params)) => handler(&req, &params),
+        None => no_update_call_handler(&req),
+    }
 }
 
 // Storage
 
-#[derive(Debug, Clone)]
-struct CertifiedHttpResponse<'a> {
-    response: HttpResponse<'a>,
-    certification: HttpCertification,
-}
-
 thread_local! {
     // todo items
     static NEXT_TODO_ID: RefCell<u32> = RefCell::new(0);
     static TODO_ITEMS: RefCell<HashMap<u32, TodoItem>> = RefCell::new(HashMap::new());
 
-    // HTTP certification tree
-    static HTTP_TREE: RefCell<HttpCertificationTree> = RefCell::new(HttpCertificationTree::default());
-
-    // responses
-    static FALLBACK_RESPONSES: RefCell<HashMap<String, CertifiedHttpResponse<'static>>> = RefCell::new(HashMap::new());
-    static RESPONSES: RefCell<HashMap<(String, String), CertifiedHttpResponse<'static>>> = RefCell::new(HashMap::new());
-
-    // routers
-    static QUERY_ROUTER: RefCell<HashMap<String, Router<RouteHandler>>> = RefCell::new(HashMap::new());
-    static UPDATE_ROUTER: RefCell<HashMap<String, Router<RouteHandler>>> = RefCell::new(HashMap::new());
+    // certified responses and update handlers
+    static ROUTER: RefCell<HttpCertificationRouter> = RefCell::new(HttpCertificationRouter::default());
 }
 
 const TODOS_PATH: &str = "/todos";
 const NOT_FOUND_PATH: &str = "";
 
 lazy_static! {
-    static ref TODOS_TREE_PATH: HttpCertificationPath<'static> =
-        HttpCertificationPath::exact(TODOS_PATH);
-    static ref NOT_FOUND_TREE_PATH: HttpCertificationPath<'static> =
-        HttpCertificationPath::wildcard(NOT_FOUND_PATH);
-
     // cel expressions
 
     // define a full CEL expression that will certify the following:
@@ -122,7 +95,6 @@ lazy_static! {
             vec![],
         ))
         .build();
-    static ref TODO_CEL_EXPR: String = TODO_CEL_EXPR_DEF.to_string();
 
     // define a response-only CEL expression that will certify the following:
     // - response
@@ -135,7 +107,6 @@ lazy_static! {
             vec![],
         ))
         .build();
-    static ref NOT_FOUND_CEL_EXPR: String = NOT_FOUND_CEL_EXPR_DEF.to_string();
 }
 
 // Certification
@@ -151,9 +122,9 @@ fn certify_list_todos_response() {
         )
         .encode()
     });
-    let mut response = create_response(StatusCode::OK, body);
+    let response = create_response(StatusCode::OK, body);
 
-    certify_response(request, &mut response, &TODOS_TREE_PATH);
+    certify_response(request, response);
 }
 
 fn certify_not_allowed_todo_responses() {
@@ -173,189 +144,56 @@ fn certify_not_allowed_todo_responses() {
             .build();
 
         let body = ErrorResponse::not_allowed().encode();
-        let mut response = create_response(StatusCode::METHOD_NOT_ALLOWED, body);
+        let response = create_response(StatusCode::METHOD_NOT_ALLOWED, body);
 
-        certify_response(request, &mut response, &TODOS_TREE_PATH);
+        certify_response(request, response);
     });
 }
 
 fn certify_not_found_response() {
     let body = ErrorResponse::not_found().encode();
-    let mut response = create_response(StatusCode::NOT_FOUND, body);
-
-    let tree_path = HttpCertificationPath::wildcard(NOT_FOUND_PATH);
-
-    // insert the `Ic-CertificationExpression` header with the stringified CEL expression as its value
-    response.add_header((
-        CERTIFICATE_EXPRESSION_HEADER_NAME.to_string(),
-        NOT_FOUND_CEL_EXPR.clone(),
-    ));
-
-    // create the certification for this response and CEL expression pair
-    let certification =
-        HttpCertification::response_only(&NOT_FOUND_CEL_EXPR_DEF, &response, None).unwrap();
-
-    FALLBACK_RESPONSES.with_borrow_mut(|responses| {
-        responses.insert(
-            NOT_FOUND_PATH.to_string(),
-            CertifiedHttpResponse {
-                response,
-                certification,
-            },
-        );
-    });
+    let response = create_response(StatusCode::NOT_FOUND, body);
 
-    HTTP_TREE.with_borrow_mut(|http_tree| {
-        // insert the certification into the certification tree
-        http_tree.insert(&HttpCertificationTreeEntry::new(tree_path, &certification));
+    ROUTER.with_borrow_mut(|router| {
+        // certify the response for all request paths without a certified response
+        router
+            .certify_fallback_response(NOT_FOUND_PATH, response, &NOT_FOUND_CEL_EXPR_DEF)
+            .unwrap();
 
         // set the canister's certified data
-        set_certified_data(&http_tree.root_hash());
+        set_certified_data(&router.root_hash());
     });
 }
 
-fn certify_response(
-    request: HttpRequest,
-    response: &mut HttpResponse<'static>,
-    tree_path: &HttpCertificationPath,
-) {
-    let request_path = request.get_path().unwrap();
-
-    // retrieve and remove any existing response for the request method and path
-    let existing_response = RESPONSES.with_borrow_mut(|responses| {
-        responses.remove(&(request.method().to_string(), request_path.clone()))
-    });
-
-    // if there is an existing response, remove its certification from the certification tree
-    if let Some(existing_response) = existing_response {
-        HTTP_TREE.with_borrow_mut(|http_tree| {
-            http_tree.delete(&HttpCertificationTreeEntry::new(
-                tree_path.clone(),
-                &existing_response.certification,
-            ));
-        })
-    }
-
-    // insert the `Ic-CertificationExpression` header with the stringified CEL expression as its value
-    response.add_header((
-        CERTIFICATE_EXPRESSION_HEADER_NAME.to_string(),
-        TODO_CEL_EXPR.clone(),
-    ));
-
-    // create the certification for this response and CEL expression pair
-    let certification =
-        HttpCertification::full(&TODO_CEL_EXPR_DEF, &request, &response, None).unwrap();
-
-    RESPONSES.with_borrow_mut(|responses| {
-        // store the response for later retrieval
-        responses.insert(
-            (request.method().to_string(), request_path),
-            CertifiedHttpResponse {
-                response: response.clone(),
-                certification: certification.clone(),
-            },
-        );
-    });
-
-    HTTP_TREE.with_borrow_mut(|http_tree| {
-        // insert the certification into the certification tree
-        http_tree.insert(&HttpCertificationTreeEntry::new(tree_path, &certification));
+fn certify_response(request: HttpRequest, response: HttpResponse<'static>) {
+    ROUTER.with_borrow_mut(|router| {
+        // certify the response, replacing any existing response for the request method and path
+        router
+            .certify_response(&request, response, &TODO_CEL_EXPR_DEF)
+            .unwrap();
 
         // set the canister's certified data
-        set_certified_data(&http_tree.root_hash());
+        set_certified_data(&router.root_hash());
     });
 }
 
 // Handlers
 
-fn prepare_query_handlers() {
-    insert_query_route("POST", "/todos", upgrade_to_update_call_handler);
-    insert_query_route("PATCH", "/todos/{id}", upgrade_to_update_call_handler);
-    insert_query_route("DELETE", "/todos/{id}", upgrade_to_update_call_handler);
-
-    insert_query_route("GET", "/{*p}", query_handler);
-    ["HEAD", "PUT", "OPTIONS", "TRACE", "CONNECT"]
-        .iter()
-        .for_each(|method| {
-            insert_query_route(method, "/{*p}", query_handler);
-        });
-}
-
-fn insert_query_route(method: &str, path: &str, route_handler: RouteHandler) {
-    QUERY_ROUTER.with_borrow_mut(|query_router| {
-        let router = query_router.entry(method.to_string()).or_default();
-
-        router.insert(path, route_handler).unwrap();
-    });
-}
-
 fn prepare_update_handlers() {
-    insert_update_route("POST", TODOS_PATH, create_todo_item_handler);
-    insert_update_route("PATCH", "/todos/{id}", update_todo_item_handler);
-    insert_update_route("DELETE", "/todos/{id}", delete_todo_item_handler);
-
-    insert_update_route("GET", "/{*p}", no_update_call_handler);
-    ["HEAD", "PUT", "OPTIONS", "TRACE", "CONNECT"]
-        .iter()
-        .for_each(|method| {
-            insert_update_route(method, "/{*p}", no_update_call_handler);
-        });
-}
-
-fn insert_update_route(method: &str, path: &str, route_handler: RouteHandler) {
-    UPDATE_ROUTER.with_borrow_mut(|update_router| {
-        let router = update_router.entry(method.to_string()).or_default();
-
-        router.insert(path, route_handler).unwrap();
-    });
-}
-
-fn query_handler(request: &HttpRequest, _params: &Params) -> HttpResponse<'static> {
-    let request_path = request.get_path().expect("Failed to get req path");
-
-    // first check if there is a certified response for the request method and path
-    let (tree_path, certified_response) = RESPONSES
-        .with_borrow(|responses| {
-            responses
-                .get(&(request.method().to_string(), request_path.clone()))
-                .map(|response| {
-                    (
-                        HttpCertificationPath::exact(&request_path),
-                        response.clone(),
-                    )
-                })
-        })
-        // if there is no certified response, use the fallback response
-        .unwrap_or_else(|| {
-            FALLBACK_RESPONSES.with_borrow(|fallback_responses| {
-                fallback_responses
-                    .get(NOT_FOUND_PATH)
-                    .clone()
-                    .map(|response| (NOT_FOUND_TREE_PATH.to_owned(), response.clone()))
-                    .unwrap()
-            })
-        });
-
-    let mut response = certified_response.response;
-
-    HTTP_TREE.with_borrow(|http_tree| {
-        add_v2_certificate_header(
-            &data_certificate().expect("No data certificate available"),
-            &mut response,
-            &http_tree
-                .witness(
-                    &HttpCertificationTreeEntry::new(&tree_path, certified_response.certification),
-                    &request_path,
-                )
-                .unwrap(),
-            &tree_path.to_expr_path(),
-        );
+    ROUTER.with_borrow_mut(|router| {
+        router
+            .add_update_route(Method::POST, TODOS_PATH, create_todo_item_handler)
+            .unwrap();
+        router
+            .add_update_route(Method::PATCH, "/todos/{id}", update_todo_item_handler)
+            .unwrap();
+        router
+            .add_update_route(Method::DELETE, "/todos/{id}", delete_todo_item_handler)
+            .unwrap();
     });
-
-    response
 }
 
-fn create_todo_item_handler(req: &HttpRequest, _params: &Params) -> HttpResponse<'static> {
+fn create_todo_item_handler(req: &HttpRequest, _params: &HttpRouteParams) -> HttpResponse<'static> {
     let req_body: CreateTodoItemRequest = json_decode(req.body());
 
     let id = NEXT_TODO_ID.with_borrow_mut(|f| {
@@ -382,7 +220,7 @@ fn create_todo_item_handler(req: &HttpRequest, _params: &Params) -> HttpResponse
     create_response(StatusCode::CREATED, body)
 }
 
-fn update_todo_item_handler(req: &HttpRequest, params: &Params) -> HttpResponse<'static> {
+fn update_todo_item_handler(req: &HttpRequest, params: &HttpRouteParams) -> HttpResponse<'static> {
     let req_body: UpdateTodoItemRequest = json_decode(req.body());
     let id: u32 = params.get("id").unwrap().parse().unwrap();
 
@@ -404,7 +242,7 @@ fn update_todo_item_handler(req: &HttpRequest, params: &Params) -> HttpResponse<
     create_response(StatusCode::OK, body)
 }
 
-fn delete_todo_item_handler(_req: &HttpRequest, params: &Params) -> HttpResponse<'static> {
+fn delete_todo_item_handler(_req: &HttpRequest, params: &HttpRouteParams) -> HttpResponse<'static> {
     let id: u32 = params.get("id").unwrap().parse().unwrap();
 
     TODO_ITEMS.with_borrow_mut(|items| {
@@ -417,14 +255,7 @@ fn delete_todo_item_handler(_req: &HttpRequest, params: &Params) -> HttpResponse
     create_response(StatusCode::NO_CONTENT, body)
 }
 
-fn upgrade_to_update_call_handler(
-    _http_request: &HttpRequest,
-    _params: &Params,
-) -> HttpResponse<'static> {
-    HttpResponse::builder().with_upgrade(true).build()
-}
-
-fn no_update_call_handler(_http_request: &HttpRequest, _params: &Params) -> HttpResponse<'static> {
+fn no_update_call_handler(_http_request: &HttpRequest) -> HttpResponse<'static> {
     create_response(StatusCode::BAD_REQUEST, vec![])
 }
 
@@ -1,5 +1,3 @@
-use ic_http_certification::{HttpRequest, HttpResponse};
-use matchit::Params;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize)]
@@ -59,5 +57,3 @@ pub type DeleteTodoItemResponse<'a> = ApiResponse<'a, ()>;
 pub type ListTodosResponse<'a> = ApiResponse<'a, Vec<TodoItem>>;
 
 pub type ErrorResponse<'a> = ApiResponse<'a, ()>;
-
-pub type RouteHandler = for<'a> fn(&'a HttpRequest, &'a Params) -> HttpResponse<'static>;
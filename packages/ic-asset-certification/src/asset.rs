@@ -1,4 +1,6 @@
-use std::borrow::Cow;
+use crate::{AssetBody, AssetReader, ReaderContent};
+use candid::{CandidType, Deserialize};
+use std::{borrow::Cow, sync::Arc};
 
 /// An asset to be certified and served by an [AssetRouter](crate::AssetRouter).
 ///
@@ -28,11 +30,22 @@ use std::borrow::Cow;
 ///
 /// let asset = Asset::new(path, content);
 /// ```
+///
+/// ## With content read on demand
+///
+/// ```
+/// use ic_asset_certification::Asset;
+///
+/// let path = "foo";
+/// let content = vec![1, 2, 3];
+///
+/// let asset = Asset::from_reader(path, content);
+/// ```
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Asset<'content, 'path> {
     pub(crate) path: Cow<'path, str>,
     pub(crate) url: Cow<'path, str>,
-    pub(crate) content: Cow<'content, [u8]>,
+    pub(crate) content: AssetBody<'content>,
 }
 
 impl<'content, 'path> Asset<'content, 'path> {
@@ -45,12 +58,87 @@ impl<'content, 'path> Asset<'content, 'path> {
         Asset {
             url: Cow::Owned(path_to_url(path.as_ref())),
             path,
-            content: content.into(),
+            content: AssetBody::Bytes(Arc::new(content.into())),
+        }
+    }
+
+    /// Creates a new asset with the given path, whose content is read on demand from the
+    /// given [AssetReader], e.g. from stable memory.
+    ///
+    /// The content is certified chunk by chunk and is only read when a response is served,
+    /// so it never has to be loaded into the heap all at once.
+    pub fn from_reader(
+        path: impl Into<Cow<'path, str>>,
+        reader: impl AssetReader + 'static,
+    ) -> Self {
+        let path = path.into();
+
+        Asset {
+            url: Cow::Owned(path_to_url(path.as_ref())),
+            path,
+            content: AssetBody::Reader(Arc::new(ReaderContent::new(reader))),
         }
     }
+
+    /// Returns the path of the asset.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the URL that the asset is served on, this is the path of the
+    /// asset with a leading slash, or one of its aliases.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Returns the content of the asset, or [None] if the content is read on demand
+    /// from an [AssetReader] or was uploaded in chunks.
+    pub fn content(&self) -> Option<&[u8]> {
+        match &self.content {
+            AssetBody::Bytes(content) | AssetBody::Precertified(content, _) => Some(content),
+            AssetBody::Reader(_) | AssetBody::Chunked(_) => None,
+        }
+    }
+
+    /// Returns the length of the content of the asset in bytes.
+    pub fn content_len(&self) -> usize {
+        self.content.len()
+    }
 }
 
-fn path_to_url(path: &str) -> String {
+/// An owned form of an [Asset] that can be sent to a canister in a Candid update call, e.g. by a
+/// controller that pushes assets to the canister at runtime. The [AssetConfig](crate::AssetConfig)
+/// of the assets can be sent in the same call.
+///
+/// # Examples
+///
+/// ```
+/// use ic_asset_certification::{Asset, OwnedAsset};
+///
+/// let owned_asset = OwnedAsset {
+///     path: "index.html".to_string(),
+///     content: b"<html></html>".to_vec(),
+/// };
+///
+/// let asset = Asset::from(owned_asset);
+/// assert_eq!(asset.url(), "/index.html");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
+pub struct OwnedAsset {
+    /// The path of the asset.
+    pub path: String,
+
+    /// The content of the asset.
+    pub content: Vec<u8>,
+}
+
+impl From<OwnedAsset> for Asset<'static, 'static> {
+    fn from(asset: OwnedAsset) -> Self {
+        Asset::new(asset.path, asset.content)
+    }
+}
+
+pub(crate) fn path_to_url(path: &str) -> String {
     if !path.starts_with('/') {
         format!("/{}", path)
     } else {
@@ -72,7 +160,7 @@ mod tests {
 
         assert_eq!(asset.path, path);
         assert_eq!(asset.url, "/foo");
-        assert_eq!(asset.content, content);
+        assert_eq!(asset.content(), Some(content.as_slice()));
     }
 
     #[rstest]
@@ -84,6 +172,30 @@ mod tests {
 
         assert_eq!(asset.path, path);
         assert_eq!(asset.url, "/foo");
-        assert_eq!(asset.content, content);
+        assert_eq!(asset.content(), Some(content));
+    }
+
+    #[rstest]
+    fn asset_from_reader() {
+        let path = "foo";
+        let content = vec![1, 2, 3];
+
+        let asset = Asset::from_reader(path, content);
+
+        assert_eq!(asset.path, path);
+        assert_eq!(asset.url, "/foo");
+        assert_eq!(asset.content(), None);
+        assert_eq!(asset.content_len(), 3);
+        assert_eq!(asset.content.read(Some(1..3)), [2, 3].as_slice());
+    }
+
+    #[rstest]
+    fn asset_accessors() {
+        let asset = Asset::new("foo/bar.html", [1, 2, 3].as_slice());
+
+        assert_eq!(asset.path(), "foo/bar.html");
+        assert_eq!(asset.url(), "/foo/bar.html");
+        assert_eq!(asset.content(), Some([1, 2, 3].as_slice()));
+        assert_eq!(asset.content_len(), 3);
     }
 }
@@ -1,7 +1,13 @@
-use crate::{Asset, AssetCertificationError};
-use globset::{Glob, GlobMatcher};
-use ic_http_certification::StatusCode;
-use std::fmt::{Display, Formatter};
+use crate::{Asset, AssetCertificationResult};
+use candid::{CandidType, Deserialize};
+use globset::{Glob, GlobBuilder, GlobMatcher, GlobSet, GlobSetBuilder};
+use ic_http_certification::{Hash, StatusCode};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter},
+    ops::Deref,
+};
 
 /// Certification configuration for [assets](Asset). This configuration
 /// is passed alongside the [assets](Asset) to the
@@ -38,6 +44,7 @@ use std::fmt::{Display, Formatter};
 ///         AssetEncoding::Brotli.default_config(),
 ///         AssetEncoding::Gzip.default_config(),
 ///     ],
+///     image_variants: vec![],
 /// };
 /// ```
 ///
@@ -62,12 +69,14 @@ use std::fmt::{Display, Formatter};
 ///     fallback_for: vec![AssetFallbackConfig {
 ///         scope: "/".to_string(),
 ///         status_code: Some(StatusCode::OK),
+///         priority: 0,
 ///     }],
 ///     aliased_by: vec!["/".to_string()],
 ///     encodings: vec![
 ///         AssetEncoding::Brotli.default_config(),
 ///         AssetEncoding::Gzip.default_config(),
 ///     ],
+///     image_variants: vec![],
 /// };
 /// ```
 ///
@@ -105,10 +114,12 @@ use std::fmt::{Display, Formatter};
 ///         AssetFallbackConfig {
 ///             scope: "/css".to_string(),
 ///             status_code: Some(StatusCode::NOT_FOUND),
+///             priority: 0,
 ///         },
 ///         AssetFallbackConfig {
 ///             scope: "/js".to_string(),
 ///             status_code: Some(StatusCode::NOT_FOUND),
+///             priority: 0,
 ///         },
 ///     ],
 ///     aliased_by: vec![
@@ -123,6 +134,7 @@ use std::fmt::{Display, Formatter};
 ///         AssetEncoding::Brotli.default_config(),
 ///         AssetEncoding::Gzip.default_config(),
 ///     ],
+///     image_variants: vec![],
 /// };
 /// ```
 ///
@@ -138,6 +150,8 @@ use std::fmt::{Display, Formatter};
 ///
 /// let config = AssetConfig::Pattern {
 ///     pattern: "**/*.css".to_string(),
+///     exclude: vec![],
+///     exclude_dotfiles: false,
 ///     content_type: Some("text/css".to_string()),
 ///     headers: vec![
 ///         ("Cache-Control".to_string(), "public, max-age=31536000, immutable".to_string()),
@@ -146,6 +160,27 @@ use std::fmt::{Display, Formatter};
 ///         AssetEncoding::Brotli.default_config(),
 ///         AssetEncoding::Gzip.default_config(),
 ///     ],
+///     image_variants: vec![],
+/// };
+/// ```
+///
+/// ## Excluding files
+///
+/// This example configures all files, except for hidden files and source maps,
+/// to be served with a `cache-control` header. The excluded files are not
+/// certified and are not served.
+///
+/// ```
+/// use ic_asset_certification::AssetConfig;
+///
+/// let config = AssetConfig::Pattern {
+///     pattern: "**/*".to_string(),
+///     exclude: vec!["**/*.map".to_string()],
+///     exclude_dotfiles: true,
+///     content_type: None,
+///     headers: vec![("Cache-Control".to_string(), "public, no-cache".to_string())],
+///     encodings: vec![],
+///     image_variants: vec![],
 /// };
 /// ```
 ///
@@ -161,6 +196,7 @@ use std::fmt::{Display, Formatter};
 ///     from: "/old".to_string(),
 ///     to: "/new".to_string(),
 ///     kind: AssetRedirectKind::Temporary,
+///     append_query: false,
 ///     headers: vec![(
 ///         "content-type".to_string(),
 ///         "text/plain; charset=utf-8".to_string(),
@@ -168,6 +204,20 @@ use std::fmt::{Display, Formatter};
 /// };
 /// ```
 ///
+/// ## Namespace
+///
+/// This example groups the configurations of a frontend deployment under the
+/// `app-v1` namespace, so all of its assets can be deleted at once.
+///
+/// ```
+/// use ic_asset_certification::AssetConfig;
+///
+/// let config = AssetConfig::Namespace {
+///     name: "app-v1".to_string(),
+///     configs: AssetConfig::spa("app-v1/index.html"),
+/// };
+/// ```
+///
 /// ## Permanent redirect
 ///
 /// This example configures a redirect from `/old` to `/new`. The redirect is
@@ -180,6 +230,7 @@ use std::fmt::{Display, Formatter};
 ///     from: "/old".to_string(),
 ///     to: "/new".to_string(),
 ///     kind: AssetRedirectKind::Permanent,
+///     append_query: false,
 ///     headers: vec![(
 ///         "content-type".to_string(),
 ///         "text/plain; charset=utf-8".to_string(),
@@ -237,9 +288,20 @@ pub enum AssetConfig {
         /// - `/assets`
         /// - `/`
         ///
-        /// If multiple fallback assets are configured, the first one found will
-        /// be used. If no asset is found with any of these fallback scopes, no
-        /// response will be returned.
+        /// If multiple fallback assets match a request, the fallback with the most
+        /// specific scope is used, following the order listed above. This order is
+        /// required by response verification, which rejects a fallback if a more
+        /// specific scope is certified for the request.
+        ///
+        /// Only one fallback can be served for each scope. If multiple assets are
+        /// configured as a fallback for the same scope, the asset with the highest
+        /// [priority](AssetFallbackConfig::priority) is used. If they also share the
+        /// same priority, the asset that was certified last is used. Once that asset
+        /// is deleted, the asset with the next highest priority is certified and served
+        /// for the scope instead.
+        ///
+        /// If no asset is found with any of these fallback scopes, no response will
+        /// be returned.
         fallback_for: Vec<AssetFallbackConfig>,
 
         /// A list of aliases for this asset. If a request is made for one of
@@ -280,7 +342,40 @@ pub enum AssetConfig {
         ///
         /// The asset router will return the highest priority encoding that has
         /// been certified and is supported by the client.
+        ///
+        /// If any encodings are configured, a certified `Vary: accept-encoding`
+        /// header is added to all responses for the asset, including the
+        /// unencoded response, so that caches don't serve the wrong encoding.
+        /// This header is not added if a `Vary` header is already configured in
+        /// the `headers` list.
         encodings: Vec<(AssetEncoding, String)>,
+
+        /// A list of alternative image formats that can be used to serve the asset,
+        /// e.g. WebP or AVIF versions of a PNG or JPEG image.
+        ///
+        /// Each entry is a tuple of the [image format](AssetImageFormat) and the
+        /// file extension used in the file path. For example, to include AVIF and
+        /// WebP versions:
+        /// `vec![AssetImageFormat::Avif.default_config(), AssetImageFormat::Webp.default_config()]`
+        ///
+        /// Each image format referenced must be provided to the asset router as a
+        /// separate file with the same filename as the original file, but with its
+        /// file extension replaced by the configured one. For example, if the current
+        /// matched file is named `logo.png`, then the asset router will look for
+        /// `logo.avif` and `logo.webp`.
+        ///
+        /// If the file is found, it will be certified and served on the same URL as
+        /// the original file, with the content type of the image format, to clients
+        /// that explicitly list the content type in their `Accept` header, e.g.
+        /// `Accept: image/avif,image/webp,*/*`. Wildcards such as `image/*` are not
+        /// considered, since browsers send them regardless of the formats they
+        /// support. If several image formats are accepted, the one with the highest
+        /// quality value is served, with AVIF taking precedence over WebP.
+        ///
+        /// If any image formats are configured, a certified `Vary: accept` header is
+        /// added to all responses for the asset, in the same way as for
+        /// [encodings](AssetConfig::File::encodings).
+        image_variants: Vec<(AssetImageFormat, String)>,
     },
 
     /// Matches files using a glob pattern.
@@ -310,6 +405,28 @@ pub enum AssetConfig {
         ///   class notation. e.g., `[*]` matches `*`.
         pattern: String,
 
+        /// Glob patterns for files that are excluded from certification, using the
+        /// same syntax as [pattern](AssetConfig::Pattern::pattern), e.g. `**/*.map`
+        /// for source maps.
+        ///
+        /// Files that match the [pattern](AssetConfig::Pattern::pattern) and one of
+        /// these globs are not certified and are not served by the
+        /// [AssetRouter](crate::AssetRouter), even if they match a configuration
+        /// that is listed after this one.
+        exclude: Vec<String>,
+
+        /// Whether hidden files and all files in hidden directories are excluded
+        /// from certification, in the same way as files matching one of the
+        /// [exclude](AssetConfig::Pattern::exclude) globs. A path is hidden if any of
+        /// its segments starts with a `.`, e.g. `.env`, `assets/.DS_Store` or
+        /// `.well-known/ic-domains`.
+        ///
+        /// Set this to `false` to certify hidden files like any other file, which is
+        /// the behavior of previous versions. Files that need to be served from a
+        /// hidden directory, such as `.well-known/ic-domains`, can still be certified
+        /// with a configuration that is listed before this one.
+        exclude_dotfiles: bool,
+
         /// The content type of the file (e.g. "text/javascript").
         ///
         /// Providing this option will auto-insert a `Content-Type` header with
@@ -359,6 +476,75 @@ pub enum AssetConfig {
         ///
         /// The asset router will return the highest priority encoding that has
         /// been certified and is supported by the client.
+        ///
+        /// If any encodings are configured, a certified `Vary: accept-encoding`
+        /// header is added to all responses for the asset, including the
+        /// unencoded response, so that caches don't serve the wrong encoding.
+        /// This header is not added if a `Vary` header is already configured in
+        /// the `headers` list.
+        encodings: Vec<(AssetEncoding, String)>,
+
+        /// A list of alternative image formats that can be used to serve the asset,
+        /// see the [image_variants](AssetConfig::File::image_variants) configuration.
+        ///
+        /// If any image formats are configured, a certified `Vary: accept` header is
+        /// added to all responses for the asset, in the same way as for
+        /// [encodings](AssetConfig::Pattern::encodings).
+        image_variants: Vec<(AssetImageFormat, String)>,
+    },
+
+    /// Matches a specific file that is served as a certified error page.
+    ///
+    /// The file is not served on its own path, instead it is served with the
+    /// configured status code for requests within the configured scopes.
+    /// Error pages with a [NOT_FOUND](StatusCode::NOT_FOUND) status code are
+    /// served by [serve_asset](crate::AssetRouter::serve_asset) when a request does
+    /// not match any asset or fallback. Error pages with any status code, e.g.
+    /// [FORBIDDEN](StatusCode::FORBIDDEN) or
+    /// [INTERNAL_SERVER_ERROR](StatusCode::INTERNAL_SERVER_ERROR), can be served
+    /// using [serve_error](crate::AssetRouter::serve_error), e.g. when a canister's
+    /// own request handler rejects a request.
+    ///
+    /// Response verification only accepts a response for the most specific scope
+    /// that is certified for a request. An error page for a scope therefore prevents
+    /// fallbacks and error pages of less specific scopes from being served within
+    /// that scope. For example, if a [FORBIDDEN](StatusCode::FORBIDDEN) error page
+    /// is configured for `/admin`, a [NOT_FOUND](StatusCode::NOT_FOUND) error page
+    /// should be configured for `/admin` as well.
+    ///
+    /// The error pages of all status codes for a scope are certified under the same
+    /// wildcard path as the fallback for that scope, so response verification accepts
+    /// any of them for any request within the scope. A malicious replica can therefore
+    /// serve one error page in place of another, e.g. the
+    /// [FORBIDDEN](StatusCode::FORBIDDEN) error page instead of the
+    /// [NOT_FOUND](StatusCode::NOT_FOUND) error page, or an error page instead of the
+    /// fallback. Content that must only be served with a specific status code should be
+    /// certified as a [File](AssetConfig::File) on its own path instead.
+    ErrorPage {
+        /// The path to the file. This path must exactly match the
+        /// path of an [Asset] provided to the [AssetRouter](crate::AssetRouter)
+        /// with this config.
+        path: String,
+
+        /// The HTTP status code to return when serving the error page.
+        status_code: StatusCode,
+
+        /// The scopes to use this error page for. Scopes are resolved in the same
+        /// way as the [fallback_for](AssetConfig::File::fallback_for) configuration,
+        /// the error page with the most specific scope is used.
+        scopes: Vec<String>,
+
+        /// The content type of the error page, see the
+        /// [content_type](AssetConfig::File::content_type) configuration.
+        content_type: Option<String>,
+
+        /// Additional headers to be inserted into the response. Each additional
+        /// header added will be included in certification and served by the
+        /// [AssetRouter](crate::AssetRouter) for matching requests.
+        headers: Vec<(String, String)>,
+
+        /// A list of alternative encodings that can be used to serve the error
+        /// page, see the [encodings](AssetConfig::File::encodings) configuration.
         encodings: Vec<(AssetEncoding, String)>,
     },
 
@@ -374,6 +560,27 @@ pub enum AssetConfig {
         /// The kind redirect to configure.
         kind: AssetRedirectKind,
 
+        /// Whether the query string of the original request should be appended
+        /// to the `Location` header, e.g. to retain tracking parameters.
+        ///
+        /// When `false`, the HTTP Gateway receives the `to` URL as the
+        /// certified `Location` header. When `true`, it receives the `to` URL
+        /// with the canonicalized query string of the request appended, e.g.
+        /// `/landing?utm_source=mail` for a request to `/promo?utm_source=mail`.
+        /// The query is joined with `&` if `to` already has a query string, is
+        /// inserted before a fragment of `to`, and is omitted if the
+        /// canonicalized query string is empty.
+        ///
+        /// **Security warning**: the value of the `Location` header then depends
+        /// on the request, so it is excluded from certification. A malicious
+        /// replica or boundary node can replace it with any URL, including one
+        /// on another origin, and the response still passes verification. Only
+        /// the status code, body and all other headers of the redirect remain
+        /// certified. HTTP Gateways that require certified redirect targets
+        /// reject these responses. This should only be enabled for redirects
+        /// whose target is not security sensitive.
+        append_query: bool,
+
         /// Additional headers to be inserted into the response. Each additional
         /// header added will be included in certification and served by the
         /// [AssetRouter](crate::AssetRouter) for matching [Assets](Asset).
@@ -382,16 +589,141 @@ pub enum AssetConfig {
         /// response with the value of the `to` field.
         headers: Vec<(String, String)>,
     },
+
+    /// Groups related configurations under a namespace, e.g. all assets of a single
+    /// frontend deployment. The configurations are applied in the same way as if they
+    /// were provided individually, in the same position.
+    ///
+    /// Assets matching one of these configurations, along with their alternative
+    /// encodings and any redirects in the namespace, can be listed and deleted as a
+    /// unit, see [get_namespace_asset_paths](crate::AssetRouter::get_namespace_asset_paths)
+    /// and [delete_namespace](crate::AssetRouter::delete_namespace). Configurations in a
+    /// nested namespace belong to the innermost namespace.
+    Namespace {
+        /// The name of the namespace.
+        name: String,
+
+        /// The configurations in the namespace.
+        configs: Vec<AssetConfig>,
+    },
+}
+
+const VARY_HEADER_NAME: &str = "vary";
+const NO_CACHE_CACHE_CONTROL: &str = "public, no-cache, no-store";
+const IMMUTABLE_CACHE_CONTROL: &str = "public, max-age=31536000, immutable";
+
+impl AssetConfig {
+    /// Returns the configurations that are commonly used for single-page applications.
+    ///
+    /// The following configurations are returned, in order:
+    /// - The file at `index_path` is served as an HTML file on its own path and
+    ///   on `/`, and is used as the fallback for the `/` scope. It is not cached.
+    /// - All other HTML files are not cached.
+    /// - JavaScript and CSS files are cached forever, this assumes that the bundler
+    ///   includes a content hash in their file names, so changed files are served
+    ///   on a new path.
+    ///
+    /// All configurations serve [Brotli](AssetEncoding::Brotli) and
+    /// [Gzip](AssetEncoding::Gzip) encoded versions of the assets, if they are provided.
+    ///
+    /// Since the first configuration matching an asset is used, additional
+    /// configurations that should take precedence must be placed before the
+    /// returned configurations.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_asset_certification::AssetConfig;
+    ///
+    /// let mut asset_configs = vec![AssetConfig::Pattern {
+    ///     pattern: "**/*.svg".to_string(),
+    ///     exclude: vec![],
+    ///     exclude_dotfiles: false,
+    ///     content_type: Some("image/svg+xml".to_string()),
+    ///     headers: vec![],
+    ///     encodings: vec![],
+    ///     image_variants: vec![],
+    /// }];
+    /// asset_configs.extend(AssetConfig::spa("index.html"));
+    /// ```
+    pub fn spa(index_path: impl Into<String>) -> Vec<AssetConfig> {
+        let encodings = vec![
+            AssetEncoding::Brotli.default_config(),
+            AssetEncoding::Gzip.default_config(),
+        ];
+        let cache_control = |value: &str| vec![("cache-control".to_string(), value.to_string())];
+
+        vec![
+            AssetConfig::File {
+                path: index_path.into(),
+                content_type: Some("text/html".to_string()),
+                headers: cache_control(NO_CACHE_CACHE_CONTROL),
+                fallback_for: vec![AssetFallbackConfig {
+                    scope: "/".to_string(),
+                    status_code: Some(StatusCode::OK),
+                    priority: 0,
+                }],
+                aliased_by: vec!["/".to_string()],
+                encodings: encodings.clone(),
+                image_variants: vec![],
+            },
+            AssetConfig::Pattern {
+                pattern: "**/*.html".to_string(),
+                exclude: vec![],
+                exclude_dotfiles: false,
+                content_type: Some("text/html".to_string()),
+                headers: cache_control(NO_CACHE_CACHE_CONTROL),
+                encodings: encodings.clone(),
+                image_variants: vec![],
+            },
+            AssetConfig::Pattern {
+                pattern: "**/*.js".to_string(),
+                exclude: vec![],
+                exclude_dotfiles: false,
+                content_type: Some("text/javascript".to_string()),
+                headers: cache_control(IMMUTABLE_CACHE_CONTROL),
+                encodings: encodings.clone(),
+                image_variants: vec![],
+            },
+            AssetConfig::Pattern {
+                pattern: "**/*.css".to_string(),
+                exclude: vec![],
+                exclude_dotfiles: false,
+                content_type: Some("text/css".to_string()),
+                headers: cache_control(IMMUTABLE_CACHE_CONTROL),
+                encodings,
+                image_variants: vec![],
+            },
+        ]
+    }
 }
 
 /// Configuration for an asset to be used as a fallback for a specific scope.
 ///
 /// See the [fallback_for](AssetConfig::File::fallback_for) configuration
 /// of the [AssetConfig] interface for more information.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct AssetFallbackConfig {
     /// The scope to use this asset as a fallback for.
     ///
+    /// The scope is either a path prefix, such as `/app`, or a glob pattern, such as
+    /// `/app/*/settings`, to use the asset as a fallback for multiple scopes at once.
+    /// Glob patterns are matched against the same scopes as path prefixes, so
+    /// `/app/*/settings` is a fallback for requests such as `/app/123/settings/profile`.
+    ///
+    /// A fallback with a glob pattern is certified for the path prefix up to the first
+    /// segment that contains a pattern, `/app/` in the example above. Response verification
+    /// accepts the fallback for any request within that prefix, the router only serves it for
+    /// requests matching the glob pattern. Since the prefix is certified, fallbacks with a less
+    /// specific scope, such as `/`, can no longer be served for requests within the prefix.
+    ///
+    /// Wildcards such as `*` and `?` do not match `/`, so `/app/*/settings` is not a fallback
+    /// for `/app/123/456/settings`; use `**` to match any number of segments.
+    ///
+    /// Glob patterns that share a prefix are all certified for the same wildcard path of that
+    /// prefix, e.g. `/app/*/settings` and `/app/*/profile`. Response verification can not
+    /// tell these fallbacks apart, so it accepts any of them for any request within the prefix.
+    ///
     /// See the [fallback_for](AssetConfig::File::fallback_for)
     /// configuration of the [AssetConfig] interface for more information.
     pub scope: String,
@@ -399,12 +731,84 @@ pub struct AssetFallbackConfig {
     /// The HTTP status code to return when serving the asset.
     /// If this value is not provided, the default status code will be 200.
     pub status_code: Option<StatusCode>,
+
+    /// The priority of this fallback when multiple assets are configured as a
+    /// fallback for the same scope. The asset with the highest priority is used,
+    /// the other assets are not certified for this scope until the asset with the
+    /// highest priority is deleted. Fallbacks for different scopes are always resolved
+    /// by how specific their scopes are.
+    ///
+    /// See the [fallback_for](AssetConfig::File::fallback_for)
+    /// configuration of the [AssetConfig] interface for the full resolution order.
+    pub priority: i32,
+}
+
+impl AssetFallbackConfig {
+    /// Creates a fallback configuration for the provided scope, served with a `200 OK`
+    /// status code and the default priority of `0`.
+    ///
+    /// ```
+    /// use ic_asset_certification::AssetFallbackConfig;
+    /// use ic_http_certification::StatusCode;
+    ///
+    /// let fallback_config = AssetFallbackConfig::new("/app")
+    ///     .with_status_code(StatusCode::NOT_FOUND)
+    ///     .with_priority(1);
+    ///
+    /// assert_eq!(fallback_config.scope, "/app");
+    /// assert_eq!(fallback_config.status_code, Some(StatusCode::NOT_FOUND));
+    /// assert_eq!(fallback_config.priority, 1);
+    /// ```
+    pub fn new(scope: impl Into<String>) -> Self {
+        Self {
+            scope: scope.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Sets the [status code](AssetFallbackConfig::status_code) to return when serving the asset.
+    pub fn with_status_code(mut self, status_code: StatusCode) -> Self {
+        self.status_code = Some(status_code);
+        self
+    }
+
+    /// Sets the [priority](AssetFallbackConfig::priority) of the fallback.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+/// Whether a fallback scope is a glob pattern rather than a path prefix, see
+/// [scope](AssetFallbackConfig::scope).
+pub(crate) fn is_glob_scope(scope: &str) -> bool {
+    scope.contains(['*', '?', '[', '{'])
+}
+
+/// The path prefix of a glob fallback scope up to the first segment that contains a
+/// glob pattern, including the trailing slash, e.g. `/app/` for `/app/*/settings`.
+/// The fallback is certified for this prefix.
+pub(crate) fn glob_scope_prefix(scope: &str) -> &str {
+    let glob_start = scope.find(['*', '?', '[', '{']).unwrap_or(scope.len());
+    let prefix_end = scope[..glob_start].rfind('/').map_or(0, |i| i + 1);
+
+    &scope[..prefix_end]
+}
+
+/// Compiles the matcher of a glob fallback scope, see [scope](AssetFallbackConfig::scope).
+pub(crate) fn glob_scope_matcher(scope: &str) -> Result<GlobMatcher, globset::Error> {
+    Ok(GlobBuilder::new(scope)
+        .literal_separator(true)
+        .build()?
+        .compile_matcher())
 }
 
 /// The type of redirect to use. Redirects can be either
-/// [permanent](AssetRedirectKind::Permanent) or
-/// [temporary](AssetRedirectKind::Temporary).
-#[derive(Debug, Clone)]
+/// [permanent](AssetRedirectKind::Permanent),
+/// [temporary](AssetRedirectKind::Temporary),
+/// [found](AssetRedirectKind::Found) or
+/// [permanent while preserving the request method](AssetRedirectKind::PermanentRedirect).
+#[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
 pub enum AssetRedirectKind {
     /// A permanent redirect (301).
     ///
@@ -429,10 +833,54 @@ pub enum AssetRedirectKind {
     /// [MDN Web Docs](https://developer.mozilla.org/en-US/docs/Web/HTTP/Status/307)
     /// for more information.
     Temporary,
+
+    /// A found redirect (302).
+    ///
+    /// Similar to a [temporary](AssetRedirectKind::Temporary) redirect, but
+    /// browsers may change the request method to `GET` when following it. This
+    /// is the most widely supported temporary redirect.
+    ///
+    /// See the
+    /// [MDN Web Docs](https://developer.mozilla.org/en-US/docs/Web/HTTP/Status/302)
+    /// for more information.
+    Found,
+
+    /// A permanent redirect that preserves the request method (308).
+    ///
+    /// Similar to a [permanent](AssetRedirectKind::Permanent) redirect, but
+    /// browsers must not change the request method or body when following it.
+    ///
+    /// See the
+    /// [MDN Web Docs](https://developer.mozilla.org/en-US/docs/Web/HTTP/Status/308)
+    /// for more information.
+    PermanentRedirect,
+}
+
+impl AssetRedirectKind {
+    /// Returns the status code of the redirect.
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            AssetRedirectKind::Permanent => StatusCode::MOVED_PERMANENTLY,
+            AssetRedirectKind::Temporary => StatusCode::TEMPORARY_REDIRECT,
+            AssetRedirectKind::Found => StatusCode::FOUND,
+            AssetRedirectKind::PermanentRedirect => StatusCode::PERMANENT_REDIRECT,
+        }
+    }
+
+    /// Returns the kind of redirect with the provided status code, if any.
+    pub(crate) fn from_status_code(status_code: StatusCode) -> Option<Self> {
+        match status_code {
+            StatusCode::MOVED_PERMANENTLY => Some(AssetRedirectKind::Permanent),
+            StatusCode::TEMPORARY_REDIRECT => Some(AssetRedirectKind::Temporary),
+            StatusCode::FOUND => Some(AssetRedirectKind::Found),
+            StatusCode::PERMANENT_REDIRECT => Some(AssetRedirectKind::PermanentRedirect),
+            _ => None,
+        }
+    }
 }
 
 /// The encoding of an asset.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, CandidType, Deserialize)]
 pub enum AssetEncoding {
     /// The asset is not encoded.
     Identity,
@@ -517,6 +965,79 @@ impl AssetEncoding {
     }
 }
 
+/// An alternative image format of an asset, see the
+/// [image_variants](AssetConfig::File::image_variants) configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, CandidType, Deserialize)]
+pub enum AssetImageFormat {
+    /// The asset is an AVIF image.
+    Avif,
+
+    /// The asset is a WebP image.
+    Webp,
+}
+
+impl AssetImageFormat {
+    /// Returns the image format and the default file extension for the image format.
+    ///
+    /// The default file extensions are:
+    /// - [Avif](AssetImageFormat::Avif): `avif`
+    /// - [Webp](AssetImageFormat::Webp): `webp`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_asset_certification::AssetImageFormat;
+    ///
+    /// let (image_format, extension) = AssetImageFormat::Avif.default_config();
+    /// assert_eq!(image_format, AssetImageFormat::Avif);
+    /// assert_eq!(extension, ".avif");
+    ///
+    /// let (image_format, extension) = AssetImageFormat::Webp.default_config();
+    /// assert_eq!(image_format, AssetImageFormat::Webp);
+    /// assert_eq!(extension, ".webp");
+    /// ```
+    pub fn default_config(self) -> (AssetImageFormat, String) {
+        let file_extension = match self {
+            AssetImageFormat::Avif => ".avif".to_string(),
+            AssetImageFormat::Webp => ".webp".to_string(),
+        };
+
+        (self, file_extension)
+    }
+
+    /// Returns an image format with a custom file extension. This is useful
+    /// when the default file extension assigned by [default_config](AssetImageFormat::default_config)
+    /// is not desired.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_asset_certification::AssetImageFormat;
+    ///
+    /// let (image_format, extension) = AssetImageFormat::Webp.custom_config(".png.webp".to_string());
+    ///
+    /// assert_eq!(image_format, AssetImageFormat::Webp);
+    /// assert_eq!(extension, ".png.webp");
+    /// ```
+    pub fn custom_config(self, extension: String) -> (AssetImageFormat, String) {
+        (self, extension)
+    }
+
+    /// Returns the content type of the image format, e.g. `image/avif`.
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            AssetImageFormat::Avif => "image/avif",
+            AssetImageFormat::Webp => "image/webp",
+        }
+    }
+}
+
+impl Display for AssetImageFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.content_type())
+    }
+}
+
 impl Display for AssetEncoding {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let str = match self {
@@ -540,26 +1061,58 @@ pub(crate) enum NormalizedAssetConfig {
         fallback_for: Vec<AssetFallbackConfig>,
         aliased_by: Vec<String>,
         encodings: Vec<(AssetEncoding, String)>,
+        image_variants: Vec<(AssetImageFormat, String)>,
+        namespace: Option<String>,
     },
     Pattern {
         pattern: GlobMatcher,
+        exclude: GlobSet,
+        exclude_dotfiles: bool,
+        content_type: Option<String>,
+        headers: Vec<(String, String)>,
+        encodings: Vec<(AssetEncoding, String)>,
+        image_variants: Vec<(AssetImageFormat, String)>,
+        namespace: Option<String>,
+    },
+    ErrorPage {
+        path: String,
+        status_code: StatusCode,
+        scopes: Vec<String>,
         content_type: Option<String>,
         headers: Vec<(String, String)>,
         encodings: Vec<(AssetEncoding, String)>,
+        namespace: Option<String>,
     },
     Redirect {
         from: String,
         to: String,
         kind: AssetRedirectKind,
+        append_query: bool,
         headers: Vec<(String, String)>,
+        namespace: Option<String>,
     },
 }
 
-impl TryFrom<AssetConfig> for NormalizedAssetConfig {
-    type Error = AssetCertificationError;
+impl NormalizedAssetConfig {
+    /// Normalizes the provided configurations, the configurations of a
+    /// [Namespace](AssetConfig::Namespace) are flattened in place.
+    pub(crate) fn normalize_all(
+        configs: impl IntoIterator<Item = AssetConfig>,
+    ) -> AssetCertificationResult<Vec<Self>> {
+        let mut normalized = vec![];
+        for config in configs {
+            Self::normalize(config, None, &mut normalized)?;
+        }
 
-    fn try_from(config: AssetConfig) -> Result<Self, Self::Error> {
-        match config {
+        Ok(normalized)
+    }
+
+    fn normalize(
+        config: AssetConfig,
+        namespace: Option<String>,
+        normalized: &mut Vec<Self>,
+    ) -> AssetCertificationResult {
+        let config = match config {
             AssetConfig::File {
                 path,
                 content_type,
@@ -567,54 +1120,480 @@ impl TryFrom<AssetConfig> for NormalizedAssetConfig {
                 fallback_for,
                 aliased_by,
                 encodings,
-            } => Ok(NormalizedAssetConfig::File {
-                path,
-                content_type,
-                headers,
-                fallback_for,
-                aliased_by,
-                encodings,
-            }),
+                image_variants,
+            } => {
+                for fallback in fallback_for.iter() {
+                    if is_glob_scope(&fallback.scope) {
+                        glob_scope_matcher(&fallback.scope)?;
+                    }
+                }
+
+                NormalizedAssetConfig::File {
+                    path,
+                    content_type,
+                    headers,
+                    fallback_for,
+                    aliased_by,
+                    encodings,
+                    image_variants,
+                    namespace,
+                }
+            }
             AssetConfig::Pattern {
                 pattern,
+                exclude,
+                exclude_dotfiles,
                 content_type,
                 headers,
                 encodings,
-            } => Ok(NormalizedAssetConfig::Pattern {
+                image_variants,
+            } => NormalizedAssetConfig::Pattern {
                 pattern: Glob::new(&pattern)?.compile_matcher(),
+                exclude: exclude
+                    .iter()
+                    .try_fold(GlobSetBuilder::new(), |mut builder, exclude| {
+                        builder.add(Glob::new(exclude)?);
+                        Ok::<_, globset::Error>(builder)
+                    })?
+                    .build()?,
+                exclude_dotfiles,
+                content_type,
+                headers,
+                encodings,
+                image_variants,
+                namespace,
+            },
+            AssetConfig::ErrorPage {
+                path,
+                status_code,
+                scopes,
+                content_type,
+                headers,
+                encodings,
+            } => NormalizedAssetConfig::ErrorPage {
+                path,
+                status_code,
+                scopes,
                 content_type,
                 headers,
                 encodings,
-            }),
+                namespace,
+            },
             AssetConfig::Redirect {
                 from,
                 to,
                 kind,
+                append_query,
                 headers,
-            } => Ok(NormalizedAssetConfig::Redirect {
+            } => NormalizedAssetConfig::Redirect {
                 from,
                 to,
                 kind,
+                append_query,
                 headers,
-            }),
+                namespace,
+            },
+            AssetConfig::Namespace { name, configs } => {
+                for config in configs {
+                    Self::normalize(config, Some(name.clone()), normalized)?;
+                }
+
+                return Ok(());
+            }
+        };
+        normalized.push(config);
+
+        Ok(())
+    }
+
+    /// Returns the name of the [Namespace](AssetConfig::Namespace) that this config belongs to.
+    pub(crate) fn namespace(&self) -> Option<&str> {
+        match self {
+            Self::File { namespace, .. }
+            | Self::Pattern { namespace, .. }
+            | Self::ErrorPage { namespace, .. }
+            | Self::Redirect { namespace, .. } => namespace.as_deref(),
         }
     }
-}
 
-impl NormalizedAssetConfig {
     pub(crate) fn matches_asset(&self, asset: &Asset) -> bool {
         match self {
-            Self::File { path, .. } => path == asset.path.as_ref(),
+            Self::File { path, .. } | Self::ErrorPage { path, .. } => path == asset.path.as_ref(),
             Self::Pattern { pattern, .. } => pattern.is_match(asset.path.as_ref()),
             Self::Redirect { .. } => false,
         }
     }
+
+    /// The paths of the alternative image formats of the provided asset, that is the path with
+    /// its file extension replaced by the configured one, e.g. `logo.avif` for `logo.png`.
+    /// Assets that are an alternative image format themselves don't have any alternatives.
+    pub(crate) fn image_variant_paths(&self, asset_path: &str) -> Vec<(AssetImageFormat, String)> {
+        let image_variants = self.image_variants();
+        if image_variants
+            .iter()
+            .any(|(_, extension)| asset_path.ends_with(extension.as_str()))
+        {
+            return vec![];
+        }
+
+        let file_name_start = asset_path.rfind('/').map_or(0, |i| i + 1);
+        let stem_end = asset_path[file_name_start..]
+            .rfind('.')
+            .filter(|i| *i > 0)
+            .map_or(asset_path.len(), |i| file_name_start + i);
+
+        image_variants
+            .iter()
+            .map(|(image_format, extension)| {
+                (
+                    *image_format,
+                    format!("{}{}", &asset_path[..stem_end], extension),
+                )
+            })
+            .collect()
+    }
+
+    /// Whether the asset is excluded from certification by this config, see
+    /// [exclude](AssetConfig::Pattern::exclude) and
+    /// [exclude_dotfiles](AssetConfig::Pattern::exclude_dotfiles).
+    pub(crate) fn excludes_asset(&self, asset: &Asset) -> bool {
+        match self {
+            Self::Pattern {
+                exclude,
+                exclude_dotfiles,
+                ..
+            } => {
+                exclude.is_match(asset.path.as_ref())
+                    || (*exclude_dotfiles
+                        && asset
+                            .path
+                            .split('/')
+                            .any(|segment| segment.starts_with('.')))
+            }
+            _ => false,
+        }
+    }
+
+    /// The path or glob pattern that assets are matched against, or `None` for redirects.
+    pub(crate) fn path_or_pattern(&self) -> Option<&str> {
+        match self {
+            Self::File { path, .. } | Self::ErrorPage { path, .. } => Some(path),
+            Self::Pattern { pattern, .. } => Some(pattern.glob().glob()),
+            Self::Redirect { .. } => None,
+        }
+    }
+
+    /// The value of the `Vary` response header for assets matching this config, if their
+    /// responses are negotiated by the `Accept-Encoding` or `Accept` request headers and no
+    /// `Vary` header is already configured.
+    pub(crate) fn vary_header(&self) -> Option<&'static str> {
+        let (headers, varies_by_encoding) = match self {
+            Self::File {
+                headers, encodings, ..
+            }
+            | Self::Pattern {
+                headers, encodings, ..
+            }
+            | Self::ErrorPage {
+                headers, encodings, ..
+            } => (headers, !encodings.is_empty()),
+            Self::Redirect { .. } => return None,
+        };
+        if headers
+            .iter()
+            .any(|(name, _)| name.eq_ignore_ascii_case(VARY_HEADER_NAME))
+        {
+            return None;
+        }
+
+        match (varies_by_encoding, !self.image_variants().is_empty()) {
+            (true, true) => Some("accept, accept-encoding"),
+            (true, false) => Some("accept-encoding"),
+            (false, true) => Some("accept"),
+            (false, false) => None,
+        }
+    }
+
+    /// The alternative image formats of assets matching this config, see
+    /// [image_variants](AssetConfig::File::image_variants).
+    pub(crate) fn image_variants(&self) -> &[(AssetImageFormat, String)] {
+        match self {
+            Self::File { image_variants, .. } | Self::Pattern { image_variants, .. } => {
+                image_variants
+            }
+            Self::ErrorPage { .. } | Self::Redirect { .. } => &[],
+        }
+    }
+
+    /// The alternative encodings of assets matching this config along with the postfix of
+    /// their paths, see [encodings](AssetConfig::File::encodings).
+    pub(crate) fn encodings(&self) -> &[(AssetEncoding, String)] {
+        match self {
+            Self::File { encodings, .. }
+            | Self::Pattern { encodings, .. }
+            | Self::ErrorPage { encodings, .. } => encodings,
+            Self::Redirect { .. } => &[],
+        }
+    }
+
+    /// The SHA-256 hash of the settings that this config applies to the responses of matching
+    /// assets, such as their content type, headers, fallbacks, aliases, encodings and image
+    /// variants. [sync_assets](crate::AssetRouter::sync_assets) compares it to re-certify assets
+    /// whose configuration changed. The path or pattern that assets are matched against is not
+    /// part of the hash, since it does not change the responses of a matching asset.
+    pub(crate) fn fingerprint(&self) -> Hash {
+        let mut hasher = Sha256::new();
+        let mut update = |value: &[u8]| {
+            hasher.update((value.len() as u64).to_le_bytes());
+            hasher.update(value);
+        };
+
+        let (content_type, headers, encodings, namespace) = match self {
+            Self::File {
+                content_type,
+                headers,
+                encodings,
+                namespace,
+                ..
+            }
+            | Self::Pattern {
+                content_type,
+                headers,
+                encodings,
+                namespace,
+                ..
+            }
+            | Self::ErrorPage {
+                content_type,
+                headers,
+                encodings,
+                namespace,
+                ..
+            } => (
+                content_type.as_deref(),
+                headers,
+                encodings.as_slice(),
+                namespace,
+            ),
+            Self::Redirect {
+                headers, namespace, ..
+            } => (None, headers, [].as_slice(), namespace),
+        };
+
+        match self {
+            Self::File {
+                fallback_for,
+                aliased_by,
+                ..
+            } => {
+                update(b"file");
+                update(&(fallback_for.len() as u64).to_le_bytes());
+                for fallback in fallback_for {
+                    update(fallback.scope.as_bytes());
+                    update(&fallback.status_code.map_or(0, |s| s.as_u16()).to_le_bytes());
+                    update(&fallback.priority.to_le_bytes());
+                }
+                update(&(aliased_by.len() as u64).to_le_bytes());
+                for alias in aliased_by {
+                    update(alias.as_bytes());
+                }
+            }
+            Self::Pattern { .. } => update(b"pattern"),
+            Self::ErrorPage {
+                status_code,
+                scopes,
+                ..
+            } => {
+                update(b"error_page");
+                update(&status_code.as_u16().to_le_bytes());
+                update(&(scopes.len() as u64).to_le_bytes());
+                for scope in scopes {
+                    update(scope.as_bytes());
+                }
+            }
+            Self::Redirect {
+                from,
+                to,
+                kind,
+                append_query,
+                ..
+            } => {
+                update(b"redirect");
+                update(from.as_bytes());
+                update(to.as_bytes());
+                update(format!("{kind:?}").as_bytes());
+                update(&[u8::from(*append_query)]);
+            }
+        }
+
+        for value in [content_type, namespace.as_deref()] {
+            match value {
+                Some(value) => {
+                    update(&[1]);
+                    update(value.as_bytes());
+                }
+                None => update(&[0]),
+            }
+        }
+        update(&(headers.len() as u64).to_le_bytes());
+        for (name, value) in headers {
+            update(name.as_bytes());
+            update(value.as_bytes());
+        }
+        update(&(encodings.len() as u64).to_le_bytes());
+        for (encoding, postfix) in encodings {
+            update(encoding.to_string().as_bytes());
+            update(postfix.as_bytes());
+        }
+        let image_variants = self.image_variants();
+        update(&(image_variants.len() as u64).to_le_bytes());
+        for (image_format, extension) in image_variants {
+            update(image_format.to_string().as_bytes());
+            update(extension.as_bytes());
+        }
+
+        hasher.finalize().into()
+    }
+
+    /// Whether assets matching this config can be certified on demand, see
+    /// [with_lazy_certification](crate::AssetRouter::with_lazy_certification).
+    /// Fallbacks and error pages may be served for any request in their scope,
+    /// so they are always certified upfront.
+    pub(crate) fn can_certify_lazily(&self) -> bool {
+        match self {
+            Self::File { fallback_for, .. } => fallback_for.is_empty(),
+            Self::Pattern { .. } => true,
+            Self::ErrorPage { .. } | Self::Redirect { .. } => false,
+        }
+    }
+}
+
+/// Normalized configurations indexed for looking up the config of an asset without
+/// matching it against every configuration.
+///
+/// [File](NormalizedAssetConfig::File) and [ErrorPage](NormalizedAssetConfig::ErrorPage)
+/// configs are indexed by their path and [Pattern](NormalizedAssetConfig::Pattern) configs
+/// by the file extension that their glob ends with, if any. Lookups return the first
+/// matching config in declaration order, the same as a linear scan would.
+#[derive(Debug, Clone)]
+pub(crate) struct NormalizedAssetConfigs {
+    configs: Vec<NormalizedAssetConfig>,
+    path_index: HashMap<String, usize>,
+    extension_index: HashMap<String, Vec<usize>>,
+    unindexed: Vec<usize>,
+}
+
+impl NormalizedAssetConfigs {
+    /// Normalizes and indexes the provided configurations, see
+    /// [NormalizedAssetConfig::normalize_all].
+    pub(crate) fn normalize_all(
+        configs: impl IntoIterator<Item = AssetConfig>,
+    ) -> AssetCertificationResult<Self> {
+        Ok(Self::new(NormalizedAssetConfig::normalize_all(configs)?))
+    }
+
+    fn new(configs: Vec<NormalizedAssetConfig>) -> Self {
+        let mut path_index = HashMap::new();
+        let mut extension_index: HashMap<String, Vec<usize>> = HashMap::new();
+        let mut unindexed = vec![];
+
+        for (i, config) in configs.iter().enumerate() {
+            match config {
+                NormalizedAssetConfig::File { path, .. }
+                | NormalizedAssetConfig::ErrorPage { path, .. } => {
+                    path_index.entry(path.clone()).or_insert(i);
+                }
+                NormalizedAssetConfig::Pattern { pattern, .. } => {
+                    match glob_extension(pattern.glob().glob()) {
+                        Some(extension) => extension_index
+                            .entry(extension.to_string())
+                            .or_default()
+                            .push(i),
+                        None => unindexed.push(i),
+                    }
+                }
+                NormalizedAssetConfig::Redirect { .. } => {}
+            }
+        }
+
+        Self {
+            configs,
+            path_index,
+            extension_index,
+            unindexed,
+        }
+    }
+
+    /// Returns the first config matching the provided asset.
+    pub(crate) fn find(&self, asset: &Asset) -> Option<&NormalizedAssetConfig> {
+        self.position(asset).map(|i| &self.configs[i])
+    }
+
+    /// Returns the index of the first config matching the provided asset.
+    pub(crate) fn position(&self, asset: &Asset) -> Option<usize> {
+        let path: &str = asset.path.as_ref();
+        let first_match = |candidates: &[usize]| {
+            candidates
+                .iter()
+                .copied()
+                .find(|i| self.configs[*i].matches_asset(asset))
+        };
+
+        let by_path = self.path_index.get(path).copied();
+        let by_extension = path
+            .rsplit_once('.')
+            .and_then(|(_, extension)| self.extension_index.get(extension))
+            .and_then(|candidates| first_match(candidates));
+        let unindexed = first_match(&self.unindexed);
+
+        [by_path, by_extension, unindexed]
+            .into_iter()
+            .flatten()
+            .min()
+    }
+}
+
+impl Deref for NormalizedAssetConfigs {
+    type Target = [NormalizedAssetConfig];
+
+    fn deref(&self) -> &Self::Target {
+        &self.configs
+    }
+}
+
+impl IntoIterator for NormalizedAssetConfigs {
+    type Item = NormalizedAssetConfig;
+    type IntoIter = std::vec::IntoIter<NormalizedAssetConfig>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.configs.into_iter()
+    }
+}
+
+/// The file extension that every path matched by the provided glob ends with,
+/// e.g. `js` for `**/*.js`, or `None` if the glob doesn't end with a literal extension,
+/// e.g. `*.{js,css}`.
+fn glob_extension(glob: &str) -> Option<&str> {
+    if glob.contains('\\') {
+        return None;
+    }
+
+    let literal_suffix = glob
+        .rfind(['*', '?', '[', ']', '{', '}'])
+        .map_or(glob, |i| &glob[i + 1..]);
+    let (_, extension) = literal_suffix.rsplit_once('.')?;
+
+    if extension.is_empty() || extension.contains('/') {
+        return None;
+    }
+
+    Some(extension)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::Asset;
+    use crate::{Asset, AssetCertificationError};
     use rstest::*;
 
     #[rstest]
@@ -628,16 +1607,17 @@ mod tests {
         #[case] expected: bool,
     ) {
         let asset = Asset::new(asset_path, vec![]);
-        let config: NormalizedAssetConfig = AssetConfig::File {
+        let config = NormalizedAssetConfig::normalize_all([AssetConfig::File {
             path: config_path.to_string(),
             content_type: None,
             headers: vec![],
             fallback_for: vec![],
             aliased_by: vec![],
             encodings: vec![],
-        }
-        .try_into()
-        .unwrap();
+            image_variants: vec![],
+        }])
+        .unwrap()
+        .remove(0);
 
         assert_eq!(config.matches_asset(&asset), expected);
     }
@@ -728,14 +1708,17 @@ mod tests {
         #[case] expected: bool,
     ) {
         let asset = Asset::new(asset_path, vec![]);
-        let config: NormalizedAssetConfig = AssetConfig::Pattern {
+        let config = NormalizedAssetConfig::normalize_all([AssetConfig::Pattern {
             pattern: config_pattern.to_string(),
+            exclude: vec![],
+            exclude_dotfiles: false,
             content_type: None,
             headers: vec![],
             encodings: vec![],
-        }
-        .try_into()
-        .unwrap();
+            image_variants: vec![],
+        }])
+        .unwrap()
+        .remove(0);
 
         assert_eq!(config.matches_asset(&asset), expected);
     }
@@ -747,21 +1730,296 @@ mod tests {
     #[case("index.css")]
     fn does_not_match_asset_redirect(#[case] asset_path: &str) {
         let asset = Asset::new(asset_path, vec![]);
-        let config: NormalizedAssetConfig = AssetConfig::Redirect {
+        let config = NormalizedAssetConfig::normalize_all([AssetConfig::Redirect {
             from: asset_path.to_string(),
             to: asset_path.to_string(),
             kind: AssetRedirectKind::Permanent,
+            append_query: false,
             headers: vec![(
                 "content-type".to_string(),
                 "text/plain; charset=utf-8".to_string(),
             )],
-        }
-        .try_into()
-        .unwrap();
+        }])
+        .unwrap()
+        .remove(0);
 
         assert!(!config.matches_asset(&asset));
     }
 
+    #[rstest]
+    #[case("index.html", Some(0))]
+    #[case("about.html", Some(1))]
+    #[case("nested/page.html", Some(1))]
+    #[case("assets/index-4f2d3a1b.js", Some(2))]
+    #[case("assets/index-4f2d3a1b.css", Some(3))]
+    #[case("favicon.ico", None)]
+    fn spa_matches_asset(#[case] asset_path: &str, #[case] expected: Option<usize>) {
+        let asset = Asset::new(asset_path, vec![]);
+        let configs = NormalizedAssetConfig::normalize_all(AssetConfig::spa("index.html")).unwrap();
+
+        assert_eq!(
+            configs
+                .iter()
+                .position(|config| config.matches_asset(&asset)),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case("index.html", Some(0))]
+    #[case("about.html", Some(1))]
+    #[case("assets/app.js", Some(2))]
+    #[case("assets/app.css", Some(2))]
+    #[case("app.min.js", Some(3))]
+    #[case("lib/app.min.js", Some(3))]
+    #[case("app.js", Some(4))]
+    #[case("archive.tar.gz", Some(5))]
+    #[case("README", None)]
+    fn indexed_configs_match_first_config(
+        #[case] asset_path: &str,
+        #[case] expected: Option<usize>,
+    ) {
+        let pattern = |pattern: &str| AssetConfig::Pattern {
+            pattern: pattern.to_string(),
+            exclude: vec![],
+            exclude_dotfiles: false,
+            content_type: None,
+            headers: vec![],
+            encodings: vec![],
+            image_variants: vec![],
+        };
+        let asset = Asset::new(asset_path, vec![]);
+        let configs = NormalizedAssetConfigs::normalize_all([
+            AssetConfig::File {
+                path: "index.html".to_string(),
+                content_type: None,
+                headers: vec![],
+                fallback_for: vec![],
+                aliased_by: vec![],
+                encodings: vec![],
+                image_variants: vec![],
+            },
+            pattern("*.html"),
+            pattern("assets/*.{js,css}"),
+            pattern("**/*.min.js"),
+            pattern("*.js"),
+            pattern("*.tar.gz"),
+        ])
+        .unwrap();
+
+        assert_eq!(configs.position(&asset), expected);
+        assert_eq!(
+            configs
+                .iter()
+                .position(|config| config.matches_asset(&asset)),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case("*.js", Some("js"))]
+    #[case("**/*.min.js", Some("js"))]
+    #[case("assets/app.js", Some("js"))]
+    #[case("*.tar.gz", Some("gz"))]
+    #[case("*.{js,css}", None)]
+    #[case("*.j?", None)]
+    #[case("assets/*", None)]
+    #[case("*.d/*", None)]
+    #[case("*.d/index", None)]
+    #[case("\\*.js", None)]
+    fn glob_extension_is_literal_suffix(#[case] glob: &str, #[case] expected: Option<&str>) {
+        assert_eq!(glob_extension(glob), expected);
+    }
+
+    #[rstest]
+    #[case("/app/*/settings", "/app/")]
+    #[case("/app/**", "/app/")]
+    #[case("/*/settings", "/")]
+    #[case("/app/v[12]/settings", "/app/")]
+    #[case("/app/{a,b}", "/app/")]
+    #[case("/app/settings-*", "/app/")]
+    fn glob_scope_prefix_strips_pattern(#[case] scope: &str, #[case] expected: &str) {
+        assert!(is_glob_scope(scope));
+        assert_eq!(glob_scope_prefix(scope), expected);
+    }
+
+    #[rstest]
+    fn pattern_excludes_asset() {
+        let configs = NormalizedAssetConfig::normalize_all([AssetConfig::Pattern {
+            pattern: "**/*".to_string(),
+            exclude: vec!["**/.*".to_string(), "**/*.map".to_string()],
+            exclude_dotfiles: false,
+            content_type: None,
+            headers: vec![],
+            encodings: vec![],
+            image_variants: vec![],
+        }])
+        .unwrap();
+        let config = &configs[0];
+
+        assert!(config.excludes_asset(&Asset::new(".env", [].as_slice())));
+        assert!(config.excludes_asset(&Asset::new("assets/.DS_Store", [].as_slice())));
+        assert!(config.excludes_asset(&Asset::new("assets/app.js.map", [].as_slice())));
+        assert!(!config.excludes_asset(&Asset::new("assets/app.js", [].as_slice())));
+        assert!(!config.excludes_asset(&Asset::new(".well-known/ic-domains", [].as_slice())));
+    }
+
+    #[rstest]
+    #[case(".env", true)]
+    #[case("assets/.DS_Store", true)]
+    #[case(".well-known/ic-domains", true)]
+    #[case("assets/.cache/app.js", true)]
+    #[case("assets/app.js", false)]
+    #[case("assets/app.v1.js", false)]
+    fn pattern_excludes_dotfiles(#[case] asset_path: &str, #[case] is_hidden: bool) {
+        let asset = Asset::new(asset_path, [].as_slice());
+        let configs = NormalizedAssetConfig::normalize_all([true, false].map(|exclude_dotfiles| {
+            AssetConfig::Pattern {
+                pattern: "**/*".to_string(),
+                exclude: vec![],
+                exclude_dotfiles,
+                content_type: None,
+                headers: vec![],
+                encodings: vec![],
+                image_variants: vec![],
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(configs[0].excludes_asset(&asset), is_hidden);
+        assert!(!configs[1].excludes_asset(&asset));
+    }
+
+    #[rstest]
+    fn normalize_invalid_exclude_glob() {
+        let result = NormalizedAssetConfig::normalize_all([AssetConfig::Pattern {
+            pattern: "**/*".to_string(),
+            exclude: vec!["**/[.*".to_string()],
+            exclude_dotfiles: false,
+            content_type: None,
+            headers: vec![],
+            encodings: vec![],
+            image_variants: vec![],
+        }]);
+
+        assert!(matches!(
+            result,
+            Err(AssetCertificationError::GlobsetError(_))
+        ));
+    }
+
+    #[rstest]
+    #[case("logo.png", vec!["logo.avif", "logo.webp"])]
+    #[case("img/logo.min.png", vec!["img/logo.min.avif", "img/logo.min.webp"])]
+    #[case("img.d/logo", vec!["img.d/logo.avif", "img.d/logo.webp"])]
+    #[case("img/.png", vec!["img/.png.avif", "img/.png.webp"])]
+    #[case("img/logo.avif", vec![])]
+    #[case("img/logo.webp", vec![])]
+    fn image_variant_paths(#[case] asset_path: &str, #[case] expected: Vec<&str>) {
+        let configs = NormalizedAssetConfig::normalize_all([AssetConfig::Pattern {
+            pattern: "**/*".to_string(),
+            exclude: vec![],
+            exclude_dotfiles: false,
+            content_type: None,
+            headers: vec![],
+            encodings: vec![],
+            image_variants: vec![
+                AssetImageFormat::Avif.default_config(),
+                AssetImageFormat::Webp.default_config(),
+            ],
+        }])
+        .unwrap();
+
+        let image_variant_paths = configs[0].image_variant_paths(asset_path);
+
+        assert_eq!(
+            image_variant_paths
+                .iter()
+                .map(|(_, path)| path.as_str())
+                .collect::<Vec<_>>(),
+            expected
+        );
+        assert!(image_variant_paths
+            .iter()
+            .map(|(image_format, _)| *image_format)
+            .eq([AssetImageFormat::Avif, AssetImageFormat::Webp]
+                .into_iter()
+                .take(expected.len())));
+    }
+
+    #[rstest]
+    fn normalize_invalid_glob_scope() {
+        let result = NormalizedAssetConfig::normalize_all([AssetConfig::File {
+            path: "index.html".to_string(),
+            content_type: None,
+            headers: vec![],
+            fallback_for: vec![AssetFallbackConfig {
+                scope: "/app/[*".to_string(),
+                status_code: None,
+                priority: 0,
+            }],
+            aliased_by: vec![],
+            encodings: vec![],
+            image_variants: vec![],
+        }]);
+
+        assert!(matches!(
+            result,
+            Err(AssetCertificationError::GlobsetError(_))
+        ));
+    }
+
+    #[rstest]
+    fn normalize_namespaces() {
+        let configs = NormalizedAssetConfig::normalize_all(vec![
+            AssetConfig::Namespace {
+                name: "app".to_string(),
+                configs: vec![
+                    AssetConfig::Pattern {
+                        pattern: "app/**/*.js".to_string(),
+                        exclude: vec![],
+                        exclude_dotfiles: false,
+                        content_type: None,
+                        headers: vec![],
+                        encodings: vec![],
+                        image_variants: vec![],
+                    },
+                    AssetConfig::Namespace {
+                        name: "admin".to_string(),
+                        configs: vec![AssetConfig::Pattern {
+                            pattern: "admin/**/*.js".to_string(),
+                            exclude: vec![],
+                            exclude_dotfiles: false,
+                            content_type: None,
+                            headers: vec![],
+                            encodings: vec![],
+                            image_variants: vec![],
+                        }],
+                    },
+                ],
+            },
+            AssetConfig::Pattern {
+                pattern: "**/*.css".to_string(),
+                exclude: vec![],
+                exclude_dotfiles: false,
+                content_type: None,
+                headers: vec![],
+                encodings: vec![],
+                image_variants: vec![],
+            },
+        ])
+        .unwrap();
+
+        assert_eq!(
+            configs
+                .iter()
+                .map(NormalizedAssetConfig::namespace)
+                .collect::<Vec<_>>(),
+            vec![Some("app"), Some("admin"), None]
+        );
+        assert!(configs[1].matches_asset(&Asset::new("admin/index.js", vec![])));
+    }
+
     #[rstest]
     fn asset_encoding_to_string() {
         assert_eq!(AssetEncoding::Brotli.to_string(), "br");
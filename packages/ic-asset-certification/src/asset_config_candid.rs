@@ -0,0 +1,335 @@
+use crate::{AssetConfig, AssetEncoding, AssetFallbackConfig, AssetImageFormat, AssetRedirectKind};
+use candid::{
+    types::{Serializer, Type},
+    CandidType, Deserialize,
+};
+use ic_http_certification::StatusCode;
+use serde::{de::Error, Deserializer};
+
+/// The Candid encoding of [AssetConfig]. [StatusCode] does not implement [CandidType],
+/// so the configuration is encoded through this mirror type that holds status codes as `nat16`.
+#[derive(CandidType, Deserialize)]
+enum CandidAssetConfig {
+    File {
+        path: String,
+        content_type: Option<String>,
+        headers: Vec<(String, String)>,
+        fallback_for: Vec<AssetFallbackConfig>,
+        aliased_by: Vec<String>,
+        encodings: Vec<(AssetEncoding, String)>,
+        image_variants: Vec<(AssetImageFormat, String)>,
+    },
+    Pattern {
+        pattern: String,
+        exclude: Vec<String>,
+        exclude_dotfiles: bool,
+        content_type: Option<String>,
+        headers: Vec<(String, String)>,
+        encodings: Vec<(AssetEncoding, String)>,
+        image_variants: Vec<(AssetImageFormat, String)>,
+    },
+    ErrorPage {
+        path: String,
+        status_code: u16,
+        scopes: Vec<String>,
+        content_type: Option<String>,
+        headers: Vec<(String, String)>,
+        encodings: Vec<(AssetEncoding, String)>,
+    },
+    Redirect {
+        from: String,
+        to: String,
+        kind: AssetRedirectKind,
+        append_query: bool,
+        headers: Vec<(String, String)>,
+    },
+    Namespace {
+        name: String,
+        configs: Vec<AssetConfig>,
+    },
+}
+
+/// The Candid encoding of [AssetFallbackConfig], see [CandidAssetConfig].
+#[derive(CandidType, Deserialize)]
+struct CandidAssetFallbackConfig {
+    scope: String,
+    status_code: Option<u16>,
+    priority: i32,
+}
+
+fn status_code_from_u16<E: Error>(status_code: u16) -> Result<StatusCode, E> {
+    StatusCode::from_u16(status_code).map_err(|_| E::custom("Invalid HTTP Status Code."))
+}
+
+impl From<AssetConfig> for CandidAssetConfig {
+    fn from(config: AssetConfig) -> Self {
+        match config {
+            AssetConfig::File {
+                path,
+                content_type,
+                headers,
+                fallback_for,
+                aliased_by,
+                encodings,
+                image_variants,
+            } => CandidAssetConfig::File {
+                path,
+                content_type,
+                headers,
+                fallback_for,
+                aliased_by,
+                encodings,
+                image_variants,
+            },
+            AssetConfig::Pattern {
+                pattern,
+                exclude,
+                exclude_dotfiles,
+                content_type,
+                headers,
+                encodings,
+                image_variants,
+            } => CandidAssetConfig::Pattern {
+                pattern,
+                exclude,
+                exclude_dotfiles,
+                content_type,
+                headers,
+                encodings,
+                image_variants,
+            },
+            AssetConfig::ErrorPage {
+                path,
+                status_code,
+                scopes,
+                content_type,
+                headers,
+                encodings,
+            } => CandidAssetConfig::ErrorPage {
+                path,
+                status_code: status_code.as_u16(),
+                scopes,
+                content_type,
+                headers,
+                encodings,
+            },
+            AssetConfig::Redirect {
+                from,
+                to,
+                kind,
+                append_query,
+                headers,
+            } => CandidAssetConfig::Redirect {
+                from,
+                to,
+                kind,
+                append_query,
+                headers,
+            },
+            AssetConfig::Namespace { name, configs } => {
+                CandidAssetConfig::Namespace { name, configs }
+            }
+        }
+    }
+}
+
+impl CandidAssetConfig {
+    fn into_asset_config<E: Error>(self) -> Result<AssetConfig, E> {
+        let config = match self {
+            CandidAssetConfig::File {
+                path,
+                content_type,
+                headers,
+                fallback_for,
+                aliased_by,
+                encodings,
+                image_variants,
+            } => AssetConfig::File {
+                path,
+                content_type,
+                headers,
+                fallback_for,
+                aliased_by,
+                encodings,
+                image_variants,
+            },
+            CandidAssetConfig::Pattern {
+                pattern,
+                exclude,
+                exclude_dotfiles,
+                content_type,
+                headers,
+                encodings,
+                image_variants,
+            } => AssetConfig::Pattern {
+                pattern,
+                exclude,
+                exclude_dotfiles,
+                content_type,
+                headers,
+                encodings,
+                image_variants,
+            },
+            CandidAssetConfig::ErrorPage {
+                path,
+                status_code,
+                scopes,
+                content_type,
+                headers,
+                encodings,
+            } => AssetConfig::ErrorPage {
+                path,
+                status_code: status_code_from_u16(status_code)?,
+                scopes,
+                content_type,
+                headers,
+                encodings,
+            },
+            CandidAssetConfig::Redirect {
+                from,
+                to,
+                kind,
+                append_query,
+                headers,
+            } => AssetConfig::Redirect {
+                from,
+                to,
+                kind,
+                append_query,
+                headers,
+            },
+            CandidAssetConfig::Namespace { name, configs } => {
+                AssetConfig::Namespace { name, configs }
+            }
+        };
+
+        Ok(config)
+    }
+}
+
+impl CandidType for AssetConfig {
+    fn _ty() -> Type {
+        CandidAssetConfig::_ty()
+    }
+
+    fn idl_serialize<S>(&self, serializer: S) -> Result<(), S::Error>
+    where
+        S: Serializer,
+    {
+        CandidAssetConfig::from(self.clone()).idl_serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for AssetConfig {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        CandidAssetConfig::deserialize(deserializer)?.into_asset_config()
+    }
+}
+
+impl CandidType for AssetFallbackConfig {
+    fn _ty() -> Type {
+        CandidAssetFallbackConfig::_ty()
+    }
+
+    fn idl_serialize<S>(&self, serializer: S) -> Result<(), S::Error>
+    where
+        S: Serializer,
+    {
+        CandidAssetFallbackConfig {
+            scope: self.scope.clone(),
+            status_code: self.status_code.map(|status_code| status_code.as_u16()),
+            priority: self.priority,
+        }
+        .idl_serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for AssetFallbackConfig {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let config = CandidAssetFallbackConfig::deserialize(deserializer)?;
+
+        Ok(AssetFallbackConfig {
+            scope: config.scope,
+            status_code: config.status_code.map(status_code_from_u16).transpose()?,
+            priority: config.priority,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candid::{decode_one, encode_one};
+    use rstest::*;
+
+    #[rstest]
+    fn asset_config_candid_round_trip() {
+        let asset_configs = vec![
+            AssetConfig::File {
+                path: "index.html".to_string(),
+                content_type: Some("text/html".to_string()),
+                headers: vec![("cache-control".to_string(), "no-cache".to_string())],
+                fallback_for: vec![AssetFallbackConfig {
+                    scope: "/".to_string(),
+                    status_code: Some(StatusCode::NOT_FOUND),
+                    priority: 1,
+                }],
+                aliased_by: vec!["/".to_string()],
+                encodings: vec![AssetEncoding::Brotli.default_config()],
+                image_variants: vec![],
+            },
+            AssetConfig::Namespace {
+                name: "admin".to_string(),
+                configs: vec![
+                    AssetConfig::Pattern {
+                        pattern: "admin/**/*.png".to_string(),
+                        exclude: vec!["admin/private/**".to_string()],
+                        exclude_dotfiles: true,
+                        content_type: Some("image/png".to_string()),
+                        headers: vec![],
+                        encodings: vec![],
+                        image_variants: vec![AssetImageFormat::Webp.default_config()],
+                    },
+                    AssetConfig::ErrorPage {
+                        path: "admin/404.html".to_string(),
+                        status_code: StatusCode::NOT_FOUND,
+                        scopes: vec!["/admin".to_string()],
+                        content_type: None,
+                        headers: vec![],
+                        encodings: vec![],
+                    },
+                ],
+            },
+            AssetConfig::Redirect {
+                from: "/old".to_string(),
+                to: "/new".to_string(),
+                kind: AssetRedirectKind::Permanent,
+                append_query: true,
+                headers: vec![],
+            },
+        ];
+
+        let bytes = encode_one(&asset_configs).unwrap();
+        let decoded = decode_one::<Vec<AssetConfig>>(&bytes).unwrap();
+
+        assert_eq!(format!("{decoded:?}"), format!("{asset_configs:?}"));
+    }
+
+    #[rstest]
+    fn asset_fallback_config_candid_invalid_status_code() {
+        let bytes = encode_one(CandidAssetFallbackConfig {
+            scope: "/".to_string(),
+            status_code: Some(1_000),
+            priority: 0,
+        })
+        .unwrap();
+
+        assert!(decode_one::<AssetFallbackConfig>(&bytes).is_err());
+    }
+}
@@ -0,0 +1,62 @@
+use ic_http_certification::{HeaderField, StatusCode};
+
+/// A response that is generated from an [Asset](crate::Asset) and passed to the hook configured
+/// with [with_response_hook](crate::AssetRouter::with_response_hook) before it is certified.
+///
+/// The hook can modify the status code and headers of the response, the body can only be read.
+#[derive(Debug)]
+pub struct AssetHookResponse<'a> {
+    status_code: StatusCode,
+    headers: Vec<HeaderField>,
+    body: Option<&'a [u8]>,
+}
+
+impl<'a> AssetHookResponse<'a> {
+    pub(crate) fn new(
+        status_code: StatusCode,
+        headers: Vec<HeaderField>,
+        body: Option<&'a [u8]>,
+    ) -> Self {
+        Self {
+            status_code,
+            headers,
+            body,
+        }
+    }
+
+    pub(crate) fn into_parts(self) -> (StatusCode, Vec<HeaderField>) {
+        (self.status_code, self.headers)
+    }
+
+    /// Returns the HTTP status code of the response.
+    pub fn status_code(&self) -> StatusCode {
+        self.status_code
+    }
+
+    /// Sets the HTTP status code of the response.
+    pub fn set_status_code(&mut self, status_code: StatusCode) {
+        self.status_code = status_code;
+    }
+
+    /// Returns the HTTP headers of the response.
+    pub fn headers(&self) -> &[HeaderField] {
+        &self.headers
+    }
+
+    /// Returns a mutable reference to the HTTP headers of the response.
+    pub fn headers_mut(&mut self) -> &mut Vec<HeaderField> {
+        &mut self.headers
+    }
+
+    /// Adds an additional header to the HTTP response.
+    pub fn add_header(&mut self, header: HeaderField) {
+        self.headers.push(header);
+    }
+
+    /// Returns the body of the response, or [None] if the content of the asset is not held in
+    /// memory, i.e. if it is read on demand from an [AssetReader](crate::AssetReader) or was
+    /// uploaded in chunks. The body of a chunk of a large asset only contains that chunk.
+    pub fn body(&self) -> Option<&[u8]> {
+        self.body
+    }
+}
@@ -1,6 +1,11 @@
-use crate::{AssetEncoding, CertifiedAssetResponse, RequestKey};
-use ic_http_certification::HttpResponse;
-use std::collections::{hash_map::Iter, HashMap};
+use crate::{
+    subresource_integrity, AssetCertificationResult, AssetEncoding, CertifiedAssetResponse,
+    RequestKey, ASSET_CHUNK_SIZE, ASSET_ENCODINGS,
+};
+use globset::{Glob, GlobMatcher};
+use ic_http_certification::{Hash, HttpResponse, StatusCode};
+use sha2::{Digest, Sha256};
+use std::collections::{hash_map::Iter, BTreeSet, HashMap};
 
 /// A map of assets, indexed by path, encoding, and the starting range.
 pub trait AssetMap<'content> {
@@ -18,12 +23,15 @@ pub trait AssetMap<'content> {
     /// individual chunks of these large assets. The first range is `Some(0)`, the second range is
     /// `Some(ASSET_CHUNK_SIZE)`, the third range is `Some(ASSET_CHUNK_SIZE * 2)`, and so on. The entire asset can
     /// also be retrieved by passing `None` as the starting range. See [ASSET_CHUNK_SIZE](crate::ASSET_CHUNK_SIZE) for the size of each chunk.
+    ///
+    /// The body of the returned response is borrowed from the map. Bodies are stored once per asset and
+    /// shared between all aliases, fallbacks and chunks of that asset.
     fn get(
         &self,
         path: impl Into<String>,
         encoding: Option<AssetEncoding>,
         starting_range: Option<usize>,
-    ) -> Option<&HttpResponse<'content>>;
+    ) -> Option<HttpResponse<'_>>;
 
     /// Returns the number of assets in the map.
     fn len(&self) -> usize;
@@ -35,6 +43,101 @@ pub trait AssetMap<'content> {
 
     /// Returns an iterator over the assets in the map.
     fn iter(&'content self) -> AssetMapIterator<'content>;
+
+    /// Returns an iterator over the assets in the map whose path matches the provided glob pattern,
+    /// e.g. `assets/**/*.js`.
+    ///
+    /// The pattern is matched against the path without its leading slash, in the same way that
+    /// [Pattern](crate::AssetConfig::Pattern) configurations are matched against asset paths.
+    /// For fallback assets, the pattern is matched against the fallback scope.
+    ///
+    /// Returns an error if the glob pattern is invalid.
+    fn iter_matching(
+        &'content self,
+        glob: &str,
+    ) -> AssetCertificationResult<AssetMapIterator<'content>> {
+        Ok(AssetMapIterator {
+            matcher: Some(Glob::new(glob)?.compile_matcher()),
+            ..self.iter()
+        })
+    }
+
+    /// Get the metadata of an asset by path.
+    ///
+    /// The path is interpreted in the same way as for the [get](AssetMap::get) method, i.e. fallback
+    /// assets are identified by their scope.
+    ///
+    /// Returns [None] if no asset is stored for the provided path.
+    fn get_metadata(&self, path: impl Into<String>) -> Option<AssetMetadata> {
+        let path = path.into();
+        // redirects are the only responses without an asset that have a `Location` header
+        let is_redirect = self.get(path.clone(), None, None).is_some_and(|response| {
+            response
+                .headers()
+                .iter()
+                .any(|(name, _)| name.eq_ignore_ascii_case("location"))
+        });
+
+        asset_metadata(path.clone(), is_redirect, |encoding| {
+            let response = self.get(path.clone(), encoding, None)?;
+            let is_chunked = self.get(path.clone(), encoding, Some(0)).is_some();
+
+            Some((
+                response.status_code(),
+                response.body().len(),
+                is_chunked,
+                Sha256::digest(response.body()).into(),
+            ))
+        })
+    }
+
+    /// Returns the metadata of all assets in the map, ordered by path.
+    ///
+    /// This can be used to expose an inventory of the certified assets, without having to
+    /// iterate over the individual responses of every encoding and chunk.
+    fn metadata(&'content self) -> Vec<AssetMetadata> {
+        self.iter()
+            .map(|((path, _, _), _)| path)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .filter_map(|path| self.get_metadata(path))
+            .collect()
+    }
+}
+
+/// Metadata describing an asset stored in an [AssetMap].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetMetadata {
+    /// The path of the asset, e.g. `/index.html`, or the scope for fallback assets, e.g. `/`.
+    pub path: String,
+
+    /// The status code of the asset's response.
+    pub status_code: StatusCode,
+
+    /// The [Subresource Integrity](https://developer.mozilla.org/en-US/docs/Web/Security/Subresource_Integrity)
+    /// digest of the unencoded asset, in the `sha256-<base64>` format used by the `integrity` attribute.
+    /// [None] if the asset is only available in encoded form, or if it is a redirect.
+    pub integrity: Option<String>,
+
+    /// The encodings that are available for the asset, the unencoded asset comes first.
+    pub encodings: Vec<AssetEncodingMetadata>,
+}
+
+/// Metadata describing a single encoding of an asset stored in an [AssetMap].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetEncodingMetadata {
+    /// The encoding of the asset, see [AssetEncoding]. [None] for the unencoded asset.
+    pub encoding: Option<AssetEncoding>,
+
+    /// The length of the full response body in bytes.
+    pub content_length: usize,
+
+    /// The number of chunks that the response body is split into. Assets that are not larger
+    /// than [ASSET_CHUNK_SIZE](crate::ASSET_CHUNK_SIZE) consist of a single chunk.
+    pub chunk_count: usize,
+
+    /// The SHA-256 hash of the full response body.
+    pub sha256: Hash,
 }
 
 impl<'content> AssetMap<'content> for HashMap<RequestKey, CertifiedAssetResponse<'content>> {
@@ -43,10 +146,10 @@ impl<'content> AssetMap<'content> for HashMap<RequestKey, CertifiedAssetResponse
         path: impl Into<String>,
         encoding: Option<AssetEncoding>,
         range_begin: Option<usize>,
-    ) -> Option<&HttpResponse<'content>> {
+    ) -> Option<HttpResponse<'_>> {
         let req_key = RequestKey::new(path, encoding.map(|e| e.to_string()), range_begin);
 
-        self.get(&req_key).map(|e| &e.response)
+        self.get(&req_key).map(|e| e.response.as_http_response())
     }
 
     fn len(&self) -> usize {
@@ -54,28 +157,109 @@ impl<'content> AssetMap<'content> for HashMap<RequestKey, CertifiedAssetResponse
     }
 
     fn iter(&'content self) -> AssetMapIterator<'content> {
-        AssetMapIterator { inner: self.iter() }
+        AssetMapIterator {
+            inner: self.iter(),
+            matcher: None,
+        }
+    }
+
+    fn get_metadata(&self, path: impl Into<String>) -> Option<AssetMetadata> {
+        let path = path.into();
+        let is_redirect = self
+            .get(&RequestKey::new(&path, None, None))
+            .is_some_and(|response| response.asset_path.is_none());
+
+        asset_metadata(path.clone(), is_redirect, |encoding| {
+            let encoding = encoding.map(|encoding| encoding.to_string());
+            let response = &self
+                .get(&RequestKey::new(&path, encoding.clone(), None))?
+                .response;
+            let is_chunked = self.contains_key(&RequestKey::new(&path, encoding, Some(0)));
+
+            Some((
+                response.status_code,
+                response.body_len(),
+                is_chunked,
+                response.body_hash,
+            ))
+        })
+    }
+
+    fn metadata(&'content self) -> Vec<AssetMetadata> {
+        self.keys()
+            .map(|key| key.path.as_str())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .filter_map(|path| self.get_metadata(path))
+            .collect()
     }
 }
 
+/// Collects the metadata of the asset at the provided path from the status code, body length,
+/// whether it is split into chunks and body hash of the full response in each encoding, which
+/// are returned by `encoding_response` if the asset is available in that encoding. Redirects
+/// have no content, so they have no integrity digest.
+fn asset_metadata(
+    path: String,
+    is_redirect: bool,
+    encoding_response: impl Fn(Option<AssetEncoding>) -> Option<(StatusCode, usize, bool, Hash)>,
+) -> Option<AssetMetadata> {
+    let mut status_code = None;
+    let encodings = std::iter::once(None)
+        .chain(ASSET_ENCODINGS.map(Some))
+        .filter_map(|encoding| {
+            let (response_status_code, content_length, is_chunked, sha256) =
+                encoding_response(encoding)?;
+            status_code.get_or_insert(response_status_code);
+
+            Some(AssetEncodingMetadata {
+                encoding,
+                content_length,
+                chunk_count: match is_chunked {
+                    true => content_length.div_ceil(ASSET_CHUNK_SIZE),
+                    false => 1,
+                },
+                sha256,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let first_encoding = encodings.first()?;
+    Some(AssetMetadata {
+        path,
+        status_code: status_code?,
+        integrity: (first_encoding.encoding.is_none() && !is_redirect)
+            .then(|| subresource_integrity(first_encoding.sha256)),
+        encodings,
+    })
+}
+
 /// An iterator over the assets in an asset map.
 #[derive(Debug)]
 pub struct AssetMapIterator<'content> {
     inner: Iter<'content, RequestKey, CertifiedAssetResponse<'content>>,
+    matcher: Option<GlobMatcher>,
 }
 
 impl<'content> Iterator for AssetMapIterator<'content> {
     type Item = (
         (&'content str, Option<&'content str>, Option<usize>),
-        &'content HttpResponse<'content>,
+        HttpResponse<'content>,
     );
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.inner.next().map(|(key, asset)| {
-            (
-                (key.path.as_str(), key.encoding.as_deref(), key.range_begin),
-                &asset.response,
-            )
-        })
+        let matcher = &self.matcher;
+
+        self.inner
+            .find(|(key, _)| match matcher {
+                Some(matcher) => matcher.is_match(key.path.trim_start_matches('/')),
+                None => true,
+            })
+            .map(|(key, asset)| {
+                (
+                    (key.path.as_str(), key.encoding.as_deref(), key.range_begin),
+                    asset.response.as_http_response(),
+                )
+            })
     }
 }
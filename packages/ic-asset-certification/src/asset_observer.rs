@@ -0,0 +1,41 @@
+/// An observer that is notified whenever the set of assets that are certified by the
+/// [AssetRouter](crate::AssetRouter) changes, see
+/// [with_asset_observer](crate::AssetRouter::with_asset_observer).
+///
+/// This allows canisters to emit audit logs or to keep secondary indexes up to date without
+/// tracking the operations of the router separately.
+///
+/// # Examples
+///
+/// ```
+/// use ic_asset_certification::{Asset, AssetObserver, AssetRouter};
+///
+/// #[derive(Debug)]
+/// struct AuditLog;
+///
+/// impl AssetObserver for AuditLog {
+///     fn on_certified(&mut self, path: &str, encoding: Option<&str>) {
+///         // this should normally be written to a log, e.g. using `ic_cdk::println!`.
+///         println!("certified {path} ({encoding:?})");
+///     }
+///
+///     fn on_deleted(&mut self, path: &str) {
+///         println!("deleted {path}");
+///     }
+/// }
+///
+/// let mut asset_router = AssetRouter::default().with_asset_observer(AuditLog);
+/// asset_router
+///     .certify_assets(vec![Asset::new("index.html", b"<html></html>".as_slice())], vec![])
+///     .unwrap();
+/// ```
+pub trait AssetObserver: Send + Sync {
+    /// Called for each asset that was certified by an operation of the router, i.e. each asset
+    /// that was not certified before or whose content changed. The encoding is the value of the
+    /// asset's `Content-Encoding` header, e.g. `gzip`, or [None] for unencoded assets.
+    fn on_certified(&mut self, path: &str, encoding: Option<&str>);
+
+    /// Called for each asset that was certified before an operation of the router, and is no
+    /// longer certified after it.
+    fn on_deleted(&mut self, path: &str);
+}
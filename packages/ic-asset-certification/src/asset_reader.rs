@@ -0,0 +1,82 @@
+use ic_http_certification::Hash;
+
+/// A source of asset content that is read on demand, such as a file in stable memory.
+///
+/// Assets that are created with [Asset::from_reader](crate::Asset::from_reader) are certified
+/// chunk by chunk, so their content never has to be loaded into the heap all at once. The content
+/// of a response is only read when the response is served, one chunk of
+/// [ASSET_CHUNK_SIZE](crate::ASSET_CHUNK_SIZE) bytes at a time.
+///
+/// The content must not change while the asset is certified, otherwise the served responses
+/// will no longer match their certification.
+///
+/// The hash of the full content is calculated at most once per [Asset](crate::Asset). To avoid
+/// reading the content again whenever assets are synchronized with
+/// [sync_assets](crate::AssetRouter::sync_assets), a reader can return a hash that was stored
+/// along with the content from [sha256](AssetReader::sha256).
+///
+/// # Examples
+///
+/// ```
+/// use ic_asset_certification::AssetReader;
+///
+/// #[derive(Debug)]
+/// struct StableFile {
+///     offset: usize,
+///     len: usize,
+/// }
+///
+/// impl AssetReader for StableFile {
+///     fn len(&self) -> usize {
+///         self.len
+///     }
+///
+///     fn read(&self, offset: usize, buf: &mut [u8]) {
+///         // this should normally be read using `ic_cdk::api::stable::stable_read`.
+///         let _stable_offset = self.offset + offset;
+///         buf.fill(0);
+///     }
+/// }
+/// ```
+pub trait AssetReader: Send + Sync {
+    /// Returns the length of the content in bytes.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if the content is empty.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Fills `buf` with the content starting at `offset`.
+    ///
+    /// The [AssetRouter](crate::AssetRouter) never reads beyond the [length](AssetReader::len)
+    /// of the content.
+    fn read(&self, offset: usize, buf: &mut [u8]);
+
+    /// Returns the SHA-256 hash of the full content if it is known without reading the content,
+    /// e.g. because it was stored along with the content. The hash is certified as it is, so it
+    /// must match the content.
+    fn sha256(&self) -> Option<Hash> {
+        None
+    }
+}
+
+impl AssetReader for [u8] {
+    fn len(&self) -> usize {
+        <[u8]>::len(self)
+    }
+
+    fn read(&self, offset: usize, buf: &mut [u8]) {
+        buf.copy_from_slice(&self[offset..offset + buf.len()]);
+    }
+}
+
+impl AssetReader for Vec<u8> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn read(&self, offset: usize, buf: &mut [u8]) {
+        self.as_slice().read(offset, buf)
+    }
+}
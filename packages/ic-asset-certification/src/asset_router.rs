@@ -1,14 +1,32 @@
 use crate::{
-    Asset, AssetCertificationError, AssetCertificationResult, AssetConfig, AssetEncoding,
-    AssetFallbackConfig, AssetMap, AssetRedirectKind, CertifiedAssetResponse,
-    NormalizedAssetConfig, RequestKey,
+    asset::path_to_url, glob_scope_matcher, glob_scope_prefix, is_glob_scope,
+    subresource_integrity, AliasCollision, Asset, AssetBody, AssetCertificationError,
+    AssetCertificationResult, AssetConfig, AssetEncoding, AssetFallbackConfig, AssetHookResponse,
+    AssetImageFormat, AssetMap, AssetObserver, AssetRedirectKind, AssetResponse, AssetUpload,
+    CertificationReport, CertificationTreeHandle, CertifiedAssetResponse, CertifiedRedirect,
+    HashedAssetAliasConfig, HashedAssetManifest, MissingEncoding, NormalizedAssetConfig,
+    NormalizedAssetConfigs, PrecertifiedAssets, RequestKey, ServeOptions, SharedAsset,
+    WellKnownAssets,
 };
+use globset::GlobMatcher;
+use ic_certification::HashTree;
 use ic_http_certification::{
-    utils::add_v2_certificate_header, DefaultCelBuilder, DefaultResponseCertification, Hash,
-    HttpCertification, HttpCertificationPath, HttpCertificationTree, HttpCertificationTreeEntry,
-    HttpRequest, HttpResponse, StatusCode, CERTIFICATE_EXPRESSION_HEADER_NAME,
+    header, utils::add_v2_certificate_header, DefaultCelBuilder, DefaultFullCelExpression,
+    DefaultResponseCertification, Hash, HttpCertification, HttpCertificationError,
+    HttpCertificationPath, HttpCertificationTree, HttpCertificationTreeEntry, HttpRequest,
+    HttpResponse, StatusCode, CERTIFICATE_EXPRESSION_HEADER_NAME,
+};
+use sha2::{Digest, Sha256};
+use std::{
+    borrow::Cow,
+    cell::RefCell,
+    cmp,
+    collections::{hash_map::Entry, BTreeMap, BTreeSet, HashMap, HashSet},
+    fmt,
+    ops::Range,
+    rc::Rc,
+    sync::{Arc, OnceLock, RwLock},
 };
-use std::{borrow::Cow, cell::RefCell, cmp, collections::HashMap, rc::Rc};
 
 /// A router for certifying and serving static [Assets](Asset).
 ///
@@ -42,6 +60,7 @@ use std::{borrow::Cow, cell::RefCell, cmp, collections::HashMap, rc::Rc};
 ///         )],
 ///         fallback_for: vec![AssetFallbackConfig {
 ///             status_code: Some(StatusCode::OK),
+///             priority: 0,
 ///             scope: "/".to_string(),
 ///         }],
 ///         aliased_by: vec!["/".to_string()],
@@ -49,9 +68,12 @@ use std::{borrow::Cow, cell::RefCell, cmp, collections::HashMap, rc::Rc};
 ///             AssetEncoding::Brotli.default_config(),
 ///             AssetEncoding::Gzip.default_config(),
 ///         ],
+///         image_variants: vec![],
 ///     },
 ///     AssetConfig::Pattern {
 ///         pattern: "**/*.js".to_string(),
+///         exclude: vec![],
+///         exclude_dotfiles: false,
 ///         content_type: Some("text/javascript".to_string()),
 ///         headers: vec![(
 ///             "cache-control".to_string(),
@@ -61,9 +83,12 @@ use std::{borrow::Cow, cell::RefCell, cmp, collections::HashMap, rc::Rc};
 ///             AssetEncoding::Brotli.default_config(),
 ///             AssetEncoding::Gzip.default_config(),
 ///         ],
+///         image_variants: vec![],
 ///     },
 ///     AssetConfig::Pattern {
 ///         pattern: "**/*.css".to_string(),
+///         exclude: vec![],
+///         exclude_dotfiles: false,
 ///         content_type: Some("text/css".to_string()),
 ///         headers: vec![(
 ///             "cache-control".to_string(),
@@ -73,11 +98,13 @@ use std::{borrow::Cow, cell::RefCell, cmp, collections::HashMap, rc::Rc};
 ///             AssetEncoding::Brotli.default_config(),
 ///             AssetEncoding::Gzip.default_config(),
 ///         ],
+///         image_variants: vec![],
 ///     },
 ///     AssetConfig::Redirect {
 ///         from: "/old-url".to_string(),
 ///         to: "/".to_string(),
 ///         kind: AssetRedirectKind::Permanent,
+///         append_query: false,
 ///         headers: vec![(
 ///             "content-type".to_string(),
 ///             "text/plain; charset=utf-8".to_string(),
@@ -87,6 +114,7 @@ use std::{borrow::Cow, cell::RefCell, cmp, collections::HashMap, rc::Rc};
 ///         from: "/css/app.css".to_string(),
 ///         to: "/css/app-ba74b708.css".to_string(),
 ///         kind: AssetRedirectKind::Temporary,
+///         append_query: false,
 ///         headers: vec![(
 ///             "content-type".to_string(),
 ///             "text/plain; charset=utf-8".to_string(),
@@ -119,11 +147,162 @@ use std::{borrow::Cow, cell::RefCell, cmp, collections::HashMap, rc::Rc};
 /// let mut http_certification_tree: Rc<RefCell<HttpCertificationTree>> = Default::default();
 /// let mut asset_router = AssetRouter::with_tree(http_certification_tree.clone());
 /// ```
+///
+/// The tree is shared using an `Rc<RefCell<_>>` by default, which is sufficient for single-threaded
+/// canisters. To use the router in multi-threaded environments, such as test harnesses or state machines
+/// that require [Send] and [Sync], use a [SyncAssetRouter] instead. See [CertificationTreeHandle] for
+/// more information.
 #[derive(Debug)]
-pub struct AssetRouter<'content> {
-    tree: Rc<RefCell<HttpCertificationTree>>,
+pub struct AssetRouter<'content, T = Rc<RefCell<HttpCertificationTree>>> {
+    tree: T,
     responses: HashMap<RequestKey, CertifiedAssetResponse<'content>>,
     fallback_responses: HashMap<RequestKey, CertifiedAssetResponse<'content>>,
+    /// Fallbacks that are shadowed by a fallback with a higher priority for the same scope.
+    /// They are certified again once the fallback that shadows them is deleted.
+    shadowed_fallback_responses: HashMap<RequestKey, Vec<CertifiedAssetResponse<'content>>>,
+    /// Responses for the alternative image formats of assets, see
+    /// [image_variants](AssetConfig::File::image_variants).
+    image_variant_responses:
+        HashMap<(AssetImageFormat, RequestKey), CertifiedAssetResponse<'content>>,
+    /// Matchers for the fallback scopes that are glob patterns, indexed by the prefix that
+    /// they are certified for and by their scope.
+    fallback_scope_matchers: HashMap<String, HashMap<String, GlobMatcher>>,
+    error_responses: HashMap<StatusCode, HashMap<RequestKey, CertifiedAssetResponse<'content>>>,
+    /// The key of each certified asset that [sync_assets](AssetRouter::sync_assets) compares to
+    /// skip assets that did not change, see [sync_keys](AssetRouter::sync_keys).
+    asset_sync_keys: HashMap<String, Hash>,
+    integrity_header: Option<String>,
+    trailing_slash_aliases: bool,
+    directory_index_aliases: bool,
+    stripped_query_params: Vec<String>,
+    strict_query_strings: bool,
+    strict_asset_configs: bool,
+    encoding_priority: Option<Vec<AssetEncoding>>,
+    response_hook: Option<ResponseHook>,
+    root_hash_change_hook: Option<RootHashChangeHook>,
+    asset_observer: Option<AssetObserverHook>,
+    lazy_certification: bool,
+    precertified_assets: PrecertifiedAssets,
+    max_asset_size: Option<usize>,
+    max_total_asset_size: Option<usize>,
+    asset_sizes: AssetSizes,
+    /// Assets that are certified on demand, indexed by their URL.
+    pending_assets: HashMap<String, Vec<PendingAsset<'content>>>,
+    /// The URLs of assets that are certified on demand, including aliases,
+    /// mapped to the URL of the asset.
+    pending_urls: HashMap<String, String>,
+    /// The URLs of assets that are certified on demand, mapped to all URLs in `pending_urls`
+    /// that map to them, including aliases.
+    pending_url_aliases: HashMap<String, HashSet<String>>,
+    /// The paths of assets that belong to a namespace, mapped to the name of the namespace.
+    asset_namespaces: HashMap<String, String>,
+    /// The URLs of redirects that belong to a namespace, mapped to the name of the namespace.
+    redirect_namespaces: HashMap<String, String>,
+    /// The asset that is currently being uploaded, see [begin_asset](AssetRouter::begin_asset).
+    asset_upload: Option<AssetUpload>,
+}
+
+/// An asset that is certified on demand, see
+/// [with_lazy_certification](AssetRouter::with_lazy_certification).
+#[derive(Debug)]
+struct PendingAsset<'content> {
+    asset: SharedAsset<'content>,
+    asset_config: Option<NormalizedAssetConfig>,
+    encoding: Option<AssetEncoding>,
+    additional_headers: Vec<(String, String)>,
+}
+
+/// The content sizes of all certified and pending assets, indexed by their path, along with their
+/// total size, see [with_max_total_asset_size](AssetRouter::with_max_total_asset_size).
+///
+/// The sizes are updated whenever a response or pending asset is inserted or deleted, so the
+/// total size does not have to be recalculated for each operation. Each asset counts the responses
+/// and pending assets that were generated from it, and its size is only subtracted from the total
+/// once the last of them is deleted. The total size saturates at [usize::MAX].
+#[derive(Debug, Default)]
+struct AssetSizes {
+    sizes: HashMap<String, (usize, usize)>,
+    total_size: usize,
+}
+
+impl AssetSizes {
+    fn size(&self, asset_path: &str) -> usize {
+        self.sizes.get(asset_path).map_or(0, |(size, _)| *size)
+    }
+
+    fn insert(&mut self, asset_path: &str, size: usize) {
+        let (asset_size, count) = self.sizes.entry(asset_path.to_string()).or_default();
+        self.total_size = self
+            .total_size
+            .saturating_sub(*asset_size)
+            .saturating_add(size);
+        *asset_size = size;
+        *count += 1;
+    }
+
+    fn remove(&mut self, asset_path: &str) {
+        let Entry::Occupied(mut entry) = self.sizes.entry(asset_path.to_string()) else {
+            return;
+        };
+
+        let (size, count) = entry.get_mut();
+        *count -= 1;
+        if *count == 0 {
+            self.total_size = self.total_size.saturating_sub(*size);
+            entry.remove();
+        }
+    }
+
+    fn insert_response(&mut self, response: &CertifiedAssetResponse) {
+        if let Some(asset_path) = &response.asset_path {
+            self.insert(asset_path, response.response.body.len());
+        }
+    }
+
+    fn remove_response(&mut self, response: &CertifiedAssetResponse) {
+        if let Some(asset_path) = &response.asset_path {
+            self.remove(asset_path);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.sizes.clear();
+        self.total_size = 0;
+    }
+}
+
+/// A hook that is run on each asset response before it is certified, see
+/// [with_response_hook](AssetRouter::with_response_hook).
+struct ResponseHook(Box<ResponseHookFn>);
+
+type ResponseHookFn = dyn FnMut(&Asset, &mut AssetHookResponse) + Send + Sync;
+
+impl fmt::Debug for ResponseHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ResponseHook")
+    }
+}
+
+/// A hook that is run whenever an operation changes the root hash of the tree, see
+/// [on_root_hash_change](AssetRouter::on_root_hash_change).
+struct RootHashChangeHook(Box<RootHashChangeHookFn>);
+
+type RootHashChangeHookFn = dyn FnMut(&Hash) + Send + Sync;
+
+impl fmt::Debug for RootHashChangeHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RootHashChangeHook")
+    }
+}
+
+/// An observer that is notified whenever an operation changes the certified assets, see
+/// [with_asset_observer](AssetRouter::with_asset_observer).
+struct AssetObserverHook(Box<dyn AssetObserver>);
+
+impl fmt::Debug for AssetObserverHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AssetObserverHook")
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -136,10 +315,115 @@ struct RangeRequestValues {
 /// The chunk size that will be used when splitting assets larger than 2mb down into smaller chunks.
 pub const ASSET_CHUNK_SIZE: usize = 2_000_000;
 
+const LOCATION_HEADER_NAME: &str = "location";
+
+const RANGE_HEADER_NAME: &str = "range";
+
+/// A CEL expression together with its string representation. Every response is certified
+/// with one of a few CEL expressions, so they are built once and reused.
+struct InternedCelExpression {
+    cel_expr: DefaultFullCelExpression<'static>,
+    cel_expr_str: String,
+}
+
+impl InternedCelExpression {
+    fn new(cel_expr: DefaultFullCelExpression<'static>) -> Self {
+        let cel_expr_str = cel_expr.to_string();
+
+        Self {
+            cel_expr,
+            cel_expr_str,
+        }
+    }
+
+    /// The CEL expression of asset responses, partial responses additionally certify the
+    /// `Range` request header.
+    fn asset(is_range_request: bool) -> &'static Self {
+        static ASSET: OnceLock<InternedCelExpression> = OnceLock::new();
+        static RANGE_ASSET: OnceLock<InternedCelExpression> = OnceLock::new();
+
+        let (interned, request_headers): (_, &'static [&'static str]) = if is_range_request {
+            (&RANGE_ASSET, &[RANGE_HEADER_NAME])
+        } else {
+            (&ASSET, &[])
+        };
+
+        interned.get_or_init(|| {
+            Self::new(
+                DefaultCelBuilder::full_certification()
+                    .with_request_headers(request_headers)
+                    .with_response_certification(
+                        DefaultResponseCertification::response_header_exclusions(vec![]),
+                    )
+                    .build(),
+            )
+        })
+    }
+
+    /// The CEL expression of redirect responses, the location of a redirect that appends the
+    /// query string of the request depends on the request so it is excluded from certification.
+    fn redirect(append_query: bool) -> &'static Self {
+        static REDIRECT: OnceLock<InternedCelExpression> = OnceLock::new();
+        static QUERY_PRESERVING_REDIRECT: OnceLock<InternedCelExpression> = OnceLock::new();
+
+        let (interned, excluded_response_headers): (_, &'static [&'static str]) = if append_query {
+            (&QUERY_PRESERVING_REDIRECT, &[LOCATION_HEADER_NAME])
+        } else {
+            (&REDIRECT, &[])
+        };
+
+        interned.get_or_init(|| {
+            Self::new(
+                DefaultCelBuilder::full_certification()
+                    .with_response_certification(
+                        DefaultResponseCertification::response_header_exclusions(
+                            excluded_response_headers,
+                        ),
+                    )
+                    .build(),
+            )
+        })
+    }
+}
+
 fn encoding_str(maybe_encoding: Option<AssetEncoding>) -> Option<String> {
     maybe_encoding.map(|enc| enc.to_string())
 }
 
+/// Combines the content hash of an asset with the hashes of the configurations that its
+/// responses are certified with, see [sync_keys](AssetRouter::sync_keys).
+fn sync_key(content_hash: Hash, mut config_hashes: Vec<Hash>) -> Hash {
+    config_hashes.sort();
+
+    let mut hasher = Sha256::new();
+    hasher.update(content_hash);
+    for config_hash in config_hashes {
+        hasher.update(config_hash);
+    }
+
+    hasher.finalize().into()
+}
+
+/// All encodings that a response can be certified with, in addition to no encoding.
+pub(crate) const ASSET_ENCODINGS: [AssetEncoding; 5] = [
+    AssetEncoding::Identity,
+    AssetEncoding::Brotli,
+    AssetEncoding::Zstd,
+    AssetEncoding::Gzip,
+    AssetEncoding::Deflate,
+];
+
+/// Parses the quality value from the parameters of an `Accept` or `Accept-Encoding` header entry,
+/// e.g. `q=0.8`. Missing or invalid quality values default to `1`.
+fn quality_value<'a>(params: impl Iterator<Item = &'a str>) -> f32 {
+    params
+        .filter_map(|param| param.split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("q"))
+        .and_then(|(_, value)| value.trim().parse::<f32>().ok())
+        .filter(|quality| quality.is_finite())
+        .map_or(1.0, |quality| quality.clamp(0.0, 1.0))
+}
+
 fn parse_range_header_str(range_str: &str) -> Result<RangeRequestValues, String> {
     // expected format: `bytes=<range-begin>-[<range-end>]`
     let str_value = range_str.trim();
@@ -175,24 +459,328 @@ fn parse_range_header_str(range_str: &str) -> Result<RangeRequestValues, String>
     })
 }
 
+/// An [AssetRouter] that shares its
+/// [HttpCertificationTree](ic_http_certification::HttpCertificationTree) using an `Arc<RwLock<_>>`,
+/// so that the router is [Send] and [Sync].
+///
+/// ```
+/// use std::sync::{Arc, RwLock};
+/// use ic_http_certification::HttpCertificationTree;
+/// use ic_asset_certification::{Asset, AssetConfig, SyncAssetRouter};
+///
+/// let http_certification_tree: Arc<RwLock<HttpCertificationTree>> = Default::default();
+/// let mut asset_router = SyncAssetRouter::with_tree(http_certification_tree.clone());
+///
+/// asset_router
+///     .certify_assets(
+///         vec![Asset::new("index.html", b"<html></html>".as_slice())],
+///         vec![AssetConfig::File {
+///             path: "index.html".to_string(),
+///             content_type: Some("text/html".to_string()),
+///             headers: vec![],
+///             fallback_for: vec![],
+///             aliased_by: vec![],
+///             encodings: vec![],
+///             image_variants: vec![],
+///         }],
+///     )
+///     .unwrap();
+///
+/// let root_hash = std::thread::spawn(move || asset_router.root_hash())
+///     .join()
+///     .unwrap();
+/// assert_eq!(root_hash, http_certification_tree.read().unwrap().root_hash());
+/// ```
+pub type SyncAssetRouter<'content> = AssetRouter<'content, Arc<RwLock<HttpCertificationTree>>>;
+
 impl<'content> AssetRouter<'content> {
     /// Creates a new [AssetRouter].
     pub fn new() -> Self {
-        AssetRouter {
-            tree: Default::default(),
-            responses: HashMap::new(),
-            fallback_responses: HashMap::new(),
-        }
+        Self::with_tree(Default::default())
     }
+}
 
+impl<'content, T: CertificationTreeHandle> AssetRouter<'content, T> {
     /// Creates a new [AssetRouter] using the provided
     /// [HttpCertificationTree](ic_http_certification::HttpCertificationTree)
     /// for certifying assets.
-    pub fn with_tree(tree: Rc<RefCell<HttpCertificationTree>>) -> Self {
+    pub fn with_tree(tree: T) -> Self {
         AssetRouter {
             tree,
             responses: HashMap::new(),
             fallback_responses: HashMap::new(),
+            shadowed_fallback_responses: HashMap::new(),
+            image_variant_responses: HashMap::new(),
+            fallback_scope_matchers: HashMap::new(),
+            error_responses: HashMap::new(),
+            asset_sync_keys: HashMap::new(),
+            integrity_header: None,
+            trailing_slash_aliases: false,
+            directory_index_aliases: false,
+            stripped_query_params: vec![],
+            strict_query_strings: false,
+            strict_asset_configs: false,
+            encoding_priority: None,
+            response_hook: None,
+            root_hash_change_hook: None,
+            asset_observer: None,
+            lazy_certification: false,
+            precertified_assets: PrecertifiedAssets::default(),
+            max_asset_size: None,
+            max_total_asset_size: None,
+            asset_sizes: AssetSizes::default(),
+            pending_assets: HashMap::new(),
+            pending_urls: HashMap::new(),
+            pending_url_aliases: HashMap::new(),
+            asset_namespaces: HashMap::new(),
+            redirect_namespaces: HashMap::new(),
+            asset_upload: None,
+        }
+    }
+
+    /// Configures the [AssetRouter] to add the
+    /// [Subresource Integrity](https://developer.mozilla.org/en-US/docs/Web/Security/Subresource_Integrity)
+    /// digest of each asset as a certified response header with the provided name, e.g. `x-integrity`.
+    ///
+    /// The digest has the `sha256-<base64>` format that is expected by the `integrity` attribute of
+    /// `<script>` and `<link>` elements. It is always calculated over the unencoded content of an asset,
+    /// so encoded responses carry the same digest as the unencoded response.
+    ///
+    /// This must be configured before any assets are certified, otherwise previously certified
+    /// assets cannot be deleted by configuration.
+    ///
+    /// The digest of a certified asset can also be retrieved using the [AssetMap::get_metadata] function,
+    /// regardless of this configuration.
+    pub fn with_integrity_header(mut self, header_name: impl Into<String>) -> Self {
+        self.integrity_header = Some(header_name.into());
+        self
+    }
+
+    /// Configures the [AssetRouter] to automatically alias the paths of certified assets
+    /// with and without a trailing slash, so they don't need to be listed manually in the
+    /// [aliased_by](AssetConfig::File::aliased_by) configuration.
+    ///
+    /// With this configuration enabled:
+    /// - assets named `index.html` are aliased by their directory, e.g. `/dir/index.html` is
+    ///   served for `/dir/` and `/dir`, see
+    ///   [with_directory_index_aliases](AssetRouter::with_directory_index_aliases).
+    /// - paths ending with a trailing slash are aliased by the same path without it, e.g. an
+    ///   `/about/` alias is also served for `/about`.
+    /// - paths without a file extension are aliased by the same path with a trailing slash,
+    ///   e.g. an `/about` alias is also served for `/about/`.
+    ///
+    /// Automatic aliases are certified and deleted together with the asset or alias that they
+    /// are derived from. Like other aliases, they are not deleted by the
+    /// [delete_assets_by_path](AssetRouter::delete_assets_by_path) function unless they are
+    /// passed explicitly.
+    ///
+    /// This must be configured before any assets are certified, otherwise previously certified
+    /// assets cannot be deleted by configuration.
+    pub fn with_trailing_slash_aliases(mut self) -> Self {
+        self.trailing_slash_aliases = true;
+        self
+    }
+
+    /// Configures the [AssetRouter] to automatically alias assets named `index.html` by their
+    /// directory, with and without a trailing slash. For example, `/dir/index.html` is also
+    /// served for `/dir/` and `/dir`, and `/index.html` is also served for `/`. This matches
+    /// the behavior of the DFX asset canister.
+    ///
+    /// Unlike [with_trailing_slash_aliases](AssetRouter::with_trailing_slash_aliases), this
+    /// does not add trailing-slash variants for any other paths.
+    ///
+    /// This must be configured before any assets are certified, otherwise previously certified
+    /// assets cannot be deleted by configuration.
+    pub fn with_directory_index_aliases(mut self) -> Self {
+        self.directory_index_aliases = true;
+        self
+    }
+
+    /// Configures the [AssetRouter] to remove the provided query parameters from the query string
+    /// of a request before it is processed, e.g. cache-busting parameters such as `v` in `/app.js?v=123`.
+    ///
+    /// Query strings never affect which asset is served for a request, but the canonicalized query
+    /// string is used when [rejecting query strings](AssetRouter::with_strict_query_strings) and
+    /// when [preserving the query string](AssetConfig::Redirect::append_query) of a redirect.
+    /// Canonicalization also removes empty query parameters, e.g. `?&a=1&` becomes `?a=1`.
+    pub fn with_stripped_query_params(
+        mut self,
+        params: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.stripped_query_params = params.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Configures the [AssetRouter] to reject requests with a query string, after removing any
+    /// [stripped query parameters](AssetRouter::with_stripped_query_params), instead of ignoring it.
+    ///
+    /// [serve_asset](AssetRouter::serve_asset) returns a
+    /// [QueryStringNotAllowed](AssetCertificationError::QueryStringNotAllowed) error for these requests,
+    /// so the canister can decide how to respond. The query string of a request is not certified, so this
+    /// configuration only affects which requests are answered with a certified asset.
+    pub fn with_strict_query_strings(mut self) -> Self {
+        self.strict_query_strings = true;
+        self
+    }
+
+    /// Configures the [AssetRouter] to reject asset configurations that don't apply to the provided assets,
+    /// instead of certifying the assets without them.
+    ///
+    /// [certify_assets](AssetRouter::certify_assets) and [sync_assets](AssetRouter::sync_assets) return a
+    /// [NoAssetMatchingPattern](AssetCertificationError::NoAssetMatchingPattern) error if an
+    /// [AssetConfig::Pattern] does not match any of the provided assets and an
+    /// [EncodedAssetNotFound](AssetCertificationError::EncodedAssetNotFound) error if an encoding is configured
+    /// for an asset, but the encoded asset was not provided. Nothing is certified if an error is returned.
+    pub fn with_strict_asset_configs(mut self) -> Self {
+        self.strict_asset_configs = true;
+        self
+    }
+
+    /// Configures the order in which the [AssetRouter] selects one of the encodings that a client
+    /// accepts, from most to least preferred. By default, encodings are selected in the order
+    /// [Brotli](AssetEncoding::Brotli), [Zstd](AssetEncoding::Zstd), [Gzip](AssetEncoding::Gzip),
+    /// [Deflate](AssetEncoding::Deflate) and [Identity](AssetEncoding::Identity).
+    ///
+    /// Encodings that are not listed are never selected, e.g. to disable Brotli entirely. Listing
+    /// [Identity](AssetEncoding::Identity) before other encodings prefers the unencoded asset over
+    /// them. The unencoded asset is served if none of the listed encodings are accepted by the
+    /// client or certified for the asset.
+    pub fn with_encoding_priority(mut self, encodings: &[AssetEncoding]) -> Self {
+        self.encoding_priority = Some(encodings.to_vec());
+        self
+    }
+
+    /// Configures the [AssetRouter] to run the provided hook on each asset response before
+    /// it is certified, e.g. to add per-asset headers such as a `Content-Security-Policy`
+    /// or custom caching rules that can't be expressed with an [AssetConfig].
+    ///
+    /// The hook receives the [Asset] that the response is generated from and can modify the
+    /// status code and headers of the response. The [body](AssetHookResponse::body) of the
+    /// response is [None] for assets whose content is not held in memory, such as assets whose
+    /// content is read from an [AssetReader](crate::AssetReader). The hook runs
+    /// for every response that is generated from an asset, including alternative encodings,
+    /// aliases, fallbacks, error pages and chunks of large assets, but not for redirects.
+    ///
+    /// The [url](Asset::url) of the asset is the URL that the response is served on. For
+    /// alternative encodings, the [path](Asset::path) is the path of the encoded asset, e.g.
+    /// `index.html.gz`, while the URL is the URL of the unencoded asset, e.g. `/index.html`.
+    ///
+    /// Responses are generated again when assets are deleted, so the hook must modify
+    /// responses for the same asset in the same way every time it runs, otherwise
+    /// previously certified assets cannot be deleted by configuration.
+    ///
+    /// This must be configured before any assets are certified.
+    pub fn with_response_hook(
+        mut self,
+        hook: impl FnMut(&Asset, &mut AssetHookResponse) + Send + Sync + 'static,
+    ) -> Self {
+        self.response_hook = Some(ResponseHook(Box::new(hook)));
+        self
+    }
+
+    /// Configures the [AssetRouter] to run the provided hook with the new root hash of the tree
+    /// whenever an operation of the router changes it, e.g. to set the canister's certified data
+    /// (`ic_cdk::api::set_certified_data()`) automatically instead of after every operation.
+    ///
+    /// The hook runs once at the end of each operation that inserts or deletes certified
+    /// responses, such as [certify_assets](AssetRouter::certify_assets),
+    /// [sync_assets](AssetRouter::sync_assets) or [delete_assets](AssetRouter::delete_assets),
+    /// including operations that return an error after they have changed the tree. It does not run
+    /// if the root hash is unchanged, or if the tree is changed by other code sharing the tree via
+    /// [with_tree](AssetRouter::with_tree).
+    pub fn on_root_hash_change(mut self, hook: impl FnMut(&Hash) + Send + Sync + 'static) -> Self {
+        self.root_hash_change_hook = Some(RootHashChangeHook(Box::new(hook)));
+        self
+    }
+
+    /// Configures the [AssetRouter] to notify the provided [AssetObserver] whenever an operation
+    /// of the router changes the certified assets, e.g. to emit audit logs or to update secondary
+    /// indexes.
+    ///
+    /// Like the [root hash change hook](AssetRouter::on_root_hash_change), the observer is
+    /// notified once at the end of each operation, for each asset that was certified or deleted
+    /// by the operation. An asset is identified by its [path](Asset::path), so alternative
+    /// encodings and image formats are reported separately, while aliases, fallbacks and chunks
+    /// are not. Assets that are [certified on demand](AssetRouter::with_lazy_certification) are
+    /// reported when they are certified, not when they are recorded for certification.
+    pub fn with_asset_observer(mut self, observer: impl AssetObserver + 'static) -> Self {
+        self.asset_observer = Some(AssetObserverHook(Box::new(observer)));
+        self
+    }
+
+    /// Configures the [AssetRouter] to certify assets on demand, which is useful for very
+    /// large sets of assets that exceed the instruction limit when they are certified upfront.
+    ///
+    /// With this configuration, [certify_assets](AssetRouter::certify_assets) and
+    /// [sync_assets](AssetRouter::sync_assets) only record the assets and their configuration.
+    /// Requests for an asset that has not been certified yet are answered with an
+    /// [AssetCertificationPending](AssetCertificationError::AssetCertificationPending) error by
+    /// [serve_asset](AssetRouter::serve_asset), so the canister can upgrade the request to an
+    /// update call and certify the asset with
+    /// [certify_pending_asset](AssetRouter::certify_pending_asset).
+    ///
+    /// Fallbacks, error pages and redirects can be served for requests to any path in their scope,
+    /// so they are always certified upfront.
+    pub fn with_lazy_certification(mut self) -> Self {
+        self.lazy_certification = true;
+        self
+    }
+
+    /// Configures the [AssetRouter] to certify assets using the content hashes that were
+    /// calculated ahead of time, see [PrecertifiedAssets], instead of hashing their content.
+    ///
+    /// The hashes are only used for assets that are held in memory and have the same path and
+    /// content length as the asset the hashes were calculated for. The hashes are not verified
+    /// against the content, so they must be calculated for the same content that is certified,
+    /// otherwise the responses for the affected assets fail verification.
+    pub fn with_precertified_assets(mut self, precertified_assets: PrecertifiedAssets) -> Self {
+        self.precertified_assets = precertified_assets;
+        self
+    }
+
+    /// Configures the maximum size of the content of a single asset in bytes.
+    ///
+    /// [certify_assets](AssetRouter::certify_assets), [sync_assets](AssetRouter::sync_assets) and
+    /// [append_chunk](AssetRouter::append_chunk) return an
+    /// [AssetTooLarge](AssetCertificationError::AssetTooLarge) error instead of certifying or
+    /// uploading an asset that exceeds this size, so content that is uploaded at runtime cannot
+    /// exhaust the canister's heap. No assets are certified if any of the provided assets is too large.
+    pub fn with_max_asset_size(mut self, max_size: usize) -> Self {
+        self.max_asset_size = Some(max_size);
+        self
+    }
+
+    /// Configures the maximum total size of the content of all certified assets in bytes.
+    ///
+    /// [certify_assets](AssetRouter::certify_assets), [sync_assets](AssetRouter::sync_assets) and
+    /// [append_chunk](AssetRouter::append_chunk) return a
+    /// [TotalAssetSizeExceeded](AssetCertificationError::TotalAssetSizeExceeded) error instead of
+    /// certifying or uploading assets that would increase the total size beyond this limit. Assets
+    /// that replace a certified asset with the same path only count once, and assets that are
+    /// certified on demand count from the moment they are recorded.
+    pub fn with_max_total_asset_size(mut self, max_total_size: usize) -> Self {
+        self.max_total_asset_size = Some(max_total_size);
+        self
+    }
+
+    fn canonical_query(&self, request: &HttpRequest) -> AssetCertificationResult<Option<String>> {
+        let Some(query) = request.get_query()? else {
+            return Ok(None);
+        };
+
+        let query = query
+            .split('&')
+            .filter(|param| {
+                let name = param.split_once('=').map_or(*param, |(name, _)| name);
+                !name.is_empty() && !self.stripped_query_params.iter().any(|p| p == name)
+            })
+            .collect::<Vec<_>>()
+            .join("&");
+
+        if query.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(query))
         }
     }
 
@@ -225,29 +813,276 @@ impl<'content> AssetRouter<'content> {
     /// Returns [None] if no suitable
     /// [HttpResponse](ic_http_certification::HttpResponse) is found for the
     /// given [HttpRequest](ic_http_certification::HttpRequest).
+    ///
+    /// The body of the returned response borrows the content of the asset that
+    /// it was generated from, only the headers are copied so that the certificate
+    /// header can be appended. Asset content that is owned by the router, i.e.
+    /// [Cow::Owned](std::borrow::Cow::Owned), cannot outlive the router and is
+    /// copied instead, use [serve_asset_borrowed](AssetRouter::serve_asset_borrowed)
+    /// to avoid copying it.
     pub fn serve_asset(
         &self,
         data_certificate: &[u8],
         request: &HttpRequest,
+    ) -> AssetCertificationResult<HttpResponse<'content>> {
+        self.serve_asset_with_options(data_certificate, request, &ServeOptions::default())
+    }
+
+    /// Returns the corresponding
+    /// [HttpResponse](ic_http_certification::HttpResponse) for the provided
+    /// [HttpRequest](ic_http_certification::HttpRequest), resolved according to
+    /// the provided [ServeOptions].
+    ///
+    /// This behaves like [serve_asset](AssetRouter::serve_asset) with the default
+    /// options, see [ServeOptions] for the available options.
+    pub fn serve_asset_with_options(
+        &self,
+        data_certificate: &[u8],
+        request: &HttpRequest,
+        options: &ServeOptions,
+    ) -> AssetCertificationResult<HttpResponse<'content>> {
+        let (mut response, witness, expr_path) =
+            self.witnessed_asset(request, options, AssetResponse::to_http_response)?;
+        add_v2_certificate_header(data_certificate, &mut response, &witness, &expr_path);
+
+        Ok(response)
+    }
+
+    /// Same as [serve_asset](AssetRouter::serve_asset), but the returned response
+    /// borrows its body from the router instead of copying asset content that is
+    /// owned by the router.
+    ///
+    /// This avoids copying large assets on every query call, when the response is
+    /// encoded while the router is still borrowed, for example with a `manual_reply`
+    /// query. Content that is read from an [AssetReader] or was uploaded in chunks
+    /// is still read into the response.
+    pub fn serve_asset_borrowed(
+        &self,
+        data_certificate: &[u8],
+        request: &HttpRequest,
+    ) -> AssetCertificationResult<HttpResponse<'_>> {
+        let (mut response, witness, expr_path) = self.witnessed_asset(
+            request,
+            &ServeOptions::default(),
+            AssetResponse::as_http_response,
+        )?;
+        add_v2_certificate_header(data_certificate, &mut response, &witness, &expr_path);
+
+        Ok(response)
+    }
+
+    /// Returns the corresponding
+    /// [HttpResponse](ic_http_certification::HttpResponse) for the provided
+    /// [HttpRequest](ic_http_certification::HttpRequest) like
+    /// [serve_asset](AssetRouter::serve_asset), along with its witness and expression path,
+    /// instead of adding the certificate header to the response.
+    ///
+    /// This is useful for callers that need to post-process the headers of the response or
+    /// delay adding the certificate, e.g. handlers that combine the asset router with other
+    /// certified responses. The certificate header can be added to the response using
+    /// [add_v2_certificate_header](ic_http_certification::utils::add_v2_certificate_header).
+    pub fn serve_asset_parts(
+        &self,
+        request: &HttpRequest,
+    ) -> AssetCertificationResult<(HttpResponse<'content>, HashTree, Vec<String>)> {
+        self.witnessed_asset(
+            request,
+            &ServeOptions::default(),
+            AssetResponse::to_http_response,
+        )
+    }
+
+    fn witnessed_asset<'r, 'b>(
+        &'r self,
+        request: &HttpRequest,
+        options: &ServeOptions,
+        to_http_response: impl FnOnce(&'r AssetResponse<'content>) -> HttpResponse<'b>,
+    ) -> AssetCertificationResult<(HttpResponse<'b>, HashTree, Vec<String>)> {
+        let forced_encoding = options.encoding.map(|encoding| encoding.to_string());
+        let preferred_encodings = match &forced_encoding {
+            Some(encoding) => vec![encoding.as_str()],
+            None => self.get_preferred_encodings(request),
+        };
+        let request_url = request.get_path()?;
+        let query = self.canonical_query(request)?;
+        if let (true, Some(query)) = (self.strict_query_strings, &query) {
+            return Err(AssetCertificationError::QueryStringNotAllowed {
+                request_url,
+                query: query.clone(),
+            });
+        }
+        if self.pending_urls.contains_key(&request_url) {
+            return Err(AssetCertificationError::AssetCertificationPending { request_url });
+        }
+
+        let maybe_range_begin = if options.disable_partial_content {
+            None
+        } else {
+            Self::maybe_get_range_begin(request)?
+        };
+        let identity_excluded = forced_encoding.is_none()
+            && !preferred_encodings
+                .iter()
+                .any(|encoding| encoding.eq_ignore_ascii_case("identity"));
+        // alternative image formats are not encoded
+        let preferred_image_formats = if identity_excluded {
+            vec![]
+        } else {
+            Self::get_preferred_image_formats(request)
+        };
+        let cert_response = self.get_asset_for_request(
+            &request_url,
+            preferred_encodings,
+            &preferred_image_formats,
+            maybe_range_begin,
+            options,
+        )?;
+
+        let is_unencoded_asset = cert_response.asset_path.is_some()
+            && !cert_response
+                .response
+                .headers
+                .iter()
+                .any(|(name, _)| name.eq_ignore_ascii_case("content-encoding"));
+        if identity_excluded && is_unencoded_asset {
+            return Err(AssetCertificationError::NoAcceptableEncoding { request_url });
+        }
+
+        let (mut response, witness, expr_path) =
+            self.witnessed_response(cert_response, &request_url, to_http_response)?;
+        if cert_response.append_query {
+            if let Some(query) = query {
+                Self::append_query_to_location(&mut response, &query);
+            }
+        }
+
+        Ok((response, witness, expr_path))
+    }
+
+    /// Returns the certified error page with the provided status code for the
+    /// provided [HttpRequest](ic_http_certification::HttpRequest).
+    ///
+    /// This is useful for canisters that handle some requests themselves and need
+    /// to respond with a certified error, e.g. a [FORBIDDEN](StatusCode::FORBIDDEN)
+    /// response when a request is not authorized. Error pages are configured using
+    /// the [ErrorPage](AssetConfig::ErrorPage) configuration.
+    ///
+    /// The error page with the most specific scope matching the request path is used.
+    /// Response verification only accepts a response for the most specific scope that
+    /// is certified for a request, so an error is returned if a more specific scope
+    /// has a fallback or an error page with a different status code. An error is also
+    /// returned if the request path exactly matches a certified asset.
+    pub fn serve_error(
+        &self,
+        data_certificate: &[u8],
+        request: &HttpRequest,
+        status_code: StatusCode,
     ) -> AssetCertificationResult<HttpResponse<'content>> {
         let preferred_encodings = self.get_preferred_encodings(request);
         let request_url = request.get_path()?;
-        let maybe_range_begin = Self::maybe_get_range_begin(request)?;
-        let mut cert_response = self
-            .get_asset_for_request(&request_url, preferred_encodings, maybe_range_begin)
-            .cloned()?;
+        let cert_response =
+            self.get_error_page_for_request(&request_url, &preferred_encodings, status_code)?;
+
+        self.certified_response(data_certificate, cert_response, &request_url)
+    }
+
+    /// Returns the certified response for the asset served on the provided path, with the
+    /// provided encoding and chunk, along with its witness and expression path.
+    ///
+    /// Unlike [serve_asset](AssetRouter::serve_asset), no fallbacks are searched for and no
+    /// certificate header is added to the response. This is useful for canisters that implement
+    /// their own transport, such as Candid query methods to download the chunks of an asset,
+    /// and need to retrieve an exact certified response. The certificate header can be added
+    /// to the response using
+    /// [add_v2_certificate_header](ic_http_certification::utils::add_v2_certificate_header).
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path that the asset is served on, e.g. `/index.html` or an alias.
+    /// * `encoding` - The encoding of the response, [Identity](AssetEncoding::Identity) for the
+    ///     unencoded response.
+    /// * `chunk_index` - The index of the chunk of an asset that is larger than
+    ///     [ASSET_CHUNK_SIZE](crate::ASSET_CHUNK_SIZE), or [None] for the full response.
+    ///
+    /// Returns a [NoAssetMatchingRequestUrl](AssetCertificationError::NoAssetMatchingRequestUrl)
+    /// error if no such response is certified.
+    pub fn get_certified_response(
+        &self,
+        path: &str,
+        encoding: AssetEncoding,
+        chunk_index: Option<usize>,
+    ) -> AssetCertificationResult<(HttpResponse<'content>, HashTree, Vec<String>)> {
+        let encoding = match encoding {
+            AssetEncoding::Identity => None,
+            encoding => Some(encoding.to_string()),
+        };
+        let range_begin = chunk_index.map(|chunk_index| chunk_index * ASSET_CHUNK_SIZE);
+        let cert_response = self
+            .responses
+            .get(&RequestKey::new(path, encoding, range_begin))
+            .ok_or_else(|| AssetCertificationError::NoAssetMatchingRequestUrl {
+                request_url: path.to_string(),
+            })?;
+
+        self.witnessed_response(cert_response, path, AssetResponse::to_http_response)
+    }
+
+    fn certified_response(
+        &self,
+        data_certificate: &[u8],
+        cert_response: &CertifiedAssetResponse<'content>,
+        request_url: &str,
+    ) -> AssetCertificationResult<HttpResponse<'content>> {
+        let (mut response, witness, expr_path) =
+            self.witnessed_response(cert_response, request_url, AssetResponse::to_http_response)?;
+        add_v2_certificate_header(data_certificate, &mut response, &witness, &expr_path);
+        Ok(response)
+    }
+
+    fn witnessed_response<'r, 'b>(
+        &self,
+        cert_response: &'r CertifiedAssetResponse<'content>,
+        request_url: &str,
+        to_http_response: impl FnOnce(&'r AssetResponse<'content>) -> HttpResponse<'b>,
+    ) -> AssetCertificationResult<(HttpResponse<'b>, HashTree, Vec<String>)> {
+        // the entry can be missing from the tree if it is shared and was modified externally,
+        // in that case the asset is treated as not found
         let witness = self
             .tree
-            .borrow()
-            .witness(&cert_response.tree_entry, &request_url)?;
+            .read_tree()
+            .witness(&cert_response.tree_entry, request_url)
+            .map_err(|err| match err {
+                HttpCertificationError::WitnessEntryNotFound { request_path } => {
+                    AssetCertificationError::NoAssetMatchingRequestUrl {
+                        request_url: request_path,
+                    }
+                }
+                err => err.into(),
+            })?;
         let expr_path = cert_response.tree_entry.path.to_expr_path();
-        add_v2_certificate_header(
-            data_certificate,
-            &mut cert_response.response,
-            &witness,
-            &expr_path,
-        );
-        Ok(cert_response.response.clone())
+        let response = to_http_response(&cert_response.response);
+        Ok((response, witness, expr_path))
+    }
+
+    fn append_query_to_location(response: &mut HttpResponse<'_>, query: &str) {
+        for (name, value) in response.headers_mut().iter_mut() {
+            if !name.eq_ignore_ascii_case(LOCATION_HEADER_NAME) {
+                continue;
+            }
+
+            let (location, fragment) = match value.split_once('#') {
+                Some((location, fragment)) => (location, Some(fragment)),
+                None => (value.as_str(), None),
+            };
+            let separator = if location.contains('?') { '&' } else { '?' };
+
+            let mut new_value = format!("{location}{separator}{query}");
+            if let Some(fragment) = fragment {
+                new_value.push('#');
+                new_value.push_str(fragment);
+            }
+            *value = new_value;
+        }
     }
 
     /// Returns all standard assets stored in the router.
@@ -279,29 +1114,47 @@ impl<'content> AssetRouter<'content> {
     /// If no configuration matches an individual asset, the asset will be
     /// served and certified as-is, without headers.
     ///
+    /// Returns a [CertificationReport] describing which assets were certified and which
+    /// configurations could not be applied, such as patterns that matched no asset or
+    /// encodings that were configured but not provided.
+    ///
     /// After performing this operation, one must set the canister's certified data (`ic_cdk::api::set_certified_data()`)
     /// to the new [root hash](AssetRouter::root_hash) of the tree.
     pub fn certify_assets<'path>(
         &mut self,
         assets: impl IntoIterator<Item = Asset<'content, 'path>>,
         asset_configs: impl IntoIterator<Item = AssetConfig>,
-    ) -> AssetCertificationResult {
-        let asset_configs: Vec<NormalizedAssetConfig> = asset_configs
-            .into_iter()
-            .map(TryInto::try_into)
-            .collect::<AssetCertificationResult<_>>()?;
+    ) -> AssetCertificationResult<CertificationReport> {
+        self.notify_hooks(|asset_router| asset_router.certify_assets_impl(assets, asset_configs))
+    }
 
-        let asset_map = assets
-            .into_iter()
-            .map(|asset| (asset.path.clone(), asset))
-            .collect::<HashMap<_, _>>();
+    fn certify_assets_impl<'path>(
+        &mut self,
+        assets: impl IntoIterator<Item = Asset<'content, 'path>>,
+        asset_configs: impl IntoIterator<Item = AssetConfig>,
+    ) -> AssetCertificationResult<CertificationReport> {
+        let asset_configs = NormalizedAssetConfigs::normalize_all(asset_configs)?;
+
+        let (mut asset_map, excluded_paths) = self.exclude_assets(assets, &asset_configs);
+        for asset in asset_map.values_mut() {
+            asset.content.cache_hash();
+        }
+
+        let mut report = self.certification_report(&asset_map, &asset_configs);
+        report.excluded_paths = excluded_paths;
+        if self.strict_asset_configs {
+            Self::validate_asset_configs(&asset_map, &asset_configs, &report)?;
+        }
+        self.validate_asset_sizes(&asset_map, false)?;
 
         for asset in asset_map.values() {
-            let asset_config = asset_configs.iter().find(|e| e.matches_asset(asset));
+            let asset_config = asset_configs.find(asset);
+            let additional_headers = self.additional_headers(asset, asset_config);
             for (encoding, postfix) in asset_config
                 .map(|e| match e {
                     NormalizedAssetConfig::File { encodings, .. } => encodings.clone(),
                     NormalizedAssetConfig::Pattern { encodings, .. } => encodings.clone(),
+                    NormalizedAssetConfig::ErrorPage { encodings, .. } => encodings.clone(),
                     _ => vec![],
                 })
                 .unwrap_or_default()
@@ -310,29 +1163,359 @@ impl<'content> AssetRouter<'content> {
                 let encoded_asset = asset_map.get(encoded_asset_path.as_str()).cloned();
                 if let Some(mut encoded_asset) = encoded_asset {
                     encoded_asset.url.clone_from(&asset.url);
+                    self.record_namespace(&encoded_asset.path, asset_config);
 
-                    self.certify_asset_impl(encoded_asset, asset_config, Some(encoding))?;
+                    self.certify_asset_impl(
+                        encoded_asset,
+                        asset_config,
+                        Some(encoding),
+                        additional_headers.clone(),
+                    )?;
                 }
             }
 
-            self.certify_asset_impl(asset.clone(), asset_config, None)?;
-        }
-
+            if let Some(asset_config) = asset_config {
+                for (image_format, variant_asset_path) in
+                    asset_config.image_variant_paths(&asset.path)
+                {
+                    if let Some(variant_asset) = asset_map.get(variant_asset_path.as_str()) {
+                        self.record_namespace(&variant_asset.path, Some(asset_config));
+                        self.certify_image_variant(
+                            variant_asset,
+                            asset,
+                            asset_config,
+                            image_format,
+                        )?;
+                    }
+                }
+            }
+
+            self.record_namespace(&asset.path, asset_config);
+            self.certify_asset_impl(asset.clone(), asset_config, None, additional_headers)?;
+        }
+
+        let sync_keys = self.sync_keys(&asset_map, &asset_configs);
+        self.asset_sync_keys.extend(sync_keys);
+
         for asset_config in asset_configs {
             if let NormalizedAssetConfig::Redirect {
                 from,
                 to,
                 kind,
+                append_query,
                 headers,
+                namespace,
             } = asset_config
             {
-                self.insert_redirect(from, to, kind, headers)?;
+                if let Some(namespace) = namespace {
+                    self.redirect_namespaces.insert(from.clone(), namespace);
+                }
+                self.insert_redirect(from, to, kind, append_query, headers)?;
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Generates the configured [WellKnownAssets], such as `/robots.txt`, and certifies them like
+    /// [certify_assets](AssetRouter::certify_assets). The assets are served as plain text.
+    ///
+    /// After performing this operation, one must set the canister's certified data (`ic_cdk::api::set_certified_data()`)
+    /// to the new [root hash](AssetRouter::root_hash) of the tree.
+    pub fn certify_well_known_assets(
+        &mut self,
+        well_known_assets: &WellKnownAssets,
+    ) -> AssetCertificationResult<CertificationReport> {
+        self.certify_assets(
+            well_known_assets.assets(),
+            well_known_assets.asset_configs(),
+        )
+    }
+
+    /// Certifies multiple assets like [certify_assets](AssetRouter::certify_assets), and additionally
+    /// certifies a stable alias for each asset with a content-hash filename, e.g. `app.js` for
+    /// `app-488df671.js`, see [HashedAssetManifest] for how content hashes are recognized.
+    ///
+    /// This allows HTML and other assets to reference stable paths, while the hashed assets can be
+    /// served with headers that allow them to be cached indefinitely. Alternative encodings of a
+    /// hashed asset are served on its stable alias as well. The stable aliases are served with the
+    /// content type configured for the hashed asset and the headers of the provided
+    /// [HashedAssetAliasConfig], instead of the headers configured for the hashed asset.
+    ///
+    /// A JSON manifest mapping stable paths to hashed paths is certified on the
+    /// [manifest path](HashedAssetAliasConfig::manifest_path). Stable aliases and the manifest that were
+    /// certified by a previous call are replaced. The stable aliases are deleted together with their
+    /// hashed asset by [delete_assets_by_path](AssetRouter::delete_assets_by_path), using their
+    /// stable path.
+    ///
+    /// After performing this operation, one must set the canister's certified data (`ic_cdk::api::set_certified_data()`)
+    /// to the new [root hash](AssetRouter::root_hash) of the tree.
+    pub fn certify_hashed_assets<'path>(
+        &mut self,
+        assets: impl IntoIterator<Item = Asset<'content, 'path>>,
+        asset_configs: impl IntoIterator<Item = AssetConfig>,
+        alias_config: HashedAssetAliasConfig,
+    ) -> AssetCertificationResult<CertificationReport> {
+        self.notify_hooks(|asset_router| {
+            asset_router.certify_hashed_assets_impl(assets, asset_configs, alias_config)
+        })
+    }
+
+    fn certify_hashed_assets_impl<'path>(
+        &mut self,
+        assets: impl IntoIterator<Item = Asset<'content, 'path>>,
+        asset_configs: impl IntoIterator<Item = AssetConfig>,
+        alias_config: HashedAssetAliasConfig,
+    ) -> AssetCertificationResult<CertificationReport> {
+        let assets = assets
+            .into_iter()
+            .map(|asset| self.precertified_assets.apply(asset))
+            .collect::<Vec<_>>();
+        let asset_configs = asset_configs.into_iter().collect::<Vec<_>>();
+
+        let mut report = self.certify_assets(assets.clone(), asset_configs.clone())?;
+
+        let assets = assets
+            .into_iter()
+            .filter(|asset| !report.excluded_paths.contains(&asset.path.to_string()))
+            .collect::<Vec<_>>();
+        let manifest = HashedAssetManifest::from_asset_paths(assets.iter().map(Asset::path));
+
+        let asset_configs = NormalizedAssetConfigs::normalize_all(asset_configs)?;
+        let asset_map = assets
+            .into_iter()
+            .map(|asset| (asset.path.clone(), asset))
+            .collect::<HashMap<_, _>>();
+
+        for (stable_path, hashed_path) in manifest.iter() {
+            let Some(asset) = asset_map.get(hashed_path) else {
+                continue;
+            };
+
+            let asset_config = asset_configs.find(asset);
+            let (content_type, encodings) = match asset_config {
+                Some(NormalizedAssetConfig::File {
+                    content_type,
+                    encodings,
+                    ..
+                })
+                | Some(NormalizedAssetConfig::Pattern {
+                    content_type,
+                    encodings,
+                    ..
+                })
+                | Some(NormalizedAssetConfig::ErrorPage {
+                    content_type,
+                    encodings,
+                    ..
+                }) => (content_type.clone(), encodings.clone()),
+                _ => (None, vec![]),
+            };
+            let headers = [
+                alias_config.headers.clone(),
+                self.additional_headers(asset, asset_config),
+            ]
+            .concat();
+
+            let stable_url = path_to_url(stable_path);
+            self.delete_responses_at_url(&stable_url);
+
+            for (encoding, postfix) in encodings {
+                let encoded_asset_path = format!("{}{}", hashed_path, postfix);
+                if let Some(encoded_asset) = asset_map.get(encoded_asset_path.as_str()) {
+                    let mut encoded_asset = SharedAsset::from(encoded_asset.clone());
+                    encoded_asset.url.clone_from(&stable_url);
+
+                    self.insert_static_asset(
+                        encoded_asset,
+                        content_type.clone(),
+                        headers.clone(),
+                        Some(encoding),
+                    )?;
+                }
+            }
+
+            let mut aliased_asset = SharedAsset::from(asset.clone());
+            aliased_asset.url = stable_url;
+            self.insert_static_asset(aliased_asset, content_type, headers, None)?;
+        }
+
+        let manifest_asset =
+            Asset::new(alias_config.manifest_path, manifest.to_json().into_bytes());
+        self.delete_responses_at_url(&manifest_asset.url);
+        self.asset_sync_keys.insert(
+            manifest_asset.path.to_string(),
+            sync_key(manifest_asset.content.hash(None), vec![]),
+        );
+        report.certified_paths.push(manifest_asset.path.to_string());
+        report.certified_paths.sort();
+        self.insert_static_asset(
+            SharedAsset::from(manifest_asset),
+            Some("application/json".to_string()),
+            alias_config.headers,
+            None,
+        )?;
+
+        Ok(report)
+    }
+
+    /// Certifies the asset that is served for the provided request URL, if it was recorded
+    /// for certification on demand, see [with_lazy_certification](AssetRouter::with_lazy_certification).
+    /// The asset is certified for all of its encodings and aliases.
+    ///
+    /// Returns `true` if an asset was certified, or `false` if no asset is pending certification
+    /// for the provided URL.
+    ///
+    /// After performing this operation, one must set the canister's certified data (`ic_cdk::api::set_certified_data()`)
+    /// to the new [root hash](AssetRouter::root_hash) of the tree.
+    pub fn certify_pending_asset(&mut self, request_url: &str) -> AssetCertificationResult<bool> {
+        self.notify_hooks(|asset_router| asset_router.certify_pending_asset_impl(request_url))
+    }
+
+    fn certify_pending_asset_impl(&mut self, request_url: &str) -> AssetCertificationResult<bool> {
+        let Some(asset_url) = self.pending_urls.get(request_url).cloned() else {
+            return Ok(false);
+        };
+
+        for pending_asset in self.remove_pending_assets(&asset_url) {
+            self.certify_asset_responses(
+                pending_asset.asset,
+                pending_asset.asset_config.as_ref(),
+                pending_asset.encoding,
+                pending_asset.additional_headers,
+            )?;
+        }
+
+        Ok(true)
+    }
+
+    /// Begins uploading an asset with the provided path, whose content is provided chunk by chunk
+    /// over multiple calls to [append_chunk](AssetRouter::append_chunk), e.g. from multiple update
+    /// calls, and which is certified by [commit_asset](AssetRouter::commit_asset).
+    ///
+    /// The content is hashed while the chunks arrive and the chunks are stored as they are, so
+    /// the full content never has to be copied into a single buffer. Since the certification of
+    /// each chunk of a large asset includes the total length of the asset, the certification is
+    /// only inserted into the tree when the upload is committed, using the hashes that were
+    /// calculated while the chunks arrived.
+    ///
+    /// The asset is certified using the provided [AssetConfig] enum, like the assets certified by
+    /// [certify_assets](AssetRouter::certify_assets). Alternative encodings of the asset need to be
+    /// certified together with the asset, using [certify_assets](AssetRouter::certify_assets).
+    ///
+    /// Only one asset can be uploaded at a time, an upload that was begun earlier and has not been
+    /// committed yet is discarded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_asset_certification::{AssetConfig, AssetRouter};
+    ///
+    /// let mut asset_router = AssetRouter::default();
+    ///
+    /// asset_router
+    ///     .begin_asset(
+    ///         "app.js",
+    ///         vec![AssetConfig::File {
+    ///             path: "app.js".to_string(),
+    ///             content_type: Some("text/javascript".to_string()),
+    ///             headers: vec![],
+    ///             fallback_for: vec![],
+    ///             aliased_by: vec![],
+    ///             encodings: vec![],
+    ///             image_variants: vec![],
+    ///         }],
+    ///     )
+    ///     .unwrap();
+    /// asset_router.append_chunk(b"console.log(".as_slice()).unwrap();
+    /// asset_router.append_chunk(b"'Hello World!');".as_slice()).unwrap();
+    /// let report = asset_router.commit_asset().unwrap();
+    ///
+    /// assert_eq!(report.certified_paths, vec!["app.js"]);
+    /// ```
+    pub fn begin_asset(
+        &mut self,
+        path: impl Into<String>,
+        asset_configs: impl IntoIterator<Item = AssetConfig>,
+    ) -> AssetCertificationResult {
+        let asset_configs = asset_configs.into_iter().collect::<Vec<_>>();
+        NormalizedAssetConfig::normalize_all(asset_configs.clone())?;
+
+        self.asset_upload = Some(AssetUpload::new(path.into(), asset_configs));
+
+        Ok(())
+    }
+
+    /// Appends a chunk to the content of the asset that is being uploaded, see
+    /// [begin_asset](AssetRouter::begin_asset).
+    ///
+    /// Returns a [NoAssetUploadInProgress](AssetCertificationError::NoAssetUploadInProgress) error
+    /// if no upload was begun. Returns an [AssetTooLarge](AssetCertificationError::AssetTooLarge)
+    /// error if the chunk would increase the size of the asset beyond the
+    /// [maximum asset size](AssetRouter::with_max_asset_size), or a
+    /// [TotalAssetSizeExceeded](AssetCertificationError::TotalAssetSizeExceeded) error if it would
+    /// increase the total size of all assets beyond the
+    /// [maximum total asset size](AssetRouter::with_max_total_asset_size).
+    pub fn append_chunk(&mut self, chunk: impl Into<Vec<u8>>) -> AssetCertificationResult {
+        let Some(asset_upload) = self.asset_upload.as_mut() else {
+            return Err(AssetCertificationError::NoAssetUploadInProgress);
+        };
+
+        let chunk = chunk.into();
+        let size = asset_upload.len().saturating_add(chunk.len());
+        if let Some(max_size) = self.max_asset_size {
+            if size > max_size {
+                return Err(AssetCertificationError::AssetTooLarge {
+                    asset_path: asset_upload.path.clone(),
+                    size,
+                    max_size,
+                });
+            }
+        }
+        if let Some(max_total_size) = self.max_total_asset_size {
+            // the uploaded asset replaces a certified asset with the same path
+            let total_size = self
+                .asset_sizes
+                .total_size
+                .saturating_sub(self.asset_sizes.size(&asset_upload.path))
+                .saturating_add(size);
+            if total_size > max_total_size {
+                return Err(AssetCertificationError::TotalAssetSizeExceeded {
+                    total_size,
+                    max_total_size,
+                });
             }
         }
 
+        asset_upload.append_chunk(chunk);
+
         Ok(())
     }
 
+    /// Certifies the asset that is being uploaded and inserts it into the router, see
+    /// [begin_asset](AssetRouter::begin_asset).
+    ///
+    /// Returns a [CertificationReport] like [certify_assets](AssetRouter::certify_assets), or a
+    /// [NoAssetUploadInProgress](AssetCertificationError::NoAssetUploadInProgress) error if no
+    /// upload was begun.
+    ///
+    /// After performing this operation, one must set the canister's certified data (`ic_cdk::api::set_certified_data()`)
+    /// to the new [root hash](AssetRouter::root_hash) of the tree.
+    pub fn commit_asset(&mut self) -> AssetCertificationResult<CertificationReport> {
+        let Some(asset_upload) = self.asset_upload.take() else {
+            return Err(AssetCertificationError::NoAssetUploadInProgress);
+        };
+
+        let asset_configs = asset_upload.asset_configs.clone();
+        let asset = Asset {
+            url: Cow::Owned(path_to_url(&asset_upload.path)),
+            path: Cow::Owned(asset_upload.path.clone()),
+            content: AssetBody::Chunked(Arc::new(asset_upload.finish())),
+        };
+
+        self.certify_assets(vec![asset], asset_configs)
+    }
+
     /// Deletes multiple assets from the router, including any certification for those assets.
     ///
     /// Depending on the configuration provided to the [certify_assets](AssetRouter::certify_assets) function,
@@ -346,23 +1529,27 @@ impl<'content> AssetRouter<'content> {
         assets: impl IntoIterator<Item = Asset<'content, 'path>>,
         asset_configs: impl IntoIterator<Item = AssetConfig>,
     ) -> AssetCertificationResult {
-        let asset_configs: Vec<NormalizedAssetConfig> = asset_configs
-            .into_iter()
-            .map(TryInto::try_into)
-            .collect::<AssetCertificationResult<_>>()?;
+        self.notify_hooks(|asset_router| asset_router.delete_assets_impl(assets, asset_configs))
+    }
 
-        let asset_map = assets
-            .into_iter()
-            .map(|asset| (asset.path.clone(), asset))
-            .collect::<HashMap<_, _>>();
+    fn delete_assets_impl<'path>(
+        &mut self,
+        assets: impl IntoIterator<Item = Asset<'content, 'path>>,
+        asset_configs: impl IntoIterator<Item = AssetConfig>,
+    ) -> AssetCertificationResult {
+        let asset_configs = NormalizedAssetConfigs::normalize_all(asset_configs)?;
+
+        let (asset_map, _) = self.exclude_assets(assets, &asset_configs);
 
         for asset in asset_map.values() {
-            let asset_config = asset_configs.iter().find(|e| e.matches_asset(asset));
+            let asset_config = asset_configs.find(asset);
+            let additional_headers = self.additional_headers(asset, asset_config);
 
             for (encoding, postfix) in asset_config
                 .map(|e| match e {
                     NormalizedAssetConfig::File { encodings, .. } => encodings.clone(),
                     NormalizedAssetConfig::Pattern { encodings, .. } => encodings.clone(),
+                    NormalizedAssetConfig::ErrorPage { encodings, .. } => encodings.clone(),
                     _ => vec![],
                 })
                 .unwrap_or_default()
@@ -373,22 +1560,168 @@ impl<'content> AssetRouter<'content> {
                 if let Some(mut encoded_asset) = encoded_asset {
                     encoded_asset.url.clone_from(&asset.url);
 
-                    self.delete_asset_impl(encoded_asset, asset_config, Some(encoding))?;
+                    self.delete_asset_impl(
+                        encoded_asset,
+                        asset_config,
+                        Some(encoding),
+                        additional_headers.clone(),
+                    )?;
+                }
+            }
+
+            if let Some(asset_config) = asset_config {
+                for (image_format, _) in asset_config.image_variant_paths(&asset.path) {
+                    self.delete_image_variant(&asset.url, asset_config, image_format);
+                }
+            }
+
+            self.delete_asset_impl(asset.clone(), asset_config, None, additional_headers)?;
+            self.asset_sync_keys.remove(asset.path.as_ref());
+            self.asset_namespaces.remove(asset.path.as_ref());
+        }
+
+        for asset_config in asset_configs {
+            if let NormalizedAssetConfig::Redirect {
+                from,
+                to,
+                kind,
+                append_query,
+                headers,
+                ..
+            } = asset_config
+            {
+                self.redirect_namespaces.remove(&from);
+                self.delete_redirect(from, to, kind, append_query, headers)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Synchronizes the router with the provided assets, only re-certifying assets whose content or configuration
+    /// has changed.
+    ///
+    /// The SHA-256 hash of each asset's content, together with a hash of the configuration that applies to it,
+    /// such as its content type, headers, fallbacks, aliases, encodings and image variants, is compared with the
+    /// hashes of the asset that is currently certified for the same asset path. Assets with matching hashes are
+    /// left untouched, new or changed assets are certified using the provided [AssetConfig] enum and any
+    /// previously certified assets that are not included in `assets` are deleted, so that after this operation
+    /// the router contains exactly the provided assets. Redirects are replaced with the redirects contained in
+    /// `asset_configs`.
+    ///
+    /// The content of each asset is hashed once, and the hash is reused when the asset is certified. Assets with
+    /// [precertified hashes](AssetRouter::with_precertified_assets) or an [AssetReader] that provides the
+    /// [hash](AssetReader::sha256) of its content are not hashed at all.
+    ///
+    /// After performing this operation, one must set the canister's certified data (`ic_cdk::api::set_certified_data()`)
+    /// to the new [root hash](AssetRouter::root_hash) of the tree.
+    pub fn sync_assets<'path>(
+        &mut self,
+        assets: impl IntoIterator<Item = Asset<'content, 'path>>,
+        asset_configs: impl IntoIterator<Item = AssetConfig>,
+    ) -> AssetCertificationResult {
+        self.notify_hooks(|asset_router| asset_router.sync_assets_impl(assets, asset_configs))
+    }
+
+    fn sync_assets_impl<'path>(
+        &mut self,
+        assets: impl IntoIterator<Item = Asset<'content, 'path>>,
+        asset_configs: impl IntoIterator<Item = AssetConfig>,
+    ) -> AssetCertificationResult {
+        let asset_configs = NormalizedAssetConfigs::normalize_all(asset_configs)?;
+
+        let (mut asset_map, _) = self.exclude_assets(assets, &asset_configs);
+        for asset in asset_map.values_mut() {
+            asset.content.cache_hash();
+        }
+
+        if self.strict_asset_configs {
+            let report = self.certification_report(&asset_map, &asset_configs);
+            Self::validate_asset_configs(&asset_map, &asset_configs, &report)?;
+        }
+        self.validate_asset_sizes(&asset_map, true)?;
+
+        let (unchanged_sync_keys, changed_sync_keys): (HashMap<_, _>, HashMap<_, _>) = self
+            .sync_keys(&asset_map, &asset_configs)
+            .into_iter()
+            .partition(|(asset_path, sync_key)| {
+                self.asset_sync_keys.get(asset_path) == Some(sync_key)
+            });
+        self.retain_responses(&unchanged_sync_keys.into_keys().collect());
+
+        for asset in asset_map.values() {
+            let asset_config = asset_configs.find(asset);
+            let additional_headers = self.additional_headers(asset, asset_config);
+            for (encoding, postfix) in asset_config
+                .map(|e| match e {
+                    NormalizedAssetConfig::File { encodings, .. } => encodings.clone(),
+                    NormalizedAssetConfig::Pattern { encodings, .. } => encodings.clone(),
+                    NormalizedAssetConfig::ErrorPage { encodings, .. } => encodings.clone(),
+                    _ => vec![],
+                })
+                .unwrap_or_default()
+            {
+                let encoded_asset_path = format!("{}{}", asset.path, postfix);
+                if !changed_sync_keys.contains_key(&encoded_asset_path) {
+                    continue;
+                }
+
+                let encoded_asset = asset_map.get(encoded_asset_path.as_str()).cloned();
+                if let Some(mut encoded_asset) = encoded_asset {
+                    encoded_asset.url.clone_from(&asset.url);
+                    self.record_namespace(&encoded_asset.path, asset_config);
+
+                    self.certify_asset_impl(
+                        encoded_asset,
+                        asset_config,
+                        Some(encoding),
+                        additional_headers.clone(),
+                    )?;
+                }
+            }
+
+            if let Some(asset_config) = asset_config {
+                for (image_format, variant_asset_path) in
+                    asset_config.image_variant_paths(&asset.path)
+                {
+                    if !changed_sync_keys.contains_key(&variant_asset_path) {
+                        continue;
+                    }
+
+                    if let Some(variant_asset) = asset_map.get(variant_asset_path.as_str()) {
+                        self.record_namespace(&variant_asset.path, Some(asset_config));
+                        self.certify_image_variant(
+                            variant_asset,
+                            asset,
+                            asset_config,
+                            image_format,
+                        )?;
+                    }
                 }
             }
 
-            self.delete_asset_impl(asset.clone(), asset_config, None)?;
+            if changed_sync_keys.contains_key(asset.path.as_ref()) {
+                self.record_namespace(&asset.path, asset_config);
+                self.certify_asset_impl(asset.clone(), asset_config, None, additional_headers)?;
+            }
         }
 
+        self.asset_sync_keys.extend(changed_sync_keys);
+
         for asset_config in asset_configs {
             if let NormalizedAssetConfig::Redirect {
                 from,
                 to,
                 kind,
+                append_query,
                 headers,
+                namespace,
             } = asset_config
             {
-                self.delete_redirect(from, to, kind, headers)?;
+                if let Some(namespace) = namespace {
+                    self.redirect_namespaces.insert(from.clone(), namespace);
+                }
+                self.insert_redirect(from, to, kind, append_query, headers)?;
             }
         }
 
@@ -402,6 +1735,10 @@ impl<'content> AssetRouter<'content> {
     /// for example if the `alias` configuration is used. If `alias` paths are not passed to this function,
     /// they will not be deleted.
     ///
+    /// Assets that are pending certification on demand, see
+    /// [with_lazy_certification](AssetRouter::with_lazy_certification), are deleted along with all
+    /// of their aliases, so they can no longer be certified through one of their aliases.
+    ///
     /// If multiple encodings exist for a path, all encodings will be deleted.
     ///
     /// Fallbacks are also not deleted, to delete them, use the
@@ -412,12 +1749,39 @@ impl<'content> AssetRouter<'content> {
     pub fn delete_assets_by_path<'path>(
         &mut self,
         asset_paths: impl IntoIterator<Item = &'path str>,
+    ) {
+        self.notify_hooks(|asset_router| asset_router.delete_assets_by_path_impl(asset_paths))
+    }
+
+    fn delete_assets_by_path_impl<'path>(
+        &mut self,
+        asset_paths: impl IntoIterator<Item = &'path str>,
     ) {
         for asset_path in asset_paths {
-            self.responses
-                .remove(&RequestKey::new(asset_path, None, None));
+            // the aliases of a pending asset would certify it again on demand
+            if let Some(asset_url) = self.pending_urls.get(asset_path).cloned() {
+                self.remove_pending_assets(&asset_url);
+            }
+            if let Some(response) = self
+                .responses
+                .remove(&RequestKey::new(asset_path, None, None))
+            {
+                self.asset_sizes.remove_response(&response);
+                self.forget_asset_sync_key(&response);
+            }
+            // image variants share the exact path of the asset, so they are deleted from the tree as well
+            let asset_sizes = &mut self.asset_sizes;
+            self.image_variant_responses
+                .retain(|(_, request_key), response| {
+                    let should_retain = request_key.path != asset_path;
+                    if !should_retain {
+                        asset_sizes.remove_response(response);
+                    }
+
+                    should_retain
+                });
             self.tree
-                .borrow_mut()
+                .write_tree()
                 .delete_by_path(&HttpCertificationPath::exact(asset_path));
         }
     }
@@ -432,12 +1796,58 @@ impl<'content> AssetRouter<'content> {
     pub fn delete_fallback_assets_by_path<'path>(
         &mut self,
         asset_paths: impl IntoIterator<Item = &'path str>,
+    ) {
+        self.notify_hooks(|asset_router| {
+            asset_router.delete_fallback_assets_by_path_impl(asset_paths)
+        })
+    }
+
+    fn delete_fallback_assets_by_path_impl<'path>(
+        &mut self,
+        asset_paths: impl IntoIterator<Item = &'path str>,
     ) {
         for asset_path in asset_paths {
-            self.fallback_responses
-                .remove(&RequestKey::new(asset_path, None, None));
+            // glob scopes share the wildcard path of their prefix with other fallbacks
+            if is_glob_scope(asset_path) {
+                let mut tree = self.tree.write_tree();
+                let asset_sizes = &mut self.asset_sizes;
+                self.fallback_responses.retain(|request_key, response| {
+                    let should_retain = request_key.path != asset_path;
+                    if !should_retain {
+                        tree.delete(&response.tree_entry);
+                        asset_sizes.remove_response(response);
+                    }
+
+                    should_retain
+                });
+                drop(tree);
+                self.retain_shadowed_fallbacks(|request_key, _| request_key.path != asset_path);
+                self.forget_glob_scope_matcher(asset_path);
+                continue;
+            }
+
+            self.retain_shadowed_fallbacks(|request_key, _| request_key.path != asset_path);
+
+            if let Some(response) = self
+                .fallback_responses
+                .remove(&RequestKey::new(asset_path, None, None))
+            {
+                self.asset_sizes.remove_response(&response);
+                self.forget_asset_sync_key(&response);
+            }
+            // error pages share the wildcard path of the scope, so they are deleted from the tree as well
+            for error_responses in self.error_responses.values_mut() {
+                error_responses.retain(|key, response| {
+                    let should_retain = key.path != asset_path;
+                    if !should_retain {
+                        self.asset_sizes.remove_response(response);
+                    }
+
+                    should_retain
+                });
+            }
             self.tree
-                .borrow_mut()
+                .write_tree()
                 .delete_by_path(&HttpCertificationPath::wildcard(asset_path));
         }
     }
@@ -447,2869 +1857,8023 @@ impl<'content> AssetRouter<'content> {
     /// After performing this operation, one must set the canister's certified data (`ic_cdk::api::set_certified_data()`)
     /// to the new [root hash](AssetRouter::root_hash) of the tree.
     pub fn delete_all_assets(&mut self) {
+        self.notify_hooks(|asset_router| asset_router.delete_all_assets_impl())
+    }
+
+    fn delete_all_assets_impl(&mut self) {
         self.responses.clear();
         self.fallback_responses.clear();
-        self.tree.borrow_mut().clear();
+        self.shadowed_fallback_responses.clear();
+        self.image_variant_responses.clear();
+        self.fallback_scope_matchers.clear();
+        self.error_responses.clear();
+        self.asset_sync_keys.clear();
+        self.asset_sizes.clear();
+        self.pending_assets.clear();
+        self.pending_urls.clear();
+        self.pending_url_aliases.clear();
+        self.asset_namespaces.clear();
+        self.redirect_namespaces.clear();
+        self.tree.write_tree().clear();
     }
 
-    /// Returns the root hash of the underlying
-    /// [HttpCertificationTree](ic_http_certification::HttpCertificationTree).
-    pub fn root_hash(&self) -> Hash {
-        self.tree.borrow().root_hash()
+    /// Removes all assets, fallbacks and redirects from the router, including any certification for those responses.
+    ///
+    /// Unlike [delete_all_assets](AssetRouter::delete_all_assets), this function only deletes the tree entries that
+    /// were inserted by this router. Any other entries in a tree shared with the router via
+    /// [with_tree](AssetRouter::with_tree) are left untouched.
+    ///
+    /// After performing this operation, one must set the canister's certified data (`ic_cdk::api::set_certified_data()`)
+    /// to the new [root hash](AssetRouter::root_hash) of the tree.
+    pub fn clear(&mut self) {
+        self.notify_hooks(|asset_router| asset_router.clear_impl())
     }
 
-    fn get_asset_for_request<'a>(
-        &self,
-        req_path: &'a str,
-        preferred_encodings: Vec<&'a str>,
-        maybe_range_begin: Option<usize>,
-    ) -> AssetCertificationResult<&CertifiedAssetResponse<'content>> {
-        if let Some(response) =
-            self.get_encoded_asset(&preferred_encodings, req_path, maybe_range_begin)
-        {
-            return Ok(response);
+    fn clear_impl(&mut self) {
+        let mut tree = self.tree.write_tree();
+
+        for (_, response) in self.responses.drain() {
+            tree.delete(&response.tree_entry);
         }
 
-        if let Some(response) =
-            self.responses
-                .get(&RequestKey::new(req_path, None, maybe_range_begin))
-        {
-            if response.response.body().len() > ASSET_CHUNK_SIZE {
-                if let Some(first_chunk_response) =
-                    self.responses
-                        .get(&RequestKey::new(req_path, None, Some(0)))
-                {
-                    return Ok(first_chunk_response);
-                }
-            } else {
-                return Ok(response);
-            }
+        for (_, response) in self.fallback_responses.drain() {
+            tree.delete(&response.tree_entry);
         }
 
-        let mut url_scopes = req_path.split('/').collect::<Vec<_>>();
-        url_scopes.pop();
+        for (_, response) in self.image_variant_responses.drain() {
+            tree.delete(&response.tree_entry);
+        }
 
-        while !url_scopes.is_empty() {
-            let mut scope = url_scopes.join("/");
-            scope.push('/');
+        for (_, response) in self.error_responses.drain().flat_map(|(_, e)| e) {
+            tree.delete(&response.tree_entry);
+        }
 
-            if let Some(response) = self.get_encoded_fallback_asset(&preferred_encodings, &scope) {
-                return Ok(response);
-            }
+        self.shadowed_fallback_responses.clear();
+        self.fallback_scope_matchers.clear();
+        self.asset_sync_keys.clear();
+        self.asset_sizes.clear();
+        self.pending_assets.clear();
+        self.pending_urls.clear();
+        self.pending_url_aliases.clear();
+        self.asset_namespaces.clear();
+        self.redirect_namespaces.clear();
+    }
 
-            if let Some(response) = self
-                .fallback_responses
-                .get(&RequestKey::new(&scope, None, None))
-            {
-                return Ok(response);
-            }
+    /// Returns all redirects that are currently certified by the router, ordered by the path
+    /// that is redirected, see [Redirect](AssetConfig::Redirect).
+    ///
+    /// This allows canisters to audit the certified redirects, e.g. to reconcile them with
+    /// the configuration of an admin tool, without keeping track of them separately.
+    pub fn get_redirects(&self) -> Vec<CertifiedRedirect> {
+        let mut redirects = self
+            .responses
+            .iter()
+            .filter(|(_, response)| response.asset_path.is_none())
+            .filter_map(|(request_key, response)| {
+                let status_code = response.response.status_code;
+                let kind = AssetRedirectKind::from_status_code(status_code)?;
+                let to = response
+                    .response
+                    .headers
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(LOCATION_HEADER_NAME))
+                    .map(|(_, value)| value.clone())?;
+
+                Some(CertifiedRedirect {
+                    from: request_key.path.clone(),
+                    to,
+                    kind,
+                    status_code,
+                    append_query: response.append_query,
+                    namespace: self.redirect_namespaces.get(&request_key.path).cloned(),
+                })
+            })
+            .collect::<Vec<_>>();
+        redirects.sort_by(|a, b| a.from.cmp(&b.from));
 
-            scope.pop();
+        redirects
+    }
 
-            if let Some(response) = self.get_encoded_fallback_asset(&preferred_encodings, &scope) {
-                return Ok(response);
-            }
+    /// Returns the paths of all assets that belong to the [Namespace](AssetConfig::Namespace)
+    /// with the provided name, including their alternative encodings, ordered by path.
+    pub fn get_namespace_asset_paths(&self, name: &str) -> Vec<&str> {
+        let mut asset_paths = self
+            .asset_namespaces
+            .iter()
+            .filter(|(_, namespace)| *namespace == name)
+            .map(|(asset_path, _)| asset_path.as_str())
+            .collect::<Vec<_>>();
+        asset_paths.sort_unstable();
 
-            if let Some(response) = self
-                .fallback_responses
-                .get(&RequestKey::new(&scope, None, None))
-            {
-                return Ok(response);
+        asset_paths
+    }
+
+    /// Deletes all assets and redirects that belong to the [Namespace](AssetConfig::Namespace)
+    /// with the provided name, including any certification for those responses.
+    ///
+    /// Responses generated from the assets in the namespace are deleted for all of their
+    /// encodings, aliases, fallbacks and error pages, without having to provide the assets
+    /// or their configuration again.
+    ///
+    /// After performing this operation, one must set the canister's certified data (`ic_cdk::api::set_certified_data()`)
+    /// to the new [root hash](AssetRouter::root_hash) of the tree.
+    pub fn delete_namespace(&mut self, name: &str) {
+        self.notify_hooks(|asset_router| asset_router.delete_namespace_impl(name))
+    }
+
+    fn delete_namespace_impl(&mut self, name: &str) {
+        let asset_paths = self
+            .asset_namespaces
+            .iter()
+            .filter(|(_, namespace)| *namespace == name)
+            .map(|(asset_path, _)| asset_path.clone())
+            .collect::<HashSet<_>>();
+        let redirect_urls = self
+            .redirect_namespaces
+            .iter()
+            .filter(|(_, namespace)| *namespace == name)
+            .map(|(url, _)| url.clone())
+            .collect::<HashSet<_>>();
+
+        let mut tree = self.tree.write_tree();
+        let asset_sizes = &mut self.asset_sizes;
+        let mut should_retain = |key: &RequestKey, response: &CertifiedAssetResponse<'content>| {
+            let should_delete = match &response.asset_path {
+                Some(asset_path) => asset_paths.contains(asset_path),
+                None => redirect_urls.contains(&key.path),
+            };
+            if should_delete {
+                tree.delete(&response.tree_entry);
+                asset_sizes.remove_response(response);
             }
 
-            url_scopes.pop();
+            !should_delete
+        };
+
+        self.responses
+            .retain(|key, response| should_retain(key, response));
+        self.fallback_responses
+            .retain(|key, response| should_retain(key, response));
+        self.image_variant_responses
+            .retain(|(_, key), response| should_retain(key, response));
+        for error_responses in self.error_responses.values_mut() {
+            error_responses.retain(|key, response| should_retain(key, response));
         }
-        Err(AssetCertificationError::NoAssetMatchingRequestUrl {
-            request_url: req_path.to_string(),
-        })
+        drop(tree);
+        self.retain_shadowed_fallbacks(|_, response| {
+            !response
+                .asset_path
+                .as_ref()
+                .is_some_and(|asset_path| asset_paths.contains(asset_path))
+        });
+        self.forget_unused_glob_scope_matchers();
+
+        self.asset_sync_keys
+            .retain(|asset_path, _| !asset_paths.contains(asset_path));
+        self.pending_assets.retain(|_, pending_assets| {
+            pending_assets.retain(|pending_asset| {
+                let should_retain = !asset_paths.contains(&pending_asset.asset.path);
+                if !should_retain {
+                    self.asset_sizes.remove(&pending_asset.asset.path);
+                }
+
+                should_retain
+            });
+            !pending_assets.is_empty()
+        });
+        self.pending_urls
+            .retain(|_, asset_url| self.pending_assets.contains_key(asset_url));
+        self.pending_url_aliases
+            .retain(|asset_url, _| self.pending_assets.contains_key(asset_url));
+        self.asset_namespaces
+            .retain(|_, namespace| namespace != name);
+        self.redirect_namespaces
+            .retain(|_, namespace| namespace != name);
     }
 
-    fn certify_asset_impl<'path>(
-        &mut self,
-        asset: Asset<'content, 'path>,
-        asset_config: Option<&NormalizedAssetConfig>,
-        encoding: Option<AssetEncoding>,
-    ) -> AssetCertificationResult {
-        match asset_config {
-            Some(NormalizedAssetConfig::Pattern {
-                content_type,
-                headers,
-                ..
-            }) => {
-                self.insert_static_asset(asset, content_type.clone(), headers.clone(), encoding)?;
+    /// Returns the root hash of the underlying
+    /// [HttpCertificationTree](ic_http_certification::HttpCertificationTree).
+    pub fn root_hash(&self) -> Hash {
+        self.tree.read_tree().root_hash()
+    }
+
+    /// Runs the provided operation and then the [root hash change hook](AssetRouter::on_root_hash_change),
+    /// if the operation changed the root hash, and the [asset observer](AssetRouter::with_asset_observer),
+    /// if the operation changed the certified assets. The hooks are taken for the duration of the
+    /// operation, so operations that are built on top of other operations only run them once.
+    fn notify_hooks<R>(&mut self, operation: impl FnOnce(&mut Self) -> R) -> R {
+        let mut root_hash_change_hook = self.root_hash_change_hook.take();
+        let mut asset_observer = self.asset_observer.take();
+        if root_hash_change_hook.is_none() && asset_observer.is_none() {
+            return operation(self);
+        }
+
+        let previous_root_hash = self.root_hash();
+        let previous_assets = asset_observer
+            .as_ref()
+            .map(|_| self.certified_assets())
+            .unwrap_or_default();
+        let result = operation(self);
+
+        if let Some(RootHashChangeHook(hook)) = &mut root_hash_change_hook {
+            let root_hash = self.root_hash();
+            if root_hash != previous_root_hash {
+                hook(&root_hash);
             }
-            Some(NormalizedAssetConfig::File {
-                content_type,
-                headers,
-                fallback_for,
-                aliased_by,
-                ..
-            }) => {
-                self.insert_static_asset(
-                    asset.clone(),
-                    content_type.clone(),
-                    headers.clone(),
-                    encoding,
-                )?;
+        }
 
-                for fallback_for in fallback_for.iter() {
-                    self.insert_fallback_asset(
-                        asset.clone(),
-                        content_type.clone(),
-                        headers.clone(),
-                        fallback_for.clone(),
-                        encoding,
-                    )?;
+        if let Some(AssetObserverHook(observer)) = &mut asset_observer {
+            let assets = self.certified_assets();
+            for asset_path in previous_assets.keys() {
+                if !assets.contains_key(asset_path) {
+                    observer.on_deleted(asset_path);
+                }
+            }
+            for (asset_path, asset) in assets.iter() {
+                if previous_assets.get(asset_path) != Some(asset) {
+                    let (encoding, _) = asset;
+                    observer.on_certified(asset_path, encoding.as_deref());
                 }
+            }
+        }
 
-                for aliased_by in aliased_by.iter() {
-                    let mut aliased_asset = asset.clone();
-                    aliased_asset.url = Cow::Owned(aliased_by.clone());
+        self.root_hash_change_hook = root_hash_change_hook;
+        self.asset_observer = asset_observer;
+        result
+    }
 
-                    self.insert_static_asset(
-                        aliased_asset,
-                        content_type.clone(),
-                        headers.clone(),
-                        encoding,
-                    )?;
+    /// The encoding and the content of all certified assets, indexed by their path. Encoded assets
+    /// are also served on their own URL without an encoding, so the encoding is taken from the
+    /// responses that are served with one.
+    fn certified_assets(&self) -> BTreeMap<String, (Option<String>, AssetBody<'content>)> {
+        let responses = self
+            .responses
+            .iter()
+            .chain(self.fallback_responses.iter())
+            .chain(
+                self.image_variant_responses
+                    .iter()
+                    .map(|((_, request_key), response)| (request_key, response)),
+            )
+            .chain(self.error_responses.values().flatten());
+
+        let mut certified_assets = BTreeMap::new();
+        for (request_key, response) in responses {
+            if let Some(asset_path) = &response.asset_path {
+                let (encoding, _) = certified_assets
+                    .entry(asset_path.clone())
+                    .or_insert_with(|| (None, response.response.body.clone()));
+                if encoding.is_none() {
+                    encoding.clone_from(&request_key.encoding);
                 }
             }
-            _ => {
-                self.insert_static_asset(asset, None, vec![], encoding)?;
-            }
         }
 
-        Ok(())
+        certified_assets
     }
 
-    fn delete_asset_impl<'path>(
-        &mut self,
-        asset: Asset<'content, 'path>,
+    fn additional_headers(
+        &self,
+        asset: &Asset,
         asset_config: Option<&NormalizedAssetConfig>,
-        encoding: Option<AssetEncoding>,
-    ) -> AssetCertificationResult {
-        match asset_config {
-            Some(NormalizedAssetConfig::Pattern {
-                content_type,
-                headers,
-                ..
-            }) => {
-                self.delete_static_asset(asset, content_type.clone(), headers.clone(), encoding)?;
-            }
-            Some(NormalizedAssetConfig::File {
-                content_type,
-                headers,
-                fallback_for,
-                aliased_by,
-                ..
-            }) => {
-                self.delete_static_asset(
-                    asset.clone(),
-                    content_type.clone(),
-                    headers.clone(),
-                    encoding,
-                )?;
+    ) -> Vec<(String, String)> {
+        let mut headers = self.integrity_headers(asset);
 
-                for fallback_for in fallback_for.iter() {
-                    self.delete_fallback_asset(
-                        asset.clone(),
-                        content_type.clone(),
-                        headers.clone(),
-                        fallback_for.clone(),
-                        encoding,
-                    )?;
-                }
+        if let Some(vary) = asset_config.and_then(NormalizedAssetConfig::vary_header) {
+            headers.push(("vary".to_string(), vary.to_string()));
+        }
 
-                for aliased_by in aliased_by.iter() {
-                    let mut aliased_asset = asset.clone();
-                    aliased_asset.url = Cow::Owned(aliased_by.clone());
+        headers
+    }
 
-                    self.delete_static_asset(
-                        aliased_asset,
-                        content_type.clone(),
-                        headers.clone(),
-                        encoding,
-                    )?;
+    /// Calculates the keys of the provided assets that [sync_assets](AssetRouter::sync_assets)
+    /// compares to skip assets that did not change, indexed by asset path.
+    ///
+    /// The key of an asset covers its content and the [fingerprint](NormalizedAssetConfig::fingerprint)
+    /// of its configuration. Encoded assets and image variants are certified with the configuration
+    /// of the asset they belong to, so their keys cover that configuration as well. If an
+    /// [integrity header](AssetRouter::with_integrity_header) is configured, the keys of encoded
+    /// assets also cover the content of the unencoded asset, whose digest they carry.
+    fn sync_keys<'path>(
+        &self,
+        asset_map: &HashMap<Cow<'path, str>, Asset<'content, 'path>>,
+        asset_configs: &NormalizedAssetConfigs,
+    ) -> HashMap<String, Hash> {
+        let mut config_fingerprints = HashMap::new();
+        let mut config_hashes = HashMap::<String, Vec<Hash>>::new();
+        for asset in asset_map.values() {
+            let Some(config_index) = asset_configs.position(asset) else {
+                continue;
+            };
+            let asset_config = &asset_configs[config_index];
+            let fingerprint = *config_fingerprints
+                .entry(config_index)
+                .or_insert_with(|| asset_config.fingerprint());
+
+            config_hashes
+                .entry(asset.path.to_string())
+                .or_default()
+                .push(fingerprint);
+            for (_, postfix) in asset_config.encodings() {
+                let encoded_asset_path = format!("{}{}", asset.path, postfix);
+                let encoded_asset_hashes = config_hashes.entry(encoded_asset_path).or_default();
+                encoded_asset_hashes.push(fingerprint);
+                if self.integrity_header.is_some() {
+                    encoded_asset_hashes.push(asset.content.hash(None));
                 }
             }
-            _ => {
-                self.delete_static_asset(asset, None, vec![], encoding)?;
+            for (_, variant_asset_path) in asset_config.image_variant_paths(&asset.path) {
+                config_hashes
+                    .entry(variant_asset_path)
+                    .or_default()
+                    .push(fingerprint);
             }
         }
 
-        Ok(())
+        asset_map
+            .iter()
+            .map(|(asset_path, asset)| {
+                let asset_config_hashes = config_hashes.remove(asset_path.as_ref());
+                (
+                    asset_path.to_string(),
+                    sync_key(
+                        asset.content.hash(None),
+                        asset_config_hashes.unwrap_or_default(),
+                    ),
+                )
+            })
+            .collect()
     }
 
-    fn insert_static_asset<'path>(
-        &mut self,
-        asset: Asset<'content, 'path>,
-        content_type: Option<String>,
-        additional_headers: Vec<(String, String)>,
-        encoding: Option<AssetEncoding>,
-    ) -> AssetCertificationResult<()> {
-        let asset_url = asset.url.to_string();
-        let total_length = asset.content.len();
+    fn integrity_headers(&self, asset: &Asset) -> Vec<(String, String)> {
+        self.integrity_header
+            .iter()
+            .map(|header_name| {
+                (
+                    header_name.clone(),
+                    subresource_integrity(asset.content.hash(None)),
+                )
+            })
+            .collect()
+    }
 
-        if total_length > ASSET_CHUNK_SIZE {
-            let mut range_begin = 0;
-            while range_begin < asset.content.len() {
-                let response = Self::prepare_static_asset(
-                    asset.clone(),
-                    content_type.clone(),
-                    additional_headers.clone(),
-                    encoding,
-                    Some(range_begin),
-                )?;
-                self.tree.borrow_mut().insert(&response.tree_entry);
-                self.responses.insert(
-                    RequestKey::new(&asset_url, encoding_str(encoding), Some(range_begin)),
-                    response,
-                );
-                range_begin += ASSET_CHUNK_SIZE;
-            }
-        }
+    /// Collects the provided assets by path, leaving out the assets that are excluded by the
+    /// first configuration that matches them. The paths of the excluded assets are returned
+    /// separately, ordered by path. The remaining assets carry their precertified hashes, see
+    /// [with_precertified_assets](AssetRouter::with_precertified_assets).
+    fn exclude_assets<'path>(
+        &self,
+        assets: impl IntoIterator<Item = Asset<'content, 'path>>,
+        asset_configs: &NormalizedAssetConfigs,
+    ) -> (
+        HashMap<Cow<'path, str>, Asset<'content, 'path>>,
+        Vec<String>,
+    ) {
+        let mut excluded_paths = vec![];
+        let asset_map = assets
+            .into_iter()
+            .filter(|asset| {
+                let is_excluded = asset_configs
+                    .find(asset)
+                    .is_some_and(|asset_config| asset_config.excludes_asset(asset));
+                if is_excluded {
+                    excluded_paths.push(asset.path.to_string());
+                }
 
-        let response =
-            Self::prepare_static_asset(asset, content_type, additional_headers, encoding, None)?;
+                !is_excluded
+            })
+            .map(|asset| {
+                let asset = self.precertified_assets.apply(asset);
+                (asset.path.clone(), asset)
+            })
+            .collect::<HashMap<_, _>>();
+        excluded_paths.sort();
 
-        self.tree.borrow_mut().insert(&response.tree_entry);
-        self.responses.insert(
-            RequestKey::new(&asset_url, encoding_str(encoding), None),
-            response,
-        );
-        Ok(())
+        (asset_map, excluded_paths)
     }
 
-    fn delete_static_asset<'path>(
-        &mut self,
-        asset: Asset<'content, 'path>,
-        content_type: Option<String>,
-        additional_headers: Vec<(String, String)>,
-        encoding: Option<AssetEncoding>,
-    ) -> AssetCertificationResult<()> {
-        let asset_url = asset.url.to_string();
-        let response =
-            Self::prepare_static_asset(asset, content_type, additional_headers, encoding, None)?;
+    fn certification_report<'path>(
+        &self,
+        asset_map: &HashMap<Cow<'path, str>, Asset<'content, 'path>>,
+        asset_configs: &NormalizedAssetConfigs,
+    ) -> CertificationReport {
+        let mut report = CertificationReport::default();
+        let mut applied_configs = vec![false; asset_configs.len()];
+        let mut encoded_asset_paths = HashSet::new();
+        let mut missing_encodings = vec![];
 
-        self.tree.borrow_mut().delete(&response.tree_entry);
-        self.responses
-            .remove(&RequestKey::new(&asset_url, encoding_str(encoding), None));
+        for asset in asset_map.values() {
+            let config_index = asset_configs.position(asset);
+            let Some(config_index) = config_index else {
+                continue;
+            };
+            applied_configs[config_index] = true;
+
+            let encodings = match &asset_configs[config_index] {
+                NormalizedAssetConfig::File { encodings, .. }
+                | NormalizedAssetConfig::Pattern { encodings, .. }
+                | NormalizedAssetConfig::ErrorPage { encodings, .. } => encodings.as_slice(),
+                NormalizedAssetConfig::Redirect { .. } => &[],
+            };
+            for (encoding, postfix) in encodings {
+                let encoded_asset_path = format!("{}{}", asset.path, postfix);
+                if asset_map.contains_key(encoded_asset_path.as_str()) {
+                    encoded_asset_paths.insert(encoded_asset_path);
+                } else {
+                    missing_encodings.push(MissingEncoding {
+                        path: asset.path.to_string(),
+                        encoding: *encoding,
+                        expected_path: encoded_asset_path,
+                    });
+                }
+            }
 
-        if response.response.body().len() > ASSET_CHUNK_SIZE {
-            // Delete also chunks.
-            let mut range_begin: usize = 0;
-            while range_begin < response.response.body().len() {
-                self.responses.remove(&RequestKey::new(
-                    &asset_url,
-                    encoding_str(encoding),
-                    Some(range_begin),
-                ));
-                range_begin += ASSET_CHUNK_SIZE;
+            // alternative image formats are optional and are listed like encoded assets
+            for (_, variant_asset_path) in
+                asset_configs[config_index].image_variant_paths(&asset.path)
+            {
+                if asset_map.contains_key(variant_asset_path.as_str()) {
+                    encoded_asset_paths.insert(variant_asset_path);
+                }
             }
         }
 
-        Ok(())
-    }
+        // encoded assets are expected to not have any alternative encodings of their own
+        report.missing_encodings = missing_encodings
+            .into_iter()
+            .filter(|missing_encoding| !encoded_asset_paths.contains(&missing_encoding.path))
+            .collect();
+        report
+            .missing_encodings
+            .sort_by(|a, b| a.expected_path.cmp(&b.expected_path));
 
-    fn prepare_static_asset<'path>(
-        asset: Asset<'content, 'path>,
-        content_type: Option<String>,
-        additional_headers: Vec<(String, String)>,
-        encoding: Option<AssetEncoding>,
-        range_begin: Option<usize>,
-    ) -> AssetCertificationResult<CertifiedAssetResponse<'content>> {
-        let asset_url = asset.url.to_string();
+        report.unmatched_configs = asset_configs
+            .iter()
+            .zip(applied_configs)
+            .filter(|(_, applied)| !applied)
+            .filter_map(|(asset_config, _)| asset_config.path_or_pattern())
+            .map(str::to_string)
+            .collect();
 
-        let (response, certification) = Self::prepare_asset_response_and_certification(
-            asset,
-            additional_headers,
-            content_type,
-            encoding,
-            range_begin,
-            None,
-        )?;
+        let mut url_asset_paths = HashMap::<String, BTreeSet<String>>::new();
+        for asset in asset_map.values() {
+            let asset_config = asset_configs.find(asset);
+            if asset_config.is_none() && !encoded_asset_paths.contains(asset.path.as_ref()) {
+                report.unconfigured_paths.push(asset.path.to_string());
+            }
 
-        let tree_entry =
-            HttpCertificationTreeEntry::new(HttpCertificationPath::exact(asset_url), certification);
+            let mut urls = match asset_config {
+                Some(NormalizedAssetConfig::ErrorPage { .. }) => continue,
+                Some(NormalizedAssetConfig::File { aliased_by, .. }) => {
+                    [vec![asset.url.to_string()], aliased_by.clone()].concat()
+                }
+                _ => vec![asset.url.to_string()],
+            };
+            for url in urls.clone() {
+                urls.extend(self.automatic_aliases(&url));
+            }
 
-        Ok(CertifiedAssetResponse {
-            response,
-            tree_entry,
-        })
-    }
-
-    fn insert_fallback_asset<'path>(
-        &mut self,
-        asset: Asset<'content, 'path>,
-        content_type: Option<String>,
-        additional_headers: Vec<(String, String)>,
-        fallback_for: AssetFallbackConfig,
-        encoding: Option<AssetEncoding>,
-    ) -> AssetCertificationResult<()> {
-        let response = Self::prepare_fallback_asset(
-            asset,
-            additional_headers,
-            content_type,
-            fallback_for.clone(),
-            encoding,
-        )?;
-
-        self.tree.borrow_mut().insert(&response.tree_entry);
-        self.fallback_responses.insert(
-            RequestKey::new(&fallback_for.scope, encoding_str(encoding), None),
-            response,
-        );
-        Ok(())
-    }
-
-    fn delete_fallback_asset<'path>(
-        &mut self,
-        asset: Asset<'content, 'path>,
-        content_type: Option<String>,
-        additional_headers: Vec<(String, String)>,
-        fallback_for: AssetFallbackConfig,
-        encoding: Option<AssetEncoding>,
-    ) -> AssetCertificationResult<()> {
-        let response = Self::prepare_fallback_asset(
-            asset,
-            additional_headers,
-            content_type,
-            fallback_for.clone(),
-            encoding,
-        )?;
+            for url in urls {
+                url_asset_paths
+                    .entry(url)
+                    .or_default()
+                    .insert(asset.path.to_string());
+            }
+        }
 
-        self.tree.borrow_mut().delete(&response.tree_entry);
-        self.fallback_responses.remove(&RequestKey::new(
-            &fallback_for.scope,
-            encoding_str(encoding),
-            None,
-        ));
-        Ok(())
-    }
+        for (url, asset_paths) in url_asset_paths.iter_mut() {
+            asset_paths.extend(
+                self.certified_asset_paths(url)
+                    .into_iter()
+                    .filter(|asset_path| !asset_map.contains_key(asset_path.as_str())),
+            );
+        }
 
-    fn prepare_fallback_asset<'path>(
-        asset: Asset<'content, 'path>,
-        additional_headers: Vec<(String, String)>,
-        content_type: Option<String>,
-        fallback_for: AssetFallbackConfig,
-        encoding: Option<AssetEncoding>,
-    ) -> AssetCertificationResult<CertifiedAssetResponse<'content>> {
-        let (response, certification) = Self::prepare_asset_response_and_certification(
-            asset,
-            additional_headers,
-            content_type,
-            encoding,
-            None,
-            fallback_for.status_code,
-        )?;
+        report.alias_collisions = url_asset_paths
+            .into_iter()
+            .filter(|(_, asset_paths)| asset_paths.len() > 1)
+            .map(|(url, asset_paths)| AliasCollision {
+                url,
+                asset_paths: asset_paths.into_iter().collect(),
+            })
+            .collect();
+        report.alias_collisions.sort_by(|a, b| a.url.cmp(&b.url));
 
-        let tree_entry = HttpCertificationTreeEntry::new(
-            HttpCertificationPath::wildcard(fallback_for.scope.clone()),
-            certification,
-        );
+        report.certified_paths = asset_map.keys().map(|path| path.to_string()).collect();
+        report.certified_paths.sort();
+        report.unconfigured_paths.sort();
 
-        Ok(CertifiedAssetResponse {
-            response,
-            tree_entry,
-        })
+        report
     }
 
-    fn insert_redirect(
-        &mut self,
-        from: String,
-        to: String,
-        kind: AssetRedirectKind,
-        additional_headers: Vec<(String, String)>,
-    ) -> AssetCertificationResult<()> {
-        let response = Self::prepare_redirect(from.clone(), to, kind, additional_headers)?;
+    /// Validates the provided assets against the
+    /// [maximum asset size](AssetRouter::with_max_asset_size) and the
+    /// [maximum total asset size](AssetRouter::with_max_total_asset_size), `replaces_all`
+    /// is set if the provided assets replace all previously certified assets.
+    fn validate_asset_sizes<'path>(
+        &self,
+        asset_map: &HashMap<Cow<'path, str>, Asset<'content, 'path>>,
+        replaces_all: bool,
+    ) -> AssetCertificationResult {
+        if let Some(max_size) = self.max_asset_size {
+            if let Some(asset) = asset_map
+                .values()
+                .filter(|asset| asset.content.len() > max_size)
+                .min_by_key(|asset| &asset.path)
+            {
+                return Err(AssetCertificationError::AssetTooLarge {
+                    asset_path: asset.path.to_string(),
+                    size: asset.content.len(),
+                    max_size,
+                });
+            }
+        }
 
-        self.tree.borrow_mut().insert(&response.tree_entry);
+        if let Some(max_total_size) = self.max_total_asset_size {
+            let mut total_size = if replaces_all {
+                0
+            } else {
+                self.asset_sizes.total_size
+            };
+            for (asset_path, asset) in asset_map {
+                // assets replace certified assets with the same path
+                if !replaces_all {
+                    total_size = total_size.saturating_sub(self.asset_sizes.size(asset_path));
+                }
+                total_size = total_size.saturating_add(asset.content.len());
+            }
 
-        self.responses
-            .insert(RequestKey::new(&from, None, None), response);
+            if total_size > max_total_size {
+                return Err(AssetCertificationError::TotalAssetSizeExceeded {
+                    total_size,
+                    max_total_size,
+                });
+            }
+        }
 
         Ok(())
     }
 
-    fn delete_redirect(
-        &mut self,
-        from: String,
-        to: String,
-        kind: AssetRedirectKind,
-        addtional_headers: Vec<(String, String)>,
-    ) -> AssetCertificationResult<()> {
-        let response = Self::prepare_redirect(from.clone(), to, kind, addtional_headers)?;
+    fn validate_asset_configs<'path>(
+        asset_map: &HashMap<Cow<'path, str>, Asset<'content, 'path>>,
+        asset_configs: &[NormalizedAssetConfig],
+        report: &CertificationReport,
+    ) -> AssetCertificationResult {
+        for asset_config in asset_configs {
+            if let NormalizedAssetConfig::Pattern { pattern, .. } = asset_config {
+                if !asset_map
+                    .values()
+                    .any(|asset| asset_config.matches_asset(asset))
+                {
+                    return Err(AssetCertificationError::NoAssetMatchingPattern {
+                        pattern: pattern.glob().glob().to_string(),
+                    });
+                }
+            }
+        }
 
-        self.tree.borrow_mut().delete(&response.tree_entry);
-        self.responses.remove(&RequestKey::new(&from, None, None));
+        if let Some(missing_encoding) = report.missing_encodings.first() {
+            return Err(AssetCertificationError::EncodedAssetNotFound {
+                asset_path: missing_encoding.path.clone(),
+                encoding: missing_encoding.encoding,
+                encoded_asset_path: missing_encoding.expected_path.clone(),
+            });
+        }
 
         Ok(())
     }
 
-    fn prepare_redirect(
-        from: String,
-        to: String,
-        kind: AssetRedirectKind,
-        addtional_headers: Vec<(String, String)>,
-    ) -> AssetCertificationResult<CertifiedAssetResponse<'content>> {
-        let status_code = match kind {
-            AssetRedirectKind::Permanent => StatusCode::MOVED_PERMANENTLY,
-            AssetRedirectKind::Temporary => StatusCode::TEMPORARY_REDIRECT,
-        };
+    /// The paths of the unencoded assets that are currently certified, or pending certification,
+    /// for the provided URL.
+    fn certified_asset_paths(&self, url: &str) -> Vec<String> {
+        let mut asset_paths = vec![];
 
-        let mut headers = vec![("location".to_string(), to)];
-        headers.extend(addtional_headers);
+        if let Some(asset_path) = self
+            .responses
+            .get(&RequestKey::new(url, None, None))
+            .and_then(|response| response.asset_path.clone())
+        {
+            asset_paths.push(asset_path);
+        }
 
-        let (response, certification) = Self::prepare_response_and_certification(
-            from.clone(),
-            status_code,
-            Cow::Owned(vec![]),
-            headers,
-            vec![],
-        )?;
+        if let Some(pending_assets) = self
+            .pending_urls
+            .get(url)
+            .and_then(|asset_url| self.pending_assets.get(asset_url))
+        {
+            asset_paths.extend(
+                pending_assets
+                    .iter()
+                    .filter(|pending_asset| pending_asset.encoding.is_none())
+                    .map(|pending_asset| pending_asset.asset.path.to_string()),
+            );
+        }
 
-        Ok(CertifiedAssetResponse {
-            response,
-            tree_entry: HttpCertificationTreeEntry::new(
-                HttpCertificationPath::exact(from),
-                certification,
-            ),
-        })
+        asset_paths
     }
 
-    fn prepare_asset_response_and_certification<'path>(
-        asset: Asset<'content, 'path>,
-        additional_headers: Vec<(String, String)>,
-        content_type: Option<String>,
-        encoding: Option<AssetEncoding>,
-        range_begin: Option<usize>,
-        status_code: Option<StatusCode>,
-    ) -> AssetCertificationResult<(HttpResponse<'content>, HttpCertification)> {
-        let mut content = asset.content;
-        let mut status_code = status_code.unwrap_or(StatusCode::OK);
-        let mut headers = vec![];
-        headers.extend(additional_headers);
-
-        if let Some(content_type) = content_type {
-            headers.push(("content-type".to_string(), content_type));
+    fn record_namespace(&mut self, asset_path: &str, asset_config: Option<&NormalizedAssetConfig>) {
+        if let Some(namespace) = asset_config.and_then(NormalizedAssetConfig::namespace) {
+            self.asset_namespaces
+                .insert(asset_path.to_string(), namespace.to_string());
         }
+    }
 
-        if let Some(encoding) = encoding {
-            headers.push(("content-encoding".to_string(), encoding.to_string()));
+    fn forget_asset_sync_key(&mut self, response: &CertifiedAssetResponse<'content>) {
+        if let Some(asset_path) = &response.asset_path {
+            self.asset_sync_keys.remove(asset_path);
         }
-
-        let mut request_headers = vec![];
-        if let Some(range_begin) = range_begin {
-            let total_length = content.len();
-            let range_end = cmp::min(range_begin + ASSET_CHUNK_SIZE, total_length) - 1;
-            content = content[range_begin..(range_end + 1)].to_owned().into();
-            status_code = StatusCode::PARTIAL_CONTENT;
-            headers.push((
-                http::header::CONTENT_RANGE.to_string(),
-                format!("bytes {range_begin}-{range_end}/{total_length}"),
-            ));
-            request_headers.push(http::header::RANGE.to_string());
-        };
-
-        Self::prepare_response_and_certification(
-            asset.url.to_string(),
-            status_code,
-            content,
-            headers,
-            request_headers,
-        )
     }
 
-    fn prepare_response_and_certification(
-        url: String,
-        status_code: StatusCode,
-        body: Cow<'content, [u8]>,
-        additional_response_headers: Vec<(String, String)>,
-        certified_request_headers: Vec<String>,
-    ) -> AssetCertificationResult<(HttpResponse<'content>, HttpCertification)> {
-        let mut headers = vec![("content-length".to_string(), body.len().to_string())];
-
-        headers.extend(additional_response_headers);
-        let cel_expr = DefaultCelBuilder::full_certification()
-            .with_request_headers(
-                certified_request_headers
-                    .iter()
-                    .map(|s| s.as_str())
-                    .collect::<Vec<&str>>(),
-            )
-            .with_response_certification(DefaultResponseCertification::response_header_exclusions(
-                vec![],
-            ))
-            .build();
-        let cel_expr_str = cel_expr.to_string();
-        headers.push((CERTIFICATE_EXPRESSION_HEADER_NAME.to_string(), cel_expr_str));
-
-        let request = HttpRequest::get(url).build();
-
-        let response = HttpResponse::builder()
-            .with_status_code(status_code)
-            .with_body(body)
-            .with_headers(headers)
-            .build();
+    /// Deletes all responses that are served on the provided URL, for all encodings, image formats
+    /// and ranges.
+    fn delete_responses_at_url(&mut self, url: &str) {
+        let mut tree = self.tree.write_tree();
+        let asset_sizes = &mut self.asset_sizes;
+        self.responses.retain(|request_key, response| {
+            let should_retain = request_key.path != url;
+            if !should_retain {
+                tree.delete(&response.tree_entry);
+                asset_sizes.remove_response(response);
+            }
 
-        let certification = HttpCertification::full(&cel_expr, &request, &response, None)?;
+            should_retain
+        });
+        self.image_variant_responses
+            .retain(|(_, request_key), response| {
+                let should_retain = request_key.path != url;
+                if !should_retain {
+                    tree.delete(&response.tree_entry);
+                    asset_sizes.remove_response(response);
+                }
 
-        Ok((response, certification))
+                should_retain
+            });
     }
 
-    fn get_encoded_asset(
-        &self,
-        preferred_encodings: &[&str],
-        url: &str,
-        maybe_range_begin: Option<usize>,
-    ) -> Option<&CertifiedAssetResponse<'content>> {
-        for encoding in preferred_encodings {
-            if let Some(response) = self.responses.get(&RequestKey::new(
-                url,
-                Some(encoding.to_string()),
-                maybe_range_begin,
-            )) {
-                if response.response.body().len() > ASSET_CHUNK_SIZE {
-                    if let Some(first_chunk_response) = self.responses.get(&RequestKey::new(
-                        url,
-                        Some(encoding.to_string()),
-                        Some(0),
-                    )) {
-                        return Some(first_chunk_response);
-                    } else {
-                        return None;
-                    }
-                } else {
-                    return Some(response);
-                }
+    /// Deletes all responses, including redirects, that were not generated from one of the provided asset paths.
+    fn retain_responses(&mut self, asset_paths: &HashSet<String>) {
+        let mut tree = self.tree.write_tree();
+        let asset_sizes = &mut self.asset_sizes;
+        let mut should_retain = |response: &CertifiedAssetResponse<'content>| {
+            let should_retain = response
+                .asset_path
+                .as_ref()
+                .is_some_and(|asset_path| asset_paths.contains(asset_path));
+            if !should_retain {
+                tree.delete(&response.tree_entry);
+                asset_sizes.remove_response(response);
             }
+
+            should_retain
+        };
+
+        self.responses.retain(|_, response| should_retain(response));
+        self.fallback_responses
+            .retain(|_, response| should_retain(response));
+        self.image_variant_responses
+            .retain(|_, response| should_retain(response));
+        for error_responses in self.error_responses.values_mut() {
+            error_responses.retain(|_, response| should_retain(response));
         }
+        drop(tree);
+        self.retain_shadowed_fallbacks(|_, response| {
+            response
+                .asset_path
+                .as_ref()
+                .is_some_and(|asset_path| asset_paths.contains(asset_path))
+        });
+        self.forget_unused_glob_scope_matchers();
+        self.asset_sync_keys
+            .retain(|asset_path, _| asset_paths.contains(asset_path));
+        self.pending_assets.retain(|_, pending_assets| {
+            pending_assets.retain(|pending_asset| {
+                let should_retain = asset_paths.contains(&pending_asset.asset.path);
+                if !should_retain {
+                    self.asset_sizes.remove(&pending_asset.asset.path);
+                }
 
-        None
+                should_retain
+            });
+            !pending_assets.is_empty()
+        });
+        self.pending_urls
+            .retain(|_, asset_url| self.pending_assets.contains_key(asset_url));
+        self.pending_url_aliases
+            .retain(|asset_url, _| self.pending_assets.contains_key(asset_url));
+        self.asset_namespaces
+            .retain(|asset_path, _| asset_paths.contains(asset_path));
+        self.redirect_namespaces.clear();
     }
 
-    fn get_encoded_fallback_asset(
+    fn get_asset_for_request<'a>(
         &self,
-        preferred_encodings: &[&str],
-        scope: &str,
-    ) -> Option<&CertifiedAssetResponse<'content>> {
-        for encoding in preferred_encodings {
-            if let Some(response) = self.fallback_responses.get(&RequestKey::new(
-                scope,
-                Some(encoding.to_string()),
-                None,
-            )) {
-                return Some(response);
-            }
+        req_path: &'a str,
+        preferred_encodings: Vec<&'a str>,
+        preferred_image_formats: &[AssetImageFormat],
+        maybe_range_begin: Option<usize>,
+        options: &ServeOptions,
+    ) -> AssetCertificationResult<&CertifiedAssetResponse<'content>> {
+        if let Some(response) = self.get_image_variant(
+            preferred_image_formats,
+            req_path,
+            maybe_range_begin,
+            options.disable_partial_content,
+        ) {
+            return Ok(response);
         }
 
-        None
-    }
+        if let Some(response) = self.get_encoded_asset(
+            &preferred_encodings,
+            req_path,
+            maybe_range_begin,
+            options.disable_partial_content,
+        ) {
+            return Ok(response);
+        }
 
-    fn get_range_header<'a>(request: &'a HttpRequest) -> Option<&'a str> {
-        for (name, value) in request.headers().iter() {
-            if name.to_lowercase().eq(&http::header::RANGE.as_str()) {
-                return Some(value);
+        if let Some(response) =
+            self.responses
+                .get(&RequestKey::new(req_path, None, maybe_range_begin))
+        {
+            if response.response.body_len() > ASSET_CHUNK_SIZE && !options.disable_partial_content {
+                if let Some(first_chunk_response) =
+                    self.responses
+                        .get(&RequestKey::new(req_path, None, Some(0)))
+                {
+                    return Ok(first_chunk_response);
+                }
+            } else {
+                return Ok(response);
             }
         }
-        None
-    }
 
-    fn get_preferred_encodings<'a>(&self, request: &'a HttpRequest) -> Vec<&'a str> {
-        for (name, value) in request.headers().iter() {
-            if name.to_lowercase() == "accept-encoding" {
-                return Self::prioritized_encodings(value)
-                    .iter()
-                    .map(|(encoding, _quality)| *encoding)
-                    .collect();
-            }
+        if options.disable_fallbacks {
+            return Err(AssetCertificationError::NoAssetMatchingRequestUrl {
+                request_url: req_path.to_string(),
+            });
         }
 
-        vec![]
-    }
+        let mut url_scopes = req_path.split('/').collect::<Vec<_>>();
+        url_scopes.pop();
 
-    fn prioritized_encodings(encodings: &str) -> Vec<(&str, f32)> {
-        let mut encodings = encodings
-            .split(',')
-            .filter_map(|encoding| {
-                encoding
-                    .split(';')
-                    .collect::<Vec<_>>()
-                    .first()
-                    .map(|s| s.trim())
-                    .map(|s| (s, Self::default_encoding_quality(s)))
-            })
-            .collect::<Vec<_>>();
+        let mut scopes = vec![];
+        while !url_scopes.is_empty() {
+            let scope = url_scopes.join("/");
+            scopes.push(format!("{}/", scope));
+            scopes.push(scope);
 
-        // this `unwrap()` call is safe as long as the values returned by
-        // `default_encoding_quality` are comparable (not NaN)
-        encodings.sort_unstable_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap());
+            url_scopes.pop();
+        }
 
-        encodings
-    }
+        for (i, scope) in scopes.iter().enumerate() {
+            if let Some(response) = self.get_fallback_asset(&preferred_encodings, scope) {
+                return Ok(response);
+            }
 
-    fn default_encoding_quality(encoding: &str) -> f32 {
-        if encoding.eq_ignore_ascii_case("br") {
-            return 1.0;
-        }
+            if let Some((prefix, response)) =
+                self.get_glob_fallback_asset(&preferred_encodings, scope)
+            {
+                // the fallback is certified for the prefix of its glob, so it cannot be verified
+                // if a scope between the prefix and the matched scope is certified
+                let is_shadowed = scopes[i..]
+                    .iter()
+                    .take_while(|scope| scope.len() > prefix.len())
+                    .any(|scope| self.has_scoped_response(scope));
+                if !is_shadowed {
+                    return Ok(response);
+                }
+            }
 
-        if encoding.eq_ignore_ascii_case("zstd") {
-            return 0.9;
+            // a more specific scope is certified, so a less specific fallback cannot be verified
+            if self.has_scoped_response(scope) {
+                return Err(AssetCertificationError::NoAssetMatchingRequestUrl {
+                    request_url: req_path.to_string(),
+                });
+            }
         }
 
-        if encoding.eq_ignore_ascii_case("gzip") {
-            return 0.8;
-        }
+        Err(AssetCertificationError::NoAssetMatchingRequestUrl {
+            request_url: req_path.to_string(),
+        })
+    }
 
-        if encoding.eq_ignore_ascii_case("deflate") {
-            return 0.7;
-        }
+    fn get_error_page_for_request(
+        &self,
+        req_path: &str,
+        preferred_encodings: &[&str],
+        status_code: StatusCode,
+    ) -> AssetCertificationResult<&CertifiedAssetResponse<'content>> {
+        let no_error_page = || AssetCertificationError::NoErrorPageMatchingRequestUrl {
+            request_url: req_path.to_string(),
+            status_code,
+        };
 
-        if encoding.eq_ignore_ascii_case("identity") {
-            return 0.5;
+        // an exactly matching asset prevents any scoped response from being verified
+        if Self::has_response_in_any_encoding(&self.responses, req_path) {
+            return Err(no_error_page());
         }
 
-        0.6
-    }
-}
+        let mut url_scopes = req_path.split('/').collect::<Vec<_>>();
+        url_scopes.pop();
 
-impl Default for AssetRouter<'_> {
-    fn default() -> Self {
-        Self::new()
-    }
-}
+        while !url_scopes.is_empty() {
+            let scope = url_scopes.join("/");
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::AssetFallbackConfig;
-    use assert_matches::assert_matches;
-    use ic_certification::{hash_tree::SubtreeLookupResult, HashTree};
-    use ic_http_certification::{
-        cel::DefaultFullCelExpressionBuilder, HeaderField, CERTIFICATE_HEADER_NAME,
-    };
-    use ic_response_verification::CertificateHeader;
-    use ic_response_verification_test_utils::{base64_decode, hash};
-    use rand_chacha::rand_core::{RngCore, SeedableRng};
-    use rand_chacha::ChaCha20Rng;
-    use rstest::*;
-    use std::vec;
+            for scope in [format!("{}/", scope), scope] {
+                if let Some(response) =
+                    self.get_error_page(preferred_encodings, &scope, status_code)
+                {
+                    return Ok(response);
+                }
 
-    const ONE_CHUNK_ASSET_LEN: usize = ASSET_CHUNK_SIZE;
-    const TWO_CHUNKS_ASSET_LEN: usize = ASSET_CHUNK_SIZE + 1;
-    const SIX_CHUNKS_ASSET_LEN: usize = 5 * ASSET_CHUNK_SIZE + 12;
-    const TEN_CHUNKS_ASSET_LEN: usize = 10 * ASSET_CHUNK_SIZE;
+                // a more specific scope is certified, so a less specific error page cannot be verified
+                if self.has_scoped_response(&scope) {
+                    return Err(no_error_page());
+                }
+            }
 
-    const ONE_CHUNK_ASSET_NAME: &str = "long_asset_one_chunk";
-    const TWO_CHUNKS_ASSET_NAME: &str = "long_asset_two_chunks";
-    const SIX_CHUNKS_ASSET_NAME: &str = "long_asset_six_chunks";
-    const TEN_CHUNKS_ASSET_NAME: &str = "long_asset_ten_chunks";
+            url_scopes.pop();
+        }
 
-    #[rstest]
-    #[case(0, None)]
-    #[case(ASSET_CHUNK_SIZE, None)]
-    #[case(ASSET_CHUNK_SIZE*4, None)]
-    #[case(0, Some(0))]
-    #[case(100, Some(2000))]
-    #[case(10_000, Some(300_000))]
-    #[case(ASSET_CHUNK_SIZE, Some(2 * ASSET_CHUNK_SIZE - 1))]
-    fn should_parse_range_header_str(#[case] range_begin: usize, #[case] range_end: Option<usize>) {
-        let input = if let Some(range_end) = range_end {
-            format!("bytes={}-{}", range_begin, range_end)
-        } else {
-            format!("bytes={}-", range_begin)
-        };
-        let result = parse_range_header_str(&input);
-        let output = result.unwrap_or_else(|e| panic!("failed parsing '{input}': {:?}", e));
-        assert_eq!(
-            RangeRequestValues {
-                range_begin,
-                range_end
-            },
-            output
-        );
+        Err(no_error_page())
     }
 
-    #[rstest]
-    #[case("")]
-    #[case("byte=1-2")]
-    #[case("bites=2-4")]
-    #[case("bytes 7-11")]
-    #[case("bytes=12345")]
-    #[case("something else")]
-    #[case("bytes=-5-19")]
-    fn should_fail_parse_range_header_str_on_invalid_input(#[case] malformed_input: &str) {
-        let result = parse_range_header_str(malformed_input);
-        assert_matches!(result, Err(e) if e.to_string().contains("Invalid Range header"));
-    }
+    fn certify_asset_impl<'path>(
+        &mut self,
+        asset: Asset<'content, 'path>,
+        asset_config: Option<&NormalizedAssetConfig>,
+        encoding: Option<AssetEncoding>,
+        additional_headers: Vec<(String, String)>,
+    ) -> AssetCertificationResult {
+        let asset = SharedAsset::from(asset);
 
-    #[rstest]
-    #[case("bytes=100-end")]
-    #[case("bytes=dead-beef")]
-    fn should_fail_parse_range_header_str_on_malformed_input(#[case] malformed_input: &str) {
-        let result = parse_range_header_str(malformed_input);
-        assert_matches!(result, Err(e) if e.to_string().contains("Malformed range_"));
-    }
+        let can_certify_lazily = match asset_config {
+            Some(asset_config) => asset_config.can_certify_lazily(),
+            None => true,
+        };
+        if self.lazy_certification && can_certify_lazily {
+            self.defer_asset(asset, asset_config, encoding, additional_headers);
+            return Ok(());
+        }
 
-    #[rstest]
-    #[case("bytes=100-20")]
-    #[case("bytes=20-19")]
-    fn should_fail_parse_range_header_str_on_invalid_values(#[case] malformed_input: &str) {
-        let result = parse_range_header_str(malformed_input);
-        assert_matches!(result, Err(e) if e.to_string().contains("Invalid values in Range header"));
+        self.certify_asset_responses(asset, asset_config, encoding, additional_headers)
     }
 
-    #[rstest]
-    #[case("/")]
-    #[case("https://internetcomputer.org/")]
-    fn test_index_html(mut asset_router: AssetRouter, #[case] req_url: &str) {
-        let request = HttpRequest::get(req_url).build();
+    fn defer_asset(
+        &mut self,
+        asset: SharedAsset<'content>,
+        asset_config: Option<&NormalizedAssetConfig>,
+        encoding: Option<AssetEncoding>,
+        additional_headers: Vec<(String, String)>,
+    ) {
+        let mut urls = vec![asset.url.clone()];
+        if let Some(NormalizedAssetConfig::File { aliased_by, .. }) = asset_config {
+            urls.extend(aliased_by.iter().cloned());
+        }
+        for url in urls.clone() {
+            urls.extend(self.automatic_aliases(&url));
+        }
+        for url in urls {
+            match self.pending_urls.insert(url.clone(), asset.url.clone()) {
+                Some(previous_asset_url) if previous_asset_url != asset.url => {
+                    if let Some(aliases) = self.pending_url_aliases.get_mut(&previous_asset_url) {
+                        aliases.remove(&url);
+                    }
+                }
+                _ => {}
+            }
+            self.pending_url_aliases
+                .entry(asset.url.clone())
+                .or_default()
+                .insert(url);
+        }
 
-        let mut expected_response = expected_index_html_response();
+        let pending_assets = self.pending_assets.entry(asset.url.clone()).or_default();
+        pending_assets.retain(|pending_asset| {
+            let should_retain = pending_asset.asset.path != asset.path;
+            if !should_retain {
+                self.asset_sizes.remove(&pending_asset.asset.path);
+            }
 
-        let response = asset_router
-            .serve_asset(&data_certificate(), &request)
-            .unwrap();
-        let (witness, expr_path) = extract_witness_expr_path(&response);
-        add_v2_certificate_header(
-            &data_certificate(),
-            &mut expected_response,
-            &witness,
-            &expr_path,
-        );
+            should_retain
+        });
+        self.asset_sizes.insert(&asset.path, asset.content.len());
+        pending_assets.push(PendingAsset {
+            asset,
+            asset_config: asset_config.cloned(),
+            encoding,
+            additional_headers,
+        });
+    }
 
-        assert_eq!(expr_path, vec!["http_expr", "", "<$>"]);
-        assert_matches!(
-            witness.lookup_subtree(&expr_path),
-            SubtreeLookupResult::Found(_)
-        );
-        assert_eq!(response, expected_response);
+    /// Removes an asset that has not been certified yet from the assets that are certified
+    /// on demand. Returns `false` if the asset is not pending certification.
+    fn forget_pending_asset(&mut self, asset: &SharedAsset<'content>) -> bool {
+        let Some(pending_assets) = self.pending_assets.get_mut(&asset.url) else {
+            return false;
+        };
 
-        asset_router
-            .delete_assets(
-                vec![Asset::new("index.html", index_html_body())],
-                vec![index_html_config()],
-            )
-            .unwrap();
+        let pending_len = pending_assets.len();
+        pending_assets.retain(|pending_asset| pending_asset.asset.path != asset.path);
+        let is_pending = pending_assets.len() < pending_len;
+        if is_pending {
+            self.asset_sizes.remove(&asset.path);
+        }
 
-        let result = asset_router.serve_asset(&data_certificate(), &request);
-        assert_matches!(
-            result,
-            Err(AssetCertificationError::NoAssetMatchingRequestUrl {
-                request_url,
-             }) if request_url == request.get_path().unwrap()
-        );
+        if pending_assets.is_empty() {
+            self.remove_pending_assets(&asset.url);
+        }
+
+        is_pending
     }
 
-    #[test]
-    fn test_one_chunk_long_asset_served_in_full() {
-        let asset_name = ONE_CHUNK_ASSET_NAME;
-        let long_asset_router =
-            long_asset_router_with_params(&[asset_name], &[AssetEncoding::Identity]);
-        let req_url = format!("/{asset_name}");
-        let asset_body = long_asset_body(asset_name);
-        // Request the entire "one-chunk"-asset, should obtain it in full.
-        let request = HttpRequest::get(&req_url).build();
-        let mut expected_response = build_200_response(
-            asset_body,
-            asset_cel_expr(),
-            vec![
-                (
-                    "cache-control".to_string(),
-                    "public, no-cache, no-store".to_string(),
-                ),
-                ("content-type".to_string(), "text/html".to_string()),
-            ],
-        );
+    /// Removes all assets that are certified on demand for the provided asset URL, along with
+    /// all URLs that they are served for, and returns the removed assets.
+    fn remove_pending_assets(&mut self, asset_url: &str) -> Vec<PendingAsset<'content>> {
+        for url in self
+            .pending_url_aliases
+            .remove(asset_url)
+            .unwrap_or_default()
+        {
+            self.pending_urls.remove(&url);
+        }
 
-        let response = long_asset_router
-            .serve_asset(&data_certificate(), &request)
-            .unwrap();
-        let (witness, expr_path) = extract_witness_expr_path(&response);
-        add_v2_certificate_header(
-            &data_certificate(),
-            &mut expected_response,
-            &witness,
-            &expr_path,
-        );
+        let pending_assets = self.pending_assets.remove(asset_url).unwrap_or_default();
+        for pending_asset in &pending_assets {
+            self.asset_sizes.remove(&pending_asset.asset.path);
+        }
 
-        assert_eq!(expr_path, vec!["http_expr", &req_url[1..], "<$>"]);
-        assert_matches!(
-            witness.lookup_subtree(&expr_path),
-            SubtreeLookupResult::Found(_)
-        );
-        assert_eq!(response, expected_response);
+        pending_assets
     }
 
-    #[rstest]
-    #[case(TWO_CHUNKS_ASSET_NAME)]
-    #[case(SIX_CHUNKS_ASSET_NAME)]
-    #[case(TEN_CHUNKS_ASSET_NAME)]
-    fn test_long_asset_served_in_chunks(#[case] asset_name: &str) {
-        let long_asset_router =
-            long_asset_router_with_params(&[asset_name], &[AssetEncoding::Identity]);
-        let req_url = format!("/{asset_name}");
-        let asset_body = long_asset_body(asset_name);
-        let asset_len = asset_body.len();
-        // Request the entire asset, should obtain the first chunk.
-        let request = HttpRequest::get(&req_url).build();
-        let mut expected_response = build_206_response(
-            asset_body[0..ASSET_CHUNK_SIZE].to_vec(),
-            asset_range_chunk_cel_expr(),
-            vec![
-                (
-                    "cache-control".to_string(),
-                    "public, no-cache, no-store".to_string(),
-                ),
-                ("content-type".to_string(), "text/html".to_string()),
-                (
-                    "content-range".to_string(),
-                    format!("bytes 0-{}/{}", ASSET_CHUNK_SIZE - 1, asset_len),
-                ),
-            ],
-        );
-
-        let response = long_asset_router
-            .serve_asset(&data_certificate(), &request)
+    fn certify_asset_responses(
+        &mut self,
+        asset: SharedAsset<'content>,
+        asset_config: Option<&NormalizedAssetConfig>,
+        encoding: Option<AssetEncoding>,
+        additional_headers: Vec<(String, String)>,
+    ) -> AssetCertificationResult {
+        match asset_config {
+            Some(NormalizedAssetConfig::Pattern {
+                content_type,
+                headers,
+                ..
+            }) => {
+                let headers = [headers.clone(), additional_headers].concat();
+
+                self.insert_static_asset(asset, content_type.clone(), headers, encoding)?;
+            }
+            Some(NormalizedAssetConfig::File {
+                content_type,
+                headers,
+                fallback_for,
+                aliased_by,
+                ..
+            }) => {
+                let headers = [headers.clone(), additional_headers].concat();
+
+                self.insert_static_asset(
+                    asset.clone(),
+                    content_type.clone(),
+                    headers.clone(),
+                    encoding,
+                )?;
+
+                for fallback_for in fallback_for.iter() {
+                    self.insert_fallback_asset(
+                        asset.clone(),
+                        content_type.clone(),
+                        headers.clone(),
+                        fallback_for.clone(),
+                        encoding,
+                    )?;
+                }
+
+                for aliased_by in aliased_by.iter() {
+                    let mut aliased_asset = asset.clone();
+                    aliased_asset.url.clone_from(aliased_by);
+
+                    self.insert_static_asset(
+                        aliased_asset,
+                        content_type.clone(),
+                        headers.clone(),
+                        encoding,
+                    )?;
+                }
+            }
+            Some(NormalizedAssetConfig::ErrorPage {
+                status_code,
+                scopes,
+                content_type,
+                headers,
+                ..
+            }) => {
+                let headers = [headers.clone(), additional_headers].concat();
+
+                for scope in scopes.iter() {
+                    self.insert_error_page(
+                        asset.clone(),
+                        content_type.clone(),
+                        headers.clone(),
+                        *status_code,
+                        scope.clone(),
+                        encoding,
+                    )?;
+                }
+            }
+            _ => {
+                self.insert_static_asset(asset, None, additional_headers, encoding)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn delete_asset_impl<'path>(
+        &mut self,
+        asset: Asset<'content, 'path>,
+        asset_config: Option<&NormalizedAssetConfig>,
+        encoding: Option<AssetEncoding>,
+        additional_headers: Vec<(String, String)>,
+    ) -> AssetCertificationResult {
+        let asset = SharedAsset::from(asset);
+
+        // assets that are pending certification have no responses to delete
+        if self.forget_pending_asset(&asset) {
+            return Ok(());
+        }
+
+        match asset_config {
+            Some(NormalizedAssetConfig::Pattern {
+                content_type,
+                headers,
+                ..
+            }) => {
+                let headers = [headers.clone(), additional_headers].concat();
+
+                self.delete_static_asset(asset, content_type.clone(), headers, encoding)?;
+            }
+            Some(NormalizedAssetConfig::File {
+                content_type,
+                headers,
+                fallback_for,
+                aliased_by,
+                ..
+            }) => {
+                let headers = [headers.clone(), additional_headers].concat();
+
+                self.delete_static_asset(
+                    asset.clone(),
+                    content_type.clone(),
+                    headers.clone(),
+                    encoding,
+                )?;
+
+                for fallback_for in fallback_for.iter() {
+                    self.delete_fallback_asset(
+                        asset.clone(),
+                        content_type.clone(),
+                        headers.clone(),
+                        fallback_for.clone(),
+                        encoding,
+                    )?;
+                }
+
+                for aliased_by in aliased_by.iter() {
+                    let mut aliased_asset = asset.clone();
+                    aliased_asset.url.clone_from(aliased_by);
+
+                    self.delete_static_asset(
+                        aliased_asset,
+                        content_type.clone(),
+                        headers.clone(),
+                        encoding,
+                    )?;
+                }
+            }
+            Some(NormalizedAssetConfig::ErrorPage {
+                status_code,
+                scopes,
+                content_type,
+                headers,
+                ..
+            }) => {
+                let headers = [headers.clone(), additional_headers].concat();
+
+                for scope in scopes.iter() {
+                    self.delete_error_page(
+                        asset.clone(),
+                        content_type.clone(),
+                        headers.clone(),
+                        *status_code,
+                        scope.clone(),
+                        encoding,
+                    )?;
+                }
+            }
+            _ => {
+                self.delete_static_asset(asset, None, additional_headers, encoding)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn insert_static_asset(
+        &mut self,
+        asset: SharedAsset<'content>,
+        content_type: Option<String>,
+        additional_headers: Vec<(String, String)>,
+        encoding: Option<AssetEncoding>,
+    ) -> AssetCertificationResult<()> {
+        for alias in self.automatic_aliases(&asset.url) {
+            let mut aliased_asset = asset.clone();
+            aliased_asset.url = alias;
+
+            self.insert_static_asset_at_url(
+                aliased_asset,
+                content_type.clone(),
+                additional_headers.clone(),
+                encoding,
+            )?;
+        }
+
+        self.insert_static_asset_at_url(asset, content_type, additional_headers, encoding)
+    }
+
+    fn insert_static_asset_at_url(
+        &mut self,
+        asset: SharedAsset<'content>,
+        content_type: Option<String>,
+        additional_headers: Vec<(String, String)>,
+        encoding: Option<AssetEncoding>,
+    ) -> AssetCertificationResult<()> {
+        for (request_key, response) in
+            self.prepare_static_asset_responses(asset, content_type, additional_headers, encoding)?
+        {
+            self.tree.write_tree().insert(&response.tree_entry);
+            self.asset_sizes.insert_response(&response);
+            if let Some(previous_response) = self.responses.insert(request_key, response) {
+                self.asset_sizes.remove_response(&previous_response);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Prepares the full response of a static asset, along with the response for each chunk
+    /// if the asset is larger than [ASSET_CHUNK_SIZE].
+    fn prepare_static_asset_responses(
+        &mut self,
+        asset: SharedAsset<'content>,
+        content_type: Option<String>,
+        additional_headers: Vec<(String, String)>,
+        encoding: Option<AssetEncoding>,
+    ) -> AssetCertificationResult<Vec<(RequestKey, CertifiedAssetResponse<'content>)>> {
+        let asset_url = asset.url.to_string();
+        let total_length = asset.content.len();
+        let mut responses = vec![];
+
+        if total_length > ASSET_CHUNK_SIZE {
+            let mut range_begin = 0;
+            while range_begin < asset.content.len() {
+                let response = Self::prepare_static_asset(
+                    asset.clone(),
+                    content_type.clone(),
+                    additional_headers.clone(),
+                    encoding,
+                    Some(range_begin),
+                    self.response_hook.as_mut(),
+                )?;
+                responses.push((
+                    RequestKey::new(&asset_url, encoding_str(encoding), Some(range_begin)),
+                    response,
+                ));
+                range_begin += ASSET_CHUNK_SIZE;
+            }
+        }
+
+        let response = Self::prepare_static_asset(
+            asset,
+            content_type,
+            additional_headers,
+            encoding,
+            None,
+            self.response_hook.as_mut(),
+        )?;
+        responses.push((
+            RequestKey::new(&asset_url, encoding_str(encoding), None),
+            response,
+        ));
+
+        Ok(responses)
+    }
+
+    /// Certifies the alternative image format of an asset on all URLs that the asset is served on,
+    /// apart from fallbacks, see [image_variants](AssetConfig::File::image_variants).
+    fn certify_image_variant(
+        &mut self,
+        variant_asset: &Asset<'content, '_>,
+        asset: &Asset<'content, '_>,
+        asset_config: &NormalizedAssetConfig,
+        image_format: AssetImageFormat,
+    ) -> AssetCertificationResult {
+        let headers = match asset_config {
+            NormalizedAssetConfig::File { headers, .. }
+            | NormalizedAssetConfig::Pattern { headers, .. } => headers.clone(),
+            _ => vec![],
+        };
+        let headers = [
+            headers,
+            self.additional_headers(variant_asset, Some(asset_config)),
+        ]
+        .concat();
+
+        for url in self.image_variant_urls(&asset.url, asset_config) {
+            let mut aliased_asset = SharedAsset::from(variant_asset.clone());
+            aliased_asset.url = url;
+
+            for (request_key, response) in self.prepare_static_asset_responses(
+                aliased_asset,
+                Some(image_format.content_type().to_string()),
+                headers.clone(),
+                None,
+            )? {
+                self.tree.write_tree().insert(&response.tree_entry);
+                self.asset_sizes.insert_response(&response);
+                if let Some(previous_response) = self
+                    .image_variant_responses
+                    .insert((image_format, request_key), response)
+                {
+                    self.asset_sizes.remove_response(&previous_response);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn delete_image_variant(
+        &mut self,
+        url: &str,
+        asset_config: &NormalizedAssetConfig,
+        image_format: AssetImageFormat,
+    ) {
+        let urls = self.image_variant_urls(url, asset_config);
+
+        let mut tree = self.tree.write_tree();
+        let asset_sizes = &mut self.asset_sizes;
+        self.image_variant_responses
+            .retain(|(response_image_format, request_key), response| {
+                let should_retain =
+                    *response_image_format != image_format || !urls.contains(&request_key.path);
+                if !should_retain {
+                    tree.delete(&response.tree_entry);
+                    asset_sizes.remove_response(response);
+                }
+
+                should_retain
+            });
+    }
+
+    /// The URLs that an asset is served on, apart from fallbacks.
+    fn image_variant_urls(&self, url: &str, asset_config: &NormalizedAssetConfig) -> Vec<String> {
+        let mut urls = vec![url.to_string()];
+        if let NormalizedAssetConfig::File { aliased_by, .. } = asset_config {
+            urls.extend(aliased_by.iter().cloned());
+        }
+        for url in urls.clone() {
+            urls.extend(self.automatic_aliases(&url));
+        }
+
+        urls
+    }
+
+    fn delete_static_asset(
+        &mut self,
+        asset: SharedAsset<'content>,
+        content_type: Option<String>,
+        additional_headers: Vec<(String, String)>,
+        encoding: Option<AssetEncoding>,
+    ) -> AssetCertificationResult<()> {
+        for alias in self.automatic_aliases(&asset.url) {
+            let mut aliased_asset = asset.clone();
+            aliased_asset.url = alias;
+
+            self.delete_static_asset_at_url(
+                aliased_asset,
+                content_type.clone(),
+                additional_headers.clone(),
+                encoding,
+            )?;
+        }
+
+        self.delete_static_asset_at_url(asset, content_type, additional_headers, encoding)
+    }
+
+    fn delete_static_asset_at_url(
+        &mut self,
+        asset: SharedAsset<'content>,
+        content_type: Option<String>,
+        additional_headers: Vec<(String, String)>,
+        encoding: Option<AssetEncoding>,
+    ) -> AssetCertificationResult<()> {
+        let asset_url = asset.url.to_string();
+        let response = Self::prepare_static_asset(
+            asset,
+            content_type,
+            additional_headers,
+            encoding,
+            None,
+            self.response_hook.as_mut(),
+        )?;
+
+        self.tree.write_tree().delete(&response.tree_entry);
+        if let Some(response) =
+            self.responses
+                .remove(&RequestKey::new(&asset_url, encoding_str(encoding), None))
+        {
+            self.asset_sizes.remove_response(&response);
+        }
+
+        if response.response.body_len() > ASSET_CHUNK_SIZE {
+            // Delete also chunks.
+            let mut range_begin: usize = 0;
+            while range_begin < response.response.body_len() {
+                if let Some(response) = self.responses.remove(&RequestKey::new(
+                    &asset_url,
+                    encoding_str(encoding),
+                    Some(range_begin),
+                )) {
+                    self.asset_sizes.remove_response(&response);
+                }
+                range_begin += ASSET_CHUNK_SIZE;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn automatic_aliases(&self, url: &str) -> Vec<String> {
+        let mut aliases = vec![];
+
+        if self.directory_index_aliases || self.trailing_slash_aliases {
+            if let Some(directory) = url.strip_suffix("/index.html") {
+                aliases.push(format!("{directory}/"));
+                if !directory.is_empty() {
+                    aliases.push(directory.to_string());
+                }
+            }
+        }
+
+        if self.trailing_slash_aliases && url != "/" {
+            if let Some(stripped_url) = url.strip_suffix('/') {
+                aliases.push(stripped_url.to_string());
+            } else if !url.rsplit('/').next().unwrap_or_default().contains('.') {
+                aliases.push(format!("{url}/"));
+            }
+        }
+
+        aliases
+    }
+
+    fn prepare_static_asset(
+        asset: SharedAsset<'content>,
+        content_type: Option<String>,
+        additional_headers: Vec<(String, String)>,
+        encoding: Option<AssetEncoding>,
+        range_begin: Option<usize>,
+        response_hook: Option<&mut ResponseHook>,
+    ) -> AssetCertificationResult<CertifiedAssetResponse<'content>> {
+        let asset_url = asset.url.to_string();
+        let asset_path = asset.path.to_string();
+
+        let (response, certification) = Self::prepare_asset_response_and_certification(
+            asset,
+            additional_headers,
+            content_type,
+            encoding,
+            range_begin,
+            None,
+            response_hook,
+        )?;
+
+        let tree_entry =
+            HttpCertificationTreeEntry::new(HttpCertificationPath::exact(asset_url), certification);
+
+        Ok(CertifiedAssetResponse {
+            response,
+            tree_entry,
+            asset_path: Some(asset_path),
+            priority: 0,
+            append_query: false,
+        })
+    }
+
+    fn insert_fallback_asset(
+        &mut self,
+        asset: SharedAsset<'content>,
+        content_type: Option<String>,
+        additional_headers: Vec<(String, String)>,
+        fallback_for: AssetFallbackConfig,
+        encoding: Option<AssetEncoding>,
+    ) -> AssetCertificationResult<()> {
+        let response = Self::prepare_fallback_asset(
+            asset,
+            additional_headers,
+            content_type,
+            fallback_for.clone(),
+            encoding,
+            self.response_hook.as_mut(),
+        )?;
+
+        if is_glob_scope(&fallback_for.scope) {
+            let matchers = self
+                .fallback_scope_matchers
+                .entry(glob_scope_prefix(&fallback_for.scope).to_string())
+                .or_default();
+            if !matchers.contains_key(&fallback_for.scope) {
+                matchers.insert(
+                    fallback_for.scope.clone(),
+                    glob_scope_matcher(&fallback_for.scope)?,
+                );
+            }
+        }
+
+        let req_key = RequestKey::new(&fallback_for.scope, encoding_str(encoding), None);
+        self.asset_sizes.insert_response(&response);
+
+        // the asset may already be certified for this scope, possibly with another priority
+        self.remove_shadowed_fallback(&req_key, &response.asset_path);
+        if self
+            .fallback_responses
+            .get(&req_key)
+            .is_some_and(|existing_response| existing_response.asset_path == response.asset_path)
+        {
+            if let Some(existing_response) = self.fallback_responses.remove(&req_key) {
+                self.tree.write_tree().delete(&existing_response.tree_entry);
+                self.asset_sizes.remove_response(&existing_response);
+            }
+            self.reinstate_shadowed_fallback(&req_key);
+        }
+
+        if let Some(existing_response) = self.fallback_responses.get(&req_key) {
+            if existing_response.priority > response.priority {
+                self.shadowed_fallback_responses
+                    .entry(req_key)
+                    .or_default()
+                    .push(response);
+                return Ok(());
+            }
+
+            self.tree.write_tree().delete(&existing_response.tree_entry);
+        }
+
+        self.tree.write_tree().insert(&response.tree_entry);
+        if let Some(previous_response) = self.fallback_responses.insert(req_key.clone(), response) {
+            self.shadowed_fallback_responses
+                .entry(req_key)
+                .or_default()
+                .push(previous_response);
+        }
+        Ok(())
+    }
+
+    fn delete_fallback_asset(
+        &mut self,
+        asset: SharedAsset<'content>,
+        content_type: Option<String>,
+        additional_headers: Vec<(String, String)>,
+        fallback_for: AssetFallbackConfig,
+        encoding: Option<AssetEncoding>,
+    ) -> AssetCertificationResult<()> {
+        let response = Self::prepare_fallback_asset(
+            asset,
+            additional_headers,
+            content_type,
+            fallback_for.clone(),
+            encoding,
+            self.response_hook.as_mut(),
+        )?;
+
+        // another asset with a higher priority may be the fallback for this scope
+        let req_key = RequestKey::new(&fallback_for.scope, encoding_str(encoding), None);
+        if self
+            .fallback_responses
+            .get(&req_key)
+            .is_some_and(|existing_response| existing_response.asset_path == response.asset_path)
+        {
+            self.tree.write_tree().delete(&response.tree_entry);
+            self.asset_sizes.remove_response(&response);
+            self.fallback_responses.remove(&req_key);
+            self.reinstate_shadowed_fallback(&req_key);
+            if is_glob_scope(&fallback_for.scope) {
+                self.forget_glob_scope_matcher(&fallback_for.scope);
+            }
+        } else {
+            self.remove_shadowed_fallback(&req_key, &response.asset_path);
+        }
+        Ok(())
+    }
+
+    /// Removes the fallback generated from the provided asset path from the fallbacks that
+    /// are shadowed for the provided request key.
+    fn remove_shadowed_fallback(&mut self, req_key: &RequestKey, asset_path: &Option<String>) {
+        let Some(shadowed_responses) = self.shadowed_fallback_responses.get_mut(req_key) else {
+            return;
+        };
+
+        let asset_sizes = &mut self.asset_sizes;
+        shadowed_responses.retain(|response| {
+            let should_retain = &response.asset_path != asset_path;
+            if !should_retain {
+                asset_sizes.remove_response(response);
+            }
+
+            should_retain
+        });
+        if shadowed_responses.is_empty() {
+            self.shadowed_fallback_responses.remove(req_key);
+        }
+    }
+
+    /// Certifies the shadowed fallback with the highest priority for the provided request key,
+    /// once no other fallback is certified for it. If multiple fallbacks share the highest
+    /// priority, the one that was certified last is used.
+    fn reinstate_shadowed_fallback(&mut self, req_key: &RequestKey) {
+        let Some(shadowed_responses) = self.shadowed_fallback_responses.get_mut(req_key) else {
+            return;
+        };
+        let Some(index) = shadowed_responses
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, response)| response.priority)
+            .map(|(index, _)| index)
+        else {
+            return;
+        };
+
+        let response = shadowed_responses.remove(index);
+        if shadowed_responses.is_empty() {
+            self.shadowed_fallback_responses.remove(req_key);
+        }
+        self.tree.write_tree().insert(&response.tree_entry);
+        self.fallback_responses.insert(req_key.clone(), response);
+    }
+
+    /// Removes all shadowed fallbacks that are not retained by the provided predicate and
+    /// reinstates shadowed fallbacks for all scopes whose certified fallback was deleted.
+    fn retain_shadowed_fallbacks(
+        &mut self,
+        mut should_retain: impl FnMut(&RequestKey, &CertifiedAssetResponse<'content>) -> bool,
+    ) {
+        let asset_sizes = &mut self.asset_sizes;
+        self.shadowed_fallback_responses
+            .retain(|req_key, shadowed_responses| {
+                shadowed_responses.retain(|response| {
+                    let should_retain = should_retain(req_key, response);
+                    if !should_retain {
+                        asset_sizes.remove_response(response);
+                    }
+
+                    should_retain
+                });
+
+                !shadowed_responses.is_empty()
+            });
+
+        let unshadowed_req_keys = self
+            .shadowed_fallback_responses
+            .keys()
+            .filter(|req_key| !self.fallback_responses.contains_key(req_key))
+            .cloned()
+            .collect::<Vec<_>>();
+        for req_key in unshadowed_req_keys {
+            self.reinstate_shadowed_fallback(&req_key);
+        }
+    }
+
+    /// Removes the matcher of the provided glob scope once no fallback is certified for it
+    /// in any encoding.
+    fn forget_glob_scope_matcher(&mut self, glob_scope: &str) {
+        if Self::has_response_in_any_encoding(&self.fallback_responses, glob_scope) {
+            return;
+        }
+
+        let prefix = glob_scope_prefix(glob_scope);
+        if let Some(matchers) = self.fallback_scope_matchers.get_mut(prefix) {
+            matchers.remove(glob_scope);
+            if matchers.is_empty() {
+                self.fallback_scope_matchers.remove(prefix);
+            }
+        }
+    }
+
+    /// Removes the matchers of all glob scopes that no fallback is certified for anymore.
+    fn forget_unused_glob_scope_matchers(&mut self) {
+        let fallback_responses = &self.fallback_responses;
+        self.fallback_scope_matchers.retain(|_, matchers| {
+            matchers.retain(|glob_scope, _| {
+                Self::has_response_in_any_encoding(fallback_responses, glob_scope)
+            });
+
+            !matchers.is_empty()
+        });
+    }
+
+    fn prepare_fallback_asset(
+        asset: SharedAsset<'content>,
+        additional_headers: Vec<(String, String)>,
+        content_type: Option<String>,
+        fallback_for: AssetFallbackConfig,
+        encoding: Option<AssetEncoding>,
+        response_hook: Option<&mut ResponseHook>,
+    ) -> AssetCertificationResult<CertifiedAssetResponse<'content>> {
+        let asset_path = asset.path.to_string();
+        let (response, certification) = Self::prepare_asset_response_and_certification(
+            asset,
+            additional_headers,
+            content_type,
+            encoding,
+            None,
+            fallback_for.status_code,
+            response_hook,
+        )?;
+
+        let scope = if is_glob_scope(&fallback_for.scope) {
+            glob_scope_prefix(&fallback_for.scope)
+        } else {
+            &fallback_for.scope
+        };
+        let tree_entry = HttpCertificationTreeEntry::new(
+            HttpCertificationPath::wildcard(scope.to_string()),
+            certification,
+        );
+
+        Ok(CertifiedAssetResponse {
+            response,
+            tree_entry,
+            asset_path: Some(asset_path),
+            priority: fallback_for.priority,
+            append_query: false,
+        })
+    }
+
+    fn insert_error_page(
+        &mut self,
+        asset: SharedAsset<'content>,
+        content_type: Option<String>,
+        additional_headers: Vec<(String, String)>,
+        status_code: StatusCode,
+        scope: String,
+        encoding: Option<AssetEncoding>,
+    ) -> AssetCertificationResult<()> {
+        let req_key = RequestKey::new(&scope, encoding_str(encoding), None);
+        let response = Self::prepare_error_page(
+            asset,
+            additional_headers,
+            content_type,
+            status_code,
+            scope,
+            encoding,
+            self.response_hook.as_mut(),
+        )?;
+
+        // the error pages of all status codes share the wildcard path of the scope with its
+        // fallback, so the HTTP Gateway accepts any of them for a request within the scope
+        let mut tree = self.tree.write_tree();
+        self.asset_sizes.insert_response(&response);
+        if let Some(existing_response) = self
+            .error_responses
+            .entry(status_code)
+            .or_default()
+            .insert(req_key, response.clone())
+        {
+            tree.delete(&existing_response.tree_entry);
+            self.asset_sizes.remove_response(&existing_response);
+        }
+        tree.insert(&response.tree_entry);
+
+        Ok(())
+    }
+
+    fn delete_error_page(
+        &mut self,
+        asset: SharedAsset<'content>,
+        content_type: Option<String>,
+        additional_headers: Vec<(String, String)>,
+        status_code: StatusCode,
+        scope: String,
+        encoding: Option<AssetEncoding>,
+    ) -> AssetCertificationResult<()> {
+        let req_key = RequestKey::new(&scope, encoding_str(encoding), None);
+        let response = Self::prepare_error_page(
+            asset,
+            additional_headers,
+            content_type,
+            status_code,
+            scope,
+            encoding,
+            self.response_hook.as_mut(),
+        )?;
+
+        // another asset may have replaced this error page for the same scope
+        if let Some(error_responses) = self.error_responses.get_mut(&status_code) {
+            if error_responses
+                .get(&req_key)
+                .is_some_and(|existing_response| {
+                    existing_response.asset_path == response.asset_path
+                })
+            {
+                self.tree.write_tree().delete(&response.tree_entry);
+                self.asset_sizes.remove_response(&response);
+                error_responses.remove(&req_key);
+            }
+        }
+        Ok(())
+    }
+
+    fn prepare_error_page(
+        asset: SharedAsset<'content>,
+        additional_headers: Vec<(String, String)>,
+        content_type: Option<String>,
+        status_code: StatusCode,
+        scope: String,
+        encoding: Option<AssetEncoding>,
+        response_hook: Option<&mut ResponseHook>,
+    ) -> AssetCertificationResult<CertifiedAssetResponse<'content>> {
+        Self::prepare_fallback_asset(
+            asset,
+            additional_headers,
+            content_type,
+            AssetFallbackConfig {
+                scope,
+                status_code: Some(status_code),
+                priority: 0,
+            },
+            encoding,
+            response_hook,
+        )
+    }
+
+    fn insert_redirect(
+        &mut self,
+        from: String,
+        to: String,
+        kind: AssetRedirectKind,
+        append_query: bool,
+        additional_headers: Vec<(String, String)>,
+    ) -> AssetCertificationResult<()> {
+        let response =
+            Self::prepare_redirect(from.clone(), to, kind, append_query, additional_headers)?;
+
+        self.tree.write_tree().insert(&response.tree_entry);
+
+        // a redirect replaces any asset that was served on the same URL
+        if let Some(previous_response) = self
+            .responses
+            .insert(RequestKey::new(&from, None, None), response)
+        {
+            self.asset_sizes.remove_response(&previous_response);
+        }
+
+        Ok(())
+    }
+
+    fn delete_redirect(
+        &mut self,
+        from: String,
+        to: String,
+        kind: AssetRedirectKind,
+        append_query: bool,
+        addtional_headers: Vec<(String, String)>,
+    ) -> AssetCertificationResult<()> {
+        let response =
+            Self::prepare_redirect(from.clone(), to, kind, append_query, addtional_headers)?;
+
+        self.tree.write_tree().delete(&response.tree_entry);
+        if let Some(response) = self.responses.remove(&RequestKey::new(&from, None, None)) {
+            self.asset_sizes.remove_response(&response);
+        }
+
+        Ok(())
+    }
+
+    fn prepare_redirect(
+        from: String,
+        to: String,
+        kind: AssetRedirectKind,
+        append_query: bool,
+        addtional_headers: Vec<(String, String)>,
+    ) -> AssetCertificationResult<CertifiedAssetResponse<'content>> {
+        let mut headers = vec![(LOCATION_HEADER_NAME.to_string(), to)];
+        headers.extend(addtional_headers);
+
+        let (response, certification) = Self::prepare_response_and_certification(
+            from.clone(),
+            kind.status_code(),
+            AssetBody::empty(),
+            None,
+            headers,
+            InternedCelExpression::redirect(append_query),
+            None,
+        )?;
+
+        Ok(CertifiedAssetResponse {
+            response,
+            tree_entry: HttpCertificationTreeEntry::new(
+                HttpCertificationPath::exact(from),
+                certification,
+            ),
+            asset_path: None,
+            priority: 0,
+            append_query,
+        })
+    }
+
+    fn prepare_asset_response_and_certification(
+        asset: SharedAsset<'content>,
+        additional_headers: Vec<(String, String)>,
+        content_type: Option<String>,
+        encoding: Option<AssetEncoding>,
+        range_begin: Option<usize>,
+        status_code: Option<StatusCode>,
+        response_hook: Option<&mut ResponseHook>,
+    ) -> AssetCertificationResult<(AssetResponse<'content>, HttpCertification)> {
+        let mut body_range = None;
+        let mut status_code = status_code.unwrap_or(StatusCode::OK);
+        let mut headers = vec![];
+        headers.extend(additional_headers);
+
+        if let Some(content_type) = content_type {
+            headers.push(("content-type".to_string(), content_type));
+        }
+
+        if let Some(encoding) = encoding {
+            headers.push(("content-encoding".to_string(), encoding.to_string()));
+        }
+
+        if let Some(range_begin) = range_begin {
+            let total_length = asset.content.len();
+            let range_end = cmp::min(range_begin + ASSET_CHUNK_SIZE, total_length) - 1;
+            body_range = Some(range_begin..(range_end + 1));
+            status_code = StatusCode::PARTIAL_CONTENT;
+            headers.push((
+                http::header::CONTENT_RANGE.to_string(),
+                format!("bytes {range_begin}-{range_end}/{total_length}"),
+            ));
+        };
+
+        let hook_asset = Asset {
+            path: Cow::Borrowed(&asset.path),
+            url: Cow::Borrowed(&asset.url),
+            content: asset.content.clone(),
+        };
+
+        Self::prepare_response_and_certification(
+            asset.url.clone(),
+            status_code,
+            asset.content.clone(),
+            body_range,
+            headers,
+            InternedCelExpression::asset(range_begin.is_some()),
+            response_hook.map(|hook| (hook, &hook_asset)),
+        )
+    }
+
+    fn prepare_response_and_certification(
+        url: String,
+        status_code: StatusCode,
+        body: AssetBody<'content>,
+        body_range: Option<Range<usize>>,
+        additional_response_headers: Vec<(String, String)>,
+        cel_expr: &InternedCelExpression,
+        response_hook: Option<(&mut ResponseHook, &Asset)>,
+    ) -> AssetCertificationResult<(AssetResponse<'content>, HttpCertification)> {
+        let mut response = AssetResponse {
+            status_code,
+            headers: vec![],
+            body_hash: body.hash(body_range.clone()),
+            body,
+            body_range,
+        };
+        let mut headers = vec![(
+            "content-length".to_string(),
+            response.body_len().to_string(),
+        )];
+
+        headers.extend(additional_response_headers);
+        response.headers = headers;
+
+        if let Some((ResponseHook(hook), asset)) = response_hook {
+            let mut hook_response = AssetHookResponse::new(
+                response.status_code,
+                std::mem::take(&mut response.headers),
+                response.body.as_bytes(response.body_range.clone()),
+            );
+            hook(asset, &mut hook_response);
+
+            (response.status_code, response.headers) = hook_response.into_parts();
+        }
+
+        response.headers.push((
+            CERTIFICATE_EXPRESSION_HEADER_NAME.to_string(),
+            cel_expr.cel_expr_str.clone(),
+        ));
+
+        let request = HttpRequest::get(url).build();
+
+        let certification = HttpCertification::full(
+            &cel_expr.cel_expr,
+            &request,
+            &response.as_certification_response(),
+            Some(response.body_hash),
+        )?;
+
+        Ok((response, certification))
+    }
+
+    fn get_encoded_asset(
+        &self,
+        preferred_encodings: &[&str],
+        url: &str,
+        maybe_range_begin: Option<usize>,
+        disable_partial_content: bool,
+    ) -> Option<&CertifiedAssetResponse<'content>> {
+        for encoding in preferred_encodings {
+            // the unencoded asset is preferred over the remaining encodings
+            if encoding.eq_ignore_ascii_case("identity") {
+                return None;
+            }
+
+            if let Some(response) = self.responses.get(&RequestKey::new(
+                url,
+                Some(encoding.to_string()),
+                maybe_range_begin,
+            )) {
+                if response.response.body_len() > ASSET_CHUNK_SIZE && !disable_partial_content {
+                    if let Some(first_chunk_response) = self.responses.get(&RequestKey::new(
+                        url,
+                        Some(encoding.to_string()),
+                        Some(0),
+                    )) {
+                        return Some(first_chunk_response);
+                    } else {
+                        return None;
+                    }
+                } else {
+                    return Some(response);
+                }
+            }
+        }
+
+        None
+    }
+
+    fn get_image_variant(
+        &self,
+        preferred_image_formats: &[AssetImageFormat],
+        url: &str,
+        maybe_range_begin: Option<usize>,
+        disable_partial_content: bool,
+    ) -> Option<&CertifiedAssetResponse<'content>> {
+        for image_format in preferred_image_formats {
+            let Some(response) = self
+                .image_variant_responses
+                .get(&(*image_format, RequestKey::new(url, None, maybe_range_begin)))
+            else {
+                continue;
+            };
+
+            if response.response.body_len() > ASSET_CHUNK_SIZE && !disable_partial_content {
+                return self
+                    .image_variant_responses
+                    .get(&(*image_format, RequestKey::new(url, None, Some(0))));
+            }
+
+            return Some(response);
+        }
+
+        None
+    }
+
+    /// Returns the fallback for the provided scope, or the [NOT_FOUND](StatusCode::NOT_FOUND)
+    /// error page if there is no fallback.
+    fn get_fallback_asset(
+        &self,
+        preferred_encodings: &[&str],
+        scope: &str,
+    ) -> Option<&CertifiedAssetResponse<'content>> {
+        Self::get_scoped_response(&self.fallback_responses, preferred_encodings, scope)
+            .or_else(|| self.get_error_page(preferred_encodings, scope, StatusCode::NOT_FOUND))
+    }
+
+    fn get_error_page(
+        &self,
+        preferred_encodings: &[&str],
+        scope: &str,
+        status_code: StatusCode,
+    ) -> Option<&CertifiedAssetResponse<'content>> {
+        self.error_responses
+            .get(&status_code)
+            .and_then(|responses| Self::get_scoped_response(responses, preferred_encodings, scope))
+    }
+
+    /// Returns the fallback with a glob scope matching the provided scope, along with the
+    /// prefix that it is certified for. If multiple glob scopes match, the fallback with the
+    /// most specific prefix is used.
+    fn get_glob_fallback_asset<'a>(
+        &'a self,
+        preferred_encodings: &[&str],
+        scope: &str,
+    ) -> Option<(&'a str, &'a CertifiedAssetResponse<'content>)> {
+        // only glob scopes whose prefix is a prefix of the scope can match it
+        std::iter::once("")
+            .chain(scope.match_indices('/').map(|(i, _)| &scope[..=i]))
+            .filter_map(|prefix| self.fallback_scope_matchers.get(prefix))
+            .flatten()
+            .filter(|(_, matcher)| matcher.is_match(scope))
+            .filter_map(|(glob_scope, _)| {
+                Self::get_scoped_response(&self.fallback_responses, preferred_encodings, glob_scope)
+                    .map(|response| (glob_scope.as_str(), response))
+            })
+            .max_by(|(a, a_response), (b, b_response)| {
+                glob_scope_prefix(a)
+                    .len()
+                    .cmp(&glob_scope_prefix(b).len())
+                    .then(a_response.priority.cmp(&b_response.priority))
+                    .then(b.cmp(a))
+            })
+            .map(|(glob_scope, response)| (glob_scope_prefix(glob_scope), response))
+    }
+
+    /// Returns true if a fallback or error page is certified for the provided scope in any
+    /// encoding, since the HTTP Gateway rejects responses for less specific scopes regardless of
+    /// the encodings that the client accepts.
+    fn has_scoped_response(&self, scope: &str) -> bool {
+        Self::has_response_in_any_encoding(&self.fallback_responses, scope)
+            || self
+                .error_responses
+                .values()
+                .any(|responses| Self::has_response_in_any_encoding(responses, scope))
+            || self
+                .fallback_scope_matchers
+                .get(scope)
+                .is_some_and(|matchers| {
+                    matchers.keys().any(|glob_scope| {
+                        Self::has_response_in_any_encoding(&self.fallback_responses, glob_scope)
+                    })
+                })
+    }
+
+    fn has_response_in_any_encoding(
+        responses: &HashMap<RequestKey, CertifiedAssetResponse<'content>>,
+        path: &str,
+    ) -> bool {
+        std::iter::once(None)
+            .chain(ASSET_ENCODINGS.map(Some))
+            .any(|encoding| {
+                responses.contains_key(&RequestKey::new(path, encoding_str(encoding), None))
+            })
+    }
+
+    fn get_scoped_response<'a>(
+        responses: &'a HashMap<RequestKey, CertifiedAssetResponse<'content>>,
+        preferred_encodings: &[&str],
+        scope: &str,
+    ) -> Option<&'a CertifiedAssetResponse<'content>> {
+        for encoding in preferred_encodings {
+            if let Some(response) =
+                responses.get(&RequestKey::new(scope, Some(encoding.to_string()), None))
+            {
+                return Some(response);
+            }
+        }
+
+        responses.get(&RequestKey::new(scope, None, None))
+    }
+
+    fn get_range_header<'a>(request: &'a HttpRequest) -> Option<&'a str> {
+        for (name, value) in request.headers().iter() {
+            if name.to_lowercase().eq(&http::header::RANGE.as_str()) {
+                return Some(value);
+            }
+        }
+        None
+    }
+
+    /// Orders the image formats that are explicitly accepted by the client by their quality
+    /// value, see [image_variants](AssetConfig::File::image_variants).
+    fn get_preferred_image_formats(request: &HttpRequest) -> Vec<AssetImageFormat> {
+        let Some(accept) = request.get_header(header::ACCEPT) else {
+            return vec![];
+        };
+
+        let mut image_formats = vec![];
+        for entry in accept.split(',') {
+            let mut params = entry.split(';');
+            let media_type = params.next().unwrap_or_default().trim();
+            let quality = quality_value(params);
+
+            for image_format in [AssetImageFormat::Avif, AssetImageFormat::Webp] {
+                if media_type.eq_ignore_ascii_case(image_format.content_type()) && quality > 0.0 {
+                    image_formats.push((image_format, quality));
+                }
+            }
+        }
+
+        // this `unwrap()` call is safe since quality values are never NaN
+        image_formats.sort_by(|(a_image_format, a_quality), (b_image_format, b_quality)| {
+            b_quality
+                .partial_cmp(a_quality)
+                .unwrap()
+                .then(a_image_format.cmp(b_image_format))
+        });
+
+        image_formats
+            .into_iter()
+            .map(|(image_format, _)| image_format)
+            .collect()
+    }
+
+    fn get_preferred_encodings<'a>(&self, request: &'a HttpRequest) -> Vec<&'a str> {
+        match request.get_header(header::ACCEPT_ENCODING) {
+            Some(accept_encoding) => self.prioritized_encodings(accept_encoding),
+            None => vec!["identity"],
+        }
+    }
+
+    /// Orders the encodings that are accepted by the client by their quality value first and by
+    /// the [encoding priority](AssetRouter::with_encoding_priority) of the router second. Encodings
+    /// with a quality value of `0` are excluded. The unencoded asset is acceptable unless it's
+    /// excluded by `identity;q=0`, or by `*;q=0` without a more specific entry for `identity`.
+    fn prioritized_encodings<'a>(&self, accept_encoding: &'a str) -> Vec<&'a str> {
+        let mut encodings = vec![];
+        let mut identity_quality = None;
+        let mut wildcard_quality = None;
+
+        for entry in accept_encoding.split(',') {
+            let mut params = entry.split(';');
+            let encoding = params.next().unwrap_or_default().trim();
+            let quality = quality_value(params);
+
+            match encoding {
+                "" => {}
+                "*" => wildcard_quality = Some(quality),
+                encoding if encoding.eq_ignore_ascii_case("identity") => {
+                    identity_quality = Some(quality)
+                }
+                encoding => {
+                    if let Some(priority) = self.encoding_quality(encoding) {
+                        encodings.push((encoding, quality, priority));
+                    }
+                }
+            }
+        }
+
+        // the wildcard applies to all encodings that are not listed explicitly
+        if let Some(wildcard_quality) = wildcard_quality {
+            for encoding in ["br", "zstd", "gzip", "deflate"] {
+                if encodings
+                    .iter()
+                    .any(|(listed_encoding, _, _)| listed_encoding.eq_ignore_ascii_case(encoding))
+                {
+                    continue;
+                }
+
+                if let Some(priority) = self.encoding_quality(encoding) {
+                    encodings.push((encoding, wildcard_quality, priority));
+                }
+            }
+        }
+
+        // the unencoded asset is implicitly acceptable with the same weight as the most
+        // preferred encoding, so the encoding priority of the router decides between them
+        let identity_quality = identity_quality.or(wildcard_quality).unwrap_or_else(|| {
+            encodings
+                .iter()
+                .map(|(_, quality, _)| *quality)
+                .filter(|quality| *quality > 0.0)
+                .reduce(f32::max)
+                .unwrap_or(1.0)
+        });
+        let identity_priority = self.encoding_quality("identity").unwrap_or(0.0);
+        encodings.push(("identity", identity_quality, identity_priority));
+
+        // these `unwrap()` calls are safe as long as the quality values and the values returned
+        // by `encoding_quality` are comparable (not NaN)
+        encodings.sort_by(|(_, a_quality, a_priority), (_, b_quality, b_priority)| {
+            b_quality
+                .partial_cmp(a_quality)
+                .unwrap()
+                .then(b_priority.partial_cmp(a_priority).unwrap())
+        });
+
+        encodings
+            .into_iter()
+            .filter(|(_, quality, _)| *quality > 0.0)
+            .map(|(encoding, _, _)| encoding)
+            .collect()
+    }
+
+    fn encoding_quality(&self, encoding: &str) -> Option<f32> {
+        let Some(encoding_priority) = &self.encoding_priority else {
+            return Some(Self::default_encoding_quality(encoding));
+        };
+
+        encoding_priority
+            .iter()
+            .position(|e| encoding.eq_ignore_ascii_case(&e.to_string()))
+            .map(|i| 1.0 - i as f32 / encoding_priority.len() as f32)
+    }
+
+    fn default_encoding_quality(encoding: &str) -> f32 {
+        if encoding.eq_ignore_ascii_case("br") {
+            return 1.0;
+        }
+
+        if encoding.eq_ignore_ascii_case("zstd") {
+            return 0.9;
+        }
+
+        if encoding.eq_ignore_ascii_case("gzip") {
+            return 0.8;
+        }
+
+        if encoding.eq_ignore_ascii_case("deflate") {
+            return 0.7;
+        }
+
+        if encoding.eq_ignore_ascii_case("identity") {
+            return 0.5;
+        }
+
+        0.6
+    }
+}
+
+impl Default for AssetRouter<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AssetEncodingMetadata, AssetFallbackConfig, AssetMetadata, AssetReader};
+    use assert_matches::assert_matches;
+    use ic_certification::{hash_tree::SubtreeLookupResult, HashTree};
+    use ic_http_certification::{
+        cel::DefaultFullCelExpressionBuilder, HeaderField, CERTIFICATE_HEADER_NAME,
+    };
+    use ic_response_verification::{
+        types::{FixedTimeSource, TimeConfig, VerificationPolicy},
+        verify_request_response_pair, CertificateHeader, ResponseVerificationError,
+    };
+    use ic_response_verification_test_utils::{
+        base64_decode, create_v2_certificate_fixture, get_current_timestamp, hash,
+        V2CertificateFixture,
+    };
+    use rand_chacha::rand_core::{RngCore, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+    use rstest::*;
+    use std::vec;
+
+    const ONE_CHUNK_ASSET_LEN: usize = ASSET_CHUNK_SIZE;
+    const TWO_CHUNKS_ASSET_LEN: usize = ASSET_CHUNK_SIZE + 1;
+    const SIX_CHUNKS_ASSET_LEN: usize = 5 * ASSET_CHUNK_SIZE + 12;
+    const TEN_CHUNKS_ASSET_LEN: usize = 10 * ASSET_CHUNK_SIZE;
+
+    const ONE_CHUNK_ASSET_NAME: &str = "long_asset_one_chunk";
+    const TWO_CHUNKS_ASSET_NAME: &str = "long_asset_two_chunks";
+    const SIX_CHUNKS_ASSET_NAME: &str = "long_asset_six_chunks";
+    const TEN_CHUNKS_ASSET_NAME: &str = "long_asset_ten_chunks";
+
+    #[rstest]
+    #[case(0, None)]
+    #[case(ASSET_CHUNK_SIZE, None)]
+    #[case(ASSET_CHUNK_SIZE*4, None)]
+    #[case(0, Some(0))]
+    #[case(100, Some(2000))]
+    #[case(10_000, Some(300_000))]
+    #[case(ASSET_CHUNK_SIZE, Some(2 * ASSET_CHUNK_SIZE - 1))]
+    fn should_parse_range_header_str(#[case] range_begin: usize, #[case] range_end: Option<usize>) {
+        let input = if let Some(range_end) = range_end {
+            format!("bytes={}-{}", range_begin, range_end)
+        } else {
+            format!("bytes={}-", range_begin)
+        };
+        let result = parse_range_header_str(&input);
+        let output = result.unwrap_or_else(|e| panic!("failed parsing '{input}': {:?}", e));
+        assert_eq!(
+            RangeRequestValues {
+                range_begin,
+                range_end
+            },
+            output
+        );
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("byte=1-2")]
+    #[case("bites=2-4")]
+    #[case("bytes 7-11")]
+    #[case("bytes=12345")]
+    #[case("something else")]
+    #[case("bytes=-5-19")]
+    fn should_fail_parse_range_header_str_on_invalid_input(#[case] malformed_input: &str) {
+        let result = parse_range_header_str(malformed_input);
+        assert_matches!(result, Err(e) if e.to_string().contains("Invalid Range header"));
+    }
+
+    #[rstest]
+    #[case("bytes=100-end")]
+    #[case("bytes=dead-beef")]
+    fn should_fail_parse_range_header_str_on_malformed_input(#[case] malformed_input: &str) {
+        let result = parse_range_header_str(malformed_input);
+        assert_matches!(result, Err(e) if e.to_string().contains("Malformed range_"));
+    }
+
+    #[rstest]
+    #[case("bytes=100-20")]
+    #[case("bytes=20-19")]
+    fn should_fail_parse_range_header_str_on_invalid_values(#[case] malformed_input: &str) {
+        let result = parse_range_header_str(malformed_input);
+        assert_matches!(result, Err(e) if e.to_string().contains("Invalid values in Range header"));
+    }
+
+    #[rstest]
+    #[case("/")]
+    #[case("https://internetcomputer.org/")]
+    fn test_index_html(mut asset_router: AssetRouter, #[case] req_url: &str) {
+        let request = HttpRequest::get(req_url).build();
+
+        let mut expected_response = expected_index_html_response();
+
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        let (witness, expr_path) = extract_witness_expr_path(&response);
+        add_v2_certificate_header(
+            &data_certificate(),
+            &mut expected_response,
+            &witness,
+            &expr_path,
+        );
+
+        assert_eq!(expr_path, vec!["http_expr", "", "<$>"]);
+        assert_matches!(
+            witness.lookup_subtree(&expr_path),
+            SubtreeLookupResult::Found(_)
+        );
+        assert_eq!(response, expected_response);
+
+        asset_router
+            .delete_assets(
+                vec![Asset::new("index.html", index_html_body())],
+                vec![index_html_config()],
+            )
+            .unwrap();
+
+        let result = asset_router.serve_asset(&data_certificate(), &request);
+        assert_matches!(
+            result,
+            Err(AssetCertificationError::NoAssetMatchingRequestUrl {
+                request_url,
+             }) if request_url == request.get_path().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_one_chunk_long_asset_served_in_full() {
+        let asset_name = ONE_CHUNK_ASSET_NAME;
+        let long_asset_router =
+            long_asset_router_with_params(&[asset_name], &[AssetEncoding::Identity]);
+        let req_url = format!("/{asset_name}");
+        let asset_body = long_asset_body(asset_name);
+        // Request the entire "one-chunk"-asset, should obtain it in full.
+        let request = HttpRequest::get(&req_url).build();
+        let mut expected_response = build_200_response(
+            asset_body,
+            asset_cel_expr(),
+            vec![
+                (
+                    "cache-control".to_string(),
+                    "public, no-cache, no-store".to_string(),
+                ),
+                ("vary".to_string(), "accept-encoding".to_string()),
+                ("content-type".to_string(), "text/html".to_string()),
+            ],
+        );
+
+        let response = long_asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        let (witness, expr_path) = extract_witness_expr_path(&response);
+        add_v2_certificate_header(
+            &data_certificate(),
+            &mut expected_response,
+            &witness,
+            &expr_path,
+        );
+
+        assert_eq!(expr_path, vec!["http_expr", &req_url[1..], "<$>"]);
+        assert_matches!(
+            witness.lookup_subtree(&expr_path),
+            SubtreeLookupResult::Found(_)
+        );
+        assert_eq!(response, expected_response);
+    }
+
+    #[rstest]
+    #[case(TWO_CHUNKS_ASSET_NAME)]
+    #[case(SIX_CHUNKS_ASSET_NAME)]
+    #[case(TEN_CHUNKS_ASSET_NAME)]
+    fn test_long_asset_served_in_chunks(#[case] asset_name: &str) {
+        let long_asset_router =
+            long_asset_router_with_params(&[asset_name], &[AssetEncoding::Identity]);
+        let req_url = format!("/{asset_name}");
+        let asset_body = long_asset_body(asset_name);
+        let asset_len = asset_body.len();
+        // Request the entire asset, should obtain the first chunk.
+        let request = HttpRequest::get(&req_url).build();
+        let mut expected_response = build_206_response(
+            asset_body[0..ASSET_CHUNK_SIZE].to_vec(),
+            asset_range_chunk_cel_expr(),
+            vec![
+                (
+                    "cache-control".to_string(),
+                    "public, no-cache, no-store".to_string(),
+                ),
+                ("vary".to_string(), "accept-encoding".to_string()),
+                ("content-type".to_string(), "text/html".to_string()),
+                (
+                    "content-range".to_string(),
+                    format!("bytes 0-{}/{}", ASSET_CHUNK_SIZE - 1, asset_len),
+                ),
+            ],
+        );
+
+        let response = long_asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        let (witness, expr_path) = extract_witness_expr_path(&response);
+        add_v2_certificate_header(
+            &data_certificate(),
+            &mut expected_response,
+            &witness,
+            &expr_path,
+        );
+
+        assert_eq!(expr_path, vec!["http_expr", &req_url[1..], "<$>"]);
+        assert_matches!(
+            witness.lookup_subtree(&expr_path),
+            SubtreeLookupResult::Found(_)
+        );
+        assert_eq!(response, expected_response);
+
+        // Request the subsequent chunks, should obtain them.
+        let expected_number_of_chunks =
+            (asset_len as f32 / ASSET_CHUNK_SIZE as f32).ceil() as usize;
+        let mut asset_len_so_far = response.body().len();
+        let mut number_of_chunks_so_far = 1;
+        while asset_len_so_far < asset_len {
+            let chunk_request = HttpRequest::get(&req_url)
+                .with_headers(vec![(
+                    "range".to_string(),
+                    format!("bytes={}-", asset_len_so_far),
+                )])
+                .build();
+            let expected_range_end = cmp::min(asset_len_so_far + ASSET_CHUNK_SIZE, asset_len) - 1;
+            let mut expected_response = build_206_response(
+                asset_body[asset_len_so_far..=expected_range_end].to_vec(),
+                asset_range_chunk_cel_expr(),
+                vec![
+                    (
+                        "cache-control".to_string(),
+                        "public, no-cache, no-store".to_string(),
+                    ),
+                    ("vary".to_string(), "accept-encoding".to_string()),
+                    ("content-type".to_string(), "text/html".to_string()),
+                    (
+                        "content-range".to_string(),
+                        format!(
+                            "bytes {}-{}/{}",
+                            asset_len_so_far, expected_range_end, asset_len
+                        ),
+                    ),
+                ],
+            );
+            let response = long_asset_router
+                .serve_asset(&data_certificate(), &chunk_request)
+                .unwrap();
+            let (witness, expr_path) = extract_witness_expr_path(&response);
+            assert_matches!(
+                witness.lookup_subtree(&expr_path),
+                SubtreeLookupResult::Found(_)
+            );
+            add_v2_certificate_header(
+                &data_certificate(),
+                &mut expected_response,
+                &witness,
+                &expr_path,
+            );
+            assert_eq!(response, expected_response);
+            asset_len_so_far += response.body().len();
+            number_of_chunks_so_far += 1;
+        }
+        assert_eq!(number_of_chunks_so_far, expected_number_of_chunks)
+    }
+
+    #[rstest]
+    #[case(TWO_CHUNKS_ASSET_NAME)]
+    #[case(SIX_CHUNKS_ASSET_NAME)]
+    #[case(TEN_CHUNKS_ASSET_NAME)]
+    fn test_long_asset_deletion_removes_chunks(#[case] asset_name: &str) {
+        let mut long_asset_router =
+            long_asset_router_with_params(&[asset_name], &[AssetEncoding::Identity]);
+        let req_url = format!("/{asset_name}");
+        let asset_body = long_asset_body(asset_name);
+        let asset_len = asset_body.len();
+        let mut all_requests = vec![];
+        // Request the entire asset and the chunks, all should succeed.
+        // First the asset...
+        let request = HttpRequest::get(&req_url).build();
+        let response = long_asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        let (witness, expr_path) = extract_witness_expr_path(&response);
+
+        assert_eq!(expr_path, vec!["http_expr", &req_url[1..], "<$>"]);
+        assert_matches!(
+            witness.lookup_subtree(&expr_path),
+            SubtreeLookupResult::Found(_)
+        );
+        assert_eq!(response.status_code(), StatusCode::PARTIAL_CONTENT);
+        all_requests.push(request);
+
+        // ... then the subsequent chunks.
+        let expected_number_of_chunks =
+            (asset_len as f32 / ASSET_CHUNK_SIZE as f32).ceil() as usize;
+        let mut asset_len_so_far = response.body().len();
+        let mut number_of_chunks_so_far = 1;
+        while asset_len_so_far < asset_len {
+            let chunk_request = HttpRequest::get(&req_url)
+                .with_headers(vec![(
+                    "range".to_string(),
+                    format!("bytes={}-", asset_len_so_far),
+                )])
+                .build();
+            let response = long_asset_router
+                .serve_asset(&data_certificate(), &chunk_request)
+                .unwrap();
+            let (witness, expr_path) = extract_witness_expr_path(&response);
+            assert_matches!(
+                witness.lookup_subtree(&expr_path),
+                SubtreeLookupResult::Found(_)
+            );
+            assert_eq!(response.status_code(), StatusCode::PARTIAL_CONTENT);
+            asset_len_so_far += response.body().len();
+            number_of_chunks_so_far += 1;
+            all_requests.push(chunk_request);
+        }
+        assert_eq!(number_of_chunks_so_far, expected_number_of_chunks);
+        assert_eq!(all_requests.len(), expected_number_of_chunks);
+
+        // Delete the asset.
+        long_asset_router
+            .delete_assets(
+                vec![Asset::new(&req_url, asset_body)],
+                vec![long_asset_config(asset_name)],
+            )
+            .expect("Asset deletion failed");
+
+        // Re-request the asset and the chunks, all should fail.
+        for request in all_requests {
+            let result = long_asset_router.serve_asset(&data_certificate(), &request);
+            assert_matches!(
+                result,
+                Err(AssetCertificationError::NoAssetMatchingRequestUrl {
+                    request_url,
+                 }) if request_url == request.get_path().unwrap()
+            );
+        }
+    }
+
+    #[rstest]
+    #[case(SIX_CHUNKS_ASSET_NAME, "deflate", AssetEncoding::Deflate)]
+    #[case(SIX_CHUNKS_ASSET_NAME, "deflate, identity", AssetEncoding::Deflate)]
+    #[case(SIX_CHUNKS_ASSET_NAME, "gzip", AssetEncoding::Gzip)]
+    #[case(SIX_CHUNKS_ASSET_NAME, "gzip, identity", AssetEncoding::Gzip)]
+    #[case(SIX_CHUNKS_ASSET_NAME, "gzip, deflate", AssetEncoding::Gzip)]
+    #[case(SIX_CHUNKS_ASSET_NAME, "gzip, deflate, identity", AssetEncoding::Gzip)]
+    #[case(SIX_CHUNKS_ASSET_NAME, "br", AssetEncoding::Brotli)]
+    #[case(
+        SIX_CHUNKS_ASSET_NAME,
+        "br, gzip, deflate, identity",
+        AssetEncoding::Brotli
+    )]
+    #[case(
+        SIX_CHUNKS_ASSET_NAME,
+        "gzip, deflate, identity, br",
+        AssetEncoding::Brotli
+    )]
+    fn test_encoded_long_asset_served_in_encoded_chunks(
+        #[case] asset_name: &str,
+        #[case] accept_encoding: &str,
+        #[case] expected_encoding: AssetEncoding,
+    ) {
+        let (_, expected_encoding_suffix) = expected_encoding.default_config();
+        let long_asset_router = long_asset_router_with_params(
+            &[asset_name],
+            &[AssetEncoding::Identity, expected_encoding],
+        );
+        let req_url = format!("/{asset_name}");
+        let encoded_asset_name = format!("{asset_name}{expected_encoding_suffix}");
+        let asset_body = long_asset_body(&encoded_asset_name);
+        let asset_len = asset_body.len();
+
+        let request = HttpRequest::get(&req_url)
+            .with_headers(vec![(
+                "accept-encoding".to_string(),
+                accept_encoding.to_string(),
+            )])
+            .build();
+        let mut expected_response = build_206_response(
+            asset_body[0..ASSET_CHUNK_SIZE].to_vec(),
+            encoded_range_chunk_asset_cel_expr(),
+            vec![
+                (
+                    "cache-control".to_string(),
+                    "public, no-cache, no-store".to_string(),
+                ),
+                ("vary".to_string(), "accept-encoding".to_string()),
+                ("content-type".to_string(), "text/html".to_string()),
+                (
+                    "content-encoding".to_string(),
+                    expected_encoding.to_string(),
+                ),
+                (
+                    "content-range".to_string(),
+                    format!("bytes 0-{}/{}", ASSET_CHUNK_SIZE - 1, asset_len),
+                ),
+            ],
+        );
+        let response = long_asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        let (witness, expr_path) = extract_witness_expr_path(&response);
+        add_v2_certificate_header(
+            &data_certificate(),
+            &mut expected_response,
+            &witness,
+            &expr_path,
+        );
+
+        assert_eq!(
+            expr_path,
+            HttpCertificationPath::exact(req_url.clone()).to_expr_path()
+        );
+        assert_matches!(
+            witness.lookup_subtree(&expr_path),
+            SubtreeLookupResult::Found(_)
+        );
+        assert_eq!(response, expected_response);
+
+        // Request the subsequent chunks, should obtain them.
+        let expected_number_of_chunks =
+            (asset_len as f32 / ASSET_CHUNK_SIZE as f32).ceil() as usize;
+        let mut asset_len_so_far = response.body().len();
+        let mut number_of_chunks_so_far = 1;
+        while asset_len_so_far < asset_len {
+            let chunk_request = HttpRequest::get(&req_url)
+                .with_headers(vec![
+                    ("range".to_string(), format!("bytes={}-", asset_len_so_far)),
+                    ("accept-encoding".to_string(), accept_encoding.to_string()),
+                ])
+                .build();
+            let expected_range_end = cmp::min(asset_len_so_far + ASSET_CHUNK_SIZE, asset_len) - 1;
+            let mut expected_response = build_206_response(
+                asset_body[asset_len_so_far..=expected_range_end].to_vec(),
+                encoded_range_chunk_asset_cel_expr(),
+                vec![
+                    (
+                        "cache-control".to_string(),
+                        "public, no-cache, no-store".to_string(),
+                    ),
+                    ("vary".to_string(), "accept-encoding".to_string()),
+                    ("content-type".to_string(), "text/html".to_string()),
+                    (
+                        "content-encoding".to_string(),
+                        expected_encoding.to_string(),
+                    ),
+                    (
+                        "content-range".to_string(),
+                        format!(
+                            "bytes {}-{}/{}",
+                            asset_len_so_far, expected_range_end, asset_len
+                        ),
+                    ),
+                ],
+            );
+            let response = long_asset_router
+                .serve_asset(&data_certificate(), &chunk_request)
+                .unwrap();
+            let (witness, expr_path) = extract_witness_expr_path(&response);
+            assert_matches!(
+                witness.lookup_subtree(&expr_path),
+                SubtreeLookupResult::Found(_)
+            );
+            add_v2_certificate_header(
+                &data_certificate(),
+                &mut expected_response,
+                &witness,
+                &expr_path,
+            );
+            assert_eq!(response, expected_response);
+            asset_len_so_far += response.body().len();
+            number_of_chunks_so_far += 1;
+        }
+        assert_eq!(number_of_chunks_so_far, expected_number_of_chunks)
+    }
+
+    #[rstest]
+    #[case(TWO_CHUNKS_ASSET_NAME, AssetEncoding::Brotli)]
+    #[case(TWO_CHUNKS_ASSET_NAME, AssetEncoding::Gzip)]
+    #[case(TWO_CHUNKS_ASSET_NAME, AssetEncoding::Deflate)]
+    fn test_encoded_long_asset_deletion_removes_encoded_chunks(
+        #[case] asset_name: &str,
+        #[case] encoding: AssetEncoding,
+    ) {
+        let (_, encoding_suffix) = encoding.default_config();
+        let mut long_asset_router =
+            long_asset_router_with_params(&[asset_name], &[AssetEncoding::Identity, encoding]);
+        let req_url = format!("/{asset_name}");
+        let encoded_asset_name = format!("{asset_name}{encoding_suffix}");
+        let encoded_asset_body = long_asset_body(&encoded_asset_name);
+        let asset_len = encoded_asset_body.len();
+        let mut all_requests = vec![];
+        // Request the entire asset and the chunks, all should succeed.
+        // First the asset...
+        let request = HttpRequest::get(&req_url)
+            .with_headers(vec![("accept-encoding".to_string(), encoding.to_string())])
+            .build();
+        let response = long_asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        let (witness, expr_path) = extract_witness_expr_path(&response);
+
+        assert_eq!(expr_path, vec!["http_expr", &req_url[1..], "<$>"]);
+        assert_matches!(
+            witness.lookup_subtree(&expr_path),
+            SubtreeLookupResult::Found(_)
+        );
+        assert_eq!(response.status_code(), StatusCode::PARTIAL_CONTENT);
+        all_requests.push(request);
+
+        // ... then the subsequent chunks.
+        let expected_number_of_chunks =
+            (asset_len as f32 / ASSET_CHUNK_SIZE as f32).ceil() as usize;
+        let mut asset_len_so_far = response.body().len();
+        let mut number_of_chunks_so_far = 1;
+        while asset_len_so_far < asset_len {
+            let chunk_request = HttpRequest::get(&req_url)
+                .with_headers(vec![
+                    ("range".to_string(), format!("bytes={asset_len_so_far}-")),
+                    ("accept-encoding".to_string(), encoding.to_string()),
+                ])
+                .build();
+            let response = long_asset_router
+                .serve_asset(&data_certificate(), &chunk_request)
+                .unwrap();
+            let (witness, expr_path) = extract_witness_expr_path(&response);
+            assert_matches!(
+                witness.lookup_subtree(&expr_path),
+                SubtreeLookupResult::Found(_)
+            );
+            assert_eq!(response.status_code(), StatusCode::PARTIAL_CONTENT);
+            asset_len_so_far += response.body().len();
+            number_of_chunks_so_far += 1;
+            all_requests.push(chunk_request);
+        }
+        assert_eq!(number_of_chunks_so_far, expected_number_of_chunks);
+        assert_eq!(all_requests.len(), expected_number_of_chunks);
+
+        // Delete the asset.
+        long_asset_router
+            .delete_assets(
+                vec![
+                    Asset::new(&req_url, long_asset_body(asset_name)),
+                    Asset::new(&format!("/{encoded_asset_name}"), encoded_asset_body),
+                ],
+                vec![long_asset_config(&req_url)],
+            )
+            .expect("Asset deletion failed");
+
+        // Re-request the asset and the chunks, all should fail.
+        for request in all_requests {
+            let result = long_asset_router.serve_asset(&data_certificate(), &request);
+            assert_matches!(
+                result,
+                Err(AssetCertificationError::NoAssetMatchingRequestUrl {
+                    request_url,
+                 }) if request_url == request.get_path().unwrap()
+            );
+        }
+    }
+
+    #[rstest]
+    #[case(index_html_zz_body(), "/", "deflate", "deflate")]
+    #[case(index_html_zz_body(), "/", "deflate, identity", "deflate")]
+    #[case(index_html_gz_body(), "/", "gzip", "gzip")]
+    #[case(index_html_gz_body(), "/", "gzip, identity", "gzip")]
+    #[case(index_html_gz_body(), "/", "gzip, deflate", "gzip")]
+    #[case(index_html_gz_body(), "/", "gzip, deflate, identity", "gzip")]
+    #[case(index_html_br_body(), "/", "br", "br")]
+    #[case(index_html_br_body(), "/", "br, gzip, deflate, identity", "br")]
+    #[case(index_html_br_body(), "/", "gzip, deflate, identity, br", "br")]
+    #[case(index_html_zz_body(), "/index.html", "deflate", "deflate")]
+    #[case(index_html_zz_body(), "/index.html", "deflate, identity", "deflate")]
+    #[case(index_html_gz_body(), "/index.html", "gzip", "gzip")]
+    #[case(index_html_gz_body(), "/index.html", "gzip, identity", "gzip")]
+    #[case(index_html_gz_body(), "/index.html", "gzip, deflate", "gzip")]
+    #[case(index_html_gz_body(), "/index.html", "gzip, deflate, identity", "gzip")]
+    #[case(index_html_br_body(), "/index.html", "br", "br")]
+    #[case(
+        index_html_br_body(),
+        "/index.html",
+        "br, gzip, deflate, identity",
+        "br"
+    )]
+    #[case(
+        index_html_br_body(),
+        "/index.html",
+        "gzip, deflate, identity, br",
+        "br"
+    )]
+    fn test_encoded_index_html(
+        #[case] expected_body: Vec<u8>,
+        #[case] req_url: &str,
+        #[case] accept_encoding: &str,
+        #[case] expected_encoding: &str,
+        mut asset_router: AssetRouter,
+    ) {
+        let request = HttpRequest::get(req_url)
+            .with_headers(vec![(
+                "accept-encoding".to_string(),
+                accept_encoding.to_string(),
+            )])
+            .build();
+        let mut expected_response = build_200_response(
+            expected_body,
+            encoded_asset_cel_expr(),
+            vec![
+                (
+                    "cache-control".to_string(),
+                    "public, no-cache, no-store".to_string(),
+                ),
+                ("vary".to_string(), "accept-encoding".to_string()),
+                ("content-type".to_string(), "text/html".to_string()),
+                (
+                    "content-encoding".to_string(),
+                    expected_encoding.to_string(),
+                ),
+            ],
+        );
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        let (witness, expr_path) = extract_witness_expr_path(&response);
+        add_v2_certificate_header(
+            &data_certificate(),
+            &mut expected_response,
+            &witness,
+            &expr_path,
+        );
+
+        assert_eq!(
+            expr_path,
+            HttpCertificationPath::exact(req_url).to_expr_path()
+        );
+        assert_matches!(
+            witness.lookup_subtree(&expr_path),
+            SubtreeLookupResult::Found(_)
+        );
+        assert_eq!(response, expected_response);
+
+        asset_router
+            .delete_assets(
+                vec![
+                    Asset::new("index.html", index_html_body()),
+                    Asset::new("index.html.gz", index_html_gz_body()),
+                    Asset::new("index.html.zz", index_html_zz_body()),
+                    Asset::new("index.html.br", index_html_br_body()),
+                ],
+                vec![index_html_config()],
+            )
+            .unwrap();
+
+        let result = asset_router.serve_asset(&data_certificate(), &request);
+        assert_matches!(
+            result,
+            Err(AssetCertificationError::NoAssetMatchingRequestUrl {
+                request_url,
+             }) if request_url == req_url
+        );
+    }
+
+    #[rstest]
+    #[case(index_html_zz_body(), "/something", "deflate", "deflate")]
+    #[case(index_html_zz_body(), "/something", "deflate, identity", "deflate")]
+    #[case(index_html_gz_body(), "/something", "gzip", "gzip")]
+    #[case(index_html_gz_body(), "/something", "gzip, identity", "gzip")]
+    #[case(index_html_gz_body(), "/something", "gzip, deflate", "gzip")]
+    #[case(index_html_gz_body(), "/something", "gzip, deflate, identity", "gzip")]
+    #[case(index_html_br_body(), "/something", "br", "br")]
+    #[case(
+        index_html_br_body(),
+        "/something",
+        "br, gzip, deflate, identity",
+        "br"
+    )]
+    #[case(
+        index_html_br_body(),
+        "/something",
+        "gzip, deflate, identity, br",
+        "br"
+    )]
+    #[case(index_html_zz_body(), "/assets/css/app.css", "deflate", "deflate")]
+    #[case(
+        index_html_zz_body(),
+        "/assets/css/app.css",
+        "deflate, identity",
+        "deflate"
+    )]
+    #[case(index_html_gz_body(), "/assets/css/app.css", "gzip", "gzip")]
+    #[case(index_html_gz_body(), "/assets/css/app.css", "gzip, identity", "gzip")]
+    #[case(index_html_gz_body(), "/assets/css/app.css", "gzip, deflate", "gzip")]
+    #[case(
+        index_html_gz_body(),
+        "/assets/css/app.css",
+        "gzip, deflate, identity",
+        "gzip"
+    )]
+    #[case(index_html_br_body(), "/assets/css/app.css", "br", "br")]
+    #[case(
+        index_html_br_body(),
+        "/assets/css/app.css",
+        "br, gzip, deflate, identity",
+        "br"
+    )]
+    #[case(
+        index_html_br_body(),
+        "/assets/css/app.css",
+        "gzip, deflate, identity, br",
+        "br"
+    )]
+    fn test_encoded_index_html_fallback(
+        #[case] expected_body: Vec<u8>,
+        #[case] req_url: &str,
+        #[case] accept_encoding: &str,
+        #[case] expected_encoding: &str,
+        mut asset_router: AssetRouter,
+    ) {
+        let request = HttpRequest::get(req_url)
+            .with_headers(vec![(
+                "accept-encoding".to_string(),
+                accept_encoding.to_string(),
+            )])
+            .build();
+        let mut expected_response = build_200_response(
+            expected_body,
+            encoded_asset_cel_expr(),
+            vec![
+                (
+                    "cache-control".to_string(),
+                    "public, no-cache, no-store".to_string(),
+                ),
+                ("vary".to_string(), "accept-encoding".to_string()),
+                ("content-type".to_string(), "text/html".to_string()),
+                (
+                    "content-encoding".to_string(),
+                    expected_encoding.to_string(),
+                ),
+            ],
+        );
+
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        let (witness, expr_path) = extract_witness_expr_path(&response);
+        add_v2_certificate_header(
+            &data_certificate(),
+            &mut expected_response,
+            &witness,
+            &expr_path,
+        );
+
+        let requested_expr_path = HttpCertificationPath::exact(req_url).to_expr_path();
+        assert_eq!(expr_path, vec!["http_expr", "", "<*>"]);
+        assert_matches!(
+            witness.lookup_subtree(&expr_path),
+            SubtreeLookupResult::Found(_)
+        );
+        assert_matches!(
+            witness.lookup_subtree(&requested_expr_path),
+            SubtreeLookupResult::Absent
+        );
+        assert_eq!(response, expected_response);
+
+        asset_router
+            .delete_assets(
+                vec![
+                    Asset::new("index.html", index_html_body()),
+                    Asset::new("index.html.gz", index_html_gz_body()),
+                    Asset::new("index.html.zz", index_html_zz_body()),
+                    Asset::new("index.html.br", index_html_br_body()),
+                ],
+                vec![index_html_config()],
+            )
+            .unwrap();
+
+        let result = asset_router.serve_asset(&data_certificate(), &request);
+        assert_matches!(
+            result,
+            Err(AssetCertificationError::NoAssetMatchingRequestUrl {
+                request_url,
+             }) if request_url == req_url
+        );
+    }
+
+    #[rstest]
+    #[case("/something", "/something")]
+    #[case("https://internetcomputer.org/something", "/something")]
+    fn test_index_html_root_fallback(
+        mut asset_router: AssetRouter,
+        #[case] req_url: &str,
+        #[case] req_path: &str,
+    ) {
+        let mut expected_response = expected_index_html_response();
+
+        let request = HttpRequest::get(req_url).build();
+        let requested_expr_path = HttpCertificationPath::exact(req_path).to_expr_path();
+
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        let (witness, expr_path) = extract_witness_expr_path(&response);
+        add_v2_certificate_header(
+            &data_certificate(),
+            &mut expected_response,
+            &witness,
+            &expr_path,
+        );
+
+        assert_eq!(expr_path, vec!["http_expr", "", "<*>"]);
+        assert_matches!(
+            witness.lookup_subtree(&expr_path),
+            SubtreeLookupResult::Found(_)
+        );
+        assert_matches!(
+            witness.lookup_subtree(&requested_expr_path),
+            SubtreeLookupResult::Absent
+        );
+        assert_eq!(response, expected_response);
+
+        asset_router
+            .delete_assets(
+                vec![
+                    Asset::new("index.html", index_html_body()),
+                    Asset::new("index.html.gz", index_html_gz_body()),
+                    Asset::new("index.html.zz", index_html_zz_body()),
+                    Asset::new("index.html.br", index_html_br_body()),
+                ],
+                vec![index_html_config()],
+            )
+            .unwrap();
+
+        let result = asset_router.serve_asset(&data_certificate(), &request);
+        assert_matches!(
+            result,
+            Err(AssetCertificationError::NoAssetMatchingRequestUrl {
+                request_url,
+             }) if request_url == req_path
+        );
+    }
+
+    #[rstest]
+    #[case("/assets/css/app.css", "/assets/css/app.css")]
+    #[case(
+        "https://internetcomputer.org/assets/css/app.css",
+        "/assets/css/app.css"
+    )]
+    fn test_index_html_nested_fallback(
+        mut asset_router: AssetRouter,
+        #[case] req_url: &str,
+        #[case] req_path: &str,
+    ) {
+        let mut expected_response = expected_index_html_response();
+
+        let request = HttpRequest::get(req_url).build();
+        let requested_expr_path = HttpCertificationPath::exact(req_path).to_expr_path();
+
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        let (witness, expr_path) = extract_witness_expr_path(&response);
+        add_v2_certificate_header(
+            &data_certificate(),
+            &mut expected_response,
+            &witness,
+            &expr_path,
+        );
+
+        assert_eq!(expr_path, vec!["http_expr", "", "<*>"]);
+        assert_matches!(
+            witness.lookup_subtree(&expr_path),
+            SubtreeLookupResult::Found(_)
+        );
+        assert_matches!(
+            witness.lookup_subtree(&requested_expr_path),
+            SubtreeLookupResult::Absent
+        );
+        assert_eq!(response, expected_response);
+
+        asset_router
+            .delete_assets(
+                vec![
+                    Asset::new("index.html", index_html_body()),
+                    Asset::new("index.html.gz", index_html_gz_body()),
+                    Asset::new("index.html.zz", index_html_zz_body()),
+                    Asset::new("index.html.br", index_html_br_body()),
+                ],
+                vec![index_html_config()],
+            )
+            .unwrap();
+
+        let result = asset_router.serve_asset(&data_certificate(), &request);
+        assert_matches!(
+            result,
+            Err(AssetCertificationError::NoAssetMatchingRequestUrl {
+                request_url,
+             }) if request_url == req_path
+        );
+    }
+
+    #[rstest]
+    #[case("/css/app-ba74b708.css")]
+    #[case("https://internetcomputer.org/css/app-ba74b708.css")]
+    fn text_app_css(mut asset_router: AssetRouter, #[case] req_url: &str) {
+        let request = HttpRequest::get(req_url).build();
+        let mut expected_response = build_200_response(
+            app_css_body(),
+            asset_cel_expr(),
+            vec![
+                (
+                    "cache-control".to_string(),
+                    "public, max-age=31536000, immutable".to_string(),
+                ),
+                ("vary".to_string(), "accept-encoding".to_string()),
+                ("content-type".to_string(), "text/css".to_string()),
+            ],
+        );
+
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        let (witness, expr_path) = extract_witness_expr_path(&response);
+        add_v2_certificate_header(
+            &data_certificate(),
+            &mut expected_response,
+            &witness,
+            &expr_path,
+        );
+
+        assert_eq!(
+            expr_path,
+            vec!["http_expr", "css", "app-ba74b708.css", "<$>"]
+        );
+        assert_matches!(
+            witness.lookup_subtree(&expr_path),
+            SubtreeLookupResult::Found(_)
+        );
+        assert_eq!(response, expected_response);
+
+        asset_router
+            .delete_assets(
+                vec![Asset::new("css/app-ba74b708.css", app_css_body())],
+                vec![css_config()],
+            )
+            .unwrap();
+        let mut expected_response = build_response(
+            StatusCode::NOT_FOUND,
+            not_found_html_body(),
+            asset_cel_expr(),
+            vec![
+                (
+                    "cache-control".to_string(),
+                    "public, no-cache, no-store".to_string(),
+                ),
+                ("vary".to_string(), "accept-encoding".to_string()),
+                ("content-type".to_string(), "text/html".to_string()),
+            ],
+        );
+
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        let (witness, expr_path) = extract_witness_expr_path(&response);
+        add_v2_certificate_header(
+            &data_certificate(),
+            &mut expected_response,
+            &witness,
+            &expr_path,
+        );
+
+        assert_eq!(expr_path, vec!["http_expr", "css", "<*>"]);
+        assert_matches!(
+            witness.lookup_subtree(&expr_path),
+            SubtreeLookupResult::Found(_)
+        );
+        assert_eq!(response, expected_response);
+
+        asset_router
+            .delete_assets(
+                vec![
+                    Asset::new("not-found.html", not_found_html_body()),
+                    Asset::new("not-found.html.gz", not_found_html_gz_body()),
+                    Asset::new("not-found.html.zz", not_found_html_zz_body()),
+                    Asset::new("not-found.html.br", not_found_html_br_body()),
+                ],
+                vec![not_found_html_config()],
+            )
+            .unwrap();
+        let mut expected_response = expected_index_html_response();
+
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        let (witness, expr_path) = extract_witness_expr_path(&response);
+        add_v2_certificate_header(
+            &data_certificate(),
+            &mut expected_response,
+            &witness,
+            &expr_path,
+        );
+
+        assert_eq!(expr_path, vec!["http_expr", "", "<*>"]);
+        assert_matches!(
+            witness.lookup_subtree(&expr_path),
+            SubtreeLookupResult::Found(_)
+        );
+        assert_eq!(response, expected_response);
+
+        asset_router
+            .delete_assets(
+                vec![
+                    Asset::new("index.html", index_html_body()),
+                    Asset::new("index.html.gz", index_html_gz_body()),
+                    Asset::new("index.html.zz", index_html_zz_body()),
+                    Asset::new("index.html.br", index_html_br_body()),
+                ],
+                vec![index_html_config()],
+            )
+            .unwrap();
+
+        let result = asset_router.serve_asset(&data_certificate(), &request);
+        assert_matches!(
+            result,
+            Err(AssetCertificationError::NoAssetMatchingRequestUrl {
+                request_url,
+             }) if request_url == request.get_path().unwrap()
+        );
+    }
+
+    #[rstest]
+    #[case("/css/core-8d4jhgy2.js")]
+    #[case("https://internetcomputer.org/css/core-8d4jhgy2.js")]
+    fn test_not_found_css(mut asset_router: AssetRouter, #[case] req_url: &str) {
+        let request = HttpRequest::get(req_url).build();
+        let mut expected_response = build_response(
+            StatusCode::NOT_FOUND,
+            not_found_html_body(),
+            asset_cel_expr(),
+            vec![
+                (
+                    "cache-control".to_string(),
+                    "public, no-cache, no-store".to_string(),
+                ),
+                ("vary".to_string(), "accept-encoding".to_string()),
+                ("content-type".to_string(), "text/html".to_string()),
+            ],
+        );
+
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        let (witness, expr_path) = extract_witness_expr_path(&response);
+        add_v2_certificate_header(
+            &data_certificate(),
+            &mut expected_response,
+            &witness,
+            &expr_path,
+        );
+
+        assert_eq!(expr_path, vec!["http_expr", "css", "<*>"]);
+        assert_matches!(
+            witness.lookup_subtree(&expr_path),
+            SubtreeLookupResult::Found(_)
+        );
+        assert_eq!(response, expected_response);
+
+        asset_router
+            .delete_assets(
+                vec![
+                    Asset::new("not-found.html", not_found_html_body()),
+                    Asset::new("not-found.html.gz", not_found_html_gz_body()),
+                    Asset::new("not-found.html.zz", not_found_html_zz_body()),
+                    Asset::new("not-found.html.br", not_found_html_br_body()),
+                ],
+                vec![not_found_html_config()],
+            )
+            .unwrap();
+        let mut expected_response = expected_index_html_response();
+
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        let (witness, expr_path) = extract_witness_expr_path(&response);
+        add_v2_certificate_header(
+            &data_certificate(),
+            &mut expected_response,
+            &witness,
+            &expr_path,
+        );
+
+        assert_eq!(expr_path, vec!["http_expr", "", "<*>"]);
+        assert_matches!(
+            witness.lookup_subtree(&expr_path),
+            SubtreeLookupResult::Found(_)
+        );
+        assert_eq!(response, expected_response);
+
+        asset_router
+            .delete_assets(
+                vec![
+                    Asset::new("index.html", index_html_body()),
+                    Asset::new("index.html.gz", index_html_gz_body()),
+                    Asset::new("index.html.zz", index_html_zz_body()),
+                    Asset::new("index.html.br", index_html_br_body()),
+                ],
+                vec![index_html_config()],
+            )
+            .unwrap();
+
+        let result = asset_router.serve_asset(&data_certificate(), &request);
+        assert_matches!(
+            result,
+            Err(AssetCertificationError::NoAssetMatchingRequestUrl {
+                request_url,
+             }) if request_url == request.get_path().unwrap()
+        );
+    }
+
+    #[rstest]
+    #[case("/js/app-488df671.js")]
+    #[case("https://internetcomputer.org/js/app-488df671.js")]
+    fn test_app_js(mut asset_router: AssetRouter, #[case] req_url: &str) {
+        let request = HttpRequest::get(req_url).build();
+        let mut expected_response = build_200_response(
+            app_js_body(),
+            asset_cel_expr(),
+            vec![
+                (
+                    "cache-control".to_string(),
+                    "public, max-age=31536000, immutable".to_string(),
+                ),
+                ("vary".to_string(), "accept-encoding".to_string()),
+                ("content-type".to_string(), "text/javascript".to_string()),
+            ],
+        );
+
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        let (witness, expr_path) = extract_witness_expr_path(&response);
+        add_v2_certificate_header(
+            &data_certificate(),
+            &mut expected_response,
+            &witness,
+            &expr_path,
+        );
+
+        assert_eq!(expr_path, vec!["http_expr", "js", "app-488df671.js", "<$>"]);
+        assert_matches!(
+            witness.lookup_subtree(&expr_path),
+            SubtreeLookupResult::Found(_)
+        );
+        assert_eq!(response, expected_response);
+
+        asset_router
+            .delete_assets(
+                vec![
+                    Asset::new("js/app-488df671.js", app_js_body()),
+                    Asset::new("js/app-488df671.js.gz", app_js_gz_body()),
+                    Asset::new("js/app-488df671.js.zz", app_js_zz_body()),
+                    Asset::new("js/app-488df671.js.br", app_js_br_body()),
+                ],
+                vec![js_config()],
+            )
+            .unwrap();
+        let mut expected_response = build_response(
+            StatusCode::NOT_FOUND,
+            not_found_html_body(),
+            asset_cel_expr(),
+            vec![
+                (
+                    "cache-control".to_string(),
+                    "public, no-cache, no-store".to_string(),
+                ),
+                ("vary".to_string(), "accept-encoding".to_string()),
+                ("content-type".to_string(), "text/html".to_string()),
+            ],
+        );
+
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        let (witness, expr_path) = extract_witness_expr_path(&response);
+        add_v2_certificate_header(
+            &data_certificate(),
+            &mut expected_response,
+            &witness,
+            &expr_path,
+        );
+
+        assert_eq!(expr_path, vec!["http_expr", "js", "<*>"]);
+        assert_matches!(
+            witness.lookup_subtree(&expr_path),
+            SubtreeLookupResult::Found(_)
+        );
+        assert_eq!(response, expected_response);
+
+        asset_router
+            .delete_assets(
+                vec![
+                    Asset::new("not-found.html", not_found_html_body()),
+                    Asset::new("not-found.html.gz", not_found_html_gz_body()),
+                    Asset::new("not-found.html.zz", not_found_html_zz_body()),
+                    Asset::new("not-found.html.br", not_found_html_br_body()),
+                ],
+                vec![not_found_html_config()],
+            )
+            .unwrap();
+        let mut expected_response = expected_index_html_response();
+
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        let (witness, expr_path) = extract_witness_expr_path(&response);
+        add_v2_certificate_header(
+            &data_certificate(),
+            &mut expected_response,
+            &witness,
+            &expr_path,
+        );
+
+        assert_eq!(expr_path, vec!["http_expr", "", "<*>"]);
+        assert_matches!(
+            witness.lookup_subtree(&expr_path),
+            SubtreeLookupResult::Found(_)
+        );
+        assert_eq!(response, expected_response);
+
+        asset_router
+            .delete_assets(
+                vec![
+                    Asset::new("index.html", index_html_body()),
+                    Asset::new("index.html.gz", index_html_gz_body()),
+                    Asset::new("index.html.zz", index_html_zz_body()),
+                    Asset::new("index.html.br", index_html_br_body()),
+                ],
+                vec![index_html_config()],
+            )
+            .unwrap();
+
+        let result = asset_router.serve_asset(&data_certificate(), &request);
+        assert_matches!(
+            result,
+            Err(AssetCertificationError::NoAssetMatchingRequestUrl {
+                request_url,
+             }) if request_url == request.get_path().unwrap()
+        );
+    }
+
+    #[rstest]
+    #[case(
+        app_js_zz_body(),
+        not_found_html_zz_body(),
+        index_html_zz_body(),
+        "deflate",
+        "deflate"
+    )]
+    #[case(
+        app_js_zz_body(),
+        not_found_html_zz_body(),
+        index_html_zz_body(),
+        "deflate, identity",
+        "deflate"
+    )]
+    #[case(
+        app_js_zz_body(),
+        not_found_html_zz_body(),
+        index_html_zz_body(),
+        "identity, deflate",
+        "deflate"
+    )]
+    #[case(
+        app_js_gz_body(),
+        not_found_html_gz_body(),
+        index_html_gz_body(),
+        "gzip",
+        "gzip"
+    )]
+    #[case(
+        app_js_gz_body(),
+        not_found_html_gz_body(),
+        index_html_gz_body(),
+        "gzip, identity",
+        "gzip"
+    )]
+    #[case(
+        app_js_gz_body(),
+        not_found_html_gz_body(),
+        index_html_gz_body(),
+        "identity, gzip",
+        "gzip"
+    )]
+    #[case(
+        app_js_gz_body(),
+        not_found_html_gz_body(),
+        index_html_gz_body(),
+        "gzip, deflate",
+        "gzip"
+    )]
+    #[case(
+        app_js_gz_body(),
+        not_found_html_gz_body(),
+        index_html_gz_body(),
+        "deflate, gzip",
+        "gzip"
+    )]
+    #[case(
+        app_js_gz_body(),
+        not_found_html_gz_body(),
+        index_html_gz_body(),
+        "gzip, deflate, identity",
+        "gzip"
+    )]
+    #[case(
+        app_js_gz_body(),
+        not_found_html_gz_body(),
+        index_html_gz_body(),
+        "gzip, identity, deflate",
+        "gzip"
+    )]
+    #[case(
+        app_js_gz_body(),
+        not_found_html_gz_body(),
+        index_html_gz_body(),
+        "identity, gzip, deflate",
+        "gzip"
+    )]
+    #[case(
+        app_js_gz_body(),
+        not_found_html_gz_body(),
+        index_html_gz_body(),
+        "identity, deflate, gzip",
+        "gzip"
+    )]
+    #[case(
+        app_js_gz_body(),
+        not_found_html_gz_body(),
+        index_html_gz_body(),
+        "deflate, gzip, identity",
+        "gzip"
+    )]
+    #[case(
+        app_js_gz_body(),
+        not_found_html_gz_body(),
+        index_html_gz_body(),
+        "deflate, identity, gzip",
+        "gzip"
+    )]
+    #[case(
+        app_js_br_body(),
+        not_found_html_br_body(),
+        index_html_br_body(),
+        "br",
+        "br"
+    )]
+    #[case(
+        app_js_br_body(),
+        not_found_html_br_body(),
+        index_html_br_body(),
+        "br, gzip, deflate, identity",
+        "br"
+    )]
+    #[case(
+        app_js_br_body(),
+        not_found_html_br_body(),
+        index_html_br_body(),
+        "gzip, deflate, identity, br",
+        "br"
+    )]
+    fn test_encoded_app_js(
+        #[case] expected_body: Vec<u8>,
+        #[case] expected_not_found_body: Vec<u8>,
+        #[case] expected_index_body: Vec<u8>,
+        #[case] accept_encoding: &str,
+        #[case] expected_encoding: &str,
+        mut asset_router: AssetRouter,
+    ) {
+        let request = HttpRequest::get("/js/app-488df671.js")
+            .with_headers(vec![(
+                "accept-encoding".to_string(),
+                accept_encoding.to_string(),
+            )])
+            .build();
+
+        let mut expected_response = build_200_response(
+            expected_body,
+            encoded_asset_cel_expr(),
+            vec![
+                (
+                    "cache-control".to_string(),
+                    "public, max-age=31536000, immutable".to_string(),
+                ),
+                ("vary".to_string(), "accept-encoding".to_string()),
+                ("content-type".to_string(), "text/javascript".to_string()),
+                (
+                    "content-encoding".to_string(),
+                    expected_encoding.to_string(),
+                ),
+            ],
+        );
+
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        let (witness, expr_path) = extract_witness_expr_path(&response);
+        add_v2_certificate_header(
+            &data_certificate(),
+            &mut expected_response,
+            &witness,
+            &expr_path,
+        );
+
+        assert_eq!(expr_path, vec!["http_expr", "js", "app-488df671.js", "<$>"]);
+        assert_matches!(
+            witness.lookup_subtree(&expr_path),
+            SubtreeLookupResult::Found(_)
+        );
+        assert_eq!(response, expected_response);
+
+        asset_router
+            .delete_assets(
+                vec![
+                    Asset::new("js/app-488df671.js", app_js_body()),
+                    Asset::new("js/app-488df671.js.gz", app_js_gz_body()),
+                    Asset::new("js/app-488df671.js.zz", app_js_zz_body()),
+                    Asset::new("js/app-488df671.js.br", app_js_br_body()),
+                ],
+                vec![js_config()],
+            )
+            .unwrap();
+        let mut expected_response = build_response(
+            StatusCode::NOT_FOUND,
+            expected_not_found_body,
+            encoded_asset_cel_expr(),
+            vec![
+                (
+                    "cache-control".to_string(),
+                    "public, no-cache, no-store".to_string(),
+                ),
+                ("vary".to_string(), "accept-encoding".to_string()),
+                ("content-type".to_string(), "text/html".to_string()),
+                (
+                    "content-encoding".to_string(),
+                    expected_encoding.to_string(),
+                ),
+            ],
+        );
+
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        let (witness, expr_path) = extract_witness_expr_path(&response);
+        add_v2_certificate_header(
+            &data_certificate(),
+            &mut expected_response,
+            &witness,
+            &expr_path,
+        );
+
+        assert_eq!(expr_path, vec!["http_expr", "js", "<*>"]);
+        assert_matches!(
+            witness.lookup_subtree(&expr_path),
+            SubtreeLookupResult::Found(_)
+        );
+        assert_eq!(response, expected_response);
+
+        asset_router
+            .delete_assets(
+                vec![
+                    Asset::new("not-found.html", not_found_html_body()),
+                    Asset::new("not-found.html.gz", not_found_html_gz_body()),
+                    Asset::new("not-found.html.zz", not_found_html_zz_body()),
+                    Asset::new("not-found.html.br", not_found_html_br_body()),
+                ],
+                vec![not_found_html_config()],
+            )
+            .unwrap();
+        let mut expected_response = build_200_response(
+            expected_index_body,
+            encoded_asset_cel_expr(),
+            vec![
+                (
+                    "cache-control".to_string(),
+                    "public, no-cache, no-store".to_string(),
+                ),
+                ("vary".to_string(), "accept-encoding".to_string()),
+                ("content-type".to_string(), "text/html".to_string()),
+                (
+                    "content-encoding".to_string(),
+                    expected_encoding.to_string(),
+                ),
+            ],
+        );
+
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        let (witness, expr_path) = extract_witness_expr_path(&response);
+        add_v2_certificate_header(
+            &data_certificate(),
+            &mut expected_response,
+            &witness,
+            &expr_path,
+        );
+
+        assert_eq!(expr_path, vec!["http_expr", "", "<*>"]);
+        assert_matches!(
+            witness.lookup_subtree(&expr_path),
+            SubtreeLookupResult::Found(_)
+        );
+        assert_eq!(response, expected_response);
+
+        asset_router
+            .delete_assets(
+                vec![
+                    Asset::new("index.html", index_html_body()),
+                    Asset::new("index.html.gz", index_html_gz_body()),
+                    Asset::new("index.html.zz", index_html_zz_body()),
+                    Asset::new("index.html.br", index_html_br_body()),
+                ],
+                vec![index_html_config()],
+            )
+            .unwrap();
+
+        let result = asset_router.serve_asset(&data_certificate(), &request);
+        assert_matches!(
+            result,
+            Err(AssetCertificationError::NoAssetMatchingRequestUrl {
+                request_url,
+             }) if request_url == request.get_path().unwrap()
+        );
+    }
+
+    #[rstest]
+    #[case("/js/core-7dk12y45.js")]
+    #[case("https://internetcomputer.org/js/core-7dk12y45.js")]
+    fn test_not_found_js(mut asset_router: AssetRouter, #[case] req_url: &str) {
+        let request = HttpRequest::get(req_url).build();
+        let mut expected_response = build_response(
+            StatusCode::NOT_FOUND,
+            not_found_html_body(),
+            asset_cel_expr(),
+            vec![
+                (
+                    "cache-control".to_string(),
+                    "public, no-cache, no-store".to_string(),
+                ),
+                ("vary".to_string(), "accept-encoding".to_string()),
+                ("content-type".to_string(), "text/html".to_string()),
+            ],
+        );
+
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        let (witness, expr_path) = extract_witness_expr_path(&response);
+        add_v2_certificate_header(
+            &data_certificate(),
+            &mut expected_response,
+            &witness,
+            &expr_path,
+        );
+
+        assert_eq!(expr_path, vec!["http_expr", "js", "<*>"]);
+        assert_matches!(
+            witness.lookup_subtree(&expr_path),
+            SubtreeLookupResult::Found(_)
+        );
+        assert_eq!(response, expected_response);
+
+        asset_router
+            .delete_assets(
+                vec![
+                    Asset::new("not-found.html", not_found_html_body()),
+                    Asset::new("not-found.html.gz", not_found_html_gz_body()),
+                    Asset::new("not-found.html.zz", not_found_html_zz_body()),
+                    Asset::new("not-found.html.br", not_found_html_br_body()),
+                ],
+                vec![not_found_html_config()],
+            )
+            .unwrap();
+
+        let mut expected_response = expected_index_html_response();
+
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        let (witness, expr_path) = extract_witness_expr_path(&response);
+        add_v2_certificate_header(
+            &data_certificate(),
+            &mut expected_response,
+            &witness,
+            &expr_path,
+        );
+
+        assert_eq!(expr_path, vec!["http_expr", "", "<*>"]);
+        assert_matches!(
+            witness.lookup_subtree(&expr_path),
+            SubtreeLookupResult::Found(_)
+        );
+        assert_eq!(response, expected_response);
+
+        asset_router
+            .delete_assets(
+                vec![
+                    Asset::new("index.html", index_html_body()),
+                    Asset::new("index.html.gz", index_html_gz_body()),
+                    Asset::new("index.html.zz", index_html_zz_body()),
+                    Asset::new("index.html.br", index_html_br_body()),
+                ],
+                vec![index_html_config()],
+            )
+            .unwrap();
+
+        let result = asset_router.serve_asset(&data_certificate(), &request);
+        assert_matches!(
+            result,
+            Err(AssetCertificationError::NoAssetMatchingRequestUrl {
+                request_url,
+             }) if request_url == request.get_path().unwrap()
+        );
+    }
+
+    #[rstest]
+    #[case("/404")]
+    #[case("https://internetcomputer.org/404")]
+    #[case("/404/")]
+    #[case("https://internetcomputer.org/404/")]
+    #[case("/404.html")]
+    #[case("https://internetcomputer.org/404.html")]
+    #[case("/not-found")]
+    #[case("https://internetcomputer.org/not-found")]
+    #[case("/not-found/")]
+    #[case("https://internetcomputer.org/not-found/")]
+    #[case("/not-found/index.html")]
+    #[case("https://internetcomputer.org/not-found/index.html")]
+    fn test_not_found_alias(mut asset_router: AssetRouter, #[case] req_url: &str) {
+        let request = HttpRequest::get(req_url).build();
+        let mut expected_response = build_200_response(
+            not_found_html_body(),
+            asset_cel_expr(),
+            vec![
+                (
+                    "cache-control".to_string(),
+                    "public, no-cache, no-store".to_string(),
+                ),
+                ("vary".to_string(), "accept-encoding".to_string()),
+                ("content-type".to_string(), "text/html".to_string()),
+            ],
+        );
+
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        let (witness, expr_path) = extract_witness_expr_path(&response);
+        add_v2_certificate_header(
+            &data_certificate(),
+            &mut expected_response,
+            &witness,
+            &expr_path,
+        );
+
+        assert_eq!(
+            expr_path,
+            HttpCertificationPath::exact(request.get_path().unwrap()).to_expr_path()
+        );
+        assert_matches!(
+            witness.lookup_subtree(&expr_path),
+            SubtreeLookupResult::Found(_)
+        );
+        assert_eq!(response, expected_response);
+
+        asset_router
+            .delete_assets(
+                vec![
+                    Asset::new("not-found.html", not_found_html_body()),
+                    Asset::new("not-found.html.gz", not_found_html_gz_body()),
+                    Asset::new("not-found.html.zz", not_found_html_zz_body()),
+                    Asset::new("not-found.html.br", not_found_html_br_body()),
+                ],
+                vec![not_found_html_config()],
+            )
+            .unwrap();
+
+        let mut expected_response = expected_index_html_response();
+
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        let (witness, expr_path) = extract_witness_expr_path(&response);
+        add_v2_certificate_header(
+            &data_certificate(),
+            &mut expected_response,
+            &witness,
+            &expr_path,
+        );
+
+        assert_eq!(expr_path, vec!["http_expr", "", "<*>"]);
+        assert_matches!(
+            witness.lookup_subtree(&expr_path),
+            SubtreeLookupResult::Found(_)
+        );
+        assert_eq!(response, expected_response);
+
+        asset_router
+            .delete_assets(
+                vec![
+                    Asset::new("index.html", index_html_body()),
+                    Asset::new("index.html.gz", index_html_gz_body()),
+                    Asset::new("index.html.zz", index_html_zz_body()),
+                    Asset::new("index.html.br", index_html_br_body()),
+                ],
+                vec![index_html_config()],
+            )
+            .unwrap();
+
+        let result = asset_router.serve_asset(&data_certificate(), &request);
+        assert_matches!(
+            result,
+            Err(AssetCertificationError::NoAssetMatchingRequestUrl {
+                request_url,
+             }) if request_url == request.get_path().unwrap()
+        );
+    }
+
+    #[rstest]
+    fn test_delete_all_assets() {
+        let mut asset_router = asset_router();
+
+        let request = HttpRequest::get("/index.html").build();
+
+        let mut expected_response = expected_index_html_response();
+
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        let (witness, expr_path) = extract_witness_expr_path(&response);
+        add_v2_certificate_header(
+            &data_certificate(),
+            &mut expected_response,
+            &witness,
+            &expr_path,
+        );
+
+        assert_eq!(response, expected_response);
+
+        asset_router.delete_all_assets();
+
+        assert_matches!(
+            asset_router.serve_asset(
+                &data_certificate(),
+                &request,
+            ),
+            Err(AssetCertificationError::NoAssetMatchingRequestUrl {
+                request_url,
+            }) if request_url == "/index.html"
+        );
+
+        let assets: Vec<_> = asset_router.get_assets().iter().collect();
+        assert!(assets.is_empty());
+    }
+
+    #[rstest]
+    fn test_clear(index_html_body: Vec<u8>, asset_cel_expr: String) {
+        let http_certification_tree: Rc<RefCell<HttpCertificationTree>> = Default::default();
+
+        let cel_expr = DefaultCelBuilder::full_certification()
+            .with_response_certification(DefaultResponseCertification::response_header_exclusions(
+                vec![],
+            ))
+            .build();
+        let other_request = HttpRequest::get("/api/todos").build();
+        let other_response = build_200_response(vec![], asset_cel_expr, vec![]);
+        let other_entry = HttpCertificationTreeEntry::new(
+            HttpCertificationPath::exact("/api/todos"),
+            HttpCertification::full(&cel_expr, &other_request, &other_response, None).unwrap(),
+        );
+        http_certification_tree.borrow_mut().insert(&other_entry);
+        let other_root_hash = http_certification_tree.borrow().root_hash();
+
+        let mut asset_router = AssetRouter::with_tree(http_certification_tree.clone());
+        asset_router
+            .certify_assets(
+                vec![
+                    Asset::new("index.html", index_html_body),
+                    long_asset(TWO_CHUNKS_ASSET_NAME.to_string()),
+                ],
+                vec![
+                    index_html_config(),
+                    long_asset_config(TWO_CHUNKS_ASSET_NAME),
+                    old_url_redirect_config(),
+                ],
+            )
+            .unwrap();
+        assert_ne!(asset_router.root_hash(), other_root_hash);
+
+        asset_router.clear();
+
+        assert!(asset_router.get_assets().is_empty());
+        assert!(asset_router.get_fallback_assets().is_empty());
+        assert_matches!(
+            asset_router.serve_asset(&data_certificate(), &HttpRequest::get("/").build()),
+            Err(AssetCertificationError::NoAssetMatchingRequestUrl {
+                request_url,
+            }) if request_url == "/"
+        );
+        assert_eq!(asset_router.root_hash(), other_root_hash);
+        assert_matches!(
+            http_certification_tree
+                .borrow()
+                .witness(&other_entry, "/api/todos")
+                .unwrap()
+                .lookup_subtree(&other_entry.path.to_expr_path()),
+            SubtreeLookupResult::Found(_)
+        );
+    }
+
+    #[rstest]
+    fn test_serve_asset_missing_from_shared_tree(index_html_body: Vec<u8>) {
+        let http_certification_tree: Rc<RefCell<HttpCertificationTree>> = Default::default();
+        let mut asset_router = AssetRouter::with_tree(http_certification_tree.clone());
+        asset_router
+            .certify_assets(
+                vec![Asset::new("index.html", index_html_body)],
+                vec![index_html_config()],
+            )
+            .unwrap();
+
+        // the tree is shared, so it can be modified without the router being aware of it
+        http_certification_tree.borrow_mut().clear();
+
+        assert_matches!(
+            asset_router.serve_asset(
+                &data_certificate(),
+                &HttpRequest::get("/index.html").build()
+            ),
+            Err(AssetCertificationError::NoAssetMatchingRequestUrl {
+                request_url,
+            }) if request_url == "/index.html"
+        );
+    }
+
+    #[rstest]
+    fn test_sync_assets(
+        index_html_body: Vec<u8>,
+        index_html_gz_body: Vec<u8>,
+        app_js_body: Vec<u8>,
+        app_css_body: Vec<u8>,
+    ) {
+        let asset_configs = vec![
+            index_html_config(),
+            js_config(),
+            css_config(),
+            old_url_redirect_config(),
+        ];
+        let initial_assets = vec![
+            Asset::new("index.html", index_html_body.clone()),
+            Asset::new("index.html.gz", index_html_gz_body.clone()),
+            Asset::new("js/app-488df671.js", app_js_body.clone()),
+            Asset::new("css/app-ba74b708.css", app_css_body),
+        ];
+        let updated_index_html_body = b"<html><body><h1>Hello Sync!</h1></body></html>".to_vec();
+        let updated_assets = vec![
+            Asset::new("index.html", updated_index_html_body.clone()),
+            Asset::new("index.html.gz", index_html_gz_body),
+            Asset::new("js/app-488df671.js", app_js_body),
+        ];
+
+        let mut asset_router = AssetRouter::default();
+        asset_router
+            .sync_assets(initial_assets.clone(), asset_configs.clone())
+            .unwrap();
+        let mut expected_asset_router = AssetRouter::default();
+        expected_asset_router
+            .certify_assets(initial_assets.clone(), asset_configs.clone())
+            .unwrap();
+        assert_same_responses(&asset_router, &expected_asset_router);
+
+        let initial_root_hash = asset_router.root_hash();
+        asset_router
+            .sync_assets(initial_assets, asset_configs.clone())
+            .unwrap();
+        assert_eq!(asset_router.root_hash(), initial_root_hash);
+        assert_same_responses(&asset_router, &expected_asset_router);
+
+        asset_router
+            .sync_assets(updated_assets.clone(), asset_configs.clone())
+            .unwrap();
+        let mut expected_asset_router = AssetRouter::default();
+        expected_asset_router
+            .certify_assets(updated_assets, asset_configs)
+            .unwrap();
+        assert_same_responses(&asset_router, &expected_asset_router);
+
+        let response = asset_router
+            .serve_asset(&data_certificate(), &HttpRequest::get("/").build())
             .unwrap();
+        assert_eq!(response.body(), updated_index_html_body.as_slice());
         let (witness, expr_path) = extract_witness_expr_path(&response);
+        assert_matches!(
+            witness.lookup_subtree(&expr_path),
+            SubtreeLookupResult::Found(_)
+        );
+        assert_matches!(
+            asset_router.serve_asset(
+                &data_certificate(),
+                &HttpRequest::get("/css/app-ba74b708.css").build()
+            ),
+            Ok(response) if response.body() == updated_index_html_body.as_slice()
+        );
+
+        asset_router.sync_assets(vec![], vec![]).unwrap();
+        assert!(asset_router.get_assets().is_empty());
+        assert!(asset_router.get_fallback_assets().is_empty());
+        assert_eq!(
+            asset_router.root_hash(),
+            HttpCertificationTree::default().root_hash()
+        );
+    }
+
+    #[rstest]
+    fn test_sync_assets_with_changed_config(index_html_body: Vec<u8>, index_html_gz_body: Vec<u8>) {
+        let assets = vec![
+            Asset::new("index.html", index_html_body),
+            Asset::new("index.html.gz", index_html_gz_body.clone()),
+        ];
+        let mut updated_config = index_html_config();
+        if let AssetConfig::File {
+            headers,
+            fallback_for,
+            aliased_by,
+            ..
+        } = &mut updated_config
+        {
+            headers.push(("x-frame-options".to_string(), "DENY".to_string()));
+            fallback_for[0].status_code = Some(StatusCode::NOT_FOUND);
+            aliased_by.push("/home".to_string());
+        }
+
+        let mut asset_router = AssetRouter::default();
+        asset_router
+            .sync_assets(assets.clone(), vec![index_html_config()])
+            .unwrap();
+        asset_router
+            .sync_assets(assets.clone(), vec![updated_config.clone()])
+            .unwrap();
+
+        let mut expected_asset_router = AssetRouter::default();
+        expected_asset_router
+            .certify_assets(assets, vec![updated_config])
+            .unwrap();
+        assert_same_responses(&asset_router, &expected_asset_router);
+        assert_eq!(asset_router.root_hash(), expected_asset_router.root_hash());
+
+        // encoded assets are certified with the configuration of the asset they belong to
+        let response = asset_router
+            .serve_asset(
+                &data_certificate(),
+                &HttpRequest::get("/home")
+                    .with_headers(vec![("accept-encoding".to_string(), "gzip".to_string())])
+                    .build(),
+            )
+            .unwrap();
+        assert_eq!(response.body(), index_html_gz_body.as_slice());
+        assert!(response
+            .headers()
+            .contains(&("x-frame-options".to_string(), "DENY".to_string())));
+
+        let response = asset_router
+            .serve_asset(&data_certificate(), &HttpRequest::get("/users").build())
+            .unwrap();
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    }
+
+    #[rstest]
+    fn test_integrity_header(index_html_body: Vec<u8>, index_html_gz_body: Vec<u8>) {
+        fn integrity_header<'a>(response: &'a HttpResponse) -> Option<&'a str> {
+            response
+                .headers()
+                .iter()
+                .find(|(name, _)| name == "x-integrity")
+                .map(|(_, value)| value.as_str())
+        }
+
+        let mut asset_router = AssetRouter::default().with_integrity_header("x-integrity");
+        let assets = vec![
+            Asset::new("index.html", index_html_body.clone()),
+            Asset::new("index.html.gz", index_html_gz_body.clone()),
+        ];
+        asset_router
+            .certify_assets(assets.clone(), vec![index_html_config()])
+            .unwrap();
+
+        let expected_integrity = subresource_integrity(hash(&index_html_body));
+        for request in [
+            HttpRequest::get("/").build(),
+            HttpRequest::get("/index.html")
+                .with_headers(vec![("accept-encoding".to_string(), "gzip".to_string())])
+                .build(),
+            HttpRequest::get("/nested/page").build(),
+        ] {
+            let response = asset_router
+                .serve_asset(&data_certificate(), &request)
+                .unwrap();
+            assert_eq!(
+                integrity_header(&response),
+                Some(expected_integrity.as_str())
+            );
+        }
+        assert_eq!(
+            integrity_header(
+                asset_router
+                    .get_assets()
+                    .get("/index.html.gz", None, None)
+                    .as_ref()
+                    .unwrap()
+            ),
+            Some(subresource_integrity(hash(&index_html_gz_body)).as_str())
+        );
+        assert_eq!(
+            asset_router
+                .get_assets()
+                .get_metadata("/index.html")
+                .unwrap()
+                .integrity,
+            Some(expected_integrity)
+        );
+
+        let updated_index_html_body =
+            b"<html><body><h1>Hello Integrity!</h1></body></html>".to_vec();
+        asset_router
+            .sync_assets(
+                vec![
+                    Asset::new("index.html", updated_index_html_body.clone()),
+                    Asset::new("index.html.gz", index_html_gz_body.clone()),
+                ],
+                vec![index_html_config()],
+            )
+            .unwrap();
+        let response = asset_router
+            .serve_asset(
+                &data_certificate(),
+                &HttpRequest::get("/")
+                    .with_headers(vec![("accept-encoding".to_string(), "gzip".to_string())])
+                    .build(),
+            )
+            .unwrap();
+        assert_eq!(response.body(), index_html_gz_body.as_slice());
+        assert_eq!(
+            integrity_header(&response),
+            Some(subresource_integrity(hash(&updated_index_html_body)).as_str())
+        );
+
+        asset_router
+            .sync_assets(assets.clone(), vec![index_html_config()])
+            .unwrap();
+        asset_router
+            .delete_assets(assets, vec![index_html_config()])
+            .unwrap();
+        assert!(asset_router.get_assets().is_empty());
+        assert!(asset_router.get_fallback_assets().is_empty());
+        assert_eq!(
+            asset_router.root_hash(),
+            HttpCertificationTree::default().root_hash()
+        );
+    }
+
+    #[rstest]
+    fn test_delete_by_path() {
+        let mut asset_router = asset_router();
+
+        let index_request = HttpRequest::get("/index.html").build();
+        let mut expected_index_response = expected_index_html_response();
+        let index_response = asset_router
+            .serve_asset(&data_certificate(), &index_request)
+            .unwrap();
+        let (witness, expr_path) = extract_witness_expr_path(&index_response);
         add_v2_certificate_header(
             &data_certificate(),
-            &mut expected_response,
+            &mut expected_index_response,
+            &witness,
+            &expr_path,
+        );
+        assert_eq!(index_response, expected_index_response);
+
+        let alias_index_request = HttpRequest::get("/").build();
+        let mut expected_alias_index_response = expected_index_html_response();
+        let alias_index_response = asset_router
+            .serve_asset(&data_certificate(), &alias_index_request)
+            .unwrap();
+        let (witness, expr_path) = extract_witness_expr_path(&alias_index_response);
+        add_v2_certificate_header(
+            &data_certificate(),
+            &mut expected_alias_index_response,
+            &witness,
+            &expr_path,
+        );
+        assert_eq!(alias_index_response, expected_alias_index_response);
+
+        let fallback_index_request = HttpRequest::get("/non-existent").build();
+        let mut expected_fallback_index_response = expected_index_html_response();
+        let fallback_index_response = asset_router
+            .serve_asset(&data_certificate(), &fallback_index_request)
+            .unwrap();
+        let (witness, expr_path) = extract_witness_expr_path(&fallback_index_response);
+        add_v2_certificate_header(
+            &data_certificate(),
+            &mut expected_fallback_index_response,
+            &witness,
+            &expr_path,
+        );
+        assert_eq!(fallback_index_response, expected_fallback_index_response);
+
+        asset_router.delete_fallback_assets_by_path(vec!["/"]);
+
+        let index_request = HttpRequest::get("/index.html").build();
+        let mut expected_index_response = expected_index_html_response();
+        let index_response = asset_router
+            .serve_asset(&data_certificate(), &index_request)
+            .unwrap();
+        let (witness, expr_path) = extract_witness_expr_path(&index_response);
+        add_v2_certificate_header(
+            &data_certificate(),
+            &mut expected_index_response,
+            &witness,
+            &expr_path,
+        );
+        assert_eq!(index_response, expected_index_response);
+
+        let alias_index_request = HttpRequest::get("/").build();
+        let mut expected_alias_index_response = expected_index_html_response();
+        let alias_index_response = asset_router
+            .serve_asset(&data_certificate(), &alias_index_request)
+            .unwrap();
+        let (witness, expr_path) = extract_witness_expr_path(&alias_index_response);
+        add_v2_certificate_header(
+            &data_certificate(),
+            &mut expected_alias_index_response,
             &witness,
             &expr_path,
         );
+        assert_eq!(alias_index_response, expected_alias_index_response);
+
+        assert_matches!(
+            asset_router.serve_asset(
+                &data_certificate(),
+                &fallback_index_request,
+            ),
+            Err(AssetCertificationError::NoAssetMatchingRequestUrl {
+                request_url,
+            }) if request_url == "/non-existent"
+        );
+
+        asset_router.delete_assets_by_path(vec!["/index.html", "/"]);
+
+        assert_matches!(
+            asset_router.serve_asset(
+                &data_certificate(),
+                &index_request,
+            ),
+            Err(AssetCertificationError::NoAssetMatchingRequestUrl {
+                request_url,
+            }) if request_url == "/index.html"
+        );
+        assert_matches!(
+            asset_router.serve_asset(
+                &data_certificate(),
+                &alias_index_request,
+            ),
+            Err(AssetCertificationError::NoAssetMatchingRequestUrl {
+                request_url,
+            }) if request_url == "/"
+        );
+        assert_matches!(
+            asset_router.serve_asset(
+                &data_certificate(),
+                &fallback_index_request,
+            ),
+            Err(AssetCertificationError::NoAssetMatchingRequestUrl {
+                request_url,
+            }) if request_url == "/non-existent"
+        );
+    }
+
+    #[rstest]
+    fn test_asset_map(mut asset_router: AssetRouter) {
+        let index_html_response = asset_router.get_assets().get("/index.html", None, None);
+        assert_matches!(
+            index_html_response,
+            Some(index_html_response) if index_html_response == expected_index_html_response()
+        );
+
+        let index_html_fallback_response = asset_router.get_fallback_assets().get("/", None, None);
+        assert_matches!(
+            index_html_fallback_response,
+            Some(index_html_fallback_response) if index_html_fallback_response == expected_index_html_response()
+        );
+
+        let index_html_gz_response =
+            asset_router
+                .get_assets()
+                .get("/index.html", Some(AssetEncoding::Gzip), None);
+        assert_matches!(
+            index_html_gz_response,
+            Some(index_html_gz_response) if index_html_gz_response == expected_index_html_gz_response()
+        );
+
+        let index_html_gz_fallback_response =
+            asset_router
+                .get_fallback_assets()
+                .get("/", Some(AssetEncoding::Gzip), None);
+        assert_matches!(
+            index_html_gz_fallback_response,
+            Some(index_html_gz_fallback_response) if index_html_gz_fallback_response == expected_index_html_gz_response()
+        );
+
+        let index_html_zz_response =
+            asset_router
+                .get_assets()
+                .get("/index.html", Some(AssetEncoding::Deflate), None);
+        assert_matches!(
+            index_html_zz_response,
+            Some(index_html_zz_response) if index_html_zz_response == expected_index_html_zz_response()
+        );
+
+        let index_html_zz_fallback_response =
+            asset_router
+                .get_fallback_assets()
+                .get("/", Some(AssetEncoding::Deflate), None);
+        assert_matches!(
+            index_html_zz_fallback_response,
+            Some(index_html_zz_fallback_response) if index_html_zz_fallback_response == expected_index_html_zz_response()
+        );
 
-        assert_eq!(expr_path, vec!["http_expr", &req_url[1..], "<$>"]);
+        let index_html_br_response =
+            asset_router
+                .get_assets()
+                .get("/index.html", Some(AssetEncoding::Brotli), None);
         assert_matches!(
-            witness.lookup_subtree(&expr_path),
-            SubtreeLookupResult::Found(_)
+            index_html_br_response,
+            Some(index_html_br_response) if index_html_br_response == expected_index_html_br_response()
         );
-        assert_eq!(response, expected_response);
 
-        // Request the subsequent chunks, should obtain them.
-        let expected_number_of_chunks =
-            (asset_len as f32 / ASSET_CHUNK_SIZE as f32).ceil() as usize;
-        let mut asset_len_so_far = response.body().len();
-        let mut number_of_chunks_so_far = 1;
-        while asset_len_so_far < asset_len {
-            let chunk_request = HttpRequest::get(&req_url)
-                .with_headers(vec![(
-                    "range".to_string(),
-                    format!("bytes={}-", asset_len_so_far),
-                )])
-                .build();
-            let expected_range_end = cmp::min(asset_len_so_far + ASSET_CHUNK_SIZE, asset_len) - 1;
-            let mut expected_response = build_206_response(
-                asset_body[asset_len_so_far..=expected_range_end].to_vec(),
-                asset_range_chunk_cel_expr(),
+        let index_html_br_fallback_response =
+            asset_router
+                .get_fallback_assets()
+                .get("/", Some(AssetEncoding::Brotli), None);
+        assert_matches!(
+            index_html_br_fallback_response,
+            Some(index_html_br_fallback_response) if index_html_br_fallback_response == expected_index_html_br_response()
+        );
+
+        asset_router
+            .delete_assets(
                 vec![
-                    (
-                        "cache-control".to_string(),
-                        "public, no-cache, no-store".to_string(),
-                    ),
-                    ("content-type".to_string(), "text/html".to_string()),
-                    (
-                        "content-range".to_string(),
-                        format!(
-                            "bytes {}-{}/{}",
-                            asset_len_so_far, expected_range_end, asset_len
-                        ),
-                    ),
+                    Asset::new("index.html", index_html_body()),
+                    Asset::new("index.html.gz", index_html_gz_body()),
+                    Asset::new("index.html.zz", index_html_zz_body()),
+                    Asset::new("index.html.br", index_html_br_body()),
                 ],
-            );
-            let response = long_asset_router
-                .serve_asset(&data_certificate(), &chunk_request)
-                .unwrap();
-            let (witness, expr_path) = extract_witness_expr_path(&response);
-            assert_matches!(
-                witness.lookup_subtree(&expr_path),
-                SubtreeLookupResult::Found(_)
-            );
-            add_v2_certificate_header(
-                &data_certificate(),
-                &mut expected_response,
-                &witness,
-                &expr_path,
-            );
-            assert_eq!(response, expected_response);
-            asset_len_so_far += response.body().len();
-            number_of_chunks_so_far += 1;
-        }
-        assert_eq!(number_of_chunks_so_far, expected_number_of_chunks)
+                vec![index_html_config()],
+            )
+            .unwrap();
+
+        let index_html_response = asset_router.get_assets().get("/index.html", None, None);
+        assert_matches!(index_html_response, None);
+
+        let index_html_fallback_response = asset_router.get_fallback_assets().get("/", None, None);
+        assert_matches!(index_html_fallback_response, None);
+
+        let index_html_gz_response =
+            asset_router
+                .get_assets()
+                .get("/index.html", Some(AssetEncoding::Gzip), None);
+        assert_matches!(index_html_gz_response, None);
+
+        let index_html_gz_fallback_response =
+            asset_router
+                .get_fallback_assets()
+                .get("/", Some(AssetEncoding::Gzip), None);
+        assert_matches!(index_html_gz_fallback_response, None);
+
+        let index_html_zz_response =
+            asset_router
+                .get_assets()
+                .get("/index.html", Some(AssetEncoding::Deflate), None);
+        assert_matches!(index_html_zz_response, None);
+
+        let index_html_zz_fallback_response =
+            asset_router
+                .get_fallback_assets()
+                .get("/", Some(AssetEncoding::Deflate), None);
+        assert_matches!(index_html_zz_fallback_response, None);
+
+        let index_html_br_response =
+            asset_router
+                .get_assets()
+                .get("/index.html", Some(AssetEncoding::Brotli), None);
+        assert_matches!(index_html_br_response, None);
+
+        let index_html_br_fallback_response =
+            asset_router
+                .get_fallback_assets()
+                .get("/", Some(AssetEncoding::Brotli), None);
+        assert_matches!(index_html_br_fallback_response, None);
     }
 
     #[rstest]
-    #[case(TWO_CHUNKS_ASSET_NAME)]
-    #[case(SIX_CHUNKS_ASSET_NAME)]
-    #[case(TEN_CHUNKS_ASSET_NAME)]
-    fn test_long_asset_deletion_removes_chunks(#[case] asset_name: &str) {
-        let mut long_asset_router =
-            long_asset_router_with_params(&[asset_name], &[AssetEncoding::Identity]);
-        let req_url = format!("/{asset_name}");
-        let asset_body = long_asset_body(asset_name);
-        let asset_len = asset_body.len();
-        let mut all_requests = vec![];
-        // Request the entire asset and the chunks, all should succeed.
-        // First the asset...
-        let request = HttpRequest::get(&req_url).build();
-        let response = long_asset_router
-            .serve_asset(&data_certificate(), &request)
-            .unwrap();
-        let (witness, expr_path) = extract_witness_expr_path(&response);
+    fn test_asset_map_chunked_responses() {
+        let mut asset_router = long_asset_router_with_params(
+            &[TWO_CHUNKS_ASSET_NAME],
+            &[AssetEncoding::Gzip, AssetEncoding::Identity],
+        );
+
+        let full_body = long_asset_body(TWO_CHUNKS_ASSET_NAME);
+        let (_, gzip_suffix) = AssetEncoding::Gzip.default_config();
+        let full_gz_body = long_asset_body(&format!("{}{}", TWO_CHUNKS_ASSET_NAME, gzip_suffix));
+
+        let first_chunk_body = &full_body[0..ASSET_CHUNK_SIZE];
+        let first_chunk_response =
+            asset_router
+                .get_assets()
+                .get(format!("/{}", TWO_CHUNKS_ASSET_NAME), None, Some(0));
+        let expected_first_chunk_response = build_206_response(
+            first_chunk_body.to_vec(),
+            asset_range_chunk_cel_expr(),
+            vec![
+                (
+                    "cache-control".to_string(),
+                    "public, no-cache, no-store".to_string(),
+                ),
+                ("vary".to_string(), "accept-encoding".to_string()),
+                ("content-type".to_string(), "text/html".to_string()),
+                (
+                    "content-range".to_string(),
+                    format!("bytes 0-{}/{}", first_chunk_body.len() - 1, full_body.len()),
+                ),
+            ],
+        );
+        assert_matches!(
+            first_chunk_response,
+            Some(first_chunk_response) if first_chunk_response == expected_first_chunk_response
+        );
+
+        let first_chunk_gzip_body = &full_gz_body[0..ASSET_CHUNK_SIZE];
+        let first_chunk_gzip_response = asset_router.get_assets().get(
+            format!("/{}", TWO_CHUNKS_ASSET_NAME),
+            Some(AssetEncoding::Gzip),
+            Some(0),
+        );
+        let expected_first_chunk_gzip_response = build_206_response(
+            first_chunk_gzip_body.to_vec(),
+            asset_range_chunk_cel_expr(),
+            vec![
+                (
+                    "cache-control".to_string(),
+                    "public, no-cache, no-store".to_string(),
+                ),
+                ("vary".to_string(), "accept-encoding".to_string()),
+                ("content-type".to_string(), "text/html".to_string()),
+                ("content-encoding".to_string(), "gzip".to_string()),
+                (
+                    "content-range".to_string(),
+                    format!(
+                        "bytes 0-{}/{}",
+                        first_chunk_gzip_body.len() - 1,
+                        full_gz_body.len()
+                    ),
+                ),
+            ],
+        );
+        assert_matches!(
+            first_chunk_gzip_response,
+            Some(first_chunk_gzip_response) if first_chunk_gzip_response == expected_first_chunk_gzip_response
+        );
+
+        let second_chunk_body = &full_body[ASSET_CHUNK_SIZE..full_body.len()];
+        let second_chunk_response = asset_router.get_assets().get(
+            format!("/{}", TWO_CHUNKS_ASSET_NAME),
+            None,
+            Some(ASSET_CHUNK_SIZE),
+        );
+        let expected_second_chunk_response = build_206_response(
+            second_chunk_body.to_vec(),
+            asset_range_chunk_cel_expr(),
+            vec![
+                (
+                    "cache-control".to_string(),
+                    "public, no-cache, no-store".to_string(),
+                ),
+                ("vary".to_string(), "accept-encoding".to_string()),
+                ("content-type".to_string(), "text/html".to_string()),
+                (
+                    "content-range".to_string(),
+                    format!(
+                        "bytes {}-{}/{}",
+                        first_chunk_body.len(),
+                        first_chunk_body.len() + second_chunk_body.len() - 1,
+                        full_body.len()
+                    ),
+                ),
+            ],
+        );
+        assert_matches!(
+            second_chunk_response,
+            Some(second_chunk_response) if second_chunk_response == expected_second_chunk_response
+        );
+
+        let second_chunk_gzip_body = &full_gz_body[ASSET_CHUNK_SIZE..full_gz_body.len()];
+        let second_chunk_gzip_response = asset_router.get_assets().get(
+            format!("/{}", TWO_CHUNKS_ASSET_NAME),
+            Some(AssetEncoding::Gzip),
+            Some(ASSET_CHUNK_SIZE),
+        );
+        let expected_second_chunk_gzip_response = build_206_response(
+            second_chunk_gzip_body.to_vec(),
+            asset_range_chunk_cel_expr(),
+            vec![
+                (
+                    "cache-control".to_string(),
+                    "public, no-cache, no-store".to_string(),
+                ),
+                ("vary".to_string(), "accept-encoding".to_string()),
+                ("content-type".to_string(), "text/html".to_string()),
+                ("content-encoding".to_string(), "gzip".to_string()),
+                (
+                    "content-range".to_string(),
+                    format!(
+                        "bytes {}-{}/{}",
+                        first_chunk_gzip_body.len(),
+                        first_chunk_gzip_body.len() + second_chunk_gzip_body.len() - 1,
+                        full_gz_body.len()
+                    ),
+                ),
+            ],
+        );
 
-        assert_eq!(expr_path, vec!["http_expr", &req_url[1..], "<$>"]);
         assert_matches!(
-            witness.lookup_subtree(&expr_path),
-            SubtreeLookupResult::Found(_)
+            second_chunk_gzip_response,
+            Some(second_chunk_gzip_response) if second_chunk_gzip_response == expected_second_chunk_gzip_response
         );
-        assert_eq!(response.status_code(), StatusCode::PARTIAL_CONTENT);
-        all_requests.push(request);
-
-        // ... then the subsequent chunks.
-        let expected_number_of_chunks =
-            (asset_len as f32 / ASSET_CHUNK_SIZE as f32).ceil() as usize;
-        let mut asset_len_so_far = response.body().len();
-        let mut number_of_chunks_so_far = 1;
-        while asset_len_so_far < asset_len {
-            let chunk_request = HttpRequest::get(&req_url)
-                .with_headers(vec![(
-                    "range".to_string(),
-                    format!("bytes={}-", asset_len_so_far),
-                )])
-                .build();
-            let response = long_asset_router
-                .serve_asset(&data_certificate(), &chunk_request)
-                .unwrap();
-            let (witness, expr_path) = extract_witness_expr_path(&response);
-            assert_matches!(
-                witness.lookup_subtree(&expr_path),
-                SubtreeLookupResult::Found(_)
-            );
-            assert_eq!(response.status_code(), StatusCode::PARTIAL_CONTENT);
-            asset_len_so_far += response.body().len();
-            number_of_chunks_so_far += 1;
-            all_requests.push(chunk_request);
-        }
-        assert_eq!(number_of_chunks_so_far, expected_number_of_chunks);
-        assert_eq!(all_requests.len(), expected_number_of_chunks);
 
-        // Delete the asset.
-        long_asset_router
+        asset_router
             .delete_assets(
-                vec![Asset::new(&req_url, asset_body)],
-                vec![long_asset_config(asset_name)],
+                vec![
+                    long_asset(TWO_CHUNKS_ASSET_NAME.to_string()),
+                    long_asset(format!("{}{}", TWO_CHUNKS_ASSET_NAME, gzip_suffix)),
+                ],
+                vec![
+                    long_asset_config(TWO_CHUNKS_ASSET_NAME),
+                    long_asset_config(&format!("{}{}", TWO_CHUNKS_ASSET_NAME, gzip_suffix)),
+                ],
             )
-            .expect("Asset deletion failed");
+            .unwrap();
 
-        // Re-request the asset and the chunks, all should fail.
-        for request in all_requests {
-            let result = long_asset_router.serve_asset(&data_certificate(), &request);
-            assert_matches!(
-                result,
-                Err(AssetCertificationError::NoAssetMatchingRequestUrl {
-                    request_url,
-                 }) if request_url == request.get_path().unwrap()
-            );
-        }
+        let first_chunk_response =
+            asset_router
+                .get_assets()
+                .get(format!("/{}", TWO_CHUNKS_ASSET_NAME), None, Some(0));
+        assert_matches!(first_chunk_response, None);
+
+        let first_chunk_gzip_response = asset_router.get_assets().get(
+            format!("/{}", TWO_CHUNKS_ASSET_NAME),
+            Some(AssetEncoding::Gzip),
+            Some(0),
+        );
+        assert_matches!(first_chunk_gzip_response, None);
+
+        let second_chunk_response = asset_router.get_assets().get(
+            format!("/{}", TWO_CHUNKS_ASSET_NAME),
+            None,
+            Some(ASSET_CHUNK_SIZE),
+        );
+        assert_matches!(second_chunk_response, None);
+
+        let second_chunk_gzip_response = asset_router.get_assets().get(
+            format!("/{}", TWO_CHUNKS_ASSET_NAME),
+            Some(AssetEncoding::Gzip),
+            Some(ASSET_CHUNK_SIZE),
+        );
+        assert_matches!(second_chunk_gzip_response, None);
     }
 
     #[rstest]
-    #[case(SIX_CHUNKS_ASSET_NAME, "deflate", AssetEncoding::Deflate)]
-    #[case(SIX_CHUNKS_ASSET_NAME, "deflate, identity", AssetEncoding::Deflate)]
-    #[case(SIX_CHUNKS_ASSET_NAME, "gzip", AssetEncoding::Gzip)]
-    #[case(SIX_CHUNKS_ASSET_NAME, "gzip, identity", AssetEncoding::Gzip)]
-    #[case(SIX_CHUNKS_ASSET_NAME, "gzip, deflate", AssetEncoding::Gzip)]
-    #[case(SIX_CHUNKS_ASSET_NAME, "gzip, deflate, identity", AssetEncoding::Gzip)]
-    #[case(SIX_CHUNKS_ASSET_NAME, "br", AssetEncoding::Brotli)]
-    #[case(
-        SIX_CHUNKS_ASSET_NAME,
-        "br, gzip, deflate, identity",
-        AssetEncoding::Brotli
-    )]
-    #[case(
-        SIX_CHUNKS_ASSET_NAME,
-        "gzip, deflate, identity, br",
-        AssetEncoding::Brotli
-    )]
-    fn test_encoded_long_asset_served_in_encoded_chunks(
-        #[case] asset_name: &str,
-        #[case] accept_encoding: &str,
-        #[case] expected_encoding: AssetEncoding,
-    ) {
-        let (_, expected_encoding_suffix) = expected_encoding.default_config();
-        let long_asset_router = long_asset_router_with_params(
-            &[asset_name],
-            &[AssetEncoding::Identity, expected_encoding],
-        );
-        let req_url = format!("/{asset_name}");
-        let encoded_asset_name = format!("{asset_name}{expected_encoding_suffix}");
-        let asset_body = long_asset_body(&encoded_asset_name);
-        let asset_len = asset_body.len();
+    fn test_asset_map_iter() {
+        let asset_router =
+            long_asset_router_with_params(&[TWO_CHUNKS_ASSET_NAME], &[AssetEncoding::Identity]);
+        let full_body = long_asset_body(TWO_CHUNKS_ASSET_NAME);
 
-        let request = HttpRequest::get(&req_url)
-            .with_headers(vec![(
-                "accept-encoding".to_string(),
-                accept_encoding.to_string(),
-            )])
-            .build();
-        let mut expected_response = build_206_response(
-            asset_body[0..ASSET_CHUNK_SIZE].to_vec(),
-            encoded_range_chunk_asset_cel_expr(),
+        let assets: Vec<_> = asset_router.get_assets().iter().collect();
+        assert!(assets.len() == 3);
+
+        let first_chunk_body = &full_body[0..ASSET_CHUNK_SIZE];
+        let expected_first_chunk_response = build_206_response(
+            first_chunk_body.to_vec(),
+            asset_range_chunk_cel_expr(),
             vec![
                 (
                     "cache-control".to_string(),
                     "public, no-cache, no-store".to_string(),
                 ),
+                ("vary".to_string(), "accept-encoding".to_string()),
                 ("content-type".to_string(), "text/html".to_string()),
                 (
-                    "content-encoding".to_string(),
-                    expected_encoding.to_string(),
+                    "content-range".to_string(),
+                    format!("bytes 0-{}/{}", first_chunk_body.len() - 1, full_body.len()),
+                ),
+            ],
+        );
+
+        assert!(assets.contains(&(
+            (&format!("/{}", TWO_CHUNKS_ASSET_NAME), None, Some(0)),
+            expected_first_chunk_response
+        )));
+
+        let second_chunk_body = &full_body[ASSET_CHUNK_SIZE..full_body.len()];
+        let expected_second_chunk_response = build_206_response(
+            second_chunk_body.to_vec(),
+            asset_range_chunk_cel_expr(),
+            vec![
+                (
+                    "cache-control".to_string(),
+                    "public, no-cache, no-store".to_string(),
                 ),
+                ("vary".to_string(), "accept-encoding".to_string()),
+                ("content-type".to_string(), "text/html".to_string()),
                 (
                     "content-range".to_string(),
-                    format!("bytes 0-{}/{}", ASSET_CHUNK_SIZE - 1, asset_len),
+                    format!(
+                        "bytes {}-{}/{}",
+                        first_chunk_body.len(),
+                        first_chunk_body.len() + second_chunk_body.len() - 1,
+                        full_body.len()
+                    ),
                 ),
             ],
         );
-        let response = long_asset_router
-            .serve_asset(&data_certificate(), &request)
-            .unwrap();
-        let (witness, expr_path) = extract_witness_expr_path(&response);
-        add_v2_certificate_header(
-            &data_certificate(),
-            &mut expected_response,
-            &witness,
-            &expr_path,
+        assert!(assets.contains(&(
+            (
+                &format!("/{}", TWO_CHUNKS_ASSET_NAME),
+                None,
+                Some(ASSET_CHUNK_SIZE)
+            ),
+            expected_second_chunk_response
+        )));
+
+        let expected_full_response = build_200_response(
+            full_body,
+            asset_cel_expr(),
+            vec![
+                (
+                    "cache-control".to_string(),
+                    "public, no-cache, no-store".to_string(),
+                ),
+                ("vary".to_string(), "accept-encoding".to_string()),
+                ("content-type".to_string(), "text/html".to_string()),
+            ],
         );
+        assert!(assets.contains(&(
+            (&format!("/{}", TWO_CHUNKS_ASSET_NAME), None, None),
+            expected_full_response
+        )));
+    }
 
+    #[rstest]
+    fn test_asset_map_iter_matching() {
+        let asset_router = asset_router();
+
+        let mut js_assets = asset_router
+            .get_assets()
+            .iter_matching("js/**/*.js")
+            .unwrap()
+            .map(|((path, encoding, range_begin), _)| (path, encoding, range_begin))
+            .collect::<Vec<_>>();
+        js_assets.sort();
         assert_eq!(
-            expr_path,
-            HttpCertificationPath::exact(req_url.clone()).to_expr_path()
+            js_assets,
+            vec![
+                ("/js/app-488df671.js", None, None),
+                ("/js/app-488df671.js", Some("br"), None),
+                ("/js/app-488df671.js", Some("deflate"), None),
+                ("/js/app-488df671.js", Some("gzip"), None),
+            ]
+        );
+
+        let mut css_assets = asset_router
+            .get_assets()
+            .iter_matching("**/*.css")
+            .unwrap()
+            .map(|((path, _, _), response)| (path, response.status_code()))
+            .collect::<Vec<_>>();
+        css_assets.sort_by_key(|(path, _)| *path);
+        assert_eq!(
+            css_assets,
+            vec![
+                ("/css/app-ba74b708.css", StatusCode::OK),
+                ("/css/app.css", StatusCode::TEMPORARY_REDIRECT),
+            ]
+        );
+
+        let mut fallback_scopes = asset_router
+            .get_fallback_assets()
+            .iter_matching("{css,js}")
+            .unwrap()
+            .map(|((path, _, _), _)| path)
+            .collect::<Vec<_>>();
+        fallback_scopes.sort();
+        fallback_scopes.dedup();
+        assert_eq!(fallback_scopes, vec!["/css", "/js"]);
+
+        assert_eq!(
+            asset_router
+                .get_assets()
+                .iter_matching("assets/**/*.js")
+                .unwrap()
+                .count(),
+            0
         );
         assert_matches!(
-            witness.lookup_subtree(&expr_path),
-            SubtreeLookupResult::Found(_)
+            asset_router.get_assets().iter_matching("js/[a-"),
+            Err(AssetCertificationError::GlobsetError(_))
+        );
+    }
+
+    #[rstest]
+    fn test_asset_map_metadata(
+        asset_router: AssetRouter,
+        index_html_body: Vec<u8>,
+        index_html_gz_body: Vec<u8>,
+        index_html_zz_body: Vec<u8>,
+        index_html_br_body: Vec<u8>,
+    ) {
+        let expected_encodings = vec![
+            AssetEncodingMetadata {
+                encoding: None,
+                content_length: index_html_body.len(),
+                chunk_count: 1,
+                sha256: hash(&index_html_body),
+            },
+            AssetEncodingMetadata {
+                encoding: Some(AssetEncoding::Brotli),
+                content_length: index_html_br_body.len(),
+                chunk_count: 1,
+                sha256: hash(&index_html_br_body),
+            },
+            AssetEncodingMetadata {
+                encoding: Some(AssetEncoding::Gzip),
+                content_length: index_html_gz_body.len(),
+                chunk_count: 1,
+                sha256: hash(&index_html_gz_body),
+            },
+            AssetEncodingMetadata {
+                encoding: Some(AssetEncoding::Deflate),
+                content_length: index_html_zz_body.len(),
+                chunk_count: 1,
+                sha256: hash(&index_html_zz_body),
+            },
+        ];
+
+        assert_eq!(
+            asset_router.get_assets().get_metadata("/index.html"),
+            Some(AssetMetadata {
+                path: "/index.html".to_string(),
+                status_code: StatusCode::OK,
+                integrity: Some(subresource_integrity(hash(&index_html_body))),
+                encodings: expected_encodings.clone(),
+            })
+        );
+        assert_eq!(
+            asset_router.get_fallback_assets().get_metadata("/"),
+            Some(AssetMetadata {
+                path: "/".to_string(),
+                status_code: StatusCode::OK,
+                integrity: Some(subresource_integrity(hash(&index_html_body))),
+                encodings: expected_encodings,
+            })
+        );
+        assert_eq!(
+            asset_router.get_assets().get_metadata("/old-url"),
+            Some(AssetMetadata {
+                path: "/old-url".to_string(),
+                status_code: StatusCode::MOVED_PERMANENTLY,
+                integrity: None,
+                encodings: vec![AssetEncodingMetadata {
+                    encoding: None,
+                    content_length: 0,
+                    chunk_count: 1,
+                    sha256: hash(&[]),
+                }],
+            })
+        );
+        assert_eq!(
+            asset_router.get_assets().get_metadata("/missing.html"),
+            None
+        );
+
+        let metadata = asset_router.get_assets().metadata();
+        let paths = metadata
+            .iter()
+            .map(|metadata| metadata.path.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            vec![
+                "/",
+                "/404",
+                "/404.html",
+                "/404/",
+                "/css/app-ba74b708.css",
+                "/css/app.css",
+                "/index.html",
+                "/index.html.br",
+                "/index.html.gz",
+                "/index.html.zz",
+                "/js/app-488df671.js",
+                "/js/app-488df671.js.br",
+                "/js/app-488df671.js.gz",
+                "/js/app-488df671.js.zz",
+                "/not-found",
+                "/not-found.html",
+                "/not-found.html.br",
+                "/not-found.html.gz",
+                "/not-found.html.zz",
+                "/not-found/",
+                "/not-found/index.html",
+                "/old-url",
+            ]
+        );
+    }
+
+    #[rstest]
+    fn test_asset_map_metadata_chunked_responses() {
+        let asset_router = long_asset_router_with_params(
+            &[TWO_CHUNKS_ASSET_NAME],
+            &[AssetEncoding::Gzip, AssetEncoding::Identity],
+        );
+        let full_body = long_asset_body(TWO_CHUNKS_ASSET_NAME);
+        let (_, gzip_suffix) = AssetEncoding::Gzip.default_config();
+        let full_gz_body = long_asset_body(&format!("{}{}", TWO_CHUNKS_ASSET_NAME, gzip_suffix));
+
+        let metadata = asset_router
+            .get_assets()
+            .get_metadata(format!("/{}", TWO_CHUNKS_ASSET_NAME))
+            .unwrap();
+
+        assert_eq!(metadata.status_code, StatusCode::OK);
+        assert_eq!(
+            metadata.encodings,
+            vec![
+                AssetEncodingMetadata {
+                    encoding: None,
+                    content_length: full_body.len(),
+                    chunk_count: 2,
+                    sha256: hash(&full_body),
+                },
+                AssetEncodingMetadata {
+                    encoding: Some(AssetEncoding::Gzip),
+                    content_length: full_gz_body.len(),
+                    chunk_count: 2,
+                    sha256: hash(&full_gz_body),
+                },
+            ]
         );
-        assert_eq!(response, expected_response);
+    }
 
-        // Request the subsequent chunks, should obtain them.
-        let expected_number_of_chunks =
-            (asset_len as f32 / ASSET_CHUNK_SIZE as f32).ceil() as usize;
-        let mut asset_len_so_far = response.body().len();
-        let mut number_of_chunks_so_far = 1;
-        while asset_len_so_far < asset_len {
-            let chunk_request = HttpRequest::get(&req_url)
-                .with_headers(vec![
-                    ("range".to_string(), format!("bytes={}-", asset_len_so_far)),
-                    ("accept-encoding".to_string(), accept_encoding.to_string()),
-                ])
-                .build();
-            let expected_range_end = cmp::min(asset_len_so_far + ASSET_CHUNK_SIZE, asset_len) - 1;
-            let mut expected_response = build_206_response(
-                asset_body[asset_len_so_far..=expected_range_end].to_vec(),
-                encoded_range_chunk_asset_cel_expr(),
-                vec![
-                    (
-                        "cache-control".to_string(),
-                        "public, no-cache, no-store".to_string(),
-                    ),
-                    ("content-type".to_string(), "text/html".to_string()),
-                    (
-                        "content-encoding".to_string(),
-                        expected_encoding.to_string(),
-                    ),
-                    (
-                        "content-range".to_string(),
-                        format!(
-                            "bytes {}-{}/{}",
-                            asset_len_so_far, expected_range_end, asset_len
-                        ),
-                    ),
-                ],
-            );
-            let response = long_asset_router
-                .serve_asset(&data_certificate(), &chunk_request)
-                .unwrap();
-            let (witness, expr_path) = extract_witness_expr_path(&response);
-            assert_matches!(
-                witness.lookup_subtree(&expr_path),
-                SubtreeLookupResult::Found(_)
+    #[rstest]
+    fn test_asset_bodies_are_shared() {
+        let asset_router = asset_router();
+        fn bytes<'a, 'b>(body: &'b AssetBody<'a>) -> &'b Arc<Cow<'a, [u8]>> {
+            match body {
+                AssetBody::Bytes(bytes) | AssetBody::Precertified(bytes, _) => bytes,
+                AssetBody::Reader(_) | AssetBody::Chunked(_) => {
+                    panic!("Expected the body to be held in memory")
+                }
+            }
+        }
+        fn body<'a>(
+            responses: &HashMap<RequestKey, CertifiedAssetResponse<'a>>,
+            path: &str,
+        ) -> Arc<Cow<'a, [u8]>> {
+            Arc::clone(bytes(
+                &responses[&RequestKey::new(path, None, None)].response.body,
+            ))
+        }
+
+        let index_html_body = body(&asset_router.responses, "/index.html");
+        assert!(Arc::ptr_eq(
+            &index_html_body,
+            &body(&asset_router.responses, "/")
+        ));
+        assert!(Arc::ptr_eq(
+            &index_html_body,
+            &body(&asset_router.fallback_responses, "/")
+        ));
+
+        let long_asset_router = long_asset_router_with_params(
+            &[TWO_CHUNKS_ASSET_NAME],
+            &[AssetEncoding::Identity, AssetEncoding::Gzip],
+        );
+        let path = format!("/{}", TWO_CHUNKS_ASSET_NAME);
+        let mut encoding_bodies = vec![];
+        for encoding in [None, Some("gzip".to_string())] {
+            let full_response =
+                &long_asset_router.responses[&RequestKey::new(&path, encoding.clone(), None)];
+            let first_chunk_response =
+                &long_asset_router.responses[&RequestKey::new(&path, encoding.clone(), Some(0))];
+            let second_chunk_response = &long_asset_router.responses
+                [&RequestKey::new(&path, encoding, Some(ASSET_CHUNK_SIZE))];
+
+            let full_body = bytes(&full_response.response.body);
+            assert!(Arc::ptr_eq(
+                full_body,
+                bytes(&first_chunk_response.response.body)
+            ));
+            assert!(Arc::ptr_eq(
+                full_body,
+                bytes(&second_chunk_response.response.body)
+            ));
+            assert_eq!(
+                first_chunk_response.response.body(),
+                &full_response.response.body()[..ASSET_CHUNK_SIZE]
             );
-            add_v2_certificate_header(
-                &data_certificate(),
-                &mut expected_response,
-                &witness,
-                &expr_path,
+            assert_eq!(
+                second_chunk_response.response.body(),
+                &full_response.response.body()[ASSET_CHUNK_SIZE..]
             );
-            assert_eq!(response, expected_response);
-            asset_len_so_far += response.body().len();
-            number_of_chunks_so_far += 1;
+            // a single allocation is referenced by the full response and both chunks
+            assert_eq!(Arc::strong_count(full_body), 3);
+
+            encoding_bodies.push(Arc::clone(full_body));
         }
-        assert_eq!(number_of_chunks_so_far, expected_number_of_chunks)
+        assert!(!Arc::ptr_eq(&encoding_bodies[0], &encoding_bodies[1]));
     }
 
     #[rstest]
-    #[case(TWO_CHUNKS_ASSET_NAME, AssetEncoding::Brotli)]
-    #[case(TWO_CHUNKS_ASSET_NAME, AssetEncoding::Gzip)]
-    #[case(TWO_CHUNKS_ASSET_NAME, AssetEncoding::Deflate)]
-    fn test_encoded_long_asset_deletion_removes_encoded_chunks(
-        #[case] asset_name: &str,
-        #[case] encoding: AssetEncoding,
-    ) {
-        let (_, encoding_suffix) = encoding.default_config();
-        let mut long_asset_router =
-            long_asset_router_with_params(&[asset_name], &[AssetEncoding::Identity, encoding]);
-        let req_url = format!("/{asset_name}");
-        let encoded_asset_name = format!("{asset_name}{encoding_suffix}");
-        let encoded_asset_body = long_asset_body(&encoded_asset_name);
-        let asset_len = encoded_asset_body.len();
-        let mut all_requests = vec![];
-        // Request the entire asset and the chunks, all should succeed.
-        // First the asset...
-        let request = HttpRequest::get(&req_url)
-            .with_headers(vec![("accept-encoding".to_string(), encoding.to_string())])
+    fn test_serve_asset_borrows_body() {
+        let index_html_body = b"<html><body>Hello World!</body></html>".to_vec();
+        let long_body = long_asset_body(TWO_CHUNKS_ASSET_NAME);
+
+        let mut asset_router = AssetRouter::default();
+        asset_router
+            .certify_assets(
+                vec![
+                    Asset::new("index.html", index_html_body.as_slice()),
+                    Asset::new(TWO_CHUNKS_ASSET_NAME, long_body.as_slice()),
+                ],
+                vec![
+                    index_html_config(),
+                    long_asset_config(TWO_CHUNKS_ASSET_NAME),
+                ],
+            )
+            .unwrap();
+
+        for path in ["/", "/index.html", "/nested/page"] {
+            let request = HttpRequest::get(path).build();
+            let response = asset_router
+                .serve_asset(&data_certificate(), &request)
+                .unwrap();
+
+            assert_eq!(response.body(), index_html_body.as_slice());
+            assert_eq!(response.body().as_ptr(), index_html_body.as_ptr());
+            assert_eq!(
+                response.headers().last().map(|(name, _)| name.as_str()),
+                Some(CERTIFICATE_HEADER_NAME)
+            );
+        }
+
+        let request = HttpRequest::get(format!("/{}", TWO_CHUNKS_ASSET_NAME))
+            .with_headers(vec![(
+                "range".to_string(),
+                format!("bytes={}-", ASSET_CHUNK_SIZE),
+            )])
             .build();
-        let response = long_asset_router
+        let response = asset_router
             .serve_asset(&data_certificate(), &request)
             .unwrap();
-        let (witness, expr_path) = extract_witness_expr_path(&response);
+        let expected_body = &long_body[ASSET_CHUNK_SIZE..];
 
-        assert_eq!(expr_path, vec!["http_expr", &req_url[1..], "<$>"]);
-        assert_matches!(
-            witness.lookup_subtree(&expr_path),
-            SubtreeLookupResult::Found(_)
-        );
         assert_eq!(response.status_code(), StatusCode::PARTIAL_CONTENT);
-        all_requests.push(request);
+        assert_eq!(response.body(), expected_body);
+        assert_eq!(response.body().as_ptr(), expected_body.as_ptr());
+    }
 
-        // ... then the subsequent chunks.
-        let expected_number_of_chunks =
-            (asset_len as f32 / ASSET_CHUNK_SIZE as f32).ceil() as usize;
-        let mut asset_len_so_far = response.body().len();
-        let mut number_of_chunks_so_far = 1;
-        while asset_len_so_far < asset_len {
-            let chunk_request = HttpRequest::get(&req_url)
-                .with_headers(vec![
-                    ("range".to_string(), format!("bytes={asset_len_so_far}-")),
-                    ("accept-encoding".to_string(), encoding.to_string()),
-                ])
-                .build();
-            let response = long_asset_router
-                .serve_asset(&data_certificate(), &chunk_request)
-                .unwrap();
-            let (witness, expr_path) = extract_witness_expr_path(&response);
-            assert_matches!(
-                witness.lookup_subtree(&expr_path),
-                SubtreeLookupResult::Found(_)
-            );
-            assert_eq!(response.status_code(), StatusCode::PARTIAL_CONTENT);
-            asset_len_so_far += response.body().len();
-            number_of_chunks_so_far += 1;
-            all_requests.push(chunk_request);
-        }
-        assert_eq!(number_of_chunks_so_far, expected_number_of_chunks);
-        assert_eq!(all_requests.len(), expected_number_of_chunks);
+    #[rstest]
+    fn test_serve_asset_borrowed_borrows_owned_body() {
+        let index_html_body = b"<html><body>Hello World!</body></html>".to_vec();
+        let long_body = long_asset_body(TWO_CHUNKS_ASSET_NAME);
 
-        // Delete the asset.
-        long_asset_router
-            .delete_assets(
+        let mut asset_router = AssetRouter::default();
+        asset_router
+            .certify_assets(
                 vec![
-                    Asset::new(&req_url, long_asset_body(asset_name)),
-                    Asset::new(&format!("/{encoded_asset_name}"), encoded_asset_body),
+                    Asset::new("index.html", index_html_body.clone()),
+                    Asset::new(TWO_CHUNKS_ASSET_NAME, long_body.clone()),
+                ],
+                vec![
+                    index_html_config(),
+                    long_asset_config(TWO_CHUNKS_ASSET_NAME),
                 ],
-                vec![long_asset_config(&req_url)],
             )
-            .expect("Asset deletion failed");
+            .unwrap();
 
-        // Re-request the asset and the chunks, all should fail.
-        for request in all_requests {
-            let result = long_asset_router.serve_asset(&data_certificate(), &request);
-            assert_matches!(
-                result,
-                Err(AssetCertificationError::NoAssetMatchingRequestUrl {
-                    request_url,
-                 }) if request_url == request.get_path().unwrap()
+        let requests = [
+            HttpRequest::get("/index.html").build(),
+            HttpRequest::get("/nested/page").build(),
+            HttpRequest::get(format!("/{}", TWO_CHUNKS_ASSET_NAME))
+                .with_headers(vec![(
+                    "range".to_string(),
+                    format!("bytes={}-", ASSET_CHUNK_SIZE),
+                )])
+                .build(),
+        ];
+        for request in requests {
+            let response = asset_router
+                .serve_asset_borrowed(&data_certificate(), &request)
+                .unwrap();
+            let owned_response = asset_router
+                .serve_asset(&data_certificate(), &request)
+                .unwrap();
+
+            // the owned content is copied into the owned response, but not into the borrowed one
+            assert_eq!(response, owned_response);
+            assert_ne!(response.body().as_ptr(), owned_response.body().as_ptr());
+            assert_eq!(
+                response.body().as_ptr(),
+                asset_router
+                    .serve_asset_borrowed(&data_certificate(), &request)
+                    .unwrap()
+                    .body()
+                    .as_ptr()
             );
         }
     }
 
     #[rstest]
-    #[case(index_html_zz_body(), "/", "deflate", "deflate")]
-    #[case(index_html_zz_body(), "/", "deflate, identity", "deflate")]
-    #[case(index_html_gz_body(), "/", "gzip", "gzip")]
-    #[case(index_html_gz_body(), "/", "gzip, identity", "gzip")]
-    #[case(index_html_gz_body(), "/", "gzip, deflate", "gzip")]
-    #[case(index_html_gz_body(), "/", "gzip, deflate, identity", "gzip")]
-    #[case(index_html_br_body(), "/", "br", "br")]
-    #[case(index_html_br_body(), "/", "br, gzip, deflate, identity", "br")]
-    #[case(index_html_br_body(), "/", "gzip, deflate, identity, br", "br")]
-    #[case(index_html_zz_body(), "/index.html", "deflate", "deflate")]
-    #[case(index_html_zz_body(), "/index.html", "deflate, identity", "deflate")]
-    #[case(index_html_gz_body(), "/index.html", "gzip", "gzip")]
-    #[case(index_html_gz_body(), "/index.html", "gzip, identity", "gzip")]
-    #[case(index_html_gz_body(), "/index.html", "gzip, deflate", "gzip")]
-    #[case(index_html_gz_body(), "/index.html", "gzip, deflate, identity", "gzip")]
-    #[case(index_html_br_body(), "/index.html", "br", "br")]
-    #[case(
-        index_html_br_body(),
-        "/index.html",
-        "br, gzip, deflate, identity",
-        "br"
-    )]
-    #[case(
-        index_html_br_body(),
-        "/index.html",
-        "gzip, deflate, identity, br",
-        "br"
-    )]
-    fn test_encoded_index_html(
-        #[case] expected_body: Vec<u8>,
-        #[case] req_url: &str,
-        #[case] accept_encoding: &str,
-        #[case] expected_encoding: &str,
-        mut asset_router: AssetRouter,
+    #[case(0, "/app/admin/users", "admin.html")]
+    #[case(0, "/app/admin", "app.html")]
+    #[case(0, "/app/users", "app.html")]
+    #[case(0, "/users", "index.html")]
+    #[case(1, "/app/admin/users", "admin.html")]
+    #[case(1, "/app/users", "app.html")]
+    #[case(1, "/users", "index.html")]
+    fn test_fallback_priority_nested_scopes(
+        #[case] root_priority: i32,
+        #[case] request_path: &str,
+        #[case] expected_asset: &str,
     ) {
-        let request = HttpRequest::get(req_url)
-            .with_headers(vec![(
-                "accept-encoding".to_string(),
-                accept_encoding.to_string(),
-            )])
-            .build();
-        let mut expected_response = build_200_response(
-            expected_body,
-            encoded_asset_cel_expr(),
-            vec![
-                (
-                    "cache-control".to_string(),
-                    "public, no-cache, no-store".to_string(),
-                ),
-                ("content-type".to_string(), "text/html".to_string()),
-                (
-                    "content-encoding".to_string(),
-                    expected_encoding.to_string(),
-                ),
-            ],
-        );
+        let mut asset_router = AssetRouter::default();
+        asset_router
+            .certify_assets(
+                vec![
+                    Asset::new("index.html", b"index.html".as_slice()),
+                    Asset::new("app.html", b"app.html".as_slice()),
+                    Asset::new("admin.html", b"admin.html".as_slice()),
+                ],
+                vec![
+                    fallback_config("index.html", "/", root_priority),
+                    fallback_config("app.html", "/app", 0),
+                    fallback_config("admin.html", "/app/admin", 0),
+                ],
+            )
+            .unwrap();
+
+        let request = HttpRequest::get(request_path).build();
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+
+        assert_eq!(response.body(), expected_asset.as_bytes());
+    }
+
+    #[rstest]
+    #[case(vec!["index.html", "app.html"])]
+    #[case(vec!["app.html", "index.html"])]
+    fn test_fallback_priority_same_scope(#[case] certification_order: Vec<&str>) {
+        let mut asset_router = AssetRouter::default();
+        let asset_configs = vec![
+            fallback_config("index.html", "/", 1),
+            fallback_config("app.html", "/", 0),
+        ];
+        for path in certification_order {
+            asset_router
+                .certify_assets(
+                    vec![Asset::new(path, path.as_bytes())],
+                    asset_configs.clone(),
+                )
+                .unwrap();
+        }
+
+        let request = HttpRequest::get("/users").build();
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        assert_eq!(response.body(), b"index.html");
+
+        asset_router
+            .delete_assets(
+                vec![Asset::new("app.html", b"app.html".as_slice())],
+                asset_configs.clone(),
+            )
+            .unwrap();
         let response = asset_router
             .serve_asset(&data_certificate(), &request)
             .unwrap();
-        let (witness, expr_path) = extract_witness_expr_path(&response);
-        add_v2_certificate_header(
-            &data_certificate(),
-            &mut expected_response,
-            &witness,
-            &expr_path,
-        );
+        assert_eq!(response.body(), b"index.html");
 
-        assert_eq!(
-            expr_path,
-            HttpCertificationPath::exact(req_url).to_expr_path()
-        );
+        asset_router
+            .delete_assets(
+                vec![Asset::new("index.html", b"index.html".as_slice())],
+                asset_configs,
+            )
+            .unwrap();
         assert_matches!(
-            witness.lookup_subtree(&expr_path),
-            SubtreeLookupResult::Found(_)
+            asset_router.serve_asset(&data_certificate(), &request),
+            Err(AssetCertificationError::NoAssetMatchingRequestUrl { .. })
         );
-        assert_eq!(response, expected_response);
+        assert_eq!(
+            asset_router.root_hash(),
+            HttpCertificationTree::default().root_hash()
+        );
+    }
+
+    #[rstest]
+    #[case(vec!["index.html", "app.html"])]
+    #[case(vec!["app.html", "index.html"])]
+    fn test_fallback_priority_delete_then_serve(#[case] certification_order: Vec<&str>) {
+        let mut asset_router = AssetRouter::default();
+        let asset_configs = vec![
+            AssetConfig::File {
+                path: "index.html".to_string(),
+                content_type: Some("text/html".to_string()),
+                headers: vec![],
+                fallback_for: vec![AssetFallbackConfig::new("/").with_priority(1)],
+                aliased_by: vec![],
+                encodings: vec![],
+                image_variants: vec![],
+            },
+            fallback_config("app.html", "/", 0),
+        ];
+        for path in certification_order {
+            asset_router
+                .certify_assets(
+                    vec![Asset::new(path, path.as_bytes())],
+                    asset_configs.clone(),
+                )
+                .unwrap();
+        }
 
         asset_router
             .delete_assets(
-                vec![
-                    Asset::new("index.html", index_html_body()),
-                    Asset::new("index.html.gz", index_html_gz_body()),
-                    Asset::new("index.html.zz", index_html_zz_body()),
-                    Asset::new("index.html.br", index_html_br_body()),
-                ],
-                vec![index_html_config()],
+                vec![Asset::new("index.html", b"index.html".as_slice())],
+                asset_configs.clone(),
             )
             .unwrap();
 
-        let result = asset_router.serve_asset(&data_certificate(), &request);
+        let request = HttpRequest::get("/users").build();
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        assert_eq!(response.body(), b"app.html");
+
+        let mut expected_asset_router = AssetRouter::default();
+        expected_asset_router
+            .certify_assets(
+                vec![Asset::new("app.html", b"app.html".as_slice())],
+                asset_configs.clone(),
+            )
+            .unwrap();
+        assert_eq!(asset_router.root_hash(), expected_asset_router.root_hash());
+
+        // certifying the deleted fallback again shadows the reinstated one
+        asset_router
+            .certify_assets(
+                vec![Asset::new("index.html", b"index.html".as_slice())],
+                asset_configs.clone(),
+            )
+            .unwrap();
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        assert_eq!(response.body(), b"index.html");
+
+        // deleting a shadowed fallback does not reinstate it later on
+        asset_router
+            .delete_assets(
+                vec![Asset::new("app.html", b"app.html".as_slice())],
+                asset_configs.clone(),
+            )
+            .unwrap();
+        asset_router
+            .delete_assets(
+                vec![Asset::new("index.html", b"index.html".as_slice())],
+                asset_configs,
+            )
+            .unwrap();
         assert_matches!(
-            result,
-            Err(AssetCertificationError::NoAssetMatchingRequestUrl {
-                request_url,
-             }) if request_url == req_url
+            asset_router.serve_asset(&data_certificate(), &request),
+            Err(AssetCertificationError::NoAssetMatchingRequestUrl { .. })
+        );
+        assert_eq!(
+            asset_router.root_hash(),
+            HttpCertificationTree::default().root_hash()
         );
     }
 
     #[rstest]
-    #[case(index_html_zz_body(), "/something", "deflate", "deflate")]
-    #[case(index_html_zz_body(), "/something", "deflate, identity", "deflate")]
-    #[case(index_html_gz_body(), "/something", "gzip", "gzip")]
-    #[case(index_html_gz_body(), "/something", "gzip, identity", "gzip")]
-    #[case(index_html_gz_body(), "/something", "gzip, deflate", "gzip")]
-    #[case(index_html_gz_body(), "/something", "gzip, deflate, identity", "gzip")]
-    #[case(index_html_br_body(), "/something", "br", "br")]
-    #[case(
-        index_html_br_body(),
-        "/something",
-        "br, gzip, deflate, identity",
-        "br"
-    )]
-    #[case(
-        index_html_br_body(),
-        "/something",
-        "gzip, deflate, identity, br",
-        "br"
-    )]
-    #[case(index_html_zz_body(), "/assets/css/app.css", "deflate", "deflate")]
-    #[case(
-        index_html_zz_body(),
-        "/assets/css/app.css",
-        "deflate, identity",
-        "deflate"
-    )]
-    #[case(index_html_gz_body(), "/assets/css/app.css", "gzip", "gzip")]
-    #[case(index_html_gz_body(), "/assets/css/app.css", "gzip, identity", "gzip")]
-    #[case(index_html_gz_body(), "/assets/css/app.css", "gzip, deflate", "gzip")]
-    #[case(
-        index_html_gz_body(),
-        "/assets/css/app.css",
-        "gzip, deflate, identity",
-        "gzip"
-    )]
-    #[case(index_html_br_body(), "/assets/css/app.css", "br", "br")]
+    #[case("/", "index.html", "public, no-cache, no-store")]
+    #[case("/index.html", "index.html", "public, no-cache, no-store")]
+    #[case("/users/1", "index.html", "public, no-cache, no-store")]
+    #[case("/about.html", "about.html", "public, no-cache, no-store")]
     #[case(
-        index_html_br_body(),
-        "/assets/css/app.css",
-        "br, gzip, deflate, identity",
-        "br"
+        "/assets/index-4f2d3a1b.js",
+        "index-4f2d3a1b.js",
+        "public, max-age=31536000, immutable"
     )]
     #[case(
-        index_html_br_body(),
-        "/assets/css/app.css",
-        "gzip, deflate, identity, br",
-        "br"
+        "/assets/index-4f2d3a1b.css",
+        "index-4f2d3a1b.css",
+        "public, max-age=31536000, immutable"
     )]
-    fn test_encoded_index_html_fallback(
-        #[case] expected_body: Vec<u8>,
-        #[case] req_url: &str,
-        #[case] accept_encoding: &str,
-        #[case] expected_encoding: &str,
-        mut asset_router: AssetRouter,
+    fn test_spa_config(
+        #[case] request_path: &str,
+        #[case] expected_body: &str,
+        #[case] expected_cache_control: &str,
     ) {
-        let request = HttpRequest::get(req_url)
-            .with_headers(vec![(
-                "accept-encoding".to_string(),
-                accept_encoding.to_string(),
-            )])
-            .build();
-        let mut expected_response = build_200_response(
-            expected_body,
-            encoded_asset_cel_expr(),
-            vec![
-                (
-                    "cache-control".to_string(),
-                    "public, no-cache, no-store".to_string(),
-                ),
-                ("content-type".to_string(), "text/html".to_string()),
-                (
-                    "content-encoding".to_string(),
-                    expected_encoding.to_string(),
-                ),
-            ],
-        );
+        let mut asset_router = AssetRouter::default();
+        asset_router
+            .certify_assets(
+                vec![
+                    Asset::new("index.html", b"index.html".as_slice()),
+                    Asset::new("about.html", b"about.html".as_slice()),
+                    Asset::new("assets/index-4f2d3a1b.js", b"index-4f2d3a1b.js".as_slice()),
+                    Asset::new(
+                        "assets/index-4f2d3a1b.css",
+                        b"index-4f2d3a1b.css".as_slice(),
+                    ),
+                ],
+                AssetConfig::spa("index.html"),
+            )
+            .unwrap();
 
+        let request = HttpRequest::get(request_path).build();
         let response = asset_router
             .serve_asset(&data_certificate(), &request)
             .unwrap();
-        let (witness, expr_path) = extract_witness_expr_path(&response);
-        add_v2_certificate_header(
-            &data_certificate(),
-            &mut expected_response,
-            &witness,
-            &expr_path,
+
+        assert_eq!(response.status_code(), StatusCode::OK);
+        assert_eq!(response.body(), expected_body.as_bytes());
+        assert!(response.headers().contains(&(
+            "cache-control".to_string(),
+            expected_cache_control.to_string()
+        )));
+    }
+
+    #[rstest]
+    #[case("/missing", "404.html", StatusCode::NOT_FOUND, vec!["http_expr", "", "<*>"])]
+    #[case("/404.html", "404.html", StatusCode::NOT_FOUND, vec!["http_expr", "", "<*>"])]
+    #[case("/app/users", "app.html", StatusCode::OK, vec!["http_expr", "app", "<*>"])]
+    fn test_serve_asset_not_found_error_page(
+        #[case] request_path: &str,
+        #[case] expected_body: &str,
+        #[case] expected_status_code: StatusCode,
+        #[case] expected_expr_path: Vec<&str>,
+    ) {
+        let asset_router = error_page_asset_router();
+
+        let request = HttpRequest::get(request_path).build();
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        let (_, expr_path) = extract_witness_expr_path(&response);
+
+        assert_eq!(response.status_code(), expected_status_code);
+        assert_eq!(response.body(), expected_body.as_bytes());
+        assert_eq!(expr_path, expected_expr_path);
+    }
+
+    #[rstest]
+    #[case("/users", StatusCode::FORBIDDEN, "403.html", vec!["http_expr", "", "<*>"])]
+    #[case("/users", StatusCode::INTERNAL_SERVER_ERROR, "500.html", vec!["http_expr", "", "<*>"])]
+    #[case("/users", StatusCode::NOT_FOUND, "404.html", vec!["http_expr", "", "<*>"])]
+    #[case("/admin", StatusCode::FORBIDDEN, "403.html", vec!["http_expr", "", "<*>"])]
+    #[case("/admin/users", StatusCode::FORBIDDEN, "admin-403.html", vec!["http_expr", "admin", "<*>"])]
+    #[case("/admin/", StatusCode::FORBIDDEN, "admin-403.html", vec!["http_expr", "admin", "<*>"])]
+    fn test_serve_error(
+        #[case] request_path: &str,
+        #[case] status_code: StatusCode,
+        #[case] expected_body: &str,
+        #[case] expected_expr_path: Vec<&str>,
+    ) {
+        let asset_router = error_page_asset_router();
+
+        let request = HttpRequest::get(request_path).build();
+        let response = asset_router
+            .serve_error(&data_certificate(), &request, status_code)
+            .unwrap();
+        let (_, expr_path) = extract_witness_expr_path(&response);
+
+        assert_eq!(response.status_code(), status_code);
+        assert_eq!(response.body(), expected_body.as_bytes());
+        assert_eq!(expr_path, expected_expr_path);
+    }
+
+    #[rstest]
+    fn test_serve_asset_error_page_more_specific_scope() {
+        let asset_router = error_page_asset_router();
+
+        let request = HttpRequest::get("/admin/missing").build();
+        let result = asset_router.serve_asset(&data_certificate(), &request);
+
+        assert_matches!(
+            result,
+            Err(AssetCertificationError::NoAssetMatchingRequestUrl { request_url }) if request_url == "/admin/missing"
         );
+    }
+
+    #[rstest]
+    #[case("/index.html", StatusCode::FORBIDDEN)]
+    #[case("/app/users", StatusCode::FORBIDDEN)]
+    #[case("/admin/users", StatusCode::INTERNAL_SERVER_ERROR)]
+    #[case("/users", StatusCode::UNAUTHORIZED)]
+    fn test_serve_error_not_found(#[case] request_path: &str, #[case] status_code: StatusCode) {
+        let asset_router = error_page_asset_router();
+
+        let request = HttpRequest::get(request_path).build();
+        let result = asset_router.serve_error(&data_certificate(), &request, status_code);
 
-        let requested_expr_path = HttpCertificationPath::exact(req_url).to_expr_path();
-        assert_eq!(expr_path, vec!["http_expr", "", "<*>"]);
         assert_matches!(
-            witness.lookup_subtree(&expr_path),
-            SubtreeLookupResult::Found(_)
+            result,
+            Err(AssetCertificationError::NoErrorPageMatchingRequestUrl {
+                request_url,
+                status_code: error_status_code,
+            }) if request_url == request_path && error_status_code == status_code
         );
+    }
+
+    #[rstest]
+    fn test_delete_error_pages() {
+        let mut asset_router = error_page_asset_router();
+        let request = HttpRequest::get("/admin/users").build();
+
+        asset_router
+            .delete_assets(
+                vec![Asset::new("admin-403.html", b"admin-403.html".as_slice())],
+                error_page_configs(),
+            )
+            .unwrap();
+        let response = asset_router
+            .serve_error(&data_certificate(), &request, StatusCode::FORBIDDEN)
+            .unwrap();
+        assert_eq!(response.body(), b"403.html");
+
+        asset_router
+            .delete_assets(error_page_assets(), error_page_configs())
+            .unwrap();
         assert_matches!(
-            witness.lookup_subtree(&requested_expr_path),
-            SubtreeLookupResult::Absent
+            asset_router.serve_error(&data_certificate(), &request, StatusCode::FORBIDDEN),
+            Err(AssetCertificationError::NoErrorPageMatchingRequestUrl { .. })
+        );
+        assert_eq!(
+            asset_router.root_hash(),
+            HttpCertificationTree::default().root_hash()
+        );
+
+        let mut asset_router = error_page_asset_router();
+        asset_router.clear();
+        assert_matches!(
+            asset_router.serve_error(&data_certificate(), &request, StatusCode::FORBIDDEN),
+            Err(AssetCertificationError::NoErrorPageMatchingRequestUrl { .. })
+        );
+        assert_eq!(
+            asset_router.root_hash(),
+            HttpCertificationTree::default().root_hash()
+        );
+    }
+
+    #[rstest]
+    #[case("/", Some("index.html"))]
+    #[case("/index.html", Some("index.html"))]
+    #[case("/about", Some("about/index.html"))]
+    #[case("/about/", Some("about/index.html"))]
+    #[case("/about/index.html", Some("about/index.html"))]
+    #[case("/contact", Some("contact.html"))]
+    #[case("/contact/", Some("contact.html"))]
+    #[case("/team/", Some("team.html"))]
+    #[case("/team", Some("team.html"))]
+    #[case("/app.js", Some("app.js"))]
+    #[case("/app.js/", None)]
+    #[case("/contact.html/", None)]
+    fn test_trailing_slash_aliases(#[case] request_url: &str, #[case] expected_body: Option<&str>) {
+        let assets = || {
+            [
+                "index.html",
+                "about/index.html",
+                "contact.html",
+                "team.html",
+                "app.js",
+            ]
+            .map(|path| Asset::new(path, path.as_bytes()))
+        };
+        let asset_configs = || {
+            vec![
+                AssetConfig::File {
+                    path: "contact.html".to_string(),
+                    content_type: None,
+                    headers: vec![],
+                    fallback_for: vec![],
+                    aliased_by: vec!["/contact".to_string()],
+                    encodings: vec![],
+                    image_variants: vec![],
+                },
+                AssetConfig::File {
+                    path: "team.html".to_string(),
+                    content_type: None,
+                    headers: vec![],
+                    fallback_for: vec![],
+                    aliased_by: vec!["/team/".to_string()],
+                    encodings: vec![],
+                    image_variants: vec![],
+                },
+            ]
+        };
+        let mut asset_router = AssetRouter::default().with_trailing_slash_aliases();
+        asset_router
+            .certify_assets(assets(), asset_configs())
+            .unwrap();
+
+        let request = HttpRequest::get(request_url).build();
+        let response = asset_router.serve_asset(&data_certificate(), &request);
+        match expected_body {
+            Some(expected_body) => {
+                let response = response.unwrap();
+                let (witness, expr_path) = extract_witness_expr_path(&response);
+
+                assert_eq!(response.body(), expected_body.as_bytes());
+                assert_eq!(
+                    expr_path,
+                    HttpCertificationPath::exact(request_url).to_expr_path()
+                );
+                assert_matches!(
+                    witness.lookup_subtree(&expr_path),
+                    SubtreeLookupResult::Found(_)
+                );
+            }
+            None => assert_matches!(
+                response,
+                Err(AssetCertificationError::NoAssetMatchingRequestUrl { .. })
+            ),
+        }
+
+        asset_router
+            .delete_assets(assets(), asset_configs())
+            .unwrap();
+        assert_eq!(
+            asset_router.root_hash(),
+            HttpCertificationTree::default().root_hash()
+        );
+        assert!(asset_router.get_assets().iter().next().is_none());
+    }
+
+    #[rstest]
+    #[case("/", Some("index.html"))]
+    #[case("/about", Some("about/index.html"))]
+    #[case("/about/", Some("about/index.html"))]
+    #[case("/docs/api", Some("docs/api/index.html"))]
+    #[case("/docs/api/", Some("docs/api/index.html"))]
+    #[case("/docs", None)]
+    #[case("/contact", Some("contact.html"))]
+    #[case("/contact/", None)]
+    fn test_directory_index_aliases(
+        #[case] request_url: &str,
+        #[case] expected_body: Option<&str>,
+    ) {
+        let assets = || {
+            [
+                "index.html",
+                "about/index.html",
+                "docs/api/index.html",
+                "contact.html",
+            ]
+            .map(|path| Asset::new(path, path.as_bytes()))
+        };
+        let asset_configs = || {
+            vec![AssetConfig::File {
+                path: "contact.html".to_string(),
+                content_type: None,
+                headers: vec![],
+                fallback_for: vec![],
+                aliased_by: vec!["/contact".to_string()],
+                encodings: vec![],
+                image_variants: vec![],
+            }]
+        };
+        let mut asset_router = AssetRouter::default().with_directory_index_aliases();
+        asset_router
+            .certify_assets(assets(), asset_configs())
+            .unwrap();
+
+        let request = HttpRequest::get(request_url).build();
+        let response = asset_router.serve_asset(&data_certificate(), &request);
+        match expected_body {
+            Some(expected_body) => {
+                let response = response.unwrap();
+                let (witness, expr_path) = extract_witness_expr_path(&response);
+
+                assert_eq!(response.body(), expected_body.as_bytes());
+                assert_eq!(
+                    expr_path,
+                    HttpCertificationPath::exact(request_url).to_expr_path()
+                );
+                assert_matches!(
+                    witness.lookup_subtree(&expr_path),
+                    SubtreeLookupResult::Found(_)
+                );
+            }
+            None => assert_matches!(
+                response,
+                Err(AssetCertificationError::NoAssetMatchingRequestUrl { .. })
+            ),
+        }
+
+        asset_router
+            .delete_assets(assets(), asset_configs())
+            .unwrap();
+        assert_eq!(
+            asset_router.root_hash(),
+            HttpCertificationTree::default().root_hash()
         );
-        assert_eq!(response, expected_response);
+        assert!(asset_router.get_assets().iter().next().is_none());
+    }
 
+    #[rstest]
+    fn test_trailing_slash_aliases_disabled_by_default() {
+        let mut asset_router = AssetRouter::default();
         asset_router
-            .delete_assets(
-                vec![
-                    Asset::new("index.html", index_html_body()),
-                    Asset::new("index.html.gz", index_html_gz_body()),
-                    Asset::new("index.html.zz", index_html_zz_body()),
-                    Asset::new("index.html.br", index_html_br_body()),
-                ],
-                vec![index_html_config()],
+            .certify_assets(
+                vec![Asset::new("about/index.html", b"about".as_slice())],
+                vec![],
             )
             .unwrap();
 
-        let result = asset_router.serve_asset(&data_certificate(), &request);
-        assert_matches!(
-            result,
-            Err(AssetCertificationError::NoAssetMatchingRequestUrl {
-                request_url,
-             }) if request_url == req_url
-        );
+        for request_url in ["/about", "/about/"] {
+            let request = HttpRequest::get(request_url).build();
+            assert_matches!(
+                asset_router.serve_asset(&data_certificate(), &request),
+                Err(AssetCertificationError::NoAssetMatchingRequestUrl { .. })
+            );
+        }
     }
 
     #[rstest]
-    #[case("/something", "/something")]
-    #[case("https://internetcomputer.org/something", "/something")]
-    fn test_index_html_root_fallback(
-        mut asset_router: AssetRouter,
-        #[case] req_url: &str,
-        #[case] req_path: &str,
+    #[case(vec![], vec![], vec![], None)]
+    #[case(
+        vec![],
+        vec![AssetEncoding::Gzip.default_config()],
+        vec![],
+        Some("accept-encoding")
+    )]
+    #[case(
+        vec![("Vary".to_string(), "Accept-Encoding, Origin".to_string())],
+        vec![AssetEncoding::Gzip.default_config()],
+        vec![],
+        Some("Accept-Encoding, Origin"),
+    )]
+    #[case(vec![], vec![], vec![AssetImageFormat::Webp.default_config()], Some("accept"))]
+    #[case(
+        vec![],
+        vec![AssetEncoding::Gzip.default_config()],
+        vec![AssetImageFormat::Webp.default_config()],
+        Some("accept, accept-encoding")
+    )]
+    fn test_vary_header(
+        #[case] headers: Vec<(String, String)>,
+        #[case] encodings: Vec<(AssetEncoding, String)>,
+        #[case] image_variants: Vec<(AssetImageFormat, String)>,
+        #[case] expected_vary: Option<&str>,
     ) {
-        let mut expected_response = expected_index_html_response();
+        fn vary_headers(response: &HttpResponse) -> Vec<String> {
+            response
+                .headers()
+                .iter()
+                .filter(|(name, _)| name.eq_ignore_ascii_case("vary"))
+                .map(|(_, value)| value.clone())
+                .collect()
+        }
 
-        let request = HttpRequest::get(req_url).build();
-        let requested_expr_path = HttpCertificationPath::exact(req_path).to_expr_path();
+        let mut asset_router = AssetRouter::default();
+        asset_router
+            .certify_assets(
+                vec![
+                    Asset::new("app.js", b"app.js".as_slice()),
+                    Asset::new("app.js.gz", b"app.js.gz".as_slice()),
+                ],
+                vec![AssetConfig::Pattern {
+                    pattern: "*.js".to_string(),
+                    exclude: vec![],
+                    exclude_dotfiles: false,
+                    content_type: None,
+                    headers,
+                    encodings,
+                    image_variants,
+                }],
+            )
+            .unwrap();
+        let expected_vary: Vec<String> = expected_vary.into_iter().map(String::from).collect();
 
+        let request = HttpRequest::get("/app.js").build();
         let response = asset_router
             .serve_asset(&data_certificate(), &request)
             .unwrap();
-        let (witness, expr_path) = extract_witness_expr_path(&response);
-        add_v2_certificate_header(
-            &data_certificate(),
-            &mut expected_response,
-            &witness,
-            &expr_path,
-        );
+        assert_eq!(vary_headers(&response), expected_vary);
 
-        assert_eq!(expr_path, vec!["http_expr", "", "<*>"]);
-        assert_matches!(
-            witness.lookup_subtree(&expr_path),
-            SubtreeLookupResult::Found(_)
-        );
-        assert_matches!(
-            witness.lookup_subtree(&requested_expr_path),
-            SubtreeLookupResult::Absent
-        );
-        assert_eq!(response, expected_response);
+        let request = HttpRequest::get("/app.js")
+            .with_headers(vec![("accept-encoding".to_string(), "gzip".to_string())])
+            .build();
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        assert_eq!(vary_headers(&response), expected_vary);
+    }
 
+    #[rstest]
+    fn test_response_hook() {
+        let assets = || {
+            vec![
+                Asset::new("index.html", b"index.html".as_slice()),
+                Asset::new("index.html.gz", b"index.html.gz".as_slice()),
+                Asset::new("app.js", b"app.js".as_slice()),
+            ]
+        };
+        let asset_configs = || {
+            vec![AssetConfig::File {
+                path: "index.html".to_string(),
+                content_type: Some("text/html".to_string()),
+                headers: vec![],
+                fallback_for: vec![AssetFallbackConfig {
+                    scope: "/".to_string(),
+                    status_code: Some(StatusCode::NOT_FOUND),
+                    priority: 0,
+                }],
+                aliased_by: vec!["/".to_string()],
+                encodings: vec![AssetEncoding::Gzip.default_config()],
+                image_variants: vec![],
+            }]
+        };
+        let mut asset_router = AssetRouter::default().with_response_hook(|asset, response| {
+            if asset.url().ends_with(".html") || asset.url() == "/" {
+                response.add_header((
+                    "content-security-policy".to_string(),
+                    format!("default-src 'self'; report-uri {}", asset.url()),
+                ));
+            }
+            if response.status_code() == StatusCode::NOT_FOUND {
+                response.add_header(("cache-control".to_string(), "no-store".to_string()));
+            }
+        });
         asset_router
-            .delete_assets(
-                vec![
-                    Asset::new("index.html", index_html_body()),
-                    Asset::new("index.html.gz", index_html_gz_body()),
-                    Asset::new("index.html.zz", index_html_zz_body()),
-                    Asset::new("index.html.br", index_html_br_body()),
-                ],
-                vec![index_html_config()],
-            )
+            .certify_assets(assets(), asset_configs())
             .unwrap();
 
-        let result = asset_router.serve_asset(&data_certificate(), &request);
-        assert_matches!(
-            result,
-            Err(AssetCertificationError::NoAssetMatchingRequestUrl {
-                request_url,
-             }) if request_url == req_path
-        );
-    }
+        let csp_header = |response: &HttpResponse| {
+            response
+                .headers()
+                .iter()
+                .find(|(name, _)| name == "content-security-policy")
+                .map(|(_, value)| value.clone())
+        };
 
-    #[rstest]
-    #[case("/assets/css/app.css", "/assets/css/app.css")]
-    #[case(
-        "https://internetcomputer.org/assets/css/app.css",
-        "/assets/css/app.css"
-    )]
-    fn test_index_html_nested_fallback(
-        mut asset_router: AssetRouter,
-        #[case] req_url: &str,
-        #[case] req_path: &str,
-    ) {
-        let mut expected_response = expected_index_html_response();
+        for (request_url, encoding, expected_csp) in [
+            ("/", "identity", Some("default-src 'self'; report-uri /")),
+            (
+                "/index.html",
+                "identity",
+                Some("default-src 'self'; report-uri /index.html"),
+            ),
+            (
+                "/index.html",
+                "gzip",
+                Some("default-src 'self'; report-uri /index.html"),
+            ),
+            ("/app.js", "identity", None),
+        ] {
+            let request = HttpRequest::get(request_url)
+                .with_headers(vec![("accept-encoding".to_string(), encoding.to_string())])
+                .build();
+            let response = asset_router
+                .serve_asset(&data_certificate(), &request)
+                .unwrap();
+            let (witness, expr_path) = extract_witness_expr_path(&response);
 
-        let request = HttpRequest::get(req_url).build();
-        let requested_expr_path = HttpCertificationPath::exact(req_path).to_expr_path();
+            assert_eq!(csp_header(&response).as_deref(), expected_csp);
+            assert_matches!(
+                witness.lookup_subtree(&expr_path),
+                SubtreeLookupResult::Found(_)
+            );
+        }
 
+        let request = HttpRequest::get("/missing").build();
         let response = asset_router
             .serve_asset(&data_certificate(), &request)
             .unwrap();
-        let (witness, expr_path) = extract_witness_expr_path(&response);
-        add_v2_certificate_header(
-            &data_certificate(),
-            &mut expected_response,
-            &witness,
-            &expr_path,
-        );
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+        assert!(response
+            .headers()
+            .contains(&("cache-control".to_string(), "no-store".to_string())));
 
-        assert_eq!(expr_path, vec!["http_expr", "", "<*>"]);
-        assert_matches!(
-            witness.lookup_subtree(&expr_path),
-            SubtreeLookupResult::Found(_)
+        asset_router
+            .delete_assets(assets(), asset_configs())
+            .unwrap();
+        assert_eq!(
+            asset_router.root_hash(),
+            HttpCertificationTree::default().root_hash()
         );
-        assert_matches!(
-            witness.lookup_subtree(&requested_expr_path),
-            SubtreeLookupResult::Absent
+    }
+
+    #[rstest]
+    fn test_response_hook_certifies_modified_response() {
+        let mut asset_router = AssetRouter::default().with_response_hook(|_, response| {
+            response.add_header(("x-custom".to_string(), "custom".to_string()));
+        });
+        asset_router
+            .certify_assets(vec![Asset::new("app.js", b"app.js".as_slice())], vec![])
+            .unwrap();
+
+        let mut expected_router = AssetRouter::default();
+        expected_router
+            .certify_assets(
+                vec![Asset::new("app.js", b"app.js".as_slice())],
+                vec![AssetConfig::File {
+                    path: "app.js".to_string(),
+                    content_type: None,
+                    headers: vec![("x-custom".to_string(), "custom".to_string())],
+                    fallback_for: vec![],
+                    aliased_by: vec![],
+                    encodings: vec![],
+                    image_variants: vec![],
+                }],
+            )
+            .unwrap();
+
+        let request = HttpRequest::get("/app.js").build();
+        assert_eq!(asset_router.root_hash(), expected_router.root_hash());
+        assert_eq!(
+            asset_router
+                .serve_asset(&data_certificate(), &request)
+                .unwrap(),
+            expected_router
+                .serve_asset(&data_certificate(), &request)
+                .unwrap()
         );
-        assert_eq!(response, expected_response);
+    }
 
+    #[rstest]
+    fn test_response_hook_body() {
+        let bodies: Arc<RwLock<Vec<(String, Option<Vec<u8>>)>>> = Default::default();
+        let mut asset_router = AssetRouter::default().with_response_hook({
+            let bodies = bodies.clone();
+            move |asset, response| {
+                bodies
+                    .write()
+                    .unwrap()
+                    .push((asset.url().to_string(), response.body().map(<[u8]>::to_vec)));
+            }
+        });
         asset_router
-            .delete_assets(
+            .certify_assets(
                 vec![
-                    Asset::new("index.html", index_html_body()),
-                    Asset::new("index.html.gz", index_html_gz_body()),
-                    Asset::new("index.html.zz", index_html_zz_body()),
-                    Asset::new("index.html.br", index_html_br_body()),
+                    Asset::new("app.js", b"app.js".as_slice()),
+                    Asset::from_reader("style.css", b"style.css".to_vec()),
                 ],
-                vec![index_html_config()],
+                vec![],
             )
             .unwrap();
 
-        let result = asset_router.serve_asset(&data_certificate(), &request);
-        assert_matches!(
-            result,
-            Err(AssetCertificationError::NoAssetMatchingRequestUrl {
-                request_url,
-             }) if request_url == req_path
+        let mut bodies = bodies.read().unwrap().clone();
+        bodies.sort();
+        assert_eq!(
+            bodies,
+            vec![
+                ("/app.js".to_string(), Some(b"app.js".to_vec())),
+                ("/style.css".to_string(), None),
+            ]
         );
     }
 
     #[rstest]
-    #[case("/css/app-ba74b708.css")]
-    #[case("https://internetcomputer.org/css/app-ba74b708.css")]
-    fn text_app_css(mut asset_router: AssetRouter, #[case] req_url: &str) {
-        let request = HttpRequest::get(req_url).build();
-        let mut expected_response = build_200_response(
-            app_css_body(),
-            asset_cel_expr(),
-            vec![
-                (
-                    "cache-control".to_string(),
-                    "public, max-age=31536000, immutable".to_string(),
-                ),
-                ("content-type".to_string(), "text/css".to_string()),
-            ],
-        );
+    fn test_root_hash_change_hook() {
+        let root_hashes: Arc<RwLock<Vec<Hash>>> = Default::default();
+        let mut asset_router = AssetRouter::default().on_root_hash_change({
+            let root_hashes = root_hashes.clone();
+            move |root_hash| root_hashes.write().unwrap().push(*root_hash)
+        });
+        let assets = vec![
+            Asset::new("app.js", b"app.js".as_slice()),
+            Asset::new("style.css", b"style.css".as_slice()),
+        ];
 
-        let response = asset_router
-            .serve_asset(&data_certificate(), &request)
-            .unwrap();
-        let (witness, expr_path) = extract_witness_expr_path(&response);
-        add_v2_certificate_header(
-            &data_certificate(),
-            &mut expected_response,
-            &witness,
-            &expr_path,
-        );
+        asset_router.certify_assets(assets.clone(), vec![]).unwrap();
+        let certified_root_hash = asset_router.root_hash();
+        assert_eq!(*root_hashes.read().unwrap(), vec![certified_root_hash]);
 
-        assert_eq!(
-            expr_path,
-            vec!["http_expr", "css", "app-ba74b708.css", "<$>"]
-        );
-        assert_matches!(
-            witness.lookup_subtree(&expr_path),
-            SubtreeLookupResult::Found(_)
-        );
-        assert_eq!(response, expected_response);
+        // unchanged assets don't change the root hash
+        asset_router.sync_assets(assets.clone(), vec![]).unwrap();
+        assert_eq!(*root_hashes.read().unwrap(), vec![certified_root_hash]);
 
+        // operations built on top of other operations only run the hook once
         asset_router
-            .delete_assets(
-                vec![Asset::new("css/app-ba74b708.css", app_css_body())],
-                vec![css_config()],
+            .certify_hashed_assets(
+                vec![Asset::new("app-488df671.js", b"app".as_slice())],
+                vec![],
+                HashedAssetAliasConfig {
+                    manifest_path: "asset-manifest.json".to_string(),
+                    headers: vec![],
+                },
             )
             .unwrap();
-        let mut expected_response = build_response(
-            StatusCode::NOT_FOUND,
-            not_found_html_body(),
-            asset_cel_expr(),
-            vec![
-                (
-                    "cache-control".to_string(),
-                    "public, no-cache, no-store".to_string(),
-                ),
-                ("content-type".to_string(), "text/html".to_string()),
-            ],
+        assert_eq!(root_hashes.read().unwrap().len(), 2);
+
+        asset_router.delete_assets_by_path(vec!["/app.js"]);
+        asset_router.delete_all_assets();
+
+        assert_eq!(
+            *root_hashes.read().unwrap().last().unwrap(),
+            HttpCertificationTree::default().root_hash()
         );
+        assert_eq!(root_hashes.read().unwrap().len(), 4);
+    }
 
-        let response = asset_router
-            .serve_asset(&data_certificate(), &request)
+    struct RecordingAssetObserver(Arc<RwLock<Vec<String>>>);
+
+    impl AssetObserver for RecordingAssetObserver {
+        fn on_certified(&mut self, path: &str, encoding: Option<&str>) {
+            self.0
+                .write()
+                .unwrap()
+                .push(format!("certified {path} {encoding:?}"));
+        }
+
+        fn on_deleted(&mut self, path: &str) {
+            self.0.write().unwrap().push(format!("deleted {path}"));
+        }
+    }
+
+    #[rstest]
+    fn test_asset_observer() {
+        let events: Arc<RwLock<Vec<String>>> = Default::default();
+        let mut asset_router =
+            AssetRouter::default().with_asset_observer(RecordingAssetObserver(events.clone()));
+        let asset_configs = vec![AssetConfig::Pattern {
+            pattern: "**/*.js".to_string(),
+            exclude: vec![],
+            exclude_dotfiles: false,
+            content_type: Some("text/javascript".to_string()),
+            headers: vec![],
+            encodings: vec![AssetEncoding::Gzip.default_config()],
+            image_variants: vec![],
+        }];
+        let assets = vec![
+            Asset::new("app.js", b"app.js".as_slice()),
+            Asset::new("app.js.gz", [1, 2, 3].as_slice()),
+            Asset::new("style.css", b"style.css".as_slice()),
+        ];
+
+        asset_router
+            .certify_assets(assets.clone(), asset_configs.clone())
             .unwrap();
-        let (witness, expr_path) = extract_witness_expr_path(&response);
-        add_v2_certificate_header(
-            &data_certificate(),
-            &mut expected_response,
-            &witness,
-            &expr_path,
+        assert_eq!(
+            events.write().unwrap().drain(..).collect::<Vec<_>>(),
+            vec![
+                "certified app.js None",
+                "certified app.js.gz Some(\"gzip\")",
+                "certified style.css None",
+            ]
         );
 
-        assert_eq!(expr_path, vec!["http_expr", "css", "<*>"]);
-        assert_matches!(
-            witness.lookup_subtree(&expr_path),
-            SubtreeLookupResult::Found(_)
-        );
-        assert_eq!(response, expected_response);
+        // unchanged assets are not reported
+        asset_router
+            .sync_assets(assets.clone(), asset_configs.clone())
+            .unwrap();
+        assert!(events.read().unwrap().is_empty());
 
         asset_router
-            .delete_assets(
+            .sync_assets(
                 vec![
-                    Asset::new("not-found.html", not_found_html_body()),
-                    Asset::new("not-found.html.gz", not_found_html_gz_body()),
-                    Asset::new("not-found.html.zz", not_found_html_zz_body()),
-                    Asset::new("not-found.html.br", not_found_html_br_body()),
+                    Asset::new("app.js", b"updated app.js".as_slice()),
+                    Asset::new("app.js.gz", [1, 2, 3].as_slice()),
                 ],
-                vec![not_found_html_config()],
+                asset_configs,
             )
             .unwrap();
-        let mut expected_response = expected_index_html_response();
-
-        let response = asset_router
-            .serve_asset(&data_certificate(), &request)
-            .unwrap();
-        let (witness, expr_path) = extract_witness_expr_path(&response);
-        add_v2_certificate_header(
-            &data_certificate(),
-            &mut expected_response,
-            &witness,
-            &expr_path,
+        assert_eq!(
+            events.write().unwrap().drain(..).collect::<Vec<_>>(),
+            vec!["deleted style.css", "certified app.js None"]
         );
 
-        assert_eq!(expr_path, vec!["http_expr", "", "<*>"]);
-        assert_matches!(
-            witness.lookup_subtree(&expr_path),
-            SubtreeLookupResult::Found(_)
+        asset_router.delete_all_assets();
+        assert_eq!(
+            events.write().unwrap().drain(..).collect::<Vec<_>>(),
+            vec!["deleted app.js", "deleted app.js.gz"]
         );
-        assert_eq!(response, expected_response);
+    }
 
-        asset_router
-            .delete_assets(
-                vec![
-                    Asset::new("index.html", index_html_body()),
-                    Asset::new("index.html.gz", index_html_gz_body()),
-                    Asset::new("index.html.zz", index_html_zz_body()),
-                    Asset::new("index.html.br", index_html_br_body()),
-                ],
-                vec![index_html_config()],
-            )
+    #[rstest]
+    fn test_serve_options_disable_fallbacks(asset_router: AssetRouter) {
+        let options = ServeOptions {
+            disable_fallbacks: true,
+            ..Default::default()
+        };
+
+        let request = HttpRequest::get("/index.html").build();
+        let response = asset_router
+            .serve_asset_with_options(&data_certificate(), &request, &options)
             .unwrap();
+        assert_eq!(response.status_code(), StatusCode::OK);
 
-        let result = asset_router.serve_asset(&data_certificate(), &request);
+        let request = HttpRequest::get("/missing").build();
+        assert!(asset_router
+            .serve_asset(&data_certificate(), &request)
+            .is_ok());
         assert_matches!(
-            result,
-            Err(AssetCertificationError::NoAssetMatchingRequestUrl {
-                request_url,
-             }) if request_url == request.get_path().unwrap()
+            asset_router.serve_asset_with_options(&data_certificate(), &request, &options),
+            Err(AssetCertificationError::NoAssetMatchingRequestUrl { request_url })
+                if request_url == "/missing"
         );
     }
 
     #[rstest]
-    #[case("/css/core-8d4jhgy2.js")]
-    #[case("https://internetcomputer.org/css/core-8d4jhgy2.js")]
-    fn test_not_found_css(mut asset_router: AssetRouter, #[case] req_url: &str) {
-        let request = HttpRequest::get(req_url).build();
-        let mut expected_response = build_response(
-            StatusCode::NOT_FOUND,
-            not_found_html_body(),
-            asset_cel_expr(),
-            vec![
-                (
-                    "cache-control".to_string(),
-                    "public, no-cache, no-store".to_string(),
-                ),
-                ("content-type".to_string(), "text/html".to_string()),
-            ],
-        );
+    #[case(AssetEncoding::Gzip, Some("gzip"))]
+    #[case(AssetEncoding::Brotli, Some("br"))]
+    #[case(AssetEncoding::Identity, None)]
+    #[case(AssetEncoding::Zstd, None)]
+    fn test_serve_options_encoding(
+        asset_router: AssetRouter,
+        #[case] encoding: AssetEncoding,
+        #[case] expected_content_encoding: Option<&str>,
+    ) {
+        let options = ServeOptions {
+            encoding: Some(encoding),
+            ..Default::default()
+        };
+        let request = HttpRequest::get("/index.html")
+            .with_headers(vec![("accept-encoding".to_string(), "br".to_string())])
+            .build();
 
         let response = asset_router
-            .serve_asset(&data_certificate(), &request)
+            .serve_asset_with_options(&data_certificate(), &request, &options)
             .unwrap();
         let (witness, expr_path) = extract_witness_expr_path(&response);
-        add_v2_certificate_header(
-            &data_certificate(),
-            &mut expected_response,
-            &witness,
-            &expr_path,
-        );
+        let content_encoding = response
+            .headers()
+            .iter()
+            .find(|(name, _)| name == "content-encoding")
+            .map(|(_, value)| value.as_str());
 
-        assert_eq!(expr_path, vec!["http_expr", "css", "<*>"]);
+        assert_eq!(content_encoding, expected_content_encoding);
         assert_matches!(
             witness.lookup_subtree(&expr_path),
             SubtreeLookupResult::Found(_)
         );
-        assert_eq!(response, expected_response);
+    }
 
-        asset_router
-            .delete_assets(
-                vec![
-                    Asset::new("not-found.html", not_found_html_body()),
-                    Asset::new("not-found.html.gz", not_found_html_gz_body()),
-                    Asset::new("not-found.html.zz", not_found_html_zz_body()),
-                    Asset::new("not-found.html.br", not_found_html_br_body()),
-                ],
-                vec![not_found_html_config()],
+    #[rstest]
+    #[case(None)]
+    #[case(Some(format!("bytes={ASSET_CHUNK_SIZE}-")))]
+    fn test_serve_options_disable_partial_content(#[case] range: Option<String>) {
+        let long_asset_router =
+            long_asset_router_with_params(&[TWO_CHUNKS_ASSET_NAME], &[AssetEncoding::Identity]);
+        let options = ServeOptions {
+            disable_partial_content: true,
+            ..Default::default()
+        };
+        let request = HttpRequest::get(format!("/{TWO_CHUNKS_ASSET_NAME}"))
+            .with_headers(
+                range
+                    .into_iter()
+                    .map(|range| ("range".to_string(), range))
+                    .collect(),
             )
-            .unwrap();
-        let mut expected_response = expected_index_html_response();
+            .build();
 
-        let response = asset_router
-            .serve_asset(&data_certificate(), &request)
+        let response = long_asset_router
+            .serve_asset_with_options(&data_certificate(), &request, &options)
             .unwrap();
         let (witness, expr_path) = extract_witness_expr_path(&response);
-        add_v2_certificate_header(
-            &data_certificate(),
-            &mut expected_response,
-            &witness,
-            &expr_path,
-        );
 
-        assert_eq!(expr_path, vec!["http_expr", "", "<*>"]);
+        assert_eq!(response.status_code(), StatusCode::OK);
+        assert_eq!(response.body(), long_asset_body(TWO_CHUNKS_ASSET_NAME));
+        assert_eq!(expr_path, vec!["http_expr", TWO_CHUNKS_ASSET_NAME, "<$>"]);
         assert_matches!(
             witness.lookup_subtree(&expr_path),
             SubtreeLookupResult::Found(_)
         );
+
+        let response = long_asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        assert_eq!(response.status_code(), StatusCode::PARTIAL_CONTENT);
+    }
+
+    #[rstest]
+    #[case("/index.html", AssetEncoding::Identity, None)]
+    #[case("/index.html", AssetEncoding::Gzip, Some("gzip"))]
+    #[case("/", AssetEncoding::Brotli, Some("br"))]
+    fn test_get_certified_response(
+        asset_router: AssetRouter,
+        #[case] path: &str,
+        #[case] encoding: AssetEncoding,
+        #[case] accept_encoding: Option<&str>,
+    ) {
+        let request = HttpRequest::get(path)
+            .with_headers(
+                accept_encoding
+                    .into_iter()
+                    .map(|encoding| ("accept-encoding".to_string(), encoding.to_string()))
+                    .collect(),
+            )
+            .build();
+        let expected_response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+
+        let (mut response, witness, expr_path) = asset_router
+            .get_certified_response(path, encoding, None)
+            .unwrap();
+        add_v2_certificate_header(&data_certificate(), &mut response, &witness, &expr_path);
+
         assert_eq!(response, expected_response);
+    }
 
-        asset_router
-            .delete_assets(
-                vec![
-                    Asset::new("index.html", index_html_body()),
-                    Asset::new("index.html.gz", index_html_gz_body()),
-                    Asset::new("index.html.zz", index_html_zz_body()),
-                    Asset::new("index.html.br", index_html_br_body()),
-                ],
-                vec![index_html_config()],
+    #[rstest]
+    #[case("/index.html", None)]
+    #[case("/index.html", Some("gzip"))]
+    #[case("/", Some("br"))]
+    #[case("/missing", None)]
+    #[case("/old-url", None)]
+    fn test_serve_asset_parts(
+        asset_router: AssetRouter,
+        #[case] path: &str,
+        #[case] accept_encoding: Option<&str>,
+    ) {
+        let request = HttpRequest::get(path)
+            .with_headers(
+                accept_encoding
+                    .into_iter()
+                    .map(|encoding| ("accept-encoding".to_string(), encoding.to_string()))
+                    .collect(),
             )
+            .build();
+        let expected_response = asset_router
+            .serve_asset(&data_certificate(), &request)
             .unwrap();
 
-        let result = asset_router.serve_asset(&data_certificate(), &request);
+        let (mut response, witness, expr_path) = asset_router.serve_asset_parts(&request).unwrap();
+        assert!(response
+            .headers()
+            .iter()
+            .all(|(name, _)| !name.eq_ignore_ascii_case(CERTIFICATE_HEADER_NAME)));
+        add_v2_certificate_header(&data_certificate(), &mut response, &witness, &expr_path);
+
+        assert_eq!(response, expected_response);
+    }
+
+    #[rstest]
+    fn test_get_certified_response_chunks() {
+        let long_asset_router =
+            long_asset_router_with_params(&[TWO_CHUNKS_ASSET_NAME], &[AssetEncoding::Identity]);
+        let path = format!("/{TWO_CHUNKS_ASSET_NAME}");
+        let asset_body = long_asset_body(TWO_CHUNKS_ASSET_NAME);
+
+        for chunk_index in [0, 1] {
+            let range_begin = chunk_index * ASSET_CHUNK_SIZE;
+            let request = HttpRequest::get(&path)
+                .with_headers(vec![("range".to_string(), format!("bytes={range_begin}-"))])
+                .build();
+            let expected_response = long_asset_router
+                .serve_asset(&data_certificate(), &request)
+                .unwrap();
+
+            let (mut response, witness, expr_path) = long_asset_router
+                .get_certified_response(&path, AssetEncoding::Identity, Some(chunk_index))
+                .unwrap();
+            assert_matches!(
+                witness.lookup_subtree(&expr_path),
+                SubtreeLookupResult::Found(_)
+            );
+            add_v2_certificate_header(&data_certificate(), &mut response, &witness, &expr_path);
+
+            assert_eq!(response, expected_response);
+        }
+
+        let (response, _, _) = long_asset_router
+            .get_certified_response(&path, AssetEncoding::Identity, None)
+            .unwrap();
+        assert_eq!(response.status_code(), StatusCode::OK);
+        assert_eq!(response.body(), asset_body);
+
         assert_matches!(
-            result,
-            Err(AssetCertificationError::NoAssetMatchingRequestUrl {
-                request_url,
-             }) if request_url == request.get_path().unwrap()
+            long_asset_router.get_certified_response(&path, AssetEncoding::Identity, Some(2)),
+            Err(AssetCertificationError::NoAssetMatchingRequestUrl { .. })
         );
     }
 
     #[rstest]
-    #[case("/js/app-488df671.js")]
-    #[case("https://internetcomputer.org/js/app-488df671.js")]
-    fn test_app_js(mut asset_router: AssetRouter, #[case] req_url: &str) {
-        let request = HttpRequest::get(req_url).build();
-        let mut expected_response = build_200_response(
-            app_js_body(),
-            asset_cel_expr(),
-            vec![
-                (
-                    "cache-control".to_string(),
-                    "public, max-age=31536000, immutable".to_string(),
-                ),
-                ("content-type".to_string(), "text/javascript".to_string()),
-            ],
+    #[case("/missing", AssetEncoding::Identity, None)]
+    #[case("/index.html", AssetEncoding::Zstd, None)]
+    #[case("/index.html", AssetEncoding::Identity, Some(0))]
+    fn test_get_certified_response_not_found(
+        asset_router: AssetRouter,
+        #[case] path: &str,
+        #[case] encoding: AssetEncoding,
+        #[case] chunk_index: Option<usize>,
+    ) {
+        assert_matches!(
+            asset_router.get_certified_response(path, encoding, chunk_index),
+            Err(AssetCertificationError::NoAssetMatchingRequestUrl { request_url })
+                if request_url == path
         );
+    }
 
-        let response = asset_router
-            .serve_asset(&data_certificate(), &request)
+    #[derive(Debug, Clone)]
+    struct RecordingReader {
+        content: Arc<Vec<u8>>,
+        sha256: Option<Hash>,
+        max_read_len: Arc<std::sync::atomic::AtomicUsize>,
+        total_read_len: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    impl RecordingReader {
+        fn new(content: Vec<u8>) -> Self {
+            RecordingReader {
+                content: Arc::new(content),
+                sha256: None,
+                max_read_len: Default::default(),
+                total_read_len: Default::default(),
+            }
+        }
+
+        fn total_read_len(&self) -> usize {
+            self.total_read_len
+                .load(std::sync::atomic::Ordering::Relaxed)
+        }
+    }
+
+    impl AssetReader for RecordingReader {
+        fn len(&self) -> usize {
+            self.content.len()
+        }
+
+        fn read(&self, offset: usize, buf: &mut [u8]) {
+            self.max_read_len
+                .fetch_max(buf.len(), std::sync::atomic::Ordering::Relaxed);
+            self.total_read_len
+                .fetch_add(buf.len(), std::sync::atomic::Ordering::Relaxed);
+            self.content.as_slice().read(offset, buf)
+        }
+
+        fn sha256(&self) -> Option<Hash> {
+            self.sha256
+        }
+    }
+
+    #[rstest]
+    #[case(ONE_CHUNK_ASSET_NAME)]
+    #[case(TWO_CHUNKS_ASSET_NAME)]
+    #[case(SIX_CHUNKS_ASSET_NAME)]
+    fn test_reader_assets(#[case] asset_name: &str) {
+        let asset_body = long_asset_body(asset_name);
+        let path = format!("/{asset_name}");
+        let reader = RecordingReader::new(asset_body.clone());
+
+        let mut asset_router = AssetRouter::default().with_integrity_header("x-integrity");
+        asset_router
+            .certify_assets(
+                vec![Asset::new(asset_name, asset_body.as_slice())],
+                vec![long_asset_config(asset_name)],
+            )
+            .unwrap();
+        let mut reader_asset_router = AssetRouter::default().with_integrity_header("x-integrity");
+        reader_asset_router
+            .certify_assets(
+                vec![Asset::from_reader(asset_name, reader.clone())],
+                vec![long_asset_config(asset_name)],
+            )
             .unwrap();
-        let (witness, expr_path) = extract_witness_expr_path(&response);
-        add_v2_certificate_header(
-            &data_certificate(),
-            &mut expected_response,
-            &witness,
-            &expr_path,
-        );
 
-        assert_eq!(expr_path, vec!["http_expr", "js", "app-488df671.js", "<$>"]);
-        assert_matches!(
-            witness.lookup_subtree(&expr_path),
-            SubtreeLookupResult::Found(_)
+        assert_eq!(reader_asset_router.root_hash(), asset_router.root_hash());
+        assert_eq!(
+            reader_asset_router.get_assets().metadata(),
+            asset_router.get_assets().metadata()
         );
-        assert_eq!(response, expected_response);
+        assert_eq!(
+            reader
+                .max_read_len
+                .load(std::sync::atomic::Ordering::Relaxed),
+            ASSET_CHUNK_SIZE
+        );
+
+        let mut range_begin = 0;
+        while range_begin < asset_body.len() {
+            let mut request = HttpRequest::get(&path).build();
+            if range_begin > 0 {
+                request = HttpRequest::get(&path)
+                    .with_headers(vec![("range".to_string(), format!("bytes={range_begin}-"))])
+                    .build();
+            }
+
+            assert_eq!(
+                reader_asset_router
+                    .serve_asset(&data_certificate(), &request)
+                    .unwrap(),
+                asset_router
+                    .serve_asset(&data_certificate(), &request)
+                    .unwrap()
+            );
+            range_begin += ASSET_CHUNK_SIZE;
+        }
 
         asset_router
             .delete_assets(
-                vec![
-                    Asset::new("js/app-488df671.js", app_js_body()),
-                    Asset::new("js/app-488df671.js.gz", app_js_gz_body()),
-                    Asset::new("js/app-488df671.js.zz", app_js_zz_body()),
-                    Asset::new("js/app-488df671.js.br", app_js_br_body()),
-                ],
-                vec![js_config()],
+                vec![Asset::new(asset_name, asset_body.as_slice())],
+                vec![long_asset_config(asset_name)],
+            )
+            .unwrap();
+        reader_asset_router
+            .delete_assets(
+                vec![Asset::from_reader(asset_name, reader)],
+                vec![long_asset_config(asset_name)],
+            )
+            .unwrap();
+
+        assert!(reader_asset_router.get_assets().is_empty());
+        assert_eq!(reader_asset_router.root_hash(), asset_router.root_hash());
+    }
+
+    #[rstest]
+    fn test_reader_asset_hash_is_calculated_once() {
+        let reader = RecordingReader::new(b"console.log('Hello World!');".to_vec());
+
+        let mut asset_router = AssetRouter::default();
+        asset_router
+            .certify_assets(vec![Asset::from_reader("app.js", reader.clone())], vec![])
+            .unwrap();
+
+        assert_eq!(reader.total_read_len(), reader.len());
+    }
+
+    #[rstest]
+    fn test_sync_reader_assets_with_known_hash() {
+        let asset_body = long_asset_body(TWO_CHUNKS_ASSET_NAME);
+        let reader = RecordingReader {
+            sha256: Some(hash(&asset_body)),
+            ..RecordingReader::new(asset_body.clone())
+        };
+        let mut asset_router = AssetRouter::default();
+        asset_router
+            .certify_assets(
+                vec![Asset::from_reader(TWO_CHUNKS_ASSET_NAME, reader.clone())],
+                vec![long_asset_config(TWO_CHUNKS_ASSET_NAME)],
             )
             .unwrap();
-        let mut expected_response = build_response(
-            StatusCode::NOT_FOUND,
-            not_found_html_body(),
-            asset_cel_expr(),
-            vec![
-                (
-                    "cache-control".to_string(),
-                    "public, no-cache, no-store".to_string(),
-                ),
-                ("content-type".to_string(), "text/html".to_string()),
-            ],
-        );
+        let root_hash = asset_router.root_hash();
+        let total_read_len = reader.total_read_len();
 
-        let response = asset_router
-            .serve_asset(&data_certificate(), &request)
+        // the content is unchanged, so it is not read again
+        asset_router
+            .sync_assets(
+                vec![Asset::from_reader(TWO_CHUNKS_ASSET_NAME, reader.clone())],
+                vec![long_asset_config(TWO_CHUNKS_ASSET_NAME)],
+            )
             .unwrap();
-        let (witness, expr_path) = extract_witness_expr_path(&response);
-        add_v2_certificate_header(
-            &data_certificate(),
-            &mut expected_response,
-            &witness,
-            &expr_path,
-        );
 
-        assert_eq!(expr_path, vec!["http_expr", "js", "<*>"]);
-        assert_matches!(
-            witness.lookup_subtree(&expr_path),
-            SubtreeLookupResult::Found(_)
-        );
-        assert_eq!(response, expected_response);
+        assert_eq!(reader.total_read_len(), total_read_len);
+        assert_eq!(asset_router.root_hash(), root_hash);
+    }
+
+    #[rstest]
+    #[case(ONE_CHUNK_ASSET_NAME, 1_500_000)]
+    #[case(TWO_CHUNKS_ASSET_NAME, ASSET_CHUNK_SIZE)]
+    #[case(SIX_CHUNKS_ASSET_NAME, 1_500_000)]
+    #[case(SIX_CHUNKS_ASSET_NAME, 3_000_000)]
+    fn test_uploaded_assets(#[case] asset_name: &str, #[case] chunk_len: usize) {
+        let asset_body = long_asset_body(asset_name);
+        let path = format!("/{asset_name}");
 
+        let mut asset_router = AssetRouter::default().with_integrity_header("x-integrity");
         asset_router
-            .delete_assets(
-                vec![
-                    Asset::new("not-found.html", not_found_html_body()),
-                    Asset::new("not-found.html.gz", not_found_html_gz_body()),
-                    Asset::new("not-found.html.zz", not_found_html_zz_body()),
-                    Asset::new("not-found.html.br", not_found_html_br_body()),
-                ],
-                vec![not_found_html_config()],
+            .certify_assets(
+                vec![Asset::new(asset_name, asset_body.as_slice())],
+                vec![long_asset_config(asset_name)],
             )
             .unwrap();
-        let mut expected_response = expected_index_html_response();
-
-        let response = asset_router
-            .serve_asset(&data_certificate(), &request)
+        let mut upload_asset_router = AssetRouter::default().with_integrity_header("x-integrity");
+        upload_asset_router
+            .begin_asset(asset_name, vec![long_asset_config(asset_name)])
             .unwrap();
-        let (witness, expr_path) = extract_witness_expr_path(&response);
-        add_v2_certificate_header(
-            &data_certificate(),
-            &mut expected_response,
-            &witness,
-            &expr_path,
+        for chunk in asset_body.chunks(chunk_len) {
+            upload_asset_router.append_chunk(chunk).unwrap();
+        }
+
+        assert!(upload_asset_router.get_assets().is_empty());
+
+        let report = upload_asset_router.commit_asset().unwrap();
+
+        assert_eq!(report.certified_paths, vec![asset_name]);
+        assert_eq!(upload_asset_router.root_hash(), asset_router.root_hash());
+        assert_eq!(
+            upload_asset_router.get_assets().metadata(),
+            asset_router.get_assets().metadata()
         );
 
-        assert_eq!(expr_path, vec!["http_expr", "", "<*>"]);
+        let mut range_begin = 0;
+        while range_begin < asset_body.len() {
+            let mut request = HttpRequest::get(&path).build();
+            if range_begin > 0 {
+                request = HttpRequest::get(&path)
+                    .with_headers(vec![("range".to_string(), format!("bytes={range_begin}-"))])
+                    .build();
+            }
+
+            assert_eq!(
+                upload_asset_router
+                    .serve_asset(&data_certificate(), &request)
+                    .unwrap(),
+                asset_router
+                    .serve_asset(&data_certificate(), &request)
+                    .unwrap()
+            );
+            range_begin += ASSET_CHUNK_SIZE;
+        }
+    }
+
+    #[rstest]
+    fn test_asset_upload_not_in_progress() {
+        let mut asset_router = AssetRouter::default();
+
         assert_matches!(
-            witness.lookup_subtree(&expr_path),
-            SubtreeLookupResult::Found(_)
+            asset_router.append_chunk(b"console.log".as_slice()),
+            Err(AssetCertificationError::NoAssetUploadInProgress)
+        );
+        assert_matches!(
+            asset_router.commit_asset(),
+            Err(AssetCertificationError::NoAssetUploadInProgress)
         );
-        assert_eq!(response, expected_response);
 
+        asset_router.begin_asset("app.js", vec![]).unwrap();
         asset_router
-            .delete_assets(
-                vec![
-                    Asset::new("index.html", index_html_body()),
-                    Asset::new("index.html.gz", index_html_gz_body()),
-                    Asset::new("index.html.zz", index_html_zz_body()),
-                    Asset::new("index.html.br", index_html_br_body()),
-                ],
-                vec![index_html_config()],
-            )
+            .append_chunk(b"console.log".as_slice())
             .unwrap();
+        asset_router.commit_asset().unwrap();
 
-        let result = asset_router.serve_asset(&data_certificate(), &request);
         assert_matches!(
-            result,
-            Err(AssetCertificationError::NoAssetMatchingRequestUrl {
-                request_url,
-             }) if request_url == request.get_path().unwrap()
+            asset_router.commit_asset(),
+            Err(AssetCertificationError::NoAssetUploadInProgress)
         );
+        assert!(asset_router
+            .get_assets()
+            .get("/app.js", None, None)
+            .is_some());
     }
 
     #[rstest]
-    #[case(
-        app_js_zz_body(),
-        not_found_html_zz_body(),
-        index_html_zz_body(),
-        "deflate",
-        "deflate"
-    )]
-    #[case(
-        app_js_zz_body(),
-        not_found_html_zz_body(),
-        index_html_zz_body(),
-        "deflate, identity",
-        "deflate"
-    )]
-    #[case(
-        app_js_zz_body(),
-        not_found_html_zz_body(),
-        index_html_zz_body(),
-        "identity, deflate",
-        "deflate"
-    )]
-    #[case(
-        app_js_gz_body(),
-        not_found_html_gz_body(),
-        index_html_gz_body(),
-        "gzip",
-        "gzip"
-    )]
-    #[case(
-        app_js_gz_body(),
-        not_found_html_gz_body(),
-        index_html_gz_body(),
-        "gzip, identity",
-        "gzip"
-    )]
-    #[case(
-        app_js_gz_body(),
-        not_found_html_gz_body(),
-        index_html_gz_body(),
-        "identity, gzip",
-        "gzip"
-    )]
-    #[case(
-        app_js_gz_body(),
-        not_found_html_gz_body(),
-        index_html_gz_body(),
-        "gzip, deflate",
-        "gzip"
-    )]
-    #[case(
-        app_js_gz_body(),
-        not_found_html_gz_body(),
-        index_html_gz_body(),
-        "deflate, gzip",
-        "gzip"
-    )]
-    #[case(
-        app_js_gz_body(),
-        not_found_html_gz_body(),
-        index_html_gz_body(),
-        "gzip, deflate, identity",
-        "gzip"
-    )]
-    #[case(
-        app_js_gz_body(),
-        not_found_html_gz_body(),
-        index_html_gz_body(),
-        "gzip, identity, deflate",
-        "gzip"
-    )]
-    #[case(
-        app_js_gz_body(),
-        not_found_html_gz_body(),
-        index_html_gz_body(),
-        "identity, gzip, deflate",
-        "gzip"
-    )]
-    #[case(
-        app_js_gz_body(),
-        not_found_html_gz_body(),
-        index_html_gz_body(),
-        "identity, deflate, gzip",
-        "gzip"
-    )]
-    #[case(
-        app_js_gz_body(),
-        not_found_html_gz_body(),
-        index_html_gz_body(),
-        "deflate, gzip, identity",
-        "gzip"
-    )]
-    #[case(
-        app_js_gz_body(),
-        not_found_html_gz_body(),
-        index_html_gz_body(),
-        "deflate, identity, gzip",
-        "gzip"
-    )]
-    #[case(
-        app_js_br_body(),
-        not_found_html_br_body(),
-        index_html_br_body(),
-        "br",
-        "br"
-    )]
-    #[case(
-        app_js_br_body(),
-        not_found_html_br_body(),
-        index_html_br_body(),
-        "br, gzip, deflate, identity",
-        "br"
-    )]
-    #[case(
-        app_js_br_body(),
-        not_found_html_br_body(),
-        index_html_br_body(),
-        "gzip, deflate, identity, br",
-        "br"
-    )]
-    fn test_encoded_app_js(
-        #[case] expected_body: Vec<u8>,
-        #[case] expected_not_found_body: Vec<u8>,
-        #[case] expected_index_body: Vec<u8>,
-        #[case] accept_encoding: &str,
-        #[case] expected_encoding: &str,
-        mut asset_router: AssetRouter,
-    ) {
-        let request = HttpRequest::get("/js/app-488df671.js")
-            .with_headers(vec![(
-                "accept-encoding".to_string(),
-                accept_encoding.to_string(),
-            )])
-            .build();
+    fn test_asset_upload_restarted() {
+        let mut asset_router = AssetRouter::default();
+        asset_router.begin_asset("app.js", vec![]).unwrap();
+        asset_router
+            .append_chunk(b"console.error".as_slice())
+            .unwrap();
 
-        let mut expected_response = build_200_response(
-            expected_body,
-            encoded_asset_cel_expr(),
-            vec![
-                (
-                    "cache-control".to_string(),
-                    "public, max-age=31536000, immutable".to_string(),
-                ),
-                ("content-type".to_string(), "text/javascript".to_string()),
-                (
-                    "content-encoding".to_string(),
-                    expected_encoding.to_string(),
-                ),
-            ],
-        );
+        asset_router.begin_asset("app.js", vec![]).unwrap();
+        asset_router
+            .append_chunk(b"console.log".as_slice())
+            .unwrap();
+        asset_router.commit_asset().unwrap();
 
         let response = asset_router
-            .serve_asset(&data_certificate(), &request)
+            .serve_asset(&data_certificate(), &HttpRequest::get("/app.js").build())
             .unwrap();
-        let (witness, expr_path) = extract_witness_expr_path(&response);
-        add_v2_certificate_header(
-            &data_certificate(),
-            &mut expected_response,
-            &witness,
-            &expr_path,
+
+        assert_eq!(response.body(), b"console.log");
+    }
+
+    #[rstest]
+    fn test_max_asset_size() {
+        let mut asset_router = AssetRouter::default().with_max_asset_size(10);
+
+        assert_matches!(
+            asset_router.certify_assets(
+                vec![
+                    Asset::new("app.js", b"console.log".as_slice()),
+                    Asset::new("index.html", b"<html>".as_slice()),
+                ],
+                vec![],
+            ),
+            Err(AssetCertificationError::AssetTooLarge {
+                asset_path,
+                size: 11,
+                max_size: 10,
+            }) if asset_path == "app.js"
         );
+        assert!(asset_router.get_assets().is_empty());
 
-        assert_eq!(expr_path, vec!["http_expr", "js", "app-488df671.js", "<$>"]);
+        asset_router.begin_asset("app.js", vec![]).unwrap();
+        asset_router.append_chunk(b"console".as_slice()).unwrap();
         assert_matches!(
-            witness.lookup_subtree(&expr_path),
-            SubtreeLookupResult::Found(_)
+            asset_router.append_chunk(b".log".as_slice()),
+            Err(AssetCertificationError::AssetTooLarge {
+                size: 11,
+                max_size: 10,
+                ..
+            })
         );
-        assert_eq!(response, expected_response);
+        asset_router.append_chunk(b".l".as_slice()).unwrap();
+        asset_router.commit_asset().unwrap();
+
+        assert!(asset_router
+            .get_assets()
+            .get("/app.js", None, None)
+            .is_some());
+    }
 
+    #[rstest]
+    fn test_max_total_asset_size() {
+        let mut asset_router = AssetRouter::default().with_max_total_asset_size(20);
         asset_router
-            .delete_assets(
+            .certify_assets(
                 vec![
-                    Asset::new("js/app-488df671.js", app_js_body()),
-                    Asset::new("js/app-488df671.js.gz", app_js_gz_body()),
-                    Asset::new("js/app-488df671.js.zz", app_js_zz_body()),
-                    Asset::new("js/app-488df671.js.br", app_js_br_body()),
+                    Asset::new("app.js", b"console.log".as_slice()),
+                    Asset::new("index.html", b"<html>".as_slice()),
                 ],
-                vec![js_config()],
+                vec![],
             )
             .unwrap();
-        let mut expected_response = build_response(
-            StatusCode::NOT_FOUND,
-            expected_not_found_body,
-            encoded_asset_cel_expr(),
-            vec![
-                (
-                    "cache-control".to_string(),
-                    "public, no-cache, no-store".to_string(),
-                ),
-                ("content-type".to_string(), "text/html".to_string()),
-                (
-                    "content-encoding".to_string(),
-                    expected_encoding.to_string(),
-                ),
-            ],
-        );
 
-        let response = asset_router
-            .serve_asset(&data_certificate(), &request)
+        // replacing a certified asset only counts its new size
+        asset_router
+            .certify_assets(
+                vec![Asset::new("app.js", b"console.error".as_slice())],
+                vec![],
+            )
             .unwrap();
-        let (witness, expr_path) = extract_witness_expr_path(&response);
-        add_v2_certificate_header(
-            &data_certificate(),
-            &mut expected_response,
-            &witness,
-            &expr_path,
-        );
 
-        assert_eq!(expr_path, vec!["http_expr", "js", "<*>"]);
         assert_matches!(
-            witness.lookup_subtree(&expr_path),
-            SubtreeLookupResult::Found(_)
+            asset_router.certify_assets(vec![Asset::new("app.css", b"body {}".as_slice())], vec![]),
+            Err(AssetCertificationError::TotalAssetSizeExceeded {
+                total_size: 26,
+                max_total_size: 20,
+            })
         );
-        assert_eq!(response, expected_response);
+        assert!(asset_router
+            .get_assets()
+            .get("/app.css", None, None)
+            .is_none());
 
+        // syncing replaces all certified assets
         asset_router
-            .delete_assets(
+            .sync_assets(
                 vec![
-                    Asset::new("not-found.html", not_found_html_body()),
-                    Asset::new("not-found.html.gz", not_found_html_gz_body()),
-                    Asset::new("not-found.html.zz", not_found_html_zz_body()),
-                    Asset::new("not-found.html.br", not_found_html_br_body()),
+                    Asset::new("app.css", b"body {}".as_slice()),
+                    Asset::new("index.html", b"<html>".as_slice()),
                 ],
-                vec![not_found_html_config()],
+                vec![],
             )
             .unwrap();
-        let mut expected_response = build_200_response(
-            expected_index_body,
-            encoded_asset_cel_expr(),
-            vec![
-                (
-                    "cache-control".to_string(),
-                    "public, no-cache, no-store".to_string(),
-                ),
-                ("content-type".to_string(), "text/html".to_string()),
-                (
-                    "content-encoding".to_string(),
-                    expected_encoding.to_string(),
-                ),
-            ],
+    }
+
+    #[rstest]
+    fn test_max_total_asset_size_with_uploads() {
+        let mut asset_router = AssetRouter::default().with_max_total_asset_size(20);
+        asset_router
+            .certify_assets(
+                vec![
+                    Asset::new("app.js", b"console.log".as_slice()),
+                    Asset::new("index.html", b"<html>".as_slice()),
+                ],
+                vec![AssetConfig::File {
+                    path: "index.html".to_string(),
+                    content_type: Some("text/html".to_string()),
+                    headers: vec![],
+                    fallback_for: vec![AssetFallbackConfig {
+                        scope: "/".to_string(),
+                        status_code: Some(StatusCode::OK),
+                        priority: 0,
+                    }],
+                    aliased_by: vec!["/".to_string()],
+                    encodings: vec![],
+                    image_variants: vec![],
+                }],
+            )
+            .unwrap();
+
+        // the aliases and fallbacks of an asset only count its size once
+        assert_eq!(asset_router.asset_sizes.total_size, 17);
+
+        asset_router.begin_asset("app.css", vec![]).unwrap();
+        assert_matches!(
+            asset_router.append_chunk(b"body {".as_slice()),
+            Err(AssetCertificationError::TotalAssetSizeExceeded {
+                total_size: 23,
+                max_total_size: 20,
+            })
         );
+        asset_router.append_chunk(b"bo".as_slice()).unwrap();
 
-        let response = asset_router
-            .serve_asset(&data_certificate(), &request)
+        // an upload replaces a certified asset with the same path
+        asset_router.begin_asset("app.js", vec![]).unwrap();
+        asset_router
+            .append_chunk(b"console.error".as_slice())
             .unwrap();
-        let (witness, expr_path) = extract_witness_expr_path(&response);
-        add_v2_certificate_header(
-            &data_certificate(),
-            &mut expected_response,
-            &witness,
-            &expr_path,
+        asset_router.commit_asset().unwrap();
+        assert_eq!(asset_router.asset_sizes.total_size, 19);
+
+        asset_router.delete_assets_by_path(vec!["/app.js"]);
+        assert_eq!(asset_router.asset_sizes.total_size, 6);
+
+        asset_router.begin_asset("app.css", vec![]).unwrap();
+        asset_router
+            .append_chunk(b"body { color: red; }".as_slice())
+            .unwrap_err();
+        asset_router.append_chunk(b"body {}".as_slice()).unwrap();
+        asset_router.commit_asset().unwrap();
+        assert_eq!(asset_router.asset_sizes.total_size, 13);
+
+        asset_router.delete_all_assets();
+        assert_eq!(asset_router.asset_sizes.total_size, 0);
+    }
+
+    #[rstest]
+    fn test_lazy_certification(asset_router: AssetRouter) {
+        let mut lazy_asset_router = AssetRouter::default().with_lazy_certification();
+        lazy_asset_router
+            .certify_assets(asset_router_assets(), asset_router_configs())
+            .unwrap();
+
+        // fallbacks and redirects are certified upfront
+        for path in [
+            "/index.html",
+            "/",
+            "/old-url",
+            "/css/app.css",
+            "/js/missing.js",
+        ] {
+            let request = HttpRequest::get(path).build();
+            assert_eq!(
+                lazy_asset_router
+                    .serve_asset(&data_certificate(), &request)
+                    .unwrap()
+                    .status_code(),
+                asset_router
+                    .serve_asset(&data_certificate(), &request)
+                    .unwrap()
+                    .status_code()
+            );
+        }
+
+        for path in ["/js/app-488df671.js", "/css/app-ba74b708.css"] {
+            let request = HttpRequest::get(path).build();
+            assert_matches!(
+                lazy_asset_router.serve_asset(&data_certificate(), &request),
+                Err(AssetCertificationError::AssetCertificationPending { request_url })
+                    if request_url == path
+            );
+        }
+
+        assert!(lazy_asset_router
+            .certify_pending_asset("/js/app-488df671.js")
+            .unwrap());
+        assert!(!lazy_asset_router
+            .certify_pending_asset("/js/app-488df671.js")
+            .unwrap());
+        assert!(!lazy_asset_router
+            .certify_pending_asset("/js/missing.js")
+            .unwrap());
+
+        for encoding in [
+            None,
+            Some(AssetEncoding::Gzip),
+            Some(AssetEncoding::Deflate),
+            Some(AssetEncoding::Brotli),
+        ] {
+            assert_eq!(
+                lazy_asset_router
+                    .get_assets()
+                    .get("/js/app-488df671.js", encoding, None),
+                asset_router
+                    .get_assets()
+                    .get("/js/app-488df671.js", encoding, None)
+            );
+        }
+        let request = HttpRequest::get("/js/app-488df671.js").build();
+        assert_eq!(
+            lazy_asset_router
+                .serve_asset(&data_certificate(), &request)
+                .unwrap()
+                .body(),
+            app_js_body()
         );
 
-        assert_eq!(expr_path, vec!["http_expr", "", "<*>"]);
+        let request = HttpRequest::get("/css/app-ba74b708.css").build();
         assert_matches!(
-            witness.lookup_subtree(&expr_path),
-            SubtreeLookupResult::Found(_)
+            lazy_asset_router.serve_asset(&data_certificate(), &request),
+            Err(AssetCertificationError::AssetCertificationPending { .. })
         );
-        assert_eq!(response, expected_response);
+    }
 
+    #[rstest]
+    fn test_lazy_certification_aliases() {
+        let mut asset_router = AssetRouter::default()
+            .with_lazy_certification()
+            .with_trailing_slash_aliases();
         asset_router
-            .delete_assets(
-                vec![
-                    Asset::new("index.html", index_html_body()),
-                    Asset::new("index.html.gz", index_html_gz_body()),
-                    Asset::new("index.html.zz", index_html_zz_body()),
-                    Asset::new("index.html.br", index_html_br_body()),
-                ],
-                vec![index_html_config()],
+            .certify_assets(
+                vec![Asset::new("about.html", b"<h1>About</h1>".as_slice())],
+                vec![AssetConfig::File {
+                    path: "about.html".to_string(),
+                    content_type: Some("text/html".to_string()),
+                    headers: vec![],
+                    fallback_for: vec![],
+                    aliased_by: vec!["/about".to_string()],
+                    encodings: vec![],
+                    image_variants: vec![],
+                }],
             )
             .unwrap();
 
-        let result = asset_router.serve_asset(&data_certificate(), &request);
-        assert_matches!(
-            result,
-            Err(AssetCertificationError::NoAssetMatchingRequestUrl {
-                request_url,
-             }) if request_url == request.get_path().unwrap()
-        );
+        for path in ["/about.html", "/about", "/about/"] {
+            let request = HttpRequest::get(path).build();
+            assert_matches!(
+                asset_router.serve_asset(&data_certificate(), &request),
+                Err(AssetCertificationError::AssetCertificationPending { .. })
+            );
+        }
+
+        assert!(asset_router.certify_pending_asset("/about/").unwrap());
+
+        for path in ["/about.html", "/about", "/about/"] {
+            let request = HttpRequest::get(path).build();
+            let response = asset_router
+                .serve_asset(&data_certificate(), &request)
+                .unwrap();
+
+            assert_eq!(response.status_code(), StatusCode::OK);
+            assert_eq!(response.body(), b"<h1>About</h1>");
+        }
     }
 
     #[rstest]
-    #[case("/js/core-7dk12y45.js")]
-    #[case("https://internetcomputer.org/js/core-7dk12y45.js")]
-    fn test_not_found_js(mut asset_router: AssetRouter, #[case] req_url: &str) {
-        let request = HttpRequest::get(req_url).build();
-        let mut expected_response = build_response(
-            StatusCode::NOT_FOUND,
-            not_found_html_body(),
-            asset_cel_expr(),
-            vec![
-                (
-                    "cache-control".to_string(),
-                    "public, no-cache, no-store".to_string(),
-                ),
-                ("content-type".to_string(), "text/html".to_string()),
-            ],
+    fn test_lazy_certification_delete_aliased_asset() {
+        let mut asset_router = AssetRouter::default()
+            .with_lazy_certification()
+            .with_trailing_slash_aliases();
+        asset_router
+            .certify_assets(
+                vec![Asset::new("about.html", b"<h1>About</h1>".as_slice())],
+                vec![AssetConfig::File {
+                    path: "about.html".to_string(),
+                    content_type: Some("text/html".to_string()),
+                    headers: vec![],
+                    fallback_for: vec![],
+                    aliased_by: vec!["/about".to_string()],
+                    encodings: vec![],
+                    image_variants: vec![],
+                }],
+            )
+            .unwrap();
+
+        asset_router.delete_assets_by_path(vec!["/about.html"]);
+
+        for path in ["/about.html", "/about", "/about/"] {
+            assert!(!asset_router.certify_pending_asset(path).unwrap());
+            assert_matches!(
+                asset_router.serve_asset(&data_certificate(), &HttpRequest::get(path).build()),
+                Err(AssetCertificationError::NoAssetMatchingRequestUrl { .. })
+            );
+        }
+        assert!(asset_router.pending_urls.is_empty());
+        assert!(asset_router.pending_url_aliases.is_empty());
+        assert_eq!(
+            asset_router.root_hash(),
+            HttpCertificationTree::default().root_hash()
         );
+    }
 
-        let response = asset_router
-            .serve_asset(&data_certificate(), &request)
+    #[rstest]
+    fn test_lazy_certification_delete_and_sync() {
+        let mut asset_router = AssetRouter::default().with_lazy_certification();
+        asset_router
+            .certify_assets(asset_router_assets(), asset_router_configs())
+            .unwrap();
+        asset_router
+            .certify_pending_asset("/css/app-ba74b708.css")
             .unwrap();
-        let (witness, expr_path) = extract_witness_expr_path(&response);
-        add_v2_certificate_header(
-            &data_certificate(),
-            &mut expected_response,
-            &witness,
-            &expr_path,
-        );
 
-        assert_eq!(expr_path, vec!["http_expr", "js", "<*>"]);
-        assert_matches!(
-            witness.lookup_subtree(&expr_path),
-            SubtreeLookupResult::Found(_)
+        asset_router
+            .sync_assets(
+                asset_router_assets()
+                    .into_iter()
+                    .filter(|asset| !asset.path().starts_with("js/")),
+                asset_router_configs(),
+            )
+            .unwrap();
+
+        // the pending asset was removed, so the fallback is served instead
+        let request = HttpRequest::get("/js/app-488df671.js").build();
+        assert_eq!(
+            asset_router
+                .serve_asset(&data_certificate(), &request)
+                .unwrap()
+                .status_code(),
+            StatusCode::NOT_FOUND
         );
-        assert_eq!(response, expected_response);
+        assert!(!asset_router
+            .certify_pending_asset("/js/app-488df671.js")
+            .unwrap());
 
         asset_router
             .delete_assets(
-                vec![
-                    Asset::new("not-found.html", not_found_html_body()),
-                    Asset::new("not-found.html.gz", not_found_html_gz_body()),
-                    Asset::new("not-found.html.zz", not_found_html_zz_body()),
-                    Asset::new("not-found.html.br", not_found_html_br_body()),
-                ],
-                vec![not_found_html_config()],
+                asset_router_assets()
+                    .into_iter()
+                    .filter(|asset| !asset.path().starts_with("js/")),
+                asset_router_configs(),
             )
             .unwrap();
 
-        let mut expected_response = expected_index_html_response();
+        assert!(asset_router.get_assets().is_empty());
+        assert!(asset_router.get_fallback_assets().is_empty());
+        assert_eq!(
+            asset_router.root_hash(),
+            HttpCertificationTree::default().root_hash()
+        );
+    }
 
-        let response = asset_router
-            .serve_asset(&data_certificate(), &request)
+    fn namespace_config(name: &str) -> AssetConfig {
+        AssetConfig::Namespace {
+            name: name.to_string(),
+            configs: vec![
+                AssetConfig::File {
+                    path: format!("{name}/index.html"),
+                    content_type: Some("text/html".to_string()),
+                    headers: vec![],
+                    fallback_for: vec![],
+                    aliased_by: vec![format!("/{name}")],
+                    encodings: vec![],
+                    image_variants: vec![],
+                },
+                AssetConfig::Pattern {
+                    pattern: format!("{name}/**/*.js"),
+                    exclude: vec![],
+                    exclude_dotfiles: false,
+                    content_type: Some("text/javascript".to_string()),
+                    headers: vec![],
+                    encodings: vec![AssetEncoding::Gzip.default_config()],
+                    image_variants: vec![],
+                },
+                AssetConfig::Redirect {
+                    from: format!("/{name}/old"),
+                    to: format!("/{name}"),
+                    kind: AssetRedirectKind::Permanent,
+                    append_query: false,
+                    headers: vec![],
+                },
+            ],
+        }
+    }
+
+    fn namespace_assets(name: &str) -> Vec<Asset<'static, 'static>> {
+        vec![
+            Asset::new(format!("{name}/index.html"), b"<h1>Hello</h1>".as_slice()),
+            Asset::new(
+                format!("{name}/app.js"),
+                b"console.log('Hello');".as_slice(),
+            ),
+            Asset::new(format!("{name}/app.js.gz"), [1, 2, 3].as_slice()),
+        ]
+    }
+
+    #[rstest]
+    fn test_namespaces() {
+        let mut asset_router = AssetRouter::default();
+        asset_router
+            .certify_assets(
+                [
+                    namespace_assets("v1"),
+                    namespace_assets("v2"),
+                    vec![Asset::new("favicon.ico", [4, 5, 6].as_slice())],
+                ]
+                .concat(),
+                vec![namespace_config("v1"), namespace_config("v2")],
+            )
             .unwrap();
-        let (witness, expr_path) = extract_witness_expr_path(&response);
-        add_v2_certificate_header(
-            &data_certificate(),
-            &mut expected_response,
-            &witness,
-            &expr_path,
+
+        assert_eq!(
+            asset_router.get_namespace_asset_paths("v1"),
+            vec!["v1/app.js", "v1/app.js.gz", "v1/index.html"]
         );
+        assert!(asset_router.get_namespace_asset_paths("v3").is_empty());
 
-        assert_eq!(expr_path, vec!["http_expr", "", "<*>"]);
-        assert_matches!(
-            witness.lookup_subtree(&expr_path),
-            SubtreeLookupResult::Found(_)
+        asset_router.delete_namespace("v1");
+
+        assert!(asset_router.get_namespace_asset_paths("v1").is_empty());
+        for path in ["/v1/index.html", "/v1", "/v1/app.js", "/v1/old"] {
+            let request = HttpRequest::get(path)
+                .with_headers(vec![("accept-encoding".to_string(), "gzip".to_string())])
+                .build();
+            assert_matches!(
+                asset_router.serve_asset(&data_certificate(), &request),
+                Err(AssetCertificationError::NoAssetMatchingRequestUrl { .. })
+            );
+        }
+        for (path, status_code) in [
+            ("/v2/index.html", StatusCode::OK),
+            ("/v2", StatusCode::OK),
+            ("/v2/app.js", StatusCode::OK),
+            ("/v2/old", StatusCode::MOVED_PERMANENTLY),
+            ("/favicon.ico", StatusCode::OK),
+        ] {
+            let request = HttpRequest::get(path).build();
+            assert_eq!(
+                asset_router
+                    .serve_asset(&data_certificate(), &request)
+                    .unwrap()
+                    .status_code(),
+                status_code
+            );
+        }
+
+        asset_router.delete_namespace("v2");
+        asset_router
+            .delete_assets(
+                vec![Asset::new("favicon.ico", [4, 5, 6].as_slice())],
+                vec![],
+            )
+            .unwrap();
+
+        assert!(asset_router.get_assets().is_empty());
+        assert_eq!(
+            asset_router.root_hash(),
+            HttpCertificationTree::default().root_hash()
         );
-        assert_eq!(response, expected_response);
+    }
 
+    #[rstest]
+    fn test_namespaces_delete_assets() {
+        let mut asset_router = AssetRouter::default();
         asset_router
-            .delete_assets(
+            .certify_assets(namespace_assets("v1"), vec![namespace_config("v1")])
+            .unwrap();
+        asset_router
+            .delete_assets(namespace_assets("v1"), vec![namespace_config("v1")])
+            .unwrap();
+
+        assert!(asset_router.get_namespace_asset_paths("v1").is_empty());
+        assert!(asset_router.get_assets().is_empty());
+    }
+
+    #[rstest]
+    fn test_certification_report() {
+        let mut asset_router = AssetRouter::default();
+        let report = asset_router
+            .certify_assets(
+                [
+                    namespace_assets("v1"),
+                    vec![
+                        Asset::new("v1/vendor.js", b"console.log('Vendor');".as_slice()),
+                        Asset::new("favicon.ico", [4, 5, 6].as_slice()),
+                    ],
+                ]
+                .concat(),
                 vec![
-                    Asset::new("index.html", index_html_body()),
-                    Asset::new("index.html.gz", index_html_gz_body()),
-                    Asset::new("index.html.zz", index_html_zz_body()),
-                    Asset::new("index.html.br", index_html_br_body()),
+                    namespace_config("v1"),
+                    AssetConfig::Pattern {
+                        pattern: "**/*.css".to_string(),
+                        exclude: vec![],
+                        exclude_dotfiles: false,
+                        content_type: Some("text/css".to_string()),
+                        headers: vec![],
+                        encodings: vec![],
+                        image_variants: vec![],
+                    },
+                    AssetConfig::File {
+                        path: "v1/index.html".to_string(),
+                        content_type: None,
+                        headers: vec![],
+                        fallback_for: vec![],
+                        aliased_by: vec![],
+                        encodings: vec![],
+                        image_variants: vec![],
+                    },
                 ],
-                vec![index_html_config()],
             )
             .unwrap();
 
-        let result = asset_router.serve_asset(&data_certificate(), &request);
-        assert_matches!(
-            result,
-            Err(AssetCertificationError::NoAssetMatchingRequestUrl {
-                request_url,
-             }) if request_url == request.get_path().unwrap()
+        assert_eq!(
+            report,
+            CertificationReport {
+                certified_paths: vec![
+                    "favicon.ico".to_string(),
+                    "v1/app.js".to_string(),
+                    "v1/app.js.gz".to_string(),
+                    "v1/index.html".to_string(),
+                    "v1/vendor.js".to_string(),
+                ],
+                unconfigured_paths: vec!["favicon.ico".to_string()],
+                excluded_paths: vec![],
+                unmatched_configs: vec!["**/*.css".to_string(), "v1/index.html".to_string()],
+                missing_encodings: vec![MissingEncoding {
+                    path: "v1/vendor.js".to_string(),
+                    encoding: AssetEncoding::Gzip,
+                    expected_path: "v1/vendor.js.gz".to_string(),
+                }],
+                alias_collisions: vec![],
+            }
         );
     }
 
     #[rstest]
-    #[case("/404")]
-    #[case("https://internetcomputer.org/404")]
-    #[case("/404/")]
-    #[case("https://internetcomputer.org/404/")]
-    #[case("/404.html")]
-    #[case("https://internetcomputer.org/404.html")]
-    #[case("/not-found")]
-    #[case("https://internetcomputer.org/not-found")]
-    #[case("/not-found/")]
-    #[case("https://internetcomputer.org/not-found/")]
-    #[case("/not-found/index.html")]
-    #[case("https://internetcomputer.org/not-found/index.html")]
-    fn test_not_found_alias(mut asset_router: AssetRouter, #[case] req_url: &str) {
-        let request = HttpRequest::get(req_url).build();
-        let mut expected_response = build_200_response(
-            not_found_html_body(),
-            asset_cel_expr(),
-            vec![
-                (
-                    "cache-control".to_string(),
-                    "public, no-cache, no-store".to_string(),
-                ),
-                ("content-type".to_string(), "text/html".to_string()),
-            ],
-        );
+    fn test_excluded_assets() {
+        let mut asset_router = AssetRouter::default();
+        let asset_configs = vec![
+            AssetConfig::Pattern {
+                pattern: "**/*".to_string(),
+                exclude: vec!["**/*.map".to_string()],
+                exclude_dotfiles: true,
+                content_type: None,
+                headers: vec![("Cache-Control".to_string(), "no-cache".to_string())],
+                encodings: vec![],
+                image_variants: vec![],
+            },
+            AssetConfig::Pattern {
+                pattern: "**/*.map".to_string(),
+                exclude: vec![],
+                exclude_dotfiles: false,
+                content_type: Some("application/json".to_string()),
+                headers: vec![],
+                encodings: vec![],
+                image_variants: vec![],
+            },
+        ];
+        let assets = vec![
+            Asset::new("app.js", b"console.log('Hello');".as_slice()),
+            Asset::new("app.js.map", b"{}".as_slice()),
+            Asset::new(".env", b"SECRET=1".as_slice()),
+            Asset::new("assets/.DS_Store", [1, 2, 3].as_slice()),
+        ];
 
-        let response = asset_router
-            .serve_asset(&data_certificate(), &request)
+        let report = asset_router
+            .certify_assets(assets.clone(), asset_configs.clone())
             .unwrap();
-        let (witness, expr_path) = extract_witness_expr_path(&response);
-        add_v2_certificate_header(
-            &data_certificate(),
-            &mut expected_response,
-            &witness,
-            &expr_path,
-        );
 
+        assert_eq!(report.certified_paths, vec!["app.js"]);
         assert_eq!(
-            expr_path,
-            HttpCertificationPath::exact(request.get_path().unwrap()).to_expr_path()
-        );
-        assert_matches!(
-            witness.lookup_subtree(&expr_path),
-            SubtreeLookupResult::Found(_)
+            report.excluded_paths,
+            vec![".env", "app.js.map", "assets/.DS_Store"]
         );
-        assert_eq!(response, expected_response);
+        assert_eq!(report.unmatched_configs, vec!["**/*.map"]);
 
-        asset_router
-            .delete_assets(
+        assert!(asset_router
+            .get_assets()
+            .get("/app.js", None, None)
+            .is_some());
+        for request_url in ["/app.js.map", "/.env", "/assets/.DS_Store"] {
+            assert!(asset_router
+                .get_assets()
+                .get(request_url, None, None)
+                .is_none());
+        }
+
+        asset_router.sync_assets(assets, asset_configs).unwrap();
+
+        assert_eq!(asset_router.get_assets().len(), 1);
+    }
+
+    #[rstest]
+    fn test_certification_report_alias_collisions() {
+        let mut asset_router = AssetRouter::default().with_trailing_slash_aliases();
+        let report = asset_router
+            .certify_assets(
                 vec![
-                    Asset::new("not-found.html", not_found_html_body()),
-                    Asset::new("not-found.html.gz", not_found_html_gz_body()),
-                    Asset::new("not-found.html.zz", not_found_html_zz_body()),
-                    Asset::new("not-found.html.br", not_found_html_br_body()),
+                    Asset::new("about/index.html", b"<h1>About</h1>".as_slice()),
+                    Asset::new("index.html", b"<h1>Hello</h1>".as_slice()),
                 ],
-                vec![not_found_html_config()],
+                vec![AssetConfig::File {
+                    path: "index.html".to_string(),
+                    content_type: Some("text/html".to_string()),
+                    headers: vec![],
+                    fallback_for: vec![],
+                    aliased_by: vec!["/about".to_string()],
+                    encodings: vec![],
+                    image_variants: vec![],
+                }],
             )
             .unwrap();
 
-        let mut expected_response = expected_index_html_response();
-
-        let response = asset_router
-            .serve_asset(&data_certificate(), &request)
-            .unwrap();
-        let (witness, expr_path) = extract_witness_expr_path(&response);
-        add_v2_certificate_header(
-            &data_certificate(),
-            &mut expected_response,
-            &witness,
-            &expr_path,
-        );
-
-        assert_eq!(expr_path, vec!["http_expr", "", "<*>"]);
-        assert_matches!(
-            witness.lookup_subtree(&expr_path),
-            SubtreeLookupResult::Found(_)
+        assert_eq!(
+            report.alias_collisions,
+            vec![
+                AliasCollision {
+                    url: "/about".to_string(),
+                    asset_paths: vec!["about/index.html".to_string(), "index.html".to_string()],
+                },
+                AliasCollision {
+                    url: "/about/".to_string(),
+                    asset_paths: vec!["about/index.html".to_string(), "index.html".to_string()],
+                },
+            ]
         );
-        assert_eq!(response, expected_response);
+    }
 
-        asset_router
-            .delete_assets(
+    #[rstest]
+    fn test_certification_report_alias_collisions_with_certified_assets() {
+        let mut asset_router = AssetRouter::default().with_trailing_slash_aliases();
+        let report = asset_router
+            .certify_assets(
                 vec![
-                    Asset::new("index.html", index_html_body()),
-                    Asset::new("index.html.gz", index_html_gz_body()),
-                    Asset::new("index.html.zz", index_html_zz_body()),
-                    Asset::new("index.html.br", index_html_br_body()),
+                    Asset::new("about/index.html", b"<h1>About</h1>".as_slice()),
+                    Asset::new("index.html", b"<h1>Hello</h1>".as_slice()),
                 ],
-                vec![index_html_config()],
+                vec![],
+            )
+            .unwrap();
+        assert!(report.alias_collisions.is_empty());
+
+        let report = asset_router
+            .certify_assets(
+                vec![Asset::new("about.html", b"<h1>About</h1>".as_slice())],
+                vec![AssetConfig::File {
+                    path: "about.html".to_string(),
+                    content_type: Some("text/html".to_string()),
+                    headers: vec![],
+                    fallback_for: vec![],
+                    aliased_by: vec!["/about/".to_string()],
+                    encodings: vec![],
+                    image_variants: vec![],
+                }],
             )
             .unwrap();
 
-        let result = asset_router.serve_asset(&data_certificate(), &request);
-        assert_matches!(
-            result,
-            Err(AssetCertificationError::NoAssetMatchingRequestUrl {
-                request_url,
-             }) if request_url == request.get_path().unwrap()
+        assert_eq!(report.certified_paths, vec!["about.html"]);
+        assert_eq!(
+            report.alias_collisions,
+            vec![
+                AliasCollision {
+                    url: "/about".to_string(),
+                    asset_paths: vec!["about.html".to_string(), "about/index.html".to_string()],
+                },
+                AliasCollision {
+                    url: "/about/".to_string(),
+                    asset_paths: vec!["about.html".to_string(), "about/index.html".to_string()],
+                },
+            ]
         );
+
+        let report = asset_router
+            .certify_assets(
+                vec![Asset::new("about.html", b"<h1>About</h1>".as_slice())],
+                vec![],
+            )
+            .unwrap();
+        assert!(report.alias_collisions.is_empty());
     }
 
     #[rstest]
-    fn test_delete_all_assets() {
-        let mut asset_router = asset_router();
-
-        let request = HttpRequest::get("/index.html").build();
-
-        let mut expected_response = expected_index_html_response();
+    fn test_strict_asset_configs() {
+        let mut asset_router = AssetRouter::default().with_strict_asset_configs();
+        let asset_configs = vec![
+            AssetConfig::Pattern {
+                pattern: "**/*.js".to_string(),
+                exclude: vec![],
+                exclude_dotfiles: false,
+                content_type: Some("text/javascript".to_string()),
+                headers: vec![],
+                encodings: vec![AssetEncoding::Gzip.default_config()],
+                image_variants: vec![],
+            },
+            AssetConfig::Pattern {
+                pattern: "**/*.css".to_string(),
+                exclude: vec![],
+                exclude_dotfiles: false,
+                content_type: Some("text/css".to_string()),
+                headers: vec![],
+                encodings: vec![],
+                image_variants: vec![],
+            },
+        ];
 
-        let response = asset_router
-            .serve_asset(&data_certificate(), &request)
-            .unwrap();
-        let (witness, expr_path) = extract_witness_expr_path(&response);
-        add_v2_certificate_header(
-            &data_certificate(),
-            &mut expected_response,
-            &witness,
-            &expr_path,
+        let result = asset_router.certify_assets(
+            vec![
+                Asset::new("app.js", b"console.log('Hello');".as_slice()),
+                Asset::new("app.js.gz", [1, 2, 3].as_slice()),
+            ],
+            asset_configs.clone(),
+        );
+        assert_matches!(
+            result,
+            Err(AssetCertificationError::NoAssetMatchingPattern { pattern }) if pattern == "**/*.css"
         );
 
-        assert_eq!(response, expected_response);
+        let result = asset_router.sync_assets(
+            vec![
+                Asset::new("app.js", b"console.log('Hello');".as_slice()),
+                Asset::new("app.css", b"html{}".as_slice()),
+            ],
+            asset_configs.clone(),
+        );
+        assert_matches!(
+            result,
+            Err(AssetCertificationError::EncodedAssetNotFound {
+                asset_path,
+                encoding: AssetEncoding::Gzip,
+                encoded_asset_path,
+            }) if asset_path == "app.js" && encoded_asset_path == "app.js.gz"
+        );
 
-        asset_router.delete_all_assets();
+        assert!(asset_router.get_assets().is_empty());
+        assert_eq!(
+            asset_router.root_hash(),
+            HttpCertificationTree::default().root_hash()
+        );
 
-        assert_matches!(
-            asset_router.serve_asset(
-                &data_certificate(),
-                &request,
-            ),
-            Err(AssetCertificationError::NoAssetMatchingRequestUrl {
-                request_url,
-            }) if request_url == "/index.html"
+        asset_router
+            .certify_assets(
+                vec![
+                    Asset::new("app.js", b"console.log('Hello');".as_slice()),
+                    Asset::new("app.js.gz", [1, 2, 3].as_slice()),
+                    Asset::new("app.css", b"html{}".as_slice()),
+                ],
+                asset_configs,
+            )
+            .unwrap();
+        assert_eq!(
+            asset_router
+                .serve_asset(&data_certificate(), &HttpRequest::get("/app.css").build())
+                .unwrap()
+                .status_code(),
+            StatusCode::OK
         );
+    }
 
-        let assets: Vec<_> = asset_router.get_assets().iter().collect();
-        assert!(assets.is_empty());
+    fn hashed_asset_configs() -> Vec<AssetConfig> {
+        vec![AssetConfig::Pattern {
+            pattern: "**/*.js".to_string(),
+            exclude: vec![],
+            exclude_dotfiles: false,
+            content_type: Some("text/javascript".to_string()),
+            headers: vec![(
+                "cache-control".to_string(),
+                "public, max-age=31536000, immutable".to_string(),
+            )],
+            encodings: vec![AssetEncoding::Gzip.default_config()],
+            image_variants: vec![],
+        }]
     }
 
-    #[rstest]
-    fn test_delete_by_path() {
-        let mut asset_router = asset_router();
+    fn hashed_asset_alias_config() -> HashedAssetAliasConfig {
+        HashedAssetAliasConfig {
+            manifest_path: "asset-manifest.json".to_string(),
+            headers: vec![("cache-control".to_string(), "public, no-cache".to_string())],
+        }
+    }
 
-        let index_request = HttpRequest::get("/index.html").build();
-        let mut expected_index_response = expected_index_html_response();
-        let index_response = asset_router
-            .serve_asset(&data_certificate(), &index_request)
+    #[rstest]
+    fn test_hashed_asset_aliases() {
+        let mut asset_router = AssetRouter::default();
+        let report = asset_router
+            .certify_hashed_assets(
+                vec![
+                    Asset::new("index.html", b"<h1>Hello</h1>".as_slice()),
+                    Asset::new("app-aaaaaaaa.js", b"console.log('v1');".as_slice()),
+                    Asset::new("app-aaaaaaaa.js.gz", [1, 2, 3].as_slice()),
+                ],
+                hashed_asset_configs(),
+                hashed_asset_alias_config(),
+            )
             .unwrap();
-        let (witness, expr_path) = extract_witness_expr_path(&index_response);
-        add_v2_certificate_header(
-            &data_certificate(),
-            &mut expected_index_response,
-            &witness,
-            &expr_path,
+        assert_eq!(
+            report.certified_paths,
+            vec![
+                "app-aaaaaaaa.js",
+                "app-aaaaaaaa.js.gz",
+                "asset-manifest.json",
+                "index.html"
+            ]
         );
-        assert_eq!(index_response, expected_index_response);
 
-        let alias_index_request = HttpRequest::get("/").build();
-        let mut expected_alias_index_response = expected_index_html_response();
-        let alias_index_response = asset_router
-            .serve_asset(&data_certificate(), &alias_index_request)
+        let response = asset_router
+            .serve_asset(&data_certificate(), &HttpRequest::get("/app.js").build())
             .unwrap();
-        let (witness, expr_path) = extract_witness_expr_path(&alias_index_response);
-        add_v2_certificate_header(
-            &data_certificate(),
-            &mut expected_alias_index_response,
-            &witness,
-            &expr_path,
-        );
-        assert_eq!(alias_index_response, expected_alias_index_response);
+        assert_eq!(response.body(), b"console.log('v1');");
+        assert!(response
+            .headers()
+            .contains(&("cache-control".to_string(), "public, no-cache".to_string())));
+        assert!(response
+            .headers()
+            .contains(&("content-type".to_string(), "text/javascript".to_string())));
 
-        let fallback_index_request = HttpRequest::get("/non-existent").build();
-        let mut expected_fallback_index_response = expected_index_html_response();
-        let fallback_index_response = asset_router
-            .serve_asset(&data_certificate(), &fallback_index_request)
+        let response = asset_router
+            .serve_asset(
+                &data_certificate(),
+                &HttpRequest::get("/app.js")
+                    .with_headers(vec![("accept-encoding".to_string(), "gzip".to_string())])
+                    .build(),
+            )
             .unwrap();
-        let (witness, expr_path) = extract_witness_expr_path(&fallback_index_response);
-        add_v2_certificate_header(
-            &data_certificate(),
-            &mut expected_fallback_index_response,
-            &witness,
-            &expr_path,
-        );
-        assert_eq!(fallback_index_response, expected_fallback_index_response);
+        assert_eq!(response.body(), [1, 2, 3]);
+
+        let response = asset_router
+            .serve_asset(
+                &data_certificate(),
+                &HttpRequest::get("/app-aaaaaaaa.js").build(),
+            )
+            .unwrap();
+        assert!(response.headers().contains(&(
+            "cache-control".to_string(),
+            "public, max-age=31536000, immutable".to_string()
+        )));
+
+        let response = asset_router
+            .serve_asset(
+                &data_certificate(),
+                &HttpRequest::get("/asset-manifest.json").build(),
+            )
+            .unwrap();
+        assert_eq!(response.body(), br#"{"app.js":"app-aaaaaaaa.js"}"#);
+        assert!(response
+            .headers()
+            .contains(&("content-type".to_string(), "application/json".to_string())));
 
-        asset_router.delete_fallback_assets_by_path(vec!["/"]);
+        let v2_assets = vec![Asset::new(
+            "app-bbbbbbbb.js",
+            b"console.log('v2');".as_slice(),
+        )];
+        asset_router
+            .certify_hashed_assets(
+                v2_assets.clone(),
+                hashed_asset_configs(),
+                hashed_asset_alias_config(),
+            )
+            .unwrap();
 
-        let index_request = HttpRequest::get("/index.html").build();
-        let mut expected_index_response = expected_index_html_response();
-        let index_response = asset_router
-            .serve_asset(&data_certificate(), &index_request)
+        for encoding in ["gzip", "identity"] {
+            let response = asset_router
+                .serve_asset(
+                    &data_certificate(),
+                    &HttpRequest::get("/app.js")
+                        .with_headers(vec![("accept-encoding".to_string(), encoding.to_string())])
+                        .build(),
+                )
+                .unwrap();
+            assert_eq!(response.body(), b"console.log('v2');");
+        }
+        let response = asset_router
+            .serve_asset(
+                &data_certificate(),
+                &HttpRequest::get("/asset-manifest.json").build(),
+            )
             .unwrap();
-        let (witness, expr_path) = extract_witness_expr_path(&index_response);
-        add_v2_certificate_header(
-            &data_certificate(),
-            &mut expected_index_response,
-            &witness,
-            &expr_path,
-        );
-        assert_eq!(index_response, expected_index_response);
+        assert_eq!(response.body(), br#"{"app.js":"app-bbbbbbbb.js"}"#);
 
-        let alias_index_request = HttpRequest::get("/").build();
-        let mut expected_alias_index_response = expected_index_html_response();
-        let alias_index_response = asset_router
-            .serve_asset(&data_certificate(), &alias_index_request)
+        // the aliases of the first version are no longer certified
+        let mut expected_asset_router = AssetRouter::default();
+        expected_asset_router
+            .certify_assets(
+                vec![
+                    Asset::new("index.html", b"<h1>Hello</h1>".as_slice()),
+                    Asset::new("app-aaaaaaaa.js", b"console.log('v1');".as_slice()),
+                    Asset::new("app-aaaaaaaa.js.gz", [1, 2, 3].as_slice()),
+                ],
+                hashed_asset_configs(),
+            )
             .unwrap();
-        let (witness, expr_path) = extract_witness_expr_path(&alias_index_response);
-        add_v2_certificate_header(
-            &data_certificate(),
-            &mut expected_alias_index_response,
-            &witness,
-            &expr_path,
-        );
-        assert_eq!(alias_index_response, expected_alias_index_response);
+        expected_asset_router
+            .certify_hashed_assets(
+                v2_assets,
+                hashed_asset_configs(),
+                hashed_asset_alias_config(),
+            )
+            .unwrap();
+        assert_eq!(asset_router.root_hash(), expected_asset_router.root_hash());
+    }
 
-        assert_matches!(
-            asset_router.serve_asset(
-                &data_certificate(),
-                &fallback_index_request,
-            ),
-            Err(AssetCertificationError::NoAssetMatchingRequestUrl {
-                request_url,
-            }) if request_url == "/non-existent"
-        );
+    #[rstest]
+    fn test_glob_scoped_fallbacks() {
+        let fallback_config = |path: &str, scope: &str| AssetConfig::File {
+            path: path.to_string(),
+            content_type: Some("text/html".to_string()),
+            headers: vec![],
+            fallback_for: vec![AssetFallbackConfig {
+                scope: scope.to_string(),
+                status_code: Some(StatusCode::OK),
+                priority: 0,
+            }],
+            aliased_by: vec![],
+            encodings: vec![],
+            image_variants: vec![],
+        };
 
-        asset_router.delete_assets_by_path(vec!["/index.html", "/"]);
+        let mut asset_router = AssetRouter::default();
+        asset_router
+            .certify_assets(
+                vec![
+                    Asset::new("index.html", b"index".as_slice()),
+                    Asset::new("settings.html", b"settings".as_slice()),
+                    Asset::new("profile.html", b"profile".as_slice()),
+                ],
+                vec![
+                    fallback_config("index.html", "/"),
+                    fallback_config("settings.html", "/app/*/settings"),
+                    fallback_config("profile.html", "/app/*/profile"),
+                ],
+            )
+            .unwrap();
 
-        assert_matches!(
-            asset_router.serve_asset(
-                &data_certificate(),
-                &index_request,
+        for (path, body, expected_expr_path) in [
+            (
+                "/app/1/settings/general",
+                b"settings".as_slice(),
+                vec!["http_expr", "app", "", "<*>"],
             ),
-            Err(AssetCertificationError::NoAssetMatchingRequestUrl {
-                request_url,
-            }) if request_url == "/index.html"
-        );
+            (
+                "/app/2/profile/avatar",
+                b"profile".as_slice(),
+                vec!["http_expr", "app", "", "<*>"],
+            ),
+            ("/about", b"index".as_slice(), vec!["http_expr", "", "<*>"]),
+        ] {
+            let response = asset_router
+                .serve_asset(&data_certificate(), &HttpRequest::get(path).build())
+                .unwrap();
+            let (witness, expr_path) = extract_witness_expr_path(&response);
+
+            assert_eq!(response.body(), body);
+            assert_eq!(expr_path, expected_expr_path);
+            assert_eq!(witness.digest(), asset_router.root_hash());
+        }
+
+        // the prefix of the glob scopes is certified, so the less specific fallback cannot be verified
         assert_matches!(
             asset_router.serve_asset(
                 &data_certificate(),
-                &alias_index_request,
+                &HttpRequest::get("/app/3/other/page").build()
             ),
-            Err(AssetCertificationError::NoAssetMatchingRequestUrl {
-                request_url,
-            }) if request_url == "/"
+            Err(AssetCertificationError::NoAssetMatchingRequestUrl { .. })
         );
+        // wildcards do not match across segments
         assert_matches!(
             asset_router.serve_asset(
                 &data_certificate(),
-                &fallback_index_request,
+                &HttpRequest::get("/app/3/4/settings/general").build()
             ),
-            Err(AssetCertificationError::NoAssetMatchingRequestUrl {
-                request_url,
-            }) if request_url == "/non-existent"
-        );
-    }
-
-    #[rstest]
-    fn test_asset_map(mut asset_router: AssetRouter) {
-        let index_html_response = asset_router.get_assets().get("/index.html", None, None);
-        assert_matches!(
-            index_html_response,
-            Some(index_html_response) if index_html_response == &expected_index_html_response()
-        );
-
-        let index_html_fallback_response = asset_router.get_fallback_assets().get("/", None, None);
-        assert_matches!(
-            index_html_fallback_response,
-            Some(index_html_fallback_response) if index_html_fallback_response == &expected_index_html_response()
+            Err(AssetCertificationError::NoAssetMatchingRequestUrl { .. })
         );
 
-        let index_html_gz_response =
+        asset_router.delete_fallback_assets_by_path(vec!["/app/*/settings"]);
+        assert_eq!(
             asset_router
-                .get_assets()
-                .get("/index.html", Some(AssetEncoding::Gzip), None);
-        assert_matches!(
-            index_html_gz_response,
-            Some(index_html_gz_response) if index_html_gz_response == &expected_index_html_gz_response()
+                .fallback_scope_matchers
+                .get("/app/")
+                .map(|matchers| matchers.keys().map(String::as_str).collect::<Vec<_>>()),
+            Some(vec!["/app/*/profile"])
         );
 
-        let index_html_gz_fallback_response =
-            asset_router
-                .get_fallback_assets()
-                .get("/", Some(AssetEncoding::Gzip), None);
-        assert_matches!(
-            index_html_gz_fallback_response,
-            Some(index_html_gz_fallback_response) if index_html_gz_fallback_response == &expected_index_html_gz_response()
-        );
+        asset_router.delete_fallback_assets_by_path(vec!["/app/*/profile"]);
+        assert!(asset_router.fallback_scope_matchers.is_empty());
 
-        let index_html_zz_response =
-            asset_router
-                .get_assets()
-                .get("/index.html", Some(AssetEncoding::Deflate), None);
-        assert_matches!(
-            index_html_zz_response,
-            Some(index_html_zz_response) if index_html_zz_response == &expected_index_html_zz_response()
-        );
+        let response = asset_router
+            .serve_asset(
+                &data_certificate(),
+                &HttpRequest::get("/app/1/settings/general").build(),
+            )
+            .unwrap();
+        assert_eq!(response.body(), b"index");
 
-        let index_html_zz_fallback_response =
-            asset_router
-                .get_fallback_assets()
-                .get("/", Some(AssetEncoding::Deflate), None);
-        assert_matches!(
-            index_html_zz_fallback_response,
-            Some(index_html_zz_fallback_response) if index_html_zz_fallback_response == &expected_index_html_zz_response()
+        asset_router.delete_fallback_assets_by_path(vec!["/"]);
+        assert!(asset_router.get_fallback_assets().is_empty());
+        asset_router.delete_assets_by_path(vec!["/index.html", "/settings.html", "/profile.html"]);
+        assert_eq!(
+            asset_router.root_hash(),
+            HttpCertificationTree::default().root_hash()
         );
+    }
 
-        let index_html_br_response =
-            asset_router
-                .get_assets()
-                .get("/index.html", Some(AssetEncoding::Brotli), None);
-        assert_matches!(
-            index_html_br_response,
-            Some(index_html_br_response) if index_html_br_response == &expected_index_html_br_response()
-        );
+    #[rstest]
+    fn test_glob_scoped_fallback_shadowed_by_specific_scope() {
+        let mut asset_router = AssetRouter::default();
+        asset_router
+            .certify_assets(
+                vec![
+                    Asset::new("settings.html", b"settings".as_slice()),
+                    Asset::new("app.html", b"app".as_slice()),
+                ],
+                vec![
+                    AssetConfig::File {
+                        path: "settings.html".to_string(),
+                        content_type: Some("text/html".to_string()),
+                        headers: vec![],
+                        fallback_for: vec![AssetFallbackConfig {
+                            scope: "/app/*/settings".to_string(),
+                            status_code: Some(StatusCode::OK),
+                            priority: 0,
+                        }],
+                        aliased_by: vec![],
+                        encodings: vec![],
+                        image_variants: vec![],
+                    },
+                    AssetConfig::File {
+                        path: "app.html".to_string(),
+                        content_type: Some("text/html".to_string()),
+                        headers: vec![],
+                        fallback_for: vec![AssetFallbackConfig {
+                            scope: "/app/1".to_string(),
+                            status_code: Some(StatusCode::OK),
+                            priority: 0,
+                        }],
+                        aliased_by: vec![],
+                        encodings: vec![],
+                        image_variants: vec![],
+                    },
+                ],
+            )
+            .unwrap();
 
-        let index_html_br_fallback_response =
-            asset_router
-                .get_fallback_assets()
-                .get("/", Some(AssetEncoding::Brotli), None);
-        assert_matches!(
-            index_html_br_fallback_response,
-            Some(index_html_br_fallback_response) if index_html_br_fallback_response == &expected_index_html_br_response()
-        );
+        let response = asset_router
+            .serve_asset(
+                &data_certificate(),
+                &HttpRequest::get("/app/1/settings/general").build(),
+            )
+            .unwrap();
+        assert_eq!(response.body(), b"app");
+
+        let response = asset_router
+            .serve_asset(
+                &data_certificate(),
+                &HttpRequest::get("/app/2/settings/general").build(),
+            )
+            .unwrap();
+        assert_eq!(response.body(), b"settings");
+    }
 
+    #[rstest]
+    fn test_encoded_scoped_fallback_shadows_less_specific_scope() {
+        let app_config = AssetConfig::File {
+            path: "app.html".to_string(),
+            content_type: Some("text/html".to_string()),
+            headers: vec![],
+            fallback_for: vec![AssetFallbackConfig {
+                scope: "/app".to_string(),
+                status_code: Some(StatusCode::OK),
+                priority: 0,
+            }],
+            aliased_by: vec![],
+            encodings: vec![AssetEncoding::Gzip.default_config()],
+            image_variants: vec![],
+        };
+        let mut asset_router = AssetRouter::default();
         asset_router
-            .delete_assets(
+            .certify_assets(
                 vec![
-                    Asset::new("index.html", index_html_body()),
-                    Asset::new("index.html.gz", index_html_gz_body()),
-                    Asset::new("index.html.zz", index_html_zz_body()),
-                    Asset::new("index.html.br", index_html_br_body()),
+                    Asset::new("index.html", b"index".as_slice()),
+                    Asset::new("app.html", b"app".as_slice()),
+                    Asset::new("app.html.gz", b"app gzip".as_slice()),
+                ],
+                vec![
+                    AssetConfig::File {
+                        path: "index.html".to_string(),
+                        content_type: Some("text/html".to_string()),
+                        headers: vec![],
+                        fallback_for: vec![AssetFallbackConfig {
+                            scope: "/".to_string(),
+                            status_code: Some(StatusCode::OK),
+                            priority: 0,
+                        }],
+                        aliased_by: vec![],
+                        encodings: vec![],
+                        image_variants: vec![],
+                    },
+                    app_config.clone(),
                 ],
-                vec![index_html_config()],
             )
             .unwrap();
 
-        let index_html_response = asset_router.get_assets().get("/index.html", None, None);
-        assert_matches!(index_html_response, None);
+        // only the gzip encoded fallback of the `/app` scope remains certified
+        asset_router
+            .delete_assets(
+                vec![Asset::new("app.html", b"app".as_slice())],
+                vec![app_config],
+            )
+            .unwrap();
 
-        let index_html_fallback_response = asset_router.get_fallback_assets().get("/", None, None);
-        assert_matches!(index_html_fallback_response, None);
+        assert_matches!(
+            asset_router.serve_asset(
+                &data_certificate(),
+                &HttpRequest::get("/app/settings").build(),
+            ),
+            Err(AssetCertificationError::NoAssetMatchingRequestUrl { .. })
+        );
 
-        let index_html_gz_response =
-            asset_router
-                .get_assets()
-                .get("/index.html", Some(AssetEncoding::Gzip), None);
-        assert_matches!(index_html_gz_response, None);
+        let response = asset_router
+            .serve_asset(
+                &data_certificate(),
+                &HttpRequest::get("/app/settings")
+                    .with_headers(vec![("Accept-Encoding".to_string(), "gzip".to_string())])
+                    .build(),
+            )
+            .unwrap();
+        assert_eq!(response.body(), b"app gzip");
+    }
 
-        let index_html_gz_fallback_response =
-            asset_router
-                .get_fallback_assets()
-                .get("/", Some(AssetEncoding::Gzip), None);
-        assert_matches!(index_html_gz_fallback_response, None);
+    fn encoded_asset_router(asset_router: AssetRouter<'static>) -> AssetRouter<'static> {
+        let mut asset_router = asset_router;
+        asset_router
+            .certify_assets(
+                vec![
+                    Asset::new("app.js", b"identity".as_slice()),
+                    Asset::new("app.js.br", b"br".as_slice()),
+                    Asset::new("app.js.zst", b"zstd".as_slice()),
+                    Asset::new("app.js.gz", b"gzip".as_slice()),
+                ],
+                vec![AssetConfig::Pattern {
+                    pattern: "**/*.js".to_string(),
+                    exclude: vec![],
+                    exclude_dotfiles: false,
+                    content_type: Some("text/javascript".to_string()),
+                    headers: vec![],
+                    encodings: vec![
+                        AssetEncoding::Brotli.default_config(),
+                        AssetEncoding::Zstd.default_config(),
+                        AssetEncoding::Gzip.default_config(),
+                    ],
+                    image_variants: vec![],
+                }],
+            )
+            .unwrap();
 
-        let index_html_zz_response =
-            asset_router
-                .get_assets()
-                .get("/index.html", Some(AssetEncoding::Deflate), None);
-        assert_matches!(index_html_zz_response, None);
+        asset_router
+    }
 
-        let index_html_zz_fallback_response =
-            asset_router
-                .get_fallback_assets()
-                .get("/", Some(AssetEncoding::Deflate), None);
-        assert_matches!(index_html_zz_fallback_response, None);
+    #[rstest]
+    #[case(None, "gzip, br, zstd", b"br".as_slice())]
+    #[case(None, "gzip, zstd", b"zstd".as_slice())]
+    #[case(None, "deflate", b"identity".as_slice())]
+    #[case(Some(vec![AssetEncoding::Zstd, AssetEncoding::Gzip]), "gzip, br, zstd", b"zstd".as_slice())]
+    #[case(Some(vec![AssetEncoding::Zstd, AssetEncoding::Gzip]), "br", b"identity".as_slice())]
+    #[case(Some(vec![AssetEncoding::Gzip, AssetEncoding::Brotli]), "br, gzip", b"gzip".as_slice())]
+    #[case(Some(vec![AssetEncoding::Identity, AssetEncoding::Gzip]), "gzip, identity", b"identity".as_slice())]
+    #[case(Some(vec![AssetEncoding::Brotli, AssetEncoding::Identity, AssetEncoding::Gzip]), "gzip", b"identity".as_slice())]
+    #[case(Some(vec![]), "br, gzip, zstd", b"identity".as_slice())]
+    fn test_encoding_priority(
+        #[case] encoding_priority: Option<Vec<AssetEncoding>>,
+        #[case] accept_encoding: &str,
+        #[case] expected_body: &[u8],
+    ) {
+        let mut asset_router = AssetRouter::default();
+        if let Some(encoding_priority) = encoding_priority {
+            asset_router = asset_router.with_encoding_priority(&encoding_priority);
+        }
+        let asset_router = encoded_asset_router(asset_router);
 
-        let index_html_br_response =
-            asset_router
-                .get_assets()
-                .get("/index.html", Some(AssetEncoding::Brotli), None);
-        assert_matches!(index_html_br_response, None);
+        let request = HttpRequest::get("/app.js")
+            .with_headers(vec![(
+                "accept-encoding".to_string(),
+                accept_encoding.to_string(),
+            )])
+            .build();
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
 
-        let index_html_br_fallback_response =
-            asset_router
-                .get_fallback_assets()
-                .get("/", Some(AssetEncoding::Brotli), None);
-        assert_matches!(index_html_br_fallback_response, None);
+        assert_eq!(response.body(), expected_body);
     }
 
     #[rstest]
-    fn test_asset_map_chunked_responses() {
-        let mut asset_router = long_asset_router_with_params(
-            &[TWO_CHUNKS_ASSET_NAME],
-            &[AssetEncoding::Gzip, AssetEncoding::Identity],
-        );
+    #[case("gzip;q=1, br;q=0.5", b"gzip".as_slice())]
+    #[case("br;q=0.5, gzip;q=0.8, zstd;q=0.8", b"zstd".as_slice())]
+    #[case("br;q=0, gzip", b"gzip".as_slice())]
+    #[case("gzip;q=0.5, identity", b"identity".as_slice())]
+    #[case("gzip;q=0.5, identity;q=0.4", b"gzip".as_slice())]
+    #[case("*", b"br".as_slice())]
+    #[case("*;q=0.5, gzip", b"gzip".as_slice())]
+    #[case("br;q=0, *", b"zstd".as_slice())]
+    #[case("br;q=0, zstd;q=0, gzip;q=0, *;q=0.1", b"identity".as_slice())]
+    #[case("*;q=0, identity", b"identity".as_slice())]
+    #[case("gzip; Q=0.5 , br;q=invalid", b"br".as_slice())]
+    #[case("", b"identity".as_slice())]
+    fn test_accept_encoding_quality_values(
+        #[case] accept_encoding: &str,
+        #[case] expected_body: &[u8],
+    ) {
+        let asset_router = encoded_asset_router(AssetRouter::default());
 
-        let full_body = long_asset_body(TWO_CHUNKS_ASSET_NAME);
-        let (_, gzip_suffix) = AssetEncoding::Gzip.default_config();
-        let full_gz_body = long_asset_body(&format!("{}{}", TWO_CHUNKS_ASSET_NAME, gzip_suffix));
+        let request = HttpRequest::get("/app.js")
+            .with_headers(vec![(
+                "accept-encoding".to_string(),
+                accept_encoding.to_string(),
+            )])
+            .build();
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
 
-        let first_chunk_body = &full_body[0..ASSET_CHUNK_SIZE];
-        let first_chunk_response =
-            asset_router
-                .get_assets()
-                .get(format!("/{}", TWO_CHUNKS_ASSET_NAME), None, Some(0));
-        let expected_first_chunk_response = build_206_response(
-            first_chunk_body.to_vec(),
-            asset_range_chunk_cel_expr(),
-            vec![
-                (
-                    "cache-control".to_string(),
-                    "public, no-cache, no-store".to_string(),
-                ),
-                ("content-type".to_string(), "text/html".to_string()),
-                (
-                    "content-range".to_string(),
-                    format!("bytes 0-{}/{}", first_chunk_body.len() - 1, full_body.len()),
-                ),
-            ],
-        );
-        assert_matches!(
-            first_chunk_response,
-            Some(first_chunk_response) if first_chunk_response == &expected_first_chunk_response
-        );
+        assert_eq!(response.body(), expected_body);
+    }
 
-        let first_chunk_gzip_body = &full_gz_body[0..ASSET_CHUNK_SIZE];
-        let first_chunk_gzip_response = asset_router.get_assets().get(
-            format!("/{}", TWO_CHUNKS_ASSET_NAME),
-            Some(AssetEncoding::Gzip),
-            Some(0),
-        );
-        let expected_first_chunk_gzip_response = build_206_response(
-            first_chunk_gzip_body.to_vec(),
-            asset_range_chunk_cel_expr(),
-            vec![
-                (
-                    "cache-control".to_string(),
-                    "public, no-cache, no-store".to_string(),
-                ),
-                ("content-type".to_string(), "text/html".to_string()),
-                ("content-encoding".to_string(), "gzip".to_string()),
-                (
-                    "content-range".to_string(),
-                    format!(
-                        "bytes 0-{}/{}",
-                        first_chunk_gzip_body.len() - 1,
-                        full_gz_body.len()
-                    ),
-                ),
-            ],
-        );
-        assert_matches!(
-            first_chunk_gzip_response,
-            Some(first_chunk_gzip_response) if first_chunk_gzip_response == &expected_first_chunk_gzip_response
-        );
+    #[rstest]
+    #[case("identity;q=0, deflate")]
+    #[case("*;q=0")]
+    #[case("br;q=0, identity;q=0")]
+    fn test_accept_encoding_excludes_identity(#[case] accept_encoding: &str) {
+        let asset_router = encoded_asset_router(AssetRouter::default());
 
-        let second_chunk_body = &full_body[ASSET_CHUNK_SIZE..full_body.len()];
-        let second_chunk_response = asset_router.get_assets().get(
-            format!("/{}", TWO_CHUNKS_ASSET_NAME),
-            None,
-            Some(ASSET_CHUNK_SIZE),
-        );
-        let expected_second_chunk_response = build_206_response(
-            second_chunk_body.to_vec(),
-            asset_range_chunk_cel_expr(),
-            vec![
-                (
-                    "cache-control".to_string(),
-                    "public, no-cache, no-store".to_string(),
-                ),
-                ("content-type".to_string(), "text/html".to_string()),
-                (
-                    "content-range".to_string(),
-                    format!(
-                        "bytes {}-{}/{}",
-                        first_chunk_body.len(),
-                        first_chunk_body.len() + second_chunk_body.len() - 1,
-                        full_body.len()
-                    ),
-                ),
-            ],
-        );
+        let request = HttpRequest::get("/app.js")
+            .with_headers(vec![(
+                "accept-encoding".to_string(),
+                accept_encoding.to_string(),
+            )])
+            .build();
         assert_matches!(
-            second_chunk_response,
-            Some(second_chunk_response) if second_chunk_response == &expected_second_chunk_response
+            asset_router.serve_asset(&data_certificate(), &request),
+            Err(AssetCertificationError::NoAcceptableEncoding { request_url }) if request_url == "/app.js"
         );
 
-        let second_chunk_gzip_body = &full_gz_body[ASSET_CHUNK_SIZE..full_gz_body.len()];
-        let second_chunk_gzip_response = asset_router.get_assets().get(
-            format!("/{}", TWO_CHUNKS_ASSET_NAME),
-            Some(AssetEncoding::Gzip),
-            Some(ASSET_CHUNK_SIZE),
-        );
-        let expected_second_chunk_gzip_response = build_206_response(
-            second_chunk_gzip_body.to_vec(),
-            asset_range_chunk_cel_expr(),
-            vec![
-                (
-                    "cache-control".to_string(),
-                    "public, no-cache, no-store".to_string(),
-                ),
-                ("content-type".to_string(), "text/html".to_string()),
-                ("content-encoding".to_string(), "gzip".to_string()),
-                (
-                    "content-range".to_string(),
-                    format!(
-                        "bytes {}-{}/{}",
-                        first_chunk_gzip_body.len(),
-                        first_chunk_gzip_body.len() + second_chunk_gzip_body.len() - 1,
-                        full_gz_body.len()
-                    ),
-                ),
+        // forcing an encoding ignores the `Accept-Encoding` header
+        let response = asset_router
+            .serve_asset_with_options(
+                &data_certificate(),
+                &request,
+                &ServeOptions {
+                    encoding: Some(AssetEncoding::Deflate),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(response.body(), b"identity");
+    }
+
+    fn image_variant_assets() -> Vec<Asset<'static, 'static>> {
+        vec![
+            Asset::new("img/logo.png", b"png".as_slice()),
+            Asset::new("img/logo.avif", b"avif".as_slice()),
+            Asset::new("img/logo.webp", b"webp".as_slice()),
+            Asset::new("img/icon.png", b"icon png".as_slice()),
+        ]
+    }
+
+    fn image_variant_configs() -> Vec<AssetConfig> {
+        vec![AssetConfig::Pattern {
+            pattern: "**/*.png".to_string(),
+            exclude: vec![],
+            exclude_dotfiles: false,
+            content_type: Some("image/png".to_string()),
+            headers: vec![],
+            encodings: vec![],
+            image_variants: vec![
+                AssetImageFormat::Avif.default_config(),
+                AssetImageFormat::Webp.default_config(),
             ],
-        );
+        }]
+    }
 
-        assert_matches!(
-            second_chunk_gzip_response,
-            Some(second_chunk_gzip_response) if second_chunk_gzip_response == &expected_second_chunk_gzip_response
-        );
+    #[rstest]
+    #[case("/img/logo.png", None, "png", "image/png")]
+    #[case("/img/logo.png", Some("image/webp,*/*"), "webp", "image/webp")]
+    #[case(
+        "/img/logo.png",
+        Some("image/avif,image/webp,*/*"),
+        "avif",
+        "image/avif"
+    )]
+    #[case("/img/logo.png", Some("image/webp,image/avif"), "avif", "image/avif")]
+    #[case(
+        "/img/logo.png",
+        Some("image/avif;q=0.5,image/webp"),
+        "webp",
+        "image/webp"
+    )]
+    #[case(
+        "/img/logo.png",
+        Some("image/avif;q=0,image/webp;q=0"),
+        "png",
+        "image/png"
+    )]
+    #[case("/img/logo.png", Some("image/*,*/*;q=0.8"), "png", "image/png")]
+    #[case("/img/logo.png", Some("IMAGE/AVIF"), "avif", "image/avif")]
+    #[case(
+        "/img/icon.png",
+        Some("image/avif,image/webp,*/*"),
+        "icon png",
+        "image/png"
+    )]
+    #[case("/img/logo.avif", Some("image/webp,*/*"), "avif", "")]
+    fn test_image_variants(
+        #[case] request_url: &str,
+        #[case] accept: Option<&str>,
+        #[case] expected_body: &str,
+        #[case] expected_content_type: &str,
+    ) {
+        let mut asset_router = AssetRouter::default();
+        let report = asset_router
+            .certify_assets(image_variant_assets(), image_variant_configs())
+            .unwrap();
+
+        assert!(report.unconfigured_paths.is_empty());
 
+        let mut request = HttpRequest::get(request_url);
+        if let Some(accept) = accept {
+            request = request.with_headers(vec![("Accept".to_string(), accept.to_string())]);
+        }
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request.build())
+            .unwrap();
+        let content_type = response
+            .headers()
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+            .map(|(_, value)| value.as_str())
+            .unwrap_or_default();
+        let (witness, _) = extract_witness_expr_path(&response);
+
+        assert_eq!(response.body(), expected_body.as_bytes());
+        assert_eq!(content_type, expected_content_type);
+        assert_eq!(witness.digest(), asset_router.root_hash());
+    }
+
+    #[rstest]
+    fn test_image_variants_excluded_identity() {
+        let mut asset_router = AssetRouter::default();
         asset_router
-            .delete_assets(
-                vec![
-                    long_asset(TWO_CHUNKS_ASSET_NAME.to_string()),
-                    long_asset(format!("{}{}", TWO_CHUNKS_ASSET_NAME, gzip_suffix)),
-                ],
-                vec![
-                    long_asset_config(TWO_CHUNKS_ASSET_NAME),
-                    long_asset_config(&format!("{}{}", TWO_CHUNKS_ASSET_NAME, gzip_suffix)),
-                ],
-            )
+            .certify_assets(image_variant_assets(), image_variant_configs())
             .unwrap();
 
-        let first_chunk_response =
-            asset_router
-                .get_assets()
-                .get(format!("/{}", TWO_CHUNKS_ASSET_NAME), None, Some(0));
-        assert_matches!(first_chunk_response, None);
+        let request = HttpRequest::get("/img/logo.png")
+            .with_headers(vec![
+                ("Accept".to_string(), "image/avif".to_string()),
+                ("Accept-Encoding".to_string(), "identity;q=0".to_string()),
+            ])
+            .build();
 
-        let first_chunk_gzip_response = asset_router.get_assets().get(
-            format!("/{}", TWO_CHUNKS_ASSET_NAME),
-            Some(AssetEncoding::Gzip),
-            Some(0),
+        assert_matches!(
+            asset_router.serve_asset(&data_certificate(), &request),
+            Err(AssetCertificationError::NoAcceptableEncoding { .. })
         );
-        assert_matches!(first_chunk_gzip_response, None);
+    }
 
-        let second_chunk_response = asset_router.get_assets().get(
-            format!("/{}", TWO_CHUNKS_ASSET_NAME),
-            None,
-            Some(ASSET_CHUNK_SIZE),
-        );
-        assert_matches!(second_chunk_response, None);
+    #[rstest]
+    fn test_delete_image_variants() {
+        let mut asset_router = AssetRouter::default();
+        asset_router
+            .certify_assets(image_variant_assets(), image_variant_configs())
+            .unwrap();
+        asset_router
+            .delete_assets(image_variant_assets(), image_variant_configs())
+            .unwrap();
 
-        let second_chunk_gzip_response = asset_router.get_assets().get(
-            format!("/{}", TWO_CHUNKS_ASSET_NAME),
-            Some(AssetEncoding::Gzip),
-            Some(ASSET_CHUNK_SIZE),
+        assert!(asset_router.get_assets().is_empty());
+        assert!(asset_router.image_variant_responses.is_empty());
+        assert_eq!(
+            asset_router.root_hash(),
+            HttpCertificationTree::default().root_hash()
         );
-        assert_matches!(second_chunk_gzip_response, None);
+
+        asset_router
+            .certify_assets(image_variant_assets(), image_variant_configs())
+            .unwrap();
+        asset_router.delete_assets_by_path(vec!["/img/logo.png"]);
+
+        let request = HttpRequest::get("/img/logo.png")
+            .with_headers(vec![("Accept".to_string(), "image/avif".to_string())])
+            .build();
+
+        assert!(asset_router
+            .serve_asset(&data_certificate(), &request)
+            .is_err());
+        assert!(asset_router
+            .image_variant_responses
+            .keys()
+            .all(|(_, request_key)| request_key.path != "/img/logo.png"));
     }
 
     #[rstest]
-    fn test_asset_map_iter() {
-        let asset_router =
-            long_asset_router_with_params(&[TWO_CHUNKS_ASSET_NAME], &[AssetEncoding::Identity]);
-        let full_body = long_asset_body(TWO_CHUNKS_ASSET_NAME);
+    fn test_sync_image_variants() {
+        let mut asset_router = AssetRouter::default();
+        asset_router
+            .certify_assets(image_variant_assets(), image_variant_configs())
+            .unwrap();
 
-        let assets: Vec<_> = asset_router.get_assets().iter().collect();
-        assert!(assets.len() == 3);
+        let mut assets = image_variant_assets();
+        assets[1] = Asset::new("img/logo.avif", b"new avif".as_slice());
+        asset_router
+            .sync_assets(assets.clone(), image_variant_configs())
+            .unwrap();
 
-        let first_chunk_body = &full_body[0..ASSET_CHUNK_SIZE];
-        let expected_first_chunk_response = build_206_response(
-            first_chunk_body.to_vec(),
-            asset_range_chunk_cel_expr(),
-            vec![
-                (
-                    "cache-control".to_string(),
-                    "public, no-cache, no-store".to_string(),
-                ),
-                ("content-type".to_string(), "text/html".to_string()),
-                (
-                    "content-range".to_string(),
-                    format!("bytes 0-{}/{}", first_chunk_body.len() - 1, full_body.len()),
-                ),
-            ],
-        );
+        let mut expected_asset_router = AssetRouter::default();
+        expected_asset_router
+            .certify_assets(assets, image_variant_configs())
+            .unwrap();
+
+        assert_eq!(asset_router.root_hash(), expected_asset_router.root_hash());
+
+        let request = HttpRequest::get("/img/logo.png")
+            .with_headers(vec![("Accept".to_string(), "image/avif".to_string())])
+            .build();
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+
+        assert_eq!(response.body(), b"new avif");
+    }
 
-        assert!(assets.contains(&(
-            (&format!("/{}", TWO_CHUNKS_ASSET_NAME), None, Some(0)),
-            &expected_first_chunk_response
-        )));
+    #[rstest]
+    fn test_sync_asset_router(index_html_body: Vec<u8>) {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<SyncAssetRouter>();
 
-        let second_chunk_body = &full_body[ASSET_CHUNK_SIZE..full_body.len()];
-        let expected_second_chunk_response = build_206_response(
-            second_chunk_body.to_vec(),
-            asset_range_chunk_cel_expr(),
-            vec![
-                (
-                    "cache-control".to_string(),
-                    "public, no-cache, no-store".to_string(),
-                ),
-                ("content-type".to_string(), "text/html".to_string()),
-                (
-                    "content-range".to_string(),
-                    format!(
-                        "bytes {}-{}/{}",
-                        first_chunk_body.len(),
-                        first_chunk_body.len() + second_chunk_body.len() - 1,
-                        full_body.len()
-                    ),
-                ),
-            ],
-        );
-        assert!(assets.contains(&(
-            (
-                &format!("/{}", TWO_CHUNKS_ASSET_NAME),
-                None,
-                Some(ASSET_CHUNK_SIZE)
-            ),
-            &expected_second_chunk_response
-        )));
+        let http_certification_tree: Arc<RwLock<HttpCertificationTree>> = Default::default();
+        let mut asset_router = SyncAssetRouter::with_tree(http_certification_tree.clone());
+        asset_router
+            .certify_assets(
+                vec![Asset::new("index.html", index_html_body.as_slice())],
+                vec![index_html_config()],
+            )
+            .unwrap();
 
-        let expected_full_response = build_200_response(
-            full_body,
-            asset_cel_expr(),
-            vec![
-                (
-                    "cache-control".to_string(),
-                    "public, no-cache, no-store".to_string(),
-                ),
-                ("content-type".to_string(), "text/html".to_string()),
-            ],
+        let mut expected_asset_router = AssetRouter::default();
+        expected_asset_router
+            .certify_assets(
+                vec![Asset::new("index.html", index_html_body.as_slice())],
+                vec![index_html_config()],
+            )
+            .unwrap();
+
+        std::thread::scope(|scope| {
+            for path in ["/", "/index.html", "/nested/page"] {
+                let request = HttpRequest::get(path).build();
+                let expected_response = expected_asset_router
+                    .serve_asset(&data_certificate(), &request)
+                    .unwrap();
+                let asset_router = &asset_router;
+
+                scope.spawn(move || {
+                    let response = asset_router
+                        .serve_asset(&data_certificate(), &request)
+                        .unwrap();
+
+                    assert_eq!(response, expected_response);
+                });
+            }
+        });
+
+        assert_eq!(
+            asset_router.root_hash(),
+            http_certification_tree.read().unwrap().root_hash()
         );
-        assert!(assets.contains(&(
-            (&format!("/{}", TWO_CHUNKS_ASSET_NAME), None, None),
-            &expected_full_response
-        )));
+        assert_eq!(asset_router.root_hash(), expected_asset_router.root_hash());
     }
 
     #[rstest]
@@ -3402,6 +9966,7 @@ mod tests {
                     "cache-control".to_string(),
                     "public, no-cache, no-store".to_string(),
                 ),
+                ("vary".to_string(), "accept-encoding".to_string()),
                 ("content-type".to_string(), "text/html".to_string()),
             ],
         );
@@ -3520,6 +10085,57 @@ mod tests {
         );
     }
 
+    #[rstest]
+    fn test_get_redirects(asset_router: AssetRouter) {
+        assert_eq!(
+            asset_router.get_redirects(),
+            vec![
+                CertifiedRedirect {
+                    from: "/css/app.css".to_string(),
+                    to: "/css/app-ba74b708.css".to_string(),
+                    kind: AssetRedirectKind::Temporary,
+                    status_code: StatusCode::TEMPORARY_REDIRECT,
+                    append_query: false,
+                    namespace: None,
+                },
+                CertifiedRedirect {
+                    from: "/old-url".to_string(),
+                    to: "/".to_string(),
+                    kind: AssetRedirectKind::Permanent,
+                    status_code: StatusCode::MOVED_PERMANENTLY,
+                    append_query: false,
+                    namespace: None,
+                },
+            ]
+        );
+    }
+
+    #[rstest]
+    fn test_get_redirects_namespaces() {
+        let mut asset_router = AssetRouter::default();
+        asset_router
+            .certify_assets(
+                [namespace_assets("v1"), namespace_assets("v2")].concat(),
+                vec![namespace_config("v1"), namespace_config("v2")],
+            )
+            .unwrap();
+
+        let redirects = asset_router.get_redirects();
+        assert_eq!(
+            redirects
+                .iter()
+                .map(|redirect| (redirect.from.as_str(), redirect.namespace.as_deref()))
+                .collect::<Vec<_>>(),
+            vec![("/v1/old", Some("v1")), ("/v2/old", Some("v2"))]
+        );
+
+        asset_router.delete_namespace("v1");
+
+        let redirects = asset_router.get_redirects();
+        assert_eq!(redirects.len(), 1);
+        assert_eq!(redirects[0].from, "/v2/old");
+    }
+
     #[rstest]
     fn test_init_with_tree(index_html_body: Vec<u8>, asset_cel_expr: String) {
         let http_certification_tree: Rc<RefCell<HttpCertificationTree>> = Default::default();
@@ -3536,9 +10152,11 @@ mod tests {
             fallback_for: vec![AssetFallbackConfig {
                 scope: "/".to_string(),
                 status_code: Some(StatusCode::OK),
+                priority: 0,
             }],
             aliased_by: vec!["/".to_string()],
             encodings: vec![],
+            image_variants: vec![],
         };
 
         asset_router
@@ -3611,6 +10229,7 @@ mod tests {
                     "cache-control".to_string(),
                     "public, no-cache, no-store".to_string(),
                 ),
+                ("vary".to_string(), "accept-encoding".to_string()),
                 ("content-type".to_string(), "text/html".to_string()),
             ],
         )
@@ -3638,6 +10257,7 @@ mod tests {
                     "cache-control".to_string(),
                     "public, no-cache, no-store".to_string(),
                 ),
+                ("vary".to_string(), "accept-encoding".to_string()),
                 ("content-type".to_string(), "text/html".to_string()),
                 ("content-encoding".to_string(), "gzip".to_string()),
             ],
@@ -3665,6 +10285,7 @@ mod tests {
                     "cache-control".to_string(),
                     "public, no-cache, no-store".to_string(),
                 ),
+                ("vary".to_string(), "accept-encoding".to_string()),
                 ("content-type".to_string(), "text/html".to_string()),
                 ("content-encoding".to_string(), "deflate".to_string()),
             ],
@@ -3692,6 +10313,7 @@ mod tests {
                     "cache-control".to_string(),
                     "public, no-cache, no-store".to_string(),
                 ),
+                ("vary".to_string(), "accept-encoding".to_string()),
                 ("content-type".to_string(), "text/html".to_string()),
                 ("content-encoding".to_string(), "br".to_string()),
             ],
@@ -3838,6 +10460,7 @@ mod tests {
             fallback_for: vec![AssetFallbackConfig {
                 scope: "/".to_string(),
                 status_code: Some(StatusCode::OK),
+                priority: 0,
             }],
             aliased_by: vec!["/".to_string()],
             encodings: vec![
@@ -3845,6 +10468,7 @@ mod tests {
                 AssetEncoding::Deflate.default_config(),
                 AssetEncoding::Brotli.default_config(),
             ],
+            image_variants: vec![],
         }
     }
 
@@ -3852,6 +10476,8 @@ mod tests {
     fn js_config() -> AssetConfig {
         AssetConfig::Pattern {
             pattern: r"**/*.js".to_string(),
+            exclude: vec![],
+            exclude_dotfiles: false,
             content_type: Some("text/javascript".to_string()),
             headers: vec![(
                 "cache-control".to_string(),
@@ -3862,6 +10488,7 @@ mod tests {
                 AssetEncoding::Deflate.default_config(),
                 AssetEncoding::Brotli.default_config(),
             ],
+            image_variants: vec![],
         }
     }
 
@@ -3869,6 +10496,8 @@ mod tests {
     fn css_config() -> AssetConfig {
         AssetConfig::Pattern {
             pattern: "**/*.css".to_string(),
+            exclude: vec![],
+            exclude_dotfiles: false,
             content_type: Some("text/css".to_string()),
             headers: vec![(
                 "cache-control".to_string(),
@@ -3879,6 +10508,7 @@ mod tests {
                 AssetEncoding::Deflate.default_config(),
                 AssetEncoding::Brotli.default_config(),
             ],
+            image_variants: vec![],
         }
     }
 
@@ -3895,10 +10525,12 @@ mod tests {
                 AssetFallbackConfig {
                     scope: "/js".to_string(),
                     status_code: Some(StatusCode::NOT_FOUND),
+                    priority: 0,
                 },
                 AssetFallbackConfig {
                     scope: "/css".to_string(),
                     status_code: Some(StatusCode::NOT_FOUND),
+                    priority: 0,
                 },
             ],
             aliased_by: vec![
@@ -3914,15 +10546,276 @@ mod tests {
                 AssetEncoding::Deflate.default_config(),
                 AssetEncoding::Brotli.default_config(),
             ],
+            image_variants: vec![],
+        }
+    }
+
+    #[rstest]
+    #[case(AssetRedirectKind::Permanent, StatusCode::MOVED_PERMANENTLY)]
+    #[case(AssetRedirectKind::Temporary, StatusCode::TEMPORARY_REDIRECT)]
+    #[case(AssetRedirectKind::Found, StatusCode::FOUND)]
+    #[case(AssetRedirectKind::PermanentRedirect, StatusCode::PERMANENT_REDIRECT)]
+    fn test_redirect_kinds(#[case] kind: AssetRedirectKind, #[case] status_code: StatusCode) {
+        let mut asset_router = AssetRouter::default();
+        asset_router
+            .certify_assets(
+                Vec::<Asset>::new(),
+                vec![AssetConfig::Redirect {
+                    from: "/old-url".to_string(),
+                    to: "/new-url".to_string(),
+                    kind,
+                    append_query: false,
+                    headers: vec![],
+                }],
+            )
+            .unwrap();
+
+        let request = HttpRequest::get("/old-url").build();
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+
+        assert_eq!(response.status_code(), status_code);
+        assert_eq!(
+            response.headers()[1],
+            ("location".to_string(), "/new-url".to_string())
+        );
+    }
+
+    #[rstest]
+    #[case("/promo", "/landing", "/landing")]
+    #[case("/promo?utm_source=mail", "/landing", "/landing?utm_source=mail")]
+    #[case(
+        "/promo?utm_source=mail&utm_medium=x",
+        "/landing?lang=en",
+        "/landing?lang=en&utm_source=mail&utm_medium=x"
+    )]
+    #[case(
+        "/promo?utm_source=mail",
+        "/landing#offer",
+        "/landing?utm_source=mail#offer"
+    )]
+    #[case("/promo?", "/landing", "/landing")]
+    fn test_redirect_preserve_query(
+        #[case] request_url: &str,
+        #[case] to: &str,
+        #[case] expected_location: &str,
+    ) {
+        let mut asset_router = AssetRouter::default();
+        asset_router
+            .certify_assets(
+                Vec::<Asset>::new(),
+                vec![AssetConfig::Redirect {
+                    from: "/promo".to_string(),
+                    to: to.to_string(),
+                    kind: AssetRedirectKind::Found,
+                    append_query: true,
+                    headers: vec![],
+                }],
+            )
+            .unwrap();
+        let cel_expr = DefaultFullCelExpressionBuilder::default()
+            .with_response_certification(DefaultResponseCertification::response_header_exclusions(
+                vec!["location"],
+            ))
+            .build()
+            .to_string();
+
+        let request = HttpRequest::get(request_url).build();
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        let (witness, expr_path) = extract_witness_expr_path(&response);
+        let mut expected_response = HttpResponse::builder()
+            .with_status_code(StatusCode::FOUND)
+            .with_headers(vec![
+                ("content-length".to_string(), "0".to_string()),
+                ("location".to_string(), expected_location.to_string()),
+                (CERTIFICATE_EXPRESSION_HEADER_NAME.to_string(), cel_expr),
+            ])
+            .build();
+        add_v2_certificate_header(
+            &data_certificate(),
+            &mut expected_response,
+            &witness,
+            &expr_path,
+        );
+
+        assert_eq!(expr_path, vec!["http_expr", "promo", "<$>"]);
+        assert_matches!(
+            witness.lookup_subtree(&expr_path),
+            SubtreeLookupResult::Found(_)
+        );
+        assert_eq!(response, expected_response);
+    }
+
+    #[rstest]
+    #[case("/promo?utm_source=mail&v=123", "/landing?utm_source=mail")]
+    #[case("/promo?v=123&cb=1", "/landing")]
+    #[case("/promo?&v=1&&utm_source=mail&", "/landing?utm_source=mail")]
+    #[case("/promo?version=1", "/landing?version=1")]
+    fn test_redirect_preserve_query_stripped_params(
+        #[case] request_url: &str,
+        #[case] expected_location: &str,
+    ) {
+        let mut asset_router = AssetRouter::default().with_stripped_query_params(["v", "cb"]);
+        asset_router
+            .certify_assets(
+                Vec::<Asset>::new(),
+                vec![AssetConfig::Redirect {
+                    from: "/promo".to_string(),
+                    to: "/landing".to_string(),
+                    kind: AssetRedirectKind::Found,
+                    append_query: true,
+                    headers: vec![],
+                }],
+            )
+            .unwrap();
+
+        let request = HttpRequest::get(request_url).build();
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+
+        assert_eq!(
+            response.headers()[1],
+            ("location".to_string(), expected_location.to_string())
+        );
+    }
+
+    #[rstest]
+    #[case(false)]
+    #[case(true)]
+    fn test_redirect_append_query_fails_certified_redirects_policy(#[case] append_query: bool) {
+        let mut asset_router = AssetRouter::default();
+        asset_router
+            .certify_assets(
+                Vec::<Asset>::new(),
+                vec![AssetConfig::Redirect {
+                    from: "/promo".to_string(),
+                    to: "/landing".to_string(),
+                    kind: AssetRedirectKind::Found,
+                    append_query,
+                    headers: vec![],
+                }],
+            )
+            .unwrap();
+        let current_time = get_current_timestamp();
+        let V2CertificateFixture {
+            root_key,
+            certificate_cbor,
+            canister_id,
+        } = create_v2_certificate_fixture(&asset_router.root_hash(), &current_time);
+
+        let verify = |policy: &VerificationPolicy| {
+            let request = HttpRequest::get("/promo?utm_source=mail").build();
+            let response = asset_router
+                .serve_asset(&certificate_cbor, &request)
+                .unwrap();
+
+            verify_request_response_pair(
+                request,
+                response,
+                canister_id.as_ref(),
+                &FixedTimeSource::new(current_time),
+                &TimeConfig::default(),
+                &root_key,
+                2,
+                policy,
+            )
+        };
+
+        verify(&VerificationPolicy::default()).unwrap();
+        let result = verify(&VerificationPolicy::new().with_certified_redirects(true));
+        if append_query {
+            assert_matches!(
+                result,
+                Err(ResponseVerificationError::UncertifiedRedirectLocation { status_code: 302 })
+            );
+        } else {
+            result.unwrap();
+        }
+    }
+
+    #[rstest]
+    #[case("/index.html", None)]
+    #[case("/index.html?", None)]
+    #[case("/index.html?v=123", None)]
+    #[case("/index.html?v=123&&", None)]
+    #[case("/index.html?v=123&lang=en", Some("lang=en"))]
+    #[case("/index.html?lang=en&v", Some("lang=en"))]
+    fn test_strict_query_strings(
+        #[case] request_url: &str,
+        #[case] expected_rejected_query: Option<&str>,
+    ) {
+        let mut asset_router = AssetRouter::default()
+            .with_stripped_query_params(["v"])
+            .with_strict_query_strings();
+        asset_router
+            .certify_assets(
+                vec![Asset::new("index.html", b"index.html".as_slice())],
+                vec![],
+            )
+            .unwrap();
+
+        let request = HttpRequest::get(request_url).build();
+        let response = asset_router.serve_asset(&data_certificate(), &request);
+        match expected_rejected_query {
+            Some(expected_query) => assert_matches!(
+                response,
+                Err(AssetCertificationError::QueryStringNotAllowed { request_url, query })
+                    if request_url == "/index.html" && query == expected_query
+            ),
+            None => assert_eq!(response.unwrap().body(), b"index.html"),
         }
     }
 
+    #[rstest]
+    fn test_query_strings_are_ignored_by_default() {
+        let mut asset_router = AssetRouter::default();
+        asset_router
+            .certify_assets(
+                vec![Asset::new("index.html", b"index.html".as_slice())],
+                vec![],
+            )
+            .unwrap();
+
+        let request = HttpRequest::get("/index.html?v=123&lang=en").build();
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        assert_eq!(response.body(), b"index.html");
+    }
+
+    #[rstest]
+    fn test_redirect_without_preserve_query_ignores_query(mut asset_router: AssetRouter) {
+        let request = HttpRequest::get("/old-url?utm_source=mail").build();
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+
+        assert_eq!(response.status_code(), StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(
+            response.headers()[1],
+            ("location".to_string(), "/".to_string())
+        );
+
+        asset_router
+            .delete_assets(vec![], vec![old_url_redirect_config()])
+            .unwrap();
+        let response = asset_router
+            .serve_asset(&data_certificate(), &request)
+            .unwrap();
+        assert_ne!(response.status_code(), StatusCode::MOVED_PERMANENTLY);
+    }
+
     #[fixture]
     fn old_url_redirect_config() -> AssetConfig {
         AssetConfig::Redirect {
             from: "/old-url".to_string(),
             to: "/".to_string(),
             kind: AssetRedirectKind::Permanent,
+            append_query: false,
             headers: vec![(
                 "content-type".to_string(),
                 "text/plain; charset=utf-8".to_string(),
@@ -3936,6 +10829,7 @@ mod tests {
             from: "/css/app.css".to_string(),
             to: "/css/app-ba74b708.css".to_string(),
             kind: AssetRedirectKind::Temporary,
+            append_query: false,
             headers: vec![(
                 "content-type".to_string(),
                 "text/plain; charset=utf-8".to_string(),
@@ -3943,6 +10837,64 @@ mod tests {
         }
     }
 
+    fn error_page_assets() -> Vec<Asset<'static, 'static>> {
+        [
+            "index.html",
+            "app.html",
+            "404.html",
+            "403.html",
+            "admin-403.html",
+            "500.html",
+        ]
+        .into_iter()
+        .map(|path| Asset::new(path, path.as_bytes()))
+        .collect()
+    }
+
+    fn error_page_configs() -> Vec<AssetConfig> {
+        let error_page_config = |path: &str, status_code, scope: &str| AssetConfig::ErrorPage {
+            path: path.to_string(),
+            status_code,
+            scopes: vec![scope.to_string()],
+            content_type: Some("text/html".to_string()),
+            headers: vec![],
+            encodings: vec![],
+        };
+
+        vec![
+            error_page_config("404.html", StatusCode::NOT_FOUND, "/"),
+            error_page_config("403.html", StatusCode::FORBIDDEN, "/"),
+            error_page_config("admin-403.html", StatusCode::FORBIDDEN, "/admin"),
+            error_page_config("500.html", StatusCode::INTERNAL_SERVER_ERROR, "/"),
+            fallback_config("app.html", "/app", 0),
+        ]
+    }
+
+    fn error_page_asset_router() -> AssetRouter<'static> {
+        let mut asset_router = AssetRouter::default();
+        asset_router
+            .certify_assets(error_page_assets(), error_page_configs())
+            .unwrap();
+
+        asset_router
+    }
+
+    fn fallback_config(path: &str, scope: &str, priority: i32) -> AssetConfig {
+        AssetConfig::File {
+            path: path.to_string(),
+            content_type: Some("text/html".to_string()),
+            headers: vec![],
+            fallback_for: vec![AssetFallbackConfig {
+                scope: scope.to_string(),
+                status_code: None,
+                priority,
+            }],
+            aliased_by: vec![],
+            encodings: vec![],
+            image_variants: vec![],
+        }
+    }
+
     fn long_asset_config(path: &str) -> AssetConfig {
         AssetConfig::File {
             path: path.to_string(),
@@ -3958,14 +10910,22 @@ mod tests {
                 AssetEncoding::Deflate.default_config(),
                 AssetEncoding::Gzip.default_config(),
             ],
+            image_variants: vec![],
         }
     }
 
     #[fixture]
     fn asset_router<'a>() -> AssetRouter<'a> {
         let mut asset_router = AssetRouter::default();
+        asset_router
+            .certify_assets(asset_router_assets(), asset_router_configs())
+            .unwrap();
 
-        let assets = vec![
+        asset_router
+    }
+
+    fn asset_router_assets<'a>() -> Vec<Asset<'a, 'a>> {
+        vec![
             Asset::new("index.html", index_html_body()),
             Asset::new("index.html.gz", index_html_gz_body()),
             Asset::new("index.html.zz", index_html_zz_body()),
@@ -3979,20 +10939,18 @@ mod tests {
             Asset::new("not-found.html.gz", not_found_html_gz_body()),
             Asset::new("not-found.html.zz", not_found_html_zz_body()),
             Asset::new("not-found.html.br", not_found_html_br_body()),
-        ];
+        ]
+    }
 
-        let asset_configs = vec![
+    fn asset_router_configs() -> Vec<AssetConfig> {
+        vec![
             index_html_config(),
             js_config(),
             css_config(),
             not_found_html_config(),
             old_url_redirect_config(),
             css_redirect_config(),
-        ];
-
-        asset_router.certify_assets(assets, asset_configs).unwrap();
-
-        asset_router
+        ]
     }
 
     fn long_asset(name: String) -> Asset<'static, 'static> {
@@ -4065,6 +11023,29 @@ mod tests {
             .build()
     }
 
+    fn assert_same_responses<'a>(
+        asset_router: &AssetRouter<'a>,
+        expected_asset_router: &AssetRouter<'a>,
+    ) {
+        fn responses<'a>(
+            responses: &HashMap<RequestKey, CertifiedAssetResponse<'a>>,
+        ) -> HashMap<RequestKey, HttpResponse<'a>> {
+            responses
+                .iter()
+                .map(|(key, response)| (key.clone(), response.response.to_http_response()))
+                .collect()
+        }
+
+        assert_eq!(
+            responses(&asset_router.responses),
+            responses(&expected_asset_router.responses)
+        );
+        assert_eq!(
+            responses(&asset_router.fallback_responses),
+            responses(&expected_asset_router.fallback_responses)
+        );
+    }
+
     fn extract_witness_expr_path(response: &HttpResponse) -> (HashTree, Vec<String>) {
         let (_, certificate_header_str) = response
             .headers()
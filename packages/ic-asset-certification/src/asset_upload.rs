@@ -0,0 +1,224 @@
+use crate::{AssetConfig, ContentHashes, ASSET_CHUNK_SIZE};
+use ic_http_certification::Hash;
+use sha2::{Digest, Sha256};
+use std::{cmp, fmt, ops::Range};
+
+/// An asset that is uploaded chunk by chunk, see
+/// [begin_asset](crate::AssetRouter::begin_asset).
+///
+/// The content is hashed while the chunks arrive, both in full and in ranges of
+/// [ASSET_CHUNK_SIZE] bytes, so the hashes don't have to be calculated when the
+/// upload is committed.
+pub(crate) struct AssetUpload {
+    pub(crate) path: String,
+    pub(crate) asset_configs: Vec<AssetConfig>,
+    chunks: Vec<Vec<u8>>,
+    len: usize,
+    hasher: Sha256,
+    range_hasher: Sha256,
+    /// The number of bytes that were hashed by `range_hasher`.
+    range_len: usize,
+    range_hashes: Vec<Hash>,
+}
+
+impl AssetUpload {
+    pub(crate) fn new(path: String, asset_configs: Vec<AssetConfig>) -> Self {
+        Self {
+            path,
+            asset_configs,
+            chunks: vec![],
+            len: 0,
+            hasher: Sha256::new(),
+            range_hasher: Sha256::new(),
+            range_len: 0,
+            range_hashes: vec![],
+        }
+    }
+
+    /// The number of bytes that were uploaded so far.
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn append_chunk(&mut self, chunk: Vec<u8>) {
+        self.hasher.update(&chunk);
+
+        let mut remaining = chunk.as_slice();
+        while !remaining.is_empty() {
+            let (range_chunk, rest) =
+                remaining.split_at(cmp::min(ASSET_CHUNK_SIZE - self.range_len, remaining.len()));
+            self.range_hasher.update(range_chunk);
+            self.range_len += range_chunk.len();
+            self.len += range_chunk.len();
+            remaining = rest;
+
+            if self.range_len == ASSET_CHUNK_SIZE {
+                self.range_hashes
+                    .push(self.range_hasher.finalize_reset().into());
+                self.range_len = 0;
+            }
+        }
+
+        if !chunk.is_empty() {
+            self.chunks.push(chunk);
+        }
+    }
+
+    pub(crate) fn finish(mut self) -> ChunkedContent {
+        if self.range_len > 0 {
+            self.range_hashes.push(self.range_hasher.finalize().into());
+        }
+
+        let mut offsets = Vec::with_capacity(self.chunks.len());
+        let mut offset = 0;
+        for chunk in self.chunks.iter() {
+            offsets.push(offset);
+            offset += chunk.len();
+        }
+
+        ChunkedContent {
+            chunks: self.chunks,
+            offsets,
+            hashes: ContentHashes {
+                len: self.len,
+                hash: self.hasher.finalize().into(),
+                range_hashes: self.range_hashes,
+            },
+        }
+    }
+}
+
+impl fmt::Debug for AssetUpload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AssetUpload")
+            .field("path", &self.path)
+            .field("asset_configs", &self.asset_configs)
+            .field("chunk_count", &self.chunks.len())
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+/// The content of an [AssetUpload], stored in the chunks it was uploaded in,
+/// along with the hashes that were calculated during the upload.
+pub(crate) struct ChunkedContent {
+    chunks: Vec<Vec<u8>>,
+    /// The offset of each chunk in the content.
+    offsets: Vec<usize>,
+    hashes: ContentHashes,
+}
+
+impl ChunkedContent {
+    pub(crate) fn len(&self) -> usize {
+        self.hashes.len
+    }
+
+    /// Copies the provided range of the content into `buf`.
+    pub(crate) fn read(&self, range: Range<usize>, buf: &mut Vec<u8>) {
+        // the index of the last chunk that starts before or at the start of the range
+        let first_chunk = self
+            .offsets
+            .partition_point(|offset| *offset <= range.start)
+            .saturating_sub(1);
+
+        for (chunk, offset) in self
+            .chunks
+            .iter()
+            .zip(self.offsets.iter())
+            .skip(first_chunk)
+        {
+            if *offset >= range.end {
+                break;
+            }
+
+            let chunk_start = range.start.saturating_sub(*offset);
+            let chunk_end = cmp::min(range.end - offset, chunk.len());
+            buf.extend_from_slice(&chunk[chunk_start..chunk_end]);
+        }
+    }
+
+    /// Returns the hash of the provided range of the content if it was calculated during
+    /// the upload, i.e. for the full content and for ranges of [ASSET_CHUNK_SIZE] bytes.
+    pub(crate) fn precomputed_hash(&self, range: &Range<usize>) -> Option<Hash> {
+        self.hashes.get(range)
+    }
+}
+
+impl fmt::Debug for ChunkedContent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChunkedContent")
+            .field("chunk_count", &self.chunks.len())
+            .field("len", &self.hashes.len)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    fn upload(chunk_lens: &[usize]) -> (Vec<u8>, ChunkedContent) {
+        let mut content = vec![];
+        let mut upload = AssetUpload::new("app.js".to_string(), vec![]);
+        for chunk_len in chunk_lens {
+            let chunk = (0..*chunk_len)
+                .map(|i| ((content.len() + i) % 251) as u8)
+                .collect::<Vec<_>>();
+            content.extend_from_slice(&chunk);
+            upload.append_chunk(chunk);
+        }
+
+        (content, upload.finish())
+    }
+
+    fn sha256(content: &[u8]) -> Hash {
+        Sha256::digest(content).into()
+    }
+
+    #[rstest]
+    #[case(&[])]
+    #[case(&[0])]
+    #[case(&[3, 0, 5])]
+    #[case(&[ASSET_CHUNK_SIZE])]
+    #[case(&[ASSET_CHUNK_SIZE - 1, 2])]
+    #[case(&[1_500_000, 1_500_000, 1_500_000])]
+    #[case(&[3 * ASSET_CHUNK_SIZE + 7])]
+    fn chunked_content_hashes(#[case] chunk_lens: &[usize]) {
+        let (content, chunked_content) = upload(chunk_lens);
+
+        assert_eq!(chunked_content.len(), content.len());
+        assert_eq!(
+            chunked_content.precomputed_hash(&(0..content.len())),
+            Some(sha256(&content))
+        );
+
+        for range_start in (0..content.len()).step_by(ASSET_CHUNK_SIZE) {
+            let range = range_start..cmp::min(range_start + ASSET_CHUNK_SIZE, content.len());
+            assert_eq!(
+                chunked_content.precomputed_hash(&range),
+                Some(sha256(&content[range]))
+            );
+        }
+    }
+
+    #[rstest]
+    #[case(0..0)]
+    #[case(0..10)]
+    #[case(2..3)]
+    #[case(3..8)]
+    #[case(4..15)]
+    #[case(9..10)]
+    fn chunked_content_read(#[case] range: Range<usize>) {
+        let (content, chunked_content) = upload(&[3, 5, 0, 1, 1]);
+
+        let mut buf = vec![];
+        chunked_content.read(range.clone(), &mut buf);
+
+        assert_eq!(
+            buf,
+            content[range.start..cmp::min(range.end, content.len())]
+        );
+        assert_eq!(chunked_content.precomputed_hash(&(2..3)), None);
+    }
+}
@@ -0,0 +1,89 @@
+use crate::AssetEncoding;
+
+/// A report of the assets that were certified by
+/// [certify_assets](crate::AssetRouter::certify_assets).
+///
+/// The report can be used to detect misconfigurations, such as glob patterns that
+/// don't match any asset, which would otherwise result in assets being certified
+/// without the intended headers.
+///
+/// # Examples
+///
+/// ```
+/// use ic_asset_certification::{Asset, AssetConfig, AssetRouter};
+///
+/// let mut asset_router = AssetRouter::default();
+/// let report = asset_router
+///     .certify_assets(
+///         vec![Asset::new("app.js", b"console.log('Hello World!');".as_slice())],
+///         vec![AssetConfig::Pattern {
+///             pattern: "**/*.jsx".to_string(),
+///             exclude: vec![],
+///             exclude_dotfiles: false,
+///             content_type: Some("text/javascript".to_string()),
+///             headers: vec![],
+///             encodings: vec![],
+///             image_variants: vec![],
+///         }],
+///     )
+///     .unwrap();
+///
+/// assert_eq!(report.unconfigured_paths, vec!["app.js"]);
+/// assert_eq!(report.unmatched_configs, vec!["**/*.jsx"]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CertificationReport {
+    /// The paths of all assets that were certified, ordered by path. This includes
+    /// alternative encodings and assets that are recorded for certification on demand,
+    /// see [with_lazy_certification](crate::AssetRouter::with_lazy_certification).
+    pub certified_paths: Vec<String>,
+
+    /// The paths of assets that did not match any configuration, ordered by path.
+    /// These assets are certified as-is, without any headers. Alternative encodings
+    /// and image formats of configured assets are not included.
+    pub unconfigured_paths: Vec<String>,
+
+    /// The paths of assets that were not certified because they are excluded by the
+    /// configuration they match, ordered by path, see
+    /// [exclude](crate::AssetConfig::Pattern::exclude).
+    pub excluded_paths: Vec<String>,
+
+    /// The paths and glob patterns of configurations that were not applied to any
+    /// asset, in the order they were provided. A configuration is not applied if no asset
+    /// matches it, or if all matching assets match an earlier configuration.
+    /// Redirects are not included.
+    pub unmatched_configs: Vec<String>,
+
+    /// Alternative encodings that are configured for an asset, but were not provided,
+    /// ordered by path.
+    pub missing_encodings: Vec<MissingEncoding>,
+
+    /// URLs that are served by more than one asset, either in the same call or by an
+    /// asset that was certified before, ordered by URL. The asset that is certified last
+    /// is served on the URL.
+    pub alias_collisions: Vec<AliasCollision>,
+}
+
+/// An alternative encoding that is configured for an asset but was not provided,
+/// see [CertificationReport::missing_encodings].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingEncoding {
+    /// The path of the unencoded asset.
+    pub path: String,
+
+    /// The encoding that was not provided.
+    pub encoding: AssetEncoding,
+
+    /// The path that the encoded asset was expected at, e.g. `index.html.gz`.
+    pub expected_path: String,
+}
+
+/// A URL that is served by more than one asset, see [CertificationReport::alias_collisions].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AliasCollision {
+    /// The URL that is served by more than one asset.
+    pub url: String,
+
+    /// The paths of the assets that are served on the URL, ordered by path.
+    pub asset_paths: Vec<String>,
+}
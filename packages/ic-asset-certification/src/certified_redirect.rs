@@ -0,0 +1,53 @@
+use crate::AssetRedirectKind;
+use ic_http_certification::StatusCode;
+
+/// A redirect that is currently certified by the [AssetRouter](crate::AssetRouter), see
+/// [get_redirects](crate::AssetRouter::get_redirects).
+///
+/// # Examples
+///
+/// ```
+/// use ic_asset_certification::{AssetConfig, AssetRedirectKind, AssetRouter};
+/// use ic_http_certification::StatusCode;
+///
+/// let mut asset_router = AssetRouter::default();
+/// asset_router
+///     .certify_assets(
+///         vec![],
+///         vec![AssetConfig::Redirect {
+///             from: "/old".to_string(),
+///             to: "/new".to_string(),
+///             kind: AssetRedirectKind::Permanent,
+///             append_query: false,
+///             headers: vec![],
+///         }],
+///     )
+///     .unwrap();
+///
+/// let redirects = asset_router.get_redirects();
+/// assert_eq!(redirects[0].from, "/old");
+/// assert_eq!(redirects[0].to, "/new");
+/// assert_eq!(redirects[0].status_code, StatusCode::MOVED_PERMANENTLY);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CertifiedRedirect {
+    /// The path that is redirected.
+    pub from: String,
+
+    /// The location that is redirected to.
+    pub to: String,
+
+    /// The kind of the redirect.
+    pub kind: AssetRedirectKind,
+
+    /// The status code of the redirect response.
+    pub status_code: StatusCode,
+
+    /// Whether the query string of the request is appended to the `Location` header, which is then
+    /// excluded from certification, see
+    /// [append_query](crate::AssetConfig::Redirect::append_query).
+    pub append_query: bool,
+
+    /// The name of the [Namespace](crate::AssetConfig::Namespace) that the redirect belongs to.
+    pub namespace: Option<String>,
+}
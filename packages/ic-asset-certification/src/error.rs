@@ -1,3 +1,6 @@
+use crate::AssetEncoding;
+use ic_http_certification::StatusCode;
+
 /// Asset certification result type.
 pub type AssetCertificationResult<T = ()> = Result<T, AssetCertificationError>;
 
@@ -11,6 +14,111 @@ pub enum AssetCertificationError {
         request_url: String,
     },
 
+    /// Thrown when a suitable error page cannot be found for a given request url and status code.
+    #[error(r#"No error page with status code {status_code} was found matching the current request url: {request_url}"#)]
+    NoErrorPageMatchingRequestUrl {
+        /// The request url that was not matched to any error page.
+        request_url: String,
+
+        /// The status code of the requested error page.
+        status_code: StatusCode,
+    },
+
+    /// Thrown when a request url contains a query string and the router is configured to
+    /// reject query strings, see [with_strict_query_strings](crate::AssetRouter::with_strict_query_strings).
+    #[error(
+        r#"The query string "{query}" is not allowed for the current request url: {request_url}"#
+    )]
+    QueryStringNotAllowed {
+        /// The request url that contained the query string.
+        request_url: String,
+
+        /// The query string that is not allowed.
+        query: String,
+    },
+
+    /// Thrown when the `Accept-Encoding` header of a request excludes the unencoded asset, using
+    /// `identity;q=0` or `*;q=0`, and none of the accepted encodings is certified for the asset.
+    /// Canisters can respond with a `406 Not Acceptable` status code in this case.
+    #[error(r#"No acceptable encoding was found for the current request url: {request_url}"#)]
+    NoAcceptableEncoding {
+        /// The request url that no acceptable encoding was found for.
+        request_url: String,
+    },
+
+    /// Thrown when the asset matching a request url has not been certified yet, see
+    /// [with_lazy_certification](crate::AssetRouter::with_lazy_certification).
+    #[error(
+        r#"The asset matching the current request url has not been certified yet: {request_url}"#
+    )]
+    AssetCertificationPending {
+        /// The request url that matched an asset that has not been certified yet.
+        request_url: String,
+    },
+
+    /// Thrown when a [Pattern](crate::AssetConfig::Pattern) configuration does not match any of the
+    /// provided assets and the router is configured to validate configurations, see
+    /// [with_strict_asset_configs](crate::AssetRouter::with_strict_asset_configs).
+    #[error(r#"No asset was found matching the configured pattern: {pattern}"#)]
+    NoAssetMatchingPattern {
+        /// The glob pattern that did not match any asset.
+        pattern: String,
+    },
+
+    /// Thrown when an alternative encoding is configured for an asset, but the encoded asset
+    /// was not provided and the router is configured to validate configurations, see
+    /// [with_strict_asset_configs](crate::AssetRouter::with_strict_asset_configs).
+    #[error(r#"The {encoding} encoding of the asset {asset_path} was not found, expected an asset at: {encoded_asset_path}"#)]
+    EncodedAssetNotFound {
+        /// The path of the unencoded asset.
+        asset_path: String,
+
+        /// The encoding that was not provided.
+        encoding: AssetEncoding,
+
+        /// The path that the encoded asset was expected at.
+        encoded_asset_path: String,
+    },
+
+    /// Thrown when the content of an asset exceeds the maximum size of a single asset, see
+    /// [with_max_asset_size](crate::AssetRouter::with_max_asset_size).
+    #[error(r#"The asset {asset_path} is {size} bytes, which exceeds the maximum asset size of {max_size} bytes"#)]
+    AssetTooLarge {
+        /// The path of the asset that is too large.
+        asset_path: String,
+
+        /// The size of the asset in bytes.
+        size: usize,
+
+        /// The maximum size of a single asset in bytes.
+        max_size: usize,
+    },
+
+    /// Thrown when certifying assets would exceed the maximum total size of all certified assets, see
+    /// [with_max_total_asset_size](crate::AssetRouter::with_max_total_asset_size).
+    #[error(r#"Certifying the assets would increase the total size of all certified assets to {total_size} bytes, which exceeds the maximum of {max_total_size} bytes"#)]
+    TotalAssetSizeExceeded {
+        /// The total size of all certified assets in bytes, if the assets were certified.
+        total_size: usize,
+
+        /// The maximum total size of all certified assets in bytes.
+        max_total_size: usize,
+    },
+
+    /// Thrown when a chunk is appended to, or a commit is requested for, an asset upload that
+    /// was not begun, see [begin_asset](crate::AssetRouter::begin_asset).
+    #[error(r#"No asset upload is in progress"#)]
+    NoAssetUploadInProgress,
+
+    /// Thrown when the bytes passed to [from_bytes](crate::PrecertifiedAssets::from_bytes) are not a
+    /// valid encoding of [PrecertifiedAssets](crate::PrecertifiedAssets), e.g. because they were
+    /// produced by an incompatible version of this crate.
+    #[error(r#"Invalid precertified assets: {reason}"#)]
+    InvalidPrecertifiedAssets {
+        /// The reason why the bytes are invalid.
+        reason: String,
+    },
+
     /// Thrown when the asset certification process fails.
     #[error(r#"HTTP Certification Error: "{0}""#)]
     HttpCertificationError(#[from] ic_http_certification::HttpCertificationError),
@@ -0,0 +1,209 @@
+use std::collections::BTreeMap;
+
+/// The minimum number of hexadecimal characters that a filename segment needs to
+/// be recognized as a content hash, see [HashedAssetManifest].
+pub const MIN_CONTENT_HASH_LEN: usize = 8;
+
+/// Configuration for the stable aliases of assets with content-hash filenames, see
+/// [certify_hashed_assets](crate::AssetRouter::certify_hashed_assets).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HashedAssetAliasConfig {
+    /// The path that the JSON manifest mapping stable paths to hashed paths is served on,
+    /// e.g. `asset-manifest.json`.
+    pub manifest_path: String,
+
+    /// Headers that are added to the responses of the stable aliases and the manifest,
+    /// instead of the headers configured for the hashed assets.
+    ///
+    /// Since the content served on a stable alias changes with every new version of the
+    /// asset, these headers should not allow responses to be cached indefinitely, e.g.
+    /// `cache-control: public, no-cache`.
+    pub headers: Vec<(String, String)>,
+}
+
+/// A mapping from stable asset paths to the paths of assets with content-hash filenames.
+///
+/// A filename contains a content hash if the last `-` separated segment before the file
+/// extension consists of at least [MIN_CONTENT_HASH_LEN] hexadecimal characters, e.g.
+/// `app-488df671.js` is mapped to the stable path `app.js`. Alternative encodings of a
+/// hashed asset that are provided as well, e.g. `app-488df671.js.gz`, are not listed
+/// separately.
+///
+/// # Examples
+///
+/// ```
+/// use ic_asset_certification::HashedAssetManifest;
+///
+/// let manifest = HashedAssetManifest::from_asset_paths([
+///     "index.html",
+///     "assets/app-488df671.js",
+///     "assets/app-488df671.js.gz",
+///     "assets/vendor-9f86d081.css",
+/// ]);
+///
+/// assert_eq!(manifest.get("assets/app.js"), Some("assets/app-488df671.js"));
+/// assert_eq!(manifest.get("index.html"), None);
+/// assert_eq!(
+///     manifest.to_json(),
+///     r#"{"assets/app.js":"assets/app-488df671.js","assets/vendor.css":"assets/vendor-9f86d081.css"}"#
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HashedAssetManifest {
+    aliases: BTreeMap<String, String>,
+}
+
+impl HashedAssetManifest {
+    /// Creates a manifest from the provided asset paths, ignoring paths without a content hash.
+    ///
+    /// If multiple paths map to the same stable path, the last one is used.
+    pub fn from_asset_paths<'path>(asset_paths: impl IntoIterator<Item = &'path str>) -> Self {
+        let hashed_paths = asset_paths
+            .into_iter()
+            .filter_map(|asset_path| {
+                stable_asset_path(asset_path).map(|stable_path| (asset_path, stable_path))
+            })
+            .collect::<Vec<_>>();
+
+        let mut aliases = BTreeMap::new();
+        for (asset_path, stable_path) in hashed_paths.iter() {
+            let is_encoding = hashed_paths.iter().any(|(other_asset_path, _)| {
+                asset_path.len() > other_asset_path.len()
+                    && asset_path.starts_with(other_asset_path)
+                    && asset_path.as_bytes()[other_asset_path.len()] == b'.'
+            });
+            if !is_encoding {
+                aliases.insert(stable_path.clone(), asset_path.to_string());
+            }
+        }
+
+        Self { aliases }
+    }
+
+    /// Returns the hashed path for the provided stable path.
+    pub fn get(&self, stable_path: &str) -> Option<&str> {
+        self.aliases.get(stable_path).map(String::as_str)
+    }
+
+    /// Returns an iterator over the stable paths and their hashed paths, ordered by stable path.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.aliases
+            .iter()
+            .map(|(stable_path, hashed_path)| (stable_path.as_str(), hashed_path.as_str()))
+    }
+
+    /// Returns the number of stable paths in the manifest.
+    pub fn len(&self) -> usize {
+        self.aliases.len()
+    }
+
+    /// Returns `true` if the manifest does not contain any stable paths.
+    pub fn is_empty(&self) -> bool {
+        self.aliases.is_empty()
+    }
+
+    /// Serializes the manifest to a JSON object mapping stable paths to hashed paths.
+    pub fn to_json(&self) -> String {
+        let entries = self
+            .iter()
+            .map(|(stable_path, hashed_path)| {
+                format!("{}:{}", json_string(stable_path), json_string(hashed_path))
+            })
+            .collect::<Vec<_>>();
+
+        format!("{{{}}}", entries.join(","))
+    }
+}
+
+/// Strips the content hash from the filename of the provided path, or returns [None]
+/// if the filename does not contain a content hash.
+fn stable_asset_path(asset_path: &str) -> Option<String> {
+    let file_name_start = asset_path.rfind('/').map_or(0, |i| i + 1);
+    let file_name = &asset_path[file_name_start..];
+
+    let hash_start = file_name.rfind('-').filter(|i| *i > 0)? + 1;
+    let hash_end = file_name[hash_start..]
+        .find('.')
+        .map_or(file_name.len(), |i| hash_start + i);
+    let hash = &file_name[hash_start..hash_end];
+    if hash.len() < MIN_CONTENT_HASH_LEN || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    Some(format!(
+        "{}{}{}",
+        &asset_path[..file_name_start],
+        &file_name[..hash_start - 1],
+        &file_name[hash_end..]
+    ))
+}
+
+fn json_string(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case("app-488df671.js", Some("app.js"))]
+    #[case("assets/app-488df671.js", Some("assets/app.js"))]
+    #[case("assets/app-488df671.js.gz", Some("assets/app.js.gz"))]
+    #[case("my-app-488DF671.min.js", Some("my-app.min.js"))]
+    #[case("app-488df671", Some("app"))]
+    #[case("app-488df6.js", None)]
+    #[case("app-488dfz71.js", None)]
+    #[case("my-component.js", None)]
+    #[case("app.js", None)]
+    #[case("app-488df671/index.js", None)]
+    #[case("-488df671.js", None)]
+    fn stable_asset_path_strips_content_hash(
+        #[case] asset_path: &str,
+        #[case] expected: Option<&str>,
+    ) {
+        assert_eq!(stable_asset_path(asset_path).as_deref(), expected);
+    }
+
+    #[rstest]
+    fn manifest_skips_encodings() {
+        let manifest = HashedAssetManifest::from_asset_paths([
+            "app-488df671.js.br",
+            "app-488df671.js",
+            "app-488df671.js.gz",
+            "styles-9f86d081.css.gz",
+        ]);
+
+        assert_eq!(
+            manifest.iter().collect::<Vec<_>>(),
+            vec![
+                ("app.js", "app-488df671.js"),
+                ("styles.css.gz", "styles-9f86d081.css.gz"),
+            ]
+        );
+    }
+
+    #[rstest]
+    fn manifest_to_json_escapes_paths() {
+        let manifest = HashedAssetManifest::from_asset_paths(["a\"b\\c-488df671.js"]);
+
+        assert_eq!(
+            manifest.to_json(),
+            r#"{"a\"b\\c.js":"a\"b\\c-488df671.js"}"#
+        );
+        assert_eq!(HashedAssetManifest::default().to_json(), "{}");
+    }
+}
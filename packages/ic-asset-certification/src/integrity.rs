@@ -0,0 +1,26 @@
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use ic_http_certification::Hash;
+
+/// Calculates the [Subresource Integrity](https://developer.mozilla.org/en-US/docs/Web/Security/Subresource_Integrity)
+/// digest from the SHA-256 hash of some content, in the `sha256-<base64>` format that is used by the
+/// `integrity` attribute.
+pub(crate) fn subresource_integrity(content_hash: Hash) -> String {
+    format!("sha256-{}", BASE64.encode(content_hash))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ic_representation_independent_hash::hash;
+    use rstest::*;
+
+    #[rstest]
+    #[case(b"", "sha256-47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=")]
+    #[case(
+        b"alert('Hello, world.');",
+        "sha256-qznLcsROx4GACP2dm0UCKCzCG+HiZ1guq6ZZDob/Tng="
+    )]
+    fn subresource_integrity_digest(#[case] content: &[u8], #[case] expected: &str) {
+        assert_eq!(subresource_integrity(hash(content)), expected);
+    }
+}
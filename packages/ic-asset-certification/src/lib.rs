@@ -71,6 +71,43 @@
 //! );
 //! ```
 //!
+//! Large assets that are kept in stable memory don't need to be copied into the heap
+//! to be certified. Instead, the content can be read on demand by implementing the
+//! [AssetReader] trait and creating the asset with [Asset::from_reader]. The content
+//! is then hashed one chunk at a time during certification and only the requested
+//! chunk is read when a response is served. If the hash of the content is stored along
+//! with it, the reader can return it from `sha256`, so that [sync_assets](AssetRouter::sync_assets)
+//! does not read the content again to find out whether it changed.
+//!
+//! ```rust
+//! use ic_asset_certification::{Asset, AssetReader};
+//!
+//! struct StableFile {
+//!     offset: usize,
+//!     len: usize,
+//! }
+//!
+//! impl AssetReader for StableFile {
+//!     fn len(&self) -> usize {
+//!         self.len
+//!     }
+//!
+//!     fn read(&self, offset: usize, buf: &mut [u8]) {
+//!         // this should normally be read using `ic_cdk::api::stable::stable_read`.
+//!         let _stable_offset = self.offset + offset;
+//!         buf.fill(0);
+//!     }
+//! }
+//!
+//! let asset = Asset::from_reader(
+//!     "assets/video.mp4",
+//!     StableFile {
+//!         offset: 0,
+//!         len: 1_000_000_000,
+//!     },
+//! );
+//! ```
+//!
 //! ## Configuring asset certification
 //!
 //! [AssetConfig] defines the configuration for any files that will be certified.
@@ -125,6 +162,8 @@
 //!         - Identity
 //!     - The asset router will return the highest priority encoding that has been
 //!       certified and is supported by the client.
+//!     - A certified `Vary: accept-encoding` header is added to all responses for
+//!       an asset with encodings, unless a `Vary` header is already configured.
 //!
 //! ### Configuring individual files
 //!
@@ -157,8 +196,10 @@
 //! - `/`
 //!
 //! If multiple fallback assets are configured, the first one found will be used,
-//! since that will be the most specific one available for that path. If no asset is
-//! found with any of these fallback scopes, no response will be returned.
+//! since that will be the most specific one available for that path. If multiple
+//! assets are configured as a fallback for the same scope, the one with the highest
+//! [priority](AssetFallbackConfig::priority) is used. If no asset is found with any
+//! of these fallback scopes, no response will be returned.
 //!
 //! It's also possible to register aliases for an asset. This can be useful for
 //! configuring multiple paths that should serve the same asset. For example, if an
@@ -182,12 +223,14 @@
 //!     fallback_for: vec![AssetFallbackConfig {
 //!         scope: "/".to_string(),
 //!         status_code: Some(StatusCode::OK),
+//!         priority: 0,
 //!     }],
 //!     aliased_by: vec!["/".to_string()],
 //!     encodings: vec![
 //!         AssetEncoding::Brotli.default_config(),
 //!         AssetEncoding::Gzip.default_config(),
 //!     ],
+//!     image_variants: vec![],
 //! };
 //! ```
 //!
@@ -223,10 +266,12 @@
 //!         AssetFallbackConfig {
 //!             scope: "/css".to_string(),
 //!             status_code: Some(StatusCode::NOT_FOUND),
+//!             priority: 0,
 //!         },
 //!         AssetFallbackConfig {
 //!             scope: "/js".to_string(),
 //!             status_code: Some(StatusCode::NOT_FOUND),
+//!             priority: 0,
 //!         },
 //!     ],
 //!     aliased_by: vec![
@@ -241,9 +286,52 @@
 //!         AssetEncoding::Brotli.default_config(),
 //!         AssetEncoding::Gzip.default_config(),
 //!     ],
+//!     image_variants: vec![],
 //! };
 //! ```
 //!
+//! A fallback scope can also be a glob pattern, to serve the same entry point for multiple
+//! independent scopes. The following example serves `settings.html` for requests such as
+//! `/app/123/settings/profile`. Since response verification only supports path prefixes, the
+//! fallback is certified for the prefix of the pattern, `/app/`, so fallbacks with a less
+//! specific scope are no longer served for other paths within that prefix.
+//!
+//! ```rust
+//! use ic_http_certification::StatusCode;
+//! use ic_asset_certification::{AssetConfig, AssetFallbackConfig};
+//!
+//! let config = AssetConfig::File {
+//!     path: "settings.html".to_string(),
+//!     content_type: Some("text/html".to_string()),
+//!     headers: vec![],
+//!     fallback_for: vec![AssetFallbackConfig {
+//!         scope: "/app/*/settings".to_string(),
+//!         status_code: Some(StatusCode::OK),
+//!         priority: 0,
+//!     }],
+//!     aliased_by: vec![],
+//!     encodings: vec![],
+//!     image_variants: vec![],
+//! };
+//! ```
+//!
+//! Trailing-slash variants of aliases don't need to be listed manually if the
+//! [with_trailing_slash_aliases](AssetRouter::with_trailing_slash_aliases) option of the [AssetRouter] is enabled. With this option, the
+//! configuration above only needs to list the `/404`, `/404.html` and `/not-found/index.html`
+//! aliases. Paths without a file extension are then also served with a trailing slash, paths with
+//! a trailing slash are also served without it and `index.html` assets are also served for their
+//! directory, e.g. `/not-found/` and `/not-found`.
+//!
+//! To only alias `index.html` assets by their directory, the [with_directory_index_aliases](AssetRouter::with_directory_index_aliases)
+//! option can be used instead.
+//!
+//! ```rust
+//! use ic_asset_certification::AssetRouter;
+//!
+//! let mut asset_router = AssetRouter::default().with_trailing_slash_aliases();
+//! let mut asset_router = AssetRouter::default().with_directory_index_aliases();
+//! ```
+//!
 //! ### Configuring file patterns
 //!
 //! When configuring file patterns, the `pattern` property is provided. This
@@ -281,6 +369,8 @@
 //!
 //! let config = AssetConfig::Pattern {
 //!     pattern: "js/*.js".to_string(),
+//!     exclude: vec![],
+//!     exclude_dotfiles: false,
 //!     content_type: Some("application/javascript".to_string()),
 //!     headers: vec![
 //!         ("Cache-Control".to_string(), "public, max-age=31536000, immutable".to_string()),
@@ -289,6 +379,33 @@
 //!         AssetEncoding::Brotli.default_config(),
 //!         AssetEncoding::Gzip.default_config(),
 //!     ],
+//!     image_variants: vec![],
+//! };
+//! ```
+//!
+//! The [exclude](AssetConfig::Pattern::exclude) property is a list of glob patterns,
+//! using the same syntax, for files that should never be certified, such as source
+//! maps that are accidentally included in a build. Setting
+//! [exclude_dotfiles](AssetConfig::Pattern::exclude_dotfiles) to `true` also excludes
+//! hidden files and all files in hidden directories, such as `.env` or
+//! `assets/.DS_Store`. Files that match the `pattern` and are excluded are not
+//! certified and are not served, even if they match another configuration, and are
+//! listed in the [excluded_paths](CertificationReport::excluded_paths) of the
+//! certification report:
+//!
+//! ```rust
+//! use ic_asset_certification::AssetConfig;
+//!
+//! let config = AssetConfig::Pattern {
+//!     pattern: "**/*".to_string(),
+//!     exclude: vec!["**/*.map".to_string()],
+//!     exclude_dotfiles: true,
+//!     content_type: None,
+//!     headers: vec![
+//!         ("Cache-Control".to_string(), "public, no-cache".to_string()),
+//!     ],
+//!     encodings: vec![],
+//!     image_variants: vec![],
 //! };
 //! ```
 //!
@@ -300,7 +417,10 @@
 //! `to` path. The [AssetConfig::Redirect] config is not matched against any [Asset]s.
 //!
 //! Redirects can be configured as either [permanent](AssetRedirectKind::Permanent)
-//! or [temporary](AssetRedirectKind::Temporary).
+//! or [temporary](AssetRedirectKind::Temporary). The [Found](AssetRedirectKind::Found) (302)
+//! and [PermanentRedirect](AssetRedirectKind::PermanentRedirect) (308) kinds are also
+//! supported. Unlike 302 redirects, 308 and 307 redirects require the browser to preserve the
+//! request method and body.
 //!
 //! The browser will cache permanent redirects and will not request the old
 //! location again. This is useful when the resource has permanently moved to a new
@@ -319,6 +439,24 @@
 //! [MDN Web Docs](https://developer.mozilla.org/en-US/docs/Web/HTTP/Status/307)
 //! for more information on temporary redirects.
 //!
+//! The query string of the original request can be appended to the `to` path by enabling
+//! [append_query](AssetConfig::Redirect::append_query), e.g. to retain tracking
+//! parameters. The HTTP Gateway then receives a `Location` header such as
+//! `/landing?utm_source=mail` for a request to `/promo?utm_source=mail`.
+//!
+//! **Security warning**: since the `Location` header then depends on the request, it is excluded
+//! from certification. A malicious replica or boundary node can redirect to any URL, including
+//! one on another origin, and the response still passes verification. Only the status code, body
+//! and all other headers of the redirect remain certified, and HTTP Gateways that require certified
+//! redirect targets reject the response. Do not enable this option for redirects whose target is
+//! security sensitive.
+//!
+//! Query strings never affect which asset is served for a request. Query parameters that should not
+//! be preserved, such as cache-busting parameters, can be removed using the
+//! [with_stripped_query_params](AssetRouter::with_stripped_query_params) option of the router.
+//! Requests that still have a query string after removing these parameters can be rejected with the
+//! [with_strict_query_strings](AssetRouter::with_strict_query_strings) option.
+//!
 //! The following example configures a permanent redirect from `/old` to `/new`:
 //!
 //! ```rust
@@ -328,6 +466,7 @@
 //!     from: "/old".to_string(),
 //!     to: "/new".to_string(),
 //!     kind: AssetRedirectKind::Permanent,
+//!     append_query: false,
 //!     headers: vec![(
 //!         "content-type".to_string(),
 //!         "text/plain; charset=utf-8".to_string(),
@@ -335,6 +474,113 @@
 //! };
 //! ```
 //!
+//! ### Configuring error pages
+//!
+//! Certified error pages can be configured for a set of scopes with a specific status code,
+//! using the [ErrorPage](AssetConfig::ErrorPage) configuration. Error pages are not served on their own
+//! path. Error pages with a `404` status code are served by the [serve_asset](AssetRouter::serve_asset) function when a
+//! request does not match any asset or fallback. Error pages with any status code can be served
+//! using the [serve_error](AssetRouter::serve_error) function, for example when the canister rejects a request itself.
+//!
+//! Scopes are resolved in the same way as fallbacks, the error page with the most specific scope
+//! is used. Since response verification only accepts a response for the most specific scope that
+//! is certified for a request, an error page prevents fallbacks and error pages of less specific
+//! scopes from being served within its scope.
+//!
+//! ```rust
+//! use ic_http_certification::{HttpRequest, StatusCode};
+//! use ic_asset_certification::{Asset, AssetConfig, AssetRouter};
+//!
+//! let error_page = |path: &str, status_code, scope: &str| AssetConfig::ErrorPage {
+//!     path: path.to_string(),
+//!     status_code,
+//!     scopes: vec![scope.to_string()],
+//!     content_type: Some("text/html".to_string()),
+//!     headers: vec![],
+//!     encodings: vec![],
+//! };
+//!
+//! let mut asset_router = AssetRouter::default();
+//! asset_router
+//!     .certify_assets(
+//!         vec![
+//!             Asset::new("404.html", b"<h1>Not found</h1>".as_slice()),
+//!             Asset::new("403.html", b"<h1>Forbidden</h1>".as_slice()),
+//!             Asset::new("admin/404.html", b"<h1>Admin page not found</h1>".as_slice()),
+//!             Asset::new("admin/403.html", b"<h1>Admin access required</h1>".as_slice()),
+//!         ],
+//!         vec![
+//!             error_page("404.html", StatusCode::NOT_FOUND, "/"),
+//!             error_page("403.html", StatusCode::FORBIDDEN, "/"),
+//!             error_page("admin/404.html", StatusCode::NOT_FOUND, "/admin"),
+//!             error_page("admin/403.html", StatusCode::FORBIDDEN, "/admin"),
+//!         ],
+//!     )
+//!     .unwrap();
+//!
+//! // this should normally be retrieved using `ic_cdk::api::data_certificate()`.
+//! let data_certificate = vec![1, 2, 3];
+//!
+//! let request = HttpRequest::get("/admin/users").build();
+//! let response = asset_router
+//!     .serve_error(&data_certificate, &request, StatusCode::FORBIDDEN)
+//!     .unwrap();
+//! assert_eq!(response.body(), b"<h1>Admin access required</h1>");
+//!
+//! let request = HttpRequest::get("/missing").build();
+//! let response = asset_router.serve_asset(&data_certificate, &request).unwrap();
+//! assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+//! ```
+//!
+//! ### Configuring single-page applications
+//!
+//! Most single-page applications need the same configuration: the `index.html` file is served
+//! on `/` and as the fallback for all other paths, HTML files are never cached, and JavaScript
+//! and CSS files with content hashes in their file names are cached forever. The
+//! [spa](AssetConfig::spa) function returns this configuration, which can be extended with
+//! additional configurations. Since the first configuration that matches an asset is used,
+//! additional configurations should be placed before the returned configurations.
+//!
+//! ```rust
+//! use ic_asset_certification::AssetConfig;
+//!
+//! let mut asset_configs = vec![AssetConfig::Pattern {
+//!     pattern: "**/*.svg".to_string(),
+//!     exclude: vec![],
+//!     exclude_dotfiles: false,
+//!     content_type: Some("image/svg+xml".to_string()),
+//!     headers: vec![],
+//!     encodings: vec![],
+//!     image_variants: vec![],
+//! }];
+//! asset_configs.extend(AssetConfig::spa("index.html"));
+//! ```
+//!
+//! ### Customizing responses before certification
+//!
+//! Responses can be customized beyond what an [AssetConfig] allows with the
+//! [with_response_hook](AssetRouter::with_response_hook) option of the [AssetRouter]. The hook is run on every response
+//! that is generated from an asset before it is certified, so any headers or status code
+//! changes are certified as well. The hook must modify the responses for an asset in the same
+//! way every time it runs, so that the responses can be generated again when deleting assets.
+//!
+//! ```rust
+//! use ic_asset_certification::{Asset, AssetRouter};
+//!
+//! let mut asset_router = AssetRouter::default().with_response_hook(|asset, response| {
+//!     if asset.url().ends_with(".html") {
+//!         response.add_header((
+//!             "content-security-policy".to_string(),
+//!             "default-src 'self'".to_string(),
+//!         ));
+//!     }
+//! });
+//!
+//! asset_router
+//!     .certify_assets(vec![Asset::new("index.html", b"<h1>Hello</h1>".as_slice())], vec![])
+//!     .unwrap();
+//! ```
+//!
 //! ## Inserting assets into the asset router
 //!
 //! The [AssetRouter] is responsible for certifying responses and routing requests to
@@ -399,15 +645,19 @@
 //!         fallback_for: vec![AssetFallbackConfig {
 //!             scope: "/".to_string(),
 //!             status_code: Some(StatusCode::OK),
+//!             priority: 0,
 //!         }],
 //!         aliased_by: vec!["/".to_string()],
 //!         encodings: vec![
 //!             AssetEncoding::Brotli.default_config(),
 //!             AssetEncoding::Gzip.default_config(),
 //!         ],
+//!         image_variants: vec![],
 //!     },
 //!     AssetConfig::Pattern {
 //!         pattern: "**/*.js".to_string(),
+//!         exclude: vec![],
+//!         exclude_dotfiles: false,
 //!         content_type: Some("text/javascript".to_string()),
 //!         headers: vec![(
 //!             "cache-control".to_string(),
@@ -417,9 +667,12 @@
 //!             AssetEncoding::Brotli.default_config(),
 //!             AssetEncoding::Gzip.default_config(),
 //!         ],
+//!         image_variants: vec![],
 //!     },
 //!     AssetConfig::Pattern {
 //!         pattern: "**/*.css".to_string(),
+//!         exclude: vec![],
+//!         exclude_dotfiles: false,
 //!         content_type: Some("text/css".to_string()),
 //!         headers: vec![(
 //!             "cache-control".to_string(),
@@ -429,11 +682,13 @@
 //!             AssetEncoding::Brotli.default_config(),
 //!             AssetEncoding::Gzip.default_config(),
 //!         ],
+//!         image_variants: vec![],
 //!     },
 //!     AssetConfig::Redirect {
 //!         from: "/old".to_string(),
 //!         to: "/new".to_string(),
 //!         kind: AssetRedirectKind::Permanent,
+//!         append_query: false,
 //!         headers: vec![(
 //!             "content-type".to_string(),
 //!             "text/plain; charset=utf-8".to_string(),
@@ -453,6 +708,106 @@
 //! set_certified_data(&asset_router.root_hash());
 //! ```
 //!
+//! Alternatively, the certified data can be set automatically by configuring the
+//! [on_root_hash_change](AssetRouter::on_root_hash_change) option of the [AssetRouter]. The hook is run with the new root hash
+//! at the end of every operation that changes it:
+//!
+//! ```ignore
+//! use ic_asset_certification::AssetRouter;
+//! use ic_cdk::api::set_certified_data;
+//!
+//! let mut asset_router = AssetRouter::default().on_root_hash_change(|root_hash| {
+//!     set_certified_data(root_hash);
+//! });
+//! ```
+//!
+//! To emit audit logs or update secondary indexes whenever the certified assets change, an
+//! [AssetObserver] can be configured with the [with_asset_observer](AssetRouter::with_asset_observer)
+//! option of the [AssetRouter]. The observer is notified at the end of every operation, for each asset
+//! that was certified or deleted by the operation:
+//!
+//! ```rust
+//! use ic_asset_certification::{AssetObserver, AssetRouter};
+//!
+//! struct AuditLog;
+//!
+//! impl AssetObserver for AuditLog {
+//!     fn on_certified(&mut self, path: &str, encoding: Option<&str>) {
+//!         println!("certified {path} ({encoding:?})");
+//!     }
+//!
+//!     fn on_deleted(&mut self, path: &str) {
+//!         println!("deleted {path}");
+//!     }
+//! }
+//!
+//! let mut asset_router = AssetRouter::default().with_asset_observer(AuditLog);
+//! ```
+//!
+//! [certify_assets](AssetRouter::certify_assets) returns a [CertificationReport] that lists the certified asset paths, the
+//! assets that did not match any configuration, the configurations that were not applied to any asset,
+//! the configured encodings that were not provided and the URLs that are served by more than one asset.
+//! None of these are errors, but they usually point to a mistake in the configuration:
+//!
+//! ```rust
+//! use ic_asset_certification::{Asset, AssetConfig, AssetEncoding, AssetRouter};
+//!
+//! let mut asset_router = AssetRouter::default();
+//!
+//! let report = asset_router
+//!     .certify_assets(
+//!         vec![
+//!             Asset::new("index.html", b"<h1>Hello World!</h1>".as_slice()),
+//!             Asset::new("app.js", b"console.log('Hello World!');".as_slice()),
+//!         ],
+//!         vec![AssetConfig::Pattern {
+//!             pattern: "**/*.js".to_string(),
+//!             exclude: vec![],
+//!             exclude_dotfiles: false,
+//!             content_type: Some("text/javascript".to_string()),
+//!             headers: vec![],
+//!             encodings: vec![AssetEncoding::Gzip.default_config()],
+//!             image_variants: vec![],
+//!         }],
+//!     )
+//!     .unwrap();
+//!
+//! assert_eq!(report.certified_paths, vec!["app.js", "index.html"]);
+//! assert_eq!(report.unconfigured_paths, vec!["index.html"]);
+//! assert!(report.unmatched_configs.is_empty());
+//! assert_eq!(report.missing_encodings[0].expected_path, "app.js.gz");
+//! assert!(report.alias_collisions.is_empty());
+//! ```
+//!
+//! To treat these mistakes as errors instead, the router can be configured with
+//! [with_strict_asset_configs](AssetRouter::with_strict_asset_configs). Patterns that match none of the provided assets and configured
+//! encodings that were not provided then cause [certify_assets](AssetRouter::certify_assets) to fail without certifying
+//! any of the assets:
+//!
+//! ```rust
+//! use ic_asset_certification::{Asset, AssetCertificationError, AssetConfig, AssetRouter};
+//!
+//! let mut asset_router = AssetRouter::default().with_strict_asset_configs();
+//!
+//! let result = asset_router.certify_assets(
+//!     vec![Asset::new("app.js", b"console.log('Hello World!');".as_slice())],
+//!     vec![AssetConfig::Pattern {
+//!         pattern: "**/*.css".to_string(),
+//!         exclude: vec![],
+//!         exclude_dotfiles: false,
+//!         content_type: Some("text/css".to_string()),
+//!         headers: vec![],
+//!         encodings: vec![],
+//!         image_variants: vec![],
+//!     }],
+//! );
+//!
+//! assert!(matches!(
+//!     result,
+//!     Err(AssetCertificationError::NoAssetMatchingPattern { .. })
+//! ));
+//! ```
+//!
 //! It's also possible to initialize the router with an
 //! [HttpCertificationTree](ic_http_certification::HttpCertificationTree). This is
 //! useful when direct access to the
@@ -469,6 +824,313 @@
 //! let mut asset_router = AssetRouter::with_tree(http_certification_tree.clone());
 //! ```
 //!
+//! The tree is shared using an `Rc<RefCell<HttpCertificationTree>>` by default, which
+//! prevents the [AssetRouter] from being sent between threads. For multi-threaded environments,
+//! such as test harnesses or state machines that require [Send] and [Sync], the [SyncAssetRouter]
+//! shares the tree using an `Arc<RwLock<HttpCertificationTree>>` instead.
+//!
+//! ```rust
+//! use std::sync::{Arc, RwLock};
+//! use ic_http_certification::HttpCertificationTree;
+//! use ic_asset_certification::SyncAssetRouter;
+//!
+//! let mut http_certification_tree: Arc<RwLock<HttpCertificationTree>> = Default::default();
+//! let mut asset_router = SyncAssetRouter::with_tree(http_certification_tree.clone());
+//! ```
+//!
+//! ## Synchronizing assets
+//!
+//! When a new version of the assets is uploaded, the [sync_assets](AssetRouter::sync_assets) method can be used instead of
+//! deleting and re-certifying everything. It compares the SHA-256 hash of each asset's content and of
+//! the configuration that applies to it with the hashes of the currently certified asset and only
+//! re-certifies assets whose content or configuration has changed, such as their headers, content type,
+//! fallbacks or aliases. Assets that are no longer part of the provided list are deleted, so after
+//! synchronizing, the router contains exactly the provided assets and the redirects from the provided
+//! configuration.
+//!
+//! ```rust
+//! use ic_asset_certification::{Asset, AssetConfig, AssetRouter};
+//!
+//! let mut asset_router = AssetRouter::default();
+//!
+//! let asset_configs = vec![AssetConfig::Pattern {
+//!     pattern: "**/*.js".to_string(),
+//!     exclude: vec![],
+//!     exclude_dotfiles: false,
+//!     content_type: Some("text/javascript".to_string()),
+//!     headers: vec![],
+//!     encodings: vec![],
+//!     image_variants: vec![],
+//! }];
+//!
+//! asset_router
+//!     .sync_assets(
+//!         vec![
+//!             Asset::new("app.js", b"console.log('Hello World!');".as_slice()),
+//!             Asset::new("vendor.js", b"console.log('Vendor');".as_slice()),
+//!         ],
+//!         asset_configs.clone(),
+//!     )
+//!     .unwrap();
+//!
+//! // only `app.js` is re-certified, `vendor.js` is deleted
+//! asset_router
+//!     .sync_assets(
+//!         vec![Asset::new("app.js", b"console.log('Hello Sync!');".as_slice())],
+//!         asset_configs,
+//!     )
+//!     .unwrap();
+//! ```
+//!
+//! ## Aliasing hashed assets
+//!
+//! Bundlers commonly add a content hash to the filenames of assets, e.g. `app-488df671.js`, so that
+//! the assets can be cached indefinitely. The [certify_hashed_assets](AssetRouter::certify_hashed_assets) method certifies
+//! these assets like [certify_assets](AssetRouter::certify_assets), and additionally certifies a stable alias for each of them,
+//! e.g. `/app.js`, that serves the latest version with the headers of the provided [HashedAssetAliasConfig].
+//! A JSON manifest that maps each stable path to its hashed path, e.g. `{"app.js":"app-488df671.js"}`, is
+//! certified on the configured manifest path. When a new version of an asset is certified, its stable alias
+//! and the manifest are updated:
+//!
+//! ```rust
+//! use ic_asset_certification::{
+//!     Asset, AssetConfig, AssetEncoding, AssetRouter, HashedAssetAliasConfig,
+//! };
+//!
+//! let mut asset_router = AssetRouter::default();
+//!
+//! asset_router
+//!     .certify_hashed_assets(
+//!         vec![
+//!             Asset::new("index.html", b"<script src=\"/app.js\"></script>".as_slice()),
+//!             Asset::new("app-488df671.js", b"console.log('Hello World!');".as_slice()),
+//!             Asset::new("app-488df671.js.gz", b"...".as_slice()),
+//!         ],
+//!         vec![AssetConfig::Pattern {
+//!             pattern: "**/*.js".to_string(),
+//!             exclude: vec![],
+//!             exclude_dotfiles: false,
+//!             content_type: Some("text/javascript".to_string()),
+//!             headers: vec![(
+//!                 "cache-control".to_string(),
+//!                 "public, max-age=31536000, immutable".to_string(),
+//!             )],
+//!             encodings: vec![AssetEncoding::Gzip.default_config()],
+//!             image_variants: vec![],
+//!         }],
+//!         HashedAssetAliasConfig {
+//!             manifest_path: "asset-manifest.json".to_string(),
+//!             headers: vec![("cache-control".to_string(), "public, no-cache".to_string())],
+//!         },
+//!     )
+//!     .unwrap();
+//! ```
+//!
+//! ## Certifying assets on demand
+//!
+//! Certifying a very large set of assets upfront may exceed the instruction limit of a single
+//! update call. With the [with_lazy_certification](AssetRouter::with_lazy_certification) option, the [certify_assets](AssetRouter::certify_assets) and [sync_assets](AssetRouter::sync_assets)
+//! methods only record the assets and their configuration. Fallbacks, error pages and redirects
+//! are still certified upfront, since they can be served for any request in their scope.
+//!
+//! Requests for an asset that has not been certified yet are answered with an
+//! [AssetCertificationPending](AssetCertificationError::AssetCertificationPending) error. The canister can then upgrade the request to an update
+//! call, where the asset is certified with the [certify_pending_asset](AssetRouter::certify_pending_asset) method.
+//!
+//! ```rust
+//! use ic_http_certification::HttpRequest;
+//! use ic_asset_certification::{Asset, AssetCertificationError, AssetRouter};
+//!
+//! let mut asset_router = AssetRouter::default().with_lazy_certification();
+//! asset_router
+//!     .certify_assets(
+//!         vec![Asset::new("app.js", b"console.log('Hello World!');".as_slice())],
+//!         vec![],
+//!     )
+//!     .unwrap();
+//!
+//! // this should normally be retrieved using `ic_cdk::api::data_certificate()`.
+//! let data_certificate = vec![1, 2, 3];
+//!
+//! let http_request = HttpRequest::get("/app.js").build();
+//! let result = asset_router.serve_asset(&data_certificate, &http_request);
+//! assert!(matches!(
+//!     result,
+//!     Err(AssetCertificationError::AssetCertificationPending { .. })
+//! ));
+//!
+//! // in the update call
+//! asset_router.certify_pending_asset("/app.js").unwrap();
+//! // set_certified_data(&asset_router.root_hash());
+//! ```
+//!
+//! ## Uploading assets in chunks
+//!
+//! Update calls are limited in size, so large assets are usually uploaded in chunks over
+//! multiple update calls. Instead of collecting the chunks into a single buffer, an upload can
+//! be begun with the [begin_asset](AssetRouter::begin_asset) method, which takes the path of the asset and the same
+//! configuration as [certify_assets](AssetRouter::certify_assets). Each chunk is passed to the [append_chunk](AssetRouter::append_chunk) method as it
+//! arrives and is hashed right away. The [commit_asset](AssetRouter::commit_asset) method certifies the asset using
+//! these hashes and returns a [CertificationReport].
+//!
+//! ```rust
+//! use ic_asset_certification::{AssetConfig, AssetRouter};
+//!
+//! let mut asset_router = AssetRouter::default();
+//! let asset_configs = vec![AssetConfig::File {
+//!     path: "video.mp4".to_string(),
+//!     content_type: Some("video/mp4".to_string()),
+//!     headers: vec![],
+//!     fallback_for: vec![],
+//!     aliased_by: vec![],
+//!     encodings: vec![],
+//!     image_variants: vec![],
+//! }];
+//!
+//! // in the first update call
+//! asset_router.begin_asset("video.mp4", asset_configs).unwrap();
+//!
+//! // in each following update call
+//! asset_router.append_chunk(vec![0; 1_000]).unwrap();
+//!
+//! // in the last update call
+//! asset_router.commit_asset().unwrap();
+//! // set_certified_data(&asset_router.root_hash());
+//! ```
+//!
+//! ## Receiving assets in update calls
+//!
+//! [AssetConfig] and its related types implement `CandidType` and `Deserialize`, and [OwnedAsset] is an owned form of an [Asset]
+//! that does as well. This allows controllers to push assets and their configuration to a canister in Candid update calls
+//! at runtime.
+//!
+//! ```rust
+//! use ic_asset_certification::{Asset, AssetConfig, AssetRouter, OwnedAsset};
+//!
+//! // #[update]
+//! fn certify_assets(
+//!     asset_router: &mut AssetRouter<'static>,
+//!     assets: Vec<OwnedAsset>,
+//!     asset_configs: Vec<AssetConfig>,
+//! ) {
+//!     asset_router
+//!         .certify_assets(assets.into_iter().map(Asset::from), asset_configs)
+//!         .unwrap();
+//!     // set_certified_data(&asset_router.root_hash());
+//! }
+//! ```
+//!
+//! ## Limiting asset sizes
+//!
+//! Assets that are pushed to a canister at runtime could exhaust its heap. The [with_max_asset_size](AssetRouter::with_max_asset_size) and
+//! [with_max_total_asset_size](AssetRouter::with_max_total_asset_size) options limit the size of a single asset and the total size of all
+//! certified assets. Operations that would exceed these limits return an [AssetTooLarge](AssetCertificationError::AssetTooLarge) or
+//! [TotalAssetSizeExceeded](AssetCertificationError::TotalAssetSizeExceeded) error without certifying any of the provided assets.
+//!
+//! ```rust
+//! use ic_asset_certification::{Asset, AssetCertificationError, AssetRouter};
+//!
+//! let mut asset_router = AssetRouter::default()
+//!     .with_max_asset_size(1_000_000)
+//!     .with_max_total_asset_size(100_000_000);
+//!
+//! let result = asset_router.certify_assets(vec![Asset::new("video.mp4", vec![0; 2_000_000])], vec![]);
+//! assert!(matches!(
+//!     result,
+//!     Err(AssetCertificationError::AssetTooLarge { .. })
+//! ));
+//! ```
+//!
+//! ## Precertifying assets
+//!
+//! Hashing the content of assets is the most expensive part of certifying them. For a static set of assets, the hashes
+//! can be calculated ahead of time, e.g. in a build script, with [PrecertifiedAssets::new] and encoded into a compact blob
+//! with [to_bytes](PrecertifiedAssets::to_bytes). The canister embeds the blob, decodes it with [from_bytes](PrecertifiedAssets::from_bytes)
+//! in `init` and passes it to [with_precertified_assets](AssetRouter::with_precertified_assets), so certifying the assets only hashes the
+//! headers of each response.
+//!
+//! The hashes are not verified against the content of the assets, so the blob must be calculated for the same
+//! assets that are certified by the canister.
+//!
+//! ```rust
+//! use ic_asset_certification::{Asset, AssetRouter, PrecertifiedAssets};
+//!
+//! let assets = vec![Asset::new("app.js", b"console.log('Hello World!');".as_slice())];
+//!
+//! // in a build script, the blob is written to a file and embedded using `include_bytes!`
+//! let precertified_assets_bytes = PrecertifiedAssets::new(&assets).to_bytes();
+//!
+//! // in the canister's `init` function
+//! let precertified_assets = PrecertifiedAssets::from_bytes(&precertified_assets_bytes).unwrap();
+//! let mut asset_router = AssetRouter::default().with_precertified_assets(precertified_assets);
+//! asset_router.certify_assets(assets, vec![]).unwrap();
+//! // set_certified_data(&asset_router.root_hash());
+//! ```
+//!
+//! ## Certifying well-known assets
+//!
+//! Nearly every canister serves a few well-known assets, such as `/robots.txt`. Instead of writing these by hand,
+//! they can be generated from [WellKnownAssets] and certified with the
+//! [certify_well_known_assets](AssetRouter::certify_well_known_assets) function. The supported assets are
+//! `/robots.txt`, `/.well-known/security.txt` and `/.well-known/ic-domains`, which are served as plain text.
+//!
+//! ```rust
+//! use ic_asset_certification::{AssetRouter, IcDomainsConfig, RobotsTxtConfig, WellKnownAssets};
+//!
+//! let mut asset_router = AssetRouter::default();
+//!
+//! asset_router
+//!     .certify_well_known_assets(&WellKnownAssets {
+//!         robots_txt: Some(RobotsTxtConfig {
+//!             disallow: vec!["/admin".to_string()],
+//!             ..Default::default()
+//!         }),
+//!         ic_domains: Some(IcDomainsConfig {
+//!             domains: vec!["example.com".to_string()],
+//!         }),
+//!         ..Default::default()
+//!     })
+//!     .unwrap();
+//! ```
+//!
+//! ## Subresource integrity
+//!
+//! The [AssetRouter] can add a [Subresource Integrity](https://developer.mozilla.org/en-US/docs/Web/Security/Subresource_Integrity)
+//! digest of each asset as a certified response header. The digest is always calculated over the unencoded content of an asset,
+//! so encoded responses carry the same digest as the unencoded response. The header must be configured before any assets are certified.
+//!
+//! The same digest is available through the [integrity](AssetMetadata::integrity) field of the [AssetMetadata] returned by the [AssetMap], which allows
+//! canisters that render HTML templates to emit `<script integrity="...">` attributes that are consistent with the certified assets.
+//!
+//! ```rust
+//! use ic_asset_certification::{Asset, AssetConfig, AssetMap, AssetRouter};
+//!
+//! let mut asset_router = AssetRouter::default().with_integrity_header("x-integrity");
+//!
+//! asset_router
+//!     .certify_assets(
+//!         vec![Asset::new("app.js", b"console.log('Hello World!');".as_slice())],
+//!         vec![AssetConfig::Pattern {
+//!             pattern: "**/*.js".to_string(),
+//!             exclude: vec![],
+//!             exclude_dotfiles: false,
+//!             content_type: Some("text/javascript".to_string()),
+//!             headers: vec![],
+//!             encodings: vec![],
+//!             image_variants: vec![],
+//!         }],
+//!     )
+//!     .unwrap();
+//!
+//! let integrity = asset_router
+//!     .get_assets()
+//!     .get_metadata("/app.js")
+//!     .and_then(|metadata| metadata.integrity)
+//!     .unwrap();
+//!
+//! let script_tag = format!(r#"<script src="/app.js" integrity="{integrity}"></script>"#);
+//! ```
+//!
 //! ## Serving assets
 //!
 //! Assets can be served by calling the `serve_asset` method on the `AssetRouter`.
@@ -495,9 +1157,11 @@
 //!     fallback_for: vec![AssetFallbackConfig {
 //!         scope: "/".to_string(),
 //!         status_code: Some(StatusCode::OK),
+//!         priority: 0,
 //!     }],
 //!     aliased_by: vec!["/".to_string()],
 //!     encodings: vec![],
+//!     image_variants: vec![],
 //! };
 //!
 //! let http_request = HttpRequest::get("/").build();
@@ -509,11 +1173,144 @@
 //! let response = asset_router.serve_asset(&data_certificate, &http_request).unwrap();
 //!```
 //!
+//! The body of the response returned by [serve_asset](AssetRouter::serve_asset) borrows asset
+//! content that outlives the router, but asset content that is owned by the router is copied into
+//! every response. For large assets, [serve_asset_borrowed](AssetRouter::serve_asset_borrowed)
+//! returns a response that borrows its body from the router instead, which can be encoded while the
+//! router is still borrowed, for example from a `manual_reply` query.
+//!
+//! The encoding of a response is selected using the quality values of the `Accept-Encoding`
+//! header of the request. If the unencoded asset is excluded with `identity;q=0` or `*;q=0` and none
+//! of the accepted encodings are certified for the asset, [serve_asset](AssetRouter::serve_asset) returns a
+//! [NoAcceptableEncoding](AssetCertificationError::NoAcceptableEncoding) error. Encodings
+//! with the same quality value are selected in the order Brotli, Zstd, Gzip, Deflate and finally the
+//! unencoded asset. This order can be changed with
+//! the [with_encoding_priority](AssetRouter::with_encoding_priority) method of the [AssetRouter].
+//! Encodings that are not listed are never selected:
+//!
+//! ```rust
+//! use ic_asset_certification::{AssetEncoding, AssetRouter};
+//!
+//! // prefer Zstd over Gzip and never serve Brotli
+//! let mut asset_router =
+//!     AssetRouter::default().with_encoding_priority(&[AssetEncoding::Zstd, AssetEncoding::Gzip]);
+//! ```
+//!
+//! Images can be served in alternative formats, such as AVIF or WebP, to browsers that
+//! support them. The alternative formats of an image are configured with the [image_variants](AssetConfig::File::image_variants)
+//! property of an [AssetConfig] and are certified alongside the image, at the same URL.
+//! The format of a response is selected using the quality values of the `Accept` header of the
+//! request, only considering exact media types, such as `image/avif`. [Formats](AssetImageFormat) with the same quality
+//! value are selected in the order AVIF, WebP and finally the original image. Alternative formats
+//! are selected before the encoding of a response and are only served for the URLs and aliases of the
+//! image, never for fallbacks:
+//!
+//! ```rust
+//! use ic_asset_certification::{Asset, AssetConfig, AssetImageFormat, AssetRouter};
+//!
+//! let assets = vec![
+//!     Asset::new("logo.png", b"png".as_slice()),
+//!     Asset::new("logo.avif", b"avif".as_slice()),
+//!     Asset::new("logo.webp", b"webp".as_slice()),
+//! ];
+//!
+//! let asset_configs = vec![AssetConfig::Pattern {
+//!     pattern: "**/*.png".to_string(),
+//!     exclude: vec![],
+//!     exclude_dotfiles: false,
+//!     content_type: Some("image/png".to_string()),
+//!     headers: vec![],
+//!     encodings: vec![],
+//!     image_variants: vec![
+//!         AssetImageFormat::Avif.default_config(),
+//!         AssetImageFormat::Webp.default_config(),
+//!     ],
+//! }];
+//!
+//! let mut asset_router = AssetRouter::default();
+//! asset_router.certify_assets(assets, asset_configs).unwrap();
+//! ```
+//!
+//! The resolution of a response can be controlled per request by calling the
+//! [serve_asset_with_options](AssetRouter::serve_asset_with_options) method with [ServeOptions]. The options allow disabling
+//! the search for fallbacks, forcing a specific encoding instead of negotiating it using the
+//! `Accept-Encoding` header and disabling `206 Partial Content` responses for large assets.
+//!
+//! ```rust
+//! use ic_http_certification::HttpRequest;
+//! use ic_asset_certification::{Asset, AssetEncoding, AssetRouter, ServeOptions};
+//!
+//! let mut asset_router = AssetRouter::default();
+//! asset_router
+//!     .certify_assets(vec![Asset::new("index.html", b"<h1>Hello</h1>".as_slice())], vec![])
+//!     .unwrap();
+//!
+//! // this should normally be retrieved using `ic_cdk::api::data_certificate()`.
+//! let data_certificate = vec![1, 2, 3];
+//!
+//! let options = ServeOptions {
+//!     disable_fallbacks: true,
+//!     encoding: Some(AssetEncoding::Identity),
+//!     ..Default::default()
+//! };
+//! let http_request = HttpRequest::get("/index.html").build();
+//! let response = asset_router
+//!     .serve_asset_with_options(&data_certificate, &http_request, &options)
+//!     .unwrap();
+//! ```
+//!
+//! Canisters that implement their own transport, for example a Candid query method to download
+//! individual chunks of a large asset, can look up a certified response directly with the
+//! [get_certified_response](AssetRouter::get_certified_response) method. It returns the response for the given path, encoding and
+//! chunk index, along with the witness and expression path that are needed to add the
+//! certificate header.
+//!
+//! ```rust
+//! use ic_http_certification::utils::add_v2_certificate_header;
+//! use ic_asset_certification::{Asset, AssetEncoding, AssetRouter};
+//!
+//! let mut asset_router = AssetRouter::default();
+//! asset_router
+//!     .certify_assets(vec![Asset::new("index.html", b"<h1>Hello</h1>".as_slice())], vec![])
+//!     .unwrap();
+//!
+//! // this should normally be retrieved using `ic_cdk::api::data_certificate()`.
+//! let data_certificate = vec![1, 2, 3];
+//!
+//! let (mut response, witness, expr_path) = asset_router
+//!     .get_certified_response("/index.html", AssetEncoding::Identity, None)
+//!     .unwrap();
+//! add_v2_certificate_header(&data_certificate, &mut response, &witness, &expr_path);
+//! ```
+//!
+//! Handlers that need to post-process the headers of a response, or delay adding the certificate
+//! header, can use the [serve_asset_parts](AssetRouter::serve_asset_parts) method instead of [serve_asset](AssetRouter::serve_asset). It resolves the
+//! response for a request in the same way, but returns the witness and expression path separately:
+//!
+//! ```rust
+//! use ic_http_certification::{utils::add_v2_certificate_header, HttpRequest};
+//! use ic_asset_certification::{Asset, AssetRouter};
+//!
+//! let mut asset_router = AssetRouter::default();
+//! asset_router
+//!     .certify_assets(vec![Asset::new("index.html", b"<h1>Hello</h1>".as_slice())], vec![])
+//!     .unwrap();
+//!
+//! // this should normally be retrieved using `ic_cdk::api::data_certificate()`.
+//! let data_certificate = vec![1, 2, 3];
+//!
+//! let http_request = HttpRequest::get("/index.html").build();
+//! let (mut response, witness, expr_path) = asset_router.serve_asset_parts(&http_request).unwrap();
+//! response.add_header(("x-served-by".to_string(), "composite-handler".to_string()));
+//! add_v2_certificate_header(&data_certificate, &mut response, &witness, &expr_path);
+//! ```
+//!
 //! ## Deleting assets
 //!
-//! There are three ways to delete assets from the asset router:
+//! There are four ways to delete assets from the asset router:
 //! 1. [By configuration](#deleting-assets-by-configuration).
 //! 1. [By path](#deleting-assets-by-path).
+//! 1. [By namespace](#deleting-assets-by-namespace).
 //! 1. [All at once](#deleting-all-assets).
 //!
 //! ### Deleting assets by configuration
@@ -593,15 +1390,19 @@
 //!         fallback_for: vec![AssetFallbackConfig {
 //!             scope: "/".to_string(),
 //!             status_code: Some(StatusCode::OK),
+//!             priority: 0,
 //!         }],
 //!         aliased_by: vec!["/".to_string()],
 //!         encodings: vec![
 //!             AssetEncoding::Brotli.default_config(),
 //!             AssetEncoding::Gzip.default_config(),
 //!         ],
+//!         image_variants: vec![],
 //!     },
 //!     AssetConfig::Pattern {
 //!         pattern: "**/*.js".to_string(),
+//!         exclude: vec![],
+//!         exclude_dotfiles: false,
 //!         content_type: Some("text/javascript".to_string()),
 //!         headers: vec![(
 //!             "cache-control".to_string(),
@@ -611,9 +1412,12 @@
 //!             AssetEncoding::Brotli.default_config(),
 //!             AssetEncoding::Gzip.default_config(),
 //!         ],
+//!         image_variants: vec![],
 //!     },
 //!     AssetConfig::Pattern {
 //!         pattern: "**/*.css".to_string(),
+//!         exclude: vec![],
+//!         exclude_dotfiles: false,
 //!         content_type: Some("text/css".to_string()),
 //!         headers: vec![(
 //!             "cache-control".to_string(),
@@ -623,11 +1427,13 @@
 //!             AssetEncoding::Brotli.default_config(),
 //!             AssetEncoding::Gzip.default_config(),
 //!         ],
+//!         image_variants: vec![],
 //!     },
 //!     AssetConfig::Redirect {
 //!         from: "/old".to_string(),
 //!         to: "/new".to_string(),
 //!         kind: AssetRedirectKind::Permanent,
+//!         append_query: false,
 //!         headers: vec![(
 //!             "content-type".to_string(),
 //!             "text/plain; charset=utf-8".to_string(),
@@ -666,12 +1472,14 @@
 //!             fallback_for: vec![AssetFallbackConfig {
 //!                 scope: "/".to_string(),
 //!                 status_code: Some(StatusCode::OK),
+//!                 priority: 0,
 //!             }],
 //!             aliased_by: vec!["/".to_string()],
 //!             encodings: vec![
 //!                 AssetEncoding::Brotli.default_config(),
 //!                 AssetEncoding::Gzip.default_config(),
 //!             ],
+//!             image_variants: vec![],
 //!         }],
 //!     )
 //!     .unwrap();
@@ -694,6 +1502,8 @@
 //!         ],
 //!         vec![AssetConfig::Pattern {
 //!             pattern: "**/*.js".to_string(),
+//!             exclude: vec![],
+//!             exclude_dotfiles: false,
 //!             content_type: Some("text/javascript".to_string()),
 //!             headers: vec![(
 //!                 "cache-control".to_string(),
@@ -703,6 +1513,7 @@
 //!                 AssetEncoding::Brotli.default_config(),
 //!                 AssetEncoding::Gzip.default_config(),
 //!             ],
+//!             image_variants: vec![],
 //!         }],
 //!     )
 //!     .unwrap();
@@ -734,6 +1545,8 @@
 //!     vec![
 //!         AssetConfig::Pattern {
 //!             pattern: "**/*.css".to_string(),
+//!             exclude: vec![],
+//!             exclude_dotfiles: false,
 //!             content_type: Some("text/css".to_string()),
 //!             headers: vec![(
 //!                 "cache-control".to_string(),
@@ -743,6 +1556,7 @@
 //!                 AssetEncoding::Brotli.default_config(),
 //!                 AssetEncoding::Gzip.default_config(),
 //!             ],
+//!             image_variants: vec![],
 //!         },
 //!     ]
 //! ).unwrap();
@@ -762,6 +1576,7 @@
 //!             from: "/old".to_string(),
 //!             to: "/new".to_string(),
 //!             kind: AssetRedirectKind::Permanent,
+//!             append_query: false,
 //!             headers: vec![(
 //!                 "content-type".to_string(),
 //!                 "text/plain; charset=utf-8".to_string(),
@@ -853,15 +1668,19 @@
 //!         fallback_for: vec![AssetFallbackConfig {
 //!             scope: "/".to_string(),
 //!             status_code: Some(StatusCode::OK),
+//!             priority: 0,
 //!         }],
 //!         aliased_by: vec!["/".to_string()],
 //!         encodings: vec![
 //!             AssetEncoding::Brotli.default_config(),
 //!             AssetEncoding::Gzip.default_config(),
 //!         ],
+//!         image_variants: vec![],
 //!     },
 //!     AssetConfig::Pattern {
 //!         pattern: "**/*.js".to_string(),
+//!         exclude: vec![],
+//!         exclude_dotfiles: false,
 //!         content_type: Some("text/javascript".to_string()),
 //!         headers: vec![(
 //!             "cache-control".to_string(),
@@ -871,9 +1690,12 @@
 //!             AssetEncoding::Brotli.default_config(),
 //!             AssetEncoding::Gzip.default_config(),
 //!         ],
+//!         image_variants: vec![],
 //!     },
 //!     AssetConfig::Pattern {
 //!         pattern: "**/*.css".to_string(),
+//!         exclude: vec![],
+//!         exclude_dotfiles: false,
 //!         content_type: Some("text/css".to_string()),
 //!         headers: vec![(
 //!             "cache-control".to_string(),
@@ -883,11 +1705,13 @@
 //!             AssetEncoding::Brotli.default_config(),
 //!             AssetEncoding::Gzip.default_config(),
 //!         ],
+//!         image_variants: vec![],
 //!     },
 //!     AssetConfig::Redirect {
 //!         from: "/old".to_string(),
 //!         to: "/new".to_string(),
 //!         kind: AssetRedirectKind::Permanent,
+//!         append_query: false,
 //!         headers: vec![("content-type".to_string(), "text/plain".to_string())],
 //!     },
 //! ];
@@ -960,6 +1784,48 @@
 //! set_certified_data(&asset_router.root_hash());
 //! ```
 //!
+//! ### Deleting assets by namespace
+//!
+//! Related assets, such as the assets of a single frontend deployment, can be grouped
+//! by wrapping their configurations in an [AssetConfig::Namespace]. Assets matching a
+//! configuration in the namespace, their alternative encodings and any redirects in the
+//! namespace can then be listed with [get_namespace_asset_paths](AssetRouter::get_namespace_asset_paths) and [deleted](AssetRouter::delete_namespace) as a unit,
+//! without providing the assets or their configuration again:
+//!
+//! ```rust
+//! use ic_asset_certification::{Asset, AssetConfig, AssetRouter};
+//!
+//! let mut asset_router = AssetRouter::default();
+//! asset_router
+//!     .certify_assets(
+//!         vec![
+//!             Asset::new("v1/index.html", b"<h1>Hello World!</h1>".as_slice()),
+//!             Asset::new("v1/app.js", b"console.log('Hello World!');".as_slice()),
+//!         ],
+//!         vec![AssetConfig::Namespace {
+//!             name: "v1".to_string(),
+//!             configs: AssetConfig::spa("v1/index.html"),
+//!         }],
+//!     )
+//!     .unwrap();
+//!
+//! assert_eq!(
+//!     asset_router.get_namespace_asset_paths("v1"),
+//!     vec!["v1/app.js", "v1/index.html"]
+//! );
+//!
+//! asset_router.delete_namespace("v1");
+//! ```
+//!
+//! After deleting any assets, make sure to set the canister's
+//! certified data again:
+//!
+//! ```ignore
+//! use ic_cdk::api::set_certified_data;
+//!
+//! set_certified_data(&asset_router.root_hash());
+//! ```
+//!
 //! ### Deleting all assets
 //!
 //! It's also possible to delete all assets and their certification in one go:
@@ -981,6 +1847,19 @@
 //! set_certified_data(&asset_router.root_hash());
 //! ```
 //!
+//! If the router shares its [HttpCertificationTree](ic_http_certification::HttpCertificationTree)
+//! with other certified responses, use [clear](AssetRouter::clear) instead. It removes all assets,
+//! fallbacks and redirects from the router, but only deletes the tree entries that the router
+//! inserted itself:
+//!
+//! ```rust
+//! # use ic_asset_certification::AssetRouter;
+//!
+//! # let mut asset_router = AssetRouter::default();
+//!
+//! asset_router.clear();
+//! ```
+//!
 //! ## Querying assets
 //!
 //! The [AssetRouter] has two functions to retrieve an [AssetMap] containing assets.
@@ -1002,19 +1881,101 @@
 //! `Some(ASSET_CHUNK_SIZE)`, the third range is `Some(ASSET_CHUNK_SIZE * 2)`, and so on. The entire asset can
 //! also be retrieved by passing `None` as the `starting_range`.
 //! See [ASSET_CHUNK_SIZE] for the size of each chunk.
+//!
+//! To enumerate a subset of the assets, the [iter_matching()](AssetMap::iter_matching) function
+//! accepts a glob pattern, such as `assets/**/*.js`. The pattern is matched against the asset's path
+//! without its leading slash, in the same way as [Pattern](AssetConfig::Pattern) configurations.
+//!
+//! ```rust
+//! use ic_asset_certification::{AssetMap, AssetRouter};
+//!
+//! let asset_router = AssetRouter::default();
+//!
+//! for ((path, encoding, starting_range), response) in asset_router
+//!     .get_assets()
+//!     .iter_matching("assets/**/*.js")
+//!     .unwrap()
+//! {
+//!     println!("{path} ({encoding:?}, {starting_range:?}): {}", response.status_code());
+//! }
+//! ```
+//!
+//! The [AssetMap] also provides structured metadata for each asset through the
+//! [get_metadata()](AssetMap::get_metadata) and [metadata()](AssetMap::metadata) functions.
+//! The returned [AssetMetadata] contains the asset's path, the response status code and, for each
+//! available encoding, the content length, the number of chunks and the SHA-256 hash of the response body.
+//! This is useful for exposing an inventory of the certified assets.
+//!
+//! ```rust
+//! use ic_asset_certification::{AssetMap, AssetRouter};
+//!
+//! let asset_router = AssetRouter::default();
+//!
+//! for asset in asset_router.get_assets().metadata() {
+//!     for encoding in asset.encodings {
+//!         println!(
+//!             "{} ({:?}): {} bytes in {} chunk(s)",
+//!             asset.path, encoding.encoding, encoding.content_length, encoding.chunk_count,
+//!         );
+//!     }
+//! }
+//! ```
+//!
+//! Redirects are not part of the [AssetMap], the [get_redirects()](AssetRouter::get_redirects)
+//! function returns every certified redirect as a [CertifiedRedirect] instead, ordered by the path
+//! that is redirected. This allows admin tooling to audit and reconcile the certified redirects
+//! without keeping track of them separately.
+//!
+//! ```rust
+//! use ic_asset_certification::AssetRouter;
+//!
+//! let asset_router = AssetRouter::default();
+//!
+//! for redirect in asset_router.get_redirects() {
+//!     println!(
+//!         "{} -> {} ({})",
+//!         redirect.from, redirect.to, redirect.status_code,
+//!     );
+//! }
+//! ```
 
 #![deny(missing_docs, missing_debug_implementations, rustdoc::all, clippy::all)]
 
 mod asset;
 mod asset_config;
+mod asset_config_candid;
+mod asset_hook_response;
 mod asset_map;
+mod asset_observer;
+mod asset_reader;
 mod asset_router;
+mod asset_upload;
+mod certification_report;
+mod certified_redirect;
 mod error;
+mod hashed_assets;
+mod integrity;
+mod precertified_assets;
+mod serve_options;
+mod tree_handle;
 mod types;
+mod well_known;
 
 pub use asset::*;
 pub use asset_config::*;
+pub use asset_hook_response::*;
 pub use asset_map::*;
+pub use asset_observer::*;
+pub use asset_reader::*;
 pub use asset_router::*;
+pub(crate) use asset_upload::*;
+pub use certification_report::*;
+pub use certified_redirect::*;
 pub use error::*;
+pub use hashed_assets::*;
+pub(crate) use integrity::*;
+pub use precertified_assets::*;
+pub use serve_options::*;
+pub use tree_handle::*;
 pub(crate) use types::*;
+pub use well_known::*;
@@ -0,0 +1,317 @@
+use crate::{
+    Asset, AssetBody, AssetCertificationError, AssetCertificationResult, ASSET_CHUNK_SIZE,
+};
+use ic_http_certification::Hash;
+use std::{cmp, collections::HashMap, ops::Range, sync::Arc};
+
+/// The version of the format that is produced by [PrecertifiedAssets::to_bytes].
+const FORMAT_VERSION: u8 = 1;
+
+/// Hashes of the content of [assets](Asset) that are calculated ahead of time, e.g. in a build
+/// script, so that a canister doesn't have to hash the content of its assets to certify them.
+///
+/// Hashing the content is the most expensive part of certifying an asset. The hashes are
+/// calculated off-chain with [new](PrecertifiedAssets::new) and encoded into a compact blob with
+/// [to_bytes](PrecertifiedAssets::to_bytes), which is embedded into the canister, e.g. with
+/// `include_bytes!`. The canister decodes the blob with [from_bytes](PrecertifiedAssets::from_bytes)
+/// in `init` and passes it to
+/// [with_precertified_assets](crate::AssetRouter::with_precertified_assets), the remaining
+/// certification work is then limited to the headers of each response.
+///
+/// # Examples
+///
+/// ```
+/// use ic_asset_certification::{Asset, AssetRouter, PrecertifiedAssets};
+///
+/// let assets = vec![Asset::new("index.html", b"<html></html>".as_slice())];
+///
+/// // at build time
+/// let bytes = PrecertifiedAssets::new(&assets).to_bytes();
+///
+/// // in the canister's `init` function
+/// let precertified_assets = PrecertifiedAssets::from_bytes(&bytes).unwrap();
+/// let mut asset_router = AssetRouter::default().with_precertified_assets(precertified_assets);
+/// asset_router.certify_assets(assets, vec![]).unwrap();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrecertifiedAssets {
+    content_hashes: HashMap<String, Arc<ContentHashes>>,
+}
+
+impl PrecertifiedAssets {
+    /// Calculates the hashes of the content of the provided assets.
+    pub fn new<'a, 'content: 'a, 'path: 'a>(
+        assets: impl IntoIterator<Item = &'a Asset<'content, 'path>>,
+    ) -> Self {
+        let content_hashes = assets
+            .into_iter()
+            .map(|asset| {
+                (
+                    asset.path.to_string(),
+                    Arc::new(ContentHashes::from_body(&asset.content)),
+                )
+            })
+            .collect();
+
+        Self { content_hashes }
+    }
+
+    /// Returns the number of assets that hashes were calculated for.
+    pub fn len(&self) -> usize {
+        self.content_hashes.len()
+    }
+
+    /// Returns `true` if no hashes were calculated.
+    pub fn is_empty(&self) -> bool {
+        self.content_hashes.is_empty()
+    }
+
+    /// Encodes the hashes into a compact blob, assets are ordered by path so the same assets
+    /// always produce the same blob.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut content_hashes = self.content_hashes.iter().collect::<Vec<_>>();
+        content_hashes.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let mut bytes = vec![FORMAT_VERSION];
+        bytes.extend_from_slice(&(content_hashes.len() as u32).to_le_bytes());
+        for (path, hashes) in content_hashes {
+            bytes.extend_from_slice(&(path.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(path.as_bytes());
+            bytes.extend_from_slice(&(hashes.len as u64).to_le_bytes());
+            bytes.extend_from_slice(&hashes.hash);
+            bytes.extend_from_slice(&(hashes.range_hashes.len() as u32).to_le_bytes());
+            for range_hash in hashes.range_hashes.iter() {
+                bytes.extend_from_slice(range_hash);
+            }
+        }
+
+        bytes
+    }
+
+    /// Decodes a blob that was produced by [to_bytes](PrecertifiedAssets::to_bytes).
+    pub fn from_bytes(bytes: &[u8]) -> AssetCertificationResult<Self> {
+        let mut reader = BlobReader(bytes);
+
+        let version = reader.read_bytes(1)?[0];
+        if version != FORMAT_VERSION {
+            return Err(AssetCertificationError::InvalidPrecertifiedAssets {
+                reason: format!("unsupported format version {version}"),
+            });
+        }
+
+        let asset_count = reader.read_u32()?;
+        let mut content_hashes = HashMap::new();
+        for _ in 0..asset_count {
+            let path_len = reader.read_u32()?;
+            let path = std::str::from_utf8(reader.read_bytes(path_len)?)
+                .map_err(|err| AssetCertificationError::InvalidPrecertifiedAssets {
+                    reason: format!("invalid asset path: {err}"),
+                })?
+                .to_string();
+            let len = reader.read_u64()? as usize;
+            let hash = reader.read_hash()?;
+            let range_hash_count = reader.read_u32()?;
+            let range_hashes = (0..range_hash_count)
+                .map(|_| reader.read_hash())
+                .collect::<AssetCertificationResult<Vec<_>>>()?;
+
+            content_hashes.insert(
+                path,
+                Arc::new(ContentHashes {
+                    len,
+                    hash,
+                    range_hashes,
+                }),
+            );
+        }
+
+        if !reader.0.is_empty() {
+            return Err(AssetCertificationError::InvalidPrecertifiedAssets {
+                reason: format!("{} unexpected trailing bytes", reader.0.len()),
+            });
+        }
+
+        Ok(Self { content_hashes })
+    }
+
+    /// Returns the provided asset with the hashes that were calculated for its content, if its
+    /// content is held in memory and has the same length as the content the hashes were
+    /// calculated for.
+    pub(crate) fn apply<'content, 'path>(
+        &self,
+        mut asset: Asset<'content, 'path>,
+    ) -> Asset<'content, 'path> {
+        let content = match (&asset.content, self.content_hashes.get(asset.path.as_ref())) {
+            (AssetBody::Bytes(bytes), Some(hashes)) if bytes.len() == hashes.len => {
+                AssetBody::Precertified(Arc::clone(bytes), Arc::clone(hashes))
+            }
+            _ => return asset,
+        };
+        asset.content = content;
+
+        asset
+    }
+}
+
+/// The hashes of some content, both in full and in ranges of [ASSET_CHUNK_SIZE] bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ContentHashes {
+    pub(crate) len: usize,
+    pub(crate) hash: Hash,
+    /// The hash of every range of [ASSET_CHUNK_SIZE] bytes of the content.
+    pub(crate) range_hashes: Vec<Hash>,
+}
+
+impl ContentHashes {
+    fn from_body(body: &AssetBody) -> Self {
+        let len = body.len();
+        let range_hashes = if len > ASSET_CHUNK_SIZE {
+            (0..len)
+                .step_by(ASSET_CHUNK_SIZE)
+                .map(|range_start| {
+                    body.hash(Some(
+                        range_start..cmp::min(range_start + ASSET_CHUNK_SIZE, len),
+                    ))
+                })
+                .collect()
+        } else {
+            vec![]
+        };
+
+        Self {
+            len,
+            hash: body.hash(None),
+            range_hashes,
+        }
+    }
+
+    /// Returns the hash of the provided range of the content if it was calculated,
+    /// i.e. for the full content and for ranges of [ASSET_CHUNK_SIZE] bytes.
+    pub(crate) fn get(&self, range: &Range<usize>) -> Option<Hash> {
+        if range.start == 0 && range.end == self.len {
+            return Some(self.hash);
+        }
+
+        let index = range.start / ASSET_CHUNK_SIZE;
+        let range_start = index * ASSET_CHUNK_SIZE;
+        let range_end = cmp::min(range_start + ASSET_CHUNK_SIZE, self.len);
+        if *range != (range_start..range_end) {
+            return None;
+        }
+
+        self.range_hashes.get(index).copied()
+    }
+}
+
+struct BlobReader<'a>(&'a [u8]);
+
+impl<'a> BlobReader<'a> {
+    fn read_bytes(&mut self, len: u32) -> AssetCertificationResult<&'a [u8]> {
+        let len = len as usize;
+        if self.0.len() < len {
+            return Err(AssetCertificationError::InvalidPrecertifiedAssets {
+                reason: "unexpected end of input".to_string(),
+            });
+        }
+
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+
+        Ok(bytes)
+    }
+
+    fn read_u32(&mut self) -> AssetCertificationResult<u32> {
+        let bytes = self.read_bytes(4)?;
+
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn read_u64(&mut self) -> AssetCertificationResult<u64> {
+        let mut buf = [0; 8];
+        buf.copy_from_slice(self.read_bytes(8)?);
+
+        Ok(u64::from_le_bytes(buf))
+    }
+
+    fn read_hash(&mut self) -> AssetCertificationResult<Hash> {
+        let mut hash = [0; 32];
+        hash.copy_from_slice(self.read_bytes(32)?);
+
+        Ok(hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use sha2::{Digest, Sha256};
+
+    fn sha256(content: &[u8]) -> Hash {
+        Sha256::digest(content).into()
+    }
+
+    #[rstest]
+    fn precertified_assets_round_trip() {
+        let large_content = (0..ASSET_CHUNK_SIZE * 2 + 10)
+            .map(|i| i as u8)
+            .collect::<Vec<_>>();
+        let assets = vec![
+            Asset::new("index.html", b"<html></html>".as_slice()),
+            Asset::new("video.mp4", large_content.as_slice()),
+            Asset::new("empty.txt", vec![]),
+        ];
+
+        let precertified_assets = PrecertifiedAssets::new(&assets);
+        let decoded = PrecertifiedAssets::from_bytes(&precertified_assets.to_bytes()).unwrap();
+
+        assert_eq!(decoded, precertified_assets);
+        assert_eq!(decoded.len(), 3);
+
+        let video_hashes = &decoded.content_hashes["video.mp4"];
+        assert_eq!(
+            video_hashes.get(&(0..large_content.len())),
+            Some(sha256(&large_content))
+        );
+        for range_start in (0..large_content.len()).step_by(ASSET_CHUNK_SIZE) {
+            let range = range_start..cmp::min(range_start + ASSET_CHUNK_SIZE, large_content.len());
+            assert_eq!(
+                video_hashes.get(&range),
+                Some(sha256(&large_content[range]))
+            );
+        }
+        assert_eq!(video_hashes.get(&(1..10)), None);
+    }
+
+    #[rstest]
+    fn precertified_assets_apply() {
+        let precertified_assets =
+            PrecertifiedAssets::new(&[Asset::new("index.html", b"<html></html>".as_slice())]);
+
+        let asset =
+            precertified_assets.apply(Asset::new("index.html", b"<html></html>".as_slice()));
+        assert!(matches!(asset.content, AssetBody::Precertified(..)));
+        assert_eq!(asset.content.hash(None), sha256(b"<html></html>"));
+
+        let changed_asset =
+            precertified_assets.apply(Asset::new("index.html", b"<html>".as_slice()));
+        assert!(matches!(changed_asset.content, AssetBody::Bytes(_)));
+
+        let other_asset =
+            precertified_assets.apply(Asset::new("about.html", b"<html></html>".as_slice()));
+        assert!(matches!(other_asset.content, AssetBody::Bytes(_)));
+    }
+
+    #[rstest]
+    #[case(vec![])]
+    #[case(vec![2, 0, 0, 0, 0])]
+    #[case(vec![1, 1, 0, 0, 0])]
+    #[case(vec![1, 0, 0, 0, 0, 0])]
+    #[case(vec![1, 1, 0, 0, 0, 1, 0, 0, 0, 0xff])]
+    fn precertified_assets_from_invalid_bytes(#[case] bytes: Vec<u8>) {
+        assert!(matches!(
+            PrecertifiedAssets::from_bytes(&bytes),
+            Err(AssetCertificationError::InvalidPrecertifiedAssets { .. })
+        ));
+    }
+}
@@ -0,0 +1,36 @@
+use crate::AssetEncoding;
+
+/// Options to control how the [AssetRouter](crate::AssetRouter) resolves the response for a
+/// request, see [serve_asset_with_options](crate::AssetRouter::serve_asset_with_options).
+///
+/// The default options match the behavior of [serve_asset](crate::AssetRouter::serve_asset).
+///
+/// # Examples
+///
+/// ```
+/// use ic_asset_certification::{AssetEncoding, ServeOptions};
+///
+/// let options = ServeOptions {
+///     disable_fallbacks: true,
+///     encoding: Some(AssetEncoding::Gzip),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ServeOptions {
+    /// Disables the search for fallbacks and `404` error pages if no asset exactly matches the
+    /// request path. A [NoAssetMatchingRequestUrl](crate::AssetCertificationError::NoAssetMatchingRequestUrl)
+    /// error is returned instead.
+    pub disable_fallbacks: bool,
+
+    /// Serves the provided encoding instead of negotiating the encoding with the
+    /// `Accept-Encoding` header of the request. If the asset is not certified with
+    /// this encoding, the unencoded asset is served.
+    pub encoding: Option<AssetEncoding>,
+
+    /// Disables `206 Partial Content` responses. The `Range` header of the request is
+    /// ignored and assets larger than [ASSET_CHUNK_SIZE](crate::ASSET_CHUNK_SIZE) are served
+    /// in full, instead of serving their first chunk. Note that the full response may exceed
+    /// the maximum response size of the Internet Computer.
+    pub disable_partial_content: bool,
+}
@@ -0,0 +1,59 @@
+use ic_http_certification::HttpCertificationTree;
+use std::{
+    cell::{Ref, RefCell, RefMut},
+    ops::{Deref, DerefMut},
+    rc::Rc,
+    sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+/// A shared handle to the
+/// [HttpCertificationTree](ic_http_certification::HttpCertificationTree) that is used by an
+/// [AssetRouter](crate::AssetRouter) for certifying assets.
+///
+/// This trait is implemented for `Rc<RefCell<HttpCertificationTree>>`, which is the default for
+/// single-threaded canisters, and for `Arc<RwLock<HttpCertificationTree>>`, which allows the router
+/// to be [Send] and [Sync], see [SyncAssetRouter](crate::SyncAssetRouter).
+pub trait CertificationTreeHandle {
+    /// A guard that provides shared access to the tree.
+    type Ref<'a>: Deref<Target = HttpCertificationTree>
+    where
+        Self: 'a;
+
+    /// A guard that provides exclusive access to the tree.
+    type RefMut<'a>: DerefMut<Target = HttpCertificationTree>
+    where
+        Self: 'a;
+
+    /// Acquires shared access to the tree.
+    fn read_tree(&self) -> Self::Ref<'_>;
+
+    /// Acquires exclusive access to the tree.
+    fn write_tree(&self) -> Self::RefMut<'_>;
+}
+
+impl CertificationTreeHandle for Rc<RefCell<HttpCertificationTree>> {
+    type Ref<'a> = Ref<'a, HttpCertificationTree>;
+    type RefMut<'a> = RefMut<'a, HttpCertificationTree>;
+
+    fn read_tree(&self) -> Self::Ref<'_> {
+        self.borrow()
+    }
+
+    fn write_tree(&self) -> Self::RefMut<'_> {
+        self.borrow_mut()
+    }
+}
+
+impl CertificationTreeHandle for Arc<RwLock<HttpCertificationTree>> {
+    type Ref<'a> = RwLockReadGuard<'a, HttpCertificationTree>;
+    type RefMut<'a> = RwLockWriteGuard<'a, HttpCertificationTree>;
+
+    // poisoning is ignored, consistent with the `Rc<RefCell<_>>` handle, which has no notion of it
+    fn read_tree(&self) -> Self::Ref<'_> {
+        RwLock::read(self).unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write_tree(&self) -> Self::RefMut<'_> {
+        RwLock::write(self).unwrap_or_else(PoisonError::into_inner)
+    }
+}
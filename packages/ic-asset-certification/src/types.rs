@@ -1,9 +1,297 @@
-use ic_http_certification::{HttpCertificationTreeEntry, HttpResponse};
+use crate::{Asset, AssetReader, ChunkedContent, ContentHashes, ASSET_CHUNK_SIZE};
+use ic_http_certification::{
+    Hash, HeaderField, HttpCertificationTreeEntry, HttpResponse, StatusCode,
+};
+use sha2::{Digest, Sha256};
+use std::{
+    borrow::Cow,
+    cmp, fmt,
+    ops::Range,
+    sync::{Arc, OnceLock},
+};
 
 #[derive(Debug, Clone)]
 pub(crate) struct CertifiedAssetResponse<'a> {
-    pub(crate) response: HttpResponse<'a>,
+    pub(crate) response: AssetResponse<'a>,
     pub(crate) tree_entry: HttpCertificationTreeEntry<'a>,
+    /// Path of the asset that this response was generated from, [None] for redirects.
+    pub(crate) asset_path: Option<String>,
+    /// Priority of a fallback response, see [AssetFallbackConfig::priority](crate::AssetFallbackConfig::priority).
+    /// Always `0` for other responses.
+    pub(crate) priority: i32,
+    /// Whether the request's query string is appended to the `Location` header of a redirect,
+    /// see [AssetConfig::Redirect](crate::AssetConfig::Redirect).
+    pub(crate) append_query: bool,
+}
+
+/// A response stored by the [AssetRouter](crate::AssetRouter).
+///
+/// The body is shared with all other responses that are generated from the same asset content,
+/// such as aliases, fallbacks and chunks, so it is only stored once.
+#[derive(Debug, Clone)]
+pub(crate) struct AssetResponse<'a> {
+    pub(crate) status_code: StatusCode,
+    pub(crate) headers: Vec<HeaderField>,
+    pub(crate) body: AssetBody<'a>,
+    /// The range of `body` that is served by this response, [None] for the full body.
+    pub(crate) body_range: Option<Range<usize>>,
+    /// The SHA-256 hash of the body that is served by this response, calculated once when
+    /// the response is certified.
+    pub(crate) body_hash: Hash,
+}
+
+impl<'a> AssetResponse<'a> {
+    /// Returns the body that is served by this response, it is only copied if the
+    /// content is read from an [AssetReader].
+    pub(crate) fn body(&self) -> Cow<'_, [u8]> {
+        self.body.read(self.body_range.clone())
+    }
+
+    pub(crate) fn body_len(&self) -> usize {
+        match &self.body_range {
+            Some(body_range) => body_range.len(),
+            None => self.body.len(),
+        }
+    }
+
+    /// Returns an [HttpResponse] that borrows its body from this response.
+    pub(crate) fn as_http_response(&self) -> HttpResponse<'_> {
+        self.build_http_response(self.body())
+    }
+
+    /// Returns an [HttpResponse] to certify this response along with the stored
+    /// [body_hash](AssetResponse::body_hash). The body of the returned response is left empty,
+    /// so the content is never read or copied again.
+    pub(crate) fn as_certification_response(&self) -> HttpResponse<'_> {
+        self.build_http_response(Cow::Borrowed(&[]))
+    }
+
+    /// Returns an [HttpResponse] that has the same lifetime as the original asset content,
+    /// the body is only copied if the router owns the asset content.
+    pub(crate) fn to_http_response(&self) -> HttpResponse<'a> {
+        self.build_http_response(self.body.to_cow(self.body_range.clone()))
+    }
+
+    fn build_http_response<'b>(&self, body: Cow<'b, [u8]>) -> HttpResponse<'b> {
+        // reserve space for the certificate header that is appended when the response is served
+        let mut headers = Vec::with_capacity(self.headers.len() + 1);
+        headers.extend_from_slice(&self.headers);
+
+        HttpResponse::builder()
+            .with_status_code(self.status_code)
+            .with_headers(headers)
+            .with_body(body)
+            .build()
+    }
+}
+
+/// The content of an [Asset], either held in memory, read on demand from an [AssetReader],
+/// held in memory in the chunks it was uploaded in, or held in memory along with hashes
+/// that were calculated ahead of time, see [PrecertifiedAssets](crate::PrecertifiedAssets).
+#[derive(Clone)]
+pub(crate) enum AssetBody<'a> {
+    Bytes(Arc<Cow<'a, [u8]>>),
+    Reader(Arc<ReaderContent>),
+    Chunked(Arc<ChunkedContent>),
+    Precertified(Arc<Cow<'a, [u8]>>, Arc<ContentHashes>),
+}
+
+impl<'a> AssetBody<'a> {
+    pub(crate) fn empty() -> Self {
+        AssetBody::Bytes(Arc::new(Cow::Borrowed(&[])))
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            AssetBody::Bytes(bytes) | AssetBody::Precertified(bytes, _) => bytes.len(),
+            AssetBody::Reader(content) => content.reader.len(),
+            AssetBody::Chunked(content) => content.len(),
+        }
+    }
+
+    /// Returns the provided range of the content, or the full content if no range is provided,
+    /// if the content is held in memory as a whole, or [None] otherwise.
+    pub(crate) fn as_bytes(&self, range: Option<Range<usize>>) -> Option<&[u8]> {
+        match self {
+            AssetBody::Bytes(bytes) | AssetBody::Precertified(bytes, _) => match range {
+                Some(range) => Some(&bytes[range]),
+                None => Some(bytes.as_ref()),
+            },
+            AssetBody::Reader(_) | AssetBody::Chunked(_) => None,
+        }
+    }
+
+    /// Returns the provided range of the content, or the full content if no range is provided.
+    pub(crate) fn read(&self, range: Option<Range<usize>>) -> Cow<'_, [u8]> {
+        match self {
+            AssetBody::Bytes(bytes) | AssetBody::Precertified(bytes, _) => match range {
+                Some(range) => Cow::Borrowed(&bytes[range]),
+                None => Cow::Borrowed(bytes.as_ref()),
+            },
+            AssetBody::Reader(content) => {
+                let range = range.unwrap_or(0..content.reader.len());
+                let mut buf = vec![0; range.len()];
+                content.reader.read(range.start, &mut buf);
+
+                Cow::Owned(buf)
+            }
+            AssetBody::Chunked(content) => {
+                let range = range.unwrap_or(0..content.len());
+                let mut buf = Vec::with_capacity(range.len());
+                content.read(range, &mut buf);
+
+                Cow::Owned(buf)
+            }
+        }
+    }
+
+    /// Same as [read](AssetBody::read), but only borrows content that outlives the router.
+    pub(crate) fn to_cow(&self, range: Option<Range<usize>>) -> Cow<'a, [u8]> {
+        let bytes = match self {
+            AssetBody::Bytes(bytes) | AssetBody::Precertified(bytes, _) => bytes,
+            body => return Cow::Owned(body.read(range).into_owned()),
+        };
+
+        match (bytes.as_ref(), range) {
+            (Cow::Borrowed(bytes), Some(range)) => Cow::Borrowed(&bytes[range]),
+            (Cow::Owned(bytes), Some(range)) => Cow::Owned(bytes[range].to_vec()),
+            (Cow::Borrowed(bytes), None) => Cow::Borrowed(*bytes),
+            (Cow::Owned(bytes), None) => Cow::Owned(bytes.clone()),
+        }
+    }
+
+    /// Returns the SHA-256 hash of the provided range of the content, or of the full content
+    /// if no range is provided. Content that is read from an [AssetReader] is read one chunk
+    /// at a time and the hash of its full content is only calculated once, uploaded and
+    /// precertified content reuses the hashes that were calculated during the upload or ahead
+    /// of time.
+    pub(crate) fn hash(&self, range: Option<Range<usize>>) -> Hash {
+        let range = range.unwrap_or(0..self.len());
+        let mut hasher = Sha256::new();
+
+        if let AssetBody::Reader(content) = self {
+            if range == (0..content.reader.len()) {
+                return *content.sha256.get_or_init(|| {
+                    content
+                        .reader
+                        .sha256()
+                        .unwrap_or_else(|| Self::hash_reader(content.reader.as_ref(), range))
+                });
+            }
+        }
+
+        match self {
+            AssetBody::Bytes(bytes) => hasher.update(&bytes[range]),
+            AssetBody::Precertified(bytes, hashes) => match hashes.get(&range) {
+                Some(hash) => return hash,
+                None => hasher.update(&bytes[range]),
+            },
+            AssetBody::Chunked(content) => match content.precomputed_hash(&range) {
+                Some(hash) => return hash,
+                None => hasher.update(self.read(Some(range))),
+            },
+            AssetBody::Reader(content) => return Self::hash_reader(content.reader.as_ref(), range),
+        }
+
+        hasher.finalize().into()
+    }
+
+    /// Keeps the hash of the full content along with content that is held in memory, so that
+    /// the hash is calculated only once when the content is compared and certified later on.
+    /// Other content already keeps or reuses its hashes, see [hash](AssetBody::hash).
+    pub(crate) fn cache_hash(&mut self) {
+        if let AssetBody::Bytes(bytes) = self {
+            let bytes = Arc::clone(bytes);
+            let hashes = ContentHashes {
+                len: bytes.len(),
+                hash: Sha256::digest(&bytes[..]).into(),
+                range_hashes: vec![],
+            };
+            *self = AssetBody::Precertified(bytes, Arc::new(hashes));
+        }
+    }
+
+    fn hash_reader(reader: &dyn AssetReader, range: Range<usize>) -> Hash {
+        let mut hasher = Sha256::new();
+        let mut buf = vec![0; cmp::min(ASSET_CHUNK_SIZE, range.len())];
+        let mut offset = range.start;
+        while offset < range.end {
+            let chunk_len = cmp::min(ASSET_CHUNK_SIZE, range.end - offset);
+            reader.read(offset, &mut buf[..chunk_len]);
+            hasher.update(&buf[..chunk_len]);
+            offset += chunk_len;
+        }
+
+        hasher.finalize().into()
+    }
+}
+
+impl fmt::Debug for AssetBody<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AssetBody::Bytes(bytes) => f.debug_tuple("Bytes").field(bytes).finish(),
+            AssetBody::Reader(content) => f
+                .debug_struct("Reader")
+                .field("len", &content.reader.len())
+                .finish_non_exhaustive(),
+            AssetBody::Chunked(content) => f.debug_tuple("Chunked").field(content).finish(),
+            AssetBody::Precertified(bytes, _) => {
+                f.debug_tuple("Precertified").field(bytes).finish()
+            }
+        }
+    }
+}
+
+/// Content that is read on demand from an [AssetReader], along with the hash of the full
+/// content once it is known.
+pub(crate) struct ReaderContent {
+    pub(crate) reader: Box<dyn AssetReader>,
+    pub(crate) sha256: OnceLock<Hash>,
+}
+
+impl ReaderContent {
+    pub(crate) fn new(reader: impl AssetReader + 'static) -> Self {
+        ReaderContent {
+            reader: Box::new(reader),
+            sha256: OnceLock::new(),
+        }
+    }
+}
+
+impl PartialEq for AssetBody<'_> {
+    /// Content that is read from an [AssetReader] or uploaded in chunks is only equal to itself,
+    /// content that is held in memory is equal regardless of precertified hashes.
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                AssetBody::Bytes(a) | AssetBody::Precertified(a, _),
+                AssetBody::Bytes(b) | AssetBody::Precertified(b, _),
+            ) => a == b,
+            (AssetBody::Reader(a), AssetBody::Reader(b)) => Arc::ptr_eq(a, b),
+            (AssetBody::Chunked(a), AssetBody::Chunked(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for AssetBody<'_> {}
+
+/// An [Asset] whose content is shared between all responses that are generated from it.
+#[derive(Debug, Clone)]
+pub(crate) struct SharedAsset<'a> {
+    pub(crate) path: String,
+    pub(crate) url: String,
+    pub(crate) content: AssetBody<'a>,
+}
+
+impl<'a> From<Asset<'a, '_>> for SharedAsset<'a> {
+    fn from(asset: Asset<'a, '_>) -> Self {
+        SharedAsset {
+            path: asset.path.into_owned(),
+            url: asset.url.into_owned(),
+            content: asset.content,
+        }
+    }
 }
 
 /// A key created from request data, to retrieve the corresponding response.
@@ -0,0 +1,292 @@
+use crate::{Asset, AssetConfig};
+
+/// The content type that well-known assets are served with.
+const WELL_KNOWN_CONTENT_TYPE: &str = "text/plain; charset=utf-8";
+
+/// Common well-known assets that are generated from simple configurations instead of being
+/// written by hand, see
+/// [certify_well_known_assets](crate::AssetRouter::certify_well_known_assets).
+///
+/// # Examples
+///
+/// ```
+/// use ic_asset_certification::{
+///     AssetRouter, IcDomainsConfig, RobotsTxtConfig, SecurityTxtConfig, WellKnownAssets,
+/// };
+///
+/// let well_known_assets = WellKnownAssets {
+///     robots_txt: Some(RobotsTxtConfig {
+///         disallow: vec!["/admin".to_string()],
+///         ..Default::default()
+///     }),
+///     security_txt: Some(SecurityTxtConfig {
+///         contacts: vec!["mailto:security@example.com".to_string()],
+///         expires: "2030-01-01T00:00:00.000Z".to_string(),
+///         ..Default::default()
+///     }),
+///     ic_domains: Some(IcDomainsConfig {
+///         domains: vec!["example.com".to_string()],
+///     }),
+/// };
+///
+/// let mut asset_router = AssetRouter::default();
+/// let report = asset_router
+///     .certify_well_known_assets(&well_known_assets)
+///     .unwrap();
+///
+/// assert_eq!(
+///     report.certified_paths,
+///     vec![
+///         ".well-known/ic-domains",
+///         ".well-known/security.txt",
+///         "robots.txt",
+///     ]
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WellKnownAssets {
+    /// Generates `/robots.txt`, see [RobotsTxtConfig].
+    pub robots_txt: Option<RobotsTxtConfig>,
+
+    /// Generates `/.well-known/security.txt`, see [SecurityTxtConfig].
+    pub security_txt: Option<SecurityTxtConfig>,
+
+    /// Generates `/.well-known/ic-domains`, see [IcDomainsConfig].
+    pub ic_domains: Option<IcDomainsConfig>,
+}
+
+impl WellKnownAssets {
+    /// Generates the configured assets.
+    pub fn assets(&self) -> Vec<Asset<'static, 'static>> {
+        self.contents()
+            .into_iter()
+            .map(|(path, content)| Asset::new(path, content.into_bytes()))
+            .collect()
+    }
+
+    /// Returns the [AssetConfig] of each configured asset, which serves it as plain text.
+    pub fn asset_configs(&self) -> Vec<AssetConfig> {
+        self.contents()
+            .into_iter()
+            .map(|(path, _)| AssetConfig::File {
+                path: path.to_string(),
+                content_type: Some(WELL_KNOWN_CONTENT_TYPE.to_string()),
+                headers: vec![],
+                fallback_for: vec![],
+                aliased_by: vec![],
+                encodings: vec![],
+                image_variants: vec![],
+            })
+            .collect()
+    }
+
+    fn contents(&self) -> Vec<(&'static str, String)> {
+        let mut contents = vec![];
+        if let Some(robots_txt) = &self.robots_txt {
+            contents.push(("robots.txt", robots_txt.content()));
+        }
+        if let Some(security_txt) = &self.security_txt {
+            contents.push((".well-known/security.txt", security_txt.content()));
+        }
+        if let Some(ic_domains) = &self.ic_domains {
+            contents.push((".well-known/ic-domains", ic_domains.content()));
+        }
+
+        contents
+    }
+}
+
+/// Configuration for `/robots.txt`, which applies to all crawlers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RobotsTxtConfig {
+    /// Paths that crawlers are allowed to visit, even if they are below a disallowed path.
+    pub allow: Vec<String>,
+
+    /// Paths that crawlers are not allowed to visit, e.g. `/admin`. If this is empty, crawlers
+    /// are allowed to visit all paths.
+    pub disallow: Vec<String>,
+
+    /// Absolute URLs of sitemaps, e.g. `https://example.com/sitemap.xml`.
+    pub sitemaps: Vec<String>,
+}
+
+impl RobotsTxtConfig {
+    fn content(&self) -> String {
+        let mut lines = vec!["User-agent: *".to_string()];
+        lines.extend(self.allow.iter().map(|path| format!("Allow: {path}")));
+        if self.disallow.is_empty() {
+            lines.push("Disallow:".to_string());
+        }
+        lines.extend(self.disallow.iter().map(|path| format!("Disallow: {path}")));
+        lines.extend(
+            self.sitemaps
+                .iter()
+                .map(|sitemap| format!("Sitemap: {sitemap}")),
+        );
+
+        to_content(lines)
+    }
+}
+
+/// Configuration for `/.well-known/security.txt`, as specified by
+/// [RFC 9116](https://www.rfc-editor.org/rfc/rfc9116).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SecurityTxtConfig {
+    /// URIs to report security vulnerabilities to, e.g. `mailto:security@example.com`.
+    /// At least one contact is required.
+    pub contacts: Vec<String>,
+
+    /// The date and time after which the file is considered stale, in the
+    /// [RFC 3339](https://www.rfc-editor.org/rfc/rfc3339) format, e.g. `2030-01-01T00:00:00.000Z`.
+    pub expires: String,
+
+    /// URIs of keys to encrypt reports with.
+    pub encryption: Vec<String>,
+
+    /// URIs of pages that acknowledge reporters of vulnerabilities.
+    pub acknowledgments: Vec<String>,
+
+    /// Language tags of the languages that reports can be written in, e.g. `en`.
+    pub preferred_languages: Vec<String>,
+
+    /// URIs that the file is canonically served on.
+    pub canonical: Vec<String>,
+
+    /// URIs of the vulnerability disclosure policy.
+    pub policy: Vec<String>,
+
+    /// URIs of security-related job openings.
+    pub hiring: Vec<String>,
+}
+
+impl SecurityTxtConfig {
+    fn content(&self) -> String {
+        let fields = [
+            ("Contact", &self.contacts),
+            ("Encryption", &self.encryption),
+            ("Acknowledgments", &self.acknowledgments),
+            ("Canonical", &self.canonical),
+            ("Policy", &self.policy),
+            ("Hiring", &self.hiring),
+        ];
+
+        let mut lines = vec![];
+        for (name, values) in fields {
+            lines.extend(values.iter().map(|value| format!("{name}: {value}")));
+        }
+        lines.push(format!("Expires: {}", self.expires));
+        if !self.preferred_languages.is_empty() {
+            lines.push(format!(
+                "Preferred-Languages: {}",
+                self.preferred_languages.join(", ")
+            ));
+        }
+
+        to_content(lines)
+    }
+}
+
+/// Configuration for `/.well-known/ic-domains`, which lists the custom domains that are
+/// allowed to serve the canister.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IcDomainsConfig {
+    /// The custom domains, e.g. `example.com`.
+    pub domains: Vec<String>,
+}
+
+impl IcDomainsConfig {
+    fn content(&self) -> String {
+        to_content(self.domains.clone())
+    }
+}
+
+fn to_content(lines: Vec<String>) -> String {
+    lines.into_iter().map(|line| format!("{line}\n")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AssetMap, AssetRouter};
+    use rstest::*;
+
+    #[rstest]
+    fn robots_txt_content() {
+        assert_eq!(
+            RobotsTxtConfig::default().content(),
+            "User-agent: *\nDisallow:\n"
+        );
+        assert_eq!(
+            RobotsTxtConfig {
+                allow: vec!["/admin/public".to_string()],
+                disallow: vec!["/admin".to_string(), "/api".to_string()],
+                sitemaps: vec!["https://example.com/sitemap.xml".to_string()],
+            }
+            .content(),
+            "User-agent: *\nAllow: /admin/public\nDisallow: /admin\nDisallow: /api\nSitemap: https://example.com/sitemap.xml\n"
+        );
+    }
+
+    #[rstest]
+    fn security_txt_content() {
+        let security_txt = SecurityTxtConfig {
+            contacts: vec![
+                "mailto:security@example.com".to_string(),
+                "https://example.com/security".to_string(),
+            ],
+            expires: "2030-01-01T00:00:00.000Z".to_string(),
+            policy: vec!["https://example.com/policy".to_string()],
+            preferred_languages: vec!["en".to_string(), "de".to_string()],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            security_txt.content(),
+            "Contact: mailto:security@example.com\nContact: https://example.com/security\nPolicy: https://example.com/policy\nExpires: 2030-01-01T00:00:00.000Z\nPreferred-Languages: en, de\n"
+        );
+    }
+
+    #[rstest]
+    fn ic_domains_content() {
+        let ic_domains = IcDomainsConfig {
+            domains: vec!["example.com".to_string(), "www.example.com".to_string()],
+        };
+
+        assert_eq!(ic_domains.content(), "example.com\nwww.example.com\n");
+    }
+
+    #[rstest]
+    fn certify_well_known_assets() {
+        let mut asset_router = AssetRouter::default();
+        asset_router
+            .certify_well_known_assets(&WellKnownAssets {
+                robots_txt: Some(RobotsTxtConfig::default()),
+                ic_domains: Some(IcDomainsConfig {
+                    domains: vec!["example.com".to_string()],
+                }),
+                ..Default::default()
+            })
+            .unwrap();
+
+        let response = asset_router
+            .get_assets()
+            .get("/robots.txt", None, None)
+            .unwrap();
+        assert_eq!(response.body(), b"User-agent: *\nDisallow:\n");
+        assert!(response.headers().contains(&(
+            "content-type".to_string(),
+            WELL_KNOWN_CONTENT_TYPE.to_string()
+        )));
+
+        let response = asset_router
+            .get_assets()
+            .get("/.well-known/ic-domains", None, None)
+            .unwrap();
+        assert_eq!(response.body(), b"example.com\n");
+
+        assert!(asset_router
+            .get_assets()
+            .get("/.well-known/security.txt", None, None)
+            .is_none());
+    }
+}
@@ -56,15 +56,27 @@ impl VerifyCertificate<()> for Certificate {
         allowed_certificate_time_offset: &u128,
     ) -> CertificateVerificationResult<()> {
         verify_certificate_time(self, current_time_ns, allowed_certificate_time_offset)?;
-
-        let der_key = match &self.delegation {
-            Some(delegation) => verify_delegation(delegation, canister_id, root_public_key)?,
-            _ => root_public_key.into(),
-        };
-        verify_certificate_signature(self, &der_key)
+        verify_certificate_signature_and_delegation(self, canister_id, root_public_key)
     }
 }
 
+/// Verifies the signature of the certificate and its delegation, if any, without verifying the
+/// time of the certificate.
+///
+/// This check is also performed by [VerifyCertificate::verify], use it directly in combination with
+/// [verify_certificate_time_range] to customize how the time of the certificate is verified.
+pub fn verify_certificate_signature_and_delegation(
+    certificate: &Certificate,
+    canister_id: &[u8],
+    root_public_key: &[u8],
+) -> CertificateVerificationResult {
+    let der_key = match &certificate.delegation {
+        Some(delegation) => verify_delegation(delegation, canister_id, root_public_key)?,
+        _ => root_public_key.into(),
+    };
+    verify_certificate_signature(certificate, &der_key)
+}
+
 fn verify_delegation(
     delegation: &Delegation,
     canister_id: &[u8],
@@ -119,10 +131,31 @@ fn verify_delegation(
     Ok(subnet_public_key.into())
 }
 
-fn verify_certificate_time(
+/// Verifies that the time of the certificate is within the allowed offset of the current time.
+///
+/// This check is also performed by [VerifyCertificate::verify], use it directly to check the
+/// freshness of a certificate whose signature was already verified.
+pub fn verify_certificate_time(
     certificate: &Certificate,
     current_time_ns: &u128,
     allowed_certificate_time_offset: &u128,
+) -> CertificateVerificationResult {
+    let max_certificate_time = current_time_ns + allowed_certificate_time_offset;
+    let min_certificate_time = current_time_ns - allowed_certificate_time_offset;
+
+    verify_certificate_time_range(
+        certificate,
+        min_certificate_time,
+        Some(max_certificate_time),
+    )
+}
+
+/// Verifies that the time of the certificate is not before `min_certificate_time` and, unless it
+/// is [None], not after `max_certificate_time`.
+pub fn verify_certificate_time_range(
+    certificate: &Certificate,
+    min_certificate_time: u128,
+    max_certificate_time: Option<u128>,
 ) -> CertificateVerificationResult {
     let time_path = ["time".as_bytes()];
 
@@ -139,14 +172,14 @@ fn verify_certificate_time(
             timestamp: encoded_certificate_time.to_vec(),
         }
     })? as u128;
-    let max_certificate_time = current_time_ns + allowed_certificate_time_offset;
-    let min_certificate_time = current_time_ns - allowed_certificate_time_offset;
 
-    if certificate_time > max_certificate_time {
-        return Err(CertificateVerificationError::TimeTooFarInTheFuture {
-            certificate_time,
-            max_certificate_time,
-        });
+    if let Some(max_certificate_time) = max_certificate_time {
+        if certificate_time > max_certificate_time {
+            return Err(CertificateVerificationError::TimeTooFarInTheFuture {
+                certificate_time,
+                max_certificate_time,
+            });
+        }
     }
 
     if certificate_time < min_certificate_time {
@@ -397,6 +430,39 @@ mod tests {
         ))
     }
 
+    #[test]
+    fn verify_certificate_time_range_without_max_time() {
+        let canister_id = CanisterId::from_u64(0);
+        let current_timestamp = get_current_timestamp();
+
+        let future_time = SystemTime::now().add(Duration::new(301, 0));
+        let future_timestamp = get_timestamp(future_time);
+
+        let CertificateData {
+            cbor_encoded_certificate,
+            certificate: _,
+            root_key,
+        } = CertificateBuilder::new(
+            &canister_id.to_string(),
+            &AssetTree::new().get_certified_data(),
+        )
+        .unwrap()
+        .with_time(future_timestamp)
+        .build()
+        .unwrap();
+
+        let certificate = Certificate::from_cbor(&cbor_encoded_certificate).unwrap();
+
+        verify_certificate_time_range(
+            &certificate,
+            current_timestamp - MAX_CERT_TIME_OFFSET_NS,
+            None,
+        )
+        .unwrap();
+        verify_certificate_signature_and_delegation(&certificate, canister_id.as_ref(), &root_key)
+            .unwrap();
+    }
+
     #[test]
     fn verify_certificate_with_time_too_far_in_the_past() {
         let canister_id = CanisterId::from_u64(0);
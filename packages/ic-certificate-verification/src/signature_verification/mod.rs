@@ -1,6 +1,5 @@
 use self::signature_cache::{SignatureCache, SignatureCacheEntry};
 use crate::CertificateVerificationError;
-use miracl_core_bls12381::bls12381::bls::{core_verify, BLS_OK};
 
 mod signature_cache;
 
@@ -21,12 +20,38 @@ pub fn verify_signature(
         return Ok(());
     }
 
-    let result = core_verify(sig, msg, pk);
-
-    if !matches!(result, BLS_OK) {
+    if !core_verify(pk, sig, msg) {
         return Err(CertificateVerificationError::SignatureVerificationFailed);
     }
 
     SignatureCache::global().insert(&entry);
     Ok(())
 }
+
+/// Verifies a BLS signature on G1 against a public key on G2 with [miracl_core_bls12381].
+#[cfg(not(feature = "blst"))]
+fn core_verify(pk: &[u8], sig: &[u8], msg: &[u8]) -> bool {
+    use miracl_core_bls12381::bls12381::bls::{core_verify, BLS_OK};
+
+    matches!(core_verify(sig, msg, pk), BLS_OK)
+}
+
+/// Verifies a BLS signature on G1 against a public key on G2 with [blst].
+#[cfg(feature = "blst")]
+fn core_verify(pk: &[u8], sig: &[u8], msg: &[u8]) -> bool {
+    use blst::{
+        min_sig::{PublicKey, Signature},
+        BLST_ERROR,
+    };
+
+    const DST: &[u8] = b"BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_NUL_";
+
+    let (Ok(pk), Ok(sig)) = (PublicKey::from_bytes(pk), Signature::from_bytes(sig)) else {
+        return false;
+    };
+
+    matches!(
+        sig.verify(true, msg, DST, &[], &pk, true),
+        BLST_ERROR::BLST_SUCCESS
+    )
+}
@@ -1,5 +1,8 @@
 use crate::{empty, fork, labeled, leaf, pruned, AsHashTree, Hash, HashTree, HashTreeNode, RbTree};
-use std::fmt::{Debug, Formatter};
+use std::{
+    collections::HashMap,
+    fmt::{Debug, Formatter},
+};
 
 pub trait NestedTreeKeyRequirements: Debug + Clone + AsRef<[u8]> + 'static {}
 pub trait NestedTreeValueRequirements: Debug + Clone + AsHashTree + 'static {}
@@ -141,10 +144,96 @@ impl<K: NestedTreeKeyRequirements, V: NestedTreeValueRequirements> NestedTree<K,
         }
     }
 
+    /// Inserts all `entries`, resulting in the same tree as inserting them one by one with
+    /// [insert](NestedTree::insert). Entries that share a subtree are inserted together,
+    /// so the hashes along the path to that subtree are only recomputed once.
+    pub fn insert_many<'p>(&mut self, entries: impl IntoIterator<Item = (&'p [K], V)>) {
+        let mut entries: Vec<_> = entries.into_iter().collect();
+
+        // a value at an empty path replaces the whole tree,
+        // so only the last one and the entries following it have an effect
+        if let Some(index) = entries.iter().rposition(|(path, _)| path.is_empty()) {
+            if let Some((_, value)) = entries.drain(..=index).next_back() {
+                *self = NestedTree::Leaf(value);
+            }
+        }
+
+        if entries.is_empty() {
+            return;
+        }
+
+        if let NestedTree::Leaf(_) = self {
+            self.clear();
+        }
+
+        if let NestedTree::Nested(tree) = self {
+            for (key, children) in group_by_first_key(entries) {
+                if tree.get(key.as_ref()).is_none() {
+                    tree.insert(key.clone(), NestedTree::default());
+                }
+                tree.modify(key.as_ref(), |child| child.insert_many(children));
+            }
+        }
+    }
+
+    /// Deletes all `paths`, resulting in a tree with the same entries as deleting them one by one
+    /// with [delete](NestedTree::delete). Paths that share a subtree are deleted together,
+    /// so the hashes along the path to that subtree are only recomputed once.
+    pub fn delete_many<'p>(&mut self, paths: impl IntoIterator<Item = &'p [K]>) {
+        let paths: Vec<_> = paths.into_iter().collect();
+
+        if paths.iter().any(|path| path.is_empty()) {
+            self.clear();
+            return;
+        }
+
+        if let NestedTree::Nested(tree) = self {
+            let paths = paths.into_iter().map(|path| (path, ()));
+            for (key, children) in group_by_first_key(paths) {
+                let children: Vec<_> = children.into_iter().map(|(path, _)| path).collect();
+                tree.modify(key.as_ref(), |child| child.delete_many(children));
+
+                // after deleting the paths below `key`,
+                // check if the subtree located at `key` is empty,
+                // if it is, remove it
+                if let Some(NestedTree::Nested(nested_tree)) = tree.get(key.as_ref()) {
+                    if nested_tree.is_empty() {
+                        tree.delete(key.as_ref());
+                    }
+                }
+            }
+        }
+    }
+
     pub fn clear(&mut self) {
         *self = NestedTree::default();
     }
 
+    /// Removes all subtrees that do not contain any leaves, returning the number of subtrees
+    /// that were removed. [delete](NestedTree::delete) and [delete_many](NestedTree::delete_many)
+    /// already remove the subtrees that they leave empty, so this is only needed for trees that
+    /// were built by other means.
+    pub fn prune(&mut self) -> usize {
+        let NestedTree::Nested(tree) = self else {
+            return 0;
+        };
+
+        let keys: Vec<K> = tree.iter().map(|(key, _)| key.clone()).collect();
+        let mut pruned_count = 0;
+        for key in keys {
+            tree.modify(key.as_ref(), |child| pruned_count += child.prune());
+
+            if let Some(NestedTree::Nested(nested_tree)) = tree.get(key.as_ref()) {
+                if nested_tree.is_empty() {
+                    tree.delete(key.as_ref());
+                    pruned_count += 1;
+                }
+            }
+        }
+
+        pruned_count
+    }
+
     pub fn witness(&self, path: &[K]) -> HashTree {
         if let Some(key) = path.first() {
             match self {
@@ -159,6 +248,27 @@ impl<K: NestedTreeKeyRequirements, V: NestedTreeValueRequirements> NestedTree<K,
     }
 }
 
+/// Groups non-empty `paths` by their first key, in order of first appearance,
+/// and strips that key from each path.
+fn group_by_first_key<'p, K: NestedTreeKeyRequirements, T>(
+    paths: impl IntoIterator<Item = (&'p [K], T)>,
+) -> Vec<(&'p K, Vec<(&'p [K], T)>)> {
+    let mut group_indices: HashMap<&[u8], usize> = HashMap::new();
+    let mut groups: Vec<(&K, Vec<(&[K], T)>)> = vec![];
+
+    for (path, item) in paths {
+        if let Some((key, rest)) = path.split_first() {
+            let index = *group_indices.entry(key.as_ref()).or_insert_with(|| {
+                groups.push((key, vec![]));
+                groups.len() - 1
+            });
+            groups[index].1.push((rest, item));
+        }
+    }
+
+    groups
+}
+
 pub fn merge_hash_trees(lhs: HashTree, rhs: HashTree) -> HashTree {
     match (lhs.root, rhs.root) {
         (HashTreeNode::Pruned(l), HashTreeNode::Pruned(r)) => {
@@ -197,6 +307,142 @@ pub fn merge_hash_trees(lhs: HashTree, rhs: HashTree) -> HashTree {
     }
 }
 
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::{NestedTree, NestedTreeKeyRequirements, NestedTreeValueRequirements};
+    use crate::RbTree;
+    use serde::{
+        de::{Error, SeqAccess, Visitor},
+        Deserialize, Deserializer, Serialize, Serializer,
+    };
+    use serde_bytes::{ByteBuf, Bytes};
+    use std::{fmt, marker::PhantomData};
+
+    /// A record of a serialized [NestedTree]. A nested tree is serialized as a flat sequence of
+    /// records, instead of nesting the serialized subtrees, so that the depth of the serialized
+    /// tree does not depend on the length of its paths.
+    #[derive(Serialize)]
+    #[serde(rename = "NestedTreeRecord")]
+    enum SerializedRecord<'a, V> {
+        /// A [leaf](NestedTree::Leaf) with its value.
+        Leaf(&'a V),
+
+        /// A [subtree](NestedTree::Nested) with the number of its nodes. The subtree is followed
+        /// by its nodes in pre-order.
+        Nested(usize),
+
+        /// A node of a subtree with its key and color. The node is followed by the records of its
+        /// value.
+        Node(&'a Bytes, bool),
+    }
+
+    /// The deserialized counterpart of [SerializedRecord].
+    #[derive(Deserialize)]
+    #[serde(rename = "NestedTreeRecord")]
+    enum DeserializedRecord<V> {
+        Leaf(V),
+        Nested(usize),
+        Node(ByteBuf, bool),
+    }
+
+    fn push_records<'a, K: NestedTreeKeyRequirements, V: NestedTreeValueRequirements>(
+        tree: &'a NestedTree<K, V>,
+        records: &mut Vec<SerializedRecord<'a, V>>,
+    ) {
+        match tree {
+            NestedTree::Leaf(value) => records.push(SerializedRecord::Leaf(value)),
+            NestedTree::Nested(subtree) => {
+                let nodes = subtree.pre_order();
+                records.push(SerializedRecord::Nested(nodes.len()));
+                for (key, value, is_red) in nodes {
+                    records.push(SerializedRecord::Node(Bytes::new(key.as_ref()), is_red));
+                    push_records(value, records);
+                }
+            }
+        }
+    }
+
+    fn read_records<'de, K, V, A>(seq: &mut A) -> Result<NestedTree<K, V>, A::Error>
+    where
+        K: NestedTreeKeyRequirements + From<Vec<u8>>,
+        V: NestedTreeValueRequirements + Deserialize<'de>,
+        A: SeqAccess<'de>,
+    {
+        match seq.next_element::<DeserializedRecord<V>>()? {
+            Some(DeserializedRecord::Leaf(value)) => Ok(NestedTree::Leaf(value)),
+            Some(DeserializedRecord::Nested(len)) => {
+                let mut remaining_nodes = len;
+                let subtree = RbTree::from_pre_order(|| {
+                    if remaining_nodes == 0 {
+                        return Ok(None);
+                    }
+                    remaining_nodes -= 1;
+
+                    let Some(DeserializedRecord::Node(key, is_red)) =
+                        seq.next_element::<DeserializedRecord<V>>()?
+                    else {
+                        return Err(A::Error::custom("Expected a node of a nested tree"));
+                    };
+                    let value = read_records(seq)?;
+
+                    Ok(Some((K::from(key.into_vec()), value, is_red)))
+                })?;
+
+                Ok(NestedTree::Nested(subtree))
+            }
+            Some(DeserializedRecord::Node(..)) | None => {
+                Err(A::Error::custom("Expected a leaf or a nested tree"))
+            }
+        }
+    }
+
+    /// Serializes the tree with the structure of all of its subtrees, so that the deserialized
+    /// tree has the same root hash as the serialized tree, see [RbTree].
+    impl<K: NestedTreeKeyRequirements, V: NestedTreeValueRequirements + Serialize> Serialize
+        for NestedTree<K, V>
+    {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut records = vec![];
+            push_records(self, &mut records);
+
+            serializer.collect_seq(records)
+        }
+    }
+
+    impl<'de, K, V> Deserialize<'de> for NestedTree<K, V>
+    where
+        K: NestedTreeKeyRequirements + From<Vec<u8>>,
+        V: NestedTreeValueRequirements + Deserialize<'de>,
+    {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct NestedTreeVisitor<K, V>(PhantomData<(K, V)>);
+
+            impl<'de, K, V> Visitor<'de> for NestedTreeVisitor<K, V>
+            where
+                K: NestedTreeKeyRequirements + From<Vec<u8>>,
+                V: NestedTreeValueRequirements + Deserialize<'de>,
+            {
+                type Value = NestedTree<K, V>;
+
+                fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                    formatter.write_str("the records of a nested tree")
+                }
+
+                fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                    let tree = read_records(&mut seq)?;
+                    if seq.next_element::<DeserializedRecord<V>>()?.is_some() {
+                        return Err(A::Error::custom("Unexpected record after the nested tree"));
+                    }
+
+                    Ok(tree)
+                }
+            }
+
+            deserializer.deserialize_seq(NestedTreeVisitor(PhantomData))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -292,6 +538,149 @@ mod tests {
         assert!(!tree.contains_path(&["one"]));
     }
 
+    #[rstest]
+    fn insert_many_and_delete_many() {
+        let paths: [&[&str]; 5] = [
+            &["one", "two"],
+            &["three", "four"],
+            &["one", "five"],
+            &["three", "six", "seven"],
+            &["one", "two"],
+        ];
+
+        let mut sequential_tree: NestedTree<&str, Vec<u8>> = NestedTree::default();
+        for (i, path) in paths.iter().enumerate() {
+            sequential_tree.insert(path, vec![i as u8]);
+        }
+
+        let mut tree: NestedTree<&str, Vec<u8>> = NestedTree::default();
+        tree.insert_many(
+            paths
+                .iter()
+                .enumerate()
+                .map(|(i, path)| (*path, vec![i as u8])),
+        );
+
+        assert_eq!(tree.root_hash(), sequential_tree.root_hash());
+        assert_eq!(tree.get(&["one", "two"]), Some(&vec![4]));
+        assert_eq!(tree.get(&["one", "five"]), Some(&vec![2]));
+        assert_eq!(tree.get(&["three", "four"]), Some(&vec![1]));
+        assert_eq!(tree.get(&["three", "six", "seven"]), Some(&vec![3]));
+
+        tree.delete_many([
+            &["one", "two"][..],
+            &["three", "six", "seven"][..],
+            &["one", "five"][..],
+            &["one", "missing"][..],
+        ]);
+
+        assert!(!tree.contains_path(&["one"]));
+        assert!(!tree.contains_path(&["three", "six"]));
+        assert_eq!(tree.get(&["three", "four"]), Some(&vec![1]));
+
+        let mut expected_tree: NestedTree<&str, Vec<u8>> = NestedTree::default();
+        expected_tree.insert(&["three", "four"], vec![1]);
+        assert_eq!(tree.root_hash(), expected_tree.root_hash());
+    }
+
+    #[rstest]
+    fn insert_many_empty_path() {
+        let mut tree: NestedTree<&str, Vec<u8>> = NestedTree::default();
+        tree.insert_many([
+            (&["one", "two"][..], vec![1]),
+            (&[][..], vec![2]),
+            (&["three"][..], vec![3]),
+        ]);
+
+        let mut sequential_tree: NestedTree<&str, Vec<u8>> = NestedTree::default();
+        sequential_tree.insert(&["one", "two"], vec![1]);
+        sequential_tree.insert(&[], vec![2]);
+        sequential_tree.insert(&["three"], vec![3]);
+
+        assert_eq!(tree.root_hash(), sequential_tree.root_hash());
+        assert!(!tree.contains_path(&["one"]));
+        assert_eq!(tree.get(&["three"]), Some(&vec![3]));
+
+        tree.delete_many([&["three"][..], &[][..]]);
+
+        assert!(!tree.contains_path(&["three"]));
+        assert_eq!(
+            tree.root_hash(),
+            NestedTree::<&str, Vec<u8>>::default().root_hash()
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[rstest]
+    fn serialize_deserialize() {
+        let path = |segments: &[&str]| -> Vec<Vec<u8>> {
+            segments
+                .iter()
+                .map(|segment| segment.as_bytes().to_vec())
+                .collect()
+        };
+
+        let mut tree: NestedTree<Vec<u8>, Vec<u8>> = NestedTree::default();
+        for i in (0..20u8).rev() {
+            tree.insert(&path(&["one", &i.to_string()]), vec![i]);
+            tree.insert(&path(&[&i.to_string(), "two", "three"]), vec![i]);
+        }
+        for i in (0..20u8).step_by(3) {
+            tree.delete(&path(&["one", &i.to_string()]));
+            tree.delete(&path(&[&i.to_string(), "two", "three"]));
+        }
+
+        let bytes = serde_cbor::to_vec(&tree).unwrap();
+        let mut restored_tree: NestedTree<Vec<u8>, Vec<u8>> =
+            serde_cbor::from_slice(&bytes).unwrap();
+        assert_eq!(restored_tree.root_hash(), tree.root_hash());
+        assert_eq!(restored_tree.get(&path(&["one", "1"])), Some(&vec![1]));
+        assert!(!restored_tree.contains_path(&path(&["3"])));
+
+        for t in [&mut tree, &mut restored_tree] {
+            t.insert(&path(&["one", "20"]), vec![20]);
+            t.delete(&path(&["1", "two", "three"]));
+        }
+        assert_eq!(restored_tree.root_hash(), tree.root_hash());
+
+        // records after the end of the tree are rejected
+        let mut records: Vec<serde_cbor::Value> = serde_cbor::from_slice(&bytes).unwrap();
+        records.push(records[0].clone());
+        let bytes = serde_cbor::to_vec(&records).unwrap();
+        assert!(serde_cbor::from_slice::<NestedTree<Vec<u8>, Vec<u8>>>(&bytes).is_err());
+    }
+
+    #[rstest]
+    fn prune_removes_empty_subtrees() {
+        let mut tree: NestedTree<&str, Vec<u8>> = NestedTree::default();
+        tree.insert(&["one", "two"], vec![2]);
+
+        let mut expected_tree = tree.clone();
+
+        let mut empty_subtree: RbTree<&str, NestedTree<&str, Vec<u8>>> = RbTree::new();
+        empty_subtree.insert("four", NestedTree::default());
+        if let NestedTree::Nested(root) = &mut tree {
+            root.insert("three", NestedTree::Nested(empty_subtree));
+            root.modify(b"one", |one| {
+                if let NestedTree::Nested(one) = one {
+                    one.insert("five", NestedTree::default());
+                }
+            });
+        }
+
+        assert!(tree.contains_path(&["three", "four"]));
+        assert!(tree.contains_path(&["one", "five"]));
+        assert_ne!(tree.root_hash(), expected_tree.root_hash());
+
+        assert_eq!(tree.prune(), 3);
+
+        assert!(!tree.contains_path(&["three"]));
+        assert!(!tree.contains_path(&["one", "five"]));
+        assert!(tree.contains_leaf(&["one", "two"]));
+        assert_eq!(tree.root_hash(), expected_tree.root_hash());
+        assert_eq!(expected_tree.prune(), 0);
+    }
+
     #[rstest]
     fn delete_removes_empty_subpaths() {
         let mut tree: NestedTree<&str, Vec<u8>> = NestedTree::default();
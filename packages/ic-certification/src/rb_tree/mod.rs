@@ -1019,5 +1019,158 @@ impl<'a, K: AsRef<[u8]>, V> std::fmt::Debug for DebugView<'a, K, V> {
     }
 }
 
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::{is_red, Color, Node, NodeRef, RbTree};
+    use crate::AsHashTree;
+    use serde::{
+        de::{Error, SeqAccess, Visitor},
+        Deserialize, Deserializer, Serialize, Serializer,
+    };
+    use serde_bytes::{ByteBuf, Bytes};
+    use std::{fmt, iter::Peekable, marker::PhantomData};
+
+    /// The maximum height of a balanced tree, which has at most twice as many nodes on its
+    /// longest path as on its shortest path.
+    const MAX_HEIGHT: usize = 2 * usize::BITS as usize;
+
+    impl<K: 'static + AsRef<[u8]>, V: AsHashTree + 'static> RbTree<K, V> {
+        /// Returns the key, value and color of every node in pre-order, i.e. every node before
+        /// the nodes of its left subtree and the nodes of its left subtree before the nodes of
+        /// its right subtree. The nodes and their colors determine the structure of the tree,
+        /// see [from_pre_order](RbTree::from_pre_order).
+        pub(crate) fn pre_order(&self) -> Vec<(&K, &V, bool)> {
+            let mut nodes = vec![];
+            let mut parents = self.root.as_deref().into_iter().collect::<Vec<_>>();
+            while let Some(node) = parents.pop() {
+                nodes.push((&node.key, &node.value, node.color == Color::Red));
+                parents.extend(node.right.as_deref());
+                parents.extend(node.left.as_deref());
+            }
+
+            nodes
+        }
+
+        /// Restores a tree from the key, value and color of every node in pre-order, as
+        /// returned by [pre_order](RbTree::pre_order), so that the restored tree has the same
+        /// structure, and therefore the same root hash, as the original tree.
+        ///
+        /// Returns an error if the nodes do not describe a valid left-leaning red-black tree.
+        pub(crate) fn from_pre_order<E: Error>(
+            mut next_node: impl FnMut() -> Result<Option<(K, V, bool)>, E>,
+        ) -> Result<Self, E> {
+            let mut nodes = std::iter::from_fn(|| next_node().transpose()).peekable();
+            let root = build(&mut nodes, None, None, 0)?;
+            if nodes.next().transpose()?.is_some() {
+                return Err(E::custom("The nodes of the tree are not in pre-order"));
+            }
+            if is_red(&root) {
+                return Err(E::custom("The root of the tree is red"));
+            }
+            black_height(&root)?;
+
+            Ok(Self { root })
+        }
+    }
+
+    /// Builds the subtree of the next nodes whose keys are between the given bounds.
+    fn build<K: 'static + AsRef<[u8]>, V: AsHashTree + 'static, E: Error>(
+        nodes: &mut Peekable<impl Iterator<Item = Result<(K, V, bool), E>>>,
+        lower: Option<&[u8]>,
+        upper: Option<&[u8]>,
+        height: usize,
+    ) -> Result<NodeRef<K, V>, E> {
+        let Some((key, value, is_red)) = nodes
+            .next_if(|node| match node {
+                Ok((key, _, _)) => {
+                    lower.map_or(true, |lower| key.as_ref() > lower)
+                        && upper.map_or(true, |upper| key.as_ref() < upper)
+                }
+                Err(_) => true,
+            })
+            .transpose()?
+        else {
+            return Ok(None);
+        };
+        if height >= MAX_HEIGHT {
+            return Err(E::custom("The tree is not balanced"));
+        }
+
+        let mut node = Node::new(key, value);
+        node.color = if is_red { Color::Red } else { Color::Black };
+        node.left = build(nodes, lower, Some(node.key.as_ref()), height + 1)?;
+        node.right = build(nodes, Some(node.key.as_ref()), upper, height + 1)?;
+        node.update_subtree_hash();
+
+        Ok(Some(node))
+    }
+
+    /// Returns the number of black nodes on every path from the given node to a leaf, or an
+    /// error if the paths have different numbers of black nodes, if a red node has a red
+    /// child or if a right child is red.
+    fn black_height<K, V, E: Error>(node: &NodeRef<K, V>) -> Result<usize, E> {
+        let Some(n) = node else {
+            return Ok(0);
+        };
+        if is_red(&n.right) || (is_red(node) && is_red(&n.left)) {
+            return Err(E::custom("The tree is not a left-leaning red-black tree"));
+        }
+
+        let left_height = black_height(&n.left)?;
+        if left_height != black_height(&n.right)? {
+            return Err(E::custom("The tree is not balanced"));
+        }
+
+        Ok(left_height + usize::from(!is_red(node)))
+    }
+
+    /// Serializes the key, value and color of every node of the tree in pre-order, so that the
+    /// deserialized tree has the same structure, and therefore the same root hash, as the
+    /// serialized tree. The root hash of a tree depends on the order in which its entries were
+    /// inserted and deleted, so inserting the entries into a new tree does not necessarily
+    /// restore it.
+    impl<K: 'static + AsRef<[u8]>, V: AsHashTree + Serialize + 'static> Serialize for RbTree<K, V> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_seq(
+                self.pre_order()
+                    .into_iter()
+                    .map(|(key, value, is_red)| (Bytes::new(key.as_ref()), value, is_red)),
+            )
+        }
+    }
+
+    impl<'de, K, V> Deserialize<'de> for RbTree<K, V>
+    where
+        K: 'static + AsRef<[u8]> + From<Vec<u8>>,
+        V: AsHashTree + Deserialize<'de> + 'static,
+    {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct RbTreeVisitor<K, V>(PhantomData<(K, V)>);
+
+            impl<'de, K, V> Visitor<'de> for RbTreeVisitor<K, V>
+            where
+                K: 'static + AsRef<[u8]> + From<Vec<u8>>,
+                V: AsHashTree + Deserialize<'de> + 'static,
+            {
+                type Value = RbTree<K, V>;
+
+                fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                    formatter.write_str("the nodes of a tree in pre-order")
+                }
+
+                fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                    RbTree::from_pre_order(|| {
+                        Ok(seq
+                            .next_element::<(ByteBuf, V, bool)>()?
+                            .map(|(key, value, is_red)| (K::from(key.into_vec()), value, is_red)))
+                    })
+                }
+            }
+
+            deserializer.deserialize_seq(RbTreeVisitor(PhantomData))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests;
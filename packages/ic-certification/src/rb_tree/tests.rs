@@ -389,3 +389,64 @@ fn test_ordering() {
     assert_eq!(t1.cmp(&t3), Greater);
     assert_eq!(t1.cmp(&t4), Less);
 }
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serialize_deserialize() {
+    let mut t = TreeOfBytes::new();
+    for k in (0..50u64).rev().chain(100..150) {
+        insert(&mut t, k.to_be_bytes(), (k + 1).to_be_bytes());
+    }
+    for k in (0..150u64).step_by(3) {
+        t.delete(&k.to_be_bytes());
+    }
+
+    // the shape of the tree, and therefore its root hash, depends on the order of the insertions
+    // and deletions
+    let sorted_tree: TreeOfBytes = t.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+    assert_ne!(sorted_tree.root_hash(), t.root_hash());
+
+    let bytes = serde_cbor::to_vec(&t).unwrap();
+    let mut restored_tree: TreeOfBytes = serde_cbor::from_slice(&bytes).unwrap();
+    assert_eq!(restored_tree.root_hash(), t.root_hash());
+    assert_eq!(restored_tree, t);
+
+    for k in (200..210u64).chain(0..10) {
+        insert(&mut t, k.to_be_bytes(), k.to_be_bytes());
+        insert(&mut restored_tree, k.to_be_bytes(), k.to_be_bytes());
+    }
+    t.delete(&101u64.to_be_bytes());
+    restored_tree.delete(&101u64.to_be_bytes());
+    assert_eq!(restored_tree.root_hash(), t.root_hash());
+
+    let empty_tree: TreeOfBytes =
+        serde_cbor::from_slice(&serde_cbor::to_vec(&TreeOfBytes::new()).unwrap()).unwrap();
+    assert_eq!(empty_tree.root_hash(), TreeOfBytes::new().root_hash());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_deserialize_invalid_tree() {
+    use serde_bytes::ByteBuf;
+
+    let node = |k: &str, is_red: bool| (ByteBuf::from(k.as_bytes()), vec![1u8], is_red);
+    let invalid_trees = [
+        // red root
+        vec![node("b", true)],
+        // not in pre-order
+        vec![node("b", false), node("c", false), node("a", false)],
+        // duplicate key
+        vec![node("b", false), node("b", true)],
+        // unequal black height
+        vec![node("a", false), node("b", false)],
+        // red right child
+        vec![node("a", false), node("b", true)],
+        // red child of a red node
+        vec![node("c", false), node("b", true), node("a", true)],
+    ];
+
+    for invalid_tree in invalid_trees {
+        let bytes = serde_cbor::to_vec(&invalid_tree).unwrap();
+        assert!(serde_cbor::from_slice::<TreeOfBytes>(&bytes).is_err());
+    }
+}
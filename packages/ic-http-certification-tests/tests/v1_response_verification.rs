@@ -2,7 +2,9 @@ mod tests {
     use ic_certificate_verification::CertificateVerificationError;
     use ic_certification_testing::{CertificateBuilder, CertificateData};
     use ic_http_certification::{HttpRequest, HttpResponse, CERTIFICATE_HEADER_NAME};
-    use ic_response_verification::types::{VerificationInfo, VerifiedResponse};
+    use ic_response_verification::types::{
+        FixedTimeSource, TimeConfig, VerificationInfo, VerificationPolicy, VerifiedResponse,
+    };
     use ic_response_verification::verify_request_response_pair;
     use ic_response_verification::ResponseVerificationError;
     use ic_response_verification_test_utils::{
@@ -56,10 +58,11 @@ mod tests {
             request,
             response,
             canister_id.as_ref(),
-            current_time,
-            MAX_CERT_TIME_OFFSET_NS,
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
             &root_key,
             MIN_REQUESTED_VERIFICATION_VERSION,
+            &VerificationPolicy::default(),
         )
         .unwrap();
 
@@ -68,6 +71,7 @@ mod tests {
             VerificationInfo {
                 verification_version,
                 response,
+                ..
             } if verification_version == 1 && response == Some(expected_response)
         ));
     }
@@ -114,10 +118,11 @@ mod tests {
             request,
             response,
             canister_id.as_ref(),
-            current_time,
-            MAX_CERT_TIME_OFFSET_NS,
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
             &root_key,
             MIN_REQUESTED_VERIFICATION_VERSION,
+            &VerificationPolicy::default(),
         )
         .unwrap();
 
@@ -126,6 +131,7 @@ mod tests {
             VerificationInfo {
                 verification_version,
                 response,
+                ..
             } if verification_version == 1 && response == Some(expected_response)
         ));
     }
@@ -171,10 +177,11 @@ mod tests {
             request,
             response,
             canister_id.as_ref(),
-            current_time,
-            MAX_CERT_TIME_OFFSET_NS,
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
             &root_key,
             MIN_REQUESTED_VERIFICATION_VERSION,
+            &VerificationPolicy::default(),
         )
         .unwrap();
 
@@ -183,6 +190,7 @@ mod tests {
             VerificationInfo {
                 verification_version,
                 response,
+                ..
             } if verification_version == 1 && response == Some(expected_response)
         ));
     }
@@ -223,15 +231,16 @@ mod tests {
             request,
             response,
             canister_id.as_ref(),
-            current_time,
-            MAX_CERT_TIME_OFFSET_NS,
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
             &root_key,
             MIN_REQUESTED_VERIFICATION_VERSION,
+            &VerificationPolicy::default(),
         );
 
         assert!(matches!(
             result,
-            Err(ResponseVerificationError::InvalidResponseBody)
+            Err(ResponseVerificationError::InvalidResponseBody { .. })
         ));
     }
 
@@ -272,10 +281,11 @@ mod tests {
             request,
             response,
             canister_id.as_ref(),
-            current_time,
-            MAX_CERT_TIME_OFFSET_NS,
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
             root_key,
             MIN_REQUESTED_VERIFICATION_VERSION,
+            &VerificationPolicy::default(),
         );
 
         assert!(matches!(
@@ -325,10 +335,11 @@ mod tests {
             request,
             response,
             canister_id.as_ref(),
-            current_time,
-            MAX_CERT_TIME_OFFSET_NS,
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
             &root_key,
             MIN_REQUESTED_VERIFICATION_VERSION,
+            &VerificationPolicy::default(),
         );
 
         assert!(matches!(
@@ -380,10 +391,11 @@ mod tests {
             request,
             response,
             canister_id.as_ref(),
-            current_time,
-            MAX_CERT_TIME_OFFSET_NS,
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
             &root_key,
             MIN_REQUESTED_VERIFICATION_VERSION,
+            &VerificationPolicy::default(),
         );
 
         assert!(matches!(
@@ -433,15 +445,16 @@ mod tests {
             request,
             response,
             canister_id.as_ref(),
-            current_time,
-            MAX_CERT_TIME_OFFSET_NS,
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
             &root_key,
             MIN_REQUESTED_VERIFICATION_VERSION,
+            &VerificationPolicy::default(),
         );
 
         assert!(matches!(
             result,
-            Err(ResponseVerificationError::InvalidTree)
+            Err(ResponseVerificationError::InvalidTree { .. })
         ));
     }
 
@@ -483,15 +496,16 @@ mod tests {
             request,
             response,
             canister_id.as_ref(),
-            current_time,
-            MAX_CERT_TIME_OFFSET_NS,
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
             &root_key,
             MIN_REQUESTED_VERIFICATION_VERSION,
+            &VerificationPolicy::default(),
         );
 
         assert!(matches!(
             result,
-            Err(ResponseVerificationError::InvalidTree)
+            Err(ResponseVerificationError::InvalidTree { .. })
         ));
     }
 
@@ -531,15 +545,16 @@ mod tests {
             request,
             response,
             canister_id.as_ref(),
-            current_time,
-            MAX_CERT_TIME_OFFSET_NS,
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
             &root_key,
             MIN_REQUESTED_VERIFICATION_VERSION,
+            &VerificationPolicy::default(),
         );
 
         assert!(matches!(
             result,
-            Err(ResponseVerificationError::InvalidResponseBody)
+            Err(ResponseVerificationError::InvalidResponseBody { .. })
         ));
     }
 
@@ -579,10 +594,11 @@ mod tests {
             request,
             response,
             canister_id.as_ref(),
-            current_time,
-            MAX_CERT_TIME_OFFSET_NS,
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
             &root_key,
             2,
+            &VerificationPolicy::default(),
         );
 
         assert!(matches!(
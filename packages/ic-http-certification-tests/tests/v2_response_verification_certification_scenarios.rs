@@ -11,15 +11,20 @@ mod tests {
         etag_caching_mismatch_certification, etag_caching_mismatch_request,
         etag_caching_mismatch_response, etag_certificate_tree, index_html_certification,
         index_js_certification, index_js_response, not_found_certification, not_found_response,
-        redirect_certification, redirect_response,
+        redirect_cel, redirect_certification, redirect_response,
     };
     use ic_http_certification::{
-        HttpCertificationPath, HttpCertificationTree, HttpCertificationTreeEntry, HttpRequest,
-        HttpResponse, CERTIFICATE_HEADER_NAME,
+        HttpCertification, HttpCertificationPath, HttpCertificationTree,
+        HttpCertificationTreeEntry, HttpRequest, HttpResponse, CERTIFICATE_HEADER_NAME,
     };
     use ic_response_verification::{
-        types::{VerificationInfo, VerifiedResponse},
-        verify_request_response_pair, ResponseVerificationError,
+        types::{
+            CertificateCache, FixedTimeSource, TimeConfig, VerificationInfo, VerificationPolicy,
+            VerifiedResponse,
+        },
+        verify_request_response_pair, verify_request_response_pair_streaming,
+        verify_request_response_pair_with_cache, verify_request_response_pairs,
+        ResponseVerificationError, ResponseVerificationResult,
     };
     use ic_response_verification_test_utils::{
         cbor_encode, create_v2_certificate_fixture, create_v2_header, get_current_timestamp,
@@ -100,10 +105,11 @@ mod tests {
             request,
             expected_response,
             canister_id.as_ref(),
-            current_time,
-            MAX_CERT_TIME_OFFSET_NS,
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
             &root_key,
             MIN_REQUESTED_VERIFICATION_VERSION,
+            &VerificationPolicy::default(),
         )
         .unwrap();
 
@@ -112,10 +118,347 @@ mod tests {
             VerificationInfo {
                 verification_version,
                 response,
+                ..
             } if verification_version == 2 && response == Some(expected_certified_response)
         ));
     }
 
+    #[rstest]
+    #[case::index_js_path(&"/js/index.js", index_js_response(), index_js_certification())]
+    #[case::gzip_encoding(&"/multi-encoded-path", content_encoding_gzip_response(), content_encoding_gzip_certification())]
+    #[case::deflate_encoding(&"/multi-encoded-path", content_encoding_deflate_response(), content_encoding_deflate_certification())]
+    fn streamed_body_passes_verification(
+        #[from(certificate_tree)] certification_tree: HttpCertificationTree,
+        #[case] req_path: &str,
+        #[case] mut expected_response: HttpResponse,
+        #[case] certification_tree_entry: HttpCertificationTreeEntry<'static>,
+        #[values(1, 7, 1024)] chunk_size: usize,
+    ) {
+        let request = HttpRequest::get(req_path).build();
+
+        let current_time = get_current_timestamp();
+
+        let V2CertificateFixture {
+            root_key,
+            canister_id,
+            certificate_cbor,
+        } = create_v2_certificate_fixture(&certification_tree.root_hash(), &current_time);
+        let certificate_header = create_v2_header(
+            &certification_tree_entry,
+            &certificate_cbor,
+            &cbor_encode(
+                &certification_tree
+                    .witness(&certification_tree_entry, req_path)
+                    .unwrap(),
+            ),
+        );
+
+        expected_response.add_header((CERTIFICATE_HEADER_NAME.to_string(), certificate_header));
+        let body = expected_response.body().to_vec();
+
+        let mut body_verifier = verify_request_response_pair_streaming(
+            &request,
+            &expected_response,
+            canister_id.as_ref(),
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
+            &root_key,
+            MIN_REQUESTED_VERIFICATION_VERSION,
+            &VerificationPolicy::default(),
+        )
+        .unwrap();
+        for chunk in body.chunks(chunk_size) {
+            body_verifier.update(chunk).unwrap();
+        }
+        let result = body_verifier.finalize().unwrap();
+
+        assert_eq!(result.verification_version, 2);
+        assert!(result.response.unwrap().body.is_empty());
+    }
+
+    #[rstest]
+    fn streamed_body_with_mismatched_chunk_fails_verification(
+        #[from(certificate_tree)] certification_tree: HttpCertificationTree,
+    ) {
+        let req_path = "/js/index.js";
+        let request = HttpRequest::get(req_path).build();
+        let mut expected_response = index_js_response();
+        let certification_tree_entry = index_js_certification();
+
+        let current_time = get_current_timestamp();
+
+        let V2CertificateFixture {
+            root_key,
+            canister_id,
+            certificate_cbor,
+        } = create_v2_certificate_fixture(&certification_tree.root_hash(), &current_time);
+        let certificate_header = create_v2_header(
+            &certification_tree_entry,
+            &certificate_cbor,
+            &cbor_encode(
+                &certification_tree
+                    .witness(&certification_tree_entry, req_path)
+                    .unwrap(),
+            ),
+        );
+
+        expected_response.add_header((CERTIFICATE_HEADER_NAME.to_string(), certificate_header));
+        let mut body = expected_response.body().to_vec();
+        body.push(b'!');
+
+        let mut body_verifier = verify_request_response_pair_streaming(
+            &request,
+            &expected_response,
+            canister_id.as_ref(),
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
+            &root_key,
+            MIN_REQUESTED_VERIFICATION_VERSION,
+            &VerificationPolicy::default(),
+        )
+        .unwrap();
+        for chunk in body.chunks(7) {
+            body_verifier.update(chunk).unwrap();
+        }
+        let result = body_verifier.finalize();
+
+        assert!(matches!(
+            result,
+            Err(ResponseVerificationError::InvalidResponseHashes { .. })
+        ));
+    }
+
+    #[rstest]
+    fn certified_redirect_passes_verification(
+        #[from(certificate_tree)] certification_tree: HttpCertificationTree,
+    ) {
+        let policy = VerificationPolicy::new().with_certified_redirects(true);
+
+        let result = verify_redirect(
+            &certification_tree,
+            &redirect_certification(),
+            "/old-path",
+            &policy,
+        )
+        .unwrap();
+
+        assert_eq!(result.response.unwrap().status_code, Some(301));
+    }
+
+    #[rstest]
+    fn wildcard_permanent_redirect_fails_verification() {
+        let certification_tree_entry = HttpCertificationTreeEntry::new(
+            HttpCertificationPath::wildcard("/old-path"),
+            HttpCertification::response_only(&redirect_cel(), &redirect_response(), None).unwrap(),
+        );
+        let mut certification_tree = HttpCertificationTree::default();
+        certification_tree.insert(&certification_tree_entry);
+
+        let result = verify_redirect(
+            &certification_tree,
+            &certification_tree_entry,
+            "/old-path/nested",
+            &VerificationPolicy::default(),
+        );
+        assert!(result.is_ok());
+
+        let result = verify_redirect(
+            &certification_tree,
+            &certification_tree_entry,
+            "/old-path/nested",
+            &VerificationPolicy::new().with_certified_redirects(true),
+        );
+        assert!(matches!(
+            result,
+            Err(ResponseVerificationError::WildcardPermanentRedirect {
+                status_code: 301,
+                ..
+            })
+        ));
+    }
+
+    #[rstest]
+    fn cached_certificate_passes_verification_repeatedly(
+        #[from(certificate_tree)] certification_tree: HttpCertificationTree,
+    ) {
+        let req_path = "/js/index.js";
+        let certification_tree_entry = index_js_certification();
+        let current_time = get_current_timestamp();
+
+        let V2CertificateFixture {
+            root_key,
+            canister_id,
+            certificate_cbor,
+        } = create_v2_certificate_fixture(&certification_tree.root_hash(), &current_time);
+        let certificate_header = create_v2_header(
+            &certification_tree_entry,
+            &certificate_cbor,
+            &cbor_encode(
+                &certification_tree
+                    .witness(&certification_tree_entry, req_path)
+                    .unwrap(),
+            ),
+        );
+
+        let mut cache = CertificateCache::new(10);
+        for _ in 0..3 {
+            let mut response = index_js_response();
+            response.add_header((
+                CERTIFICATE_HEADER_NAME.to_string(),
+                certificate_header.clone(),
+            ));
+
+            let result = verify_request_response_pair_with_cache(
+                HttpRequest::get(req_path).build(),
+                response,
+                canister_id.as_ref(),
+                &FixedTimeSource::new(current_time),
+                &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
+                &root_key,
+                MIN_REQUESTED_VERIFICATION_VERSION,
+                &VerificationPolicy::default(),
+                &mut cache,
+            )
+            .unwrap();
+
+            assert_eq!(result.verification_version, 2);
+        }
+
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[rstest]
+    fn batch_with_shared_certificate_verifies_each_pair(
+        #[from(certificate_tree)] certification_tree: HttpCertificationTree,
+    ) {
+        let current_time = get_current_timestamp();
+
+        let V2CertificateFixture {
+            root_key,
+            canister_id,
+            certificate_cbor,
+        } = create_v2_certificate_fixture(&certification_tree.root_hash(), &current_time);
+
+        let pairs = [
+            ("/", index_html_response(), index_html_certification()),
+            (
+                "/js/index.js",
+                index_js_response(),
+                index_js_certification(),
+            ),
+            // the index html fallback response is not accepted inside the js folder
+            (
+                "/js/not-found",
+                index_html_response(),
+                index_html_certification(),
+            ),
+        ]
+        .into_iter()
+        .map(|(req_path, mut response, certification_tree_entry)| {
+            let certificate_header = create_v2_header(
+                &certification_tree_entry,
+                &certificate_cbor,
+                &cbor_encode(
+                    &certification_tree
+                        .witness(&certification_tree_entry, req_path)
+                        .unwrap(),
+                ),
+            );
+            response.add_header((CERTIFICATE_HEADER_NAME.to_string(), certificate_header));
+
+            (HttpRequest::get(req_path).build(), response)
+        });
+
+        let results = verify_request_response_pairs(
+            pairs,
+            canister_id.as_ref(),
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
+            &root_key,
+            MIN_REQUESTED_VERIFICATION_VERSION,
+            &VerificationPolicy::default(),
+        );
+
+        assert_eq!(results.len(), 3);
+        assert!(matches!(
+            &results[0],
+            Ok(VerificationInfo {
+                verification_version: 2,
+                response: Some(_),
+                ..
+            })
+        ));
+        assert!(matches!(
+            &results[1],
+            Ok(VerificationInfo {
+                verification_version: 2,
+                response: Some(_),
+                ..
+            })
+        ));
+        assert!(matches!(
+            &results[2],
+            Err(ResponseVerificationError::ExactExpressionPathMightExistInTree { .. })
+        ));
+    }
+
+    #[rstest]
+    fn batch_with_invalid_certificate_fails_verification_of_each_pair(
+        #[from(certificate_tree)] certification_tree: HttpCertificationTree,
+    ) {
+        let current_time = get_current_timestamp();
+
+        let V2CertificateFixture {
+            canister_id,
+            certificate_cbor,
+            ..
+        } = create_v2_certificate_fixture(&certification_tree.root_hash(), &current_time);
+        let V2CertificateFixture {
+            root_key: other_root_key,
+            ..
+        } = create_v2_certificate_fixture(&certification_tree.root_hash(), &current_time);
+
+        let pairs = [
+            ("/", index_html_response(), index_html_certification()),
+            (
+                "/js/index.js",
+                index_js_response(),
+                index_js_certification(),
+            ),
+        ]
+        .into_iter()
+        .map(|(req_path, mut response, certification_tree_entry)| {
+            let certificate_header = create_v2_header(
+                &certification_tree_entry,
+                &certificate_cbor,
+                &cbor_encode(
+                    &certification_tree
+                        .witness(&certification_tree_entry, req_path)
+                        .unwrap(),
+                ),
+            );
+            response.add_header((CERTIFICATE_HEADER_NAME.to_string(), certificate_header));
+
+            (HttpRequest::get(req_path).build(), response)
+        });
+
+        let results = verify_request_response_pairs(
+            pairs,
+            canister_id.as_ref(),
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
+            &other_root_key,
+            MIN_REQUESTED_VERIFICATION_VERSION,
+            &VerificationPolicy::default(),
+        );
+
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|result| matches!(
+            result,
+            Err(ResponseVerificationError::CertificateVerificationFailed(_))
+        )));
+    }
+
     #[rstest]
     // assert that the index html fallback response is not accepted for assets inside the js folder
     #[case::index_html_for_js_not_found_path(&"/js/not-found", index_html_response(), index_html_certification())]
@@ -159,10 +502,11 @@ mod tests {
             request,
             expected_response,
             canister_id.as_ref(),
-            current_time,
-            MAX_CERT_TIME_OFFSET_NS,
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
             &root_key,
             MIN_REQUESTED_VERIFICATION_VERSION,
+            &VerificationPolicy::default(),
         );
 
         assert!(matches!(
@@ -214,10 +558,11 @@ mod tests {
             request,
             expected_response,
             canister_id.as_ref(),
-            current_time,
-            MAX_CERT_TIME_OFFSET_NS,
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
             &root_key,
             MIN_REQUESTED_VERIFICATION_VERSION,
+            &VerificationPolicy::default(),
         );
 
         assert!(matches!(
@@ -281,10 +626,11 @@ mod tests {
             request,
             expected_response,
             canister_id.as_ref(),
-            current_time,
-            MAX_CERT_TIME_OFFSET_NS,
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
             &root_key,
             MIN_REQUESTED_VERIFICATION_VERSION,
+            &VerificationPolicy::default(),
         );
 
         assert!(matches!(
@@ -336,10 +682,11 @@ mod tests {
             request,
             expected_response,
             canister_id.as_ref(),
-            current_time,
-            MAX_CERT_TIME_OFFSET_NS,
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
             &root_key,
             MIN_REQUESTED_VERIFICATION_VERSION,
+            &VerificationPolicy::default(),
         );
 
         assert!(matches!(
@@ -411,10 +758,11 @@ mod tests {
             request,
             expected_response,
             canister_id.as_ref(),
-            current_time,
-            MAX_CERT_TIME_OFFSET_NS,
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
             &root_key,
             MIN_REQUESTED_VERIFICATION_VERSION,
+            &VerificationPolicy::default(),
         )
         .unwrap();
 
@@ -423,6 +771,7 @@ mod tests {
             VerificationInfo {
                 verification_version,
                 response,
+                ..
             } if verification_version == 2 && response == Some(expected_certified_response)
         ));
     }
@@ -459,17 +808,56 @@ mod tests {
             request,
             expected_response,
             canister_id.as_ref(),
-            current_time,
-            MAX_CERT_TIME_OFFSET_NS,
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
             &root_key,
             MIN_REQUESTED_VERIFICATION_VERSION,
+            &VerificationPolicy::default(),
         );
 
         assert!(matches!(
             result,
-            Err(ResponseVerificationError::InvalidResponseHashes)
+            Err(ResponseVerificationError::InvalidResponseHashes { .. })
         ));
     }
+
+    fn verify_redirect(
+        certification_tree: &HttpCertificationTree,
+        certification_tree_entry: &HttpCertificationTreeEntry,
+        req_path: &str,
+        policy: &VerificationPolicy,
+    ) -> ResponseVerificationResult<VerificationInfo> {
+        let current_time = get_current_timestamp();
+
+        let V2CertificateFixture {
+            root_key,
+            canister_id,
+            certificate_cbor,
+        } = create_v2_certificate_fixture(&certification_tree.root_hash(), &current_time);
+        let certificate_header = create_v2_header(
+            certification_tree_entry,
+            &certificate_cbor,
+            &cbor_encode(
+                &certification_tree
+                    .witness(certification_tree_entry, req_path)
+                    .unwrap(),
+            ),
+        );
+
+        let mut response = redirect_response();
+        response.add_header((CERTIFICATE_HEADER_NAME.to_string(), certificate_header));
+
+        verify_request_response_pair(
+            HttpRequest::get(req_path).build(),
+            response,
+            canister_id.as_ref(),
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
+            &root_key,
+            MIN_REQUESTED_VERIFICATION_VERSION,
+            policy,
+        )
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
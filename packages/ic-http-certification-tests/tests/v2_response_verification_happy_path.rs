@@ -5,11 +5,13 @@ mod tests {
         CERTIFICATE_HEADER_NAME,
     };
     use ic_response_verification::{
-        types::{VerificationInfo, VerifiedResponse},
-        verify_request_response_pair,
+        types::{
+            FixedTimeSource, TimeConfig, VerificationInfo, VerificationPolicy, VerifiedResponse,
+        },
+        verify_request_response_pair, ResponseVerificationError,
     };
     use ic_response_verification_test_utils::{
-        create_v2_fixture, get_current_timestamp, V2Fixture,
+        create_v2_fixture, create_v2_tree_fixture, get_current_timestamp, V2Fixture,
     };
 
     const MAX_CERT_TIME_OFFSET_NS: u128 = 300_000_000_000;
@@ -52,10 +54,11 @@ mod tests {
             request,
             response,
             canister_id.as_ref(),
-            current_time,
-            MAX_CERT_TIME_OFFSET_NS,
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
             &root_key,
             MIN_REQUESTED_VERIFICATION_VERSION,
+            &VerificationPolicy::default(),
         )
         .unwrap();
 
@@ -64,10 +67,60 @@ mod tests {
             VerificationInfo {
                 verification_version,
                 response,
+                ..
             } if verification_version == 2 && response.is_none()
         ));
     }
 
+    #[test]
+    fn verification_info_includes_certification() {
+        let req_path = "/";
+        let body = "Hello World!";
+        let current_time = get_current_timestamp();
+        let certification_path = HttpCertificationPath::exact("/");
+        let cel_expr = DefaultCelBuilder::skip_certification();
+
+        let request = HttpRequest::get(req_path).build();
+        let mut response = HttpResponse::ok(
+            body.as_bytes(),
+            vec![(
+                CERTIFICATE_EXPRESSION_HEADER_NAME.into(),
+                cel_expr.to_string(),
+            )],
+        )
+        .build();
+
+        let certification = HttpCertification::skip();
+        let certification_tree_entry =
+            HttpCertificationTreeEntry::new(&certification_path, certification);
+
+        let V2Fixture {
+            root_key,
+            certificate_header,
+            canister_id,
+        } = create_v2_fixture(req_path, &certification_tree_entry, &current_time);
+        let certified_data =
+            create_v2_tree_fixture(req_path, &certification_tree_entry).certified_data;
+
+        response.add_header((CERTIFICATE_HEADER_NAME.to_string(), certificate_header));
+
+        let result = verify_request_response_pair(
+            request,
+            response,
+            canister_id.as_ref(),
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
+            &root_key,
+            MIN_REQUESTED_VERIFICATION_VERSION,
+            &VerificationPolicy::default(),
+        )
+        .unwrap();
+
+        assert_eq!(result.certification.tree.digest(), certified_data);
+        assert!(result.certification.certificate.delegation.is_some());
+        assert!(result.certification.delegation_subnet_id().is_some());
+    }
+
     #[test]
     fn no_request_certification_passes_verification() {
         let req_path = "/";
@@ -113,10 +166,11 @@ mod tests {
             request,
             response,
             canister_id.as_ref(),
-            current_time,
-            MAX_CERT_TIME_OFFSET_NS,
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
             &root_key,
             MIN_REQUESTED_VERIFICATION_VERSION,
+            &VerificationPolicy::default(),
         )
         .unwrap();
 
@@ -138,10 +192,97 @@ mod tests {
             VerificationInfo {
                 verification_version,
                 response,
+                ..
             } if verification_version == 2 && response == Some(expected_response)
         ));
     }
 
+    #[test]
+    fn ignored_response_headers_pass_verification() {
+        let req_path = "/";
+        let body = "Hello World!";
+        let current_time = get_current_timestamp();
+        let certification_path = HttpCertificationPath::exact("/");
+
+        let cel_expr = DefaultCelBuilder::response_only_certification()
+            .with_response_certification(DefaultResponseCertification::response_header_exclusions(
+                vec![],
+            ))
+            .build();
+
+        let request = HttpRequest::get(req_path).build();
+        let mut response = HttpResponse::ok(
+            body.as_bytes(),
+            vec![
+                (
+                    CERTIFICATE_EXPRESSION_HEADER_NAME.into(),
+                    cel_expr.to_string(),
+                ),
+                ("Cache-Control".into(), "max-age=604800".into()),
+            ],
+        )
+        .build();
+
+        let certification = HttpCertification::response_only(&cel_expr, &response, None).unwrap();
+        let certification_tree_entry =
+            HttpCertificationTreeEntry::new(&certification_path, certification);
+
+        let V2Fixture {
+            root_key,
+            certificate_header,
+            canister_id,
+        } = create_v2_fixture(req_path, &certification_tree_entry, &current_time);
+
+        response.add_header((
+            CERTIFICATE_HEADER_NAME.to_string(),
+            certificate_header.clone(),
+        ));
+        response.add_header(("X-Request-Id".into(), "1234".into()));
+
+        let result = verify_request_response_pair(
+            request.clone(),
+            response.clone(),
+            canister_id.as_ref(),
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
+            &root_key,
+            MIN_REQUESTED_VERIFICATION_VERSION,
+            &VerificationPolicy::default(),
+        );
+
+        assert!(matches!(
+            result,
+            Err(ResponseVerificationError::InvalidResponseHashes { .. })
+        ));
+
+        let result = verify_request_response_pair(
+            request,
+            response,
+            canister_id.as_ref(),
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
+            &root_key,
+            MIN_REQUESTED_VERIFICATION_VERSION,
+            &VerificationPolicy::new().with_ignored_response_headers(vec!["x-request-id"]),
+        )
+        .unwrap();
+
+        let expected_response = VerifiedResponse {
+            status_code: Some(200),
+            body: body.as_bytes().to_vec(),
+            headers: vec![
+                (
+                    CERTIFICATE_EXPRESSION_HEADER_NAME.to_lowercase(),
+                    cel_expr.to_string(),
+                ),
+                ("cache-control".into(), "max-age=604800".into()),
+                (CERTIFICATE_HEADER_NAME.into(), certificate_header),
+            ],
+        };
+
+        assert_eq!(result.response, Some(expected_response));
+    }
+
     #[test]
     fn full_certification_passes_verification() {
         let req_path = "/?q=greeting";
@@ -194,10 +335,11 @@ mod tests {
             request,
             response,
             canister_id.as_ref(),
-            current_time,
-            MAX_CERT_TIME_OFFSET_NS,
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
             &root_key,
             MIN_REQUESTED_VERIFICATION_VERSION,
+            &VerificationPolicy::default(),
         )
         .unwrap();
 
@@ -219,6 +361,7 @@ mod tests {
             VerificationInfo {
                 verification_version,
                 response,
+                ..
             } if verification_version == 2 && response == Some(expected_response)
         ));
     }
@@ -270,10 +413,11 @@ mod tests {
             request,
             response,
             canister_id.as_ref(),
-            current_time,
-            MAX_CERT_TIME_OFFSET_NS,
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
             &root_key,
             MIN_REQUESTED_VERIFICATION_VERSION,
+            &VerificationPolicy::default(),
         )
         .unwrap();
 
@@ -296,6 +440,7 @@ mod tests {
             VerificationInfo {
                 verification_version,
                 response,
+                ..
             } if verification_version == 2 && response == Some(expected_response)
         ));
     }
@@ -0,0 +1,243 @@
+mod tests {
+    use super::fixtures::{
+        cel_expr, response_only_cel_expr, router, MAX_CERT_TIME_OFFSET_NS,
+        MIN_REQUESTED_VERIFICATION_VERSION,
+    };
+    use ic_http_certification::{
+        DefaultFullCelExpression, DefaultResponseOnlyCelExpression, ErrorResponseCatalog,
+        HttpCertificationError, HttpCertificationResult, HttpCertificationRouter, HttpRequest,
+        HttpResponse, Method, StatusCode,
+    };
+    use ic_response_verification::{
+        types::{FixedTimeSource, TimeConfig, VerificationPolicy},
+        verify_request_response_pair,
+    };
+    use ic_response_verification_test_utils::{
+        create_v2_certificate_fixture, get_current_timestamp, V2CertificateFixture,
+    };
+    use rstest::*;
+
+    #[rstest]
+    #[case::exact_path(HttpRequest::get("/todos").build(), Some(StatusCode::OK))]
+    #[case::scoped_error_response(HttpRequest::get("/todos/1").build(), Some(StatusCode::NOT_FOUND))]
+    #[case::scoped_fallback(HttpRequest::get("/users/1").build(), Some(StatusCode::NOT_FOUND))]
+    #[case::root_fallback(HttpRequest::get("/health").build(), Some(StatusCode::OK))]
+    // a response certified for the request path under another method shadows all scopes
+    #[case::exact_path_with_other_method(HttpRequest::post("/todos").build(), None)]
+    fn served_query_passes_verification(
+        router: HttpCertificationRouter,
+        #[case] request: HttpRequest,
+        #[case] expected_status_code: Option<StatusCode>,
+    ) {
+        assert_verifiable_response(
+            &router,
+            request,
+            |request, data_certificate| router.serve_query(request, data_certificate),
+            expected_status_code,
+        );
+    }
+
+    #[rstest]
+    #[case::scoped(HttpRequest::get("/todos/1").build(), StatusCode::TOO_MANY_REQUESTS, true)]
+    #[case::root(HttpRequest::get("/health").build(), StatusCode::TOO_MANY_REQUESTS, true)]
+    #[case::root_only_status_code(HttpRequest::get("/health").build(), StatusCode::IM_A_TEAPOT, true)]
+    // an exact certification of the request path shadows all scopes
+    #[case::exact_path(HttpRequest::get("/todos").build(), StatusCode::TOO_MANY_REQUESTS, false)]
+    #[case::exact_path_with_other_method(HttpRequest::post("/todos").build(), StatusCode::TOO_MANY_REQUESTS, false)]
+    // a more specific scope shadows the error responses of the root scope
+    #[case::more_specific_fallback(HttpRequest::get("/users/1").build(), StatusCode::TOO_MANY_REQUESTS, false)]
+    #[case::more_specific_scope_without_status_code(HttpRequest::get("/todos/1").build(), StatusCode::IM_A_TEAPOT, false)]
+    fn served_error_passes_verification(
+        router: HttpCertificationRouter,
+        #[case] request: HttpRequest,
+        #[case] status_code: StatusCode,
+        #[case] is_served: bool,
+    ) {
+        assert_verifiable_response(
+            &router,
+            request,
+            |request, data_certificate| router.serve_error(request, status_code, data_certificate),
+            is_served.then_some(status_code),
+        );
+    }
+
+    #[rstest]
+    fn removed_fallback_keeps_identical_error_response(
+        mut router: HttpCertificationRouter,
+        response_only_cel_expr: DefaultResponseOnlyCelExpression<'static>,
+    ) {
+        // the fallback of the scope is identical to the `404 Not Found` error response
+        router
+            .certify_error_responses(
+                "/users",
+                &ErrorResponseCatalog::new().with_response(
+                    HttpResponse::not_found(b"users fallback".to_vec(), vec![]).build(),
+                ),
+                &response_only_cel_expr,
+            )
+            .unwrap();
+        router.remove_fallback_response("/users");
+
+        let request = HttpRequest::get("/users/1").build();
+        assert_verifiable_response(
+            &router,
+            request,
+            |request, data_certificate| router.serve_query(request, data_certificate),
+            Some(StatusCode::NOT_FOUND),
+        );
+    }
+
+    #[rstest]
+    fn removed_variant_keeps_identical_variant(
+        mut router: HttpCertificationRouter,
+        cel_expr: DefaultFullCelExpression<'static>,
+    ) {
+        let request = HttpRequest::get("/users").build();
+        for variant in ["application/json", "text/plain"] {
+            router
+                .certify_response_variant(
+                    &request,
+                    variant,
+                    HttpResponse::ok(b"[]".to_vec(), vec![]).build(),
+                    &cel_expr,
+                )
+                .unwrap();
+        }
+        router.remove_response_variant(&Method::GET, "/users", "application/json");
+
+        assert_verifiable_response(
+            &router,
+            request,
+            |request, data_certificate| {
+                router.serve_query_variant(request, "text/plain", data_certificate)
+            },
+            Some(StatusCode::OK),
+        );
+    }
+
+    /// Asserts that the response served by the router either passes verification with the
+    /// expected status code or is not served at all, so that the router never serves a response
+    /// that the HTTP Gateway would reject.
+    fn assert_verifiable_response(
+        router: &HttpCertificationRouter,
+        request: HttpRequest,
+        serve: impl FnOnce(&HttpRequest, &[u8]) -> HttpCertificationResult<HttpResponse<'static>>,
+        expected_status_code: Option<StatusCode>,
+    ) {
+        let current_time = get_current_timestamp();
+
+        let V2CertificateFixture {
+            root_key,
+            canister_id,
+            certificate_cbor,
+        } = create_v2_certificate_fixture(&router.root_hash(), &current_time);
+        let result = serve(&request, &certificate_cbor);
+
+        let Some(expected_status_code) = expected_status_code else {
+            assert!(matches!(
+                result,
+                Err(HttpCertificationError::WitnessEntryNotFound { .. })
+            ));
+            return;
+        };
+
+        let result = verify_request_response_pair(
+            request,
+            result.unwrap(),
+            canister_id.as_ref(),
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
+            &root_key,
+            MIN_REQUESTED_VERIFICATION_VERSION,
+            &VerificationPolicy::default(),
+        )
+        .unwrap();
+
+        assert_eq!(result.verification_version, 2);
+        assert_eq!(
+            result.response.unwrap().status_code,
+            Some(expected_status_code.as_u16())
+        );
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod fixtures {
+    use ic_http_certification::{
+        DefaultCelBuilder, DefaultFullCelExpression, DefaultResponseCertification,
+        DefaultResponseOnlyCelExpression, ErrorResponseCatalog, HttpCertificationRouter,
+        HttpRequest, HttpResponse, StatusCode,
+    };
+    use rstest::*;
+
+    pub const MAX_CERT_TIME_OFFSET_NS: u128 = 300_000_000_000;
+    pub const MIN_REQUESTED_VERIFICATION_VERSION: u8 = 2;
+
+    #[fixture]
+    pub fn cel_expr() -> DefaultFullCelExpression<'static> {
+        DefaultCelBuilder::full_certification()
+            .with_response_certification(DefaultResponseCertification::response_header_exclusions(
+                vec![],
+            ))
+            .build()
+    }
+
+    #[fixture]
+    pub fn response_only_cel_expr() -> DefaultResponseOnlyCelExpression<'static> {
+        DefaultCelBuilder::response_only_certification()
+            .with_response_certification(DefaultResponseCertification::response_header_exclusions(
+                vec![],
+            ))
+            .build()
+    }
+
+    #[fixture]
+    pub fn router(
+        cel_expr: DefaultFullCelExpression<'static>,
+        response_only_cel_expr: DefaultResponseOnlyCelExpression<'static>,
+    ) -> HttpCertificationRouter {
+        let mut router = HttpCertificationRouter::default();
+        router
+            .certify_response(
+                &HttpRequest::get("/todos").build(),
+                HttpResponse::ok(b"[]".to_vec(), vec![]).build(),
+                &cel_expr,
+            )
+            .unwrap();
+        router
+            .certify_fallback_response(
+                "",
+                HttpResponse::ok(b"<html></html>".to_vec(), vec![]).build(),
+                &response_only_cel_expr,
+            )
+            .unwrap();
+        router
+            .certify_error_responses(
+                "",
+                &ErrorResponseCatalog::new().with_response(
+                    HttpResponse::builder()
+                        .with_status_code(StatusCode::IM_A_TEAPOT)
+                        .with_body(b"I'm a teapot".to_vec())
+                        .build(),
+                ),
+                &response_only_cel_expr,
+            )
+            .unwrap();
+        router
+            .certify_error_responses(
+                "/todos",
+                &ErrorResponseCatalog::new(),
+                &response_only_cel_expr,
+            )
+            .unwrap();
+        router
+            .certify_fallback_response(
+                "/users",
+                HttpResponse::not_found(b"users fallback".to_vec(), vec![]).build(),
+                &response_only_cel_expr,
+            )
+            .unwrap();
+
+        router
+    }
+}
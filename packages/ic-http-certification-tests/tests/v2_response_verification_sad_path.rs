@@ -11,7 +11,10 @@ mod tests {
         HttpCertificationTreeEntry, HttpRequest, HttpResponse, CERTIFICATE_EXPRESSION_HEADER_NAME,
         CERTIFICATE_HEADER_NAME,
     };
-    use ic_response_verification::{verify_request_response_pair, ResponseVerificationError};
+    use ic_response_verification::{
+        types::{FixedTimeSource, TimeConfig, VerificationPolicy},
+        verify_request_response_pair, ResponseHashesMismatch, ResponseVerificationError,
+    };
     use ic_response_verification_test_utils::{
         create_v2_certificate_fixture, create_v2_fixture, create_v2_header, create_v2_tree_fixture,
         get_current_timestamp, V2CertificateFixture, V2Fixture, V2TreeFixture,
@@ -67,15 +70,23 @@ mod tests {
             wrong_request,
             response,
             canister_id.as_ref(),
-            current_time,
-            fixtures::MAX_CERT_TIME_OFFSET_NS,
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(fixtures::MAX_CERT_TIME_OFFSET_NS),
             &root_key,
             fixtures::MIN_REQUESTED_VERIFICATION_VERSION,
+            &VerificationPolicy::default(),
         );
 
+        let expected_request_hashes = vec![hex::encode(certification.request_hash().unwrap())];
         assert!(matches!(
             result,
-            Err(ResponseVerificationError::InvalidResponseHashes)
+            Err(ResponseVerificationError::InvalidResponseHashes {
+                mismatch: ResponseHashesMismatch::RequestHashNotFound {
+                    certified_request_hashes,
+                    ..
+                },
+                ..
+            }) if certified_request_hashes == expected_request_hashes
         ));
     }
 
@@ -134,15 +145,130 @@ mod tests {
             request,
             wrong_response,
             canister_id.as_ref(),
-            current_time,
-            MAX_CERT_TIME_OFFSET_NS,
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
+            &root_key,
+            MIN_REQUESTED_VERIFICATION_VERSION,
+            &VerificationPolicy::default(),
+        );
+
+        let expected_response_hashes = vec![hex::encode(certification.response_hash().unwrap())];
+        assert!(matches!(
+            result,
+            Err(ResponseVerificationError::InvalidResponseHashes {
+                mismatch: ResponseHashesMismatch::ResponseHashNotFound {
+                    certified_response_hashes,
+                    ..
+                },
+                ..
+            }) if certified_response_hashes == expected_response_hashes
+        ));
+    }
+
+    #[rstest]
+    fn uncertified_required_response_header_fails_verification(
+        #[from(full_certification_cel)] cel_expr: DefaultFullCelExpression<'static>,
+    ) {
+        let req_path = "/?q=greeting";
+        let body = "Hello World!";
+        let current_time = get_current_timestamp();
+        let certification_path = HttpCertificationPath::exact("/");
+
+        let request = HttpRequest::get(req_path)
+            .with_headers(vec![("Cache-Control".into(), "no-cache".into())])
+            .build();
+        let mut response = HttpResponse::ok(
+            body.as_bytes(),
+            vec![
+                (
+                    CERTIFICATE_EXPRESSION_HEADER_NAME.into(),
+                    cel_expr.to_string(),
+                ),
+                ("Cache-Control".into(), "max-age=604800".into()),
+                ("Content-Type".into(), "text/plain".into()),
+            ],
+        )
+        .build();
+
+        let certification = HttpCertification::full(&cel_expr, &request, &response, None).unwrap();
+        let certification_tree_entry =
+            HttpCertificationTreeEntry::new(&certification_path, certification);
+
+        let V2Fixture {
+            root_key,
+            certificate_header,
+            canister_id,
+        } = create_v2_fixture(req_path, &certification_tree_entry, &current_time);
+
+        response.add_header((CERTIFICATE_HEADER_NAME.to_string(), certificate_header));
+
+        let policy = VerificationPolicy::new()
+            .with_required_certified_response_headers(vec!["Cache-Control", "Content-Type"]);
+        let result = verify_request_response_pair(
+            request,
+            response,
+            canister_id.as_ref(),
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
             &root_key,
             MIN_REQUESTED_VERIFICATION_VERSION,
+            &policy,
+        );
+
+        assert!(matches!(
+            result,
+            Err(ResponseVerificationError::UncertifiedResponseHeader { header_name }) if header_name == "Content-Type"
+        ));
+    }
+
+    #[rstest]
+    fn certification_above_max_version_fails_verification(
+        #[from(skip_certification_cel)] cel_expr: CelExpression<'static>,
+    ) {
+        let req_path = "/";
+        let current_time = get_current_timestamp();
+        let certification_path = HttpCertificationPath::exact(req_path);
+
+        let request = HttpRequest::get(req_path).build();
+        let mut response = HttpResponse::ok(
+            b"Hello World!",
+            vec![(
+                CERTIFICATE_EXPRESSION_HEADER_NAME.into(),
+                cel_expr.to_string(),
+            )],
+        )
+        .build();
+
+        let certification_tree_entry =
+            HttpCertificationTreeEntry::new(&certification_path, HttpCertification::skip());
+
+        let V2Fixture {
+            root_key,
+            certificate_header,
+            canister_id,
+        } = create_v2_fixture(req_path, &certification_tree_entry, &current_time);
+
+        response.add_header((CERTIFICATE_HEADER_NAME.to_string(), certificate_header));
+
+        let result = verify_request_response_pair(
+            request,
+            response,
+            canister_id.as_ref(),
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
+            &root_key,
+            1,
+            &VerificationPolicy::new().with_max_verification_version(1),
         );
 
         assert!(matches!(
             result,
-            Err(ResponseVerificationError::InvalidResponseHashes)
+            Err(
+                ResponseVerificationError::RequestedVerificationVersionAboveMaximum {
+                    max_verification_version: 1,
+                    requested_version: 2,
+                }
+            )
         ));
     }
 
@@ -203,10 +329,11 @@ mod tests {
             request,
             response,
             canister_id.as_ref(),
-            current_time,
-            MAX_CERT_TIME_OFFSET_NS,
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
             &root_key,
             MIN_REQUESTED_VERIFICATION_VERSION,
+            &VerificationPolicy::default(),
         );
 
         assert!(matches!(
@@ -271,10 +398,11 @@ mod tests {
             request,
             response,
             canister_id.as_ref(),
-            current_time,
-            MAX_CERT_TIME_OFFSET_NS,
+            &FixedTimeSource::new(current_time),
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
             &root_key,
             MIN_REQUESTED_VERIFICATION_VERSION,
+            &VerificationPolicy::default(),
         );
 
         assert!(
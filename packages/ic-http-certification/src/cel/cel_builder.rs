@@ -1,9 +1,28 @@
 use super::{
     CelExpression, DefaultCelExpression, DefaultFullCelExpression, DefaultRequestCertification,
-    DefaultResponseCertification, DefaultResponseOnlyCelExpression,
+    DefaultResponseCertification, DefaultResponseCertificationType,
+    DefaultResponseOnlyCelExpression,
+};
+use crate::{
+    HttpCertificationError, HttpCertificationResult, CERTIFICATE_EXPRESSION_HEADER_NAME,
+    CERTIFICATE_HEADER_NAME,
 };
 use std::borrow::Cow;
 
+/// Headers that cannot be certified, because they are hop-by-hop headers that are removed by
+/// the HTTP gateway, or because they are always excluded from certification.
+const UNCERTIFIABLE_HEADER_NAMES: &[&str] = &[
+    CERTIFICATE_HEADER_NAME,
+    "connection",
+    "keep-alive",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+];
+
 /// A CEL expression builder for creating a default certification expression.
 #[derive(Debug, Clone)]
 pub struct DefaultCelBuilder {}
@@ -57,6 +76,17 @@ impl<'a> DefaultResponseOnlyCelBuilder<'a> {
             response: self.response_certification,
         }
     }
+
+    /// Build the CEL expression, consuming the builder, and validate it.
+    ///
+    /// Returns an error if a header is listed more than once, if a header that cannot be certified
+    /// is certified, or if the `IC-CertificateExpression` header is excluded from certification.
+    /// Such expressions would otherwise fail verification by the HTTP gateway.
+    pub fn try_build(self) -> HttpCertificationResult<DefaultResponseOnlyCelExpression<'a>> {
+        validate_response_certification(&self.response_certification)?;
+
+        Ok(self.build())
+    }
 }
 
 /// A CEL expression builder for creating expressions that will certify both the request and response.
@@ -72,7 +102,13 @@ impl<'a> DefaultFullCelExpressionBuilder<'a> {
     /// Configure the request headers that will be included in certification.
     ///
     /// As many or as little headers can be provided as desired.
-    /// Providing an empty list, or not calling this method, will result in no request query parameters being certified.
+    /// Providing an empty list, or not calling this method, will result in no request headers being certified.
+    ///
+    /// Unlike response headers, request headers can only be included in certification, there is no
+    /// equivalent of [response_header_exclusions](DefaultResponseCertification::response_header_exclusions).
+    /// The HTTP Gateway Protocol does not define an exclusion list for request headers, because the
+    /// headers of a request are added and modified by browsers, proxies and the HTTP Gateway itself,
+    /// so a request that certifies "all headers except X" would not be verifiable.
     pub fn with_request_headers(mut self, headers: impl Into<Cow<'a, [&'a str]>>) -> Self {
         self.request_headers = headers.into();
 
@@ -115,12 +151,80 @@ impl<'a> DefaultFullCelExpressionBuilder<'a> {
             response: self.response_certification,
         }
     }
+
+    /// Build the CEL expression, consuming the builder, and validate it.
+    ///
+    /// Returns an error if a header or query parameter is listed more than once, if a header that
+    /// cannot be certified is certified, or if the `IC-CertificateExpression` header is excluded
+    /// from certification. Such expressions would otherwise fail verification by the HTTP gateway.
+    pub fn try_build(self) -> HttpCertificationResult<DefaultFullCelExpression<'a>> {
+        validate_unique_names(&self.request_headers)?;
+        validate_certifiable_headers(&self.request_headers)?;
+        validate_unique_names(&self.request_query_parameters)?;
+        validate_response_certification(&self.response_certification)?;
+
+        Ok(self.build())
+    }
+}
+
+fn validate_response_certification(
+    response_certification: &DefaultResponseCertification,
+) -> HttpCertificationResult {
+    match response_certification.get_type() {
+        DefaultResponseCertificationType::CertifiedResponseHeaders(headers) => {
+            validate_unique_names(headers)?;
+            validate_certifiable_headers(headers)
+        }
+        DefaultResponseCertificationType::ResponseHeaderExclusions(headers) => {
+            validate_unique_names(headers)?;
+
+            if headers
+                .iter()
+                .any(|header| header.eq_ignore_ascii_case(CERTIFICATE_EXPRESSION_HEADER_NAME))
+            {
+                return Err(HttpCertificationError::CertificateExpressionHeaderExcluded);
+            }
+
+            Ok(())
+        }
+    }
+}
+
+fn validate_unique_names(names: &[&str]) -> HttpCertificationResult {
+    for (i, name) in names.iter().enumerate() {
+        if names[..i]
+            .iter()
+            .any(|other_name| other_name.eq_ignore_ascii_case(name))
+        {
+            return Err(HttpCertificationError::DuplicateCelExpressionName {
+                name: name.to_string(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_certifiable_headers(headers: &[&str]) -> HttpCertificationResult {
+    let uncertifiable_header = headers.iter().find(|header| {
+        UNCERTIFIABLE_HEADER_NAMES
+            .iter()
+            .any(|uncertifiable_header| uncertifiable_header.eq_ignore_ascii_case(header))
+    });
+
+    match uncertifiable_header {
+        Some(header) => Err(HttpCertificationError::UncertifiableHeader {
+            header_name: header.to_string(),
+        }),
+        None => Ok(()),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::cel::fixtures::*;
+    use assert_matches::assert_matches;
     use rstest::*;
 
     #[rstest]
@@ -328,4 +432,83 @@ mod tests {
         assert_eq!(implicit_cel_expr, empty_request_response_exclusions_cel);
         assert_eq!(explicit_cel_expr, empty_request_response_exclusions_cel);
     }
+
+    #[rstest]
+    fn try_build_valid() {
+        let cel_expr = DefaultCelBuilder::full_certification()
+            .with_request_headers(vec!["Accept", "Accept-Encoding"])
+            .with_request_query_parameters(vec!["foo", "bar"])
+            .with_response_certification(DefaultResponseCertification::response_header_exclusions(
+                vec!["Date", "Set-Cookie"],
+            ))
+            .try_build()
+            .unwrap();
+
+        assert_eq!(
+            cel_expr,
+            DefaultCelBuilder::full_certification()
+                .with_request_headers(vec!["Accept", "Accept-Encoding"])
+                .with_request_query_parameters(vec!["foo", "bar"])
+                .with_response_certification(
+                    DefaultResponseCertification::response_header_exclusions(vec![
+                        "Date",
+                        "Set-Cookie"
+                    ],)
+                )
+                .build()
+        );
+    }
+
+    #[rstest]
+    fn try_build_duplicate_names() {
+        assert_matches!(
+            DefaultCelBuilder::full_certification()
+                .with_request_headers(vec!["Accept", "accept"])
+                .try_build(),
+            Err(HttpCertificationError::DuplicateCelExpressionName { name }) if name == "accept"
+        );
+        assert_matches!(
+            DefaultCelBuilder::full_certification()
+                .with_request_query_parameters(vec!["foo", "foo"])
+                .try_build(),
+            Err(HttpCertificationError::DuplicateCelExpressionName { name }) if name == "foo"
+        );
+        assert_matches!(
+            DefaultCelBuilder::response_only_certification()
+                .with_response_certification(DefaultResponseCertification::certified_response_headers(
+                    vec!["ETag", "Cache-Control", "ETAG"],
+                ))
+                .try_build(),
+            Err(HttpCertificationError::DuplicateCelExpressionName { name }) if name == "ETAG"
+        );
+    }
+
+    #[rstest]
+    fn try_build_uncertifiable_headers() {
+        assert_matches!(
+            DefaultCelBuilder::full_certification()
+                .with_request_headers(vec!["Accept", "Connection"])
+                .try_build(),
+            Err(HttpCertificationError::UncertifiableHeader { header_name }) if header_name == "Connection"
+        );
+        assert_matches!(
+            DefaultCelBuilder::response_only_certification()
+                .with_response_certification(DefaultResponseCertification::certified_response_headers(
+                    vec!["IC-Certificate"],
+                ))
+                .try_build(),
+            Err(HttpCertificationError::UncertifiableHeader { header_name }) if header_name == "IC-Certificate"
+        );
+        assert_matches!(
+            DefaultCelBuilder::response_only_certification()
+                .with_response_certification(
+                    DefaultResponseCertification::response_header_exclusions(vec![
+                        "Date",
+                        "ic-certificateexpression"
+                    ],)
+                )
+                .try_build(),
+            Err(HttpCertificationError::CertificateExpressionHeaderExcluded)
+        );
+    }
 }
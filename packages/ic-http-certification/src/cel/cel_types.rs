@@ -2,6 +2,7 @@ use super::{
     create_cel_expr, create_default_cel_expr, create_default_full_cel_expr,
     create_default_response_only_cel_expr,
 };
+use crate::{HttpCertificationError, HttpCertificationResult, HttpResponse};
 use std::{
     borrow::Cow,
     fmt::{Display, Formatter},
@@ -9,8 +10,15 @@ use std::{
 
 /// A certification CEL expression defintion.
 /// Contains an enum variant for each CEL function supported for certification.
-/// Currently only one variant is supported: [CelExpression::Default].
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Currently only one function is supported: [CelExpression::Default]. Expressions using functions
+/// that are not supported yet are carried as [CelExpression::Unknown], so that more variants can be
+/// added as the HTTP certification protocol evolves.
+///
+/// Two expressions are equal if they certify the same [HttpRequest](crate::HttpRequest) and
+/// [HttpResponse](crate::HttpResponse) properties, regardless of the order, casing or duplication of
+/// header and query parameter names, see [canonicalize](CelExpression::canonicalize).
+#[derive(Debug, Clone)]
+#[non_exhaustive]
 pub enum CelExpression<'a> {
     /// A certification CEL expression definition that uses the `default_certification` function.
     /// This is currently the only supported function.
@@ -19,6 +27,12 @@ pub enum CelExpression<'a> {
     /// Providing [None] will opt out of certification, while providing [Some] will opt in to certification.
     /// See [DefaultCelExpression] for more details on its available parameters.
     Default(DefaultCelExpression<'a>),
+
+    /// A certification CEL expression that uses a function that is not supported by this version of
+    /// the library, such as a function added by a later version of the HTTP certification protocol.
+    ///
+    /// The enum's inner value is the [String] representation of the expression, which is used as is.
+    Unknown(Cow<'a, str>),
 }
 
 impl Display for CelExpression<'_> {
@@ -27,6 +41,172 @@ impl Display for CelExpression<'_> {
     }
 }
 
+impl<'a> CelExpression<'a> {
+    /// Returns the canonical form of this expression, where header and query parameter names are
+    /// sorted and deduplicated, ignoring their casing. The casing of the names is preserved.
+    /// [Unknown](CelExpression::Unknown) expressions can not be interpreted, so only their
+    /// whitespace is removed.
+    ///
+    /// The order, casing and duplication of these names does not affect certification, so two
+    /// expressions are [equal](PartialEq) if their canonical forms only differ in the casing of names.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{
+    ///     DefaultCelBuilder,
+    ///     cel::{CelExpression, DefaultCelExpression},
+    /// };
+    ///
+    /// let cel_expr = CelExpression::Default(DefaultCelExpression::Full(
+    ///     DefaultCelBuilder::full_certification()
+    ///         .with_request_headers(vec!["Accept", "Accept-Encoding", "accept"])
+    ///         .build(),
+    /// ));
+    /// let canonical_cel_expr = CelExpression::Default(DefaultCelExpression::Full(
+    ///     DefaultCelBuilder::full_certification()
+    ///         .with_request_headers(vec!["Accept", "Accept-Encoding"])
+    ///         .build(),
+    /// ));
+    ///
+    /// assert_eq!(
+    ///     cel_expr.canonicalize().to_string(),
+    ///     canonical_cel_expr.to_string()
+    /// );
+    /// assert_eq!(cel_expr, canonical_cel_expr);
+    /// ```
+    pub fn canonicalize(&self) -> CelExpression<'a> {
+        let cel_expr = match self {
+            CelExpression::Default(cel_expr) => cel_expr,
+            CelExpression::Unknown(cel_expr) => {
+                return CelExpression::Unknown(remove_whitespace(cel_expr).into());
+            }
+        };
+
+        let cel_expr = match cel_expr {
+            DefaultCelExpression::Skip => DefaultCelExpression::Skip,
+            DefaultCelExpression::ResponseOnly(cel_expr) => {
+                DefaultCelExpression::ResponseOnly(DefaultResponseOnlyCelExpression {
+                    response: canonical_response_certification(&cel_expr.response),
+                })
+            }
+            DefaultCelExpression::Full(cel_expr) => {
+                DefaultCelExpression::Full(DefaultFullCelExpression {
+                    request: DefaultRequestCertification::new(
+                        canonical_names(&cel_expr.request.headers),
+                        canonical_names(&cel_expr.request.query_parameters),
+                    ),
+                    response: canonical_response_certification(&cel_expr.response),
+                })
+            }
+        };
+
+        CelExpression::Default(cel_expr)
+    }
+}
+
+impl PartialEq for CelExpression<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        let (lhs, rhs) = match (self, other) {
+            (CelExpression::Default(lhs), CelExpression::Default(rhs)) => (lhs, rhs),
+            (CelExpression::Unknown(lhs), CelExpression::Unknown(rhs)) => {
+                return remove_whitespace(lhs) == remove_whitespace(rhs);
+            }
+            _ => return false,
+        };
+
+        match (lhs, rhs) {
+            (DefaultCelExpression::Skip, DefaultCelExpression::Skip) => true,
+            (DefaultCelExpression::ResponseOnly(lhs), DefaultCelExpression::ResponseOnly(rhs)) => {
+                response_certifications_eq(&lhs.response, &rhs.response)
+            }
+            (DefaultCelExpression::Full(lhs), DefaultCelExpression::Full(rhs)) => {
+                names_eq(&lhs.request.headers, &rhs.request.headers)
+                    && names_eq(&lhs.request.query_parameters, &rhs.request.query_parameters)
+                    && response_certifications_eq(&lhs.response, &rhs.response)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Eq for CelExpression<'_> {}
+
+/// Removes whitespace from a CEL expression, except for whitespace within strings.
+fn remove_whitespace(cel_expr: &str) -> String {
+    let mut result = String::with_capacity(cel_expr.len());
+    let mut is_in_string = false;
+    let mut is_escaped = false;
+
+    for c in cel_expr.chars() {
+        if is_in_string {
+            match c {
+                _ if is_escaped => is_escaped = false,
+                '\\' => is_escaped = true,
+                '"' => is_in_string = false,
+                _ => {}
+            }
+        } else if c == '"' {
+            is_in_string = true;
+        } else if c.is_whitespace() {
+            continue;
+        }
+
+        result.push(c);
+    }
+
+    result
+}
+
+fn canonical_names<'a>(names: &[&'a str]) -> Vec<&'a str> {
+    let mut names = names.to_vec();
+    names.sort_by_key(|name| name.to_ascii_lowercase());
+    names.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+
+    names
+}
+
+fn canonical_response_certification<'a>(
+    response_certification: &DefaultResponseCertification<'a>,
+) -> DefaultResponseCertification<'a> {
+    match response_certification.get_type() {
+        DefaultResponseCertificationType::CertifiedResponseHeaders(headers) => {
+            DefaultResponseCertification::certified_response_headers(canonical_names(headers))
+        }
+        DefaultResponseCertificationType::ResponseHeaderExclusions(headers) => {
+            DefaultResponseCertification::response_header_exclusions(canonical_names(headers))
+        }
+    }
+}
+
+fn names_eq(lhs: &[&str], rhs: &[&str]) -> bool {
+    let lhs = canonical_names(lhs);
+    let rhs = canonical_names(rhs);
+
+    lhs.len() == rhs.len()
+        && lhs
+            .iter()
+            .zip(rhs.iter())
+            .all(|(lhs, rhs)| lhs.eq_ignore_ascii_case(rhs))
+}
+
+fn response_certifications_eq(
+    lhs: &DefaultResponseCertification,
+    rhs: &DefaultResponseCertification,
+) -> bool {
+    match (lhs.get_type(), rhs.get_type()) {
+        (
+            DefaultResponseCertificationType::CertifiedResponseHeaders(lhs),
+            DefaultResponseCertificationType::CertifiedResponseHeaders(rhs),
+        )
+        | (
+            DefaultResponseCertificationType::ResponseHeaderExclusions(lhs),
+            DefaultResponseCertificationType::ResponseHeaderExclusions(rhs),
+        ) => names_eq(lhs, rhs),
+        _ => false,
+    }
+}
+
 /// A certification CEL expression definition that uses the default CEL function.
 ///
 /// This enum has three variants:
@@ -170,6 +350,71 @@ impl<'a> DefaultResponseCertification<'a> {
         ))
     }
 
+    /// Returns whether the response header with the given name is certified, comparing header
+    /// names case-insensitively.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::DefaultResponseCertification;
+    ///
+    /// let response_certification = DefaultResponseCertification::certified_response_headers(vec!["ETag"]);
+    /// assert!(response_certification.is_header_certified("etag"));
+    /// assert!(!response_certification.is_header_certified("Cache-Control"));
+    ///
+    /// let response_certification = DefaultResponseCertification::response_header_exclusions(vec!["Date"]);
+    /// assert!(response_certification.is_header_certified("Cache-Control"));
+    /// assert!(!response_certification.is_header_certified("date"));
+    /// ```
+    pub fn is_header_certified(&self, header_name: &str) -> bool {
+        match &self.0 {
+            DefaultResponseCertificationType::CertifiedResponseHeaders(headers_to_include) => {
+                headers_to_include
+                    .iter()
+                    .any(|header_to_include| header_to_include.eq_ignore_ascii_case(header_name))
+            }
+            DefaultResponseCertificationType::ResponseHeaderExclusions(headers_to_exclude) => {
+                !headers_to_exclude
+                    .iter()
+                    .any(|header_to_exclude| header_to_exclude.eq_ignore_ascii_case(header_name))
+            }
+        }
+    }
+
+    /// Validates that every header included in certification with
+    /// [certified_response_headers](DefaultResponseCertification::certified_response_headers)
+    /// is present in the given response.
+    ///
+    /// A certified header that is missing from the response is certified as absent, which is
+    /// usually a mistake rather than the intent. Header exclusions are always valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{DefaultResponseCertification, HttpCertificationError, HttpResponse};
+    ///
+    /// let response_certification = DefaultResponseCertification::certified_response_headers(vec!["ETag"]);
+    /// let response = HttpResponse::ok(b"Hello, World!", vec![]).build();
+    ///
+    /// assert!(matches!(
+    ///     response_certification.validate_against(&response),
+    ///     Err(HttpCertificationError::CertifiedResponseHeaderMissing { header_name }) if header_name == "ETag"
+    /// ));
+    /// ```
+    pub fn validate_against(&self, response: &HttpResponse) -> HttpCertificationResult {
+        match &self.0 {
+            DefaultResponseCertificationType::CertifiedResponseHeaders(headers) => headers
+                .iter()
+                .find(|header_name| response.get_header(header_name).is_none())
+                .map_or(Ok(()), |header_name| {
+                    Err(HttpCertificationError::CertifiedResponseHeaderMissing {
+                        header_name: header_name.to_string(),
+                    })
+                }),
+            DefaultResponseCertificationType::ResponseHeaderExclusions(_) => Ok(()),
+        }
+    }
+
     pub(crate) fn get_type(&self) -> &DefaultResponseCertificationType<'a> {
         &self.0
     }
@@ -180,3 +425,141 @@ impl Default for DefaultResponseCertification<'_> {
         Self::certified_response_headers(vec![])
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    fn full_cel_expr<'a>(
+        request_headers: &'a [&'a str],
+        query_parameters: &'a [&'a str],
+        response: DefaultResponseCertification<'a>,
+    ) -> CelExpression<'a> {
+        CelExpression::Default(DefaultCelExpression::Full(DefaultFullCelExpression {
+            request: DefaultRequestCertification::new(request_headers, query_parameters),
+            response,
+        }))
+    }
+
+    #[rstest]
+    fn response_certification_validate_against() {
+        let response =
+            HttpResponse::ok(b"Hello, World!", vec![("etag".into(), "\"1\"".into())]).build();
+
+        assert!(
+            DefaultResponseCertification::certified_response_headers(vec!["ETag"])
+                .validate_against(&response)
+                .is_ok()
+        );
+        assert!(
+            DefaultResponseCertification::certified_response_headers(vec!["ETag", "Vary"])
+                .validate_against(&response)
+                .is_err()
+        );
+        assert!(
+            DefaultResponseCertification::response_header_exclusions(vec!["Vary"])
+                .validate_against(&response)
+                .is_ok()
+        );
+    }
+
+    #[rstest]
+    fn canonicalize_cel_expression() {
+        let cel_expr = full_cel_expr(
+            &["If-None-Match", "Accept", "accept"],
+            &["b", "a"],
+            DefaultResponseCertification::certified_response_headers(vec!["ETag", "Cache-Control"]),
+        );
+
+        assert_eq!(
+            cel_expr.canonicalize().to_string(),
+            full_cel_expr(
+                &["Accept", "If-None-Match"],
+                &["a", "b"],
+                DefaultResponseCertification::certified_response_headers(vec![
+                    "Cache-Control",
+                    "ETag"
+                ]),
+            )
+            .to_string()
+        );
+    }
+
+    #[rstest]
+    fn cel_expression_semantic_equality() {
+        let cel_expr = full_cel_expr(
+            &["Accept", "If-None-Match"],
+            &["a", "b"],
+            DefaultResponseCertification::response_header_exclusions(vec!["Date"]),
+        );
+
+        assert_eq!(
+            cel_expr,
+            full_cel_expr(
+                &["if-none-match", "ACCEPT", "Accept"],
+                &["b", "a"],
+                DefaultResponseCertification::response_header_exclusions(vec!["date", "Date"]),
+            )
+        );
+        assert_ne!(
+            cel_expr,
+            full_cel_expr(
+                &["Accept"],
+                &["a", "b"],
+                DefaultResponseCertification::response_header_exclusions(vec!["Date"]),
+            )
+        );
+        assert_ne!(
+            cel_expr,
+            full_cel_expr(
+                &["Accept", "If-None-Match"],
+                &["a", "b"],
+                DefaultResponseCertification::certified_response_headers(vec!["Date"]),
+            )
+        );
+        assert_ne!(
+            cel_expr,
+            CelExpression::Default(DefaultCelExpression::ResponseOnly(
+                DefaultResponseOnlyCelExpression {
+                    response: DefaultResponseCertification::response_header_exclusions(vec![
+                        "Date"
+                    ]),
+                }
+            ))
+        );
+        assert_eq!(
+            CelExpression::Default(DefaultCelExpression::Skip),
+            CelExpression::Default(DefaultCelExpression::Skip)
+        );
+    }
+
+    #[rstest]
+    fn unknown_cel_expression() {
+        let cel_expr = CelExpression::Unknown(
+            r#"future_certification(ValidationArgs { headers: ["Accept", "X Custom"] })"#.into(),
+        );
+
+        assert_eq!(
+            cel_expr.to_string(),
+            r#"future_certification(ValidationArgs { headers: ["Accept", "X Custom"] })"#
+        );
+        assert_eq!(
+            cel_expr.canonicalize().to_string(),
+            r#"future_certification(ValidationArgs{headers:["Accept","X Custom"]})"#
+        );
+        assert_eq!(
+            cel_expr,
+            CelExpression::Unknown(
+                r#"future_certification(ValidationArgs{headers:["Accept","X Custom"]})"#.into()
+            )
+        );
+        assert_ne!(
+            cel_expr,
+            CelExpression::Unknown(
+                r#"future_certification(ValidationArgs{headers:["Accept","XCustom"]})"#.into()
+            )
+        );
+        assert_ne!(cel_expr, CelExpression::Default(DefaultCelExpression::Skip));
+    }
+}
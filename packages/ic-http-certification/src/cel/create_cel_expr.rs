@@ -11,6 +11,7 @@ use super::{
 pub fn create_cel_expr(certification: &CelExpression) -> String {
     match certification {
         CelExpression::Default(certification) => create_default_cel_expr(certification),
+        CelExpression::Unknown(certification) => certification.to_string(),
     }
 }
 
@@ -11,6 +11,26 @@ pub enum HttpCertificationError {
     #[error(r#"Failed to parse url: "{0}""#)]
     MalformedUrl(String),
 
+    /// The HTTP method was not a valid token and could not be parsed.
+    #[error(r#"Failed to parse method: "{0}""#)]
+    MalformedMethod(String),
+
+    /// The path of the URL was rejected when parsed in
+    /// [strict](crate::UrlParsingMode::Strict) mode.
+    #[error(r#"The path of the url "{url}" is not allowed: {reason}"#)]
+    DisallowedUrlPath {
+        /// The URL whose path was rejected.
+        url: String,
+
+        /// The reason that the path was rejected.
+        reason: UrlPathRejection,
+    },
+
+    /// Error serializing a JSON response body.
+    #[cfg(feature = "serde_json")]
+    #[error(r#"Error serializing JSON response body: "{0}""#)]
+    JsonSerializationError(#[from] serde_json::Error),
+
     /// Error converting UTF-8 string.
     #[error(r#"Error converting UTF8 string bytes: "{0}""#)]
     Utf8ConversionError(#[from] std::string::FromUtf8Error),
@@ -25,6 +45,41 @@ pub enum HttpCertificationError {
         request_path: String,
     },
 
+    /// The [HttpCertificationTreeEntry](crate::HttpCertificationTreeEntry) to generate a witness for
+    /// was not found in the [HttpCertificationTree](crate::HttpCertificationTree).
+    #[error(r#"No certification was found in the tree for request path "{request_path}""#)]
+    WitnessEntryNotFound {
+        /// The request path that the witness was requested for.
+        request_path: String,
+    },
+
+    /// A response verification version 1 operation was performed on an
+    /// [HttpCertificationTree](crate::HttpCertificationTree) that was not created with
+    /// [with_v1_certification](crate::HttpCertificationTree::with_v1_certification).
+    #[error(r#"Response verification version 1 certification is not enabled for this tree"#)]
+    V1CertificationNotEnabled,
+
+    /// The route template of an [HttpCertificationRoute](crate::HttpCertificationRoute) was not valid.
+    #[error(r#"Invalid route template "{template}": {reason}"#)]
+    InvalidRouteTemplate {
+        /// The route template that was not valid.
+        template: String,
+
+        /// The reason that the route template was not valid.
+        reason: String,
+    },
+
+    /// The request path did not match the route template of an
+    /// [HttpCertificationRoute](crate::HttpCertificationRoute).
+    #[error(r#"Request path "{request_path}" does not match route template "{template}""#)]
+    RequestPathDoesNotMatchRouteTemplate {
+        /// The request path that did not match the route template.
+        request_path: String,
+
+        /// The route template that the request path did not match.
+        template: String,
+    },
+
     /// The `IC-CertificateExpression` header in a response did not match the Cel expression used to certify the [HttpResponse](crate::HttpResponse).
     #[error(r#"The IC-CertificateExpression header in the response did not match the Cel expression used to certify the response. Expected: "{expected}", Actual: "{actual}""#)]
     CertificateExpressionHeaderMismatch {
@@ -49,10 +104,84 @@ pub enum HttpCertificationError {
         expected: String,
     },
 
+    /// The `IC-CertificateExpression` header of an [HttpResponse](crate::HttpResponse) did not
+    /// match the Cel expression of the [HttpCertification](crate::HttpCertification) it was
+    /// validated against, or the header was missing or contained multiple values.
+    #[error(r#"The IC-CertificateExpression header in the response does not match the Cel expression of the certification. Expected exactly one matching header, Actual: {actual:?}"#)]
+    CertificateExpressionHeaderDoesNotMatchCertification {
+        /// The values of the `IC-CertificateExpression` header in the response.
+        actual: Vec<String>,
+    },
+
+    /// A header certified by a CEL expression was missing from the
+    /// [HttpResponse](crate::HttpResponse).
+    #[error(r#"The certified header "{header_name}" was missing from the response"#)]
+    CertifiedResponseHeaderMissing {
+        /// The name of the certified header that was missing from the response.
+        header_name: String,
+    },
+
+    /// No subtree with the given key was inserted into a [CertifiedData](crate::CertifiedData).
+    #[error(r#"No subtree with the key "{key}" was found in the certified data"#)]
+    CertifiedDataSubtreeNotFound {
+        /// The key of the subtree that was not found.
+        key: String,
+    },
+
+    /// A witness did not match the root hash of the subtree of a
+    /// [CertifiedData](crate::CertifiedData) that it was combined with.
+    #[error(r#"The witness does not match the root hash of the subtree with the key "{key}" in the certified data"#)]
+    CertifiedDataWitnessMismatch {
+        /// The key of the subtree that the witness did not match.
+        key: String,
+    },
+
+    /// A header or query parameter name was listed more than once in a CEL expression.
+    #[error(r#"The name "{name}" is listed more than once in the CEL expression"#)]
+    DuplicateCelExpressionName {
+        /// The name that was listed more than once.
+        name: String,
+    },
+
+    /// A CEL expression certifies a header that cannot be certified, because it is removed by
+    /// the HTTP gateway or always excluded from certification.
+    #[error(r#"The header "{header_name}" cannot be certified, it is removed by the HTTP gateway or always excluded from certification"#)]
+    UncertifiableHeader {
+        /// The name of the header that cannot be certified.
+        header_name: String,
+    },
+
+    /// A CEL expression excludes the `IC-CertificateExpression` header from certification,
+    /// but this header is always certified.
+    #[error(r#"The IC-CertificateExpression header is always certified and cannot be excluded from certification"#)]
+    CertificateExpressionHeaderExcluded,
+
     /// Error converting a number into an HTTP status code.
     #[error(r#"Error converting number into HTTP status code: "{status_code}""#)]
     InvalidHttpStatusCode {
         /// The HTTP status code that was not recognized.
         status_code: u16,
     },
+
+    /// An [HttpResponse](crate::HttpResponse) that upgrades the request to an update call was
+    /// converted into an [HttpUpdateResponse](crate::HttpUpdateResponse).
+    #[error(r#"A response that upgrades the request to an update call cannot be used as the response to an update call"#)]
+    UnexpectedUpgradeResponse,
+}
+
+/// The reason that the path of a URL was rejected when parsed in
+/// [strict](crate::UrlParsingMode::Strict) mode.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UrlPathRejection {
+    /// The decoded path contains a backslash.
+    #[error("the path contains a backslash")]
+    Backslash,
+
+    /// The decoded path contains a control character.
+    #[error("the path contains a control character")]
+    ControlCharacter,
+
+    /// The path contains a `%` that is not followed by two hexadecimal digits.
+    #[error("the path contains an invalid percent-encoding")]
+    InvalidPercentEncoding,
 }
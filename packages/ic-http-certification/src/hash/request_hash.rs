@@ -1,6 +1,12 @@
 use super::Hash;
 use crate::{cel::DefaultRequestCertification, HttpCertificationResult, HttpRequest};
-use ic_representation_independent_hash::{hash, representation_independent_hash, Value};
+use ic_representation_independent_hash::{
+    hash, hash_chunks, representation_independent_hash, Value,
+};
+
+const REQUEST_METHOD_PSEUDO_HEADER_NAME: &str = ":ic-cert-method";
+
+const REQUEST_QUERY_PSEUDO_HEADER_NAME: &str = ":ic-cert-query";
 
 /// Calculates the
 /// [Representation Independent Hash](https://internetcomputer.org/docs/current/references/ic-interface-spec/#hash-of-map)
@@ -9,33 +15,145 @@ pub fn request_hash<'a>(
     request: &'a HttpRequest,
     request_certification: &'a DefaultRequestCertification,
 ) -> HttpCertificationResult<Hash> {
-    let mut filtered_headers = get_filtered_headers(request.headers(), request_certification);
+    request_hash_with_body_hash(request, request_certification, hash(request.body()))
+}
 
-    filtered_headers.push((
-        ":ic-cert-method".into(),
-        Value::String(request.method().to_string()),
-    ));
+/// Calculates the
+/// [Representation Independent Hash](https://internetcomputer.org/docs/current/references/ic-interface-spec/#hash-of-map)
+/// of an [HttpRequest] according to a CEL expression defined by [DefaultRequestCertification],
+/// in the same way as [request_hash], but with the request body provided in chunks.
+///
+/// This allows large request bodies, such as bodies kept in stable memory, to be hashed without
+/// copying them into a single contiguous buffer. The body of the `request` itself is ignored.
+///
+/// # Examples
+///
+/// ```
+/// use ic_http_certification::{request_hash, request_hash_streaming, DefaultCelBuilder, HttpRequest};
+///
+/// let cel_expr = DefaultCelBuilder::full_certification().build();
+/// let request = HttpRequest::post("/upload").with_body(b"Hello World!").build();
+/// let chunks: [&[u8]; 2] = [b"Hello ", b"World!"];
+///
+/// assert_eq!(
+///     request_hash_streaming(&request, &cel_expr.request, chunks).unwrap(),
+///     request_hash(&request, &cel_expr.request).unwrap(),
+/// );
+/// ```
+pub fn request_hash_streaming<'a>(
+    request: &HttpRequest,
+    request_certification: &DefaultRequestCertification,
+    body_chunks: impl IntoIterator<Item = &'a [u8]>,
+) -> HttpCertificationResult<Hash> {
+    request_hash_with_body_hash(request, request_certification, hash_chunks(body_chunks))
+}
 
+fn request_hash_with_body_hash(
+    request: &HttpRequest,
+    request_certification: &DefaultRequestCertification,
+    body_hash: Hash,
+) -> HttpCertificationResult<Hash> {
+    let filtered_headers = get_filtered_headers(request.headers(), request_certification);
     let filtered_query = request
         .get_query()?
         .and_then(|query| get_filtered_query(&query, request_certification));
-    if let Some(query_hash) = filtered_query {
-        filtered_headers.push((":ic-cert-query".into(), Value::String(query_hash)))
-    }
 
     let concatenated_hashes = [
-        representation_independent_hash(&filtered_headers),
-        hash(request.body()),
+        request_headers_hash(
+            &filtered_headers,
+            &request.method().to_string(),
+            filtered_query.as_deref(),
+        ),
+        body_hash,
     ]
     .concat();
 
     Ok(hash(concatenated_hashes.as_slice()))
 }
 
+/// A breakdown of the values that are fed into the
+/// [Representation Independent Hash](https://internetcomputer.org/docs/current/references/ic-interface-spec/#hash-of-map)
+/// of an [HttpRequest], as calculated by [request_hash_details].
+///
+/// This is intended for debugging requests that do not pass verification, by comparing the
+/// details calculated by the canister with those calculated from the request that the HTTP gateway
+/// received.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestHashDetails {
+    /// The method of the request, included in the hash as the `:ic-cert-method` pseudo header.
+    pub method: String,
+    /// The lowercased names and values of the headers that are included in the hash,
+    /// in the order that they appear in the request.
+    pub headers: Vec<(String, String)>,
+    /// The certified query parameters of the request, included in the hash as the `:ic-cert-query`
+    /// pseudo header, or `None` if no query parameters are certified.
+    pub query: Option<String>,
+    /// The body of the request.
+    pub body: Vec<u8>,
+    /// The hash of the method, headers and query parameters.
+    pub headers_hash: Hash,
+    /// The hash of the request body.
+    pub body_hash: Hash,
+    /// The hash of the request, as calculated by [request_hash].
+    pub hash: Hash,
+}
+
+/// Calculates the hash of an [HttpRequest] in the same way as [request_hash], returning a
+/// [RequestHashDetails] with the exact method, headers, query parameters and body that were fed
+/// into the hash.
+pub fn request_hash_details(
+    request: &HttpRequest,
+    request_certification: &DefaultRequestCertification,
+) -> HttpCertificationResult<RequestHashDetails> {
+    let method = request.method().to_string();
+    let headers = get_filtered_headers(request.headers(), request_certification);
+    let query = request
+        .get_query()?
+        .and_then(|query| get_filtered_query(&query, request_certification));
+    let headers_hash = request_headers_hash(&headers, &method, query.as_deref());
+    let body_hash = hash(request.body());
+
+    Ok(RequestHashDetails {
+        method,
+        headers,
+        query,
+        body: request.body().to_vec(),
+        headers_hash,
+        body_hash,
+        hash: hash([headers_hash, body_hash].concat().as_slice()),
+    })
+}
+
+fn request_headers_hash(headers: &[(String, String)], method: &str, query: Option<&str>) -> Hash {
+    let mut headers_to_hash: Vec<(String, Value)> = headers
+        .iter()
+        .map(|(header_name, header_value)| {
+            (
+                header_name.to_string(),
+                Value::String(String::from(header_value)),
+            )
+        })
+        .collect();
+
+    headers_to_hash.push((
+        REQUEST_METHOD_PSEUDO_HEADER_NAME.into(),
+        Value::String(method.to_string()),
+    ));
+
+    if let Some(query) = query {
+        headers_to_hash.push((
+            REQUEST_QUERY_PSEUDO_HEADER_NAME.into(),
+            Value::String(query.to_string()),
+        ));
+    }
+
+    representation_independent_hash(&headers_to_hash)
+}
+
 fn get_filtered_headers(
     headers: &[(String, String)],
     request_certification: &DefaultRequestCertification,
-) -> Vec<(String, Value)> {
+) -> Vec<(String, String)> {
     headers
         .iter()
         .filter_map(|(header_name, header_value)| {
@@ -53,7 +171,7 @@ fn get_filtered_headers(
 
             Some((
                 header_name.to_string().to_ascii_lowercase(),
-                Value::String(String::from(header_value)),
+                String::from(header_value),
             ))
         })
         .collect()
@@ -165,6 +283,63 @@ mod tests {
         assert_eq!(result, result_with_fragment);
     }
 
+    #[test]
+    fn request_hash_details_with_query() {
+        let request_certification =
+            DefaultRequestCertification::new(vec!["host"], vec!["q", "name"]);
+        let request =
+            create_request("https://ic0.app?q=hello+world&name=foo&name=bar&color=purple");
+
+        let result = request_hash_details(&request, &request_certification).unwrap();
+
+        assert_eq!(result.method, "POST");
+        assert_eq!(
+            result.headers,
+            vec![("host".to_string(), "https://ic0.app".to_string())]
+        );
+        assert_eq!(
+            result.query.as_deref(),
+            Some("q=hello+world&name=foo&name=bar")
+        );
+        assert_eq!(result.body, vec![0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(result.body_hash, hash(request.body()));
+        assert_eq!(
+            result.hash,
+            request_hash(&request, &request_certification).unwrap()
+        );
+    }
+
+    #[test]
+    fn request_hash_details_without_query() {
+        let request_certification = DefaultRequestCertification::new(vec!["host"], vec!["q"]);
+        let request = create_request("https://ic0.app");
+
+        let result = request_hash_details(&request, &request_certification).unwrap();
+
+        assert_eq!(result.query, None);
+        assert_eq!(
+            result.hash,
+            request_hash(&request, &request_certification).unwrap()
+        );
+    }
+
+    #[test]
+    fn request_hash_streaming_with_query() {
+        let request_certification =
+            DefaultRequestCertification::new(vec!["host"], vec!["q", "name"]);
+        let request =
+            create_request("https://ic0.app?q=hello+world&name=foo&name=bar&color=purple");
+
+        let result =
+            request_hash_streaming(&request, &request_certification, request.body().chunks(3))
+                .unwrap();
+
+        assert_eq!(
+            result,
+            request_hash(&request, &request_certification).unwrap()
+        );
+    }
+
     fn create_request(uri: &str) -> HttpRequest {
         HttpRequest::post(uri)
             .with_headers(vec![
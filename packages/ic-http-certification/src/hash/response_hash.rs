@@ -1,6 +1,8 @@
 use super::Hash;
-use crate::{cel::DefaultResponseCertificationType, DefaultResponseCertification, HttpResponse};
-use ic_representation_independent_hash::{hash, representation_independent_hash, Value};
+use crate::{DefaultResponseCertification, HttpResponse};
+use ic_representation_independent_hash::{
+    hash, hash_chunks, representation_independent_hash, Value,
+};
 
 /// The name of the IC-Certificate header.
 pub const CERTIFICATE_HEADER_NAME: &str = "IC-Certificate";
@@ -25,23 +27,6 @@ pub fn filter_response_headers(
     response: &HttpResponse,
     response_certification: &DefaultResponseCertification<'_>,
 ) -> ResponseHeaders {
-    let headers_filter: Box<dyn Fn(_) -> _> = match response_certification.get_type() {
-        DefaultResponseCertificationType::CertifiedResponseHeaders(headers_to_include) => {
-            Box::new(move |header_name: &String| {
-                headers_to_include.iter().any(|header_to_include| {
-                    header_to_include.eq_ignore_ascii_case(&header_name.to_string())
-                })
-            })
-        }
-        DefaultResponseCertificationType::ResponseHeaderExclusions(headers_to_exclude) => {
-            Box::new(move |header_name: &String| {
-                !headers_to_exclude.iter().any(|header_to_exclude| {
-                    header_to_exclude.eq_ignore_ascii_case(&header_name.to_string())
-                })
-            })
-        }
-    };
-
     let mut response_headers = ResponseHeaders {
         headers: vec![],
         certificate: None,
@@ -69,7 +54,7 @@ pub fn filter_response_headers(
                 ));
             }
 
-            if headers_filter(header_name) {
+            if response_certification.is_header_certified(header_name) {
                 return Some((
                     header_name.to_string().to_ascii_lowercase(),
                     String::from(header_value),
@@ -120,13 +105,98 @@ pub fn response_hash(
     let response_body_hash = response_body_hash.unwrap_or_else(|| hash(response.body()));
 
     let filtered_headers = filter_response_headers(response, response_certification);
-    let concatenated_hashes = [
-        response_headers_hash(&response.status_code().as_u16().into(), &filtered_headers),
-        response_body_hash,
-    ]
-    .concat();
+    let headers_hash =
+        response_headers_hash(&response.status_code().as_u16().into(), &filtered_headers);
 
-    hash(concatenated_hashes.as_slice())
+    combine_response_hashes(&headers_hash, &response_body_hash)
+}
+
+/// Calculates the
+/// [Representation Independent Hash](https://internetcomputer.org/docs/current/references/ic-interface-spec/#hash-of-map)
+/// of an [HttpResponse] according to a CEL expression defined by [DefaultResponseCertification],
+/// in the same way as [response_hash], but with the response body provided in chunks.
+///
+/// This allows large response bodies, such as bodies kept in stable memory, to be hashed without
+/// copying them into a single contiguous buffer. The body of the `response` itself is ignored.
+///
+/// # Examples
+///
+/// ```
+/// use ic_http_certification::{
+///     response_hash, response_hash_streaming, DefaultResponseCertification, HttpResponse,
+/// };
+///
+/// let response_certification = DefaultResponseCertification::response_header_exclusions(vec![]);
+/// let response = HttpResponse::ok(b"Hello World!", vec![]).build();
+/// let chunks: [&[u8]; 2] = [b"Hello ", b"World!"];
+///
+/// assert_eq!(
+///     response_hash_streaming(&response, &response_certification, chunks),
+///     response_hash(&response, &response_certification, None),
+/// );
+/// ```
+pub fn response_hash_streaming<'a>(
+    response: &HttpResponse,
+    response_certification: &DefaultResponseCertification,
+    body_chunks: impl IntoIterator<Item = &'a [u8]>,
+) -> Hash {
+    response_hash(
+        response,
+        response_certification,
+        Some(hash_chunks(body_chunks)),
+    )
+}
+
+/// Combines the hash of the headers and status code of a response, as calculated by
+/// [response_headers_hash], with the hash of its body into the hash of the response.
+pub(crate) fn combine_response_hashes(headers_hash: &Hash, body_hash: &Hash) -> Hash {
+    hash(
+        [headers_hash.as_slice(), body_hash.as_slice()]
+            .concat()
+            .as_slice(),
+    )
+}
+
+/// A breakdown of the values that are fed into the
+/// [Representation Independent Hash](https://internetcomputer.org/docs/current/references/ic-interface-spec/#hash-of-map)
+/// of an [HttpResponse], as calculated by [response_hash_details].
+///
+/// This is intended for debugging responses that do not pass verification, by comparing the
+/// details calculated by the canister with those calculated from the response that the HTTP gateway
+/// received.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResponseHashDetails {
+    /// The lowercased names and values of the headers that are included in the hash,
+    /// in the order that they appear in the response.
+    pub headers: Vec<(String, String)>,
+    /// The status code of the response, included in the hash as the `:ic-cert-status` pseudo header.
+    pub status_code: u64,
+    /// The hash of the headers and status code, calculated by [response_headers_hash].
+    pub headers_hash: Hash,
+    /// The hash of the response body.
+    pub body_hash: Hash,
+    /// The hash of the response, as calculated by [response_hash].
+    pub hash: Hash,
+}
+
+/// Calculates the hash of an [HttpResponse] in the same way as [response_hash], returning a
+/// [ResponseHashDetails] with the exact headers and body hash that were fed into the hash.
+pub fn response_hash_details(
+    response: &HttpResponse,
+    response_certification: &DefaultResponseCertification,
+) -> ResponseHashDetails {
+    let status_code = response.status_code().as_u16().into();
+    let filtered_headers = filter_response_headers(response, response_certification);
+    let headers_hash = response_headers_hash(&status_code, &filtered_headers);
+    let body_hash = hash(response.body());
+
+    ResponseHashDetails {
+        headers: filtered_headers.headers,
+        status_code,
+        headers_hash,
+        body_hash,
+        hash: combine_response_hashes(&headers_hash, &body_hash),
+    }
 }
 
 #[cfg(test)]
@@ -432,6 +502,52 @@ mod tests {
         assert_eq!(result, expected_hash.as_slice());
     }
 
+    #[test]
+    fn response_hash_streaming_with_certified_headers() {
+        let response_certification =
+            DefaultResponseCertification::certified_response_headers(vec![
+                "Accept-Encoding",
+                "Cache-Control",
+            ]);
+        let response = create_response(CERTIFIED_HEADERS_CEL_EXPRESSION);
+
+        let result = response_hash_streaming(
+            &response,
+            &response_certification,
+            response.body().chunks(4),
+        );
+
+        assert_eq!(
+            result,
+            response_hash(&response, &response_certification, None)
+        );
+    }
+
+    #[test]
+    fn response_hash_details_with_certified_headers() {
+        let response_certification =
+            DefaultResponseCertification::certified_response_headers(vec![
+                "Accept-Encoding",
+                "Cache-Control",
+            ]);
+        let response = create_response(CERTIFIED_HEADERS_CEL_EXPRESSION);
+        let filtered_headers = filter_response_headers(&response, &response_certification);
+
+        let result = response_hash_details(&response, &response_certification);
+
+        assert_eq!(result.headers, filtered_headers.headers);
+        assert_eq!(result.status_code, 200);
+        assert_eq!(
+            result.headers_hash,
+            response_headers_hash(&200, &filtered_headers)
+        );
+        assert_eq!(result.body_hash, hash(HELLO_WORLD_BODY));
+        assert_eq!(
+            result.hash,
+            response_hash(&response, &response_certification, None)
+        );
+    }
+
     fn create_response(cel_expression: &str) -> HttpResponse {
         HttpResponse::ok(
             HELLO_WORLD_BODY,
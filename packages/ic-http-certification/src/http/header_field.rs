@@ -1,2 +1,21 @@
+pub use http::header::{self, HeaderName};
+
 /// An HTTP header field, represented as a tuple of (name, value).
 pub type HeaderField = (String, String);
+
+pub(crate) fn find_header_value<'a>(headers: &'a [HeaderField], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(header_name, _)| header_name.eq_ignore_ascii_case(name))
+        .map(|(_, header_value)| header_value.as_str())
+}
+
+pub(crate) fn find_header_values<'a>(
+    headers: &'a [HeaderField],
+    name: impl AsRef<str> + 'a,
+) -> impl Iterator<Item = &'a str> {
+    headers
+        .iter()
+        .filter(move |(header_name, _)| header_name.eq_ignore_ascii_case(name.as_ref()))
+        .map(|(_, header_value)| header_value.as_str())
+}
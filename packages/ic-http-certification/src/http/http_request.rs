@@ -1,46 +1,69 @@
-use crate::{HeaderField, HttpCertificationError, HttpCertificationResult};
-use candid::{
-    types::{Serializer, Type, TypeInner},
-    CandidType, Deserialize,
+use crate::{
+    http::header_field::{find_header_value, find_header_values},
+    HeaderField, HttpCertificationError, HttpCertificationResult, Method, UrlPathRejection,
 };
-pub use http::Method;
+use candid::{CandidType, Deserialize};
 use http::Uri;
-use serde::Deserializer;
-use std::{borrow::Cow, str::FromStr};
+use std::borrow::Cow;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-struct MethodWrapper(Method);
+fn parse_path(url: &str, mode: UrlParsingMode) -> HttpCertificationResult<String> {
+    let disallowed_url_path = |reason| HttpCertificationError::DisallowedUrlPath {
+        url: url.to_string(),
+        reason,
+    };
 
-impl CandidType for MethodWrapper {
-    fn _ty() -> Type {
-        TypeInner::Text.into()
+    let uri = url
+        .parse::<Uri>()
+        .map_err(|_| HttpCertificationError::MalformedUrl(url.to_string()))?;
+
+    if mode == UrlParsingMode::Strict && !has_valid_percent_encodings(uri.path()) {
+        return Err(disallowed_url_path(
+            UrlPathRejection::InvalidPercentEncoding,
+        ));
     }
 
-    fn idl_serialize<S>(&self, serializer: S) -> Result<(), S::Error>
-    where
-        S: Serializer,
-    {
-        self.0.as_str().idl_serialize(serializer)
+    let decoded_path = urlencoding::decode(uri.path()).map(|path| path.into_owned())?;
+
+    if mode == UrlParsingMode::Strict {
+        if decoded_path.contains('\\') {
+            return Err(disallowed_url_path(UrlPathRejection::Backslash));
+        }
+
+        if decoded_path.chars().any(char::is_control) {
+            return Err(disallowed_url_path(UrlPathRejection::ControlCharacter));
+        }
     }
+
+    Ok(decoded_path)
 }
 
-impl<'de> Deserialize<'de> for MethodWrapper {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        String::deserialize(deserializer).and_then(|method| {
-            Method::from_str(&method)
-                .map(Into::into)
-                .map_err(|_| serde::de::Error::custom("Invalid HTTP method"))
-        })
-    }
+/// Returns `true` if every `%` in the given path is followed by two hexadecimal digits.
+fn has_valid_percent_encodings(path: &str) -> bool {
+    let bytes = path.as_bytes();
+
+    bytes.iter().enumerate().all(|(index, byte)| {
+        *byte != b'%'
+            || bytes
+                .get(index + 1..index + 3)
+                .is_some_and(|digits| digits.iter().all(u8::is_ascii_hexdigit))
+    })
 }
 
-impl From<Method> for MethodWrapper {
-    fn from(method: Method) -> Self {
-        Self(method)
-    }
+/// The mode used to parse the path of a request URL, see
+/// [get_path_with_mode](HttpRequest::get_path_with_mode).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UrlParsingMode {
+    /// Invalid percent-encodings are kept as they are, and backslashes and control characters
+    /// are allowed in the decoded path. This is the mode used by
+    /// [get_path](HttpRequest::get_path).
+    #[default]
+    Lenient,
+
+    /// The path is rejected with [HttpCertificationError::DisallowedUrlPath] if it contains an
+    /// invalid percent-encoding, or if the decoded path contains a backslash or a control
+    /// character. Percent-encoded bytes that are not valid UTF-8, such as overlong encodings, are
+    /// rejected in both modes.
+    Strict,
 }
 
 /// A Candid-encodable representation of an HTTP request. This struct is used by
@@ -88,7 +111,7 @@ impl From<Method> for MethodWrapper {
 #[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
 pub struct HttpRequest<'a> {
     /// HTTP request method.
-    method: MethodWrapper,
+    method: Method,
 
     /// HTTP request URL.
     url: String,
@@ -250,7 +273,7 @@ impl<'a> HttpRequest<'a> {
     /// ```
     #[inline]
     pub fn method(&self) -> &Method {
-        &self.method.0
+        &self.method
     }
 
     /// Returns the URL of the request.
@@ -287,6 +310,53 @@ impl<'a> HttpRequest<'a> {
         &self.headers
     }
 
+    /// Returns the value of the first header of the request with the given name, if any.
+    /// Header names are compared case-insensitively.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{header, HttpRequest};
+    ///
+    /// let request = HttpRequest::get("/")
+    ///     .with_headers(vec![("Content-Type".into(), "text/plain".into())])
+    ///     .build();
+    ///
+    /// assert_eq!(request.get_header(header::CONTENT_TYPE), Some("text/plain"));
+    /// assert_eq!(request.get_header("content-type"), Some("text/plain"));
+    /// assert_eq!(request.get_header("ETag"), None);
+    /// ```
+    pub fn get_header(&self, name: impl AsRef<str>) -> Option<&str> {
+        find_header_value(&self.headers, name.as_ref())
+    }
+
+    /// Returns the values of all headers of the request with the given name, in the order that
+    /// they appear in the request. Header names are compared case-insensitively.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{header, HttpRequest};
+    ///
+    /// let request = HttpRequest::get("/")
+    ///     .with_headers(vec![
+    ///         ("Vary".into(), "Accept".into()),
+    ///         ("vary".into(), "Accept-Encoding".into()),
+    ///     ])
+    ///     .build();
+    ///
+    /// assert_eq!(
+    ///     request.get_header_values(header::VARY).collect::<Vec<_>>(),
+    ///     vec!["Accept", "Accept-Encoding"]
+    /// );
+    /// ```
+    pub fn get_header_values<'b>(
+        &'b self,
+        name: impl AsRef<str> + 'b,
+    ) -> impl Iterator<Item = &'b str> {
+        find_header_values(&self.headers, name)
+    }
+
     /// Returns the body of the request.
     ///
     /// # Examples
@@ -326,6 +396,13 @@ impl<'a> HttpRequest<'a> {
 
     /// Returns the path of the request URL, without domain, query parameters or fragments.
     ///
+    /// The path is percent-decoded in the same way as the HTTP gateway decodes it when looking up
+    /// certifications, so the returned path can be used directly for routing and for
+    /// [HttpCertificationTree](crate::HttpCertificationTree) lookups. Dot segments and duplicate
+    /// slashes are not removed, since the HTTP gateway does not remove them either. To route
+    /// requests by their normalized path, use [normalize_path](crate::utils::normalize_path) on
+    /// the URL path, but still certify responses for the path returned by this function.
+    ///
     /// # Examples
     ///
     /// ```
@@ -334,15 +411,38 @@ impl<'a> HttpRequest<'a> {
     /// let request = HttpRequest::get("https://canister.com/sample-asset.txt").build();
     ///
     /// assert_eq!(request.get_path().unwrap(), "/sample-asset.txt");
+    ///
+    /// let request = HttpRequest::get("https://canister.com/%73ample%20asset.txt").build();
+    ///
+    /// assert_eq!(request.get_path().unwrap(), "/sample asset.txt");
     /// ```
     pub fn get_path(&self) -> HttpCertificationResult<String> {
-        let uri = self
-            .url
-            .parse::<Uri>()
-            .map_err(|_| HttpCertificationError::MalformedUrl(self.url.to_string()))?;
+        self.get_path_with_mode(UrlParsingMode::Lenient)
+    }
 
-        let decoded_path = urlencoding::decode(uri.path()).map(|path| path.into_owned())?;
-        Ok(decoded_path)
+    /// Returns the path of the request URL in the same way as [get_path](HttpRequest::get_path),
+    /// using the given [UrlParsingMode].
+    ///
+    /// In [strict](UrlParsingMode::Strict) mode, paths that are commonly used to bypass path based
+    /// access checks, such as paths containing backslashes, control characters or invalid
+    /// percent-encodings, are rejected with [HttpCertificationError::DisallowedUrlPath] instead of
+    /// being passed on to the canister's handlers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{HttpRequest, UrlParsingMode};
+    ///
+    /// let request = HttpRequest::get("https://canister.com/assets/..%5Csecret.txt").build();
+    ///
+    /// assert_eq!(
+    ///     request.get_path_with_mode(UrlParsingMode::Lenient).unwrap(),
+    ///     "/assets/..\\secret.txt"
+    /// );
+    /// assert!(request.get_path_with_mode(UrlParsingMode::Strict).is_err());
+    /// ```
+    pub fn get_path_with_mode(&self, mode: UrlParsingMode) -> HttpCertificationResult<String> {
+        parse_path(&self.url, mode)
     }
 
     /// Returns the query parameters of the request URL, if any, as a string.
@@ -362,6 +462,54 @@ impl<'a> HttpRequest<'a> {
             .map(|uri| uri.query().map(|uri| uri.to_owned()))
             .map_err(|_| HttpCertificationError::MalformedUrl(self.url.to_string()))
     }
+
+    /// Returns the decoded query parameters of the request URL, in the order that they appear in
+    /// the URL. A parameter that appears multiple times is returned once for each occurrence.
+    ///
+    /// Names and values are percent-decoded, and `+` is decoded as a space.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::HttpRequest;
+    ///
+    /// let request = HttpRequest::get("/todos?tag=a&tag=b&q=hello+world").build();
+    ///
+    /// assert_eq!(
+    ///     request.get_query_params().unwrap(),
+    ///     vec![
+    ///         ("tag".to_string(), "a".to_string()),
+    ///         ("tag".to_string(), "b".to_string()),
+    ///         ("q".to_string(), "hello world".to_string()),
+    ///     ]
+    /// );
+    /// ```
+    pub fn get_query_params(&self) -> HttpCertificationResult<Vec<(String, String)>> {
+        self.get_query()?
+            .map(|query| parse_query_params(&query))
+            .unwrap_or_else(|| Ok(vec![]))
+    }
+
+    /// Returns the decoded value of the first query parameter of the request URL with the given
+    /// name, if any. See [get_query_params](HttpRequest::get_query_params) for all query parameters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::HttpRequest;
+    ///
+    /// let request = HttpRequest::get("/todos?tag=a&tag=b").build();
+    ///
+    /// assert_eq!(request.get_query_param("tag").unwrap(), Some("a".to_string()));
+    /// assert_eq!(request.get_query_param("q").unwrap(), None);
+    /// ```
+    pub fn get_query_param(&self, name: &str) -> HttpCertificationResult<Option<String>> {
+        Ok(self
+            .get_query_params()?
+            .into_iter()
+            .find(|(param_name, _)| param_name == name)
+            .map(|(_, param_value)| param_value))
+    }
 }
 
 /// An HTTP request builder.
@@ -390,8 +538,9 @@ impl<'a> HttpRequest<'a> {
 /// ```
 #[derive(Debug, Clone, Default)]
 pub struct HttpRequestBuilder<'a> {
-    method: Option<MethodWrapper>,
+    method: Option<Method>,
     url: Option<String>,
+    query_params: Vec<(String, String)>,
     headers: Vec<HeaderField>,
     body: Cow<'a, [u8]>,
     certificate_version: Option<u16>,
@@ -443,7 +592,7 @@ impl<'a> HttpRequestBuilder<'a> {
     /// ```
     #[inline]
     pub fn with_method(mut self, method: Method) -> Self {
-        self.method = Some(method.into());
+        self.method = Some(method);
 
         self
     }
@@ -471,6 +620,34 @@ impl<'a> HttpRequestBuilder<'a> {
         self
     }
 
+    /// Add a query parameter to the URL of the [HttpRequest].
+    ///
+    /// The name and value are percent-encoded and appended to the query string of the URL when
+    /// the request is built, after any query parameters that are already part of the URL. The
+    /// encoding is reversed by [get_query_params](HttpRequest::get_query_params), so names and
+    /// values can contain any characters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::HttpRequestBuilder;
+    ///
+    /// let request = HttpRequestBuilder::new()
+    ///     .with_url("/todos?page=1")
+    ///     .with_query_param("q", "hello world")
+    ///     .with_query_param("tag", "a&b")
+    ///     .build();
+    ///
+    /// assert_eq!(request.url(), "/todos?page=1&q=hello%20world&tag=a%26b");
+    /// assert_eq!(request.get_query_param("tag").unwrap(), Some("a&b".to_string()));
+    /// ```
+    pub fn with_query_param(mut self, name: impl AsRef<str>, value: impl AsRef<str>) -> Self {
+        self.query_params
+            .push((name.as_ref().to_string(), value.as_ref().to_string()));
+
+        self
+    }
+
     /// Set the HTTP headers of the [HttpRequest].
     ///
     /// By default the headers will be an empty array.
@@ -568,9 +745,11 @@ impl<'a> HttpRequestBuilder<'a> {
     /// ```
     #[inline]
     pub fn build(self) -> HttpRequest<'a> {
+        let url = self.url_with_query_params();
+
         HttpRequest {
-            method: self.method.unwrap_or(Method::GET.into()),
-            url: self.url.unwrap_or("/".to_string()),
+            method: self.method.unwrap_or(Method::GET),
+            url,
             headers: self.headers,
             body: self.body,
             certificate_version: self.certificate_version,
@@ -602,13 +781,49 @@ impl<'a> HttpRequestBuilder<'a> {
     /// ```
     #[inline]
     pub fn build_update(self) -> HttpUpdateRequest<'a> {
+        let url = self.url_with_query_params();
+
         HttpUpdateRequest {
-            method: self.method.unwrap_or(Method::GET.into()),
-            url: self.url.unwrap_or("/".to_string()),
+            method: self.method.unwrap_or(Method::GET),
+            url,
             headers: self.headers,
             body: self.body,
         }
     }
+
+    fn url_with_query_params(&self) -> String {
+        let url = self.url.as_deref().unwrap_or("/");
+        if self.query_params.is_empty() {
+            return url.to_string();
+        }
+
+        let (url, fragment) = match url.split_once('#') {
+            Some((url, fragment)) => (url, Some(fragment)),
+            None => (url, None),
+        };
+        let separator = match url.split_once('?') {
+            Some((_, "")) => "",
+            Some(_) => "&",
+            None => "?",
+        };
+        let query = self
+            .query_params
+            .iter()
+            .map(|(name, value)| {
+                format!(
+                    "{}={}",
+                    urlencoding::encode(name),
+                    urlencoding::encode(value)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("&");
+
+        match fragment {
+            Some(fragment) => format!("{url}{separator}{query}#{fragment}"),
+            None => format!("{url}{separator}{query}"),
+        }
+    }
 }
 
 /// A Candid-encodable representation of an HTTP update request. This struct is
@@ -639,7 +854,7 @@ impl<'a> HttpRequestBuilder<'a> {
 #[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
 pub struct HttpUpdateRequest<'a> {
     /// HTTP request method.
-    method: MethodWrapper,
+    method: Method,
 
     /// HTTP request URL.
     url: String,
@@ -665,7 +880,7 @@ impl<'a> HttpUpdateRequest<'a> {
     /// ```
     #[inline]
     pub fn method(&self) -> &Method {
-        &self.method.0
+        &self.method
     }
 
     /// Returns the URL of the request.
@@ -702,6 +917,53 @@ impl<'a> HttpUpdateRequest<'a> {
         &self.headers
     }
 
+    /// Returns the value of the first header of the request with the given name, if any.
+    /// Header names are compared case-insensitively.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{header, HttpRequest};
+    ///
+    /// let request = HttpRequest::get("/")
+    ///     .with_headers(vec![("Content-Type".into(), "text/plain".into())])
+    ///     .build();
+    ///
+    /// assert_eq!(request.get_header(header::CONTENT_TYPE), Some("text/plain"));
+    /// assert_eq!(request.get_header("content-type"), Some("text/plain"));
+    /// assert_eq!(request.get_header("ETag"), None);
+    /// ```
+    pub fn get_header(&self, name: impl AsRef<str>) -> Option<&str> {
+        find_header_value(&self.headers, name.as_ref())
+    }
+
+    /// Returns the values of all headers of the request with the given name, in the order that
+    /// they appear in the request. Header names are compared case-insensitively.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{header, HttpRequest};
+    ///
+    /// let request = HttpRequest::get("/")
+    ///     .with_headers(vec![
+    ///         ("Vary".into(), "Accept".into()),
+    ///         ("vary".into(), "Accept-Encoding".into()),
+    ///     ])
+    ///     .build();
+    ///
+    /// assert_eq!(
+    ///     request.get_header_values(header::VARY).collect::<Vec<_>>(),
+    ///     vec!["Accept", "Accept-Encoding"]
+    /// );
+    /// ```
+    pub fn get_header_values<'b>(
+        &'b self,
+        name: impl AsRef<str> + 'b,
+    ) -> impl Iterator<Item = &'b str> {
+        find_header_values(&self.headers, name)
+    }
+
     /// Returns the body of the request.
     ///
     /// # Examples
@@ -722,6 +984,13 @@ impl<'a> HttpUpdateRequest<'a> {
 
     /// Returns the path of the request URL, without domain, query parameters or fragments.
     ///
+    /// The path is percent-decoded in the same way as the HTTP gateway decodes it when looking up
+    /// certifications, so the returned path can be used directly for routing and for
+    /// [HttpCertificationTree](crate::HttpCertificationTree) lookups. Dot segments and duplicate
+    /// slashes are not removed, since the HTTP gateway does not remove them either. To route
+    /// requests by their normalized path, use [normalize_path](crate::utils::normalize_path) on
+    /// the URL path, but still certify responses for the path returned by this function.
+    ///
     /// # Examples
     ///
     /// ```
@@ -730,15 +999,20 @@ impl<'a> HttpUpdateRequest<'a> {
     /// let request = HttpRequest::get("https://canister.com/sample-asset.txt").build();
     ///
     /// assert_eq!(request.get_path().unwrap(), "/sample-asset.txt");
+    ///
+    /// let request = HttpRequest::get("https://canister.com/%73ample%20asset.txt").build();
+    ///
+    /// assert_eq!(request.get_path().unwrap(), "/sample asset.txt");
     /// ```
     pub fn get_path(&self) -> HttpCertificationResult<String> {
-        let uri = self
-            .url
-            .parse::<Uri>()
-            .map_err(|_| HttpCertificationError::MalformedUrl(self.url.to_string()))?;
+        self.get_path_with_mode(UrlParsingMode::Lenient)
+    }
 
-        let decoded_path = urlencoding::decode(uri.path()).map(|path| path.into_owned())?;
-        Ok(decoded_path)
+    /// Returns the path of the request URL in the same way as
+    /// [get_path](HttpUpdateRequest::get_path), using the given [UrlParsingMode]. See
+    /// [HttpRequest::get_path_with_mode] for details.
+    pub fn get_path_with_mode(&self, mode: UrlParsingMode) -> HttpCertificationResult<String> {
+        parse_path(&self.url, mode)
     }
 
     /// Returns the query parameters of the request URL, if any, as a string.
@@ -758,6 +1032,54 @@ impl<'a> HttpUpdateRequest<'a> {
             .map(|uri| uri.query().map(|uri| uri.to_owned()))
             .map_err(|_| HttpCertificationError::MalformedUrl(self.url.to_string()))
     }
+
+    /// Returns the decoded query parameters of the request URL, in the order that they appear in
+    /// the URL. A parameter that appears multiple times is returned once for each occurrence.
+    ///
+    /// Names and values are percent-decoded, and `+` is decoded as a space.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{HttpRequest, HttpUpdateRequest};
+    ///
+    /// let request = HttpUpdateRequest::from(HttpRequest::get("/todos?tag=a&tag=b&q=hello+world").build());
+    ///
+    /// assert_eq!(
+    ///     request.get_query_params().unwrap(),
+    ///     vec![
+    ///         ("tag".to_string(), "a".to_string()),
+    ///         ("tag".to_string(), "b".to_string()),
+    ///         ("q".to_string(), "hello world".to_string()),
+    ///     ]
+    /// );
+    /// ```
+    pub fn get_query_params(&self) -> HttpCertificationResult<Vec<(String, String)>> {
+        self.get_query()?
+            .map(|query| parse_query_params(&query))
+            .unwrap_or_else(|| Ok(vec![]))
+    }
+
+    /// Returns the decoded value of the first query parameter of the request URL with the given
+    /// name, if any. See [get_query_params](HttpUpdateRequest::get_query_params) for all query parameters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{HttpRequest, HttpUpdateRequest};
+    ///
+    /// let request = HttpUpdateRequest::from(HttpRequest::get("/todos?tag=a&tag=b").build());
+    ///
+    /// assert_eq!(request.get_query_param("tag").unwrap(), Some("a".to_string()));
+    /// assert_eq!(request.get_query_param("q").unwrap(), None);
+    /// ```
+    pub fn get_query_param(&self, name: &str) -> HttpCertificationResult<Option<String>> {
+        Ok(self
+            .get_query_params()?
+            .into_iter()
+            .find(|(param_name, _)| param_name == name)
+            .map(|(_, param_value)| param_value))
+    }
 }
 
 impl<'a> From<HttpRequest<'a>> for HttpUpdateRequest<'a> {
@@ -771,9 +1093,118 @@ impl<'a> From<HttpRequest<'a>> for HttpUpdateRequest<'a> {
     }
 }
 
+fn parse_query_params(query: &str) -> HttpCertificationResult<Vec<(String, String)>> {
+    query
+        .split('&')
+        .filter(|query_fragment| !query_fragment.is_empty())
+        .map(|query_fragment| {
+            let (name, value) = query_fragment
+                .split_once('=')
+                .unwrap_or((query_fragment, ""));
+
+            Ok((
+                decode_query_component(name)?,
+                decode_query_component(value)?,
+            ))
+        })
+        .collect()
+}
+
+fn decode_query_component(component: &str) -> HttpCertificationResult<String> {
+    let decoded_component = urlencoding::decode(&component.replace('+', " "))?;
+
+    Ok(decoded_component.into_owned())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use assert_matches::assert_matches;
+
+    #[test]
+    fn request_builder_with_query_params() {
+        let cases = [
+            (None, "/?a=1&b=x%2By"),
+            (Some("/todos"), "/todos?a=1&b=x%2By"),
+            (Some("/todos?"), "/todos?a=1&b=x%2By"),
+            (Some("/todos?page=2"), "/todos?page=2&a=1&b=x%2By"),
+            (Some("/todos#top"), "/todos?a=1&b=x%2By#top"),
+        ];
+
+        for (url, expected_url) in cases {
+            let builder = match url {
+                Some(url) => HttpRequest::builder().with_url(url),
+                None => HttpRequest::builder(),
+            };
+            let request = builder
+                .with_query_param("a", "1")
+                .with_query_param("b", "x+y")
+                .build();
+
+            assert_eq!(request.url(), expected_url);
+            assert_eq!(
+                request.get_query_param("b").unwrap(),
+                Some("x+y".to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn request_get_path_strict() {
+        let allowed_urls = [
+            ("https://canister.com/sample-asset.txt", "/sample-asset.txt"),
+            ("https://canister.com/a%20file.txt", "/a file.txt"),
+            ("/%E2%9C%93", "/\u{2713}"),
+        ];
+        for (url, expected_path) in allowed_urls {
+            let request = HttpRequest::get(url).build();
+
+            assert_eq!(
+                request.get_path_with_mode(UrlParsingMode::Strict).unwrap(),
+                expected_path
+            );
+            assert_eq!(request.get_path().unwrap(), expected_path);
+        }
+
+        let disallowed_urls = [
+            (
+                "https://canister.com/assets/..%5Csecret.txt",
+                UrlPathRejection::Backslash,
+            ),
+            (
+                "https://canister.com/assets/%00.txt",
+                UrlPathRejection::ControlCharacter,
+            ),
+            (
+                "https://canister.com/assets/%0A.txt",
+                UrlPathRejection::ControlCharacter,
+            ),
+            (
+                "https://canister.com/assets/%zz.txt",
+                UrlPathRejection::InvalidPercentEncoding,
+            ),
+            (
+                "https://canister.com/assets/%2",
+                UrlPathRejection::InvalidPercentEncoding,
+            ),
+        ];
+        for (url, expected_reason) in disallowed_urls {
+            let request = HttpRequest::get(url).build();
+
+            assert!(request.get_path().is_ok());
+            assert_matches!(
+                request.get_path_with_mode(UrlParsingMode::Strict),
+                Err(HttpCertificationError::DisallowedUrlPath { url: rejected_url, reason })
+                    if rejected_url == url && reason == expected_reason
+            );
+        }
+
+        let overlong_request = HttpRequest::get("https://canister.com/%C0%AF").build();
+        assert!(overlong_request.get_path().is_err());
+        assert!(overlong_request
+            .get_path_with_mode(UrlParsingMode::Strict)
+            .is_err());
+    }
 
     #[test]
     fn request_get_uri() {
@@ -819,4 +1250,47 @@ mod tests {
             assert_eq!(query.unwrap_or_default(), *expected_query);
         }
     }
+
+    #[test]
+    fn request_get_query_params() {
+        let req = HttpRequest::get(
+            "https://canister.com/todos?tag=a&tag=b&q=hello+world&name=test%20component&flag&&empty=",
+        )
+        .build();
+
+        let query_params = req.get_query_params().unwrap();
+
+        assert_eq!(
+            query_params,
+            vec![
+                ("tag".to_string(), "a".to_string()),
+                ("tag".to_string(), "b".to_string()),
+                ("q".to_string(), "hello world".to_string()),
+                ("name".to_string(), "test component".to_string()),
+                ("flag".to_string(), "".to_string()),
+                ("empty".to_string(), "".to_string()),
+            ]
+        );
+        assert_eq!(req.get_query_param("tag").unwrap(), Some("a".to_string()));
+        assert_eq!(req.get_query_param("flag").unwrap(), Some("".to_string()));
+        assert_eq!(req.get_query_param("missing").unwrap(), None);
+    }
+
+    #[test]
+    fn request_get_query_params_without_query() {
+        let req = HttpRequest::get("https://canister.com/todos").build();
+
+        assert!(req.get_query_params().unwrap().is_empty());
+        assert_eq!(req.get_query_param("tag").unwrap(), None);
+    }
+
+    #[test]
+    fn request_get_query_params_invalid_utf8() {
+        let req = HttpRequest::get("https://canister.com/todos?tag=%ff").build();
+
+        assert!(matches!(
+            req.get_query_params(),
+            Err(HttpCertificationError::Utf8ConversionError(_))
+        ));
+    }
 }
@@ -1,4 +1,7 @@
-use crate::HeaderField;
+use crate::{
+    http::header_field::{find_header_value, find_header_values},
+    HeaderField, HttpCertificationError, HttpCertificationResult, StreamingStrategy,
+};
 use candid::{
     types::{Serializer, Type, TypeInner},
     CandidType, Deserialize,
@@ -81,6 +84,9 @@ impl From<StatusCode> for StatusCodeWrapper {
 /// - [TOO_MANY_REQUESTS](HttpResponse::too_many_requests)
 /// - [INTERNAL_SERVER_ERROR](HttpResponse::internal_server_error)
 ///
+/// An [HttpResponse] that upgrades the request to an update call can be created with
+/// [upgrade_to_update_call](HttpResponse::upgrade_to_update_call).
+///
 /// ```
 /// use ic_http_certification::{HttpResponse, StatusCode};
 ///
@@ -104,6 +110,10 @@ pub struct HttpResponse<'a> {
     /// Whether the corresponding HTTP request should be upgraded to an update
     /// call.
     upgrade: Option<bool>,
+
+    /// The strategy the HTTP Gateway should use to retrieve the remainder of
+    /// the response body.
+    streaming_strategy: Option<StreamingStrategy>,
 }
 
 impl<'a> HttpResponse<'a> {
@@ -454,6 +464,81 @@ impl<'a> HttpResponse<'a> {
             .with_headers(headers)
     }
 
+    /// Creates a new [HttpResponseBuilder] initialized with the upgrade flag set to `true`,
+    /// instructing the HTTP Gateway to retry the request as an `http_request_update` update call.
+    ///
+    /// This method returns an instance of [HttpResponseBuilder] that can be used to
+    /// to create an [HttpResponse].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{HttpResponse, StatusCode};
+    ///
+    /// let response = HttpResponse::upgrade_to_update_call().build();
+    ///
+    /// assert_eq!(response.status_code(), StatusCode::OK);
+    /// assert_eq!(response.upgrade(), Some(true));
+    /// assert!(response.is_upgrade());
+    /// ```
+    pub fn upgrade_to_update_call() -> HttpResponseBuilder<'a> {
+        Self::builder().with_upgrade(true)
+    }
+
+    /// Creates a new [HttpResponseBuilder] initialized with the given status code and
+    /// the given value serialized as a JSON body.
+    ///
+    /// The `content-type` header is set to `application/json` and the `content-length` header is set
+    /// to the length of the serialized body. Note that [HttpResponseBuilder::with_headers] replaces
+    /// these headers, so additional headers should be added with [HttpResponse::add_header] instead.
+    ///
+    /// This method returns an instance of [HttpResponseBuilder] that can be used to
+    /// to create an [HttpResponse], or an error if the value cannot be serialized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{HttpResponse, StatusCode};
+    /// use serde::Serialize;
+    ///
+    /// #[derive(Serialize)]
+    /// struct TodoItem {
+    ///     id: u32,
+    ///     title: String,
+    /// }
+    ///
+    /// let todo_item = TodoItem {
+    ///     id: 1,
+    ///     title: "Learn Rust".to_string(),
+    /// };
+    /// let response = HttpResponse::json(StatusCode::CREATED, &todo_item).unwrap().build();
+    ///
+    /// assert_eq!(response.status_code(), StatusCode::CREATED);
+    /// assert_eq!(
+    ///     response.headers(),
+    ///     &[
+    ///         ("content-type".into(), "application/json".into()),
+    ///         ("content-length".into(), "29".into()),
+    ///     ]
+    /// );
+    /// assert_eq!(response.body(), br#"{"id":1,"title":"Learn Rust"}"#);
+    /// ```
+    #[cfg(feature = "serde_json")]
+    pub fn json(
+        status_code: StatusCode,
+        body: &impl serde::Serialize,
+    ) -> crate::HttpCertificationResult<HttpResponseBuilder<'a>> {
+        let body = serde_json::to_vec(body)?;
+
+        Ok(Self::builder()
+            .with_status_code(status_code)
+            .with_headers(vec![
+                ("content-type".to_string(), "application/json".to_string()),
+                ("content-length".to_string(), body.len().to_string()),
+            ])
+            .with_body(body))
+    }
+
     /// Creates and returns an instance of [HttpResponseBuilder], a builder-style
     /// object that can be used to construct an [HttpResponse].
     ///
@@ -515,6 +600,53 @@ impl<'a> HttpResponse<'a> {
         &self.headers
     }
 
+    /// Returns the value of the first header of the response with the given name, if any.
+    /// Header names are compared case-insensitively.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{header, HttpResponse};
+    ///
+    /// let response = HttpResponse::ok(b"Hello, World!", vec![])
+    ///     .with_headers(vec![("Content-Type".into(), "text/plain".into())])
+    ///     .build();
+    ///
+    /// assert_eq!(response.get_header(header::CONTENT_TYPE), Some("text/plain"));
+    /// assert_eq!(response.get_header("content-type"), Some("text/plain"));
+    /// assert_eq!(response.get_header("ETag"), None);
+    /// ```
+    pub fn get_header(&self, name: impl AsRef<str>) -> Option<&str> {
+        find_header_value(&self.headers, name.as_ref())
+    }
+
+    /// Returns the values of all headers of the response with the given name, in the order that
+    /// they appear in the response. Header names are compared case-insensitively.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{header, HttpResponse};
+    ///
+    /// let response = HttpResponse::ok(b"Hello, World!", vec![])
+    ///     .with_headers(vec![
+    ///         ("Vary".into(), "Accept".into()),
+    ///         ("vary".into(), "Accept-Encoding".into()),
+    ///     ])
+    ///     .build();
+    ///
+    /// assert_eq!(
+    ///     response.get_header_values(header::VARY).collect::<Vec<_>>(),
+    ///     vec!["Accept", "Accept-Encoding"]
+    /// );
+    /// ```
+    pub fn get_header_values<'b>(
+        &'b self,
+        name: impl AsRef<str> + 'b,
+    ) -> impl Iterator<Item = &'b str> {
+        find_header_values(&self.headers, name)
+    }
+
     /// Returns a mutable reference to the HTTP headers of the response.
     ///
     /// # Examples
@@ -591,6 +723,118 @@ impl<'a> HttpResponse<'a> {
     pub fn upgrade(&self) -> Option<bool> {
         self.upgrade
     }
+
+    /// Returns `true` if the response will upgrade the request to an update call, which is the
+    /// case if the [upgrade](HttpResponse::upgrade) flag is set to `Some(true)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::HttpResponse;
+    ///
+    /// assert!(HttpResponse::upgrade_to_update_call().build().is_upgrade());
+    /// assert!(!HttpResponse::builder().with_upgrade(false).build().is_upgrade());
+    /// assert!(!HttpResponse::builder().build().is_upgrade());
+    /// ```
+    #[inline]
+    pub fn is_upgrade(&self) -> bool {
+        self.upgrade == Some(true)
+    }
+
+    /// Returns the streaming strategy of the response. This will determine how the HTTP Gateway
+    /// will retrieve the remainder of the response body.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{HttpResponse, StreamingCallbackToken, StreamingStrategy};
+    /// use candid::{Func, Principal};
+    ///
+    /// let strategy = StreamingStrategy::Callback {
+    ///     callback: Func {
+    ///         principal: Principal::management_canister(),
+    ///         method: "http_request_streaming_callback".to_string(),
+    ///     },
+    ///     token: StreamingCallbackToken::new("/video.mp4", "identity", 1),
+    /// };
+    ///
+    /// let response = HttpResponse::builder()
+    ///     .with_streaming_strategy(strategy.clone())
+    ///     .build();
+    ///
+    /// assert_eq!(response.streaming_strategy(), Some(&strategy));
+    /// ```
+    #[inline]
+    pub fn streaming_strategy(&self) -> Option<&StreamingStrategy> {
+        self.streaming_strategy.as_ref()
+    }
+
+    /// Consumes the response and returns its HTTP body, without copying it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::HttpResponse;
+    ///
+    /// let response = HttpResponse::ok(b"Hello, World!", vec![]).build();
+    ///
+    /// assert_eq!(response.into_body().as_ref(), b"Hello, World!");
+    /// ```
+    #[inline]
+    pub fn into_body(self) -> Cow<'a, [u8]> {
+        self.body
+    }
+
+    /// Returns a copy of the response that borrows its body from this response, instead of
+    /// copying it. Only the status code, headers and streaming strategy are cloned.
+    ///
+    /// This allows a response that is stored by the canister, such as a certified response, to
+    /// be served or encoded without copying a potentially large body.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::HttpResponse;
+    /// use std::borrow::Cow;
+    ///
+    /// let response = HttpResponse::ok(vec![0; 1024], vec![]).build();
+    /// let borrowed_response = response.to_borrowed();
+    ///
+    /// assert_eq!(borrowed_response, response);
+    /// assert!(matches!(borrowed_response.into_body(), Cow::Borrowed(_)));
+    /// ```
+    pub fn to_borrowed(&self) -> HttpResponse<'_> {
+        HttpResponse {
+            status_code: self.status_code,
+            headers: self.headers.clone(),
+            body: Cow::Borrowed(&self.body),
+            upgrade: self.upgrade,
+            streaming_strategy: self.streaming_strategy.clone(),
+        }
+    }
+
+    /// Converts the response into a response that owns its body, copying the body only if it is
+    /// borrowed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::HttpResponse;
+    ///
+    /// let body = b"Hello, World!".to_vec();
+    /// let response: HttpResponse<'static> = HttpResponse::ok(&body, vec![]).build().into_owned();
+    ///
+    /// assert_eq!(response.body(), b"Hello, World!");
+    /// ```
+    pub fn into_owned(self) -> HttpResponse<'static> {
+        HttpResponse {
+            status_code: self.status_code,
+            headers: self.headers,
+            body: Cow::Owned(self.body.into_owned()),
+            upgrade: self.upgrade,
+            streaming_strategy: self.streaming_strategy,
+        }
+    }
 }
 
 /// An HTTP response builder.
@@ -621,6 +865,7 @@ pub struct HttpResponseBuilder<'a> {
     headers: Vec<HeaderField>,
     body: Cow<'a, [u8]>,
     upgrade: Option<bool>,
+    streaming_strategy: Option<StreamingStrategy>,
 }
 
 impl<'a> HttpResponseBuilder<'a> {
@@ -734,10 +979,43 @@ impl<'a> HttpResponseBuilder<'a> {
         self
     }
 
+    /// Sets the streaming strategy of the HTTP response. This will determine how the HTTP
+    /// Gateway will retrieve the remainder of the response body, after the chunk of the body
+    /// that is included in the response.
+    ///
+    /// By default, the streaming strategy will be set to `None`, meaning that the response body
+    /// is not streamed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{HttpResponse, StreamingCallbackToken, StreamingStrategy};
+    /// use candid::{Func, Principal};
+    ///
+    /// let strategy = StreamingStrategy::Callback {
+    ///     callback: Func {
+    ///         principal: Principal::management_canister(),
+    ///         method: "http_request_streaming_callback".to_string(),
+    ///     },
+    ///     token: StreamingCallbackToken::new("/video.mp4", "identity", 1),
+    /// };
+    ///
+    /// let response = HttpResponse::builder()
+    ///     .with_streaming_strategy(strategy.clone())
+    ///     .build();
+    ///
+    /// assert_eq!(response.streaming_strategy(), Some(&strategy));
+    /// ```
+    pub fn with_streaming_strategy(mut self, streaming_strategy: StreamingStrategy) -> Self {
+        self.streaming_strategy = Some(streaming_strategy);
+
+        self
+    }
+
     /// Build an [HttpResponse] from the builder.
     ///
     /// If the status code is not set, it will default to `200`.
-    /// If the upgrade flag is not set, it will default to `None`.
+    /// If the upgrade flag or streaming strategy are not set, they will default to `None`.
     /// If the headers or body are not set, they will default to empty arrays.
     ///
     /// # Examples
@@ -763,12 +1041,14 @@ impl<'a> HttpResponseBuilder<'a> {
             headers: self.headers,
             body: self.body,
             upgrade: self.upgrade,
+            streaming_strategy: self.streaming_strategy,
         }
     }
 
     /// Build an [HttpUpdateResponse] from the builder.
     ///
     /// If the status code is not set, it will default to `200`.
+    /// If the streaming strategy is not set, it will default to `None`.
     /// If the headers or body are not set, they will default to empty arrays.
     ///
     /// # Examples
@@ -793,6 +1073,7 @@ impl<'a> HttpResponseBuilder<'a> {
             status_code: self.status_code.unwrap_or(StatusCode::OK.into()),
             headers: self.headers,
             body: self.body,
+            streaming_strategy: self.streaming_strategy,
         }
     }
 }
@@ -804,6 +1085,7 @@ impl<'a> From<HttpResponse<'a>> for HttpResponseBuilder<'a> {
             headers: response.headers,
             body: response.body,
             upgrade: response.upgrade,
+            streaming_strategy: response.streaming_strategy,
         }
     }
 }
@@ -820,6 +1102,7 @@ impl PartialEq for HttpResponse<'_> {
             && a_headers == b_headers
             && self.body == other.body
             && self.upgrade == other.upgrade
+            && self.streaming_strategy == other.streaming_strategy
     }
 }
 
@@ -838,6 +1121,7 @@ impl Debug for HttpResponse<'_> {
             .field("headers", &self.headers)
             .field("body", &formatted_body)
             .field("upgrade", &self.upgrade)
+            .field("streaming_strategy", &self.streaming_strategy)
             .finish()
     }
 }
@@ -875,9 +1159,65 @@ pub struct HttpUpdateResponse<'a> {
 
     /// HTTP response body as an array of bytes.
     body: Cow<'a, [u8]>,
+
+    /// The strategy the HTTP Gateway should use to retrieve the remainder of
+    /// the response body.
+    streaming_strategy: Option<StreamingStrategy>,
 }
 
 impl<'a> HttpUpdateResponse<'a> {
+    /// Creates and returns an instance of [HttpUpdateResponseBuilder], a builder-style object
+    /// which can be used to create an [HttpUpdateResponse].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{HttpUpdateResponse, StatusCode};
+    ///
+    /// let response = HttpUpdateResponse::builder()
+    ///     .with_status_code(StatusCode::CREATED)
+    ///     .with_headers(vec![("Content-Type".into(), "text/plain".into())])
+    ///     .with_body(b"Hello, World!")
+    ///     .build();
+    ///
+    /// assert_eq!(response.status_code(), StatusCode::CREATED);
+    /// assert_eq!(response.headers(), &[("Content-Type".into(), "text/plain".into())]);
+    /// assert_eq!(response.body(), b"Hello, World!");
+    /// ```
+    #[inline]
+    pub fn builder() -> HttpUpdateResponseBuilder<'a> {
+        HttpUpdateResponseBuilder::new()
+    }
+
+    /// Converts an [HttpResponse] into an [HttpUpdateResponse], failing with
+    /// [HttpCertificationError::UnexpectedUpgradeResponse] if the response would upgrade the
+    /// request to an update call.
+    ///
+    /// Unlike the [From] conversion, which discards the [upgrade](HttpResponse::upgrade) flag,
+    /// this catches query call responses that are accidentally returned from an update call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{HttpResponse, HttpUpdateResponse, StatusCode};
+    ///
+    /// let response = HttpResponse::ok(b"Hello, World!", vec![]).build();
+    /// let update_response = HttpUpdateResponse::try_from_response(response).unwrap();
+    ///
+    /// assert_eq!(update_response.status_code(), StatusCode::OK);
+    ///
+    /// let upgrade_response = HttpResponse::upgrade_to_update_call().build();
+    ///
+    /// assert!(HttpUpdateResponse::try_from_response(upgrade_response).is_err());
+    /// ```
+    pub fn try_from_response(response: HttpResponse<'a>) -> HttpCertificationResult<Self> {
+        if response.is_upgrade() {
+            return Err(HttpCertificationError::UnexpectedUpgradeResponse);
+        }
+
+        Ok(response.into())
+    }
+
     /// Returns the HTTP status code of the response.
     ///
     /// # Examples
@@ -914,6 +1254,53 @@ impl<'a> HttpUpdateResponse<'a> {
         &self.headers
     }
 
+    /// Returns the value of the first header of the response with the given name, if any.
+    /// Header names are compared case-insensitively.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{header, HttpResponse};
+    ///
+    /// let response = HttpResponse::ok(b"Hello, World!", vec![])
+    ///     .with_headers(vec![("Content-Type".into(), "text/plain".into())])
+    ///     .build();
+    ///
+    /// assert_eq!(response.get_header(header::CONTENT_TYPE), Some("text/plain"));
+    /// assert_eq!(response.get_header("content-type"), Some("text/plain"));
+    /// assert_eq!(response.get_header("ETag"), None);
+    /// ```
+    pub fn get_header(&self, name: impl AsRef<str>) -> Option<&str> {
+        find_header_value(&self.headers, name.as_ref())
+    }
+
+    /// Returns the values of all headers of the response with the given name, in the order that
+    /// they appear in the response. Header names are compared case-insensitively.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{header, HttpResponse};
+    ///
+    /// let response = HttpResponse::ok(b"Hello, World!", vec![])
+    ///     .with_headers(vec![
+    ///         ("Vary".into(), "Accept".into()),
+    ///         ("vary".into(), "Accept-Encoding".into()),
+    ///     ])
+    ///     .build();
+    ///
+    /// assert_eq!(
+    ///     response.get_header_values(header::VARY).collect::<Vec<_>>(),
+    ///     vec!["Accept", "Accept-Encoding"]
+    /// );
+    /// ```
+    pub fn get_header_values<'b>(
+        &'b self,
+        name: impl AsRef<str> + 'b,
+    ) -> impl Iterator<Item = &'b str> {
+        find_header_values(&self.headers, name)
+    }
+
     /// Returns a mutable reference to the HTTP headers of the response.
     ///
     /// # Examples
@@ -971,14 +1358,274 @@ impl<'a> HttpUpdateResponse<'a> {
     pub fn body(&self) -> &[u8] {
         &self.body
     }
+
+    /// Returns the streaming strategy of the response. This will determine how the HTTP Gateway
+    /// will retrieve the remainder of the response body.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{HttpResponse, StreamingCallbackToken, StreamingStrategy};
+    /// use candid::{Func, Principal};
+    ///
+    /// let strategy = StreamingStrategy::Callback {
+    ///     callback: Func {
+    ///         principal: Principal::management_canister(),
+    ///         method: "http_request_streaming_callback".to_string(),
+    ///     },
+    ///     token: StreamingCallbackToken::new("/video.mp4", "identity", 1),
+    /// };
+    ///
+    /// let response = HttpResponse::builder()
+    ///     .with_streaming_strategy(strategy.clone())
+    ///     .build_update();
+    ///
+    /// assert_eq!(response.streaming_strategy(), Some(&strategy));
+    /// ```
+    #[inline]
+    pub fn streaming_strategy(&self) -> Option<&StreamingStrategy> {
+        self.streaming_strategy.as_ref()
+    }
 }
 
+/// An HTTP update response builder.
+///
+/// This type can be used to construct an instance of an [HttpUpdateResponse] using a builder-like
+/// pattern.
+///
+/// # Examples
+///
+/// ```
+/// use ic_http_certification::{HttpUpdateResponseBuilder, StatusCode};
+///
+/// let response = HttpUpdateResponseBuilder::new()
+///     .with_status_code(StatusCode::CREATED)
+///     .with_headers(vec![("Content-Type".into(), "text/plain".into())])
+///     .with_body(b"Hello, World!")
+///     .build();
+///
+/// assert_eq!(response.status_code(), StatusCode::CREATED);
+/// assert_eq!(response.headers(), &[("Content-Type".into(), "text/plain".into())]);
+/// assert_eq!(response.body(), b"Hello, World!");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct HttpUpdateResponseBuilder<'a> {
+    status_code: Option<StatusCodeWrapper>,
+    headers: Vec<HeaderField>,
+    body: Cow<'a, [u8]>,
+    streaming_strategy: Option<StreamingStrategy>,
+}
+
+impl<'a> HttpUpdateResponseBuilder<'a> {
+    /// Creates a new instance of the [HttpUpdateResponseBuilder] that can be used to
+    /// construct an [HttpUpdateResponse].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{HttpUpdateResponseBuilder, StatusCode};
+    ///
+    /// let response = HttpUpdateResponseBuilder::new()
+    ///     .with_status_code(StatusCode::OK)
+    ///     .build();
+    ///
+    /// assert_eq!(response.status_code(), StatusCode::OK);
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the status code of the HTTP update response.
+    ///
+    /// By default, the status code will be set to `200`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{HttpUpdateResponse, StatusCode};
+    ///
+    /// let response = HttpUpdateResponse::builder()
+    ///     .with_status_code(StatusCode::NO_CONTENT)
+    ///     .build();
+    ///
+    /// assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+    /// ```
+    pub fn with_status_code(mut self, status_code: StatusCode) -> Self {
+        self.status_code = Some(status_code.into());
+
+        self
+    }
+
+    /// Sets the headers of the HTTP update response.
+    ///
+    /// By default, the headers will be set to an empty array.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::HttpUpdateResponse;
+    ///
+    /// let response = HttpUpdateResponse::builder()
+    ///     .with_headers(vec![("Content-Type".into(), "text/plain".into())])
+    ///     .build();
+    ///
+    /// assert_eq!(response.headers(), &[("Content-Type".into(), "text/plain".into())]);
+    /// ```
+    pub fn with_headers(mut self, headers: Vec<HeaderField>) -> Self {
+        self.headers = headers;
+
+        self
+    }
+
+    /// Sets the body of the HTTP update response.
+    ///
+    /// This function will accept both owned and borrowed values. By default,
+    /// the body will be set to an empty array.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::HttpUpdateResponse;
+    ///
+    /// let response = HttpUpdateResponse::builder()
+    ///     .with_body(b"Hello, World!")
+    ///     .build();
+    ///
+    /// assert_eq!(response.body(), b"Hello, World!");
+    /// ```
+    pub fn with_body(mut self, body: impl Into<Cow<'a, [u8]>>) -> Self {
+        self.body = body.into();
+
+        self
+    }
+
+    /// Sets the streaming strategy of the HTTP update response. This will determine how the HTTP
+    /// Gateway will retrieve the remainder of the response body.
+    ///
+    /// By default, the streaming strategy will be set to `None`, meaning that the response body
+    /// is not streamed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{HttpUpdateResponse, StreamingCallbackToken, StreamingStrategy};
+    /// use candid::{Func, Principal};
+    ///
+    /// let strategy = StreamingStrategy::Callback {
+    ///     callback: Func {
+    ///         principal: Principal::management_canister(),
+    ///         method: "http_request_streaming_callback".to_string(),
+    ///     },
+    ///     token: StreamingCallbackToken::new("/video.mp4", "identity", 1),
+    /// };
+    ///
+    /// let response = HttpUpdateResponse::builder()
+    ///     .with_streaming_strategy(strategy.clone())
+    ///     .build();
+    ///
+    /// assert_eq!(response.streaming_strategy(), Some(&strategy));
+    /// ```
+    pub fn with_streaming_strategy(mut self, streaming_strategy: StreamingStrategy) -> Self {
+        self.streaming_strategy = Some(streaming_strategy);
+
+        self
+    }
+
+    /// Build an [HttpUpdateResponse] from the builder.
+    ///
+    /// If the status code is not set, it will default to `200`.
+    /// If the streaming strategy is not set, it will default to `None`.
+    /// If the headers or body are not set, they will default to empty arrays.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{HttpUpdateResponse, StatusCode};
+    ///
+    /// let response = HttpUpdateResponse::builder()
+    ///     .with_body(b"Hello, World!")
+    ///     .build();
+    ///
+    /// assert_eq!(response.status_code(), StatusCode::OK);
+    /// assert!(response.headers().is_empty());
+    /// assert_eq!(response.body(), b"Hello, World!");
+    /// ```
+    pub fn build(self) -> HttpUpdateResponse<'a> {
+        HttpUpdateResponse {
+            status_code: self.status_code.unwrap_or(StatusCode::OK.into()),
+            headers: self.headers,
+            body: self.body,
+            streaming_strategy: self.streaming_strategy,
+        }
+    }
+}
+
+impl<'a> From<HttpUpdateResponse<'a>> for HttpUpdateResponseBuilder<'a> {
+    fn from(response: HttpUpdateResponse<'a>) -> Self {
+        Self {
+            status_code: Some(response.status_code),
+            headers: response.headers,
+            body: response.body,
+            streaming_strategy: response.streaming_strategy,
+        }
+    }
+}
+
+impl<'a> From<HttpResponseBuilder<'a>> for HttpUpdateResponseBuilder<'a> {
+    fn from(builder: HttpResponseBuilder<'a>) -> Self {
+        Self {
+            status_code: builder.status_code,
+            headers: builder.headers,
+            body: builder.body,
+            streaming_strategy: builder.streaming_strategy,
+        }
+    }
+}
+
+/// Converts an [HttpUpdateResponse] into an [HttpResponse], with the
+/// [upgrade](HttpResponse::upgrade) flag unset.
+///
+/// # Examples
+///
+/// ```
+/// use ic_http_certification::{HttpResponse, HttpUpdateResponse, StatusCode};
+///
+/// let update_response = HttpUpdateResponse::builder()
+///     .with_status_code(StatusCode::CREATED)
+///     .with_body(b"Hello, World!")
+///     .build();
+///
+/// let response = HttpResponse::from(update_response);
+///
+/// assert_eq!(response.status_code(), StatusCode::CREATED);
+/// assert_eq!(response.body(), b"Hello, World!");
+/// assert_eq!(response.upgrade(), None);
+/// ```
+impl<'a> From<HttpUpdateResponse<'a>> for HttpResponse<'a> {
+    fn from(response: HttpUpdateResponse<'a>) -> Self {
+        Self {
+            status_code: response.status_code,
+            headers: response.headers,
+            body: response.body,
+            upgrade: None,
+            streaming_strategy: response.streaming_strategy,
+        }
+    }
+}
+
+/// Converts an [HttpResponse] into an [HttpUpdateResponse], discarding the
+/// [upgrade](HttpResponse::upgrade) flag.
+///
+/// Use [HttpUpdateResponse::try_from_response] instead to reject responses that would upgrade
+/// the request to an update call, since such a response is not meaningful as the response to an
+/// update call.
 impl<'a> From<HttpResponse<'a>> for HttpUpdateResponse<'a> {
     fn from(response: HttpResponse<'a>) -> Self {
         Self {
             status_code: response.status_code,
             headers: response.headers,
             body: response.body,
+            streaming_strategy: response.streaming_strategy,
         }
     }
 }
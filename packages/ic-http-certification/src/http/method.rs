@@ -0,0 +1,300 @@
+use crate::HttpCertificationError;
+use candid::{
+    types::{Serializer, Type, TypeInner},
+    CandidType, Deserialize,
+};
+use serde::Deserializer;
+use std::{
+    fmt::{self, Display, Formatter},
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
+
+/// The method of an [HttpRequest](crate::HttpRequest).
+///
+/// Standard methods are parsed case-insensitively, so that a method sent as `get` is treated the
+/// same as `GET` when hashing the request and matching routes. Extension methods are
+/// case-sensitive and are kept as-is in [Other](Method::Other).
+///
+/// The uppercase associated constants, such as [Method::GET], are aliases of the standard
+/// variants.
+///
+/// # Examples
+///
+/// ```
+/// use ic_http_certification::Method;
+///
+/// let method: Method = "get".parse().unwrap();
+/// assert_eq!(method, Method::GET);
+/// assert_eq!(method.to_string(), "GET");
+///
+/// let method: Method = "PROPFIND".parse().unwrap();
+/// assert_eq!(method, Method::Other("PROPFIND".to_string()));
+/// assert_eq!(method.as_str(), "PROPFIND");
+/// ```
+#[derive(Debug, Clone)]
+pub enum Method {
+    /// The `GET` method.
+    Get,
+
+    /// The `HEAD` method.
+    Head,
+
+    /// The `POST` method.
+    Post,
+
+    /// The `PUT` method.
+    Put,
+
+    /// The `DELETE` method.
+    Delete,
+
+    /// The `CONNECT` method.
+    Connect,
+
+    /// The `OPTIONS` method.
+    Options,
+
+    /// The `TRACE` method.
+    Trace,
+
+    /// The `PATCH` method.
+    Patch,
+
+    /// An extension method, such as `PROPFIND`.
+    ///
+    /// Methods created with [FromStr] only use this variant for methods that are not standard,
+    /// but an [Other](Method::Other) variant containing the name of a standard method is still
+    /// equal to the standard variant.
+    Other(String),
+}
+
+impl Method {
+    /// The `GET` method.
+    pub const GET: Method = Method::Get;
+
+    /// The `HEAD` method.
+    pub const HEAD: Method = Method::Head;
+
+    /// The `POST` method.
+    pub const POST: Method = Method::Post;
+
+    /// The `PUT` method.
+    pub const PUT: Method = Method::Put;
+
+    /// The `DELETE` method.
+    pub const DELETE: Method = Method::Delete;
+
+    /// The `CONNECT` method.
+    pub const CONNECT: Method = Method::Connect;
+
+    /// The `OPTIONS` method.
+    pub const OPTIONS: Method = Method::Options;
+
+    /// The `TRACE` method.
+    pub const TRACE: Method = Method::Trace;
+
+    /// The `PATCH` method.
+    pub const PATCH: Method = Method::Patch;
+
+    const STANDARD_METHODS: [Method; 9] = [
+        Method::Get,
+        Method::Head,
+        Method::Post,
+        Method::Put,
+        Method::Delete,
+        Method::Connect,
+        Method::Options,
+        Method::Trace,
+        Method::Patch,
+    ];
+
+    /// Returns the name of the method, with standard methods in uppercase.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Method::Get => "GET",
+            Method::Head => "HEAD",
+            Method::Post => "POST",
+            Method::Put => "PUT",
+            Method::Delete => "DELETE",
+            Method::Connect => "CONNECT",
+            Method::Options => "OPTIONS",
+            Method::Trace => "TRACE",
+            Method::Patch => "PATCH",
+            Method::Other(method) => method,
+        }
+    }
+}
+
+/// Returns `true` if the given byte is a `tchar`, as defined by
+/// [RFC 9110](https://www.rfc-editor.org/rfc/rfc9110#name-tokens).
+fn is_token_char(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&byte)
+}
+
+impl FromStr for Method {
+    type Err = HttpCertificationError;
+
+    fn from_str(method: &str) -> Result<Self, Self::Err> {
+        if let Some(standard_method) = Method::STANDARD_METHODS
+            .into_iter()
+            .find(|standard_method| standard_method.as_str().eq_ignore_ascii_case(method))
+        {
+            return Ok(standard_method);
+        }
+
+        if method.is_empty() || !method.bytes().all(is_token_char) {
+            return Err(HttpCertificationError::MalformedMethod(method.to_string()));
+        }
+
+        Ok(Method::Other(method.to_string()))
+    }
+}
+
+impl Display for Method {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl PartialEq for Method {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for Method {}
+
+impl Hash for Method {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+    }
+}
+
+impl PartialEq<Method> for &Method {
+    fn eq(&self, other: &Method) -> bool {
+        *self == other
+    }
+}
+
+impl PartialEq<str> for Method {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for Method {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl CandidType for Method {
+    fn _ty() -> Type {
+        TypeInner::Text.into()
+    }
+
+    fn idl_serialize<S>(&self, serializer: S) -> Result<(), S::Error>
+    where
+        S: Serializer,
+    {
+        self.as_str().idl_serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Method {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer).and_then(|method| {
+            method
+                .parse()
+                .map_err(|err: HttpCertificationError| serde::de::Error::custom(err.to_string()))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use candid::{Decode, Encode};
+    use rstest::*;
+    use std::collections::HashSet;
+
+    #[rstest]
+    #[case("get", Method::GET)]
+    #[case("Post", Method::POST)]
+    #[case("DELETE", Method::DELETE)]
+    #[case("patch", Method::PATCH)]
+    #[case("PROPFIND", Method::Other("PROPFIND".to_string()))]
+    fn method_from_str(#[case] method: &str, #[case] expected: Method) {
+        assert_eq!(method.parse::<Method>().unwrap(), expected);
+    }
+
+    #[test]
+    fn extension_method_is_case_sensitive() {
+        let method: Method = "propfind".parse().unwrap();
+
+        assert_eq!(method.as_str(), "propfind");
+        assert_ne!(method, Method::Other("PROPFIND".to_string()));
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("GET POST")]
+    #[case("GET\r\n")]
+    #[case("(GET)")]
+    fn method_from_str_rejects_invalid_methods(#[case] method: &str) {
+        assert_matches!(
+            method.parse::<Method>(),
+            Err(HttpCertificationError::MalformedMethod(invalid_method)) if invalid_method == method
+        );
+    }
+
+    #[test]
+    fn method_display_round_trips() {
+        for method in Method::STANDARD_METHODS
+            .into_iter()
+            .chain([Method::Other("PROPFIND".to_string())])
+        {
+            assert_eq!(method.to_string().parse::<Method>().unwrap(), method);
+        }
+    }
+
+    #[test]
+    fn other_method_equals_standard_method() {
+        let other_get = Method::Other("GET".to_string());
+
+        assert_eq!(other_get, Method::GET);
+        assert_eq!(
+            HashSet::from([other_get, Method::GET, Method::POST]).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn method_is_decoded_case_insensitively() {
+        let cases = [
+            ("get", Method::GET),
+            ("Post", Method::POST),
+            ("DELETE", Method::DELETE),
+            ("PROPFIND", Method::Other("PROPFIND".to_string())),
+        ];
+
+        for (encoded_method, expected_method) in cases {
+            let bytes = Encode!(&encoded_method).unwrap();
+            let decoded_method = Decode!(&bytes, Method).unwrap();
+
+            assert_eq!(decoded_method, expected_method);
+        }
+    }
+
+    #[test]
+    fn invalid_method_is_not_decoded() {
+        let bytes = Encode!(&"GET POST").unwrap();
+
+        assert!(Decode!(&bytes, Method).is_err());
+    }
+}
@@ -5,7 +5,11 @@
 mod header_field;
 mod http_request;
 mod http_response;
+mod method;
+mod streaming;
 
 pub use header_field::*;
 pub use http_request::*;
 pub use http_response::*;
+pub use method::*;
+pub use streaming::*;
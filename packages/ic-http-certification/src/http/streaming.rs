@@ -0,0 +1,365 @@
+use candid::{CandidType, Deserialize, Func, Nat};
+use serde_bytes::ByteBuf;
+use std::{borrow::Cow, fmt::Debug};
+
+/// A Candid-encodable representation of the token that is passed to a
+/// [streaming callback](StreamingStrategy::Callback) to retrieve the next chunk of a streamed
+/// response body.
+///
+/// The HTTP Gateway treats the token as opaque and passes it back to the canister unchanged,
+/// so its fields can be used to track the progress of the stream.
+///
+/// # Examples
+///
+/// ```
+/// use ic_http_certification::StreamingCallbackToken;
+/// use candid::Nat;
+///
+/// let token = StreamingCallbackToken::new("/video.mp4", "identity", 1);
+///
+/// assert_eq!(token.key, "/video.mp4");
+/// assert_eq!(token.content_encoding, "identity");
+/// assert_eq!(token.index, Nat::from(1u32));
+/// assert_eq!(token.sha256, None);
+/// ```
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct StreamingCallbackToken {
+    /// The key of the asset that is being streamed, typically its path.
+    pub key: String,
+
+    /// The content encoding of the asset that is being streamed.
+    pub content_encoding: String,
+
+    /// The index of the next chunk that should be returned by the streaming callback.
+    pub index: Nat,
+
+    /// The SHA-256 hash of the full asset body, if known.
+    pub sha256: Option<ByteBuf>,
+}
+
+impl StreamingCallbackToken {
+    /// Creates a new [StreamingCallbackToken] for the chunk at the given index of the asset
+    /// with the given key and content encoding.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::StreamingCallbackToken;
+    /// use candid::Nat;
+    ///
+    /// let token = StreamingCallbackToken::new("/video.mp4", "gzip", 2);
+    ///
+    /// assert_eq!(token.index, Nat::from(2u32));
+    /// ```
+    pub fn new(key: impl Into<String>, content_encoding: impl Into<String>, index: u32) -> Self {
+        Self {
+            key: key.into(),
+            content_encoding: content_encoding.into(),
+            index: index.into(),
+            sha256: None,
+        }
+    }
+
+    /// Sets the SHA-256 hash of the full asset body.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::StreamingCallbackToken;
+    ///
+    /// let token = StreamingCallbackToken::new("/video.mp4", "identity", 1).with_sha256([0; 32]);
+    ///
+    /// assert_eq!(token.sha256.unwrap().into_vec(), vec![0; 32]);
+    /// ```
+    pub fn with_sha256(mut self, sha256: impl Into<Vec<u8>>) -> Self {
+        self.sha256 = Some(ByteBuf::from(sha256.into()));
+
+        self
+    }
+}
+
+/// A Candid-encodable representation of the strategy that the HTTP Gateway should use to
+/// retrieve the remainder of a streamed response body.
+///
+/// # Examples
+///
+/// ```
+/// use ic_http_certification::{HttpResponse, StreamingCallbackToken, StreamingStrategy};
+/// use candid::{Func, Principal};
+///
+/// let strategy = StreamingStrategy::Callback {
+///     callback: Func {
+///         principal: Principal::management_canister(),
+///         method: "http_request_streaming_callback".to_string(),
+///     },
+///     token: StreamingCallbackToken::new("/video.mp4", "identity", 1),
+/// };
+///
+/// let response = HttpResponse::ok(b"first chunk", vec![])
+///     .with_streaming_strategy(strategy.clone())
+///     .build();
+///
+/// assert_eq!(response.streaming_strategy(), Some(&strategy));
+/// ```
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub enum StreamingStrategy {
+    /// The HTTP Gateway will repeatedly call the `callback` query method of the canister with
+    /// the `token`, until the callback returns a response without a token.
+    Callback {
+        /// The canister query method to call for the next chunk of the response body.
+        callback: Func,
+
+        /// The token to pass to the callback.
+        token: StreamingCallbackToken,
+    },
+}
+
+/// A Candid-encodable representation of the response of a
+/// [streaming callback](StreamingStrategy::Callback), containing the next chunk of a streamed
+/// response body.
+///
+/// # Examples
+///
+/// ```
+/// use ic_http_certification::{StreamingCallbackHttpResponse, StreamingCallbackToken};
+///
+/// let response = StreamingCallbackHttpResponse::builder()
+///     .with_body(b"second chunk")
+///     .with_token(StreamingCallbackToken::new("/video.mp4", "identity", 2))
+///     .build();
+///
+/// assert_eq!(response.body(), b"second chunk");
+/// assert_eq!(response.token(), Some(&StreamingCallbackToken::new("/video.mp4", "identity", 2)));
+/// ```
+#[derive(Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct StreamingCallbackHttpResponse<'a> {
+    /// The next chunk of the response body as an array of bytes.
+    body: Cow<'a, [u8]>,
+
+    /// The token to pass to the streaming callback for the next chunk, or `None` if this is
+    /// the last chunk.
+    token: Option<StreamingCallbackToken>,
+}
+
+impl<'a> StreamingCallbackHttpResponse<'a> {
+    /// Creates and returns an instance of [StreamingCallbackHttpResponseBuilder], a builder-style
+    /// object which can be used to create a [StreamingCallbackHttpResponse].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::StreamingCallbackHttpResponse;
+    ///
+    /// let response = StreamingCallbackHttpResponse::builder()
+    ///     .with_body(b"last chunk")
+    ///     .build();
+    ///
+    /// assert_eq!(response.body(), b"last chunk");
+    /// assert_eq!(response.token(), None);
+    /// ```
+    #[inline]
+    pub fn builder() -> StreamingCallbackHttpResponseBuilder<'a> {
+        StreamingCallbackHttpResponseBuilder::new()
+    }
+
+    /// Returns the chunk of the response body.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::StreamingCallbackHttpResponse;
+    ///
+    /// let response = StreamingCallbackHttpResponse::builder()
+    ///     .with_body(b"last chunk")
+    ///     .build();
+    ///
+    /// assert_eq!(response.body(), b"last chunk");
+    /// ```
+    #[inline]
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// Returns the token for the next chunk of the response body, or `None` if this is the last
+    /// chunk.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{StreamingCallbackHttpResponse, StreamingCallbackToken};
+    ///
+    /// let token = StreamingCallbackToken::new("/video.mp4", "identity", 2);
+    /// let response = StreamingCallbackHttpResponse::builder()
+    ///     .with_token(token.clone())
+    ///     .build();
+    ///
+    /// assert_eq!(response.token(), Some(&token));
+    /// ```
+    #[inline]
+    pub fn token(&self) -> Option<&StreamingCallbackToken> {
+        self.token.as_ref()
+    }
+}
+
+impl Debug for StreamingCallbackHttpResponse<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Truncate body to 100 characters for debug output
+        let max_body_len = 100;
+        let formatted_body = if self.body.len() > max_body_len {
+            format!("{:?}...", &self.body[..max_body_len])
+        } else {
+            format!("{:?}", &self.body)
+        };
+
+        f.debug_struct("StreamingCallbackHttpResponse")
+            .field("body", &formatted_body)
+            .field("token", &self.token)
+            .finish()
+    }
+}
+
+/// A streaming callback response builder.
+///
+/// This type can be used to construct an instance of a [StreamingCallbackHttpResponse] using a
+/// builder-like pattern.
+///
+/// # Examples
+///
+/// ```
+/// use ic_http_certification::{StreamingCallbackHttpResponseBuilder, StreamingCallbackToken};
+///
+/// let response = StreamingCallbackHttpResponseBuilder::new()
+///     .with_body(b"second chunk")
+///     .with_token(StreamingCallbackToken::new("/video.mp4", "identity", 2))
+///     .build();
+///
+/// assert_eq!(response.body(), b"second chunk");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct StreamingCallbackHttpResponseBuilder<'a> {
+    body: Cow<'a, [u8]>,
+    token: Option<StreamingCallbackToken>,
+}
+
+impl<'a> StreamingCallbackHttpResponseBuilder<'a> {
+    /// Creates a new instance of the [StreamingCallbackHttpResponseBuilder] that can be used to
+    /// construct a [StreamingCallbackHttpResponse].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::StreamingCallbackHttpResponseBuilder;
+    ///
+    /// let response = StreamingCallbackHttpResponseBuilder::new()
+    ///     .with_body(b"last chunk")
+    ///     .build();
+    ///
+    /// assert_eq!(response.body(), b"last chunk");
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the chunk of the response body.
+    ///
+    /// This function will accept both owned and borrowed values. By default,
+    /// the body will be set to an empty array.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::StreamingCallbackHttpResponse;
+    ///
+    /// let response = StreamingCallbackHttpResponse::builder()
+    ///     .with_body(b"last chunk")
+    ///     .build();
+    ///
+    /// assert_eq!(response.body(), b"last chunk");
+    /// ```
+    pub fn with_body(mut self, body: impl Into<Cow<'a, [u8]>>) -> Self {
+        self.body = body.into();
+
+        self
+    }
+
+    /// Sets the token for the next chunk of the response body.
+    ///
+    /// By default, the token will be set to `None`, which ends the stream.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{StreamingCallbackHttpResponse, StreamingCallbackToken};
+    ///
+    /// let token = StreamingCallbackToken::new("/video.mp4", "identity", 2);
+    /// let response = StreamingCallbackHttpResponse::builder()
+    ///     .with_token(token.clone())
+    ///     .build();
+    ///
+    /// assert_eq!(response.token(), Some(&token));
+    /// ```
+    pub fn with_token(mut self, token: StreamingCallbackToken) -> Self {
+        self.token = Some(token);
+
+        self
+    }
+
+    /// Build a [StreamingCallbackHttpResponse] from the builder.
+    ///
+    /// If the body is not set, it will default to an empty array.
+    /// If the token is not set, it will default to `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::StreamingCallbackHttpResponse;
+    ///
+    /// let response = StreamingCallbackHttpResponse::builder().build();
+    ///
+    /// assert_eq!(response.body(), b"");
+    /// assert_eq!(response.token(), None);
+    /// ```
+    pub fn build(self) -> StreamingCallbackHttpResponse<'a> {
+        StreamingCallbackHttpResponse {
+            body: self.body,
+            token: self.token,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candid::{Decode, Encode, Principal};
+
+    #[test]
+    fn streaming_callback_http_response_candid_round_trip() {
+        let response = StreamingCallbackHttpResponse::builder()
+            .with_body(b"second chunk".to_vec())
+            .with_token(
+                StreamingCallbackToken::new("/video.mp4", "identity", 2).with_sha256([1; 32]),
+            )
+            .build();
+
+        let bytes = Encode!(&response).unwrap();
+        let decoded = Decode!(&bytes, StreamingCallbackHttpResponse).unwrap();
+
+        assert_eq!(decoded, response);
+    }
+
+    #[test]
+    fn streaming_strategy_candid_round_trip() {
+        let strategy = StreamingStrategy::Callback {
+            callback: Func {
+                principal: Principal::management_canister(),
+                method: "http_request_streaming_callback".to_string(),
+            },
+            token: StreamingCallbackToken::new("/video.mp4", "gzip", 1),
+        };
+
+        let bytes = Encode!(&strategy).unwrap();
+        let decoded = Decode!(&bytes, StreamingStrategy).unwrap();
+
+        assert_eq!(decoded, strategy);
+    }
+}
@@ -0,0 +1,150 @@
+//! The instrumentation module records the number of instructions spent on certification
+//! operations, so that the cost of certification can be profiled in a deployed canister.
+//!
+//! This module is only available with the `instrumentation` feature enabled. Instructions are
+//! counted with the canister's performance counter, so they are only recorded when the canister
+//! is compiled for the `wasm32` target. On other targets, only the number of operations is
+//! recorded.
+
+use std::cell::Cell;
+
+/// The number of times that an operation was performed, and the total number of instructions
+/// that were spent on it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OperationStats {
+    /// The number of times that the operation was performed.
+    pub count: u64,
+
+    /// The total number of instructions that were spent on the operation.
+    pub instructions: u64,
+}
+
+/// Instruction counts of certification operations, see [instruction_stats].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InstructionStats {
+    /// Inserting entries with [HttpCertificationTree::insert](crate::HttpCertificationTree::insert)
+    /// and [HttpCertificationTree::insert_many](crate::HttpCertificationTree::insert_many).
+    pub tree_insert: OperationStats,
+
+    /// Deleting entries with [HttpCertificationTree::delete](crate::HttpCertificationTree::delete)
+    /// and [HttpCertificationTree::delete_many](crate::HttpCertificationTree::delete_many).
+    pub tree_delete: OperationStats,
+
+    /// Creating witnesses with
+    /// [HttpCertificationTree::witness](crate::HttpCertificationTree::witness).
+    pub tree_witness: OperationStats,
+
+    /// Hashing requests and responses when creating certifications with
+    /// [HttpCertification::response_only](crate::HttpCertification::response_only) and
+    /// [HttpCertification::full](crate::HttpCertification::full).
+    pub certification_hash: OperationStats,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Operation {
+    TreeInsert,
+    TreeDelete,
+    TreeWitness,
+    CertificationHash,
+}
+
+thread_local! {
+    static INSTRUCTION_STATS: Cell<InstructionStats> = Cell::new(InstructionStats::default());
+}
+
+/// Returns the instruction counts of the certification operations that were performed since the
+/// canister was started, or since the stats were last [reset](reset_instruction_stats).
+///
+/// # Examples
+///
+/// ```
+/// use ic_http_certification::{
+///     instrumentation::instruction_stats, HttpCertification, HttpCertificationPath,
+///     HttpCertificationTree, HttpCertificationTreeEntry,
+/// };
+///
+/// let mut http_certification_tree = HttpCertificationTree::default();
+/// http_certification_tree.insert(&HttpCertificationTreeEntry::new(
+///     HttpCertificationPath::exact("/"),
+///     HttpCertification::skip(),
+/// ));
+///
+/// assert_eq!(instruction_stats().tree_insert.count, 1);
+/// ```
+pub fn instruction_stats() -> InstructionStats {
+    INSTRUCTION_STATS.with(Cell::get)
+}
+
+/// Resets the instruction counts returned by [instruction_stats].
+pub fn reset_instruction_stats() {
+    INSTRUCTION_STATS.with(|stats| stats.set(InstructionStats::default()));
+}
+
+/// Records the instructions spent between its creation and its drop for an [Operation].
+#[derive(Debug)]
+pub(crate) struct Measurement {
+    operation: Operation,
+    start: u64,
+}
+
+impl Measurement {
+    pub(crate) fn start(operation: Operation) -> Self {
+        Self {
+            operation,
+            start: performance_counter(),
+        }
+    }
+}
+
+impl Drop for Measurement {
+    fn drop(&mut self) {
+        let instructions = performance_counter().saturating_sub(self.start);
+
+        INSTRUCTION_STATS.with(|stats| {
+            let mut instruction_stats = stats.get();
+            let operation_stats = match self.operation {
+                Operation::TreeInsert => &mut instruction_stats.tree_insert,
+                Operation::TreeDelete => &mut instruction_stats.tree_delete,
+                Operation::TreeWitness => &mut instruction_stats.tree_witness,
+                Operation::CertificationHash => &mut instruction_stats.certification_hash,
+            };
+            operation_stats.count += 1;
+            operation_stats.instructions += instructions;
+
+            stats.set(instruction_stats);
+        });
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn performance_counter() -> u64 {
+    // counter type 0 counts the instructions executed in the current message
+    ic_cdk::api::performance_counter(0)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn performance_counter() -> u64 {
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measurement_records_operation() {
+        reset_instruction_stats();
+
+        drop(Measurement::start(Operation::TreeWitness));
+        drop(Measurement::start(Operation::TreeWitness));
+        drop(Measurement::start(Operation::CertificationHash));
+
+        let stats = instruction_stats();
+        assert_eq!(stats.tree_witness.count, 2);
+        assert_eq!(stats.certification_hash.count, 1);
+        assert_eq!(stats.tree_insert, OperationStats::default());
+
+        reset_instruction_stats();
+        assert_eq!(instruction_stats(), InstructionStats::default());
+    }
+}
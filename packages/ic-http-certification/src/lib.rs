@@ -92,10 +92,28 @@ let cel_expr = DefaultCelBuilder::full_certification()
     .build();
 ```
 
+To catch mistakes that would otherwise only surface when the HTTP gateway fails to verify a response, use [try_build](cel::DefaultFullCelExpressionBuilder::try_build) instead of `build`. It returns an error if a header or query parameter is listed more than once, if a header that the HTTP gateway removes (such as `Connection`) or `IC-Certificate` is certified, or if `IC-CertificateExpression` is excluded from certification.
+
+```
+use ic_http_certification::{DefaultCelBuilder, DefaultResponseCertification};
+
+let result = DefaultCelBuilder::full_certification()
+    .with_request_headers(vec!["Accept", "accept"])
+    .with_response_certification(DefaultResponseCertification::certified_response_headers(vec![
+        "Cache-Control",
+        "ETag",
+    ]))
+    .try_build();
+
+assert!(result.is_err());
+```
+
 #### Partially certified request
 
 Any number of request headers or request query parameters can be certified via `with_request_headers` and `with_request_query_parameters` respectively. Both methods will accept empty arrays, which is the same as not calling them at all. Likewise for `with_request_query_parameters`, if it is called with an empty array, or not called at all, then no request query parameters will be certified. If both are called with an empty array, or neither are called, then only the request body and method will be certified, in addition to the response. As a reminder here, the response is always at least partially certified if the request is certified.
 
+Request headers can only be included in certification by name. Unlike response headers, there is no exclusion mode for request headers, because the HTTP Gateway Protocol does not define one: the headers of a request are added and modified by browsers, proxies and the HTTP Gateway itself, so certifying all request headers except a few would not be verifiable.
+
 For example, to certify only the request body and method, in addition to the response:
 
 ```rust
@@ -308,6 +326,20 @@ use ic_http_certification::HttpCertificationPath;
 let path = HttpCertificationPath::exact("/js/example.js");
 ```
 
+Canisters that route requests with templates, such as `/todos/{id}`, can use the [HttpCertificationRoute] struct to determine the path that responses for a route should be certified under. Routes without a catch-all parameter produce an exact path for every request path, while routes ending with a catch-all parameter, such as `/assets/{*path}`, produce a wildcard path for the part of the request path preceding it. Creating a tree entry through the route ensures that the request path matches the route, so all responses for the route are consistently certified under the same kind of path.
+
+```rust
+use ic_http_certification::{HttpCertification, HttpCertificationPath, HttpCertificationRoute, HttpCertificationTreeEntry, HttpRequest};
+
+let route = HttpCertificationRoute::new("/todos/{id}").unwrap();
+let request = HttpRequest::get("/todos/1").build();
+
+let entry = route.tree_entry(&request, HttpCertification::skip()).unwrap();
+assert_eq!(*entry.path, HttpCertificationPath::exact("/todos/1"));
+```
+
+Certifications are looked up with the path returned by [HttpRequest::get_path], which is percent-decoded but otherwise left as it was requested, so responses must be certified under that path. The [normalize_path](utils::normalize_path) util collapses duplicate slashes and removes dot segments, which is useful for routing, for example to redirect `//js/./example.js` to `/js/example.js`, but it does not return the path that certifications are looked up with. A response served for `//js/./example.js` must still be certified for `//js/./example.js`.
+
 ### Using the HTTP certification tree
 
 The [HttpCertificationTree] can be easily initialized with the [Default] trait and entries can be added to, removed from, or have witnesses generated by the tree using the [HttpCertificationTreeEntry] struct. The [HttpCertificationTreeEntry] requires an [HttpCertification] and an [HttpCertificationPath].
@@ -364,15 +396,120 @@ http_certification_tree.delete(&entry);
 
 ### Handling upgrades
 
-CEL expressions, certifications, the certification tree, and the corresponding requests and responses are not persisted across upgrades, by default. This means that if a canister is upgraded, all of this information will be lost. To handle upgrades effectively, all initialization logic run in the canister's `init` hook should also be run in the `post_upgrade` hook. This will ensure that the certification tree is correctly re-initialized after an upgrade. Most data structures, aside from the certification tree, can be persisted using stable memory, and the certification tree can be re-initialized using this persisted data. Care should be taken to not exceed the canister's instruction limit when re-initializing the certification tree, which can easily occur if the number of responses being certified grows very large. This case could potentially be addressed in the future by developing a stable memory compatible certification tree.
+CEL expressions, certifications, the certification tree, and the corresponding requests and responses are not persisted across upgrades, by default. This means that if a canister is upgraded, all of this information will be lost. To handle upgrades effectively, all initialization logic run in the canister's `init` hook should also be run in the `post_upgrade` hook. This will ensure that the certification tree is correctly re-initialized after an upgrade. Most data structures, aside from the certification tree, can be persisted using stable memory, and the certification tree can be re-initialized using this persisted data. Care should be taken to not exceed the canister's instruction limit when re-initializing the certification tree, which can easily occur if the number of responses being certified grows very large. To avoid this, the certification tree can be serialized, since it implements `serde`'s `Serialize` and `Deserialize` traits. The tree can be written to stable memory in the `pre_upgrade` hook and restored in the `post_upgrade` hook, instead of certifying every response again:
+
+```rust
+use ic_http_certification::HttpCertificationTree;
+
+let http_certification_tree = HttpCertificationTree::default();
+
+// in the `pre_upgrade` hook
+let bytes = serde_cbor::to_vec(&http_certification_tree).unwrap();
+
+// in the `post_upgrade` hook
+let http_certification_tree: HttpCertificationTree = serde_cbor::from_slice(&bytes).unwrap();
+```
+
+The structure of the tree is serialized along with its entries, so the root hash of the restored tree is equal to the root hash before the upgrade, even though it depends on the order in which entries were inserted and deleted. The canister's certified variable therefore does not need to be updated in the `post_upgrade` hook after restoring the tree.
 
 ### Changing data
 
 In addition to initializing certifications in the `init` and `post_upgrade` hooks, if a response is changed during the canister's lifetime in response to an `update` call, the certification tree should be updated to reflect this change. This can be done by deleting the old certification from the tree and inserting the new certification. This should be done in the same `update` call as the response is changed to ensure that the certification tree is always up-to-date, otherwise, `query` calls returning that response will fail verification.
 
+If the old certification is no longer known, for example because only the new response is kept in memory, all certifications of a path can be deleted at once with [HttpCertificationTree::delete_by_path], regardless of the request and response that they certify:
+
+```rust
+use ic_http_certification::{HttpCertification, HttpCertificationPath, HttpCertificationTree, HttpCertificationTreeEntry};
+
+let mut http_certification_tree = HttpCertificationTree::default();
+
+let path = HttpCertificationPath::exact("/example.json");
+let entry = HttpCertificationTreeEntry::new(&path, HttpCertification::skip());
+http_certification_tree.insert(&entry);
+
+// delete every certification of `/example.json`
+http_certification_tree.delete_by_path(&path);
+```
+
+### Routing certified responses
+
+Instead of keeping responses, their certifications and the certification tree in sync manually, the [HttpCertificationRouter] can be used. Responses are certified with [HttpCertificationRouter::certify_response], or with [HttpCertificationRouter::certify_fallback_response] for fallback responses such as a 404 response, which replace any response previously certified for the same request or scope together with its certification. In the `http_request` query call, [HttpCertificationRouter::serve_query] serves the certified response for a request with the `IC-Certificate` header already added, or upgrades the request to an `update` call if it matches an update route. In the `http_request_update` update call, [HttpCertificationRouter::update_handler] returns the handler for the request together with its route parameters.
+
+Several alternative responses can be certified for the same method and path, such as one response per `Accept` type, with [HttpCertificationRouter::certify_response_variant]. The canister then selects the variant for a request and serves it with [HttpCertificationRouter::serve_query_variant], which adds the witness for that variant's certification.
+
+[HttpCertificationRouter::serve_query] copies the body of the certified response, since the returned response must outlive the router's borrow. For large bodies, [HttpCertificationRouter::serve_query_borrowed] returns a response that borrows its body from the router instead, which can be encoded while the router is still borrowed, for example from a `manual_reply` query.
+
+API canisters usually need a certified response for every error they can return. An [ErrorResponseCatalog] contains plain text `400`, `401`, `403`, `404`, `405`, `429` and `500` responses, whose bodies and headers can be customized, and [HttpCertificationRouter::certify_error_responses] certifies all of them for a scope in one call. When a request fails, [HttpCertificationRouter::serve_error] serves the certified response for the status code, and the `404` response is also served for request paths without any other certified response. Since the HTTP Gateway only accepts a response certified for a scope if no response is certified for the exact request path under any method and no more specific scope covers the request path, error and fallback responses are only served from the most specific scope covering a request path, and never for request paths that have a response certified for another method.
+
+```rust
+use ic_http_certification::{DefaultCelBuilder, HttpCertificationRouter, HttpRequest, HttpResponse, HttpRouteParams, Method, StatusCode};
+
+fn create_todo_handler(_request: &HttpRequest, _params: &HttpRouteParams) -> HttpResponse<'static> {
+    HttpResponse::builder().with_status_code(StatusCode::CREATED).build()
+}
+
+let cel_expr = DefaultCelBuilder::full_certification().build();
+let mut router = HttpCertificationRouter::default();
+
+let request = HttpRequest::get("/todos").build();
+let response = HttpResponse::ok(b"[]", vec![]).build();
+router.certify_response(&request, response, &cel_expr).unwrap();
+router.add_update_route(Method::POST, "/todos", create_todo_handler).unwrap();
+
+// set the canister's certified variable to `router.root_hash()`, then in the `http_request` query call
+let data_certificate = vec![1, 2, 3];
+let response = router.serve_query(&request, &data_certificate).unwrap();
+```
+
+### Supporting response verification version 1
+
+HTTP Gateways that only support version 1 of response verification look up the SHA-256 hash of the response body for a request path, rather than the certifications described above. To serve these gateways during a transition period, create the tree with [HttpCertificationTree::with_v1_certification]. Certify response bodies for version 1 with [insert_v1](HttpCertificationTree::insert_v1), alongside the entries for version 2, and add the witness returned by [witness_v1](HttpCertificationTree::witness_v1) to the response with [utils::add_v1_certificate_header]. The root hash of the tree covers both versions, so it can still be used as the canister's certified variable.
+
+```rust
+use ic_http_certification::{HttpCertification, HttpCertificationPath, HttpCertificationTree, HttpCertificationTreeEntry};
+
+let mut http_certification_tree = HttpCertificationTree::with_v1_certification();
+
+let body = b"<html></html>";
+let entry = HttpCertificationTreeEntry::new(HttpCertificationPath::exact("/index.html"), HttpCertification::skip());
+http_certification_tree.insert(&entry);
+http_certification_tree.insert_v1("/index.html", body).unwrap();
+
+// for clients requesting version 2
+let witness = http_certification_tree.witness(&entry, "/index.html").unwrap();
+
+// for clients requesting version 1
+let v1_witness = http_certification_tree.witness_v1("/index.html").unwrap();
+```
+
+### Combining certified data
+
+A canister only has a single certified variable, so if it certifies other state alongside its HTTP responses, the root hashes of the trees need to be combined. [CertifiedData] combines the root hashes of several subtrees, identified by a key that is typically the label of the subtree. The root hash of an HTTP certification tree is inserted with the key [CertifiedData::HTTP_CERTIFICATION_TREE_KEY]. The witnesses of each subtree are combined with the pruned hashes of the other subtrees with [witness](CertifiedData::witness), so that HTTP Gateways can verify them against the canister's certified variable.
+
+```rust
+use ic_http_certification::{CertifiedData, HttpCertification, HttpCertificationPath, HttpCertificationTree, HttpCertificationTreeEntry};
+
+let mut http_certification_tree = HttpCertificationTree::default();
+let entry = HttpCertificationTreeEntry::new(HttpCertificationPath::exact("/index.html"), HttpCertification::skip());
+http_certification_tree.insert(&entry);
+
+let mut certified_data = CertifiedData::new();
+certified_data.insert(CertifiedData::HTTP_CERTIFICATION_TREE_KEY, http_certification_tree.root_hash());
+certified_data.insert("state", [0; 32]);
+
+// set_certified_data(&certified_data.root_hash());
+
+let witness = http_certification_tree.witness(&entry, "/index.html").unwrap();
+let witness = certified_data.witness(CertifiedData::HTTP_CERTIFICATION_TREE_KEY, witness).unwrap();
+```
+
+### Profiling certification
+
+With the `instrumentation` feature enabled, the number of instructions spent on inserting entries into the tree, deleting entries from the tree, creating witnesses and hashing requests and responses for certifications is recorded, and can be retrieved with `instrumentation::instruction_stats`. This can be used to profile the cost of certification in a deployed canister, for example by exposing the stats through a query call.
+
 ## Directly creating a CEL expression
 
-To define a CEL expression, start with the [CelExpression] enum. This enum provides a set of variants that can be used to define different types of CEL expressions supported by ICP HTTP gateways. Currently only one variant is supported, known as the "default" certification expression, but more may be added in the future as the HTTP certification protocol evolves over time.
+To define a CEL expression, start with the [CelExpression] enum. This enum provides a set of variants that can be used to define different types of CEL expressions supported by ICP HTTP gateways. Currently only one variant is supported, known as the "default" certification expression, but more may be added in the future as the HTTP certification protocol evolves over time. Expressions that use a function which is not supported by this library yet can be carried as a raw string using the [CelExpression::Unknown] variant.
 
 When certifying requests:
 
@@ -620,6 +757,10 @@ pub mod http;
 pub use http::*;
 pub mod tree;
 pub use tree::*;
+pub mod router;
+pub use router::*;
+#[cfg(feature = "instrumentation")]
+pub mod instrumentation;
 pub mod utils;
 
 // https://github.com/la10736/rstest/tree/master/rstest_reuse#cavelets
@@ -0,0 +1,1443 @@
+use crate::{
+    utils::add_v2_certificate_header, DefaultFullCelExpression, DefaultResponseOnlyCelExpression,
+    ErrorResponseCatalog, Hash, HttpCertification, HttpCertificationError, HttpCertificationPath,
+    HttpCertificationResult, HttpCertificationRoute, HttpCertificationTree,
+    HttpCertificationTreeEntry, HttpRequest, HttpResponse, HttpRouteParams, Method, StatusCode,
+    CERTIFICATE_EXPRESSION_HEADER_NAME,
+};
+use std::collections::{BTreeMap, HashMap};
+
+/// A handler for an update route of an [HttpCertificationRouter].
+pub type HttpRouteHandler = fn(&HttpRequest, &HttpRouteParams) -> HttpResponse<'static>;
+
+#[derive(Debug, Clone)]
+struct CertifiedResponse {
+    response: HttpResponse<'static>,
+    certification: HttpCertification,
+    tree_path: HttpCertificationPath<'static>,
+}
+
+#[derive(Clone)]
+struct UpdateRoute {
+    method: Method,
+    route: HttpCertificationRoute,
+    handler: HttpRouteHandler,
+}
+
+impl std::fmt::Debug for UpdateRoute {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UpdateRoute")
+            .field("method", &self.method)
+            .field("route", &self.route)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A router that serves certified responses to `http_request` query calls and dispatches
+/// `http_request_update` update calls to handlers.
+///
+/// Responses are certified ahead of time with [certify_response](HttpCertificationRouter::certify_response)
+/// and [certify_fallback_response](HttpCertificationRouter::certify_fallback_response), which keep
+/// the responses and their certifications in an [HttpCertificationTree] together, so a response is
+/// never served without its certification and a replaced response never leaves a stale
+/// certification behind. [serve_query](HttpCertificationRouter::serve_query) then adds the
+/// `IC-Certificate` header to the response for a request.
+///
+/// Requests matching an [update route](HttpCertificationRouter::add_update_route) are upgraded to
+/// update calls, and are handled by the handler returned by
+/// [update_handler](HttpCertificationRouter::update_handler).
+///
+/// After certifying or removing responses, the canister's certified variable will need to be
+/// updated with the new [root hash](HttpCertificationRouter::root_hash) of the router.
+///
+/// # Examples
+///
+/// ```
+/// use ic_http_certification::{
+///     DefaultCelBuilder, DefaultResponseCertification, HttpCertificationRouter, HttpRequest,
+///     HttpResponse, HttpRouteParams, Method,
+/// };
+///
+/// fn create_todo_handler(_request: &HttpRequest, _params: &HttpRouteParams) -> HttpResponse<'static> {
+///     HttpResponse::created(b"{}".to_vec(), vec![]).build()
+/// }
+///
+/// let cel_expr = DefaultCelBuilder::full_certification()
+///     .with_response_certification(DefaultResponseCertification::response_header_exclusions(vec![]))
+///     .build();
+/// let not_found_cel_expr = DefaultCelBuilder::response_only_certification()
+///     .with_response_certification(DefaultResponseCertification::response_header_exclusions(vec![]))
+///     .build();
+///
+/// let mut router = HttpCertificationRouter::default();
+/// router
+///     .certify_response(
+///         &HttpRequest::get("/todos").build(),
+///         HttpResponse::ok(b"[]".to_vec(), vec![]).build(),
+///         &cel_expr,
+///     )
+///     .unwrap();
+/// router
+///     .certify_fallback_response("", HttpResponse::not_found(b"Not Found".to_vec(), vec![]).build(), &not_found_cel_expr)
+///     .unwrap();
+/// router.add_update_route(Method::POST, "/todos", create_todo_handler).unwrap();
+/// // set_certified_data(&router.root_hash());
+///
+/// // this should normally be retrieved using `ic_cdk::api::data_certificate()`.
+/// let data_certificate = vec![1, 2, 3];
+///
+/// // in `http_request`
+/// let response = router.serve_query(&HttpRequest::get("/todos").build(), &data_certificate).unwrap();
+/// assert_eq!(response.body(), b"[]");
+///
+/// let response = router.serve_query(&HttpRequest::get("/users").build(), &data_certificate).unwrap();
+/// assert_eq!(response.body(), b"Not Found");
+///
+/// let request = HttpRequest::post("/todos").build();
+/// let response = router.serve_query(&request, &data_certificate).unwrap();
+/// assert!(response.is_upgrade());
+///
+/// // in `http_request_update`
+/// let (handler, params) = router.update_handler(&request).unwrap().unwrap();
+/// let response = handler(&request, &params);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct HttpCertificationRouter {
+    tree: HttpCertificationTree,
+    responses: HashMap<(Method, String), CertifiedResponse>,
+    response_variants: HashMap<(Method, String), BTreeMap<String, CertifiedResponse>>,
+    exact_path_responses: HashMap<String, usize>,
+    fallback_responses: BTreeMap<String, CertifiedResponse>,
+    error_responses: BTreeMap<String, BTreeMap<StatusCode, CertifiedResponse>>,
+    /// The number of certified responses that share each tree entry, since identical responses,
+    /// such as a fallback and the error response for the same scope, have the same tree entry.
+    tree_entries: HashMap<Vec<Vec<u8>>, usize>,
+    update_routes: Vec<UpdateRoute>,
+    /// The indices of the update routes for each method and static first segment, or [None] for
+    /// routes that start with a parameter, so only routes that can match a request are tried.
+    update_route_indices: HashMap<(Method, Option<String>), Vec<usize>>,
+}
+
+impl HttpCertificationRouter {
+    /// Creates a new empty [HttpCertificationRouter] that certifies responses in the given
+    /// [HttpCertificationTree]. The [default](HttpCertificationRouter::default) implementation
+    /// should be used in most cases.
+    pub fn new(tree: HttpCertificationTree) -> Self {
+        Self {
+            tree,
+            responses: HashMap::new(),
+            response_variants: HashMap::new(),
+            exact_path_responses: HashMap::new(),
+            fallback_responses: BTreeMap::new(),
+            error_responses: BTreeMap::new(),
+            tree_entries: HashMap::new(),
+            update_routes: vec![],
+            update_route_indices: HashMap::new(),
+        }
+    }
+
+    /// Returns the root hash of the router's [HttpCertificationTree].
+    /// This hash can be used as the canister's certified variable.
+    pub fn root_hash(&self) -> Hash {
+        self.tree.root_hash()
+    }
+
+    /// Returns the [HttpCertificationTree] that the router certifies responses in.
+    pub fn tree(&self) -> &HttpCertificationTree {
+        &self.tree
+    }
+
+    /// Certifies the given response for the method and path of the given request with a full
+    /// certification, replacing any response that was previously certified for them.
+    ///
+    /// The `IC-CertificateExpression` header is added to the response, so it should not already
+    /// be present.
+    pub fn certify_response(
+        &mut self,
+        request: &HttpRequest,
+        response: HttpResponse<'static>,
+        cel_expr: &DefaultFullCelExpression,
+    ) -> HttpCertificationResult {
+        let (key, certified_response) = Self::certified_response(request, response, cel_expr)?;
+        let request_path = key.1.clone();
+        let previous_response = self.responses.insert(key, certified_response.clone());
+
+        if previous_response.is_none() {
+            self.add_exact_path_response(request_path);
+        }
+        self.replace_certification(previous_response, &certified_response);
+
+        Ok(())
+    }
+
+    /// Certifies the given response as an alternative variant of the response for the method and
+    /// path of the given request, such as a response with a different content type, replacing any
+    /// response that was previously certified for the same variant.
+    ///
+    /// All variants are certified at the same exact path, alongside the response certified with
+    /// [certify_response](HttpCertificationRouter::certify_response), and the variant to serve is
+    /// selected with [serve_query_variant](HttpCertificationRouter::serve_query_variant). The CEL
+    /// expression should certify the request headers that the variant is selected by, so that
+    /// the HTTP Gateway can verify that the right variant was served.
+    ///
+    /// The `IC-CertificateExpression` header is added to the response, so it should not already
+    /// be present.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{DefaultCelBuilder, HttpCertificationRouter, HttpRequest, HttpResponse, Method};
+    ///
+    /// let cel_expr = DefaultCelBuilder::full_certification()
+    ///     .with_request_headers(vec!["Accept"])
+    ///     .build();
+    /// let mut router = HttpCertificationRouter::default();
+    ///
+    /// let json_request = HttpRequest::get("/todos")
+    ///     .with_headers(vec![("Accept".into(), "application/json".into())])
+    ///     .build();
+    /// let json_response = HttpResponse::ok(b"[]", vec![("Content-Type".into(), "application/json".into())]).build();
+    /// router.certify_response_variant(&json_request, "application/json", json_response, &cel_expr).unwrap();
+    ///
+    /// let html_request = HttpRequest::get("/todos")
+    ///     .with_headers(vec![("Accept".into(), "text/html".into())])
+    ///     .build();
+    /// let html_response = HttpResponse::ok(b"<ul></ul>", vec![("Content-Type".into(), "text/html".into())]).build();
+    /// router.certify_response_variant(&html_request, "text/html", html_response, &cel_expr).unwrap();
+    ///
+    /// assert_eq!(
+    ///     router.response_variants(&Method::GET, "/todos").collect::<Vec<_>>(),
+    ///     vec!["application/json", "text/html"],
+    /// );
+    ///
+    /// // this should normally be retrieved using `ic_cdk::api::data_certificate()`.
+    /// let data_certificate = vec![1, 2, 3];
+    ///
+    /// let variant = html_request.get_header("Accept").unwrap();
+    /// let response = router.serve_query_variant(&html_request, variant, &data_certificate).unwrap();
+    /// assert_eq!(response.body(), b"<ul></ul>");
+    /// ```
+    pub fn certify_response_variant(
+        &mut self,
+        request: &HttpRequest,
+        variant: impl Into<String>,
+        response: HttpResponse<'static>,
+        cel_expr: &DefaultFullCelExpression,
+    ) -> HttpCertificationResult {
+        let (key, certified_response) = Self::certified_response(request, response, cel_expr)?;
+        let request_path = key.1.clone();
+        let previous_response = self
+            .response_variants
+            .entry(key)
+            .or_default()
+            .insert(variant.into(), certified_response.clone());
+
+        if previous_response.is_none() {
+            self.add_exact_path_response(request_path);
+        }
+        self.replace_certification(previous_response, &certified_response);
+
+        Ok(())
+    }
+
+    /// Certifies the given response with a response-only certification, to be served for any
+    /// request path within the given scope that has no other certified response, such as a
+    /// `404 Not Found` response. An empty scope covers all request paths.
+    ///
+    /// Only the response for the most specific scope with a fallback or
+    /// [error responses](HttpCertificationRouter::certify_error_responses) can be verified by the
+    /// HTTP Gateway, so the response is not served for request paths that are covered by a more
+    /// specific scope, or that have a response certified for any method.
+    ///
+    /// The `IC-CertificateExpression` header is added to the response, so it should not already
+    /// be present.
+    pub fn certify_fallback_response(
+        &mut self,
+        scope: &str,
+        response: HttpResponse<'static>,
+        cel_expr: &DefaultResponseOnlyCelExpression,
+    ) -> HttpCertificationResult {
+        let certified_response = Self::scoped_response(scope, response, cel_expr)?;
+        let previous_response = self
+            .fallback_responses
+            .insert(scope.to_string(), certified_response.clone());
+
+        self.replace_certification(previous_response, &certified_response);
+
+        Ok(())
+    }
+
+    /// Certifies all responses of the given [ErrorResponseCatalog] with a response-only
+    /// certification for any request path within the given scope, replacing any error responses
+    /// that were previously certified for the same scope.
+    ///
+    /// An error response is served with [serve_error](HttpCertificationRouter::serve_error),
+    /// after the canister has decided that a request fails with its status code. The
+    /// `404 Not Found` response of the catalog is also served by
+    /// [serve_query](HttpCertificationRouter::serve_query) for request paths within the scope that
+    /// have no other certified response, unless a
+    /// [fallback response](HttpCertificationRouter::certify_fallback_response) is certified for
+    /// the same scope.
+    ///
+    /// As with fallback responses, only the responses for the most specific scope covering a
+    /// request path can be verified by the HTTP Gateway, so error responses are not served for
+    /// request paths that are covered by a more specific scope, or that have a response certified
+    /// for any method.
+    ///
+    /// The error responses and the fallback response of a scope share one wildcard path, so the
+    /// HTTP Gateway accepts any of them for a request path within the scope, see
+    /// [ErrorResponseCatalog].
+    ///
+    /// The `IC-CertificateExpression` header is added to the responses, so it should not already
+    /// be present.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{
+    ///     DefaultCelBuilder, DefaultResponseCertification, ErrorResponseCatalog,
+    ///     HttpCertificationRouter, HttpRequest, HttpResponse, StatusCode,
+    /// };
+    ///
+    /// let cel_expr = DefaultCelBuilder::response_only_certification()
+    ///     .with_response_certification(DefaultResponseCertification::response_header_exclusions(vec![]))
+    ///     .build();
+    /// let catalog = ErrorResponseCatalog::new()
+    ///     .with_headers(vec![("Cache-Control".into(), "no-store".into())]);
+    ///
+    /// let mut router = HttpCertificationRouter::default();
+    /// router.certify_error_responses("", &catalog, &cel_expr).unwrap();
+    ///
+    /// // this should normally be retrieved using `ic_cdk::api::data_certificate()`.
+    /// let data_certificate = vec![1, 2, 3];
+    ///
+    /// let request = HttpRequest::post("/todos").build();
+    /// let response = router.serve_error(&request, StatusCode::TOO_MANY_REQUESTS, &data_certificate).unwrap();
+    /// assert_eq!(response.status_code(), StatusCode::TOO_MANY_REQUESTS);
+    /// assert_eq!(response.body(), b"Too Many Requests");
+    ///
+    /// let response = router.serve_query(&HttpRequest::get("/users").build(), &data_certificate).unwrap();
+    /// assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    /// ```
+    pub fn certify_error_responses(
+        &mut self,
+        scope: &str,
+        catalog: &ErrorResponseCatalog,
+        cel_expr: &DefaultResponseOnlyCelExpression,
+    ) -> HttpCertificationResult {
+        let certified_responses = catalog
+            .responses()
+            .map(|response| {
+                Self::scoped_response(scope, response.clone(), cel_expr)
+                    .map(|certified_response| (response.status_code(), certified_response))
+            })
+            .collect::<HttpCertificationResult<BTreeMap<_, _>>>()?;
+
+        self.remove_error_responses(scope);
+        for certified_response in certified_responses.values() {
+            self.insert_tree_entry(certified_response);
+        }
+        self.error_responses
+            .insert(scope.to_string(), certified_responses);
+
+        Ok(())
+    }
+
+    /// Removes the response certified for the given method and request path, if any. Variants
+    /// of the response are removed separately with
+    /// [remove_response_variant](HttpCertificationRouter::remove_response_variant).
+    pub fn remove_response(&mut self, method: &Method, request_path: &str) {
+        if let Some(certified_response) = self
+            .responses
+            .remove(&(method.clone(), request_path.to_string()))
+        {
+            self.remove_exact_path_response(request_path);
+            self.delete_tree_entry(&certified_response);
+        }
+    }
+
+    /// Removes the variant of the response certified for the given method and request path with
+    /// [certify_response_variant](HttpCertificationRouter::certify_response_variant), if any.
+    pub fn remove_response_variant(&mut self, method: &Method, request_path: &str, variant: &str) {
+        let key = (method.clone(), request_path.to_string());
+        let Some(variants) = self.response_variants.get_mut(&key) else {
+            return;
+        };
+
+        let Some(certified_response) = variants.remove(variant) else {
+            return;
+        };
+
+        if variants.is_empty() {
+            self.response_variants.remove(&key);
+        }
+        self.remove_exact_path_response(request_path);
+        self.delete_tree_entry(&certified_response);
+    }
+
+    /// Returns the variants of the response certified for the given method and request path with
+    /// [certify_response_variant](HttpCertificationRouter::certify_response_variant), in
+    /// lexicographical order.
+    pub fn response_variants(
+        &self,
+        method: &Method,
+        request_path: &str,
+    ) -> impl Iterator<Item = &str> {
+        self.response_variants
+            .get(&(method.clone(), request_path.to_string()))
+            .into_iter()
+            .flat_map(|variants| variants.keys().map(String::as_str))
+    }
+
+    /// Removes the fallback response certified for the given scope, if any.
+    pub fn remove_fallback_response(&mut self, scope: &str) {
+        if let Some(certified_response) = self.fallback_responses.remove(scope) {
+            self.delete_tree_entry(&certified_response);
+        }
+    }
+
+    /// Removes the error responses certified for the given scope with
+    /// [certify_error_responses](HttpCertificationRouter::certify_error_responses), if any.
+    pub fn remove_error_responses(&mut self, scope: &str) {
+        if let Some(certified_responses) = self.error_responses.remove(scope) {
+            for certified_response in certified_responses.values() {
+                self.delete_tree_entry(certified_response);
+            }
+        }
+    }
+
+    /// Adds a route that upgrades matching query calls to update calls, which are then handled by
+    /// the given handler. The template uses the syntax of [HttpCertificationRoute].
+    ///
+    /// Returns an error if the template is not valid.
+    pub fn add_update_route(
+        &mut self,
+        method: Method,
+        template: &str,
+        handler: HttpRouteHandler,
+    ) -> HttpCertificationResult {
+        let route = HttpCertificationRoute::new(template)?;
+        let key = (
+            method.clone(),
+            route.first_static_segment().map(str::to_string),
+        );
+        self.update_route_indices
+            .entry(key)
+            .or_default()
+            .push(self.update_routes.len());
+        self.update_routes.push(UpdateRoute {
+            method,
+            route,
+            handler,
+        });
+
+        Ok(())
+    }
+
+    /// Serves the response for the given request to an `http_request` query call.
+    ///
+    /// If the request matches an [update route](HttpCertificationRouter::add_update_route), a
+    /// response that upgrades the query call to an update call is returned. Otherwise, the
+    /// response certified for the method and path of the request is returned. If there is none, the
+    /// fallback response, or else the `404 Not Found`
+    /// [error response](HttpCertificationRouter::certify_error_responses), of the most specific
+    /// scope covering the request path is returned. The `IC-Certificate` header is added to the
+    /// response using the given `data_certificate`.
+    ///
+    /// Returns [HttpCertificationError::WitnessEntryNotFound] if no response is certified for
+    /// the request, or if the only responses covering the request could not be verified by the
+    /// HTTP Gateway, because a response is certified for the request path under another method or
+    /// the most specific scope has neither a fallback nor a `404 Not Found` response.
+    pub fn serve_query(
+        &self,
+        request: &HttpRequest,
+        data_certificate: &[u8],
+    ) -> HttpCertificationResult<HttpResponse<'static>> {
+        self.serve(request, None, data_certificate)
+            .map(HttpResponse::into_owned)
+    }
+
+    /// Same as [serve_query](HttpCertificationRouter::serve_query), but the returned response
+    /// borrows its body from the router instead of copying it.
+    ///
+    /// This avoids copying large response bodies on every query call, when the response is
+    /// encoded while the router is still borrowed, for example with a `manual_reply` query.
+    pub fn serve_query_borrowed(
+        &self,
+        request: &HttpRequest,
+        data_certificate: &[u8],
+    ) -> HttpCertificationResult<HttpResponse<'_>> {
+        self.serve(request, None, data_certificate)
+    }
+
+    /// Serves the given variant of the response for the given request to an `http_request` query
+    /// call, as certified with
+    /// [certify_response_variant](HttpCertificationRouter::certify_response_variant).
+    ///
+    /// If the variant was not certified for the method and path of the request, the response is
+    /// served in the same way as [serve_query](HttpCertificationRouter::serve_query).
+    pub fn serve_query_variant(
+        &self,
+        request: &HttpRequest,
+        variant: &str,
+        data_certificate: &[u8],
+    ) -> HttpCertificationResult<HttpResponse<'static>> {
+        self.serve(request, Some(variant), data_certificate)
+            .map(HttpResponse::into_owned)
+    }
+
+    /// Serves the [error response](HttpCertificationRouter::certify_error_responses) with the given
+    /// status code and the most specific scope covering the path of the given request, with the
+    /// `IC-Certificate` header added using the given `data_certificate`.
+    ///
+    /// Returns [HttpCertificationError::WitnessEntryNotFound] if the most specific scope has no
+    /// error response with the given status code, or if a response is certified for the request
+    /// path under any method, because the HTTP Gateway would not accept an error response for the
+    /// request path in either case.
+    pub fn serve_error(
+        &self,
+        request: &HttpRequest,
+        status_code: StatusCode,
+        data_certificate: &[u8],
+    ) -> HttpCertificationResult<HttpResponse<'static>> {
+        let request_path = request.get_path()?;
+        let certified_response = self
+            .verifiable_scope(&request_path)
+            .and_then(|scope| self.error_responses.get(scope)?.get(&status_code))
+            .ok_or_else(|| HttpCertificationError::WitnessEntryNotFound {
+                request_path: request_path.clone(),
+            })?;
+
+        self.certified(certified_response, &request_path, data_certificate)
+            .map(HttpResponse::into_owned)
+    }
+
+    /// Returns the handler of the first [update route](HttpCertificationRouter::add_update_route)
+    /// that matches the given request, along with the values of the route's parameters, to handle
+    /// an `http_request_update` update call. The handler is returned rather than called, so that
+    /// it can certify new responses with the router.
+    pub fn update_handler(
+        &self,
+        request: &HttpRequest,
+    ) -> HttpCertificationResult<Option<(HttpRouteHandler, HttpRouteParams)>> {
+        let request_path = request.get_path()?;
+        let first_segment = request_path
+            .strip_prefix('/')
+            .unwrap_or(&request_path)
+            .split('/')
+            .next()
+            .map(str::to_string);
+
+        // routes with a static first segment and routes that start with a parameter are tried in
+        // the order they were added
+        let mut static_indices = self
+            .update_route_indices(request.method(), first_segment)
+            .iter()
+            .peekable();
+        let mut param_indices = self
+            .update_route_indices(request.method(), None)
+            .iter()
+            .peekable();
+        let route_indices = std::iter::from_fn(|| {
+            match (
+                static_indices.peek().copied(),
+                param_indices.peek().copied(),
+            ) {
+                (Some(static_index), Some(param_index)) if param_index < static_index => {
+                    param_indices.next()
+                }
+                (Some(_), _) => static_indices.next(),
+                (None, _) => param_indices.next(),
+            }
+        });
+
+        Ok(route_indices
+            .map(|index| &self.update_routes[*index])
+            .find_map(|update_route| {
+                update_route
+                    .route
+                    .params(&request_path)
+                    .ok()
+                    .map(|params| (update_route.handler, params))
+            }))
+    }
+
+    fn update_route_indices(&self, method: &Method, first_segment: Option<String>) -> &[usize] {
+        self.update_route_indices
+            .get(&(method.clone(), first_segment))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    fn serve(
+        &self,
+        request: &HttpRequest,
+        variant: Option<&str>,
+        data_certificate: &[u8],
+    ) -> HttpCertificationResult<HttpResponse<'_>> {
+        if self.update_handler(request)?.is_some() {
+            return Ok(HttpResponse::upgrade_to_update_call().build());
+        }
+
+        let request_path = request.get_path()?;
+        let key = (request.method().clone(), request_path.clone());
+        let certified_response = variant
+            .and_then(|variant| self.response_variants.get(&key)?.get(variant))
+            .or_else(|| self.responses.get(&key))
+            .or_else(|| {
+                let scope = self.verifiable_scope(&request_path)?;
+
+                self.fallback_responses
+                    .get(scope)
+                    .or_else(|| self.error_responses.get(scope)?.get(&StatusCode::NOT_FOUND))
+            })
+            .ok_or_else(|| HttpCertificationError::WitnessEntryNotFound {
+                request_path: request_path.clone(),
+            })?;
+
+        self.certified(certified_response, &request_path, data_certificate)
+    }
+
+    fn certified<'a>(
+        &self,
+        certified_response: &'a CertifiedResponse,
+        request_path: &str,
+        data_certificate: &[u8],
+    ) -> HttpCertificationResult<HttpResponse<'a>> {
+        let witness = self
+            .tree
+            .witness(&Self::tree_entry(certified_response), request_path)?;
+        let mut response = certified_response.response.to_borrowed();
+        add_v2_certificate_header(
+            data_certificate,
+            &mut response,
+            &witness,
+            &certified_response.tree_path.to_expr_path(),
+        );
+
+        Ok(response)
+    }
+
+    fn certified_response(
+        request: &HttpRequest,
+        mut response: HttpResponse<'static>,
+        cel_expr: &DefaultFullCelExpression,
+    ) -> HttpCertificationResult<((Method, String), CertifiedResponse)> {
+        let request_path = request.get_path()?;
+
+        response.add_header((
+            CERTIFICATE_EXPRESSION_HEADER_NAME.to_string(),
+            cel_expr.to_string(),
+        ));
+        let certification = HttpCertification::full(cel_expr, request, &response, None)?;
+
+        Ok((
+            (request.method().clone(), request_path.clone()),
+            CertifiedResponse {
+                response,
+                certification,
+                tree_path: HttpCertificationPath::exact(request_path),
+            },
+        ))
+    }
+
+    fn scoped_response(
+        scope: &str,
+        mut response: HttpResponse<'static>,
+        cel_expr: &DefaultResponseOnlyCelExpression,
+    ) -> HttpCertificationResult<CertifiedResponse> {
+        response.add_header((
+            CERTIFICATE_EXPRESSION_HEADER_NAME.to_string(),
+            cel_expr.to_string(),
+        ));
+        let certification = HttpCertification::response_only(cel_expr, &response, None)?;
+
+        Ok(CertifiedResponse {
+            response,
+            certification,
+            tree_path: HttpCertificationPath::wildcard(scope.to_string()),
+        })
+    }
+
+    /// Returns the most specific scope with a fallback or error response covering the given
+    /// request path, if responses certified for it can be verified for the request path.
+    ///
+    /// The HTTP Gateway rejects a response certified for a scope if a response might be certified
+    /// for the exact request path, under any method, or for a more specific scope covering the
+    /// request path. A less specific scope is therefore never used, even if the most specific
+    /// scope has no response with the status code that is served.
+    fn verifiable_scope(&self, request_path: &str) -> Option<&str> {
+        if self.exact_path_responses.contains_key(request_path) {
+            return None;
+        }
+
+        // the scopes covering the request path are the request path itself and the prefixes that
+        // end before or after a `/`, which are looked up from the most to the least specific
+        std::iter::once(request_path.len())
+            .chain(
+                request_path
+                    .rmatch_indices('/')
+                    .flat_map(|(index, _)| [index + 1, index]),
+            )
+            .map(|len| &request_path[..len])
+            .find_map(|scope| {
+                self.fallback_responses
+                    .get_key_value(scope)
+                    .map(|(scope, _)| scope)
+                    .or_else(|| {
+                        self.error_responses
+                            .get_key_value(scope)
+                            .map(|(scope, _)| scope)
+                    })
+            })
+            .map(String::as_str)
+    }
+
+    fn add_exact_path_response(&mut self, request_path: String) {
+        *self.exact_path_responses.entry(request_path).or_default() += 1;
+    }
+
+    fn remove_exact_path_response(&mut self, request_path: &str) {
+        if let Some(count) = self.exact_path_responses.get_mut(request_path) {
+            *count -= 1;
+            if *count == 0 {
+                self.exact_path_responses.remove(request_path);
+            }
+        }
+    }
+
+    fn replace_certification(
+        &mut self,
+        previous_response: Option<CertifiedResponse>,
+        certified_response: &CertifiedResponse,
+    ) {
+        // the new response is inserted first, so an identical certification stays in the tree
+        self.insert_tree_entry(certified_response);
+        if let Some(previous_response) = previous_response {
+            self.delete_tree_entry(&previous_response);
+        }
+    }
+
+    /// Inserts the tree entry of the given response, unless another certified response already
+    /// shares it.
+    fn insert_tree_entry(&mut self, certified_response: &CertifiedResponse) {
+        let tree_entry = Self::tree_entry(certified_response);
+        let count = self
+            .tree_entries
+            .entry(tree_entry.to_tree_path())
+            .or_default();
+        *count += 1;
+        if *count == 1 {
+            self.tree.insert(&tree_entry);
+        }
+    }
+
+    /// Deletes the tree entry of the given response, unless another certified response still
+    /// shares it.
+    fn delete_tree_entry(&mut self, certified_response: &CertifiedResponse) {
+        let tree_entry = Self::tree_entry(certified_response);
+        let tree_path = tree_entry.to_tree_path();
+        let Some(count) = self.tree_entries.get_mut(&tree_path) else {
+            return;
+        };
+
+        *count -= 1;
+        if *count == 0 {
+            self.tree_entries.remove(&tree_path);
+            self.tree.delete(&tree_entry);
+        }
+    }
+
+    fn tree_entry(certified_response: &CertifiedResponse) -> HttpCertificationTreeEntry<'_> {
+        HttpCertificationTreeEntry::new(
+            &certified_response.tree_path,
+            certified_response.certification,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefaultCelBuilder, DefaultResponseCertification, CERTIFICATE_HEADER_NAME};
+    use assert_matches::assert_matches;
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+    use ic_certification::{HashTree, SubtreeLookupResult};
+    use rstest::*;
+
+    const DATA_CERTIFICATE: &[u8] = &[1, 2, 3];
+
+    #[fixture]
+    fn cel_expr() -> DefaultFullCelExpression<'static> {
+        DefaultCelBuilder::full_certification()
+            .with_response_certification(DefaultResponseCertification::response_header_exclusions(
+                vec![],
+            ))
+            .build()
+    }
+
+    #[fixture]
+    fn response_only_cel_expr() -> DefaultResponseOnlyCelExpression<'static> {
+        DefaultCelBuilder::response_only_certification()
+            .with_response_certification(DefaultResponseCertification::response_header_exclusions(
+                vec![],
+            ))
+            .build()
+    }
+
+    #[fixture]
+    fn router(
+        cel_expr: DefaultFullCelExpression<'static>,
+        response_only_cel_expr: DefaultResponseOnlyCelExpression<'static>,
+    ) -> HttpCertificationRouter {
+        let mut router = HttpCertificationRouter::default();
+        router
+            .certify_response(
+                &HttpRequest::get("/todos").build(),
+                HttpResponse::ok(b"todos".to_vec(), vec![]).build(),
+                &cel_expr,
+            )
+            .unwrap();
+        router
+            .certify_fallback_response(
+                "",
+                HttpResponse::not_found(b"root fallback".to_vec(), vec![]).build(),
+                &response_only_cel_expr,
+            )
+            .unwrap();
+        router
+            .certify_fallback_response(
+                "/todos",
+                HttpResponse::not_found(b"todos fallback".to_vec(), vec![]).build(),
+                &response_only_cel_expr,
+            )
+            .unwrap();
+        router
+            .add_update_route(Method::POST, "/todos/{id}", create_todo_handler)
+            .unwrap();
+
+        router
+    }
+
+    #[fixture]
+    fn variant_router(cel_expr: DefaultFullCelExpression<'static>) -> HttpCertificationRouter {
+        let mut router = HttpCertificationRouter::default();
+        router
+            .certify_response(
+                &HttpRequest::get("/todos").build(),
+                HttpResponse::ok(b"todos".to_vec(), vec![]).build(),
+                &cel_expr,
+            )
+            .unwrap();
+        for (variant, body) in [("application/json", "[]"), ("text/html", "<ul></ul>")] {
+            router
+                .certify_response_variant(
+                    &HttpRequest::get("/todos").build(),
+                    variant,
+                    HttpResponse::ok(body.as_bytes().to_vec(), vec![]).build(),
+                    &cel_expr,
+                )
+                .unwrap();
+        }
+
+        router
+    }
+
+    #[fixture]
+    fn error_router(
+        cel_expr: DefaultFullCelExpression<'static>,
+        response_only_cel_expr: DefaultResponseOnlyCelExpression<'static>,
+    ) -> HttpCertificationRouter {
+        let mut router = HttpCertificationRouter::default();
+        router
+            .certify_response(
+                &HttpRequest::get("/todos").build(),
+                HttpResponse::ok(b"todos".to_vec(), vec![]).build(),
+                &cel_expr,
+            )
+            .unwrap();
+        router
+            .certify_error_responses(
+                "",
+                &ErrorResponseCatalog::new().with_response(
+                    HttpResponse::builder()
+                        .with_status_code(StatusCode::IM_A_TEAPOT)
+                        .with_body(b"I'm a teapot".to_vec())
+                        .build(),
+                ),
+                &response_only_cel_expr,
+            )
+            .unwrap();
+        router
+            .certify_error_responses(
+                "/todos",
+                &ErrorResponseCatalog::new().with_response(
+                    HttpResponse::not_found(b"todo not found".to_vec(), vec![]).build(),
+                ),
+                &response_only_cel_expr,
+            )
+            .unwrap();
+        router
+            .certify_fallback_response(
+                "/users",
+                HttpResponse::not_found(b"users fallback".to_vec(), vec![]).build(),
+                &response_only_cel_expr,
+            )
+            .unwrap();
+
+        router
+    }
+
+    #[rstest]
+    #[case::exact_path("/todos", b"todos", &["http_expr", "todos", "<$>"])]
+    #[case::scoped_fallback("/todos/1", b"todos fallback", &["http_expr", "todos", "<*>"])]
+    #[case::nested_scoped_fallback("/todos/1/2", b"todos fallback", &["http_expr", "todos", "<*>"])]
+    #[case::root_fallback("/users", b"root fallback", &["http_expr", "<*>"])]
+    #[case::scope_prefix_is_not_a_segment("/todosx", b"root fallback", &["http_expr", "<*>"])]
+    fn serve_query_prefers_exact_path_over_most_specific_fallback(
+        router: HttpCertificationRouter,
+        #[case] request_url: &str,
+        #[case] expected_body: &[u8],
+        #[case] expected_expr_path: &[&str],
+    ) {
+        let response = router
+            .serve_query(&HttpRequest::get(request_url).build(), DATA_CERTIFICATE)
+            .unwrap();
+
+        assert_eq!(response.body(), expected_body);
+        assert_eq!(witnessed_expr_path(&response), expected_expr_path);
+    }
+
+    #[rstest]
+    fn serve_query_looks_up_method_and_path(cel_expr: DefaultFullCelExpression<'static>) {
+        let mut router = HttpCertificationRouter::default();
+        router
+            .certify_response(
+                &HttpRequest::get("/todos").build(),
+                HttpResponse::ok(b"get todos".to_vec(), vec![]).build(),
+                &cel_expr,
+            )
+            .unwrap();
+        router
+            .certify_response(
+                &HttpRequest::put("/todos").build(),
+                HttpResponse::ok(b"put todos".to_vec(), vec![]).build(),
+                &cel_expr,
+            )
+            .unwrap();
+
+        let response = router
+            .serve_query(&HttpRequest::put("/todos").build(), DATA_CERTIFICATE)
+            .unwrap();
+        assert_eq!(response.body(), b"put todos");
+        assert_eq!(
+            witnessed_expr_path(&response),
+            ["http_expr", "todos", "<$>"]
+        );
+
+        assert_matches!(
+            router.serve_query(&HttpRequest::get("/users").build(), DATA_CERTIFICATE),
+            Err(HttpCertificationError::WitnessEntryNotFound { request_path }) if request_path == "/users"
+        );
+    }
+
+    #[rstest]
+    #[case::exact_path("/todos")]
+    #[case::fallback("/todos/1")]
+    fn serve_query_borrowed_borrows_certified_body(
+        router: HttpCertificationRouter,
+        #[case] request_url: &str,
+    ) {
+        let request = HttpRequest::get(request_url).build();
+
+        let response = router
+            .serve_query_borrowed(&request, DATA_CERTIFICATE)
+            .unwrap();
+        let certified_response = router
+            .responses
+            .get(&(Method::GET, request_url.to_string()))
+            .or_else(|| {
+                router
+                    .fallback_responses
+                    .get(router.verifiable_scope(request_url)?)
+            })
+            .unwrap();
+        assert_eq!(
+            response.body().as_ptr(),
+            certified_response.response.body().as_ptr()
+        );
+
+        let owned_response = router.serve_query(&request, DATA_CERTIFICATE).unwrap();
+        assert_ne!(
+            owned_response.body().as_ptr(),
+            certified_response.response.body().as_ptr()
+        );
+        assert_eq!(response, owned_response);
+        witnessed_expr_path(&response);
+    }
+
+    #[rstest]
+    #[case("/a/b", Some("/a/b"))]
+    #[case("/a/b/c", Some("/a/b"))]
+    #[case("/a/bc", Some("/a/"))]
+    #[case("/a/", Some("/a/"))]
+    #[case("/a", Some("/a"))]
+    #[case("/ab", Some(""))]
+    #[case("/todos", None)]
+    fn verifiable_scope_returns_most_specific_scope(
+        mut router: HttpCertificationRouter,
+        response_only_cel_expr: DefaultResponseOnlyCelExpression<'static>,
+        #[case] request_path: &str,
+        #[case] expected_scope: Option<&str>,
+    ) {
+        for scope in ["/a", "/a/", "/a/b"] {
+            router
+                .certify_fallback_response(
+                    scope,
+                    HttpResponse::not_found(scope.as_bytes().to_vec(), vec![]).build(),
+                    &response_only_cel_expr,
+                )
+                .unwrap();
+        }
+
+        assert_eq!(router.verifiable_scope(request_path), expected_scope);
+    }
+
+    #[rstest]
+    #[case(Method::POST, "/todos/1", Some(("id", "1")))]
+    #[case(Method::POST, "/todos", Some(("path", "todos")))]
+    #[case(Method::POST, "/users/1", Some(("path", "users/1")))]
+    #[case(Method::GET, "/todos/1", None)]
+    fn update_handler_returns_first_matching_route(
+        #[case] method: Method,
+        #[case] request_path: &str,
+        #[case] expected_param: Option<(&str, &str)>,
+    ) {
+        let mut router = HttpCertificationRouter::default();
+        router
+            .add_update_route(Method::POST, "/todos/{id}", create_todo_handler)
+            .unwrap();
+        router
+            .add_update_route(Method::POST, "/{*path}", create_todo_handler)
+            .unwrap();
+        router
+            .add_update_route(Method::POST, "/todos", create_todo_handler)
+            .unwrap();
+
+        let request = HttpRequest::builder()
+            .with_method(method)
+            .with_url(request_path)
+            .build();
+        let params = router
+            .update_handler(&request)
+            .unwrap()
+            .map(|(_, params)| params);
+
+        assert_eq!(
+            params,
+            expected_param.map(|(name, value)| HttpRouteParams::from([(
+                name.to_string(),
+                value.to_string()
+            )]))
+        );
+    }
+
+    #[rstest]
+    fn serve_query_upgrades_update_routes(router: HttpCertificationRouter) {
+        let request = HttpRequest::post("/todos/1").build();
+
+        let response = router.serve_query(&request, DATA_CERTIFICATE).unwrap();
+        assert!(response.is_upgrade());
+        assert!(!response
+            .headers()
+            .iter()
+            .any(|(name, _)| name == CERTIFICATE_HEADER_NAME));
+
+        let (handler, params) = router.update_handler(&request).unwrap().unwrap();
+        assert_eq!(params.get("id").map(String::as_str), Some("1"));
+        assert_eq!(
+            handler(&request, &params).status_code(),
+            StatusCode::CREATED
+        );
+
+        let request = HttpRequest::get("/todos/1").build();
+        assert!(!router
+            .serve_query(&request, DATA_CERTIFICATE)
+            .unwrap()
+            .is_upgrade());
+        assert!(router.update_handler(&request).unwrap().is_none());
+    }
+
+    #[rstest]
+    fn certify_response_replaces_certification(
+        mut router: HttpCertificationRouter,
+        cel_expr: DefaultFullCelExpression<'static>,
+        response_only_cel_expr: DefaultResponseOnlyCelExpression<'static>,
+    ) {
+        let root_hash = router.root_hash();
+
+        router
+            .certify_response(
+                &HttpRequest::get("/todos").build(),
+                HttpResponse::ok(b"updated todos".to_vec(), vec![]).build(),
+                &cel_expr,
+            )
+            .unwrap();
+        router
+            .certify_fallback_response(
+                "/todos",
+                HttpResponse::not_found(b"updated todos fallback".to_vec(), vec![]).build(),
+                &response_only_cel_expr,
+            )
+            .unwrap();
+        assert_ne!(router.root_hash(), root_hash);
+
+        let mut expected_router = HttpCertificationRouter::default();
+        expected_router
+            .certify_fallback_response(
+                "",
+                HttpResponse::not_found(b"root fallback".to_vec(), vec![]).build(),
+                &response_only_cel_expr,
+            )
+            .unwrap();
+        expected_router
+            .certify_fallback_response(
+                "/todos",
+                HttpResponse::not_found(b"updated todos fallback".to_vec(), vec![]).build(),
+                &response_only_cel_expr,
+            )
+            .unwrap();
+        expected_router
+            .certify_response(
+                &HttpRequest::get("/todos").build(),
+                HttpResponse::ok(b"updated todos".to_vec(), vec![]).build(),
+                &cel_expr,
+            )
+            .unwrap();
+        assert_eq!(router.root_hash(), expected_router.root_hash());
+
+        let response = router
+            .serve_query(&HttpRequest::get("/todos").build(), DATA_CERTIFICATE)
+            .unwrap();
+        assert_eq!(response.body(), b"updated todos");
+        assert_eq!(
+            witnessed_expr_path(&response),
+            ["http_expr", "todos", "<$>"]
+        );
+    }
+
+    #[rstest]
+    fn remove_response_deletes_certification(mut router: HttpCertificationRouter) {
+        let root_hash = router.root_hash();
+
+        router.remove_response(&Method::GET, "/todos");
+        router.remove_fallback_response("/todos");
+        router.remove_fallback_response("");
+        assert_ne!(router.root_hash(), root_hash);
+        assert_eq!(
+            router.root_hash(),
+            HttpCertificationRouter::default().root_hash()
+        );
+
+        assert_matches!(
+            router.serve_query(&HttpRequest::get("/todos").build(), DATA_CERTIFICATE),
+            Err(HttpCertificationError::WitnessEntryNotFound { request_path }) if request_path == "/todos"
+        );
+    }
+
+    #[rstest]
+    fn remove_missing_response_keeps_certifications(mut router: HttpCertificationRouter) {
+        let root_hash = router.root_hash();
+
+        router.remove_response(&Method::PUT, "/todos");
+        router.remove_fallback_response("/users");
+
+        assert_eq!(router.root_hash(), root_hash);
+    }
+
+    #[rstest]
+    #[case::json_variant(Some("application/json"), b"[]")]
+    #[case::html_variant(Some("text/html"), b"<ul></ul>")]
+    #[case::missing_variant(Some("text/plain"), b"todos")]
+    #[case::no_variant(None, b"todos")]
+    fn serve_query_variant_serves_certified_variant(
+        variant_router: HttpCertificationRouter,
+        #[case] variant: Option<&str>,
+        #[case] expected_body: &[u8],
+    ) {
+        let request = HttpRequest::get("/todos").build();
+        let response = match variant {
+            Some(variant) => {
+                variant_router.serve_query_variant(&request, variant, DATA_CERTIFICATE)
+            }
+            None => variant_router.serve_query(&request, DATA_CERTIFICATE),
+        }
+        .unwrap();
+
+        assert_eq!(response.body(), expected_body);
+        assert_eq!(
+            witnessed_expr_path(&response),
+            ["http_expr", "todos", "<$>"]
+        );
+    }
+
+    #[rstest]
+    fn certify_response_variant_replaces_certification(
+        mut variant_router: HttpCertificationRouter,
+        cel_expr: DefaultFullCelExpression<'static>,
+    ) {
+        let root_hash = variant_router.root_hash();
+
+        variant_router
+            .certify_response_variant(
+                &HttpRequest::get("/todos").build(),
+                "application/json",
+                HttpResponse::ok(b"[{}]".to_vec(), vec![]).build(),
+                &cel_expr,
+            )
+            .unwrap();
+        assert_ne!(variant_router.root_hash(), root_hash);
+
+        let mut expected_router = HttpCertificationRouter::default();
+        for (variant, body) in [("application/json", "[{}]"), ("text/html", "<ul></ul>")] {
+            expected_router
+                .certify_response_variant(
+                    &HttpRequest::get("/todos").build(),
+                    variant,
+                    HttpResponse::ok(body.as_bytes().to_vec(), vec![]).build(),
+                    &cel_expr,
+                )
+                .unwrap();
+        }
+        expected_router
+            .certify_response(
+                &HttpRequest::get("/todos").build(),
+                HttpResponse::ok(b"todos".to_vec(), vec![]).build(),
+                &cel_expr,
+            )
+            .unwrap();
+        assert_eq!(variant_router.root_hash(), expected_router.root_hash());
+
+        let response = variant_router
+            .serve_query_variant(
+                &HttpRequest::get("/todos").build(),
+                "application/json",
+                DATA_CERTIFICATE,
+            )
+            .unwrap();
+        assert_eq!(response.body(), b"[{}]");
+        assert_eq!(
+            witnessed_expr_path(&response),
+            ["http_expr", "todos", "<$>"]
+        );
+    }
+
+    #[rstest]
+    fn remove_response_variant_deletes_certification(mut variant_router: HttpCertificationRouter) {
+        let root_hash = variant_router.root_hash();
+
+        variant_router.remove_response_variant(&Method::GET, "/todos", "text/plain");
+        assert_eq!(variant_router.root_hash(), root_hash);
+
+        variant_router.remove_response_variant(&Method::GET, "/todos", "application/json");
+        assert_ne!(variant_router.root_hash(), root_hash);
+        assert_eq!(
+            variant_router
+                .response_variants(&Method::GET, "/todos")
+                .collect::<Vec<_>>(),
+            vec!["text/html"]
+        );
+        let response = variant_router
+            .serve_query_variant(
+                &HttpRequest::get("/todos").build(),
+                "application/json",
+                DATA_CERTIFICATE,
+            )
+            .unwrap();
+        assert_eq!(response.body(), b"todos");
+
+        variant_router.remove_response_variant(&Method::GET, "/todos", "text/html");
+        variant_router.remove_response(&Method::GET, "/todos");
+        assert_eq!(
+            variant_router
+                .response_variants(&Method::GET, "/todos")
+                .count(),
+            0
+        );
+        assert_eq!(
+            variant_router.root_hash(),
+            HttpCertificationRouter::default().root_hash()
+        );
+    }
+
+    #[rstest]
+    #[case::scoped(
+        HttpRequest::get("/todos/1").build(),
+        StatusCode::TOO_MANY_REQUESTS,
+        Some(&["http_expr", "todos", "<*>"][..]),
+    )]
+    #[case::root(
+        HttpRequest::get("/health").build(),
+        StatusCode::TOO_MANY_REQUESTS,
+        Some(&["http_expr", "<*>"][..]),
+    )]
+    #[case::root_only_status_code(
+        HttpRequest::get("/health").build(),
+        StatusCode::IM_A_TEAPOT,
+        Some(&["http_expr", "<*>"][..])
+    )]
+    #[case::exact_path(
+        HttpRequest::get("/todos").build(),
+        StatusCode::TOO_MANY_REQUESTS,
+        None
+    )]
+    #[case::exact_path_with_other_method(
+        HttpRequest::post("/todos").build(),
+        StatusCode::TOO_MANY_REQUESTS,
+        None
+    )]
+    #[case::more_specific_fallback(
+        HttpRequest::get("/users/1").build(),
+        StatusCode::TOO_MANY_REQUESTS,
+        None
+    )]
+    #[case::more_specific_scope_without_status_code(
+        HttpRequest::get("/todos/1").build(),
+        StatusCode::IM_A_TEAPOT,
+        None
+    )]
+    fn serve_error_serves_most_specific_scope(
+        error_router: HttpCertificationRouter,
+        #[case] request: HttpRequest,
+        #[case] status_code: StatusCode,
+        #[case] expected_expr_path: Option<&[&str]>,
+    ) {
+        let result = error_router.serve_error(&request, status_code, DATA_CERTIFICATE);
+
+        match expected_expr_path {
+            Some(expected_expr_path) => {
+                let response = result.unwrap();
+                assert_eq!(response.status_code(), status_code);
+                assert_eq!(witnessed_expr_path(&response), expected_expr_path);
+            }
+            None => assert_matches!(
+                result,
+                Err(HttpCertificationError::WitnessEntryNotFound { request_path })
+                    if request_path == request.get_path().unwrap()
+            ),
+        }
+    }
+
+    #[rstest]
+    #[case::scoped_error_response(
+        HttpRequest::get("/todos/1").build(),
+        Some((&b"todo not found"[..], &["http_expr", "todos", "<*>"][..])),
+    )]
+    #[case::root_error_response(
+        HttpRequest::get("/health").build(),
+        Some((&b"Not Found"[..], &["http_expr", "<*>"][..])),
+    )]
+    #[case::more_specific_fallback(
+        HttpRequest::get("/users/1").build(),
+        Some((&b"users fallback"[..], &["http_expr", "users", "<*>"][..])),
+    )]
+    #[case::exact_path_with_other_method(HttpRequest::post("/todos").build(), None)]
+    fn serve_query_serves_most_specific_not_found_response(
+        error_router: HttpCertificationRouter,
+        #[case] request: HttpRequest,
+        #[case] expected: Option<(&[u8], &[&str])>,
+    ) {
+        let result = error_router.serve_query(&request, DATA_CERTIFICATE);
+
+        match expected {
+            Some((expected_body, expected_expr_path)) => {
+                let response = result.unwrap();
+                assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+                assert_eq!(response.body(), expected_body);
+                assert_eq!(witnessed_expr_path(&response), expected_expr_path);
+            }
+            None => assert_matches!(
+                result,
+                Err(HttpCertificationError::WitnessEntryNotFound { request_path })
+                    if request_path == "/todos"
+            ),
+        }
+    }
+
+    #[rstest]
+    fn remove_error_responses_deletes_certifications(
+        mut error_router: HttpCertificationRouter,
+        response_only_cel_expr: DefaultResponseOnlyCelExpression<'static>,
+    ) {
+        let root_hash = error_router.root_hash();
+
+        error_router.remove_error_responses("/users");
+        assert_eq!(error_router.root_hash(), root_hash);
+
+        error_router.remove_error_responses("/todos");
+        assert_ne!(error_router.root_hash(), root_hash);
+        let response = error_router
+            .serve_error(
+                &HttpRequest::get("/todos/1").build(),
+                StatusCode::IM_A_TEAPOT,
+                DATA_CERTIFICATE,
+            )
+            .unwrap();
+        assert_eq!(witnessed_expr_path(&response), ["http_expr", "<*>"]);
+
+        error_router.remove_response(&Method::GET, "/todos");
+        error_router.remove_error_responses("");
+        error_router.remove_fallback_response("/users");
+        assert_eq!(
+            error_router.root_hash(),
+            HttpCertificationRouter::default().root_hash()
+        );
+        assert_matches!(
+            error_router.serve_query(&HttpRequest::get("/todos/1").build(), DATA_CERTIFICATE),
+            Err(HttpCertificationError::WitnessEntryNotFound { .. })
+        );
+
+        error_router
+            .certify_error_responses("", &ErrorResponseCatalog::new(), &response_only_cel_expr)
+            .unwrap();
+        let response = error_router
+            .serve_query(&HttpRequest::get("/todos").build(), DATA_CERTIFICATE)
+            .unwrap();
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+        assert_eq!(witnessed_expr_path(&response), ["http_expr", "<*>"]);
+    }
+
+    fn create_todo_handler(
+        _request: &HttpRequest,
+        _params: &HttpRouteParams,
+    ) -> HttpResponse<'static> {
+        HttpResponse::created(b"{}".to_vec(), vec![]).build()
+    }
+
+    /// Returns the expression path of the `IC-Certificate` header of the given response, after
+    /// asserting that the witness of the header contains it.
+    fn witnessed_expr_path(response: &HttpResponse) -> Vec<String> {
+        let (_, certificate_header) = response
+            .headers()
+            .iter()
+            .find(|(name, _)| name == CERTIFICATE_HEADER_NAME)
+            .unwrap();
+        let field = |name: &str| {
+            let value = certificate_header
+                .split(", ")
+                .find_map(|field| field.strip_prefix(name)?.strip_prefix("=:"))
+                .and_then(|value| value.strip_suffix(':'))
+                .unwrap();
+
+            BASE64.decode(value).unwrap()
+        };
+
+        let witness: HashTree = serde_cbor::from_slice(&field("tree")).unwrap();
+        let expr_path: Vec<String> = serde_cbor::from_slice(&field("expr_path")).unwrap();
+        assert_matches!(
+            witness.lookup_subtree(&expr_path),
+            SubtreeLookupResult::Found(_)
+        );
+
+        expr_path
+    }
+}
@@ -0,0 +1,123 @@
+use crate::{HeaderField, HttpResponse, StatusCode};
+use std::collections::BTreeMap;
+
+/// A catalog of standard error responses, to be certified at once for a scope of request paths
+/// with [certify_error_responses](crate::HttpCertificationRouter::certify_error_responses).
+///
+/// A [new](ErrorResponseCatalog::new) catalog contains a plain text response for each of the
+/// [STATUS_CODES](ErrorResponseCatalog::STATUS_CODES), with the canonical reason of the status
+/// code as the body. The responses can be replaced with
+/// [with_response](ErrorResponseCatalog::with_response), and headers that are common to all
+/// responses can be added with [with_headers](ErrorResponseCatalog::with_headers).
+///
+/// All responses of a catalog are certified under the same wildcard path as the
+/// [fallback response](crate::HttpCertificationRouter::certify_fallback_response) of the scope,
+/// so the HTTP Gateway accepts any of them, as well as the fallback response, for any request path
+/// within the scope. A malicious replica can therefore serve one of these responses in place of
+/// another, such as `403 Forbidden` instead of `404 Not Found`. Responses that must not be
+/// substituted for each other should be certified for exact paths with
+/// [certify_response](crate::HttpCertificationRouter::certify_response) instead.
+///
+/// # Examples
+///
+/// ```
+/// use ic_http_certification::{ErrorResponseCatalog, HttpResponse, StatusCode};
+///
+/// let catalog = ErrorResponseCatalog::new()
+///     .with_response(HttpResponse::not_found(
+///         br#"{"error":"not found"}"#,
+///         vec![("Content-Type".into(), "application/json".into())],
+///     ).build())
+///     .with_headers(vec![("Cache-Control".into(), "no-store".into())]);
+///
+/// let not_found = catalog.response(StatusCode::NOT_FOUND).unwrap();
+/// assert_eq!(not_found.body(), br#"{"error":"not found"}"#);
+/// assert_eq!(
+///     not_found.headers(),
+///     &[
+///         ("Content-Type".into(), "application/json".into()),
+///         ("Cache-Control".into(), "no-store".into()),
+///     ]
+/// );
+///
+/// let bad_request = catalog.response(StatusCode::BAD_REQUEST).unwrap();
+/// assert_eq!(bad_request.body(), b"Bad Request");
+/// ```
+#[derive(Debug, Clone)]
+pub struct ErrorResponseCatalog {
+    responses: BTreeMap<StatusCode, HttpResponse<'static>>,
+}
+
+impl ErrorResponseCatalog {
+    /// The status codes of the responses in a [new](ErrorResponseCatalog::new) catalog.
+    pub const STATUS_CODES: [StatusCode; 7] = [
+        StatusCode::BAD_REQUEST,
+        StatusCode::UNAUTHORIZED,
+        StatusCode::FORBIDDEN,
+        StatusCode::NOT_FOUND,
+        StatusCode::METHOD_NOT_ALLOWED,
+        StatusCode::TOO_MANY_REQUESTS,
+        StatusCode::INTERNAL_SERVER_ERROR,
+    ];
+
+    /// Creates a new [ErrorResponseCatalog] with a plain text response for each of the
+    /// [STATUS_CODES](ErrorResponseCatalog::STATUS_CODES).
+    pub fn new() -> Self {
+        let responses = Self::STATUS_CODES
+            .into_iter()
+            .map(|status_code| {
+                let body = status_code.canonical_reason().unwrap_or_default();
+                let response = HttpResponse::builder()
+                    .with_status_code(status_code)
+                    .with_body(body.as_bytes())
+                    .with_headers(vec![(
+                        "Content-Type".to_string(),
+                        "text/plain; charset=utf-8".to_string(),
+                    )])
+                    .build();
+
+                (status_code, response)
+            })
+            .collect();
+
+        Self { responses }
+    }
+
+    /// Sets the response for the status code of the given response, replacing any response that
+    /// was previously set for it. Responses for status codes other than the
+    /// [STATUS_CODES](ErrorResponseCatalog::STATUS_CODES) are added to the catalog.
+    pub fn with_response(mut self, response: HttpResponse<'static>) -> Self {
+        self.responses.insert(response.status_code(), response);
+
+        self
+    }
+
+    /// Adds the given headers to all responses that are currently in the catalog.
+    pub fn with_headers(mut self, headers: Vec<HeaderField>) -> Self {
+        for response in self.responses.values_mut() {
+            for header in &headers {
+                response.add_header(header.clone());
+            }
+        }
+
+        self
+    }
+
+    /// Returns the response for the given status code, if any.
+    #[inline]
+    pub fn response(&self, status_code: StatusCode) -> Option<&HttpResponse<'static>> {
+        self.responses.get(&status_code)
+    }
+
+    /// Returns the responses in the catalog, ordered by their status code.
+    #[inline]
+    pub fn responses(&self) -> impl Iterator<Item = &HttpResponse<'static>> {
+        self.responses.values()
+    }
+}
+
+impl Default for ErrorResponseCatalog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,9 @@
+//! The Router module contains an [HttpCertificationRouter] that combines routing of
+//! [HttpRequest](crate::HttpRequest)s with the certification of the corresponding
+//! [HttpResponse](crate::HttpResponse)s in an [HttpCertificationTree](crate::HttpCertificationTree).
+
+mod certification_router;
+mod error_responses;
+
+pub use certification_router::*;
+pub use error_responses::*;
@@ -1,11 +1,14 @@
+#[cfg(feature = "instrumentation")]
+use crate::instrumentation::{Measurement, Operation};
 use crate::{
-    request_hash, response_hash, DefaultCelBuilder, DefaultFullCelExpression,
+    combine_response_hashes, filter_response_headers, request_hash, response_headers_hash,
+    DefaultCelBuilder, DefaultFullCelExpression, DefaultResponseCertification,
     DefaultResponseOnlyCelExpression, HttpCertificationError, HttpCertificationResult, HttpRequest,
     HttpResponse, CERTIFICATE_EXPRESSION_HEADER_NAME,
 };
 use ic_certification::Hash;
 use ic_representation_independent_hash::hash;
-use std::borrow::Cow;
+use std::{borrow::Cow, sync::OnceLock};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum HttpCertificationType {
@@ -14,11 +17,13 @@ enum HttpCertificationType {
     },
     ResponseOnly {
         cel_expr_hash: Hash,
+        response_headers_hash: Hash,
         response_hash: Hash,
     },
     Full {
         cel_expr_hash: Hash,
         request_hash: Hash,
+        response_headers_hash: Hash,
         response_hash: Hash,
     },
 }
@@ -42,9 +47,17 @@ pub struct HttpCertification(HttpCertificationType);
 impl HttpCertification {
     /// Creates a certification that excludes both the [HttpRequest] and
     /// the corresponding [HttpResponse].
+    ///
+    /// The hash of the skip CEL expression is computed once and reused for every
+    /// subsequent skip certification.
     pub fn skip() -> HttpCertification {
-        let cel_expr = DefaultCelBuilder::skip_certification().to_string();
-        let cel_expr_hash = hash(cel_expr.as_bytes());
+        static SKIP_CEL_EXPR_HASH: OnceLock<Hash> = OnceLock::new();
+
+        let cel_expr_hash = *SKIP_CEL_EXPR_HASH.get_or_init(|| {
+            let cel_expr = DefaultCelBuilder::skip_certification().to_string();
+
+            hash(cel_expr.as_bytes())
+        });
 
         Self(HttpCertificationType::Skip { cel_expr_hash })
     }
@@ -56,15 +69,19 @@ impl HttpCertification {
         response: &HttpResponse,
         response_body_hash: Option<Hash>,
     ) -> HttpCertificationResult<HttpCertification> {
+        #[cfg(feature = "instrumentation")]
+        let _measurement = Measurement::start(Operation::CertificationHash);
         let cel_expr = cel_expr_def.to_string();
         Self::validate_response(response, &cel_expr)?;
 
         let cel_expr_hash = hash(cel_expr.as_bytes());
-        let response_hash = response_hash(response, &cel_expr_def.response, response_body_hash);
+        let response_headers_hash = Self::response_headers_hash(response, &cel_expr_def.response);
+        let response_body_hash = response_body_hash.unwrap_or_else(|| hash(response.body()));
 
         Ok(Self(HttpCertificationType::ResponseOnly {
             cel_expr_hash,
-            response_hash,
+            response_headers_hash,
+            response_hash: combine_response_hashes(&response_headers_hash, &response_body_hash),
         }))
     }
 
@@ -76,26 +93,217 @@ impl HttpCertification {
         response: &HttpResponse,
         response_body_hash: Option<Hash>,
     ) -> HttpCertificationResult<HttpCertification> {
+        #[cfg(feature = "instrumentation")]
+        let _measurement = Measurement::start(Operation::CertificationHash);
         let cel_expr = cel_expr_def.to_string();
         Self::validate_response(response, &cel_expr)?;
 
         let cel_expr_hash = hash(cel_expr.as_bytes());
         let request_hash = request_hash(request, &cel_expr_def.request)?;
-        let response_hash = response_hash(response, &cel_expr_def.response, response_body_hash);
+        let response_headers_hash = Self::response_headers_hash(response, &cel_expr_def.response);
+        let response_body_hash = response_body_hash.unwrap_or_else(|| hash(response.body()));
 
         Ok(Self(HttpCertificationType::Full {
             cel_expr_hash,
             request_hash,
-            response_hash,
+            response_headers_hash,
+            response_hash: combine_response_hashes(&response_headers_hash, &response_body_hash),
         }))
     }
 
+    /// Creates a certification for a response that only differs from the response certified by
+    /// this certification in its body, reusing the hash of the response's headers and status
+    /// code instead of hashing them again. This is cheaper than creating the certification from
+    /// scratch for responses that change frequently, such as a counter.
+    ///
+    /// The headers of the new response, including any `Content-Length` header, and its status
+    /// code must be the same as those of the previously certified response, otherwise the
+    /// certification will not match the response. A [skip](HttpCertification::skip)
+    /// certification is returned unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{DefaultCelBuilder, HttpCertification, HttpResponse, CERTIFICATE_EXPRESSION_HEADER_NAME};
+    ///
+    /// let cel_expr = DefaultCelBuilder::response_only_certification().build();
+    /// let headers = vec![(CERTIFICATE_EXPRESSION_HEADER_NAME.to_string(), cel_expr.to_string())];
+    ///
+    /// let response = HttpResponse::ok(b"1", headers.clone()).build();
+    /// let certification = HttpCertification::response_only(&cel_expr, &response, None).unwrap();
+    ///
+    /// let updated_response = HttpResponse::ok(b"2", headers).build();
+    /// let updated_certification = certification.with_response_body(updated_response.body());
+    ///
+    /// assert_eq!(
+    ///     updated_certification,
+    ///     HttpCertification::response_only(&cel_expr, &updated_response, None).unwrap()
+    /// );
+    /// ```
+    pub fn with_response_body(&self, response_body: &[u8]) -> HttpCertification {
+        self.with_response_body_hash(hash(response_body))
+    }
+
+    /// Same as [with_response_body](HttpCertification::with_response_body), but uses a
+    /// precomputed hash of the new response body.
+    pub fn with_response_body_hash(&self, response_body_hash: Hash) -> HttpCertification {
+        let mut certification_type = self.0;
+        match &mut certification_type {
+            HttpCertificationType::Skip { .. } => {}
+            HttpCertificationType::ResponseOnly {
+                response_headers_hash,
+                response_hash,
+                ..
+            }
+            | HttpCertificationType::Full {
+                response_headers_hash,
+                response_hash,
+                ..
+            } => {
+                *response_hash =
+                    combine_response_hashes(response_headers_hash, &response_body_hash);
+            }
+        }
+
+        Self(certification_type)
+    }
+
+    /// Returns the hash of the CEL expression used by this certification.
+    ///
+    /// The hash is computed once when the certification is created and is reused by every
+    /// subsequent tree operation, such as inserting, deleting or witnessing an
+    /// [HttpCertificationTreeEntry](crate::HttpCertificationTreeEntry) with this certification.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{DefaultCelBuilder, HttpCertification};
+    /// use ic_representation_independent_hash::hash;
+    ///
+    /// let cel_expr = DefaultCelBuilder::skip_certification().to_string();
+    ///
+    /// assert_eq!(HttpCertification::skip().cel_expr_hash(), hash(cel_expr.as_bytes()));
+    /// ```
+    #[inline]
+    pub fn cel_expr_hash(&self) -> Hash {
+        match self.0 {
+            HttpCertificationType::Skip { cel_expr_hash }
+            | HttpCertificationType::ResponseOnly { cel_expr_hash, .. }
+            | HttpCertificationType::Full { cel_expr_hash, .. } => cel_expr_hash,
+        }
+    }
+
+    /// Returns the hash of the [HttpRequest] certified by this certification, as computed by
+    /// [request_hash](crate::request_hash), or [None] if the request is excluded from
+    /// certification.
+    ///
+    /// Together with [cel_expr_hash](HttpCertification::cel_expr_hash) and
+    /// [response_hash](HttpCertification::response_hash), this can be used to cross-check the
+    /// contents of an [HttpCertificationTree](crate::HttpCertificationTree) against recorded
+    /// requests and responses.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{DefaultCelBuilder, HttpCertification, HttpRequest, HttpResponse, request_hash, CERTIFICATE_EXPRESSION_HEADER_NAME};
+    ///
+    /// let cel_expr = DefaultCelBuilder::full_certification().build();
+    /// let request = HttpRequest::get("/index.html").build();
+    /// let response = HttpResponse::ok(b"", vec![(CERTIFICATE_EXPRESSION_HEADER_NAME.to_string(), cel_expr.to_string())]).build();
+    /// let certification = HttpCertification::full(&cel_expr, &request, &response, None).unwrap();
+    ///
+    /// assert_eq!(certification.request_hash(), Some(request_hash(&request, &cel_expr.request).unwrap()));
+    /// assert_eq!(HttpCertification::skip().request_hash(), None);
+    /// ```
+    #[inline]
+    pub fn request_hash(&self) -> Option<Hash> {
+        match self.0 {
+            HttpCertificationType::Full { request_hash, .. } => Some(request_hash),
+            HttpCertificationType::Skip { .. } | HttpCertificationType::ResponseOnly { .. } => None,
+        }
+    }
+
+    /// Returns the hash of the [HttpResponse] certified by this certification, as computed by
+    /// [response_hash](crate::response_hash), or [None] if certification is skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{DefaultCelBuilder, HttpCertification, HttpResponse, response_hash, CERTIFICATE_EXPRESSION_HEADER_NAME};
+    ///
+    /// let cel_expr = DefaultCelBuilder::response_only_certification().build();
+    /// let response = HttpResponse::ok(b"", vec![(CERTIFICATE_EXPRESSION_HEADER_NAME.to_string(), cel_expr.to_string())]).build();
+    /// let certification = HttpCertification::response_only(&cel_expr, &response, None).unwrap();
+    ///
+    /// assert_eq!(certification.response_hash(), Some(response_hash(&response, &cel_expr.response, None)));
+    /// assert_eq!(HttpCertification::skip().response_hash(), None);
+    /// ```
+    #[inline]
+    pub fn response_hash(&self) -> Option<Hash> {
+        match self.0 {
+            HttpCertificationType::Skip { .. } => None,
+            HttpCertificationType::ResponseOnly { response_hash, .. }
+            | HttpCertificationType::Full { response_hash, .. } => Some(response_hash),
+        }
+    }
+
+    /// Validates that the given response can be served with this certification, by checking
+    /// that it contains exactly one `IC-CertificateExpression` header, and that the value of
+    /// this header is the CEL expression used to create this certification.
+    ///
+    /// This catches responses that were certified without the `IC-CertificateExpression` header,
+    /// or that were modified after certification, before they are served, instead of the
+    /// mismatch only being detected by the HTTP Gateway. The headers certified by the CEL
+    /// expression can be checked with
+    /// [DefaultResponseCertification::validate_against](crate::DefaultResponseCertification::validate_against).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{DefaultCelBuilder, HttpCertification, HttpResponse, CERTIFICATE_EXPRESSION_HEADER_NAME};
+    ///
+    /// let cel_expr = DefaultCelBuilder::skip_certification().to_string();
+    /// let certification = HttpCertification::skip();
+    ///
+    /// let response = HttpResponse::ok(b"Hello, World!", vec![
+    ///     (CERTIFICATE_EXPRESSION_HEADER_NAME.to_string(), cel_expr),
+    /// ]).build();
+    /// assert!(certification.validate_against(&response).is_ok());
+    ///
+    /// let response = HttpResponse::ok(b"Hello, World!", vec![]).build();
+    /// assert!(certification.validate_against(&response).is_err());
+    /// ```
+    pub fn validate_against(&self, response: &HttpResponse) -> HttpCertificationResult {
+        let cel_exprs: Vec<&str> = response
+            .get_header_values(CERTIFICATE_EXPRESSION_HEADER_NAME)
+            .collect();
+
+        match cel_exprs.as_slice() {
+            [cel_expr] if hash(cel_expr.as_bytes()) == self.cel_expr_hash() => Ok(()),
+            _ => Err(
+                HttpCertificationError::CertificateExpressionHeaderDoesNotMatchCertification {
+                    actual: cel_exprs.into_iter().map(String::from).collect(),
+                },
+            ),
+        }
+    }
+
+    fn response_headers_hash(
+        response: &HttpResponse,
+        response_certification: &DefaultResponseCertification,
+    ) -> Hash {
+        let filtered_headers = filter_response_headers(response, response_certification);
+
+        response_headers_hash(&response.status_code().as_u16().into(), &filtered_headers)
+    }
+
     pub(crate) fn to_tree_path(self) -> Vec<Vec<u8>> {
         match self.0 {
             HttpCertificationType::Skip { cel_expr_hash } => vec![cel_expr_hash.to_vec()],
             HttpCertificationType::ResponseOnly {
                 cel_expr_hash,
                 response_hash,
+                ..
             } => vec![
                 cel_expr_hash.to_vec(),
                 "".as_bytes().to_vec(),
@@ -105,6 +313,7 @@ impl HttpCertification {
                 cel_expr_hash,
                 request_hash,
                 response_hash,
+                ..
             } => vec![
                 cel_expr_hash.to_vec(),
                 request_hash.to_vec(),
@@ -117,7 +326,7 @@ impl HttpCertification {
         let mut found_header = false;
 
         for (header_name, header_value) in response.headers() {
-            if header_name.to_lowercase() == CERTIFICATE_EXPRESSION_HEADER_NAME.to_lowercase() {
+            if header_name.eq_ignore_ascii_case(CERTIFICATE_EXPRESSION_HEADER_NAME) {
                 match header_value == cel_expr {
                     true => {
                         if found_header {
@@ -167,7 +376,8 @@ impl<'a> From<&'a HttpCertification> for Cow<'a, HttpCertification> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{DefaultResponseCertification, StatusCode};
+    use crate::{response_hash, StatusCode};
+    use assert_matches::assert_matches;
     use rstest::*;
 
     #[rstest]
@@ -182,6 +392,10 @@ mod tests {
             HttpCertificationType::Skip { cel_expr_hash } if cel_expr_hash == expected_cel_expr_hash
         ));
         assert_eq!(result.to_tree_path(), vec![expected_cel_expr_hash.to_vec()]);
+        assert_eq!(result.cel_expr_hash(), expected_cel_expr_hash);
+        assert_eq!(result.request_hash(), None);
+        assert_eq!(result.response_hash(), None);
+        assert_eq!(HttpCertification::skip(), result);
     }
 
     #[rstest]
@@ -208,7 +422,8 @@ mod tests {
             result.0,
             HttpCertificationType::ResponseOnly {
                 cel_expr_hash,
-                response_hash
+                response_hash,
+                ..
             } if cel_expr_hash == expected_cel_expr_hash &&
                 response_hash == expected_response_hash
         ));
@@ -220,6 +435,97 @@ mod tests {
                 expected_response_hash.to_vec()
             ]
         );
+        assert_eq!(result.cel_expr_hash(), expected_cel_expr_hash);
+        assert_eq!(result.request_hash(), None);
+        assert_eq!(result.response_hash(), Some(expected_response_hash));
+    }
+
+    #[rstest]
+    fn validate_against_response() {
+        let cel_expr = DefaultCelBuilder::response_only_certification()
+            .with_response_certification(DefaultResponseCertification::certified_response_headers(
+                vec!["ETag"],
+            ))
+            .build();
+        let response = &HttpResponse::ok(
+            b"Hello, World!",
+            vec![(
+                CERTIFICATE_EXPRESSION_HEADER_NAME.to_string(),
+                cel_expr.to_string(),
+            )],
+        )
+        .build();
+        let certification = HttpCertification::response_only(&cel_expr, response, None).unwrap();
+
+        assert_matches!(certification.validate_against(response), Ok(()));
+
+        let skip_cel_expr = DefaultCelBuilder::skip_certification().to_string();
+        let cases = [
+            vec![],
+            vec![(
+                CERTIFICATE_EXPRESSION_HEADER_NAME.to_string(),
+                skip_cel_expr.clone(),
+            )],
+            vec![
+                (
+                    CERTIFICATE_EXPRESSION_HEADER_NAME.to_string(),
+                    cel_expr.to_string(),
+                ),
+                (
+                    CERTIFICATE_EXPRESSION_HEADER_NAME.to_string(),
+                    cel_expr.to_string(),
+                ),
+            ],
+        ];
+        for headers in cases {
+            let expected_actual: Vec<String> =
+                headers.iter().map(|(_, value)| value.clone()).collect();
+            let invalid_response = HttpResponse::ok(b"Hello, World!", headers).build();
+
+            assert_matches!(
+                certification.validate_against(&invalid_response),
+                Err(HttpCertificationError::CertificateExpressionHeaderDoesNotMatchCertification {
+                    actual
+                }) if actual == expected_actual
+            );
+        }
+    }
+
+    #[rstest]
+    fn full_certification_with_response_body() {
+        let cel_expr = DefaultCelBuilder::full_certification()
+            .with_response_certification(DefaultResponseCertification::response_header_exclusions(
+                vec![],
+            ))
+            .build();
+        let request = &HttpRequest::get("/counter").build();
+        let headers = vec![(
+            CERTIFICATE_EXPRESSION_HEADER_NAME.to_string(),
+            cel_expr.to_string(),
+        )];
+        let response = &HttpResponse::ok(b"1", headers.clone()).build();
+        let updated_response = &HttpResponse::ok(b"2", headers).build();
+
+        let certification = HttpCertification::full(&cel_expr, request, response, None).unwrap();
+        let expected_certification =
+            HttpCertification::full(&cel_expr, request, updated_response, None).unwrap();
+
+        assert_eq!(
+            certification.with_response_body(updated_response.body()),
+            expected_certification
+        );
+        assert_eq!(
+            certification.with_response_body_hash(hash(updated_response.body())),
+            expected_certification
+        );
+        assert_eq!(
+            expected_certification.with_response_body(response.body()),
+            certification
+        );
+        assert_eq!(
+            HttpCertification::skip().with_response_body(b"2"),
+            HttpCertification::skip()
+        );
     }
 
     #[rstest]
@@ -328,7 +634,8 @@ mod tests {
             HttpCertificationType::Full {
                 cel_expr_hash,
                 request_hash,
-                response_hash
+                response_hash,
+                ..
             } if cel_expr_hash == expected_cel_expr_hash &&
                 request_hash == expected_request_hash &&
                 response_hash == expected_response_hash
@@ -341,6 +648,9 @@ mod tests {
                 expected_response_hash.to_vec()
             ]
         );
+        assert_eq!(result.cel_expr_hash(), expected_cel_expr_hash);
+        assert_eq!(result.request_hash(), Some(expected_request_hash));
+        assert_eq!(result.response_hash(), Some(expected_response_hash));
     }
 
     #[rstest]
@@ -0,0 +1,347 @@
+use super::{HttpCertificationPath, HttpCertificationTreeEntry};
+use crate::{HttpCertification, HttpCertificationError, HttpCertificationResult, HttpRequest};
+use std::{borrow::Cow, collections::HashMap};
+
+/// The values of the parameters of an [HttpCertificationRoute] for a request path, by parameter name.
+pub type HttpRouteParams = HashMap<String, String>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum RouteSegment {
+    Static(String),
+    Param(String),
+    CatchAll(String),
+}
+
+/// A route template, such as `/todos/{id}`, that determines which [HttpCertificationPath]
+/// the responses for a route are certified under.
+///
+/// Templates use the same syntax as the [matchit](https://docs.rs/matchit) crate:
+///
+/// - `{name}` matches a single segment of the request path.
+/// - `{*name}` matches the remainder of the request path and is only allowed as the last segment.
+///
+/// Responses for a route without a catch-all parameter are certified under an
+/// [exact](HttpCertificationPath::exact()) path for every concrete request path, such as `/todos/1`,
+/// so that the response for one request path cannot be served for another.
+/// Responses for a route that ends with a catch-all parameter are certified under a
+/// [wildcard](HttpCertificationPath::wildcard()) path for the part of the request path that precedes
+/// the catch-all parameter.
+///
+/// # Examples
+///
+/// ```
+/// use ic_http_certification::{HttpCertificationPath, HttpCertificationRoute};
+///
+/// let route = HttpCertificationRoute::new("/todos/{id}").unwrap();
+/// assert_eq!(
+///     route.tree_path("/todos/1").unwrap(),
+///     HttpCertificationPath::exact("/todos/1")
+/// );
+/// assert!(route.tree_path("/users/1").is_err());
+///
+/// let route = HttpCertificationRoute::new("/assets/{*path}").unwrap();
+/// assert_eq!(
+///     route.tree_path("/assets/js/app.js").unwrap(),
+///     HttpCertificationPath::wildcard("/assets")
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpCertificationRoute {
+    template: String,
+    segments: Vec<RouteSegment>,
+}
+
+impl HttpCertificationRoute {
+    /// Parses a route template, returning an error if the template is not valid.
+    pub fn new(template: impl Into<String>) -> HttpCertificationResult<Self> {
+        let template = template.into();
+        let invalid_template = |reason: &str| HttpCertificationError::InvalidRouteTemplate {
+            template: template.clone(),
+            reason: reason.to_string(),
+        };
+
+        let Some(path) = template.strip_prefix('/') else {
+            return Err(invalid_template("the template must start with a `/`"));
+        };
+
+        let mut segments = vec![];
+        for segment in path.split('/') {
+            if matches!(segments.last(), Some(RouteSegment::CatchAll(_))) {
+                return Err(invalid_template(
+                    "a catch-all parameter must be the last segment",
+                ));
+            }
+
+            let segment = match segment
+                .strip_prefix('{')
+                .and_then(|segment| segment.strip_suffix('}'))
+            {
+                Some(name) => {
+                    let (name, is_catch_all) = match name.strip_prefix('*') {
+                        Some(name) => (name, true),
+                        None => (name, false),
+                    };
+                    if name.is_empty() || name.contains(['{', '}']) {
+                        return Err(invalid_template("parameters must have a valid name"));
+                    }
+
+                    match is_catch_all {
+                        true => RouteSegment::CatchAll(name.to_string()),
+                        false => RouteSegment::Param(name.to_string()),
+                    }
+                }
+                None => {
+                    if segment.contains(['{', '}']) {
+                        return Err(invalid_template(
+                            "parameters must span a whole path segment",
+                        ));
+                    }
+
+                    RouteSegment::Static(segment.to_string())
+                }
+            };
+            segments.push(segment);
+        }
+
+        Ok(Self { template, segments })
+    }
+
+    /// The route template that this route was created from.
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    /// The first segment of this route, if it is static. Request paths only match this route if
+    /// their first segment is equal to it.
+    pub(crate) fn first_static_segment(&self) -> Option<&str> {
+        match self.segments.first() {
+            Some(RouteSegment::Static(segment)) => Some(segment),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the given request path matches this route.
+    pub fn matches(&self, request_path: &str) -> bool {
+        self.tree_path(request_path).is_ok()
+    }
+
+    /// Returns the [HttpCertificationPath] that responses for the given request path are certified
+    /// under, or an error if the request path does not match this route.
+    pub fn tree_path(
+        &self,
+        request_path: &str,
+    ) -> HttpCertificationResult<HttpCertificationPath<'static>> {
+        self.match_request_path(request_path)
+            .map(|(tree_path, _)| tree_path)
+    }
+
+    /// Returns the values of the parameters of this route for the given request path, or an error
+    /// if the request path does not match this route. The value of a catch-all parameter is the
+    /// remainder of the request path, without a leading slash.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::HttpCertificationRoute;
+    ///
+    /// let route = HttpCertificationRoute::new("/users/{id}/files/{*path}").unwrap();
+    /// let params = route.params("/users/1/files/images/logo.png").unwrap();
+    ///
+    /// assert_eq!(params["id"], "1");
+    /// assert_eq!(params["path"], "images/logo.png");
+    /// ```
+    pub fn params(&self, request_path: &str) -> HttpCertificationResult<HttpRouteParams> {
+        self.match_request_path(request_path)
+            .map(|(_, params)| params)
+    }
+
+    /// Creates an [HttpCertificationTreeEntry] for the given request and certification,
+    /// using the [tree path](HttpCertificationRoute::tree_path) for the path of the request.
+    /// This ensures that all responses for this route are certified under a consistent path.
+    pub fn tree_entry<'a>(
+        &self,
+        request: &HttpRequest,
+        certification: impl Into<Cow<'a, HttpCertification>>,
+    ) -> HttpCertificationResult<HttpCertificationTreeEntry<'a>> {
+        let tree_path: HttpCertificationPath<'a> = self.tree_path(&request.get_path()?)?;
+
+        Ok(HttpCertificationTreeEntry::new(tree_path, certification))
+    }
+
+    fn match_request_path(
+        &self,
+        request_path: &str,
+    ) -> HttpCertificationResult<(HttpCertificationPath<'static>, HttpRouteParams)> {
+        let mut request_segments = request_path
+            .strip_prefix('/')
+            .unwrap_or(request_path)
+            .split('/');
+        let mut wildcard_prefix = String::new();
+        let mut params = HttpRouteParams::new();
+
+        for segment in self.segments.iter() {
+            let Some(request_segment) = request_segments.next() else {
+                return Err(self.mismatch(request_path));
+            };
+
+            match segment {
+                RouteSegment::CatchAll(name) if !request_segment.is_empty() => {
+                    let value = std::iter::once(request_segment)
+                        .chain(request_segments)
+                        .collect::<Vec<_>>()
+                        .join("/");
+                    params.insert(name.clone(), value);
+
+                    return Ok((HttpCertificationPath::wildcard(wildcard_prefix), params));
+                }
+                RouteSegment::Static(expected) if expected == request_segment => {}
+                RouteSegment::Param(name) if !request_segment.is_empty() => {
+                    params.insert(name.clone(), request_segment.to_string());
+                }
+                _ => return Err(self.mismatch(request_path)),
+            }
+
+            wildcard_prefix.push('/');
+            wildcard_prefix.push_str(request_segment);
+        }
+
+        if request_segments.next().is_some() {
+            return Err(self.mismatch(request_path));
+        }
+
+        Ok((
+            HttpCertificationPath::exact(request_path.to_string()),
+            params,
+        ))
+    }
+
+    fn mismatch(&self, request_path: &str) -> HttpCertificationError {
+        HttpCertificationError::RequestPathDoesNotMatchRouteTemplate {
+            request_path: request_path.to_string(),
+            template: self.template.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use rstest::*;
+
+    #[rstest]
+    #[case("/todos", "/todos", HttpCertificationPath::exact("/todos"))]
+    #[case("/todos/", "/todos/", HttpCertificationPath::exact("/todos/"))]
+    #[case("/todos/{id}", "/todos/1", HttpCertificationPath::exact("/todos/1"))]
+    #[case(
+        "/users/{id}/profile",
+        "/users/1/profile",
+        HttpCertificationPath::exact("/users/1/profile")
+    )]
+    #[case("/{*path}", "/index.html", HttpCertificationPath::wildcard(""))]
+    #[case(
+        "/assets/{*path}",
+        "/assets/js/app.js",
+        HttpCertificationPath::wildcard("/assets")
+    )]
+    #[case(
+        "/users/{id}/files/{*path}",
+        "/users/1/files/images/logo.png",
+        HttpCertificationPath::wildcard("/users/1/files")
+    )]
+    fn tree_path(
+        #[case] template: &str,
+        #[case] request_path: &str,
+        #[case] expected: HttpCertificationPath,
+    ) {
+        let route = HttpCertificationRoute::new(template).unwrap();
+
+        assert!(route.matches(request_path));
+        assert_eq!(route.tree_path(request_path).unwrap(), expected);
+    }
+
+    #[rstest]
+    #[case("/todos", "/todos/")]
+    #[case("/todos", "/users")]
+    #[case("/todos/{id}", "/todos")]
+    #[case("/todos/{id}", "/todos/")]
+    #[case("/todos/{id}", "/todos/1/comments")]
+    #[case("/assets/{*path}", "/assets")]
+    #[case("/assets/{*path}", "/assets/")]
+    #[case("/assets/{*path}", "/images/logo.png")]
+    fn tree_path_mismatch(#[case] template: &str, #[case] request_path: &str) {
+        let route = HttpCertificationRoute::new(template).unwrap();
+
+        assert!(!route.matches(request_path));
+        assert_matches!(
+            route.tree_path(request_path),
+            Err(HttpCertificationError::RequestPathDoesNotMatchRouteTemplate {
+                request_path: actual_request_path,
+                template: actual_template,
+            }) if actual_request_path == request_path && actual_template == template
+        );
+    }
+
+    #[rstest]
+    #[case("todos")]
+    #[case("/todos/{}")]
+    #[case("/todos/{*}")]
+    #[case("/todos/item-{id}")]
+    #[case("/assets/{*path}/index.html")]
+    fn invalid_template(#[case] template: &str) {
+        assert_matches!(
+            HttpCertificationRoute::new(template),
+            Err(HttpCertificationError::InvalidRouteTemplate {
+                template: actual_template,
+                ..
+            }) if actual_template == template
+        );
+    }
+
+    #[rstest]
+    #[case("/todos", "/todos", &[])]
+    #[case("/todos/{id}", "/todos/1", &[("id", "1")])]
+    #[case(
+        "/users/{id}/files/{*path}",
+        "/users/1/files/images/logo.png",
+        &[("id", "1"), ("path", "images/logo.png")]
+    )]
+    fn params(
+        #[case] template: &str,
+        #[case] request_path: &str,
+        #[case] expected: &[(&str, &str)],
+    ) {
+        let route = HttpCertificationRoute::new(template).unwrap();
+        let expected: HttpRouteParams = expected
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+
+        assert_eq!(route.params(request_path).unwrap(), expected);
+    }
+
+    #[rstest]
+    fn tree_entry() {
+        let route = HttpCertificationRoute::new("/todos/{id}").unwrap();
+        let certification = HttpCertification::skip();
+
+        let entry = route
+            .tree_entry(
+                &HttpRequest::get("/todos/1?done=true").build(),
+                certification,
+            )
+            .unwrap();
+        assert_eq!(
+            entry,
+            HttpCertificationTreeEntry::new(
+                HttpCertificationPath::exact("/todos/1"),
+                certification
+            )
+        );
+
+        assert_matches!(
+            route.tree_entry(&HttpRequest::get("/users/1").build(), certification),
+            Err(HttpCertificationError::RequestPathDoesNotMatchRouteTemplate { .. })
+        );
+    }
+}
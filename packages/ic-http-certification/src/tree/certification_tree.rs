@@ -2,26 +2,172 @@ use super::{
     certification_tree_entry::HttpCertificationTreeEntry,
     certification_tree_path::CertificationTreePathSegment,
 };
+#[cfg(feature = "instrumentation")]
+use crate::instrumentation::{Measurement, Operation};
 use crate::{
     tree::HttpCertificationPathType,
     utils::{more_specific_wildcards_for, PATH_PREFIX_BYTES},
     HttpCertificationError, HttpCertificationPath, HttpCertificationResult,
 };
-use ic_certification::{labeled, labeled_hash, merge_hash_trees, AsHashTree, HashTree, NestedTree};
-use ic_representation_independent_hash::Sha256Digest;
-use std::fmt::{Debug, Formatter};
+use ic_certification::{
+    fork, fork_hash, labeled, labeled_hash, merge_hash_trees, pruned, AsHashTree, HashTree,
+    NestedTree, RbTree,
+};
+use ic_representation_independent_hash::{hash, Sha256Digest};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    fmt::{Debug, Formatter},
+    mem,
+};
 
 type CertificationTree = NestedTree<CertificationTreePathSegment, Vec<u8>>;
 
+/// A tree of request paths to response body hashes, as used by response verification version 1.
+type V1CertificationTree = RbTree<Vec<u8>, Sha256Digest>;
+
+const V1_PATH_PREFIX_BYTES: &[u8] = b"http_assets";
+
 /// A certification tree for generic HTTP requests.
-#[derive(Clone)]
+///
+/// The tree implements [Serialize] and [Deserialize], so it can be written to stable memory
+/// in a canister's `pre_upgrade` hook and restored in its `post_upgrade` hook, instead of
+/// certifying every response again. The structure of the tree, which depends on the order in
+/// which entries were inserted and deleted, is serialized along with its entries, so the root
+/// hash of the restored tree is equal to the root hash of the original tree and the canister's
+/// certified variable does not need to be updated after the tree is restored.
+///
+/// ```
+/// use ic_http_certification::{
+///     HttpCertification, HttpCertificationPath, HttpCertificationTree,
+///     HttpCertificationTreeEntry,
+/// };
+///
+/// let mut tree = HttpCertificationTree::default();
+/// let entry = HttpCertificationTreeEntry::new(
+///     HttpCertificationPath::exact("/"),
+///     HttpCertification::skip(),
+/// );
+/// tree.insert(&entry);
+///
+/// // in `pre_upgrade`
+/// let bytes = serde_cbor::to_vec(&tree).unwrap();
+///
+/// // in `post_upgrade`
+/// let restored_tree: HttpCertificationTree = serde_cbor::from_slice(&bytes).unwrap();
+///
+/// assert_eq!(restored_tree.root_hash(), tree.root_hash());
+/// assert!(restored_tree.witness(&entry, "/").is_ok());
+/// ```
+///
+/// A tree created with [with_v1_certification](HttpCertificationTree::with_v1_certification)
+/// additionally maintains the request path to response body hash tree that is used by
+/// response verification version 1, so that a canister can serve HTTP Gateways that only
+/// support version 1 during a transition period.
 pub struct HttpCertificationTree {
     tree: CertificationTree,
+    v1_tree: Option<V1CertificationTree>,
+    root_hash_generation: u64,
+    root_hash_change_hook: Option<RootHashChangeHook>,
+}
+
+/// The hook that is configured with
+/// [on_root_hash_change](HttpCertificationTree::on_root_hash_change).
+struct RootHashChangeHook(Box<RootHashChangeHookFn>);
+
+type RootHashChangeHookFn = dyn FnMut(&Sha256Digest) + Send + Sync;
+
+/// Statistics about the size of an [HttpCertificationTree], see
+/// [stats](HttpCertificationTree::stats).
+///
+/// Comparing the statistics over time can be used to monitor the growth of the tree, and
+/// to detect certifications of responses that were updated without deleting the certification
+/// of the previous response.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HttpCertificationTreeStats {
+    /// The number of [HttpCertificationTreeEntry]s in the tree.
+    pub entry_count: usize,
+
+    /// The number of nodes in the tree, i.e. the number of path segments, expression hashes,
+    /// request hashes and response hashes that are stored in the tree.
+    pub node_count: usize,
+
+    /// The approximate number of bytes that the tree occupies on the heap.
+    pub approximate_size: usize,
+}
+
+/// The approximate size of a node in the tree, apart from the segment that it stores: the segment
+/// itself, its subtree, the references to its children, its hash and its color.
+const APPROXIMATE_NODE_SIZE: usize = mem::size_of::<CertificationTreePathSegment>()
+    + mem::size_of::<CertificationTree>()
+    + 2 * mem::size_of::<Option<Box<CertificationTree>>>()
+    + mem::size_of::<Sha256Digest>()
+    + mem::size_of::<usize>();
+
+/// The serialized representation of an [HttpCertificationTree]. The trees are serialized with
+/// their structure, so that the root hash of the deserialized tree is equal to the root hash of
+/// the serialized tree.
+#[derive(Serialize, Deserialize)]
+struct SerializedHttpCertificationTree<T, V1> {
+    tree: T,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    v1_tree: Option<V1>,
+}
+
+impl Serialize for HttpCertificationTree {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        SerializedHttpCertificationTree {
+            tree: &self.tree,
+            v1_tree: self.v1_tree.as_ref(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for HttpCertificationTree {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let SerializedHttpCertificationTree { tree, v1_tree } =
+            SerializedHttpCertificationTree::<CertificationTree, V1CertificationTree>::deserialize(
+                deserializer,
+            )?;
+
+        Ok(Self {
+            tree,
+            v1_tree,
+            root_hash_generation: 0,
+            root_hash_change_hook: None,
+        })
+    }
+}
+
+impl Clone for HttpCertificationTree {
+    /// Clones the entries of the tree and its
+    /// [root hash generation](HttpCertificationTree::root_hash_generation). The
+    /// [root hash change hook](HttpCertificationTree::on_root_hash_change) is not cloned, so
+    /// changes to the clone do not overwrite the canister's certified variable.
+    fn clone(&self) -> Self {
+        Self {
+            tree: self.tree.clone(),
+            v1_tree: self.v1_tree.clone(),
+            root_hash_generation: self.root_hash_generation,
+            root_hash_change_hook: None,
+        }
+    }
 }
 
 impl Debug for HttpCertificationTree {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "tree: {:#?}", self.tree)
+        write!(f, "tree: {:#?}", self.tree)?;
+        if let Some(v1_tree) = &self.v1_tree {
+            write!(f, ", v1_tree: {:#?}", v1_tree)?;
+        }
+
+        Ok(())
     }
 }
 
@@ -35,29 +181,270 @@ impl HttpCertificationTree {
     /// Creates a new empty [HttpCertificationTree] from a given [CertificationTree](ic_certification::NestedTree).
     /// The [default](HttpCertificationTree::default) implementation should be used in most cases.
     pub fn new(tree: CertificationTree) -> Self {
-        Self { tree }
+        Self {
+            tree,
+            v1_tree: None,
+            root_hash_generation: 0,
+            root_hash_change_hook: None,
+        }
+    }
+
+    /// Creates a new empty [HttpCertificationTree] that also certifies responses for response
+    /// verification version 1, using [insert_v1](HttpCertificationTree::insert_v1) and
+    /// [witness_v1](HttpCertificationTree::witness_v1).
+    ///
+    /// The [root hash](HttpCertificationTree::root_hash) of such a tree covers both versions, and
+    /// [witnesses](HttpCertificationTree::witness) for version 2 prune the version 1 tree and
+    /// vice versa, so a single certified variable can be used to serve both versions.
+    pub fn with_v1_certification() -> Self {
+        Self {
+            tree: CertificationTree::default(),
+            v1_tree: Some(V1CertificationTree::new()),
+            root_hash_generation: 0,
+            root_hash_change_hook: None,
+        }
     }
 
     /// Returns the root hash of the tree.
     /// This hash can be used as the canister's certified variable.
     pub fn root_hash(&self) -> Sha256Digest {
+        let root_hash = self.v2_root_hash();
+
+        match &self.v1_tree {
+            Some(v1_tree) => fork_hash(&Self::v1_root_hash(v1_tree), &root_hash),
+            None => root_hash,
+        }
+    }
+
+    /// Returns a counter that is incremented every time the [root hash](HttpCertificationTree::root_hash)
+    /// of the tree changes. The counter starts at `0` when the tree is created or deserialized.
+    ///
+    /// Wrappers around the tree can compare the counter before and after handing out mutable
+    /// access to the tree to detect whether the canister's certified variable needs to be updated,
+    /// without comparing root hashes themselves. To be notified of every change instead, use
+    /// [on_root_hash_change](HttpCertificationTree::on_root_hash_change).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{HttpCertification, HttpCertificationPath, HttpCertificationTree, HttpCertificationTreeEntry};
+    ///
+    /// let mut http_certification_tree = HttpCertificationTree::default();
+    /// let generation = http_certification_tree.root_hash_generation();
+    ///
+    /// let entry = HttpCertificationTreeEntry::new(HttpCertificationPath::exact("/index.html"), HttpCertification::skip());
+    /// http_certification_tree.insert(&entry);
+    /// assert_ne!(http_certification_tree.root_hash_generation(), generation);
+    ///
+    /// // inserting the same entry again does not change the root hash
+    /// let generation = http_certification_tree.root_hash_generation();
+    /// http_certification_tree.insert(&entry);
+    /// assert_eq!(http_certification_tree.root_hash_generation(), generation);
+    /// ```
+    pub fn root_hash_generation(&self) -> u64 {
+        self.root_hash_generation
+    }
+
+    /// Configures the tree to run the provided hook with the new root hash whenever a mutation
+    /// changes it, e.g. to set the canister's certified data (`ic_cdk::api::set_certified_data()`)
+    /// automatically instead of after every mutation.
+    ///
+    /// The hook runs once at the end of every mutation of the tree that changes the
+    /// [root hash](HttpCertificationTree::root_hash), such as
+    /// [insert](HttpCertificationTree::insert), [delete](HttpCertificationTree::delete) or
+    /// [clear](HttpCertificationTree::clear), and does not run if the root hash is unchanged.
+    /// The hook is neither serialized nor cloned, so it needs to be configured again on a tree
+    /// that is restored after an upgrade.
+    ///
+    /// Since wrappers such as an asset router mutate the tree several times per operation, they
+    /// run the hook several times as well. Use the hook of the wrapper instead, if it has one, to
+    /// run it only once per operation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{HttpCertification, HttpCertificationPath, HttpCertificationTree, HttpCertificationTreeEntry};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let certified_data = Arc::new(Mutex::new(None));
+    /// let mut http_certification_tree = HttpCertificationTree::default().on_root_hash_change({
+    ///     let certified_data = certified_data.clone();
+    ///     move |root_hash| {
+    ///         // ic_cdk::api::set_certified_data(root_hash);
+    ///         *certified_data.lock().unwrap() = Some(*root_hash);
+    ///     }
+    /// });
+    ///
+    /// let entry = HttpCertificationTreeEntry::new(HttpCertificationPath::exact("/index.html"), HttpCertification::skip());
+    /// http_certification_tree.insert(&entry);
+    ///
+    /// assert_eq!(*certified_data.lock().unwrap(), Some(http_certification_tree.root_hash()));
+    /// ```
+    pub fn on_root_hash_change(
+        mut self,
+        hook: impl FnMut(&Sha256Digest) + Send + Sync + 'static,
+    ) -> Self {
+        self.root_hash_change_hook = Some(RootHashChangeHook(Box::new(hook)));
+        self
+    }
+
+    /// Runs the given mutation of the tree, incrementing the
+    /// [root hash generation](HttpCertificationTree::root_hash_generation) and running the
+    /// [root hash change hook](HttpCertificationTree::on_root_hash_change) if it changed the
+    /// root hash.
+    fn track_root_hash<R>(&mut self, mutation: impl FnOnce(&mut Self) -> R) -> R {
+        let previous_root_hash = self.root_hash();
+        let result = mutation(self);
+
+        let root_hash = self.root_hash();
+        if root_hash != previous_root_hash {
+            self.root_hash_generation = self.root_hash_generation.wrapping_add(1);
+            if let Some(RootHashChangeHook(hook)) = &mut self.root_hash_change_hook {
+                hook(&root_hash);
+            }
+        }
+
+        result
+    }
+
+    /// Certifies the given response body for the given request path for response verification
+    /// version 1, replacing any body that was previously certified for the path.
+    /// After performing this operation, the canister's certified variable will need to be updated
+    /// with the new [root hash](HttpCertificationTree::root_hash) of the tree.
+    ///
+    /// Returns [HttpCertificationError::V1CertificationNotEnabled] if the tree was not created with
+    /// [with_v1_certification](HttpCertificationTree::with_v1_certification).
+    pub fn insert_v1(&mut self, request_path: &str, body: &[u8]) -> HttpCertificationResult {
+        self.track_root_hash(|http_tree| {
+            let v1_tree = http_tree
+                .v1_tree
+                .as_mut()
+                .ok_or(HttpCertificationError::V1CertificationNotEnabled)?;
+            v1_tree.insert(request_path.as_bytes().to_vec(), hash(body));
+
+            Ok(())
+        })
+    }
+
+    /// Deletes the response verification version 1 certification for the given request path.
+    /// After performing this operation, the canister's certified variable will need to be updated
+    /// with the new [root hash](HttpCertificationTree::root_hash) of the tree.
+    ///
+    /// Returns [HttpCertificationError::V1CertificationNotEnabled] if the tree was not created with
+    /// [with_v1_certification](HttpCertificationTree::with_v1_certification).
+    pub fn delete_v1(&mut self, request_path: &str) -> HttpCertificationResult {
+        self.track_root_hash(|http_tree| {
+            let v1_tree = http_tree
+                .v1_tree
+                .as_mut()
+                .ok_or(HttpCertificationError::V1CertificationNotEnabled)?;
+            v1_tree.delete(request_path.as_bytes());
+
+            Ok(())
+        })
+    }
+
+    /// Returns a pruned [HashTree] that will prove the response body hash certified for the given
+    /// request path with [insert_v1](HttpCertificationTree::insert_v1), for use with
+    /// [add_v1_certificate_header](crate::utils::add_v1_certificate_header).
+    ///
+    /// Returns [HttpCertificationError::V1CertificationNotEnabled] if the tree was not created with
+    /// [with_v1_certification](HttpCertificationTree::with_v1_certification), or
+    /// [HttpCertificationError::WitnessEntryNotFound] if no body is certified for the request path.
+    pub fn witness_v1(&self, request_path: &str) -> HttpCertificationResult<HashTree> {
+        let v1_tree = self
+            .v1_tree
+            .as_ref()
+            .ok_or(HttpCertificationError::V1CertificationNotEnabled)?;
+        if v1_tree.get(request_path.as_bytes()).is_none() {
+            return Err(HttpCertificationError::WitnessEntryNotFound {
+                request_path: request_path.to_string(),
+            });
+        }
+
+        Ok(fork(
+            labeled(
+                V1_PATH_PREFIX_BYTES,
+                v1_tree.witness(request_path.as_bytes()),
+            ),
+            pruned(self.v2_root_hash()),
+        ))
+    }
+
+    fn v2_root_hash(&self) -> Sha256Digest {
         labeled_hash(PATH_PREFIX_BYTES, &self.tree.root_hash())
     }
 
+    fn v1_root_hash(v1_tree: &V1CertificationTree) -> Sha256Digest {
+        labeled_hash(V1_PATH_PREFIX_BYTES, &v1_tree.root_hash())
+    }
+
     /// Inserts a given [HttpCertificationTreeEntry] into the tree.
     /// After performing this operation, the canister's certified variable will need to be updated
     /// with the new [root hash](HttpCertificationTree::root_hash) of the tree.
     pub fn insert(&mut self, entry: &HttpCertificationTreeEntry) {
+        #[cfg(feature = "instrumentation")]
+        let _measurement = Measurement::start(Operation::TreeInsert);
         let tree_path = entry.to_tree_path();
-        self.tree.insert(&tree_path, vec![]);
+        self.track_root_hash(|http_tree| http_tree.tree.insert(&tree_path, vec![]));
     }
 
     /// Deletes a given [HttpCertificationTreeEntry] from the tree.
     /// After performing this operation, the canister's certified variable will need to be updated
     /// with the new [root hash](HttpCertificationTree::root_hash) of the tree.
     pub fn delete(&mut self, entry: &HttpCertificationTreeEntry) {
+        #[cfg(feature = "instrumentation")]
+        let _measurement = Measurement::start(Operation::TreeDelete);
         let tree_path = entry.to_tree_path();
-        self.tree.delete(&tree_path);
+        self.track_root_hash(|http_tree| http_tree.tree.delete(&tree_path));
+    }
+
+    /// Inserts all given [HttpCertificationTreeEntry]s into the tree.
+    /// This results in the same tree as [inserting](HttpCertificationTree::insert) them one by one,
+    /// but entries that share a path are inserted together, so the hashes of the tree are
+    /// recomputed fewer times. This is useful when certifying a large number of responses at once,
+    /// such as in a canister's `init` function.
+    /// After performing this operation, the canister's certified variable will need to be updated
+    /// with the new [root hash](HttpCertificationTree::root_hash) of the tree.
+    pub fn insert_many<'a, 'b>(
+        &mut self,
+        entries: impl IntoIterator<Item = &'a HttpCertificationTreeEntry<'b>>,
+    ) {
+        #[cfg(feature = "instrumentation")]
+        let _measurement = Measurement::start(Operation::TreeInsert);
+        let tree_paths: Vec<_> = entries
+            .into_iter()
+            .map(HttpCertificationTreeEntry::to_tree_path)
+            .collect();
+        self.track_root_hash(|http_tree| {
+            http_tree.tree.insert_many(
+                tree_paths
+                    .iter()
+                    .map(|tree_path| (tree_path.as_slice(), vec![])),
+            )
+        });
+    }
+
+    /// Deletes all given [HttpCertificationTreeEntry]s from the tree.
+    /// Similarly to [insert_many](HttpCertificationTree::insert_many), entries that share a path
+    /// are deleted together, so the hashes of the tree are recomputed fewer times.
+    /// After performing this operation, the canister's certified variable will need to be updated
+    /// with the new [root hash](HttpCertificationTree::root_hash) of the tree.
+    pub fn delete_many<'a, 'b>(
+        &mut self,
+        entries: impl IntoIterator<Item = &'a HttpCertificationTreeEntry<'b>>,
+    ) {
+        #[cfg(feature = "instrumentation")]
+        let _measurement = Measurement::start(Operation::TreeDelete);
+        let tree_paths: Vec<_> = entries
+            .into_iter()
+            .map(HttpCertificationTreeEntry::to_tree_path)
+            .collect();
+        self.track_root_hash(|http_tree| {
+            http_tree
+                .tree
+                .delete_many(tree_paths.iter().map(|tree_path| tree_path.as_slice()))
+        });
     }
 
     /// Deletes all [HttpCertificationTreeEntry]s that match a given [HttpCertificationPath].
@@ -65,25 +452,167 @@ impl HttpCertificationTree {
     /// with the new [root hash](HttpCertificationTree::root_hash) of the tree.
     pub fn delete_by_path(&mut self, path: &HttpCertificationPath) {
         let tree_path = path.to_tree_path();
-        self.tree.delete(&tree_path);
+        self.track_root_hash(|http_tree| http_tree.tree.delete(&tree_path));
     }
 
-    /// Clears the tree of all [HttpCertificationTreeEntry].
+    /// Clears the tree of all [HttpCertificationTreeEntry], resetting its
+    /// [root hash](HttpCertificationTree::root_hash) to the root hash of an empty tree.
+    /// This allows all certifications to be replaced without keeping track of the previously
+    /// inserted entries.
     /// After performing this operation, the canister's certified variable will need to be updated
     /// with the new [root hash](HttpCertificationTree::root_hash) of the tree.
     pub fn clear(&mut self) {
-        self.tree.clear();
+        self.track_root_hash(|http_tree| {
+            http_tree.tree.clear();
+            if let Some(v1_tree) = &mut http_tree.v1_tree {
+                *v1_tree = V1CertificationTree::new();
+            }
+        });
+    }
+
+    /// Removes all subtrees of the tree that do not contain any [HttpCertificationTreeEntry],
+    /// returning the number of subtrees that were removed.
+    ///
+    /// [delete](HttpCertificationTree::delete) and the other delete methods already remove the
+    /// subtrees that they leave empty, so this is only needed for a tree that was created with
+    /// [new](HttpCertificationTree::new) from a [CertificationTree](ic_certification::NestedTree)
+    /// that was built by other means. Empty subtrees are included in witnesses, so removing them
+    /// keeps witnesses small.
+    /// After performing this operation, if any subtrees were removed, the canister's certified
+    /// variable will need to be updated with the new [root hash](HttpCertificationTree::root_hash)
+    /// of the tree.
+    pub fn prune(&mut self) -> usize {
+        self.track_root_hash(|http_tree| http_tree.tree.prune())
+    }
+
+    /// Returns [statistics](HttpCertificationTreeStats) about the size of the tree.
+    ///
+    /// This traverses the whole tree, so it should not be called in every request.
+    pub fn stats(&self) -> HttpCertificationTreeStats {
+        fn collect_stats(tree: &CertificationTree, stats: &mut HttpCertificationTreeStats) {
+            match tree {
+                NestedTree::Leaf(value) => {
+                    stats.entry_count += 1;
+                    stats.approximate_size += value.len();
+                }
+                NestedTree::Nested(children) => {
+                    for (segment, child) in children.iter() {
+                        stats.node_count += 1;
+                        stats.approximate_size += APPROXIMATE_NODE_SIZE + segment.len();
+                        collect_stats(child, stats);
+                    }
+                }
+            }
+        }
+
+        let mut stats = HttpCertificationTreeStats::default();
+        collect_stats(&self.tree, &mut stats);
+
+        stats
+    }
+
+    /// Renders the tree in the [Graphviz DOT](https://graphviz.org/doc/info/lang.html) language,
+    /// for debugging which expression paths are certified in the tree.
+    ///
+    /// Every node is labeled with its path segment, up to and including the `<$>` segment of an
+    /// exact path or the `<*>` segment of a wildcard path. The nodes below these segments are
+    /// labeled with the hex encoded CEL expression hash, request hash and response hash of the
+    /// certifications for that path. The request hash of a response only certification is empty.
+    ///
+    /// This traverses the whole tree, so it should not be called in every request.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_http_certification::{HttpCertification, HttpCertificationPath, HttpCertificationTree, HttpCertificationTreeEntry};
+    ///
+    /// let mut http_certification_tree = HttpCertificationTree::default();
+    /// http_certification_tree.insert(&HttpCertificationTreeEntry::new(
+    ///     HttpCertificationPath::wildcard("/assets"),
+    ///     HttpCertification::skip(),
+    /// ));
+    ///
+    /// let dot = http_certification_tree.to_dot();
+    ///
+    /// assert!(dot.starts_with("digraph http_certification_tree {"));
+    /// assert!(dot.contains(r#"[label="<*>"]"#));
+    /// ```
+    pub fn to_dot(&self) -> String {
+        fn write_nodes(
+            tree: &CertificationTree,
+            parent_id: usize,
+            hash_depth: Option<usize>,
+            next_id: &mut usize,
+            dot: &mut String,
+        ) {
+            let NestedTree::Nested(children) = tree else {
+                return;
+            };
+
+            for (segment, child) in children.iter() {
+                let id = *next_id;
+                *next_id += 1;
+
+                let (label, child_hash_depth) = match hash_depth {
+                    None => {
+                        let label = String::from_utf8_lossy(segment).into_owned();
+                        let is_terminator = label == "<$>" || label == "<*>";
+
+                        (label, is_terminator.then_some(0))
+                    }
+                    Some(depth) => {
+                        let hash_name = match depth {
+                            0 => "cel",
+                            1 => "request",
+                            _ => "response",
+                        };
+                        let hex_hash: String =
+                            segment.iter().map(|byte| format!("{byte:02x}")).collect();
+
+                        (format!("{hash_name}: {hex_hash}"), Some(depth + 1))
+                    }
+                };
+                let label = label.replace('\\', "\\\\").replace('"', "\\\"");
+
+                dot.push_str(&format!("    n{id} [label=\"{label}\"];\n"));
+                dot.push_str(&format!("    n{parent_id} -> n{id};\n"));
+
+                write_nodes(child, id, child_hash_depth, next_id, dot);
+            }
+        }
+
+        let mut dot = String::from("digraph http_certification_tree {\n");
+        dot.push_str(&format!(
+            "    n0 [label=\"{}\"];\n",
+            String::from_utf8_lossy(PATH_PREFIX_BYTES)
+        ));
+        write_nodes(&self.tree, 0, None, &mut 1, &mut dot);
+        dot.push_str("}\n");
+
+        dot
     }
 
     /// Returns a pruned [HashTree] that will prove the presence of a given [HttpCertificationTreeEntry]
     /// in the full [HttpCertificationTree], without needing to return the full tree.
     ///
     /// `request_url` is required so that the witness can be generated with respect to the request URL.
+    ///
+    /// Returns [HttpCertificationError::WitnessEntryNotFound] if the entry has not been inserted into
+    /// the tree, so callers can respond as if the requested resource does not exist. Any other error
+    /// means that a witness cannot be generated for the entry with respect to the request URL.
     pub fn witness(
         &self,
         entry: &HttpCertificationTreeEntry,
         request_url: &str,
     ) -> HttpCertificationResult<HashTree> {
+        #[cfg(feature = "instrumentation")]
+        let _measurement = Measurement::start(Operation::TreeWitness);
+        if !self.tree.contains_leaf(&entry.to_tree_path()) {
+            return Err(HttpCertificationError::WitnessEntryNotFound {
+                request_path: request_url.to_string(),
+            });
+        }
+
         let witness = match entry.path.get_type() {
             HttpCertificationPathType::Exact(_) => self.tree.witness(&entry.to_tree_path()),
 
@@ -115,7 +644,12 @@ impl HttpCertificationTree {
             }
         };
 
-        Ok(labeled(PATH_PREFIX_BYTES, witness))
+        let witness = labeled(PATH_PREFIX_BYTES, witness);
+
+        match &self.v1_tree {
+            Some(v1_tree) => Ok(fork(pruned(Self::v1_root_hash(v1_tree)), witness)),
+            None => Ok(witness),
+        }
     }
 }
 
@@ -127,8 +661,9 @@ mod tests {
         HttpResponse, StatusCode, CERTIFICATE_EXPRESSION_HEADER_NAME,
     };
     use assert_matches::assert_matches;
-    use ic_certification::SubtreeLookupResult;
+    use ic_certification::{LookupResult, SubtreeLookupResult};
     use rstest::*;
+    use std::sync::{Arc, Mutex};
 
     #[rstest]
     fn test_witness() {
@@ -217,6 +752,373 @@ mod tests {
         );
     }
 
+    #[rstest]
+    fn test_serialize_deserialize() {
+        let mut tree = HttpCertificationTree::default();
+        let cel_expr = DefaultCelBuilder::full_certification()
+            .with_response_certification(DefaultResponseCertification::response_header_exclusions(
+                vec![],
+            ))
+            .build();
+        let response = HttpResponse::ok(
+            br#"console.log("Hello, World!")"#,
+            vec![(
+                CERTIFICATE_EXPRESSION_HEADER_NAME.into(),
+                cel_expr.to_string(),
+            )],
+        )
+        .build();
+
+        let entries = [
+            "/",
+            "/index.html",
+            "/assets/js/hello.js",
+            "/assets/js/world.js",
+        ]
+        .into_iter()
+        .map(|url| {
+            let request = HttpRequest::get(url).build();
+
+            HttpCertificationTreeEntry::new(
+                HttpCertificationPath::exact(url),
+                HttpCertification::full(&cel_expr, &request, &response, None).unwrap(),
+            )
+        })
+        .chain([HttpCertificationTreeEntry::new(
+            HttpCertificationPath::wildcard("/assets"),
+            HttpCertification::skip(),
+        )])
+        .collect::<Vec<_>>();
+        for entry in entries.iter() {
+            tree.insert(entry);
+        }
+        tree.delete(&entries[1]);
+
+        let bytes = serde_cbor::to_vec(&tree).unwrap();
+        let restored_tree: HttpCertificationTree = serde_cbor::from_slice(&bytes).unwrap();
+
+        assert_eq!(restored_tree.root_hash(), tree.root_hash());
+        assert!(!restored_tree.tree.contains_leaf(&entries[1].to_tree_path()));
+        for entry in entries.iter().filter(|entry| **entry != entries[1]) {
+            assert!(restored_tree.tree.contains_leaf(&entry.to_tree_path()));
+        }
+
+        let empty_tree: HttpCertificationTree =
+            serde_cbor::from_slice(&serde_cbor::to_vec(&HttpCertificationTree::default()).unwrap())
+                .unwrap();
+        assert_eq!(
+            empty_tree.root_hash(),
+            HttpCertificationTree::default().root_hash()
+        );
+    }
+
+    #[rstest]
+    fn test_serialize_deserialize_keeps_root_hash() {
+        let mut tree = HttpCertificationTree::with_v1_certification();
+        let paths: Vec<String> = (0..50).rev().map(|i| format!("/assets/{i}.js")).collect();
+        for path in &paths {
+            tree.insert(&HttpCertificationTreeEntry::new(
+                HttpCertificationPath::exact(path),
+                HttpCertification::skip(),
+            ));
+            tree.insert_v1(path, path.as_bytes()).unwrap();
+        }
+        tree.insert(&HttpCertificationTreeEntry::new(
+            HttpCertificationPath::wildcard("/assets"),
+            HttpCertification::skip(),
+        ));
+        for path in paths.iter().step_by(3) {
+            tree.delete(&HttpCertificationTreeEntry::new(
+                HttpCertificationPath::exact(path),
+                HttpCertification::skip(),
+            ));
+            tree.delete_v1(path).unwrap();
+        }
+
+        let bytes = serde_cbor::to_vec(&tree).unwrap();
+        let mut restored_tree: HttpCertificationTree = serde_cbor::from_slice(&bytes).unwrap();
+        assert_eq!(restored_tree.root_hash(), tree.root_hash());
+
+        // the restored tree keeps changing in the same way as the original tree
+        for t in [&mut tree, &mut restored_tree] {
+            t.insert(&HttpCertificationTreeEntry::new(
+                HttpCertificationPath::exact("/index.html"),
+                HttpCertification::skip(),
+            ));
+            t.delete_v1(&paths[1]).unwrap();
+        }
+        assert_eq!(restored_tree.root_hash(), tree.root_hash());
+    }
+
+    #[rstest]
+    fn test_stats() {
+        let mut tree = HttpCertificationTree::default();
+        assert_eq!(tree.stats(), HttpCertificationTreeStats::default());
+
+        let entries = [
+            HttpCertificationTreeEntry::new(
+                HttpCertificationPath::exact("/assets/js/hello.js"),
+                HttpCertification::skip(),
+            ),
+            HttpCertificationTreeEntry::new(
+                HttpCertificationPath::exact("/assets/js/world.js"),
+                HttpCertification::skip(),
+            ),
+            HttpCertificationTreeEntry::new(
+                HttpCertificationPath::wildcard("/assets"),
+                HttpCertification::skip(),
+            ),
+        ];
+        for entry in entries.iter() {
+            tree.insert(entry);
+        }
+
+        let nodes = entries
+            .iter()
+            .flat_map(|entry| {
+                let tree_path = entry.to_tree_path();
+                (1..=tree_path.len())
+                    .map(|len| tree_path[..len].to_vec())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<std::collections::HashSet<_>>();
+        let segments_size = nodes
+            .iter()
+            .map(|node| node.last().unwrap().len())
+            .sum::<usize>();
+
+        let stats = tree.stats();
+        assert_eq!(stats.entry_count, entries.len());
+        assert_eq!(stats.node_count, nodes.len());
+        assert_eq!(
+            stats.approximate_size,
+            nodes.len() * APPROXIMATE_NODE_SIZE + segments_size
+        );
+
+        // a certification that is not deleted when its response is updated remains in the tree
+        let cel_expr = DefaultCelBuilder::full_certification()
+            .with_response_certification(DefaultResponseCertification::response_header_exclusions(
+                vec![],
+            ))
+            .build();
+        let request = HttpRequest::get("/assets/js/hello.js").build();
+        let response = HttpResponse::ok(
+            br#"console.log("Hello")"#,
+            vec![(
+                CERTIFICATE_EXPRESSION_HEADER_NAME.into(),
+                cel_expr.to_string(),
+            )],
+        )
+        .build();
+        tree.insert(&HttpCertificationTreeEntry::new(
+            HttpCertificationPath::exact(request.url()),
+            HttpCertification::full(&cel_expr, &request, &response, None).unwrap(),
+        ));
+        assert_eq!(tree.stats().entry_count, entries.len() + 1);
+
+        tree.clear();
+        assert_eq!(tree.stats(), HttpCertificationTreeStats::default());
+    }
+
+    #[rstest]
+    fn test_to_dot() {
+        let mut tree = HttpCertificationTree::default();
+        assert_eq!(
+            tree.to_dot(),
+            "digraph http_certification_tree {\n    n0 [label=\"http_expr\"];\n}\n"
+        );
+
+        let cel_expr = DefaultCelBuilder::response_only_certification()
+            .with_response_certification(DefaultResponseCertification::response_header_exclusions(
+                vec![],
+            ))
+            .build();
+        let response = HttpResponse::ok(
+            b"Hello, World!",
+            vec![(
+                CERTIFICATE_EXPRESSION_HEADER_NAME.into(),
+                cel_expr.to_string(),
+            )],
+        )
+        .build();
+        let certification = HttpCertification::response_only(&cel_expr, &response, None).unwrap();
+        tree.insert(&HttpCertificationTreeEntry::new(
+            HttpCertificationPath::exact("/hello"),
+            certification,
+        ));
+
+        let cel_expr_hash: String = certification
+            .cel_expr_hash()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        let dot = tree.to_dot();
+        let lines: Vec<&str> = dot.lines().collect();
+
+        assert_eq!(lines.len(), 13);
+        assert_eq!(lines[2], r#"    n1 [label="hello"];"#);
+        assert_eq!(lines[3], "    n0 -> n1;");
+        assert_eq!(lines[4], r#"    n2 [label="<$>"];"#);
+        assert_eq!(
+            lines[6],
+            format!(r#"    n3 [label="cel: {cel_expr_hash}"];"#)
+        );
+        assert_eq!(lines[8], r#"    n4 [label="request: "];"#);
+        assert!(lines[10].starts_with(r#"    n5 [label="response: "#));
+        assert_eq!(lines[11], "    n4 -> n5;");
+    }
+
+    #[rstest]
+    fn test_clear() {
+        let mut tree = HttpCertificationTree::default();
+        let empty_root_hash = tree.root_hash();
+
+        let entries = [
+            HttpCertificationTreeEntry::new(
+                HttpCertificationPath::exact("/index.html"),
+                HttpCertification::skip(),
+            ),
+            HttpCertificationTreeEntry::new(
+                HttpCertificationPath::wildcard("/assets"),
+                HttpCertification::skip(),
+            ),
+        ];
+        for entry in entries.iter() {
+            tree.insert(entry);
+        }
+        assert_ne!(tree.root_hash(), empty_root_hash);
+
+        tree.clear();
+
+        assert_eq!(tree.root_hash(), empty_root_hash);
+        for entry in entries.iter() {
+            assert!(!tree.tree.contains_path(&entry.to_tree_path()));
+        }
+
+        // the tree can be used again after it is cleared
+        tree.insert(&entries[0]);
+        assert!(tree.tree.contains_leaf(&entries[0].to_tree_path()));
+    }
+
+    #[rstest]
+    fn test_v1_certification() {
+        let mut tree = HttpCertificationTree::with_v1_certification();
+        let entry = HttpCertificationTreeEntry::new(
+            HttpCertificationPath::exact("/index.html"),
+            HttpCertification::skip(),
+        );
+        let body = b"<html></html>";
+        tree.insert(&entry);
+        tree.insert_v1("/index.html", body).unwrap();
+
+        let witness = tree.witness(&entry, "/index.html").unwrap();
+        assert_eq!(witness.digest(), tree.root_hash());
+        assert_matches!(
+            witness.lookup_subtree(["http_expr", "index.html", "<$>"]),
+            SubtreeLookupResult::Found(_)
+        );
+
+        let v1_witness = tree.witness_v1("/index.html").unwrap();
+        assert_eq!(v1_witness.digest(), tree.root_hash());
+        assert_eq!(
+            v1_witness.lookup_path(["http_assets".as_bytes(), "/index.html".as_bytes()]),
+            LookupResult::Found(hash(body).as_slice())
+        );
+
+        let bytes = serde_cbor::to_vec(&tree).unwrap();
+        let restored_tree: HttpCertificationTree = serde_cbor::from_slice(&bytes).unwrap();
+        assert_eq!(
+            restored_tree
+                .witness_v1("/index.html")
+                .unwrap()
+                .lookup_path(["http_assets".as_bytes(), "/index.html".as_bytes()]),
+            LookupResult::Found(hash(body).as_slice())
+        );
+
+        tree.delete_v1("/index.html").unwrap();
+        assert_matches!(
+            tree.witness_v1("/index.html"),
+            Err(HttpCertificationError::WitnessEntryNotFound { .. })
+        );
+
+        tree.clear();
+        assert_eq!(
+            tree.root_hash(),
+            HttpCertificationTree::with_v1_certification().root_hash()
+        );
+        assert_ne!(
+            tree.root_hash(),
+            HttpCertificationTree::default().root_hash()
+        );
+    }
+
+    #[rstest]
+    fn test_v1_certification_not_enabled() {
+        let mut tree = HttpCertificationTree::default();
+
+        assert_matches!(
+            tree.insert_v1("/index.html", b"<html></html>"),
+            Err(HttpCertificationError::V1CertificationNotEnabled)
+        );
+        assert_matches!(
+            tree.delete_v1("/index.html"),
+            Err(HttpCertificationError::V1CertificationNotEnabled)
+        );
+        assert_matches!(
+            tree.witness_v1("/index.html"),
+            Err(HttpCertificationError::V1CertificationNotEnabled)
+        );
+    }
+
+    #[rstest]
+    fn test_insert_many_delete_many() {
+        let entries = [
+            HttpCertificationTreeEntry::new(
+                HttpCertificationPath::exact("/index.html"),
+                HttpCertification::skip(),
+            ),
+            HttpCertificationTreeEntry::new(
+                HttpCertificationPath::wildcard("/assets"),
+                HttpCertification::skip(),
+            ),
+            HttpCertificationTreeEntry::new(
+                HttpCertificationPath::exact("/assets/app.js"),
+                HttpCertification::skip(),
+            ),
+            HttpCertificationTreeEntry::new(
+                HttpCertificationPath::exact("/assets/app.css"),
+                HttpCertification::skip(),
+            ),
+        ];
+
+        let mut sequential_tree = HttpCertificationTree::default();
+        for entry in entries.iter() {
+            sequential_tree.insert(entry);
+        }
+
+        let mut tree = HttpCertificationTree::default();
+        tree.insert_many(entries.iter());
+
+        assert_eq!(tree.root_hash(), sequential_tree.root_hash());
+        for entry in entries.iter() {
+            assert!(tree.tree.contains_leaf(&entry.to_tree_path()));
+        }
+
+        tree.delete_many(&entries[1..3]);
+
+        assert!(tree.tree.contains_leaf(&entries[0].to_tree_path()));
+        assert!(!tree.tree.contains_path(&entries[1].to_tree_path()));
+        assert!(!tree.tree.contains_path(&entries[2].to_tree_path()));
+        assert!(tree.tree.contains_leaf(&entries[3].to_tree_path()));
+
+        tree.delete_many(entries.iter());
+
+        assert_eq!(
+            tree.root_hash(),
+            HttpCertificationTree::default().root_hash()
+        );
+    }
+
     #[rstest]
     fn test_delete_by_path() {
         let mut http_tree = HttpCertificationTree::default();
@@ -343,18 +1245,12 @@ mod tests {
         http_tree.delete_by_path(&HttpCertificationPath::exact(req_one.url()));
 
         assert_matches!(
-            http_tree
-                .witness(&req_one_entry, req_one_url)
-                .unwrap()
-                .lookup_subtree(&lookup_path_from_entry(&req_one_entry)),
-            SubtreeLookupResult::Absent
+            http_tree.witness(&req_one_entry, req_one_url),
+            Err(HttpCertificationError::WitnessEntryNotFound { .. })
         );
         assert_matches!(
-            http_tree
-                .witness(&req_one_alt_entry, req_one_url)
-                .unwrap()
-                .lookup_subtree(&lookup_path_from_entry(&req_one_alt_entry)),
-            SubtreeLookupResult::Absent
+            http_tree.witness(&req_one_alt_entry, req_one_url),
+            Err(HttpCertificationError::WitnessEntryNotFound { .. })
         );
 
         assert_matches!(
@@ -392,33 +1288,21 @@ mod tests {
         http_tree.delete_by_path(&HttpCertificationPath::exact(req_two.url()));
 
         assert_matches!(
-            http_tree
-                .witness(&req_one_entry, req_one_url)
-                .unwrap()
-                .lookup_subtree(&lookup_path_from_entry(&req_one_entry)),
-            SubtreeLookupResult::Absent
+            http_tree.witness(&req_one_entry, req_one_url),
+            Err(HttpCertificationError::WitnessEntryNotFound { .. })
         );
         assert_matches!(
-            http_tree
-                .witness(&req_one_alt_entry, req_one_url)
-                .unwrap()
-                .lookup_subtree(&lookup_path_from_entry(&req_one_alt_entry)),
-            SubtreeLookupResult::Absent
+            http_tree.witness(&req_one_alt_entry, req_one_url),
+            Err(HttpCertificationError::WitnessEntryNotFound { .. })
         );
 
         assert_matches!(
-            http_tree
-                .witness(&req_two_entry, req_two_url)
-                .unwrap()
-                .lookup_subtree(&lookup_path_from_entry(&req_two_entry)),
-            SubtreeLookupResult::Absent
+            http_tree.witness(&req_two_entry, req_two_url),
+            Err(HttpCertificationError::WitnessEntryNotFound { .. })
         );
         assert_matches!(
-            http_tree
-                .witness(&req_two_alt_entry, req_two_url)
-                .unwrap()
-                .lookup_subtree(&lookup_path_from_entry(&req_two_alt_entry)),
-            SubtreeLookupResult::Absent
+            http_tree.witness(&req_two_alt_entry, req_two_url),
+            Err(HttpCertificationError::WitnessEntryNotFound { .. })
         );
 
         assert!(!http_tree
@@ -438,6 +1322,98 @@ mod tests {
             .contains_path(&req_two_alt_entry.to_tree_path()));
     }
 
+    #[rstest]
+    fn test_root_hash_generation() {
+        let mut http_tree = HttpCertificationTree::with_v1_certification();
+        let path = HttpCertificationPath::exact("/index.html");
+        let entry = HttpCertificationTreeEntry::new(&path, HttpCertification::skip());
+
+        assert_eq!(http_tree.root_hash_generation(), 0);
+
+        http_tree.insert(&entry);
+        assert_eq!(http_tree.root_hash_generation(), 1);
+
+        http_tree
+            .insert_v1("/index.html", b"<html></html>")
+            .unwrap();
+        assert_eq!(http_tree.root_hash_generation(), 2);
+
+        // deleting an entry that is not in the tree does not change the root hash
+        http_tree.delete(&HttpCertificationTreeEntry::new(
+            HttpCertificationPath::exact("/app.js"),
+            HttpCertification::skip(),
+        ));
+        assert_eq!(http_tree.root_hash_generation(), 2);
+
+        http_tree.delete_by_path(&path);
+        assert_eq!(http_tree.root_hash_generation(), 3);
+
+        http_tree.clear();
+        assert_eq!(http_tree.root_hash_generation(), 4);
+
+        http_tree.clear();
+        assert_eq!(http_tree.root_hash_generation(), 4);
+    }
+
+    #[rstest]
+    fn test_root_hash_change_hook() {
+        let root_hashes = Arc::new(Mutex::new(vec![]));
+        let mut http_tree = HttpCertificationTree::default().on_root_hash_change({
+            let root_hashes = root_hashes.clone();
+            move |root_hash| root_hashes.lock().unwrap().push(*root_hash)
+        });
+        let entry = HttpCertificationTreeEntry::new(
+            HttpCertificationPath::exact("/index.html"),
+            HttpCertification::skip(),
+        );
+
+        http_tree.insert(&entry);
+        let inserted_root_hash = http_tree.root_hash();
+
+        // inserting the same entry again does not change the root hash
+        http_tree.insert(&entry);
+        assert_eq!(*root_hashes.lock().unwrap(), vec![inserted_root_hash]);
+
+        // mutations of a clone do not run the hook
+        let mut cloned_tree = http_tree.clone();
+        cloned_tree.delete(&entry);
+        assert_eq!(*root_hashes.lock().unwrap(), vec![inserted_root_hash]);
+
+        http_tree.delete(&entry);
+        assert_eq!(
+            *root_hashes.lock().unwrap(),
+            vec![inserted_root_hash, http_tree.root_hash()]
+        );
+    }
+
+    #[rstest]
+    fn test_prune() {
+        let path = HttpCertificationPath::exact("/index.html");
+        let entry = HttpCertificationTreeEntry::new(&path, HttpCertification::skip());
+
+        let mut tree = CertificationTree::default();
+        tree.insert(&entry.to_tree_path(), vec![]);
+        if let NestedTree::Nested(root) = &mut tree {
+            root.modify(PATH_PREFIX_BYTES, |http_expr| {
+                if let NestedTree::Nested(http_expr) = http_expr {
+                    http_expr.insert(b"empty".to_vec(), NestedTree::default());
+                }
+            });
+        }
+
+        let mut http_tree = HttpCertificationTree::new(tree);
+        let mut expected_http_tree = HttpCertificationTree::default();
+        expected_http_tree.insert(&entry);
+
+        assert_ne!(http_tree.root_hash(), expected_http_tree.root_hash());
+
+        assert_eq!(http_tree.prune(), 1);
+
+        assert_eq!(http_tree.root_hash(), expected_http_tree.root_hash());
+        assert!(http_tree.witness(&entry, "/index.html").is_ok());
+        assert_eq!(http_tree.prune(), 0);
+    }
+
     #[rstest]
     fn delete_removes_empty_subpaths() {
         let mut http_tree = HttpCertificationTree::default();
@@ -552,11 +1528,8 @@ mod tests {
         http_tree.delete(&get_entry);
 
         assert_matches!(
-            http_tree
-                .witness(&get_entry, req_url)
-                .unwrap()
-                .lookup_subtree(&lookup_path_from_entry(&get_entry)),
-            SubtreeLookupResult::Absent
+            http_tree.witness(&get_entry, req_url),
+            Err(HttpCertificationError::WitnessEntryNotFound { .. })
         );
         assert_matches!(
             http_tree
@@ -592,18 +1565,12 @@ mod tests {
         http_tree.delete(&post_entry);
 
         assert_matches!(
-            http_tree
-                .witness(&get_entry, req_url)
-                .unwrap()
-                .lookup_subtree(&lookup_path_from_entry(&get_entry)),
-            SubtreeLookupResult::Absent
+            http_tree.witness(&get_entry, req_url),
+            Err(HttpCertificationError::WitnessEntryNotFound { .. })
         );
         assert_matches!(
-            http_tree
-                .witness(&post_entry, req_url)
-                .unwrap()
-                .lookup_subtree(&lookup_path_from_entry(&post_entry)),
-            SubtreeLookupResult::Absent
+            http_tree.witness(&post_entry, req_url),
+            Err(HttpCertificationError::WitnessEntryNotFound { .. })
         );
         assert_matches!(
             http_tree
@@ -632,25 +1599,16 @@ mod tests {
         http_tree.delete(&alt_get_entry);
 
         assert_matches!(
-            http_tree
-                .witness(&get_entry, req_url)
-                .unwrap()
-                .lookup_subtree(&lookup_path_from_entry(&get_entry)),
-            SubtreeLookupResult::Absent
+            http_tree.witness(&get_entry, req_url),
+            Err(HttpCertificationError::WitnessEntryNotFound { .. })
         );
         assert_matches!(
-            http_tree
-                .witness(&post_entry, req_url)
-                .unwrap()
-                .lookup_subtree(&lookup_path_from_entry(&post_entry)),
-            SubtreeLookupResult::Absent
+            http_tree.witness(&post_entry, req_url),
+            Err(HttpCertificationError::WitnessEntryNotFound { .. })
         );
         assert_matches!(
-            http_tree
-                .witness(&alt_get_entry, req_url)
-                .unwrap()
-                .lookup_subtree(&lookup_path_from_entry(&alt_get_entry)),
-            SubtreeLookupResult::Absent
+            http_tree.witness(&alt_get_entry, req_url),
+            Err(HttpCertificationError::WitnessEntryNotFound { .. })
         );
         assert_matches!(
             http_tree
@@ -672,32 +1630,20 @@ mod tests {
         http_tree.delete(&alt_post_entry);
 
         assert_matches!(
-            http_tree
-                .witness(&get_entry, req_url)
-                .unwrap()
-                .lookup_subtree(&lookup_path_from_entry(&get_entry)),
-            SubtreeLookupResult::Absent
+            http_tree.witness(&get_entry, req_url),
+            Err(HttpCertificationError::WitnessEntryNotFound { .. })
         );
         assert_matches!(
-            http_tree
-                .witness(&post_entry, req_url)
-                .unwrap()
-                .lookup_subtree(&lookup_path_from_entry(&post_entry)),
-            SubtreeLookupResult::Absent
+            http_tree.witness(&post_entry, req_url),
+            Err(HttpCertificationError::WitnessEntryNotFound { .. })
         );
         assert_matches!(
-            http_tree
-                .witness(&alt_get_entry, req_url)
-                .unwrap()
-                .lookup_subtree(&lookup_path_from_entry(&alt_get_entry)),
-            SubtreeLookupResult::Absent
+            http_tree.witness(&alt_get_entry, req_url),
+            Err(HttpCertificationError::WitnessEntryNotFound { .. })
         );
         assert_matches!(
-            http_tree
-                .witness(&alt_post_entry, req_url)
-                .unwrap()
-                .lookup_subtree(&lookup_path_from_entry(&alt_post_entry)),
-            SubtreeLookupResult::Absent
+            http_tree.witness(&alt_post_entry, req_url),
+            Err(HttpCertificationError::WitnessEntryNotFound { .. })
         );
 
         assert!(!http_tree
@@ -40,7 +40,7 @@ impl<'a> HttpCertificationTreeEntry<'a> {
         }
     }
 
-    pub(super) fn to_tree_path(&self) -> InnerTreePath {
+    pub(crate) fn to_tree_path(&self) -> InnerTreePath {
         let mut tree_path = vec![];
         tree_path.append(&mut self.path.to_tree_path());
         tree_path.append(&mut self.certification.to_tree_path());
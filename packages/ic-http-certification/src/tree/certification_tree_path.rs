@@ -4,8 +4,8 @@ use crate::utils::{
 };
 use std::borrow::Cow;
 
-pub(super) type CertificationTreePathSegment = Vec<u8>;
-pub(super) type InnerTreePath = Vec<CertificationTreePathSegment>;
+pub(crate) type CertificationTreePathSegment = Vec<u8>;
+pub(crate) type InnerTreePath = Vec<CertificationTreePathSegment>;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(super) enum HttpCertificationPathType<'a> {
@@ -24,6 +24,13 @@ pub(super) enum HttpCertificationPathType<'a> {
 ///
 /// - The [Wildcard](HttpCertificationPath::wildcard()) variant is used for paths that match a URL path prefix.
 /// For example, `HttpCertificationPath::wildcard('/foo')` will match the URL paths `/foo/bar` and `/foo/baz`.
+///
+/// The HTTP gateway protocol only supports a wildcard as the last segment of a path, so intermediate
+/// wildcards such as `/users/<*>/profile` cannot be certified. Templated routes, such as
+/// `/users/{id}/profile`, can be certified either with an exact path for every concrete URL path,
+/// or with a wildcard path for their longest static prefix, such as `/users`. In the latter case,
+/// the same certification will be used for all URL paths starting with that prefix, unless a more
+/// specific path is inserted into the tree.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct HttpCertificationPath<'a>(HttpCertificationPathType<'a>);
 
@@ -0,0 +1,225 @@
+use crate::{HttpCertificationError, HttpCertificationResult};
+use ic_certification::{empty, fork, fork_hash, pruned, HashTree};
+use ic_representation_independent_hash::Sha256Digest;
+use std::collections::BTreeMap;
+
+/// Combines the root hashes of several certified subtrees, such as an
+/// [HttpCertificationTree](crate::HttpCertificationTree) and a tree of other certified state,
+/// into the single 32 byte value that a canister can set as its certified data.
+///
+/// Each subtree is identified by a key, which determines the order of the subtrees in the
+/// combined tree. The HTTP Gateway looks up labels in the certified tree assuming that they
+/// are sorted, so the key of a subtree must not be greater than any of the labels at the top
+/// level of the subtree, and all of these labels must be smaller than the key of the next subtree.
+/// Typically, the key is the label that the subtree is labeled with, and for an
+/// [HttpCertificationTree](crate::HttpCertificationTree) it is
+/// [HTTP_CERTIFICATION_TREE_KEY](CertifiedData::HTTP_CERTIFICATION_TREE_KEY).
+///
+/// A witness for a subtree is combined with the pruned hashes of the other subtrees by
+/// [witness](CertifiedData::witness), so that it can be verified against the combined root hash.
+///
+/// If only a single subtree is added, the combined root hash is the root hash of that subtree, and
+/// its witnesses are returned unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use ic_certification::{labeled, labeled_hash, AsHashTree, RbTree};
+/// use ic_http_certification::{
+///     CertifiedData, HttpCertification, HttpCertificationPath, HttpCertificationTree,
+///     HttpCertificationTreeEntry,
+/// };
+///
+/// let mut http_certification_tree = HttpCertificationTree::default();
+/// let entry = HttpCertificationTreeEntry::new(HttpCertificationPath::wildcard("/"), HttpCertification::skip());
+/// http_certification_tree.insert(&entry);
+///
+/// let mut counter_tree: RbTree<&str, Vec<u8>> = RbTree::new();
+/// counter_tree.insert("counter", 42u64.to_be_bytes().to_vec());
+///
+/// let mut certified_data = CertifiedData::new();
+/// certified_data.insert(CertifiedData::HTTP_CERTIFICATION_TREE_KEY, http_certification_tree.root_hash());
+/// certified_data.insert("state", labeled_hash(b"state", &counter_tree.root_hash()));
+///
+/// // set_certified_data(&certified_data.root_hash());
+///
+/// let http_witness = http_certification_tree.witness(&entry, "/").unwrap();
+/// let witness = certified_data
+///     .witness(CertifiedData::HTTP_CERTIFICATION_TREE_KEY, http_witness)
+///     .unwrap();
+/// assert_eq!(witness.digest(), certified_data.root_hash());
+///
+/// let state_witness = labeled("state", counter_tree.witness(b"counter"));
+/// let witness = certified_data.witness("state", state_witness).unwrap();
+/// assert_eq!(witness.digest(), certified_data.root_hash());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CertifiedData {
+    subtrees: BTreeMap<Vec<u8>, Sha256Digest>,
+}
+
+impl CertifiedData {
+    /// The key to use for the root hash of an [HttpCertificationTree](crate::HttpCertificationTree).
+    ///
+    /// This is the smallest label at the top level of the tree, which is used when the tree is
+    /// created with [with_v1_certification](crate::HttpCertificationTree::with_v1_certification).
+    pub const HTTP_CERTIFICATION_TREE_KEY: &'static [u8] = b"http_assets";
+
+    /// Creates a new, empty [CertifiedData].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the root hash of the subtree with the given key, replacing any previous root hash.
+    ///
+    /// After performing this operation, the canister's certified variable will need to be updated
+    /// with the new [root hash](CertifiedData::root_hash).
+    pub fn insert(&mut self, key: impl Into<Vec<u8>>, root_hash: Sha256Digest) {
+        self.subtrees.insert(key.into(), root_hash);
+    }
+
+    /// Removes the subtree with the given key, if any.
+    ///
+    /// After performing this operation, the canister's certified variable will need to be updated
+    /// with the new [root hash](CertifiedData::root_hash).
+    pub fn remove(&mut self, key: impl AsRef<[u8]>) {
+        self.subtrees.remove(key.as_ref());
+    }
+
+    /// Returns the combined root hash of all subtrees, which should be set as the canister's
+    /// certified data.
+    pub fn root_hash(&self) -> Sha256Digest {
+        let subtrees: Vec<_> = self.subtrees.values().collect();
+
+        Self::subtrees_hash(&subtrees)
+    }
+
+    /// Combines the given witness of the subtree with the given key with the pruned hashes of the
+    /// other subtrees, so that it can be verified against the [root hash](CertifiedData::root_hash).
+    ///
+    /// Returns [HttpCertificationError::CertifiedDataSubtreeNotFound] if no subtree with the
+    /// given key was inserted, or [HttpCertificationError::CertifiedDataWitnessMismatch] if the
+    /// witness does not match the root hash of the subtree.
+    pub fn witness(
+        &self,
+        key: impl AsRef<[u8]>,
+        witness: HashTree,
+    ) -> HttpCertificationResult<HashTree> {
+        let key = key.as_ref();
+        let subtrees: Vec<_> = self.subtrees.values().collect();
+        let (index, root_hash) = self
+            .subtrees
+            .iter()
+            .enumerate()
+            .find_map(|(index, (subtree_key, root_hash))| {
+                (subtree_key.as_slice() == key).then_some((index, root_hash))
+            })
+            .ok_or_else(|| HttpCertificationError::CertifiedDataSubtreeNotFound {
+                key: String::from_utf8_lossy(key).into_owned(),
+            })?;
+
+        if witness.digest() != *root_hash {
+            return Err(HttpCertificationError::CertifiedDataWitnessMismatch {
+                key: String::from_utf8_lossy(key).into_owned(),
+            });
+        }
+
+        Ok(Self::subtrees_witness(&subtrees, index, witness))
+    }
+
+    fn subtrees_hash(subtrees: &[&Sha256Digest]) -> Sha256Digest {
+        match subtrees {
+            [] => empty().digest(),
+            [root_hash] => **root_hash,
+            _ => {
+                let (left, right) = subtrees.split_at(subtrees.len() / 2);
+
+                fork_hash(&Self::subtrees_hash(left), &Self::subtrees_hash(right))
+            }
+        }
+    }
+
+    fn subtrees_witness(subtrees: &[&Sha256Digest], index: usize, witness: HashTree) -> HashTree {
+        if subtrees.len() == 1 {
+            return witness;
+        }
+
+        let mid = subtrees.len() / 2;
+        let (left, right) = subtrees.split_at(mid);
+
+        if index < mid {
+            fork(
+                Self::subtrees_witness(left, index, witness),
+                pruned(Self::subtrees_hash(right)),
+            )
+        } else {
+            fork(
+                pruned(Self::subtrees_hash(left)),
+                Self::subtrees_witness(right, index - mid, witness),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use ic_certification::{labeled, labeled_hash, leaf, LookupResult};
+    use rstest::*;
+
+    #[rstest]
+    fn single_subtree_is_unchanged() {
+        let subtree = labeled("counter", leaf(b"42".to_vec()));
+        let mut certified_data = CertifiedData::new();
+        certified_data.insert("counter", subtree.digest());
+
+        assert_eq!(certified_data.root_hash(), subtree.digest());
+        assert_eq!(
+            certified_data.witness("counter", subtree.clone()).unwrap(),
+            subtree
+        );
+    }
+
+    #[rstest]
+    fn witnesses_are_sorted_and_verifiable() {
+        let subtrees = [
+            labeled("a", leaf(b"1".to_vec())),
+            labeled("b", leaf(b"2".to_vec())),
+            labeled("c", leaf(b"3".to_vec())),
+        ];
+        let mut certified_data = CertifiedData::new();
+        for (key, subtree) in ["c", "a", "b"].into_iter().zip([2, 0, 1]) {
+            certified_data.insert(key, subtrees[subtree].digest());
+        }
+
+        for (key, subtree) in ["a", "b", "c"].into_iter().zip(subtrees.iter()) {
+            let witness = certified_data.witness(key, subtree.clone()).unwrap();
+
+            assert_eq!(witness.digest(), certified_data.root_hash());
+            assert_matches!(witness.lookup_path([key]), LookupResult::Found(_));
+        }
+    }
+
+    #[rstest]
+    fn witness_errors() {
+        let subtree = labeled("counter", leaf(b"42".to_vec()));
+        let mut certified_data = CertifiedData::new();
+        certified_data.insert("counter", subtree.digest());
+        certified_data.insert("other", labeled_hash(b"other", &empty().digest()));
+
+        assert_matches!(
+            certified_data.witness("missing", subtree),
+            Err(HttpCertificationError::CertifiedDataSubtreeNotFound { key }) if key == "missing"
+        );
+        assert_matches!(
+            certified_data.witness("counter", labeled("counter", leaf(b"43".to_vec()))),
+            Err(HttpCertificationError::CertifiedDataWitnessMismatch { key }) if key == "counter"
+        );
+    }
+
+    #[rstest]
+    fn empty_certified_data() {
+        assert_eq!(CertifiedData::new().root_hash(), empty().digest());
+    }
+}
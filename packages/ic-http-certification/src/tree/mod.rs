@@ -5,11 +5,15 @@
 //! Certifications are prepared using the [HttpCertification] enum.
 
 mod certification;
+mod certification_route;
 mod certification_tree;
 mod certification_tree_entry;
 mod certification_tree_path;
+mod certified_data;
 
 pub use certification::*;
+pub use certification_route::*;
 pub use certification_tree::*;
 pub use certification_tree_entry::*;
 pub use certification_tree_path::*;
+pub use certified_data::*;
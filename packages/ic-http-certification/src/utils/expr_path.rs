@@ -0,0 +1,61 @@
+use crate::{HttpCertificationPath, HttpCertificationResult, HttpRequest};
+
+/// Returns the `expr_path` of the certification for the given request, as expected by
+/// [add_v2_certificate_header](crate::utils::add_v2_certificate_header).
+///
+/// If `exact` is `true`, this is the `expr_path` of an
+/// [exact](crate::HttpCertificationPath::exact) path matching the request path, otherwise it is
+/// the `expr_path` of a [wildcard](crate::HttpCertificationPath::wildcard) path for the request
+/// path. Like [HttpRequest::get_path], the request path is percent-decoded.
+///
+/// # Examples
+///
+/// ```
+/// use ic_http_certification::{utils::expr_path_for_request, HttpRequest};
+///
+/// let request = HttpRequest::get("/assets/app.js?v=1").build();
+///
+/// assert_eq!(
+///     expr_path_for_request(&request, true).unwrap(),
+///     vec!["http_expr", "assets", "app.js", "<$>"]
+/// );
+/// assert_eq!(
+///     expr_path_for_request(&request, false).unwrap(),
+///     vec!["http_expr", "assets", "app.js", "<*>"]
+/// );
+/// ```
+pub fn expr_path_for_request(
+    request: &HttpRequest,
+    exact: bool,
+) -> HttpCertificationResult<Vec<String>> {
+    let request_path = request.get_path()?;
+    let path = if exact {
+        HttpCertificationPath::exact(request_path)
+    } else {
+        HttpCertificationPath::wildcard(request_path)
+    };
+
+    Ok(path.to_expr_path())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case("/", true, vec!["http_expr", "", "<$>"])]
+    #[case("/", false, vec!["http_expr", "", "<*>"])]
+    #[case("/index.html", true, vec!["http_expr", "index.html", "<$>"])]
+    #[case("/assets/", true, vec!["http_expr", "assets", "", "<$>"])]
+    #[case("/assets/%73ample%20asset.txt", false, vec!["http_expr", "assets", "sample asset.txt", "<*>"])]
+    fn expr_path_for_request_matches_certification_path(
+        #[case] url: &str,
+        #[case] exact: bool,
+        #[case] expected: Vec<&str>,
+    ) {
+        let request = HttpRequest::get(url).build();
+
+        assert_eq!(expr_path_for_request(&request, exact).unwrap(), expected);
+    }
+}
@@ -10,3 +10,9 @@ pub use wildcard_paths::*;
 
 mod skip_certification;
 pub use skip_certification::*;
+
+mod path_normalization;
+pub use path_normalization::*;
+
+mod expr_path;
+pub use expr_path::*;
@@ -0,0 +1,111 @@
+use crate::HttpCertificationResult;
+
+/// Normalizes a percent-encoded URL path for routing.
+///
+/// Duplicate slashes are collapsed and dot segments (`.` and `..`) are removed as described in
+/// [RFC 3986](https://www.rfc-editor.org/rfc/rfc3986#section-5.2.4), and the resulting path is
+/// percent-decoded. Dot segments and slashes are removed before decoding, so percent-encoded
+/// slashes and dots, such as `%2F` or `%2e%2e`, do not change the structure of the path. A path
+/// ending with a slash or a dot segment keeps a single trailing slash, since paths with and
+/// without a trailing slash are distinct paths in an
+/// [HttpCertificationTree](crate::HttpCertificationTree).
+///
+/// This is **not** the path that certifications are looked up with. The HTTP gateway looks up
+/// certifications with the path returned by [HttpRequest::get_path](crate::HttpRequest::get_path),
+/// which is only percent-decoded, so responses must be certified under that path. The normalized
+/// path can be used to decide which response to serve, or to redirect to the normalized path.
+///
+/// The path should not include a query string or fragment, and should not have been decoded
+/// already, such as the result of [HttpRequest::get_path](crate::HttpRequest::get_path), otherwise
+/// it would be decoded twice.
+///
+/// # Examples
+///
+/// ```
+/// use ic_http_certification::{utils::normalize_path, HttpRequest};
+///
+/// assert_eq!(normalize_path("/%73ample-asset.txt").unwrap(), "/sample-asset.txt");
+/// assert_eq!(normalize_path("//assets/./js/../app.js").unwrap(), "/assets/app.js");
+/// assert_eq!(normalize_path("/assets/js/..").unwrap(), "/assets/");
+///
+/// // responses are still certified for the path of the request
+/// let request = HttpRequest::get("//assets/./js/../app.js").build();
+/// assert_eq!(request.get_path().unwrap(), "//assets/./js/../app.js");
+/// ```
+pub fn normalize_path(path: &str) -> HttpCertificationResult<String> {
+    let mut segments = vec![];
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+
+    let is_directory = matches!(path.rsplit('/').next(), Some("" | "." | ".."));
+
+    let mut normalized_path = format!("/{}", segments.join("/"));
+    if is_directory && !segments.is_empty() {
+        normalized_path.push('/');
+    }
+
+    Ok(urlencoding::decode(&normalized_path)?.into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HttpCertificationError, HttpRequest};
+    use assert_matches::assert_matches;
+    use rstest::*;
+
+    #[rstest]
+    #[case("", "/")]
+    #[case("/", "/")]
+    #[case("//", "/")]
+    #[case("/sample-asset.txt", "/sample-asset.txt")]
+    #[case("/%73ample-asset.txt", "/sample-asset.txt")]
+    #[case("/hello%20world.txt", "/hello world.txt")]
+    #[case("/assets/", "/assets/")]
+    #[case("/assets//", "/assets/")]
+    #[case("//assets///js//app.js", "/assets/js/app.js")]
+    #[case("/assets/./js/app.js", "/assets/js/app.js")]
+    #[case("/assets/js/../app.js", "/assets/app.js")]
+    #[case("/assets/%2e%2e/app.js", "/assets/../app.js")]
+    #[case("/assets/%2e/app.js", "/assets/./app.js")]
+    #[case("/assets%2Fjs/../app.js", "/app.js")]
+    #[case("/assets/js%2F..%2Fapp.js", "/assets/js/../app.js")]
+    #[case("/assets/js/.", "/assets/js/")]
+    #[case("/assets/js/..", "/assets/")]
+    #[case("/../../app.js", "/app.js")]
+    #[case("/..", "/")]
+    fn normalize_path_cases(#[case] path: &str, #[case] expected: &str) {
+        assert_eq!(normalize_path(path).unwrap(), expected);
+    }
+
+    #[rstest]
+    #[case("/sample-asset.txt", true)]
+    #[case("/%73ample%20asset.txt", true)]
+    #[case("/assets/js/", true)]
+    #[case("//assets/js/app.js", false)]
+    #[case("/assets/./js/app.js", false)]
+    #[case("/assets/js/../app.js", false)]
+    fn normalize_path_differs_from_certified_path(#[case] path: &str, #[case] is_normalized: bool) {
+        let certified_path = HttpRequest::get(path).build().get_path().unwrap();
+
+        assert_eq!(
+            normalize_path(path).unwrap() == certified_path,
+            is_normalized
+        );
+    }
+
+    #[rstest]
+    fn normalize_path_invalid_utf8() {
+        assert_matches!(
+            normalize_path("/%ff.txt"),
+            Err(HttpCertificationError::Utf8ConversionError(_))
+        );
+    }
+}
@@ -89,6 +89,63 @@ pub fn add_v2_certificate_header(
     ));
 }
 
+/// Adds the legacy [`IC-Certificate` header](https://internetcomputer.org/docs/current/references/http-gateway-protocol-spec/#the-certificate-header)
+/// for response verification version 1 to a given [`HttpResponse`]. This is only required for
+/// canisters that still need to serve HTTP Gateways that do not support version 2 of response verification,
+/// [add_v2_certificate_header] should be used otherwise.
+///
+/// # Arguments
+///
+/// * `data_certificate` - A certificate used by the HTTP Gateway to verify a response.
+///     Retrieved using `ic_cdk::api::data_certificate`. This value is not validated by this function
+///     and is expected to be a valid certificate. The function will not fail if the certificate is invalid,
+///     but verification of the certificate by the HTTP Gateway will fail.
+/// * `response` - The [`HttpResponse`] to add the certificate header to.
+///     Created using [`HttpResponse::builder()`](crate::HttpResponse::builder).
+/// * `witness` - A pruned merkle tree revealing the SHA-256 hash of the response body under the
+///     `http_assets` label, at the path of the current request. The witness is not validated to be
+///     correct for the given response, and the function will not fail if the witness is invalid.
+///     The HTTP Gateway will fail to verify the response if the witness is invalid.
+///
+/// # Examples
+///
+/// ```
+/// use ic_certification::{labeled, leaf};
+/// use ic_http_certification::{HttpResponse, CERTIFICATE_HEADER_NAME, utils::add_v1_certificate_header};
+/// use ic_representation_independent_hash::hash;
+///
+/// let body = b"Hello World!";
+/// let mut response = HttpResponse::ok(body, vec![]).build();
+///
+/// let witness = labeled(b"http_assets", labeled(b"/index.html", leaf(hash(body))));
+///
+/// // this should normally be retrieved using `ic_cdk::api::data_certificate()`.
+/// let data_certificate = vec![1, 2, 3];
+///
+/// add_v1_certificate_header(&data_certificate, &mut response, &witness);
+///
+/// let (header_name, header_value) = &response.headers()[0];
+/// assert_eq!(header_name, CERTIFICATE_HEADER_NAME);
+/// assert!(header_value.starts_with("certificate=:AQID:, tree=:"));
+/// assert!(!header_value.contains("version"));
+/// ```
+pub fn add_v1_certificate_header(
+    data_certificate: &[u8],
+    response: &mut HttpResponse,
+    witness: &HashTree,
+) {
+    let witness = cbor_encode(witness);
+
+    response.add_header((
+        CERTIFICATE_HEADER_NAME.to_string(),
+        format!(
+            "certificate=:{}:, tree=:{}:",
+            BASE64.encode(data_certificate),
+            BASE64.encode(witness)
+        ),
+    ));
+}
+
 fn cbor_encode(value: &impl Serialize) -> Vec<u8> {
     let mut serializer = serde_cbor::Serializer::new(Vec::new());
     serializer
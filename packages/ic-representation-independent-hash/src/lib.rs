@@ -17,6 +17,26 @@ pub fn hash(data: &[u8]) -> Sha256Digest {
     hasher.finalize().into()
 }
 
+/// Calculates the SHA-256 hash of the concatenation of the given chunks, without requiring
+/// the chunks to be contiguous in memory.
+///
+/// # Examples
+///
+/// ```
+/// use ic_representation_independent_hash::{hash, hash_chunks};
+///
+/// let chunks: [&[u8]; 2] = [b"Hello ", b"World!"];
+///
+/// assert_eq!(hash_chunks(chunks), hash(b"Hello World!"));
+/// ```
+pub fn hash_chunks<'a>(chunks: impl IntoIterator<Item = &'a [u8]>) -> Sha256Digest {
+    let mut hasher = Sha256::new();
+    for chunk in chunks {
+        hasher.update(chunk);
+    }
+    hasher.finalize().into()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -33,4 +53,14 @@ mod tests {
 
         assert_eq!(result, expected_hash);
     }
+
+    #[test]
+    fn hash_text_chunks() {
+        let text = "Hello World!";
+        let chunks = text.as_bytes().chunks(5);
+
+        let result = hash_chunks(chunks);
+
+        assert_eq!(result, hash(text.as_bytes()));
+    }
 }
@@ -3,26 +3,18 @@ use anyhow::{anyhow, Result};
 use ic_agent::export::Principal;
 use ic_agent::Agent;
 use ic_http_certification::{HttpRequest, HttpResponse};
-use ic_response_verification::types::VerificationInfo;
+use ic_response_verification::types::{
+    SystemTimeSource, TimeConfig, VerificationInfo, VerificationPolicy,
+};
 use ic_utils::call::SyncCall;
 use ic_utils::interfaces::http_request::HeaderField;
 use ic_utils::interfaces::HttpRequestCanister;
 use std::env;
 use std::fs;
 use std::println;
-use std::time::{SystemTime, UNIX_EPOCH};
 
 mod agent;
 
-fn get_current_time() -> u128 {
-    let start = SystemTime::now();
-
-    start
-        .duration_since(UNIX_EPOCH)
-        .expect("Time went backwards")
-        .as_nanos()
-}
-
 fn read_file(file_path: &str) -> Result<Vec<u8>> {
     let path = format!(
         "packages/ic-response-verification-tests/src/frontend/{}",
@@ -71,6 +63,7 @@ async fn v1_test(canister_id: &str, agent: &Agent) -> Result<()> {
         VerificationInfo {
             verification_version,
             response: _,
+            ..
         } if verification_version == 1
     ));
 
@@ -81,6 +74,7 @@ async fn v1_test(canister_id: &str, agent: &Agent) -> Result<()> {
         VerificationInfo {
             verification_version,
             response: _,
+            ..
         } if verification_version == 1
     ));
 
@@ -152,6 +146,7 @@ async fn v2_load_asset(
         VerificationInfo {
             verification_version,
             response: _,
+            ..
         } if verification_version == 2
     ));
     assert_eq!(asset, response.body().to_vec());
@@ -186,17 +181,15 @@ async fn perform_test(
                 .collect::<Vec<_>>(),
         )
         .build();
-    let current_time_ns = get_current_time();
-    let max_cert_time_offset_ns = 300_000_000_000; // 5 mins
-
     let result = ic_response_verification::verify_request_response_pair(
         request,
         response.clone(),
         canister_id.as_slice(),
-        current_time_ns,
-        max_cert_time_offset_ns,
+        &SystemTimeSource,
+        &TimeConfig::default(),
         agent.read_root_key().as_slice(),
         ic_response_verification::MIN_VERIFICATION_VERSION,
+        &VerificationPolicy::default(),
     )?;
 
     Ok((result, response))
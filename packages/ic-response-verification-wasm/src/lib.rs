@@ -3,8 +3,9 @@
 use crate::request::request_from_js;
 use crate::response::response_from_js;
 use ic_response_verification::{
-    types::VerificationInfo, verify_request_response_pair as verify_request_response_pair_impl,
-    ResponseVerificationJsError, MAX_VERIFICATION_VERSION, MIN_VERIFICATION_VERSION,
+    types::{FixedTimeSource, TimeConfig, VerificationInfo, VerificationPolicy},
+    verify_request_response_pair as verify_request_response_pair_impl, ResponseVerificationJsError,
+    MAX_VERIFICATION_VERSION, MIN_VERIFICATION_VERSION,
 };
 use wasm_bindgen::{prelude::*, JsCast};
 
@@ -59,10 +60,11 @@ pub fn verify_request_response_pair(
         request.into(),
         response.into(),
         canister_id,
-        current_time_ns as u128,
-        max_cert_time_offset_ns as u128,
+        &FixedTimeSource::new(current_time_ns as u128),
+        &TimeConfig::new(max_cert_time_offset_ns as u128),
         ic_public_key,
         min_requested_verification_version,
+        &VerificationPolicy::default(),
     )
     .map(|verification_result| {
         JsValue::from(VerificationInfo::from(verification_result))
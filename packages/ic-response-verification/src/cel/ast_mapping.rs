@@ -9,10 +9,10 @@ use ic_http_certification::{
 };
 use std::collections::HashMap;
 
-fn validate_object<'a>(
-    cel: &'a CelValue<'a>,
+fn validate_object<'a, 'b>(
+    cel: &'b CelValue<'a>,
     name: &str,
-) -> CelParserResult<&'a HashMap<&'a str, CelValue<'a>>> {
+) -> CelParserResult<&'b HashMap<&'a str, CelValue<'a>>> {
     let CelValue::Object(object_name, object_value) = cel else {
         return Err(CelParserError::UnexpectedNodeType {
             node_name: name.into(),
@@ -32,10 +32,10 @@ fn validate_object<'a>(
     Ok(object_value)
 }
 
-fn validate_function<'a>(
-    cel: &'a CelValue<'a>,
-    name: &'a str,
-) -> CelParserResult<&'a Vec<CelValue<'a>>> {
+fn validate_function<'a, 'b>(
+    cel: &'b CelValue<'a>,
+    name: &str,
+) -> CelParserResult<&'b Vec<CelValue<'a>>> {
     let CelValue::Function(function_name, function_value) = cel else {
         return Err(CelParserError::UnexpectedNodeType {
             node_name: name.into(),
@@ -55,10 +55,7 @@ fn validate_function<'a>(
     Ok(function_value)
 }
 
-fn validate_string_array<'a>(
-    cel: &'a CelValue<'a>,
-    name: &'a str,
-) -> CelParserResult<Vec<&'a str>> {
+fn validate_string_array<'a>(cel: &CelValue<'a>, name: &str) -> CelParserResult<Vec<&'a str>> {
     let CelValue::Array(array) = cel else {
         return Err(CelParserError::UnexpectedNodeType {
             node_name: name.into(),
@@ -86,7 +83,7 @@ fn validate_string_array<'a>(
 }
 
 fn validate_request_certification<'a>(
-    certification: &'a HashMap<&'a str, CelValue<'a>>,
+    certification: &HashMap<&'a str, CelValue<'a>>,
 ) -> CelParserResult<Option<DefaultRequestCertification<'a>>> {
     let no_request_certification = certification.get("no_request_certification");
     let request_certification = certification.get("request_certification");
@@ -130,7 +127,7 @@ fn validate_request_certification<'a>(
 }
 
 fn validate_response_certification<'a>(
-    certification: &'a HashMap<&'a str, CelValue<'a>>,
+    certification: &HashMap<&'a str, CelValue<'a>>,
 ) -> CelParserResult<DefaultResponseCertification<'a>> {
     let Some(response_certification) = certification.get("response_certification") else {
         return Err(CelParserError::MissingObjectProperty {
@@ -141,7 +138,7 @@ fn validate_response_certification<'a>(
     let response_certification = validate_object(response_certification, "ResponseCertification")?;
 
     let get_response_certification_headers =
-        |property_name| -> CelParserResult<Option<Vec<&'a str>>> {
+        |property_name: &str| -> CelParserResult<Option<Vec<&'a str>>> {
             response_certification
                 .get(property_name)
                 .map(|certified_response_headers| {
@@ -171,7 +168,7 @@ fn validate_response_certification<'a>(
     }
 }
 
-pub(crate) fn map_cel_ast<'a>(cel: &'a CelValue<'a>) -> CelParserResult<CelExpression<'a>> {
+pub(crate) fn map_cel_ast<'a>(cel: &CelValue<'a>) -> CelParserResult<CelExpression<'a>> {
     let default_certification = validate_function(cel, "default_certification")?;
 
     let Some(validation_args) = default_certification.first() else {
@@ -0,0 +1,47 @@
+use crate::cel::{parse_cel_expression_header, CelParserError};
+use ic_http_certification::cel::CelExpression;
+
+/// Parses the value of an `IC-CertificateExpression` header and compares it with the given
+/// [CelExpression].
+///
+/// The comparison ignores whitespace, as well as the order, casing and duplication of header and
+/// query parameter names, see [CelExpression::canonicalize].
+///
+/// # Examples
+///
+/// ```
+/// use ic_http_certification::{
+///     DefaultCelBuilder,
+///     cel::{CelExpression, DefaultCelExpression},
+/// };
+/// use ic_response_verification::cel::cel_expression_header_eq;
+///
+/// let cel_expr = CelExpression::Default(DefaultCelExpression::Full(
+///     DefaultCelBuilder::full_certification()
+///         .with_request_headers(vec!["Accept", "If-None-Match"])
+///         .build(),
+/// ));
+/// let header = r#"
+///     default_certification(ValidationArgs{
+///         certification: Certification {
+///             request_certification: RequestCertification {
+///                 certified_request_headers: ["if-none-match", "accept"],
+///                 certified_query_parameters: []
+///             },
+///             response_certification: ResponseCertification {
+///                 certified_response_headers: ResponseHeaderList { headers: [] }
+///             }
+///         }
+///     })
+/// "#;
+///
+/// assert!(cel_expression_header_eq(header, &cel_expr).unwrap());
+/// ```
+pub fn cel_expression_header_eq(
+    certificate_expression_header: &str,
+    cel_expr: &CelExpression,
+) -> Result<bool, CelParserError> {
+    let parsed_cel_expr = parse_cel_expression_header(certificate_expression_header)?;
+
+    Ok(parsed_cel_expr == *cel_expr)
+}
@@ -0,0 +1,42 @@
+use crate::cel::{map_cel_ast, parse_cel_expression, CelParserError};
+use ic_http_certification::cel::CelExpression;
+
+/// Parses the value of an `IC-CertificateExpression` header into a [CelExpression], describing
+/// which parts of the request and response the certification of the response covers.
+///
+/// The returned [CelExpression] borrows the header and query parameter names from the given
+/// header value.
+///
+/// # Examples
+///
+/// ```
+/// use ic_http_certification::cel::{CelExpression, DefaultCelExpression};
+/// use ic_response_verification::cel::parse_cel_expression_header;
+///
+/// let header = r#"
+///     default_certification(ValidationArgs{
+///         certification: Certification {
+///             no_request_certification: Empty {},
+///             response_certification: ResponseCertification {
+///                 certified_response_headers: ResponseHeaderList { headers: ["content-type"] }
+///             }
+///         }
+///     })
+/// "#;
+///
+/// let CelExpression::Default(DefaultCelExpression::ResponseOnly(cel_expr)) =
+///     parse_cel_expression_header(header).unwrap()
+/// else {
+///     panic!("Expected a response only certification");
+/// };
+///
+/// assert!(cel_expr.response.is_header_certified("content-type"));
+/// assert!(!cel_expr.response.is_header_certified("cache-control"));
+/// ```
+pub fn parse_cel_expression_header(
+    certificate_expression_header: &str,
+) -> Result<CelExpression<'_>, CelParserError> {
+    let cel_ast = parse_cel_expression(certificate_expression_header)?;
+
+    map_cel_ast(&cel_ast)
+}
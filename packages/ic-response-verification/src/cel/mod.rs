@@ -6,6 +6,12 @@ pub use error::*;
 mod ast_mapping;
 mod parser;
 
+mod header;
+pub use header::*;
+
+mod comparison;
+pub use comparison::*;
+
 pub(crate) use ast_mapping::map_cel_ast;
 pub(crate) use parser::parse_cel_expression;
 
@@ -1,4 +1,7 @@
-use crate::cel::{map_cel_ast, parse_cel_expression};
+use crate::cel::{
+    cel_expression_header_eq, map_cel_ast, parse_cel_expression, parse_cel_expression_header,
+    CelParserError,
+};
 use ic_http_certification::{
     cel::{
         CelExpression, DefaultCelExpression, DefaultFullCelExpression, DefaultRequestCertification,
@@ -111,3 +114,81 @@ fn parses_full_certification_expression() {
     assert_eq!(&result, &expected_result);
     assert_eq!(&minified_result, &expected_result);
 }
+
+#[test]
+fn compares_cel_expression_header() {
+    let cel_expression = r#"
+        default_certification (
+            ValidationArgs {
+                certification: Certification {
+                    request_certification: RequestCertification {
+                        certified_request_headers: ["Host", "accept"],
+                        certified_query_parameters: ["filter"]
+                    },
+                    response_certification: ResponseCertification {
+                        response_header_exclusions: ResponseHeaderList {
+                            headers: ["Date", "Server"]
+                        }
+                    }
+                }
+            }
+        )
+    "#;
+    let expected_cel_expr =
+        CelExpression::Default(DefaultCelExpression::Full(DefaultFullCelExpression {
+            request: DefaultRequestCertification::new(vec!["Accept", "host"], vec!["filter"]),
+            response: DefaultResponseCertification::response_header_exclusions(vec![
+                "Server", "Date",
+            ]),
+        }));
+    let other_cel_expr =
+        CelExpression::Default(DefaultCelExpression::Full(DefaultFullCelExpression {
+            request: DefaultRequestCertification::new(vec!["Accept", "host"], vec![]),
+            response: DefaultResponseCertification::response_header_exclusions(vec![
+                "Server", "Date",
+            ]),
+        }));
+
+    assert!(cel_expression_header_eq(cel_expression, &expected_cel_expr).unwrap());
+    assert!(
+        cel_expression_header_eq(&remove_whitespace(cel_expression), &expected_cel_expr).unwrap()
+    );
+    assert!(!cel_expression_header_eq(cel_expression, &other_cel_expr).unwrap());
+    assert!(cel_expression_header_eq("not_a_cel_expression", &expected_cel_expr).is_err());
+}
+
+#[test]
+fn parses_cel_expression_header() {
+    let cel_expression = r#"
+        default_certification (
+            ValidationArgs {
+                certification: Certification {
+                    request_certification: RequestCertification {
+                        certified_request_headers: ["host"],
+                        certified_query_parameters: []
+                    },
+                    response_certification: ResponseCertification {
+                        certified_response_headers: ResponseHeaderList {
+                            headers: ["Content-Type"]
+                        }
+                    }
+                }
+            }
+        )
+    "#;
+    let expected_result =
+        CelExpression::Default(DefaultCelExpression::Full(DefaultFullCelExpression {
+            request: DefaultRequestCertification::new(vec!["host"], vec![]),
+            response: DefaultResponseCertification::certified_response_headers(vec![
+                "Content-Type",
+            ]),
+        }));
+
+    let result = parse_cel_expression_header(cel_expression).unwrap();
+
+    assert_eq!(result, expected_result);
+    assert!(matches!(
+        parse_cel_expression_header("unknown_certification(ValidationArgs{})"),
+        Err(CelParserError::UnexpectedNodeName { .. })
+    ));
+}
@@ -35,6 +35,28 @@ pub enum ResponseVerificationError {
         requested_version: u8,
     },
 
+    /// The actual requested version is higher than the maximum verification version of the
+    /// [VerificationPolicy](crate::types::VerificationPolicy)
+    #[error(r#"The requested verification version {requested_version:?} is higher than the maximum verification version {max_verification_version:?}"#)]
+    RequestedVerificationVersionAboveMaximum {
+        /// The maximum verification version of the verification policy
+        max_verification_version: u8,
+        /// The actual requested version
+        requested_version: u8,
+    },
+
+    /// None of the verification versions offered by the canister is supported by this package
+    /// and accepted by the [VerificationPolicy](crate::types::VerificationPolicy)
+    #[error(r#"None of the offered verification versions {offered_versions:?} is within the accepted verification versions {min_accepted_version:?} to {max_accepted_version:?}"#)]
+    NoCommonVerificationVersion {
+        /// The verification versions offered by the canister
+        offered_versions: Vec<u8>,
+        /// The minimum verification version that is accepted
+        min_accepted_version: u8,
+        /// The maximum verification version that is accepted
+        max_accepted_version: u8,
+    },
+
     /// Error parsing CEL expression
     #[error("Cel parser error")]
     CelError(#[from] cel::CelParserError),
@@ -48,8 +70,13 @@ pub enum ResponseVerificationError {
     ParseIntError(#[from] std::num::ParseIntError),
 
     /// The tree has different root hash from the expected value in the certified variables
-    #[error("Invalid tree root hash")]
-    InvalidTree,
+    #[error(r#"Invalid tree root hash, the root hash of the tree ({tree_root_hash}) does not match the certified data of the canister ({certified_data:?})"#)]
+    InvalidTree {
+        /// The hex encoded root hash of the tree provided by the "IC-Certificate" response header
+        tree_root_hash: String,
+        /// The hex encoded certified data of the canister in the certificate, if it was found
+        certified_data: Option<String>,
+    },
 
     /// The CEL expression path is invalid
     #[error("Invalid expression path")]
@@ -136,12 +163,92 @@ pub enum ResponseVerificationError {
     },
 
     /// The response body was a mismatch from the expected values in the tree
-    #[error("Invalid response body")]
-    InvalidResponseBody,
+    #[error(r#"Invalid response body, the hash of the response body ({body_hash}) does not match the hash certified for the request path ({request_path:?}) in the tree ({certified_body_hash:?})"#)]
+    InvalidResponseBody {
+        /// The request path
+        request_path: String,
+        /// The hex encoded hash of the response body
+        body_hash: String,
+        /// The hex encoded body hash certified for the request path, if it was found in the tree
+        certified_body_hash: Option<String>,
+    },
 
     /// The response hashes were a mismatch from the expected values in the tree
-    #[error("Invalid response hashes")]
-    InvalidResponseHashes,
+    #[error(r#"Invalid response hashes for the expression path provided by the "IC-Certificate" response header ({provided_expr_path:?}), {mismatch} (response headers hash: {response_headers_hash}, body hash: {body_hash})"#)]
+    InvalidResponseHashes {
+        /// The expression path provided by the "IC-Certificate" response header
+        provided_expr_path: Vec<String>,
+        /// The hash that was not found in the tree
+        mismatch: ResponseHashesMismatch,
+        /// The hex encoded hash of the certified response headers and status code
+        response_headers_hash: String,
+        /// The hex encoded hash of the response body
+        body_hash: String,
+    },
+
+    /// A response header that is required to be certified by the
+    /// [VerificationPolicy](crate::types::VerificationPolicy) was excluded from certification
+    #[error(r#"The response header {header_name:?} is required to be certified by the verification policy, but it was excluded from certification"#)]
+    UncertifiedResponseHeader {
+        /// The name of the response header
+        header_name: String,
+    },
+
+    /// A redirect response does not certify its "Location" header, as required by the
+    /// [VerificationPolicy](crate::types::VerificationPolicy)
+    #[error(r#"The redirect response with status code {status_code} does not certify its "Location" header, which is required by the verification policy"#)]
+    UncertifiedRedirectLocation {
+        /// The status code of the redirect response
+        status_code: u16,
+    },
+
+    /// A permanent redirect response is certified with a wildcard expression path, but the
+    /// [VerificationPolicy](crate::types::VerificationPolicy) requires an exact expression path
+    #[error(r#"The permanent redirect response with status code {status_code} is certified with the wildcard expression path {expr_path:?}, but the verification policy requires an exact expression path"#)]
+    WildcardPermanentRedirect {
+        /// The status code of the redirect response
+        status_code: u16,
+        /// The expression path provided by the "IC-Certificate" response header
+        expr_path: Vec<String>,
+    },
+
+    /// A chunk of a chunked response could not be reassembled with the previous chunks
+    #[error(r#"Invalid response chunk at index {chunk_index}, {mismatch}"#)]
+    InvalidResponseChunk {
+        /// The index of the chunk in the sequence of chunks
+        chunk_index: usize,
+        /// The reason why the chunk could not be reassembled
+        mismatch: ResponseChunkMismatch,
+    },
+
+    /// The chunks of a chunked response do not cover the full response body
+    #[error(r#"Incomplete chunked response, the chunks cover {length} of the {total_length} bytes of the full response body"#)]
+    IncompleteChunkedResponse {
+        /// The number of bytes covered by the chunks
+        length: usize,
+        /// The length of the full response body
+        total_length: usize,
+    },
+
+    /// No chunks were provided to reassemble a chunked response from
+    #[error("Response chunks not found")]
+    MissingResponseChunks,
+
+    /// The decoded response body is larger than the maximum decoded body size of the
+    /// [VerificationPolicy](crate::types::VerificationPolicy)
+    #[error(r#"The decoded response body exceeds the maximum decoded body size of {max_decoded_body_size} bytes"#)]
+    DecodedBodyTooLarge {
+        /// The maximum decoded body size of the verification policy
+        max_decoded_body_size: usize,
+    },
+
+    /// The decoded response body is larger than the maximum compression ratio of the
+    /// [VerificationPolicy](crate::types::VerificationPolicy) allows for the encoded body
+    #[error(r#"The decoded response body exceeds the maximum compression ratio of {max_compression_ratio}"#)]
+    CompressionRatioTooHigh {
+        /// The maximum compression ratio of the verification policy
+        max_compression_ratio: usize,
+    },
 
     /// The certificate was missing from the certification header
     #[error("Certificate not found")]
@@ -178,6 +285,168 @@ pub enum ResponseVerificationError {
     HttpCertificationError(#[from] ic_http_certification::HttpCertificationError),
 }
 
+impl ResponseVerificationError {
+    /// Returns the numeric code of this error, for consumers of the verification API that cannot
+    /// match on the error type, such as gateways written in other languages.
+    ///
+    /// Each variant has its own code, which does not change between releases. New variants are
+    /// assigned new codes, so codes of removed variants are never reused.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ic_response_verification::ResponseVerificationError;
+    ///
+    /// assert_eq!(ResponseVerificationError::MissingCertification.code(), 22);
+    /// ```
+    pub fn code(&self) -> u32 {
+        match self {
+            ResponseVerificationError::IoError(_) => 0,
+            ResponseVerificationError::UnsupportedVerificationVersion { .. } => 1,
+            ResponseVerificationError::RequestedVerificationVersionMismatch { .. } => 2,
+            ResponseVerificationError::RequestedVerificationVersionAboveMaximum { .. } => 27,
+            ResponseVerificationError::NoCommonVerificationVersion { .. } => 35,
+            ResponseVerificationError::CelError(_) => 3,
+            ResponseVerificationError::Base64DecodingError(_) => 4,
+            ResponseVerificationError::ParseIntError(_) => 5,
+            ResponseVerificationError::InvalidTree { .. } => 6,
+            ResponseVerificationError::InvalidExpressionPath => 7,
+            ResponseVerificationError::UnexpectedExpressionPathPrefix { .. } => 8,
+            ResponseVerificationError::UnexpectedExpressionPathSuffix { .. } => 9,
+            ResponseVerificationError::ExactExpressionPathNotFoundInTree { .. } => 10,
+            ResponseVerificationError::ExactExpressionPathMismatch { .. } => 11,
+            ResponseVerificationError::ExactExpressionPathMightExistInTree { .. } => 12,
+            ResponseVerificationError::WildcardExpressionPathNotFoundInTree { .. } => 13,
+            ResponseVerificationError::WildcardExpressionPathMismatch { .. } => 14,
+            ResponseVerificationError::MoreSpecificWildcardExpressionMightExistInTree {
+                ..
+            } => 15,
+            ResponseVerificationError::InvalidResponseBody { .. } => 16,
+            ResponseVerificationError::InvalidResponseHashes { .. } => 17,
+            ResponseVerificationError::UncertifiedResponseHeader { .. } => 26,
+            ResponseVerificationError::UncertifiedRedirectLocation { .. } => 33,
+            ResponseVerificationError::WildcardPermanentRedirect { .. } => 34,
+            ResponseVerificationError::InvalidResponseChunk { .. } => 28,
+            ResponseVerificationError::IncompleteChunkedResponse { .. } => 29,
+            ResponseVerificationError::MissingResponseChunks => 30,
+            ResponseVerificationError::DecodedBodyTooLarge { .. } => 31,
+            ResponseVerificationError::CompressionRatioTooHigh { .. } => 32,
+            ResponseVerificationError::MissingCertificate => 18,
+            ResponseVerificationError::MissingTree => 19,
+            ResponseVerificationError::MissingCertificateExpressionPath => 20,
+            ResponseVerificationError::MissingCertificateExpression => 21,
+            ResponseVerificationError::MissingCertification => 22,
+            ResponseVerificationError::CborDecodingFailed(_) => 23,
+            ResponseVerificationError::CertificateVerificationFailed(_) => 24,
+            ResponseVerificationError::HttpCertificationError(_) => 25,
+        }
+    }
+}
+
+/// The hash that was not found in the tree when validating the hashes of a request and response
+/// pair against the expression path provided by the "IC-Certificate" response header.
+///
+/// The hashes are looked up in order, so the first hash that was not found is reported, together
+/// with the hashes that are certified at the same position in the tree.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum ResponseHashesMismatch {
+    /// The hash of the "IC-CertificateExpression" response header was not found in the tree
+    #[error(r#"the hash of the "IC-CertificateExpression" response header ({expr_hash}) was not found in the tree, the certified expression hashes are {certified_expr_hashes:?}"#)]
+    ExpressionHashNotFound {
+        /// The hex encoded hash of the "IC-CertificateExpression" response header
+        expr_hash: String,
+        /// The hex encoded expression hashes certified for the expression path
+        certified_expr_hashes: Vec<String>,
+    },
+
+    /// The request hash was not found in the tree
+    #[error(r#"the request hash ({request_hash:?}) was not found in the tree, the certified request hashes are {certified_request_hashes:?}"#)]
+    RequestHashNotFound {
+        /// The hex encoded request hash, which is empty if the request is not certified
+        request_hash: String,
+        /// The hex encoded request hashes certified for the expression hash
+        certified_request_hashes: Vec<String>,
+    },
+
+    /// The response hash was not found in the tree
+    #[error(r#"the response hash ({response_hash}) was not found in the tree, the certified response hashes are {certified_response_hashes:?}"#)]
+    ResponseHashNotFound {
+        /// The hex encoded response hash
+        response_hash: String,
+        /// The hex encoded response hashes certified for the request hash
+        certified_response_hashes: Vec<String>,
+    },
+}
+
+/// The reason why a chunk of a chunked response could not be reassembled with the previous chunks.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum ResponseChunkMismatch {
+    /// The chunk was verified for a different request path than the previous chunks
+    #[error(r#"the chunk was verified for the request path {request_path:?}, but {expected_request_path:?} was expected"#)]
+    RequestPathMismatch {
+        /// The request path of the previous chunks
+        expected_request_path: String,
+        /// The request path of the chunk
+        request_path: String,
+    },
+
+    /// The certified "ETag" header of the chunk differs from the one of the previous chunks
+    #[error(r#"the chunk has the "ETag" header {etag:?}, but {expected_etag:?} was expected"#)]
+    ETagMismatch {
+        /// The "ETag" header of the previous chunks, if any
+        expected_etag: Option<String>,
+        /// The "ETag" header of the chunk, if any
+        etag: Option<String>,
+    },
+
+    /// The chunk is not a certified partial content response
+    #[error(r#"the status code of the chunk ({status_code:?}) is not a certified "206 Partial Content" status code"#)]
+    UnexpectedStatusCode {
+        /// The certified status code of the chunk, if any
+        status_code: Option<u16>,
+    },
+
+    /// The "Content-Range" header is missing from the certified headers of the chunk
+    #[error(r#"the "Content-Range" header is missing from the certified headers of the chunk"#)]
+    MissingContentRange,
+
+    /// The "Content-Range" header of the chunk could not be parsed
+    #[error(r#"the "Content-Range" header of the chunk ({content_range:?}) is malformed"#)]
+    MalformedContentRange {
+        /// The value of the "Content-Range" header
+        content_range: String,
+    },
+
+    /// The chunk does not start where the previous chunk ended
+    #[error(
+        r#"the chunk starts at byte {range_begin}, but byte {expected_range_begin} was expected"#
+    )]
+    DiscontinuousRange {
+        /// The first byte of the chunk that was expected from the previous chunks
+        expected_range_begin: usize,
+        /// The first byte of the chunk according to its "Content-Range" header
+        range_begin: usize,
+    },
+
+    /// The length of the full response body differs from the one of the previous chunks
+    #[error(r#"the chunk is part of a response body of {total_length} bytes, but {expected_total_length} bytes were expected"#)]
+    TotalLengthMismatch {
+        /// The length of the full response body according to the previous chunks
+        expected_total_length: usize,
+        /// The length of the full response body according to the chunk
+        total_length: usize,
+    },
+
+    /// The length of the body of the chunk differs from the length of its range
+    #[error(r#"the chunk has a body of {body_length} bytes, but its "Content-Range" header covers {range_length} bytes"#)]
+    BodyLengthMismatch {
+        /// The number of bytes covered by the "Content-Range" header of the chunk
+        range_length: usize,
+        /// The length of the body of the chunk
+        body_length: usize,
+    },
+}
+
 impl From<std::io::Error> for ResponseVerificationError {
     fn from(error: std::io::Error) -> Self {
         ResponseVerificationError::IoError(error.to_string())
@@ -185,72 +454,102 @@ impl From<std::io::Error> for ResponseVerificationError {
 }
 
 /// JS Representation of the ResponseVerificationError code
+///
+/// The numeric value of each code is the same as the one returned by
+/// [ResponseVerificationError::code] and does not change between releases.
 #[cfg(all(target_arch = "wasm32", feature = "js"))]
 #[wasm_bindgen(js_name = ResponseVerificationErrorCode)]
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum ResponseVerificationJsErrorCode {
     /// Error converting UTF-8 string
-    IoError,
+    IoError = 0,
     /// An unsupported verification version was requested
-    UnsupportedVerificationVersion,
+    UnsupportedVerificationVersion = 1,
     /// Mismatch between the minimum requested version and the actual requested version
-    RequestedVerificationVersionMismatch,
+    RequestedVerificationVersionMismatch = 2,
+    /// The actual requested version is higher than the maximum verification version of the
+    /// [VerificationPolicy](crate::types::VerificationPolicy)
+    RequestedVerificationVersionAboveMaximum = 27,
+    /// None of the verification versions offered by the canister is supported by this package
+    /// and accepted by the [VerificationPolicy](crate::types::VerificationPolicy)
+    NoCommonVerificationVersion = 35,
     /// Error parsing CEL expression
-    CelError,
+    CelError = 3,
     /// Error decoding base64
-    Base64DecodingError,
+    Base64DecodingError = 4,
     /// Error parsing int
-    ParseIntError,
+    ParseIntError = 5,
     /// The tree has different root hash from the expected value in the certified variables
-    InvalidTree,
+    InvalidTree = 6,
     /// The CEL expression path is invalid
-    InvalidExpressionPath,
+    InvalidExpressionPath = 7,
     /// The expression path provided by the "IC-Certificate" response header
     /// has an unexpected suffix and should end with "<$>" or "<*>"
-    UnexpectedExpressionPathPrefix,
+    UnexpectedExpressionPathPrefix = 8,
     /// The expression path provided by the "IC-Certificate" response header
     /// has an unexpected suffix and should end with "<$>" or "<*>"
-    UnexpectedExpressionPathSuffix,
+    UnexpectedExpressionPathSuffix = 9,
     /// The exact expression path provided by the "IC-Certificate" response header
     /// was not found in the tree
-    ExactExpressionPathNotFoundInTree,
+    ExactExpressionPathNotFoundInTree = 10,
     /// The exact expression path provided by the "IC-Certificate" response header
     /// is not valid for the request path
-    ExactExpressionPathMismatch,
+    ExactExpressionPathMismatch = 11,
     /// A wildcard expression path was provided by the "IC-Certificate" response header
     /// but a potential exact expression path is valid for the request path and might
     /// exist in the tree
-    ExactExpressionPathMightExistInTree,
+    ExactExpressionPathMightExistInTree = 12,
     /// The wildcard expression path provided by the "IC-Certificate" response
     /// was not found in the tree
-    WildcardExpressionPathNotFoundInTree,
+    WildcardExpressionPathNotFoundInTree = 13,
     /// The wildcard expression path provided by the "IC-Certificate" response
     /// header is not valid for the request path
-    WildcardExpressionPathMismatch,
+    WildcardExpressionPathMismatch = 14,
     /// A more specific wildcard expression path than the one provided by the
     /// "IC-Certificate" response header that is valid for the request path might
     /// exist in the tree
-    MoreSpecificWildcardExpressionMightExistInTree,
+    MoreSpecificWildcardExpressionMightExistInTree = 15,
     /// The response body was a mismatch from the expected values in the tree
-    InvalidResponseBody,
+    InvalidResponseBody = 16,
     /// The response hashes were a mismatch from the expected values in the tree
-    InvalidResponseHashes,
+    InvalidResponseHashes = 17,
+    /// A response header that is required to be certified by the
+    /// [VerificationPolicy](crate::types::VerificationPolicy) was excluded from certification
+    UncertifiedResponseHeader = 26,
+    /// A redirect response does not certify its "Location" header, as required by the
+    /// [VerificationPolicy](crate::types::VerificationPolicy)
+    UncertifiedRedirectLocation = 33,
+    /// A permanent redirect response is certified with a wildcard expression path, but the
+    /// [VerificationPolicy](crate::types::VerificationPolicy) requires an exact expression path
+    WildcardPermanentRedirect = 34,
+    /// A chunk of a chunked response could not be reassembled with the previous chunks
+    InvalidResponseChunk = 28,
+    /// The chunks of a chunked response do not cover the full response body
+    IncompleteChunkedResponse = 29,
+    /// No chunks were provided to reassemble a chunked response from
+    MissingResponseChunks = 30,
+    /// The decoded response body is larger than the maximum decoded body size of the
+    /// [VerificationPolicy](crate::types::VerificationPolicy)
+    DecodedBodyTooLarge = 31,
+    /// The decoded response body is larger than the maximum compression ratio of the
+    /// [VerificationPolicy](crate::types::VerificationPolicy) allows for the encoded body
+    CompressionRatioTooHigh = 32,
     /// The certificate was missing from the certification header
-    MissingCertificate,
+    MissingCertificate = 18,
     /// The tree was missing from the certification header
-    MissingTree,
+    MissingTree = 19,
     /// The certificate expression path was missing from the certification header
-    MissingCertificateExpressionPath,
+    MissingCertificateExpressionPath = 20,
     /// The certificate expression was missing from the response headers
-    MissingCertificateExpression,
+    MissingCertificateExpression = 21,
     /// The certification values could not be found in the response headers
-    MissingCertification,
+    MissingCertification = 22,
     /// Failed to decode CBOR
-    CborDecodingFailed,
+    CborDecodingFailed = 23,
     /// Failed to verify certificate
-    CertificateVerificationFailed,
+    CertificateVerificationFailed = 24,
     /// HTTP Certification error
-    HttpCertificationError,
+    HttpCertificationError = 25,
 }
 
 /// JS Representation of the ResponseVerificationError
@@ -278,6 +577,12 @@ impl From<ResponseVerificationError> for ResponseVerificationJsError {
             ResponseVerificationError::RequestedVerificationVersionMismatch { .. } => {
                 ResponseVerificationJsErrorCode::RequestedVerificationVersionMismatch
             }
+            ResponseVerificationError::RequestedVerificationVersionAboveMaximum { .. } => {
+                ResponseVerificationJsErrorCode::RequestedVerificationVersionAboveMaximum
+            }
+            ResponseVerificationError::NoCommonVerificationVersion { .. } => {
+                ResponseVerificationJsErrorCode::NoCommonVerificationVersion
+            }
             ResponseVerificationError::CelError(_) => ResponseVerificationJsErrorCode::CelError,
             ResponseVerificationError::Base64DecodingError(_) => {
                 ResponseVerificationJsErrorCode::Base64DecodingError
@@ -285,7 +590,9 @@ impl From<ResponseVerificationError> for ResponseVerificationJsError {
             ResponseVerificationError::ParseIntError(_) => {
                 ResponseVerificationJsErrorCode::ParseIntError
             }
-            ResponseVerificationError::InvalidTree => ResponseVerificationJsErrorCode::InvalidTree,
+            ResponseVerificationError::InvalidTree { .. } => {
+                ResponseVerificationJsErrorCode::InvalidTree
+            }
             ResponseVerificationError::InvalidExpressionPath => {
                 ResponseVerificationJsErrorCode::InvalidExpressionPath
             }
@@ -313,12 +620,36 @@ impl From<ResponseVerificationError> for ResponseVerificationJsError {
             ResponseVerificationError::MoreSpecificWildcardExpressionMightExistInTree {
                 ..
             } => ResponseVerificationJsErrorCode::MoreSpecificWildcardExpressionMightExistInTree,
-            ResponseVerificationError::InvalidResponseBody => {
+            ResponseVerificationError::InvalidResponseBody { .. } => {
                 ResponseVerificationJsErrorCode::InvalidResponseBody
             }
-            ResponseVerificationError::InvalidResponseHashes => {
+            ResponseVerificationError::InvalidResponseHashes { .. } => {
                 ResponseVerificationJsErrorCode::InvalidResponseHashes
             }
+            ResponseVerificationError::UncertifiedResponseHeader { .. } => {
+                ResponseVerificationJsErrorCode::UncertifiedResponseHeader
+            }
+            ResponseVerificationError::UncertifiedRedirectLocation { .. } => {
+                ResponseVerificationJsErrorCode::UncertifiedRedirectLocation
+            }
+            ResponseVerificationError::WildcardPermanentRedirect { .. } => {
+                ResponseVerificationJsErrorCode::WildcardPermanentRedirect
+            }
+            ResponseVerificationError::InvalidResponseChunk { .. } => {
+                ResponseVerificationJsErrorCode::InvalidResponseChunk
+            }
+            ResponseVerificationError::IncompleteChunkedResponse { .. } => {
+                ResponseVerificationJsErrorCode::IncompleteChunkedResponse
+            }
+            ResponseVerificationError::MissingResponseChunks => {
+                ResponseVerificationJsErrorCode::MissingResponseChunks
+            }
+            ResponseVerificationError::DecodedBodyTooLarge { .. } => {
+                ResponseVerificationJsErrorCode::DecodedBodyTooLarge
+            }
+            ResponseVerificationError::CompressionRatioTooHigh { .. } => {
+                ResponseVerificationJsErrorCode::CompressionRatioTooHigh
+            }
             ResponseVerificationError::MissingCertificate => {
                 ResponseVerificationJsErrorCode::MissingCertificate
             }
@@ -362,6 +693,15 @@ mod tests {
     use ic_response_verification_test_utils::hex_decode;
     use wasm_bindgen_test::wasm_bindgen_test;
 
+    #[wasm_bindgen_test]
+    fn error_into_code() {
+        for (error, expected_code) in code_tests::errors_with_codes() {
+            let result = ResponseVerificationJsError::from(error);
+
+            assert_eq!(result.code as u32, expected_code, "{}", result.message);
+        }
+    }
+
     #[wasm_bindgen_test]
     fn error_into_http_certification_error() {
         let error = ResponseVerificationError::HttpCertificationError(
@@ -456,6 +796,43 @@ mod tests {
         )
     }
 
+    #[wasm_bindgen_test]
+    fn error_into_verification_version_above_maximum() {
+        let error = ResponseVerificationError::RequestedVerificationVersionAboveMaximum {
+            max_verification_version: 1,
+            requested_version: 2,
+        };
+
+        let result = ResponseVerificationJsError::from(error);
+
+        assert_eq!(
+            result,
+            ResponseVerificationJsError {
+                code: ResponseVerificationJsErrorCode::RequestedVerificationVersionAboveMaximum,
+                message: r#"The requested verification version 2 is higher than the maximum verification version 1"#.into(),
+            }
+        )
+    }
+
+    #[wasm_bindgen_test]
+    fn error_into_no_common_verification_version() {
+        let error = ResponseVerificationError::NoCommonVerificationVersion {
+            offered_versions: vec![1],
+            min_accepted_version: 2,
+            max_accepted_version: 2,
+        };
+
+        let result = ResponseVerificationJsError::from(error);
+
+        assert_eq!(
+            result,
+            ResponseVerificationJsError {
+                code: ResponseVerificationJsErrorCode::NoCommonVerificationVersion,
+                message: r#"None of the offered verification versions [1] is within the accepted verification versions 2 to 2"#.into(),
+            }
+        )
+    }
+
     #[wasm_bindgen_test]
     fn error_into_cel_error() {
         let inner_error = CelParserError::CelSyntaxException(
@@ -514,14 +891,19 @@ mod tests {
 
     #[wasm_bindgen_test]
     fn error_into_invalid_tree_error() {
-        let error = ResponseVerificationError::InvalidTree;
+        let error = ResponseVerificationError::InvalidTree {
+            tree_root_hash: "aa".into(),
+            certified_data: Some("bb".into()),
+        };
         let result = ResponseVerificationJsError::from(error);
 
         assert_eq!(
             result,
             ResponseVerificationJsError {
                 code: ResponseVerificationJsErrorCode::InvalidTree,
-                message: format!(r#"Invalid tree root hash"#),
+                message: format!(
+                    r#"Invalid tree root hash, the root hash of the tree (aa) does not match the certified data of the canister (Some("bb"))"#
+                ),
             }
         )
     }
@@ -694,28 +1076,188 @@ mod tests {
 
     #[wasm_bindgen_test]
     fn error_into_invalid_response_body_error() {
-        let error = ResponseVerificationError::InvalidResponseBody;
+        let error = ResponseVerificationError::InvalidResponseBody {
+            request_path: "/".into(),
+            body_hash: "aa".into(),
+            certified_body_hash: None,
+        };
         let result = ResponseVerificationJsError::from(error);
 
         assert_eq!(
             result,
             ResponseVerificationJsError {
                 code: ResponseVerificationJsErrorCode::InvalidResponseBody,
-                message: format!(r#"Invalid response body"#),
+                message: format!(
+                    r#"Invalid response body, the hash of the response body (aa) does not match the hash certified for the request path ("/") in the tree (None)"#
+                ),
             }
         )
     }
 
     #[wasm_bindgen_test]
     fn error_into_invalid_response_hashes_error() {
-        let error = ResponseVerificationError::InvalidResponseHashes;
+        let error = ResponseVerificationError::InvalidResponseHashes {
+            provided_expr_path: vec!["http_expr".into(), "<*>".into()],
+            mismatch: ResponseHashesMismatch::ResponseHashNotFound {
+                response_hash: "aa".into(),
+                certified_response_hashes: vec!["bb".into()],
+            },
+            response_headers_hash: "cc".into(),
+            body_hash: "dd".into(),
+        };
         let result = ResponseVerificationJsError::from(error);
 
         assert_eq!(
             result,
             ResponseVerificationJsError {
                 code: ResponseVerificationJsErrorCode::InvalidResponseHashes,
-                message: format!(r#"Invalid response hashes"#),
+                message: format!(
+                    r#"Invalid response hashes for the expression path provided by the "IC-Certificate" response header (["http_expr", "<*>"]), the response hash (aa) was not found in the tree, the certified response hashes are ["bb"] (response headers hash: cc, body hash: dd)"#
+                ),
+            }
+        )
+    }
+
+    #[wasm_bindgen_test]
+    fn error_into_uncertified_response_header_error() {
+        let error = ResponseVerificationError::UncertifiedResponseHeader {
+            header_name: "Content-Type".into(),
+        };
+        let result = ResponseVerificationJsError::from(error);
+
+        assert_eq!(
+            result,
+            ResponseVerificationJsError {
+                code: ResponseVerificationJsErrorCode::UncertifiedResponseHeader,
+                message: format!(
+                    r#"The response header "Content-Type" is required to be certified by the verification policy, but it was excluded from certification"#
+                ),
+            }
+        )
+    }
+
+    #[wasm_bindgen_test]
+    fn error_into_uncertified_redirect_location_error() {
+        let error = ResponseVerificationError::UncertifiedRedirectLocation { status_code: 302 };
+        let result = ResponseVerificationJsError::from(error);
+
+        assert_eq!(
+            result,
+            ResponseVerificationJsError {
+                code: ResponseVerificationJsErrorCode::UncertifiedRedirectLocation,
+                message: format!(
+                    r#"The redirect response with status code 302 does not certify its "Location" header, which is required by the verification policy"#
+                ),
+            }
+        )
+    }
+
+    #[wasm_bindgen_test]
+    fn error_into_wildcard_permanent_redirect_error() {
+        let error = ResponseVerificationError::WildcardPermanentRedirect {
+            status_code: 301,
+            expr_path: vec!["http_expr".into(), "<*>".into()],
+        };
+        let result = ResponseVerificationJsError::from(error);
+
+        assert_eq!(
+            result,
+            ResponseVerificationJsError {
+                code: ResponseVerificationJsErrorCode::WildcardPermanentRedirect,
+                message: format!(
+                    r#"The permanent redirect response with status code 301 is certified with the wildcard expression path ["http_expr", "<*>"], but the verification policy requires an exact expression path"#
+                ),
+            }
+        )
+    }
+
+    #[wasm_bindgen_test]
+    fn error_into_invalid_response_chunk_error() {
+        let error = ResponseVerificationError::InvalidResponseChunk {
+            chunk_index: 1,
+            mismatch: ResponseChunkMismatch::DiscontinuousRange {
+                expected_range_begin: 10,
+                range_begin: 12,
+            },
+        };
+        let result = ResponseVerificationJsError::from(error);
+
+        assert_eq!(
+            result,
+            ResponseVerificationJsError {
+                code: ResponseVerificationJsErrorCode::InvalidResponseChunk,
+                message: format!(
+                    r#"Invalid response chunk at index 1, the chunk starts at byte 12, but byte 10 was expected"#
+                ),
+            }
+        )
+    }
+
+    #[wasm_bindgen_test]
+    fn error_into_incomplete_chunked_response_error() {
+        let error = ResponseVerificationError::IncompleteChunkedResponse {
+            length: 10,
+            total_length: 20,
+        };
+        let result = ResponseVerificationJsError::from(error);
+
+        assert_eq!(
+            result,
+            ResponseVerificationJsError {
+                code: ResponseVerificationJsErrorCode::IncompleteChunkedResponse,
+                message: format!(
+                    r#"Incomplete chunked response, the chunks cover 10 of the 20 bytes of the full response body"#
+                ),
+            }
+        )
+    }
+
+    #[wasm_bindgen_test]
+    fn error_into_missing_response_chunks_error() {
+        let error = ResponseVerificationError::MissingResponseChunks;
+        let result = ResponseVerificationJsError::from(error);
+
+        assert_eq!(
+            result,
+            ResponseVerificationJsError {
+                code: ResponseVerificationJsErrorCode::MissingResponseChunks,
+                message: format!(r#"Response chunks not found"#),
+            }
+        )
+    }
+
+    #[wasm_bindgen_test]
+    fn error_into_decoded_body_too_large_error() {
+        let error = ResponseVerificationError::DecodedBodyTooLarge {
+            max_decoded_body_size: 1024,
+        };
+        let result = ResponseVerificationJsError::from(error);
+
+        assert_eq!(
+            result,
+            ResponseVerificationJsError {
+                code: ResponseVerificationJsErrorCode::DecodedBodyTooLarge,
+                message: format!(
+                    r#"The decoded response body exceeds the maximum decoded body size of 1024 bytes"#
+                ),
+            }
+        )
+    }
+
+    #[wasm_bindgen_test]
+    fn error_into_compression_ratio_too_high_error() {
+        let error = ResponseVerificationError::CompressionRatioTooHigh {
+            max_compression_ratio: 100,
+        };
+        let result = ResponseVerificationJsError::from(error);
+
+        assert_eq!(
+            result,
+            ResponseVerificationJsError {
+                code: ResponseVerificationJsErrorCode::CompressionRatioTooHigh,
+                message: format!(
+                    r#"The decoded response body exceeds the maximum compression ratio of 100"#
+                ),
             }
         )
     }
@@ -824,3 +1366,290 @@ mod tests {
         )
     }
 }
+
+#[cfg(test)]
+mod code_tests {
+    use super::*;
+    use crate::cel::CelParserError;
+    use base64::{engine::general_purpose, Engine as _};
+    use ic_cbor::CborError;
+    use ic_certificate_verification::CertificateVerificationError;
+    use ic_http_certification::HttpCertificationError;
+
+    /// Returns an error of every variant of [ResponseVerificationError] with its expected code.
+    /// The codes are exposed to JavaScript as `ResponseVerificationErrorCode`, so they must never
+    /// change.
+    pub(super) fn errors_with_codes() -> Vec<(ResponseVerificationError, u32)> {
+        vec![
+            (ResponseVerificationError::IoError("IO error".into()), 0),
+            (
+                ResponseVerificationError::UnsupportedVerificationVersion {
+                    min_supported_version: 1,
+                    max_supported_version: 2,
+                    requested_version: 42,
+                },
+                1,
+            ),
+            (
+                ResponseVerificationError::RequestedVerificationVersionMismatch {
+                    min_requested_verification_version: 2,
+                    requested_version: 1,
+                },
+                2,
+            ),
+            (
+                ResponseVerificationError::CelError(CelParserError::CelSyntaxException(
+                    "Garbage is not allowed in the CEL expression!".into(),
+                )),
+                3,
+            ),
+            (
+                ResponseVerificationError::Base64DecodingError(
+                    general_purpose::STANDARD
+                        .decode("!")
+                        .expect_err("Expected error"),
+                ),
+                4,
+            ),
+            (
+                ResponseVerificationError::ParseIntError(
+                    "fortytwo".parse::<u8>().expect_err("Expected error"),
+                ),
+                5,
+            ),
+            (
+                ResponseVerificationError::InvalidTree {
+                    tree_root_hash: "00".into(),
+                    certified_data: None,
+                },
+                6,
+            ),
+            (ResponseVerificationError::InvalidExpressionPath, 7),
+            (
+                ResponseVerificationError::UnexpectedExpressionPathPrefix {
+                    provided_expr_path: vec!["http_expr".into()],
+                },
+                8,
+            ),
+            (
+                ResponseVerificationError::UnexpectedExpressionPathSuffix {
+                    provided_expr_path: vec!["http_expr".into()],
+                },
+                9,
+            ),
+            (
+                ResponseVerificationError::ExactExpressionPathNotFoundInTree {
+                    provided_expr_path: vec!["http_expr".into(), "<$>".into()],
+                },
+                10,
+            ),
+            (
+                ResponseVerificationError::ExactExpressionPathMismatch {
+                    provided_expr_path: vec!["http_expr".into(), "<$>".into()],
+                    request_path: "/".into(),
+                },
+                11,
+            ),
+            (
+                ResponseVerificationError::ExactExpressionPathMightExistInTree {
+                    provided_expr_path: vec!["http_expr".into(), "<*>".into()],
+                    potential_expr_path: vec!["http_expr".into(), "".into(), "<$>".into()],
+                    request_path: "/".into(),
+                },
+                12,
+            ),
+            (
+                ResponseVerificationError::WildcardExpressionPathNotFoundInTree {
+                    provided_expr_path: vec!["http_expr".into(), "<*>".into()],
+                    request_path: "/".into(),
+                },
+                13,
+            ),
+            (
+                ResponseVerificationError::WildcardExpressionPathMismatch {
+                    provided_expr_path: vec!["http_expr".into(), "<*>".into()],
+                    request_path: "/".into(),
+                },
+                14,
+            ),
+            (
+                ResponseVerificationError::MoreSpecificWildcardExpressionMightExistInTree {
+                    provided_expr_path: vec!["http_expr".into(), "<*>".into()],
+                    more_specific_expr_path: vec!["http_expr".into(), "".into(), "<*>".into()],
+                    request_path: "/".into(),
+                },
+                15,
+            ),
+            (
+                ResponseVerificationError::InvalidResponseBody {
+                    request_path: "/".into(),
+                    body_hash: "00".into(),
+                    certified_body_hash: None,
+                },
+                16,
+            ),
+            (
+                ResponseVerificationError::InvalidResponseHashes {
+                    provided_expr_path: vec!["http_expr".into(), "<$>".into()],
+                    mismatch: ResponseHashesMismatch::RequestHashNotFound {
+                        request_hash: "00".into(),
+                        certified_request_hashes: vec![],
+                    },
+                    response_headers_hash: "00".into(),
+                    body_hash: "00".into(),
+                },
+                17,
+            ),
+            (ResponseVerificationError::MissingCertificate, 18),
+            (ResponseVerificationError::MissingTree, 19),
+            (
+                ResponseVerificationError::MissingCertificateExpressionPath,
+                20,
+            ),
+            (ResponseVerificationError::MissingCertificateExpression, 21),
+            (ResponseVerificationError::MissingCertification, 22),
+            (
+                ResponseVerificationError::CborDecodingFailed(CborError::MalformedCbor(
+                    "HashTree CBOR is malformed".into(),
+                )),
+                23,
+            ),
+            (
+                ResponseVerificationError::CertificateVerificationFailed(
+                    CertificateVerificationError::MissingTimePathInTree {
+                        path: vec![b"time".to_vec()],
+                    },
+                ),
+                24,
+            ),
+            (
+                ResponseVerificationError::HttpCertificationError(
+                    HttpCertificationError::MalformedUrl("https://internetcomputer.org".into()),
+                ),
+                25,
+            ),
+            (
+                ResponseVerificationError::UncertifiedResponseHeader {
+                    header_name: "Set-Cookie".into(),
+                },
+                26,
+            ),
+            (
+                ResponseVerificationError::RequestedVerificationVersionAboveMaximum {
+                    max_verification_version: 1,
+                    requested_version: 2,
+                },
+                27,
+            ),
+            (
+                ResponseVerificationError::InvalidResponseChunk {
+                    chunk_index: 1,
+                    mismatch: ResponseChunkMismatch::ETagMismatch {
+                        expected_etag: Some("\"1\"".into()),
+                        etag: None,
+                    },
+                },
+                28,
+            ),
+            (
+                ResponseVerificationError::IncompleteChunkedResponse {
+                    length: 1,
+                    total_length: 2,
+                },
+                29,
+            ),
+            (ResponseVerificationError::MissingResponseChunks, 30),
+            (
+                ResponseVerificationError::DecodedBodyTooLarge {
+                    max_decoded_body_size: 1024,
+                },
+                31,
+            ),
+            (
+                ResponseVerificationError::CompressionRatioTooHigh {
+                    max_compression_ratio: 100,
+                },
+                32,
+            ),
+            (
+                ResponseVerificationError::UncertifiedRedirectLocation { status_code: 302 },
+                33,
+            ),
+            (
+                ResponseVerificationError::WildcardPermanentRedirect {
+                    status_code: 301,
+                    expr_path: vec!["http_expr".into(), "<*>".into()],
+                },
+                34,
+            ),
+            (
+                ResponseVerificationError::NoCommonVerificationVersion {
+                    offered_versions: vec![3],
+                    min_accepted_version: 1,
+                    max_accepted_version: 2,
+                },
+                35,
+            ),
+        ]
+    }
+
+    /// Returns the expected code of the error. The match has no wildcard arm, so adding a variant
+    /// to [ResponseVerificationError] fails to compile until it is given a code here.
+    fn expected_code(error: &ResponseVerificationError) -> u32 {
+        match error {
+            ResponseVerificationError::IoError(_) => 0,
+            ResponseVerificationError::UnsupportedVerificationVersion { .. } => 1,
+            ResponseVerificationError::RequestedVerificationVersionMismatch { .. } => 2,
+            ResponseVerificationError::CelError(_) => 3,
+            ResponseVerificationError::Base64DecodingError(_) => 4,
+            ResponseVerificationError::ParseIntError(_) => 5,
+            ResponseVerificationError::InvalidTree { .. } => 6,
+            ResponseVerificationError::InvalidExpressionPath => 7,
+            ResponseVerificationError::UnexpectedExpressionPathPrefix { .. } => 8,
+            ResponseVerificationError::UnexpectedExpressionPathSuffix { .. } => 9,
+            ResponseVerificationError::ExactExpressionPathNotFoundInTree { .. } => 10,
+            ResponseVerificationError::ExactExpressionPathMismatch { .. } => 11,
+            ResponseVerificationError::ExactExpressionPathMightExistInTree { .. } => 12,
+            ResponseVerificationError::WildcardExpressionPathNotFoundInTree { .. } => 13,
+            ResponseVerificationError::WildcardExpressionPathMismatch { .. } => 14,
+            ResponseVerificationError::MoreSpecificWildcardExpressionMightExistInTree {
+                ..
+            } => 15,
+            ResponseVerificationError::InvalidResponseBody { .. } => 16,
+            ResponseVerificationError::InvalidResponseHashes { .. } => 17,
+            ResponseVerificationError::MissingCertificate => 18,
+            ResponseVerificationError::MissingTree => 19,
+            ResponseVerificationError::MissingCertificateExpressionPath => 20,
+            ResponseVerificationError::MissingCertificateExpression => 21,
+            ResponseVerificationError::MissingCertification => 22,
+            ResponseVerificationError::CborDecodingFailed(_) => 23,
+            ResponseVerificationError::CertificateVerificationFailed(_) => 24,
+            ResponseVerificationError::HttpCertificationError(_) => 25,
+            ResponseVerificationError::UncertifiedResponseHeader { .. } => 26,
+            ResponseVerificationError::RequestedVerificationVersionAboveMaximum { .. } => 27,
+            ResponseVerificationError::InvalidResponseChunk { .. } => 28,
+            ResponseVerificationError::IncompleteChunkedResponse { .. } => 29,
+            ResponseVerificationError::MissingResponseChunks => 30,
+            ResponseVerificationError::DecodedBodyTooLarge { .. } => 31,
+            ResponseVerificationError::CompressionRatioTooHigh { .. } => 32,
+            ResponseVerificationError::UncertifiedRedirectLocation { .. } => 33,
+            ResponseVerificationError::WildcardPermanentRedirect { .. } => 34,
+            ResponseVerificationError::NoCommonVerificationVersion { .. } => 35,
+        }
+    }
+
+    #[test]
+    fn error_codes_are_stable() {
+        let errors_with_codes = errors_with_codes();
+
+        for (error, code) in &errors_with_codes {
+            assert_eq!(expected_code(error), *code, "{error:?}");
+            assert_eq!(error.code(), *code, "{error:?}");
+        }
+
+        // every code is used by exactly one variant, and no code is skipped
+        let mut codes: Vec<u32> = errors_with_codes.iter().map(|(_, code)| *code).collect();
+        codes.sort_unstable();
+        assert_eq!(codes, (0..codes.len() as u32).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,437 @@
+use crate::{error::ResponseVerificationResult, types::TimeConfig};
+use ic_cbor::CertificateToCbor;
+use ic_certificate_verification::verify_certificate_signature_and_delegation;
+use ic_certification::Certificate;
+use std::collections::{BTreeMap, HashMap};
+
+/// A cache of parsed certificates whose signature was already verified, keyed by the certificate
+/// of the `IC-Certificate` response header, to be used with
+/// [verify_request_response_pair_with_cache](crate::verify_request_response_pair_with_cache).
+///
+/// Responses that are served repeatedly with the same certificate, such as frequently requested
+/// assets, skip CBOR decoding and signature verification of the certificate after the first
+/// response. The time of the certificate is still checked for each response, so that cached
+/// certificates are only accepted within the allowed time offset.
+///
+/// Only certificates whose signature was successfully verified are added to the cache, so that
+/// forged or malformed certificates can not evict verified ones. When the cache is full, the least
+/// recently used certificate is evicted. The signature verification of a certificate is only
+/// reused for the same canister ID and root key.
+///
+/// # Examples
+///
+/// ```
+/// use ic_response_verification::types::CertificateCache;
+///
+/// let cache = CertificateCache::new(1_000);
+///
+/// assert_eq!(cache.capacity(), 1_000);
+/// assert!(cache.is_empty());
+/// ```
+#[derive(Debug)]
+pub struct CertificateCache {
+    capacity: Option<usize>,
+    certificates: HashMap<Vec<u8>, CachedCertificate>,
+    recently_used: BTreeMap<u64, Vec<u8>>,
+    clock: u64,
+}
+
+#[derive(Debug)]
+struct CachedCertificate {
+    certificate: Certificate,
+    verified_for: (Vec<u8>, Vec<u8>),
+    last_used: u64,
+}
+
+impl CertificateCache {
+    /// Creates a new, empty [CertificateCache] that holds at most `capacity` certificates.
+    /// A capacity of `0` disables the cache, so that every certificate is decoded and verified.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: Some(capacity),
+            certificates: HashMap::new(),
+            recently_used: BTreeMap::new(),
+            clock: 0,
+        }
+    }
+
+    /// Creates a new, empty [CertificateCache] without a capacity, for a single verification or
+    /// batch of verifications.
+    pub(crate) fn unbounded() -> Self {
+        Self {
+            capacity: None,
+            certificates: HashMap::new(),
+            recently_used: BTreeMap::new(),
+            clock: 0,
+        }
+    }
+
+    /// Returns the maximum number of certificates in the cache.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity.unwrap_or(usize::MAX)
+    }
+
+    /// Returns the number of certificates in the cache.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.certificates.len()
+    }
+
+    /// Returns true if the cache does not contain any certificates.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.certificates.is_empty()
+    }
+
+    /// Removes all certificates from the cache.
+    pub fn clear(&mut self) {
+        self.certificates.clear();
+        self.recently_used.clear();
+    }
+
+    /// Decodes the given CBOR encoded certificate, unless it is already in the cache. The
+    /// certificate is not added to the cache, since its signature has not been verified yet.
+    pub(crate) fn parse(
+        &self,
+        certificate_bytes: &[u8],
+    ) -> ResponseVerificationResult<Certificate> {
+        match self.certificates.get(certificate_bytes) {
+            Some(cached_certificate) => Ok(cached_certificate.certificate.clone()),
+            None => Ok(Certificate::from_cbor(certificate_bytes)?),
+        }
+    }
+
+    /// Verifies the given certificate, decoded from the given CBOR encoded certificate bytes. The
+    /// signature of the certificate is only verified if it was not already verified for the same
+    /// canister ID and root key, and the certificate is only added to the cache once its
+    /// signature was verified successfully.
+    pub(crate) fn verify(
+        &mut self,
+        certificate_bytes: &[u8],
+        certificate: &Certificate,
+        canister_id: &[u8],
+        ic_public_key: &[u8],
+        current_time_ns: u128,
+        time_config: &TimeConfig,
+    ) -> ResponseVerificationResult {
+        time_config.verify_certificate_time(certificate, current_time_ns)?;
+
+        let is_verified = self.certificates.get(certificate_bytes).is_some_and(
+            |CachedCertificate {
+                 verified_for: (verified_canister_id, verified_public_key),
+                 ..
+             }| {
+                verified_canister_id == canister_id && verified_public_key == ic_public_key
+            },
+        );
+        if is_verified {
+            self.mark_used(certificate_bytes);
+
+            return Ok(());
+        }
+
+        verify_certificate_signature_and_delegation(certificate, canister_id, ic_public_key)?;
+        self.insert(
+            certificate_bytes,
+            CachedCertificate {
+                certificate: certificate.clone(),
+                verified_for: (canister_id.to_vec(), ic_public_key.to_vec()),
+                last_used: 0,
+            },
+        );
+
+        Ok(())
+    }
+
+    fn mark_used(&mut self, certificate_bytes: &[u8]) {
+        let Some(cached_certificate) = self.certificates.get_mut(certificate_bytes) else {
+            return;
+        };
+
+        self.clock += 1;
+        if let Some(key) = self.recently_used.remove(&cached_certificate.last_used) {
+            self.recently_used.insert(self.clock, key);
+        }
+        cached_certificate.last_used = self.clock;
+    }
+
+    fn insert(&mut self, certificate_bytes: &[u8], mut cached_certificate: CachedCertificate) {
+        if self.capacity == Some(0) {
+            return;
+        }
+
+        self.clock += 1;
+        cached_certificate.last_used = self.clock;
+        if let Some(previous_certificate) = self
+            .certificates
+            .insert(certificate_bytes.to_vec(), cached_certificate)
+        {
+            self.recently_used.remove(&previous_certificate.last_used);
+        }
+        self.recently_used
+            .insert(self.clock, certificate_bytes.to_vec());
+
+        self.evict_least_recently_used();
+    }
+
+    fn evict_least_recently_used(&mut self) {
+        let Some(capacity) = self.capacity else {
+            return;
+        };
+
+        while self.certificates.len() > capacity {
+            let Some((_, certificate_bytes)) = self.recently_used.pop_first() else {
+                return;
+            };
+
+            self.certificates.remove(&certificate_bytes);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ResponseVerificationError;
+    use ic_certification_testing::{CertificateBuilder, CertificateData};
+    use ic_response_verification_test_utils::{
+        create_canister_id, create_certified_data, get_current_timestamp,
+    };
+
+    static CANISTER_ID: &str = "r7inp-6aaaa-aaaaa-aaabq-cai";
+    const MAX_CERT_TIME_OFFSET_NS: u128 = 300_000_000_000;
+
+    fn create_certificate(certified_data: &str, current_time: u128) -> (Vec<u8>, Vec<u8>) {
+        let canister_id = create_canister_id(CANISTER_ID);
+
+        let CertificateData {
+            cbor_encoded_certificate,
+            certificate: _,
+            root_key,
+        } = CertificateBuilder::new(
+            &canister_id.to_string(),
+            &create_certified_data(certified_data),
+        )
+        .unwrap()
+        .with_time(current_time)
+        .build()
+        .unwrap();
+
+        (cbor_encoded_certificate, root_key)
+    }
+
+    #[test]
+    fn verify_cached_certificate() {
+        let canister_id = create_canister_id(CANISTER_ID);
+        let current_time = get_current_timestamp();
+        let (certificate_bytes, root_key) = create_certificate(
+            "8160c07b45d617dba08a20eaa71ace28b5962965034b7539e42ebdb80da729a9",
+            current_time,
+        );
+        let mut cache = CertificateCache::new(10);
+
+        for _ in 0..2 {
+            let certificate = cache.parse(&certificate_bytes).unwrap();
+            cache
+                .verify(
+                    &certificate_bytes,
+                    &certificate,
+                    canister_id.as_ref(),
+                    &root_key,
+                    current_time,
+                    &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
+                )
+                .unwrap();
+        }
+
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn parse_does_not_cache_certificate() {
+        let current_time = get_current_timestamp();
+        let (certificate_bytes, _) = create_certificate(
+            "8160c07b45d617dba08a20eaa71ace28b5962965034b7539e42ebdb80da729a9",
+            current_time,
+        );
+        let cache = CertificateCache::new(10);
+
+        cache.parse(&certificate_bytes).unwrap();
+
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn verify_cached_certificate_checks_time() {
+        let canister_id = create_canister_id(CANISTER_ID);
+        let current_time = get_current_timestamp();
+        let (certificate_bytes, root_key) = create_certificate(
+            "8160c07b45d617dba08a20eaa71ace28b5962965034b7539e42ebdb80da729a9",
+            current_time,
+        );
+        let mut cache = CertificateCache::new(10);
+
+        verify(&mut cache, &certificate_bytes, &root_key, current_time).unwrap();
+        let result = verify(
+            &mut cache,
+            &certificate_bytes,
+            &root_key,
+            current_time + 2 * MAX_CERT_TIME_OFFSET_NS,
+        );
+
+        assert!(matches!(
+            result,
+            Err(ResponseVerificationError::CertificateVerificationFailed(_))
+        ));
+    }
+
+    #[test]
+    fn verify_cached_certificate_with_other_root_key() {
+        let current_time = get_current_timestamp();
+        let (certificate_bytes, root_key) = create_certificate(
+            "8160c07b45d617dba08a20eaa71ace28b5962965034b7539e42ebdb80da729a9",
+            current_time,
+        );
+        let (_, other_root_key) = create_certificate(
+            "8160c07b45d617dba08a20eaa71ace28b5962965034b7539e42ebdb80da729a9",
+            current_time,
+        );
+        let mut cache = CertificateCache::new(10);
+
+        verify(&mut cache, &certificate_bytes, &root_key, current_time).unwrap();
+        let result = verify(
+            &mut cache,
+            &certificate_bytes,
+            &other_root_key,
+            current_time,
+        );
+
+        assert!(matches!(
+            result,
+            Err(ResponseVerificationError::CertificateVerificationFailed(_))
+        ));
+    }
+
+    #[test]
+    fn unverified_certificates_do_not_evict_verified_certificates() {
+        let current_time = get_current_timestamp();
+        let (certificate_bytes, root_key) = create_certificate(
+            "8160c07b45d617dba08a20eaa71ace28b5962965034b7539e42ebdb80da729a9",
+            current_time,
+        );
+        let (forged_certificate_bytes, _) = create_certificate(
+            "2f4e5ce1e1c4d93c3e6fe0c73a4ef2ac8a6b1e5a6a7c3b9d0f0e1d2c3b4a5968",
+            current_time,
+        );
+        let mut cache = CertificateCache::new(1);
+
+        verify(&mut cache, &certificate_bytes, &root_key, current_time).unwrap();
+
+        let result = verify(
+            &mut cache,
+            &forged_certificate_bytes,
+            &root_key,
+            current_time,
+        );
+        assert!(matches!(
+            result,
+            Err(ResponseVerificationError::CertificateVerificationFailed(_))
+        ));
+        assert!(cache.parse(&[0xff, 0x00]).is_err());
+
+        assert_eq!(cache.len(), 1);
+        assert!(cache.certificates.contains_key(&certificate_bytes));
+    }
+
+    #[test]
+    fn zero_capacity_disables_cache() {
+        let current_time = get_current_timestamp();
+        let (certificate_bytes, root_key) = create_certificate(
+            "8160c07b45d617dba08a20eaa71ace28b5962965034b7539e42ebdb80da729a9",
+            current_time,
+        );
+        let mut cache = CertificateCache::new(0);
+
+        for _ in 0..2 {
+            verify(&mut cache, &certificate_bytes, &root_key, current_time).unwrap();
+
+            assert!(cache.is_empty());
+            assert!(cache.recently_used.is_empty());
+        }
+    }
+
+    #[test]
+    fn evict_least_recently_used_certificate() {
+        let current_time = get_current_timestamp();
+        let (first_certificate, first_root_key) = create_certificate(
+            "8160c07b45d617dba08a20eaa71ace28b5962965034b7539e42ebdb80da729a9",
+            current_time,
+        );
+        let (second_certificate, second_root_key) = create_certificate(
+            "2f4e5ce1e1c4d93c3e6fe0c73a4ef2ac8a6b1e5a6a7c3b9d0f0e1d2c3b4a5968",
+            current_time,
+        );
+        let (third_certificate, third_root_key) = create_certificate(
+            "a3c1f4b0e2d5c6b7a8e9f0d1c2b3a4958677e6d5c4b3a29180f7e6d5c4b3a291",
+            current_time,
+        );
+        let mut cache = CertificateCache::new(2);
+
+        verify(
+            &mut cache,
+            &first_certificate,
+            &first_root_key,
+            current_time,
+        )
+        .unwrap();
+        verify(
+            &mut cache,
+            &second_certificate,
+            &second_root_key,
+            current_time,
+        )
+        .unwrap();
+        verify(
+            &mut cache,
+            &first_certificate,
+            &first_root_key,
+            current_time,
+        )
+        .unwrap();
+        verify(
+            &mut cache,
+            &third_certificate,
+            &third_root_key,
+            current_time,
+        )
+        .unwrap();
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.certificates.contains_key(&first_certificate));
+        assert!(!cache.certificates.contains_key(&second_certificate));
+        assert!(cache.certificates.contains_key(&third_certificate));
+        assert_eq!(
+            cache.recently_used,
+            BTreeMap::from([(3, first_certificate), (4, third_certificate)])
+        );
+    }
+
+    fn verify(
+        cache: &mut CertificateCache,
+        certificate_bytes: &[u8],
+        root_key: &[u8],
+        current_time: u128,
+    ) -> ResponseVerificationResult {
+        let certificate = cache.parse(certificate_bytes)?;
+
+        cache.verify(
+            certificate_bytes,
+            &certificate,
+            create_canister_id(CANISTER_ID).as_ref(),
+            root_key,
+            current_time,
+            &TimeConfig::new(MAX_CERT_TIME_OFFSET_NS),
+        )
+    }
+}
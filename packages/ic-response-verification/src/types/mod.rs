@@ -7,3 +7,15 @@ pub use verification_result::*;
 /// Types to represent a certified response that clients can use to determine which parts of a response are safe to use.
 mod verified_response;
 pub use verified_response::*;
+
+/// Types to represent the requirements that a response must meet to pass verification.
+mod verification_policy;
+pub use verification_policy::*;
+
+/// Types to cache certificates that were already verified across multiple verifications.
+mod certificate_cache;
+pub use certificate_cache::*;
+
+/// Types to configure the current time that certificates are verified against.
+mod time_config;
+pub use time_config::*;
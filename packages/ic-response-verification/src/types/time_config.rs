@@ -0,0 +1,217 @@
+use crate::ResponseVerificationResult;
+use ic_certificate_verification::verify_certificate_time_range;
+use ic_certification::Certificate;
+
+/// A source of the current time that certificates are verified against.
+///
+/// Use [FixedTimeSource] to verify against a known time, for example to replay recorded traffic or
+/// in tests, or [SystemTimeSource] to verify against the system clock. Closures returning the
+/// current time in nanoseconds since the UNIX epoch are time sources too.
+///
+/// # Examples
+///
+/// ```
+/// use ic_response_verification::types::{FixedTimeSource, TimeSource};
+///
+/// let time_source = FixedTimeSource::new(1_700_000_000_000_000_000);
+/// assert_eq!(time_source.current_time_ns(), 1_700_000_000_000_000_000);
+///
+/// let time_source = || 1_700_000_000_000_000_000u128;
+/// assert_eq!(time_source.current_time_ns(), 1_700_000_000_000_000_000);
+/// ```
+pub trait TimeSource {
+    /// Returns the current time in nanoseconds since the UNIX epoch.
+    fn current_time_ns(&self) -> u128;
+}
+
+impl<F: Fn() -> u128> TimeSource for F {
+    fn current_time_ns(&self) -> u128 {
+        self()
+    }
+}
+
+/// A [TimeSource] that always returns the same time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedTimeSource {
+    current_time_ns: u128,
+}
+
+impl FixedTimeSource {
+    /// Creates a new [FixedTimeSource] that returns the given time in nanoseconds since the UNIX
+    /// epoch.
+    pub fn new(current_time_ns: u128) -> Self {
+        Self { current_time_ns }
+    }
+}
+
+impl TimeSource for FixedTimeSource {
+    fn current_time_ns(&self) -> u128 {
+        self.current_time_ns
+    }
+}
+
+/// A [TimeSource] that returns the time of the system clock.
+///
+/// This time source is not available for `wasm32` targets, where the system clock is not
+/// accessible from Rust.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SystemTimeSource;
+
+#[cfg(not(target_arch = "wasm32"))]
+impl TimeSource for SystemTimeSource {
+    fn current_time_ns(&self) -> u128 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|duration| duration.as_nanos())
+            .unwrap_or_default()
+    }
+}
+
+/// Configures how the time of a certificate is verified against the current time of a
+/// [TimeSource].
+///
+/// By default, certificates may be up to five minutes older or newer than the current time.
+///
+/// # Examples
+///
+/// ```
+/// use ic_response_verification::types::TimeConfig;
+///
+/// let time_config = TimeConfig::new(60_000_000_000).with_allow_future_certificates(true);
+///
+/// assert_eq!(time_config.max_clock_skew_ns(), 60_000_000_000);
+/// assert!(time_config.allow_future_certificates());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeConfig {
+    max_clock_skew_ns: u128,
+    allow_future_certificates: bool,
+}
+
+impl TimeConfig {
+    /// The default maximum clock skew of five minutes.
+    pub const DEFAULT_MAX_CLOCK_SKEW_NS: u128 = 300_000_000_000;
+
+    /// Creates a new [TimeConfig] that accepts certificates whose time differs from the current
+    /// time by at most `max_clock_skew_ns` nanoseconds.
+    pub fn new(max_clock_skew_ns: u128) -> Self {
+        Self {
+            max_clock_skew_ns,
+            allow_future_certificates: false,
+        }
+    }
+
+    /// Sets whether certificates whose time is ahead of the current time by more than the
+    /// maximum clock skew are accepted, for example when the clock of the verifier is known to
+    /// lag behind. Certificates that are too old are rejected either way.
+    pub fn with_allow_future_certificates(mut self, allow_future_certificates: bool) -> Self {
+        self.allow_future_certificates = allow_future_certificates;
+
+        self
+    }
+
+    /// Returns the maximum difference between the time of a certificate and the current time, in
+    /// nanoseconds.
+    #[inline]
+    pub fn max_clock_skew_ns(&self) -> u128 {
+        self.max_clock_skew_ns
+    }
+
+    /// Returns whether certificates whose time is too far in the future are accepted.
+    #[inline]
+    pub fn allow_future_certificates(&self) -> bool {
+        self.allow_future_certificates
+    }
+
+    /// Verifies the time of the given certificate against the given current time.
+    pub(crate) fn verify_certificate_time(
+        &self,
+        certificate: &Certificate,
+        current_time_ns: u128,
+    ) -> ResponseVerificationResult {
+        let min_certificate_time = current_time_ns.saturating_sub(self.max_clock_skew_ns);
+        let max_certificate_time = (!self.allow_future_certificates)
+            .then(|| current_time_ns.saturating_add(self.max_clock_skew_ns));
+
+        verify_certificate_time_range(certificate, min_certificate_time, max_certificate_time)?;
+
+        Ok(())
+    }
+}
+
+impl Default for TimeConfig {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_MAX_CLOCK_SKEW_NS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ResponseVerificationError;
+    use ic_cbor::CertificateToCbor;
+    use ic_certificate_verification::CertificateVerificationError;
+    use ic_certification_testing::{CertificateBuilder, CertificateData};
+    use ic_response_verification_test_utils::{
+        create_canister_id, get_current_timestamp, AssetTree,
+    };
+
+    const MAX_CLOCK_SKEW_NS: u128 = 60_000_000_000;
+
+    fn create_certificate(certificate_time: u128) -> Certificate {
+        let canister_id = create_canister_id("r7inp-6aaaa-aaaaa-aaabq-cai");
+
+        let CertificateData {
+            cbor_encoded_certificate,
+            certificate: _,
+            root_key: _,
+        } = CertificateBuilder::new(
+            &canister_id.to_string(),
+            &AssetTree::default().get_certified_data(),
+        )
+        .unwrap()
+        .with_time(certificate_time)
+        .build()
+        .unwrap();
+
+        Certificate::from_cbor(&cbor_encoded_certificate).unwrap()
+    }
+
+    #[test]
+    fn verify_future_certificate_time() {
+        let current_time = get_current_timestamp();
+        let certificate = create_certificate(current_time + 2 * MAX_CLOCK_SKEW_NS);
+
+        let result =
+            TimeConfig::new(MAX_CLOCK_SKEW_NS).verify_certificate_time(&certificate, current_time);
+        assert!(matches!(
+            result,
+            Err(ResponseVerificationError::CertificateVerificationFailed(
+                CertificateVerificationError::TimeTooFarInTheFuture { .. }
+            ))
+        ));
+
+        TimeConfig::new(MAX_CLOCK_SKEW_NS)
+            .with_allow_future_certificates(true)
+            .verify_certificate_time(&certificate, current_time)
+            .unwrap();
+    }
+
+    #[test]
+    fn verify_past_certificate_time() {
+        let current_time = get_current_timestamp();
+        let certificate = create_certificate(current_time - 2 * MAX_CLOCK_SKEW_NS);
+
+        let result = TimeConfig::new(MAX_CLOCK_SKEW_NS)
+            .with_allow_future_certificates(true)
+            .verify_certificate_time(&certificate, current_time);
+
+        assert!(matches!(
+            result,
+            Err(ResponseVerificationError::CertificateVerificationFailed(
+                CertificateVerificationError::TimeTooFarInThePast { .. }
+            ))
+        ));
+    }
+}
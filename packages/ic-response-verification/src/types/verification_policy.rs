@@ -0,0 +1,402 @@
+use crate::{ResponseVerificationError, ResponseVerificationResult};
+use http::{header, StatusCode};
+use ic_http_certification::{
+    utils::EXACT_PATH_TERMINATOR, DefaultResponseCertification, CERTIFICATE_EXPRESSION_HEADER_NAME,
+};
+
+/// Requirements that a response must meet to pass verification, in addition to the checks
+/// performed by [verify_request_response_pair](crate::verify_request_response_pair).
+///
+/// The [default](VerificationPolicy::default) policy has no additional requirements.
+///
+/// # Examples
+///
+/// ```
+/// use ic_response_verification::types::VerificationPolicy;
+///
+/// let policy = VerificationPolicy::new()
+///     .with_required_certified_response_headers(vec!["Content-Type", "Cache-Control"])
+///     .with_max_verification_version(2);
+///
+/// assert_eq!(
+///     policy.required_certified_response_headers(),
+///     &["Content-Type".to_string(), "Cache-Control".to_string()]
+/// );
+/// assert_eq!(policy.max_verification_version(), Some(2));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerificationPolicy {
+    required_certified_response_headers: Vec<String>,
+    max_verification_version: Option<u8>,
+    max_decoded_body_size: Option<usize>,
+    max_compression_ratio: Option<usize>,
+    ignored_response_headers: Vec<String>,
+    certified_redirects: bool,
+}
+
+impl VerificationPolicy {
+    /// Creates a new [VerificationPolicy] without any additional requirements.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requires that the response headers with the given names are certified, comparing header
+    /// names case-insensitively.
+    ///
+    /// Verification fails with [ResponseVerificationError::UncertifiedResponseHeader] if the
+    /// canister's CEL expression excludes any of these headers from certification, or if the
+    /// response is verified with a version of response verification that does not certify
+    /// response headers. A required header that is missing from the response still passes, as
+    /// long as it is certified, because its absence is then certified too.
+    pub fn with_required_certified_response_headers(
+        mut self,
+        header_names: Vec<impl Into<String>>,
+    ) -> Self {
+        self.required_certified_response_headers =
+            header_names.into_iter().map(Into::into).collect();
+
+        self
+    }
+
+    /// Returns the names of the response headers that are required to be certified.
+    #[inline]
+    pub fn required_certified_response_headers(&self) -> &[String] {
+        &self.required_certified_response_headers
+    }
+
+    /// Sets the maximum verification version that responses may be certified with, as the
+    /// counterpart of the minimum requested verification version.
+    ///
+    /// Verification fails with
+    /// [ResponseVerificationError::RequestedVerificationVersionAboveMaximum] if the response is
+    /// certified with a higher version, so that gateways can roll out support for new versions
+    /// gradually. By default, all [supported](crate::MAX_VERIFICATION_VERSION) versions are
+    /// accepted.
+    pub fn with_max_verification_version(mut self, max_verification_version: u8) -> Self {
+        self.max_verification_version = Some(max_verification_version);
+
+        self
+    }
+
+    /// Returns the maximum verification version that responses may be certified with, if any.
+    #[inline]
+    pub fn max_verification_version(&self) -> Option<u8> {
+        self.max_verification_version
+    }
+
+    /// Sets the maximum size of a response body after it is decoded according to its
+    /// `Content-Encoding` header, in bytes.
+    ///
+    /// Only response bodies that are verified with version 1 of response verification are
+    /// decoded. Verification fails with [ResponseVerificationError::DecodedBodyTooLarge] as soon
+    /// as the decoded body exceeds this size, so that a small, highly compressed body cannot
+    /// keep the verifier busy decoding it. By default, the decoded body size is not limited.
+    pub fn with_max_decoded_body_size(mut self, max_decoded_body_size: usize) -> Self {
+        self.max_decoded_body_size = Some(max_decoded_body_size);
+
+        self
+    }
+
+    /// Returns the maximum size of a decoded response body in bytes, if any.
+    #[inline]
+    pub fn max_decoded_body_size(&self) -> Option<usize> {
+        self.max_decoded_body_size
+    }
+
+    /// Sets the maximum ratio between the size of a decoded response body and the size of the
+    /// encoded response body.
+    ///
+    /// The ratio is checked while the body is decoded, against the part of the encoded body that
+    /// was received so far. Verification fails with
+    /// [ResponseVerificationError::CompressionRatioTooHigh] as soon as the decoded body exceeds
+    /// this ratio. By default, the compression ratio is not limited.
+    pub fn with_max_compression_ratio(mut self, max_compression_ratio: usize) -> Self {
+        self.max_compression_ratio = Some(max_compression_ratio);
+
+        self
+    }
+
+    /// Returns the maximum ratio between the size of a decoded response body and the size of the
+    /// encoded response body, if any.
+    #[inline]
+    pub fn max_compression_ratio(&self) -> Option<usize> {
+        self.max_compression_ratio
+    }
+
+    /// Ignores the response headers with the given names when verifying the response, comparing
+    /// header names case-insensitively.
+    ///
+    /// This is meant for headers that are known to be added by intermediaries between the replica
+    /// and the verifier, such as `Date` or `X-Request-Id`, which would otherwise fail verification
+    /// of responses that certify all headers except for a list of exclusions. Ignored headers are
+    /// left out of the certified response headers and of the verified response. A response whose
+    /// canister certifies one of these headers fails verification, so only headers that the
+    /// canister never sets should be ignored. The `IC-CertificateExpression` header is never
+    /// ignored.
+    pub fn with_ignored_response_headers(mut self, header_names: Vec<impl Into<String>>) -> Self {
+        self.ignored_response_headers = header_names.into_iter().map(Into::into).collect();
+
+        self
+    }
+
+    /// Returns the names of the response headers that are ignored when verifying the response.
+    #[inline]
+    pub fn ignored_response_headers(&self) -> &[String] {
+        &self.ignored_response_headers
+    }
+
+    /// Sets whether redirect responses are required to certify where they redirect to.
+    ///
+    /// If enabled, verification of a redirect response fails with
+    /// [ResponseVerificationError::UncertifiedRedirectLocation] unless its `Location` header is
+    /// certified, which also requires a version of response verification that certifies response
+    /// headers. Clients cache permanent redirects (`301` and `308`) indefinitely, so these must
+    /// additionally be certified with an exact expression path, otherwise verification fails with
+    /// [ResponseVerificationError::WildcardPermanentRedirect]. This prevents a wildcard
+    /// certification, such as the fallback of a single page application, from certifying a
+    /// redirect for every path below it. Since ignored response headers are not verified, a
+    /// redirect also fails verification if the `Location` header is one of the
+    /// [ignored response headers](VerificationPolicy::with_ignored_response_headers). By
+    /// default, redirects are not validated.
+    pub fn with_certified_redirects(mut self, certified_redirects: bool) -> Self {
+        self.certified_redirects = certified_redirects;
+
+        self
+    }
+
+    /// Returns whether redirect responses are required to certify where they redirect to.
+    #[inline]
+    pub fn certified_redirects(&self) -> bool {
+        self.certified_redirects
+    }
+
+    /// Removes the response headers that are ignored by the policy from the given response
+    /// headers.
+    pub(crate) fn remove_ignored_response_headers(&self, headers: &mut Vec<(String, String)>) {
+        headers.retain(|(header_name, _)| !self.is_ignored_response_header(header_name));
+    }
+
+    fn is_ignored_response_header(&self, header_name: &str) -> bool {
+        !header_name.eq_ignore_ascii_case(CERTIFICATE_EXPRESSION_HEADER_NAME)
+            && self
+                .ignored_response_headers
+                .iter()
+                .any(|ignored_header_name| ignored_header_name.eq_ignore_ascii_case(header_name))
+    }
+
+    /// Validates the policy against a response with the given status code, whose response
+    /// certification is [None] if no response headers are certified, and whose certification was
+    /// found at the given expression path.
+    pub(crate) fn validate_redirect(
+        &self,
+        status_code: u16,
+        response_certification: Option<&DefaultResponseCertification>,
+        expr_path: &[String],
+    ) -> ResponseVerificationResult {
+        if !self.certified_redirects {
+            return Ok(());
+        }
+
+        let Ok(status_code) = StatusCode::from_u16(status_code) else {
+            return Ok(());
+        };
+
+        let is_permanent_redirect = matches!(
+            status_code,
+            StatusCode::MOVED_PERMANENTLY | StatusCode::PERMANENT_REDIRECT
+        );
+        let is_redirect = is_permanent_redirect
+            || matches!(
+                status_code,
+                StatusCode::FOUND | StatusCode::SEE_OTHER | StatusCode::TEMPORARY_REDIRECT
+            );
+        if !is_redirect {
+            return Ok(());
+        }
+
+        let is_location_certified = !self.is_ignored_response_header(header::LOCATION.as_str())
+            && response_certification.is_some_and(|response_certification| {
+                response_certification.is_header_certified(header::LOCATION.as_str())
+            });
+        if !is_location_certified {
+            return Err(ResponseVerificationError::UncertifiedRedirectLocation {
+                status_code: status_code.as_u16(),
+            });
+        }
+
+        let is_exact_expr_path = expr_path
+            .last()
+            .is_some_and(|terminator| terminator == EXACT_PATH_TERMINATOR);
+        if is_permanent_redirect && !is_exact_expr_path {
+            return Err(ResponseVerificationError::WildcardPermanentRedirect {
+                status_code: status_code.as_u16(),
+                expr_path: expr_path.to_vec(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Validates the policy against the response certification of the canister's CEL expression,
+    /// which is [None] if no response headers are certified.
+    pub(crate) fn validate(
+        &self,
+        response_certification: Option<&DefaultResponseCertification>,
+    ) -> ResponseVerificationResult {
+        let uncertified_header =
+            self.required_certified_response_headers
+                .iter()
+                .find(|header_name| {
+                    !response_certification.is_some_and(|response_certification| {
+                        response_certification.is_header_certified(header_name)
+                    })
+                });
+
+        match uncertified_header {
+            Some(header_name) => Err(ResponseVerificationError::UncertifiedResponseHeader {
+                header_name: header_name.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_policy_is_always_valid() {
+        let policy = VerificationPolicy::default();
+
+        assert!(policy.validate(None).is_ok());
+        assert!(policy
+            .validate(Some(
+                &DefaultResponseCertification::certified_response_headers(vec![])
+            ))
+            .is_ok());
+    }
+
+    #[test]
+    fn required_headers_must_be_certified() {
+        let policy = VerificationPolicy::new()
+            .with_required_certified_response_headers(vec!["Content-Type", "Cache-Control"]);
+
+        assert!(policy
+            .validate(Some(
+                &DefaultResponseCertification::certified_response_headers(vec![
+                    "cache-control",
+                    "content-type",
+                ])
+            ))
+            .is_ok());
+        assert!(policy
+            .validate(Some(
+                &DefaultResponseCertification::response_header_exclusions(vec!["Date"])
+            ))
+            .is_ok());
+
+        assert!(matches!(
+            policy.validate(Some(
+                &DefaultResponseCertification::certified_response_headers(vec!["Content-Type"])
+            )),
+            Err(ResponseVerificationError::UncertifiedResponseHeader { header_name }) if header_name == "Cache-Control"
+        ));
+        assert!(matches!(
+            policy.validate(Some(
+                &DefaultResponseCertification::response_header_exclusions(vec!["content-type"])
+            )),
+            Err(ResponseVerificationError::UncertifiedResponseHeader { header_name }) if header_name == "Content-Type"
+        ));
+        assert!(matches!(
+            policy.validate(None),
+            Err(ResponseVerificationError::UncertifiedResponseHeader { header_name }) if header_name == "Content-Type"
+        ));
+    }
+
+    #[test]
+    fn ignored_headers_are_removed() {
+        let policy = VerificationPolicy::new().with_ignored_response_headers(vec![
+            "Date",
+            "x-request-id",
+            CERTIFICATE_EXPRESSION_HEADER_NAME,
+        ]);
+        let mut headers = vec![
+            (
+                "date".to_string(),
+                "Wed, 21 Oct 2015 07:28:00 GMT".to_string(),
+            ),
+            ("content-type".to_string(), "text/plain".to_string()),
+            ("X-Request-Id".to_string(), "1234".to_string()),
+            (
+                CERTIFICATE_EXPRESSION_HEADER_NAME.to_lowercase(),
+                "default_certification(ValidationArgs{no_certification:Empty{}})".to_string(),
+            ),
+        ];
+
+        policy.remove_ignored_response_headers(&mut headers);
+
+        assert_eq!(
+            headers,
+            vec![
+                ("content-type".to_string(), "text/plain".to_string()),
+                (
+                    CERTIFICATE_EXPRESSION_HEADER_NAME.to_lowercase(),
+                    "default_certification(ValidationArgs{no_certification:Empty{}})".to_string(),
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn redirects_must_certify_location() {
+        let policy = VerificationPolicy::new().with_certified_redirects(true);
+        let exact_expr_path = vec!["http_expr".to_string(), "<$>".to_string()];
+        let wildcard_expr_path = vec!["http_expr".to_string(), "<*>".to_string()];
+        let location_certified =
+            DefaultResponseCertification::certified_response_headers(vec!["Location"]);
+        let location_excluded =
+            DefaultResponseCertification::response_header_exclusions(vec!["location"]);
+
+        assert!(VerificationPolicy::default()
+            .validate_redirect(302, None, &wildcard_expr_path)
+            .is_ok());
+        assert!(policy.validate_redirect(200, None, &[]).is_ok());
+        assert!(policy.validate_redirect(304, None, &[]).is_ok());
+        assert!(policy
+            .validate_redirect(302, Some(&location_certified), &wildcard_expr_path)
+            .is_ok());
+        assert!(policy
+            .validate_redirect(308, Some(&location_certified), &exact_expr_path)
+            .is_ok());
+
+        assert!(matches!(
+            policy.validate_redirect(307, None, &[]),
+            Err(ResponseVerificationError::UncertifiedRedirectLocation { status_code: 307 })
+        ));
+        assert!(matches!(
+            policy.validate_redirect(302, Some(&location_excluded), &exact_expr_path),
+            Err(ResponseVerificationError::UncertifiedRedirectLocation { status_code: 302 })
+        ));
+        assert!(matches!(
+            policy.validate_redirect(301, Some(&location_certified), &wildcard_expr_path),
+            Err(ResponseVerificationError::WildcardPermanentRedirect { status_code: 301, expr_path }) if expr_path == wildcard_expr_path
+        ));
+    }
+
+    #[test]
+    fn redirects_must_not_ignore_location() {
+        let policy = VerificationPolicy::new()
+            .with_certified_redirects(true)
+            .with_ignored_response_headers(vec!["LOCATION"]);
+        let exact_expr_path = vec!["http_expr".to_string(), "<$>".to_string()];
+        let location_certified =
+            DefaultResponseCertification::certified_response_headers(vec!["Location"]);
+
+        assert!(policy.validate_redirect(200, None, &[]).is_ok());
+        assert!(matches!(
+            policy.validate_redirect(302, Some(&location_certified), &exact_expr_path),
+            Err(ResponseVerificationError::UncertifiedRedirectLocation { status_code: 302 })
+        ));
+    }
+}
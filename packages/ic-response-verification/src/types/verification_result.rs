@@ -1,4 +1,6 @@
 use crate::types::VerifiedResponse;
+use candid::Principal;
+use ic_certification::{Certificate, HashTree};
 
 #[cfg(all(target_arch = "wasm32", feature = "js"))]
 use wasm_bindgen::prelude::*;
@@ -21,6 +23,30 @@ pub struct VerificationInfo {
     pub response: Option<VerifiedResponse>,
     /// The version of verification that was used to verify the response
     pub verification_version: u16,
+    /// The certificate and tree of the `IC-Certificate` header that the response was verified
+    /// against. This is only available to Rust consumers and is not passed on to JavaScript.
+    pub certification: VerifiedCertification,
+}
+
+/// The decoded certificate and tree of a response that passed verification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifiedCertification {
+    /// The [certificate](https://internetcomputer.org/docs/current/references/ic-interface-spec/#certificate)
+    /// that certifies the root hash of the tree, including its delegation, if any.
+    pub certificate: Certificate,
+    /// The pruned hash tree containing the witness that certifies the response.
+    pub tree: HashTree,
+}
+
+impl VerifiedCertification {
+    /// Returns the ID of the subnet that the certificate was delegated to, or [None] if the
+    /// certificate was signed with the root key directly.
+    pub fn delegation_subnet_id(&self) -> Option<Principal> {
+        self.certificate
+            .delegation
+            .as_ref()
+            .map(|delegation| Principal::from_slice(&delegation.subnet_id))
+    }
 }
 
 #[cfg(all(target_arch = "wasm32", feature = "js"))]
@@ -46,10 +72,22 @@ impl From<VerificationInfo> for JsValue {
 #[cfg(all(target_arch = "wasm32", feature = "js", test))]
 mod tests {
     use super::*;
+    use ic_certification::hash_tree::empty;
     use js_sys::JSON;
     use wasm_bindgen::JsValue;
     use wasm_bindgen_test::wasm_bindgen_test;
 
+    fn certification() -> VerifiedCertification {
+        VerifiedCertification {
+            certificate: Certificate {
+                tree: empty(),
+                signature: vec![],
+                delegation: None,
+            },
+            tree: empty(),
+        }
+    }
+
     #[wasm_bindgen_test]
     fn serialize_verification_result_with_no_response() {
         let expected = r#"{"verificationVersion":1}"#;
@@ -58,6 +96,7 @@ mod tests {
             JSON::stringify(&JsValue::from(VerificationInfo {
                 response: None,
                 verification_version: 1,
+                certification: certification(),
             }))
             .unwrap(),
             expected
@@ -76,6 +115,7 @@ mod tests {
                     headers: vec![],
                 }),
                 verification_version: 2,
+                certification: certification(),
             }))
             .unwrap(),
             expected
@@ -22,6 +22,18 @@ pub struct VerifiedResponse {
     pub body: Vec<u8>,
 }
 
+/// Represents a certified `206 Partial Content` response from the
+/// [Internet Computer](https://internetcomputer.org) that is one chunk of a larger response, see
+/// [reassemble_chunked_response](crate::reassemble_chunked_response).
+#[derive(Debug, PartialEq, Eq)]
+pub struct VerifiedResponseChunk {
+    /// The path of the request that the chunk was verified for, excluding the query string,
+    /// i.e. `/assets/video.mp4`.
+    pub request_path: String,
+    /// The verified response of the chunk.
+    pub response: VerifiedResponse,
+}
+
 #[cfg(all(target_arch = "wasm32", feature = "js"))]
 impl From<VerifiedResponse> for JsValue {
     fn from(response: VerifiedResponse) -> Self {
@@ -1,25 +1,20 @@
 use ic_certification::{Certificate, HashTree, LookupResult};
 
-pub fn validate_tree(canister_id: &[u8], certificate: &Certificate, tree: &HashTree) -> bool {
+pub fn certified_data<'a>(canister_id: &[u8], certificate: &'a Certificate) -> Option<&'a [u8]> {
     let certified_data_path = [
         "canister".as_bytes(),
         canister_id,
         "certified_data".as_bytes(),
     ];
 
-    let witness = match certificate.tree.lookup_path(&certified_data_path) {
-        LookupResult::Found(witness) => witness,
-        _ => {
-            return false;
-        }
-    };
-
-    let digest = tree.digest();
-    if witness != digest {
-        return false;
+    match certificate.tree.lookup_path(&certified_data_path) {
+        LookupResult::Found(witness) => Some(witness),
+        _ => None,
     }
+}
 
-    true
+pub fn validate_tree(canister_id: &[u8], certificate: &Certificate, tree: &HashTree) -> bool {
+    certified_data(canister_id, certificate).is_some_and(|witness| witness == tree.digest())
 }
 
 #[cfg(test)]
@@ -1,25 +1,25 @@
 use ic_certification::{hash_tree::Hash, HashTree, LookupResult};
 
-pub fn validate_body(tree: &HashTree, request_path: &str, body_sha: &Hash) -> bool {
+pub fn certified_body_hash<'a>(tree: &'a HashTree, request_path: &str) -> Option<&'a [u8]> {
     let asset_path = ["http_assets".as_bytes(), request_path.as_bytes()];
     let index_fallback_path = ["http_assets".as_bytes(), "/index.html".as_bytes()];
 
-    let tree_sha = match tree.lookup_path(&asset_path) {
-        LookupResult::Found(v) => v,
+    match tree.lookup_path(&asset_path) {
+        LookupResult::Found(v) => Some(v),
 
         // This is a strange fallback, but it is necessary for SPA routing at the moment.
         // https://internetcomputer.org/docs/current/references/ic-interface-spec/#http-gateway-certification
         //
         // It may be possible to remove this with a combination of asset canister redirect rules and v2 response verification.
         _ => match tree.lookup_path(&index_fallback_path) {
-            LookupResult::Found(v) => v,
-            _ => {
-                return false;
-            }
+            LookupResult::Found(v) => Some(v),
+            _ => None,
         },
-    };
+    }
+}
 
-    body_sha == tree_sha
+pub fn validate_body(tree: &HashTree, request_path: &str, body_sha: &Hash) -> bool {
+    certified_body_hash(tree, request_path).is_some_and(|tree_sha| body_sha == tree_sha)
 }
 
 #[cfg(test)]
@@ -1,12 +1,10 @@
-use crate::{ResponseVerificationError, ResponseVerificationResult};
+use crate::{ResponseHashesMismatch, ResponseVerificationError, ResponseVerificationResult};
 use ic_certification::hash_tree::HashTreeNode;
 use ic_certification::{hash_tree::Hash, HashTree, Label, SubtreeLookupResult};
-use ic_http_certification::cel::DefaultCelExpression;
 use ic_http_certification::utils::{
     is_wildcard_path_valid_for_request_path, more_specific_wildcards_for,
     EXACT_PATH_TERMINATOR_BYTES, PATH_PREFIX_BYTES,
 };
-use ic_http_certification::CelExpression;
 
 fn path_from_parts<T>(parts: &[T]) -> Vec<Vec<u8>>
 where
@@ -23,6 +21,22 @@ fn path_exists_in_tree(path: &[Vec<u8>], tree: &HashTree) -> bool {
     matches!(tree.lookup_subtree(path), SubtreeLookupResult::Found(_))
 }
 
+fn certified_labels(tree: &HashTree, path: &[Vec<u8>]) -> Vec<String> {
+    let SubtreeLookupResult::Found(subtree) = tree.lookup_subtree(path) else {
+        return vec![];
+    };
+
+    let mut labels: Vec<String> = subtree
+        .list_paths()
+        .iter()
+        .filter_map(|path| path.first())
+        .map(|label| hex::encode(label.as_bytes()))
+        .collect();
+    labels.dedup();
+
+    labels
+}
+
 fn expr_path_has_valid_suffix(expr_path: &[String]) -> bool {
     expr_path.ends_with(&["<$>".to_string()]) || expr_path.ends_with(&["<*>".to_string()])
 }
@@ -167,27 +181,43 @@ pub fn validate_hashes(
     response_hash: &Hash,
     expr_path: &[String],
     tree: &HashTree,
-    certification: &CelExpression,
-) -> bool {
+) -> Result<(), ResponseHashesMismatch> {
     let Some(expr_tree) = validate_expr_hash(expr_path, expr_hash, tree) else {
-        return false;
+        return Err(ResponseHashesMismatch::ExpressionHashNotFound {
+            expr_hash: hex::encode(expr_hash),
+            certified_expr_hashes: certified_labels(tree, &path_from_parts(expr_path)),
+        });
     };
 
-    let mut expr_tree_path: Vec<Label> = vec![];
-    if let (CelExpression::Default(DefaultCelExpression::Full(_)), Some(request_hash)) =
-        (&certification, request_hash)
-    {
-        expr_tree_path.push(request_hash.into());
-    } else {
-        expr_tree_path.push("".into());
-    }
-    expr_tree_path.push(response_hash.into());
+    // the request hash is only certified by a full certification
+    let request_label: Label = match request_hash {
+        Some(request_hash) => request_hash.into(),
+        None => "".into(),
+    };
 
-    match expr_tree.lookup_subtree(&expr_tree_path) {
-        SubtreeLookupResult::Found(res_tree) => {
-            HashTreeNode::from(res_tree).eq(&HashTreeNode::Leaf("".as_bytes().to_vec()))
+    let res_tree = match expr_tree.lookup_subtree([&request_label]) {
+        SubtreeLookupResult::Found(res_tree) => res_tree,
+        _ => {
+            return Err(ResponseHashesMismatch::RequestHashNotFound {
+                request_hash: hex::encode(request_label.as_bytes()),
+                certified_request_hashes: certified_labels(&expr_tree, &[]),
+            });
+        }
+    };
+
+    let is_response_hash_certified = match res_tree.lookup_subtree([response_hash]) {
+        SubtreeLookupResult::Found(leaf_tree) => {
+            HashTreeNode::from(leaf_tree).eq(&HashTreeNode::Leaf("".as_bytes().to_vec()))
         }
         _ => false,
+    };
+
+    match is_response_hash_certified {
+        true => Ok(()),
+        false => Err(ResponseHashesMismatch::ResponseHashNotFound {
+            response_hash: hex::encode(response_hash),
+            certified_response_hashes: certified_labels(&res_tree, &[]),
+        }),
     }
 }
 
@@ -196,10 +226,6 @@ mod tests {
     use super::*;
     use crate::test_utils::{create_pruned, remove_whitespace, sha256_from_hex};
     use ic_certification::hash_tree::{fork, label, leaf};
-    use ic_http_certification::{
-        cel::{DefaultFullCelExpression, DefaultRequestCertification},
-        DefaultResponseCertification,
-    };
     use ic_representation_independent_hash::hash;
     use ic_response_verification_test_utils::hex_decode;
 
@@ -258,7 +284,6 @@ mod tests {
             ),
             create_pruned("ea7fd1a6b0cac1fe118016ca3026e58d5ae67a6965478acb561edba542732e24"),
         );
-        let certification = create_certification();
 
         let result = validate_hashes(
             &expr_hash,
@@ -266,10 +291,9 @@ mod tests {
             &response_hash,
             &expr_path,
             &tree,
-            &certification,
         );
 
-        assert!(result);
+        assert_eq!(result, Ok(()));
     }
 
     #[test]
@@ -306,7 +330,6 @@ mod tests {
             ),
             create_pruned("ea7fd1a6b0cac1fe118016ca3026e58d5ae67a6965478acb561edba542732e24"),
         );
-        let certification = create_certification();
 
         let result = validate_hashes(
             &expr_hash,
@@ -314,10 +337,12 @@ mod tests {
             &response_hash,
             &expr_path,
             &tree,
-            &certification,
         );
 
-        assert!(!result);
+        assert!(matches!(
+            result,
+            Err(ResponseHashesMismatch::ExpressionHashNotFound { .. })
+        ));
     }
 
     #[test]
@@ -354,7 +379,6 @@ mod tests {
             ),
             create_pruned("ea7fd1a6b0cac1fe118016ca3026e58d5ae67a6965478acb561edba542732e24"),
         );
-        let certification = create_certification();
 
         let result = validate_hashes(
             &expr_hash,
@@ -362,10 +386,17 @@ mod tests {
             &response_hash,
             &expr_path,
             &tree,
-            &certification,
         );
 
-        assert!(!result);
+        assert_eq!(
+            result,
+            Err(ResponseHashesMismatch::ExpressionHashNotFound {
+                expr_hash: hex::encode(expr_hash),
+                certified_expr_hashes: vec![
+                    "c5dbe9d11756d4a7b05e5c0e246035dedcd1e4e71bd1e726c4011940d811496b".into()
+                ],
+            })
+        );
     }
 
     #[test]
@@ -396,7 +427,6 @@ mod tests {
             ),
             create_pruned("ea7fd1a6b0cac1fe118016ca3026e58d5ae67a6965478acb561edba542732e24"),
         );
-        let certification = create_certification();
 
         let result = validate_hashes(
             &expr_hash,
@@ -404,10 +434,12 @@ mod tests {
             &response_hash,
             &expr_path,
             &tree,
-            &certification,
         );
 
-        assert!(!result);
+        assert!(matches!(
+            result,
+            Err(ResponseHashesMismatch::RequestHashNotFound { .. })
+        ));
     }
 
     #[test]
@@ -438,7 +470,6 @@ mod tests {
             ),
             create_pruned("ea7fd1a6b0cac1fe118016ca3026e58d5ae67a6965478acb561edba542732e24"),
         );
-        let certification = create_certification();
 
         let result = validate_hashes(
             &expr_hash,
@@ -446,10 +477,12 @@ mod tests {
             &response_hash,
             &expr_path,
             &tree,
-            &certification,
         );
 
-        assert!(!result);
+        assert!(matches!(
+            result,
+            Err(ResponseHashesMismatch::RequestHashNotFound { .. })
+        ));
     }
 
     #[test]
@@ -480,7 +513,6 @@ mod tests {
             ),
             create_pruned("ea7fd1a6b0cac1fe118016ca3026e58d5ae67a6965478acb561edba542732e24"),
         );
-        let certification = create_certification();
 
         let result = validate_hashes(
             &expr_hash,
@@ -488,10 +520,12 @@ mod tests {
             &response_hash,
             &expr_path,
             &tree,
-            &certification,
         );
 
-        assert!(!result);
+        assert!(matches!(
+            result,
+            Err(ResponseHashesMismatch::ResponseHashNotFound { .. })
+        ));
     }
 
     #[test]
@@ -522,7 +556,6 @@ mod tests {
             ),
             create_pruned("ea7fd1a6b0cac1fe118016ca3026e58d5ae67a6965478acb561edba542732e24"),
         );
-        let certification = create_certification();
 
         let result = validate_hashes(
             &expr_hash,
@@ -530,10 +563,12 @@ mod tests {
             &response_hash,
             &expr_path,
             &tree,
-            &certification,
         );
 
-        assert!(!result);
+        assert!(matches!(
+            result,
+            Err(ResponseHashesMismatch::ResponseHashNotFound { .. })
+        ));
     }
 
     #[test]
@@ -1136,14 +1171,4 @@ mod tests {
             ));
         }
     }
-
-    fn create_certification<'a>() -> CelExpression<'a> {
-        CelExpression::Default(DefaultCelExpression::Full(DefaultFullCelExpression {
-            request: DefaultRequestCertification::new(vec!["Host"], vec![]),
-            response: DefaultResponseCertification::certified_response_headers(vec![
-                "Accept-Encoding",
-                "Cache-Control",
-            ]),
-        }))
-    }
 }
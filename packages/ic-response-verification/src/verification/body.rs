@@ -1,29 +1,174 @@
-use crate::error::ResponseVerificationResult;
-use flate2::read::{DeflateDecoder, GzDecoder};
-use std::io::Read;
+use crate::{
+    error::{ResponseVerificationError, ResponseVerificationResult},
+    types::VerificationPolicy,
+};
+use flate2::write::{DeflateDecoder, GzDecoder};
+use ic_certification::hash_tree::Hash;
+use sha2::{Digest, Sha256};
+use std::io::{self, Write};
 
-const MAX_CHUNK_SIZE_TO_DECOMPRESS: usize = 1_024;
+/// Hashes a response body as its chunks are written.
+#[derive(Debug, Default)]
+pub struct BodyHasher(Sha256);
 
-pub fn decode_body(body: &[u8], encoding: Option<&str>) -> ResponseVerificationResult<Vec<u8>> {
-    match encoding {
-        Some("gzip") => body_from_decoder(GzDecoder::new(body)),
-        Some("deflate") => body_from_decoder(DeflateDecoder::new(body)),
-        _ => Ok(body.to_owned()),
+impl BodyHasher {
+    pub fn update(&mut self, chunk: &[u8]) {
+        self.0.update(chunk);
     }
+
+    pub fn finalize(self) -> Hash {
+        self.0.finalize().into()
+    }
+}
+
+/// Hashes a decoded response body as its chunks are written, failing as soon as the body
+/// exceeds a maximum length.
+#[derive(Debug)]
+struct LimitedBodyHasher {
+    hasher: BodyHasher,
+    length: usize,
+    max_length: usize,
+}
+
+impl Write for LimitedBodyHasher {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let length = self.length.saturating_add(buf.len());
+        if length > self.max_length {
+            return Err(io::Error::other(DecodedLengthExceeded { length }));
+        }
+
+        self.hasher.update(buf);
+        self.length = length;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The error that [LimitedBodyHasher] fails with when the decoded body exceeds its maximum
+/// length.
+#[derive(thiserror::Error, Debug)]
+#[error("the decoded body exceeds its maximum length with {length} bytes")]
+struct DecodedLengthExceeded {
+    length: usize,
+}
+
+/// Limits on the size of a decoded response body, see [VerificationPolicy].
+#[derive(Debug, Clone, Copy)]
+struct DecodingLimits {
+    max_decoded_body_size: Option<usize>,
+    max_compression_ratio: Option<usize>,
 }
 
-fn body_from_decoder<D: Read>(mut decoder: D) -> ResponseVerificationResult<Vec<u8>> {
-    let mut decoded = Vec::new();
-    let mut buffer = [0u8; MAX_CHUNK_SIZE_TO_DECOMPRESS];
+impl DecodingLimits {
+    /// Returns the maximum length of the decoded body after the given number of encoded bytes.
+    fn max_decoded_length(&self, encoded_length: usize) -> usize {
+        let max_compressed_length = self
+            .max_compression_ratio
+            .map(|max_compression_ratio| encoded_length.saturating_mul(max_compression_ratio));
 
-    loop {
-        let bytes = decoder.read(&mut buffer)?;
+        [self.max_decoded_body_size, max_compressed_length]
+            .into_iter()
+            .flatten()
+            .min()
+            .unwrap_or(usize::MAX)
+    }
 
-        if bytes == 0 {
-            return Ok(decoded);
+    /// Maps an error of the decoder to the limit that was exceeded, if any.
+    fn decoding_error(&self, error: io::Error) -> ResponseVerificationError {
+        let Some(&DecodedLengthExceeded { length }) = error
+            .get_ref()
+            .and_then(|error| error.downcast_ref::<DecodedLengthExceeded>())
+        else {
+            return error.into();
+        };
+
+        match (self.max_decoded_body_size, self.max_compression_ratio) {
+            (Some(max_decoded_body_size), _) if length > max_decoded_body_size => {
+                ResponseVerificationError::DecodedBodyTooLarge {
+                    max_decoded_body_size,
+                }
+            }
+            (_, Some(max_compression_ratio)) => {
+                ResponseVerificationError::CompressionRatioTooHigh {
+                    max_compression_ratio,
+                }
+            }
+            _ => error.into(),
         }
+    }
+}
+
+#[derive(Debug)]
+enum Decoder {
+    Gzip(GzDecoder<LimitedBodyHasher>),
+    Deflate(DeflateDecoder<LimitedBodyHasher>),
+}
+
+/// Decodes a response body as its chunks are written, and hashes the decoded body.
+#[derive(Debug)]
+pub struct BodyDecoder {
+    decoder: Decoder,
+    encoded_length: usize,
+    limits: DecodingLimits,
+}
+
+impl BodyDecoder {
+    /// Returns a decoder for the given content encoding, or [None] if the body does not need
+    /// to be decoded. The decoded body is limited according to the given [VerificationPolicy].
+    pub fn new(encoding: Option<&str>, policy: &VerificationPolicy) -> Option<Self> {
+        let hasher = LimitedBodyHasher {
+            hasher: BodyHasher::default(),
+            length: 0,
+            max_length: 0,
+        };
+
+        let decoder = match encoding {
+            Some("gzip") => Decoder::Gzip(GzDecoder::new(hasher)),
+            Some("deflate") => Decoder::Deflate(DeflateDecoder::new(hasher)),
+            _ => return None,
+        };
 
-        decoded.extend_from_slice(&buffer[..bytes]);
+        Some(Self {
+            decoder,
+            encoded_length: 0,
+            limits: DecodingLimits {
+                max_decoded_body_size: policy.max_decoded_body_size(),
+                max_compression_ratio: policy.max_compression_ratio(),
+            },
+        })
+    }
+
+    pub fn update(&mut self, chunk: &[u8]) -> ResponseVerificationResult {
+        self.encoded_length = self.encoded_length.saturating_add(chunk.len());
+        let max_length = self.limits.max_decoded_length(self.encoded_length);
+
+        let result = match &mut self.decoder {
+            Decoder::Gzip(decoder) => {
+                decoder.get_mut().max_length = max_length;
+                decoder.write_all(chunk)
+            }
+            Decoder::Deflate(decoder) => {
+                decoder.get_mut().max_length = max_length;
+                decoder.write_all(chunk)
+            }
+        };
+
+        result.map_err(|error| self.limits.decoding_error(error))
+    }
+
+    pub fn finalize(self) -> ResponseVerificationResult<Hash> {
+        let result = match self.decoder {
+            Decoder::Gzip(decoder) => decoder.finish(),
+            Decoder::Deflate(decoder) => decoder.finish(),
+        };
+
+        result
+            .map(|hasher| hasher.hasher.finalize())
+            .map_err(|error| self.limits.decoding_error(error))
     }
 }
 
@@ -32,15 +177,29 @@ mod tests {
     use super::*;
     use flate2::write::{DeflateEncoder, GzEncoder};
     use flate2::Compression;
-    use std::io::Write;
+    use ic_representation_independent_hash::hash;
 
     const BODY: &[u8] = &[1, 2, 3, 4, 5, 6, 7, 8];
 
+    fn decoded_body_hash(encoded_body: &[u8], encoding: &str, chunk_size: usize) -> Hash {
+        let mut decoder = BodyDecoder::new(Some(encoding), &VerificationPolicy::default()).unwrap();
+        for chunk in encoded_body.chunks(chunk_size) {
+            decoder.update(chunk).unwrap();
+        }
+
+        decoder.finalize().unwrap()
+    }
+
     #[test]
-    fn decode_simple_body() {
-        let result = decode_body(BODY, None).unwrap();
+    fn hash_simple_body() {
+        let mut hasher = BodyHasher::default();
+        for chunk in BODY.chunks(3) {
+            hasher.update(chunk);
+        }
 
-        assert_eq!(result.as_slice(), BODY);
+        assert!(BodyDecoder::new(None, &VerificationPolicy::default()).is_none());
+        assert!(BodyDecoder::new(Some("identity"), &VerificationPolicy::default()).is_none());
+        assert_eq!(hasher.finalize(), hash(BODY));
     }
 
     #[test]
@@ -49,9 +208,11 @@ mod tests {
         encoder.write_all(BODY).unwrap();
         let encoded_body = encoder.finish().unwrap();
 
-        let result = decode_body(&encoded_body, Some("gzip")).unwrap();
-
-        assert_eq!(result.as_slice(), BODY);
+        assert_eq!(decoded_body_hash(&encoded_body, "gzip", 1), hash(BODY));
+        assert_eq!(
+            decoded_body_hash(&encoded_body, "gzip", encoded_body.len()),
+            hash(BODY)
+        );
     }
 
     #[test]
@@ -60,8 +221,57 @@ mod tests {
         encoder.write_all(BODY).unwrap();
         let encoded_body = encoder.finish().unwrap();
 
-        let result = decode_body(&encoded_body, Some("deflate")).unwrap();
+        assert_eq!(decoded_body_hash(&encoded_body, "deflate", 1), hash(BODY));
+        assert_eq!(
+            decoded_body_hash(&encoded_body, "deflate", encoded_body.len()),
+            hash(BODY)
+        );
+    }
+
+    #[test]
+    fn limit_decoded_body_size() {
+        let body = vec![0; 1024];
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&body).unwrap();
+        let encoded_body = encoder.finish().unwrap();
+
+        let policy = VerificationPolicy::new().with_max_decoded_body_size(body.len());
+        let mut decoder = BodyDecoder::new(Some("gzip"), &policy).unwrap();
+        decoder.update(&encoded_body).unwrap();
+        assert_eq!(decoder.finalize().unwrap(), hash(&body));
+
+        let policy = VerificationPolicy::new().with_max_decoded_body_size(body.len() - 1);
+        let mut decoder = BodyDecoder::new(Some("gzip"), &policy).unwrap();
+        let result = decoder
+            .update(&encoded_body)
+            .and_then(|_| decoder.finalize().map(|_| ()));
+
+        assert!(matches!(
+            result,
+            Err(ResponseVerificationError::DecodedBodyTooLarge {
+                max_decoded_body_size: 1023,
+            })
+        ));
+    }
+
+    #[test]
+    fn limit_compression_ratio() {
+        let body = vec![0; 1024 * 1024];
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(&body).unwrap();
+        let encoded_body = encoder.finish().unwrap();
+
+        let policy = VerificationPolicy::new().with_max_compression_ratio(2);
+        let mut decoder = BodyDecoder::new(Some("deflate"), &policy).unwrap();
+        let result = decoder
+            .update(&encoded_body)
+            .and_then(|_| decoder.finalize().map(|_| ()));
 
-        assert_eq!(result.as_slice(), BODY);
+        assert!(matches!(
+            result,
+            Err(ResponseVerificationError::CompressionRatioTooHigh {
+                max_compression_ratio: 2,
+            })
+        ));
     }
 }
@@ -0,0 +1,150 @@
+use super::body::{BodyDecoder, BodyHasher};
+use crate::{
+    error::{ResponseVerificationError, ResponseVerificationResult},
+    types::{VerificationInfo, VerifiedCertification, VerifiedResponse},
+    validation::{certified_body_hash, validate_body, validate_hashes},
+};
+use ic_certification::{hash_tree::Hash, Certificate, HashTree};
+use ic_representation_independent_hash::hash;
+
+/// The checks that remain to be performed once the response body is complete.
+#[derive(Debug)]
+pub(super) enum PendingVerification {
+    /// The response body is not certified, so verification is already complete.
+    Verified(VerificationInfo),
+    V1 {
+        certificate: Certificate,
+        tree: HashTree,
+        request_path: String,
+        encoded: bool,
+    },
+    V2 {
+        certificate: Certificate,
+        tree: HashTree,
+        expr_path: Vec<String>,
+        expr_hash: Hash,
+        request_hash: Option<Hash>,
+        response_headers_hash: Hash,
+        response: VerifiedResponse,
+    },
+}
+
+/// Verifies the body of a response incrementally, as its chunks arrive from the replica, after the
+/// rest of the response has been verified with
+/// [verify_request_response_pair_streaming](crate::verify_request_response_pair_streaming).
+///
+/// Each chunk is hashed as it is passed to [update](BodyVerifier::update), so the body does not
+/// need to be buffered, and the hash of the complete body is checked against the certification by
+/// [finalize](BodyVerifier::finalize). The response body must not be used before it has been
+/// finalized successfully.
+#[derive(Debug)]
+pub struct BodyVerifier {
+    hasher: BodyHasher,
+    decoder: Option<BodyDecoder>,
+    pending_verification: PendingVerification,
+}
+
+impl BodyVerifier {
+    pub(super) fn new(
+        pending_verification: PendingVerification,
+        decoder: Option<BodyDecoder>,
+    ) -> Self {
+        Self {
+            hasher: BodyHasher::default(),
+            decoder,
+            pending_verification,
+        }
+    }
+
+    /// Adds the next chunk of the response body, as it was received from the replica.
+    ///
+    /// Returns an error if the chunk cannot be decoded according to the `Content-Encoding` header
+    /// of the response.
+    pub fn update(&mut self, chunk: &[u8]) -> ResponseVerificationResult {
+        self.hasher.update(chunk);
+
+        if let Some(decoder) = self.decoder.as_mut() {
+            decoder.update(chunk)?;
+        }
+
+        Ok(())
+    }
+
+    /// Completes verification after all chunks of the response body have been added with
+    /// [update](BodyVerifier::update).
+    ///
+    /// The returned [VerificationInfo] is the same as the one returned by
+    /// [verify_request_response_pair](crate::verify_request_response_pair), except that the body
+    /// of the verified response is empty, since it was not buffered.
+    pub fn finalize(self) -> ResponseVerificationResult<VerificationInfo> {
+        let Self {
+            hasher,
+            decoder,
+            pending_verification,
+        } = self;
+        let body_hash = hasher.finalize();
+
+        match pending_verification {
+            PendingVerification::Verified(verification_info) => Ok(verification_info),
+            PendingVerification::V1 {
+                certificate,
+                tree,
+                request_path,
+                encoded,
+            } => {
+                let decoded_body_hash = decoder
+                    .map(BodyDecoder::finalize)
+                    .transpose()?
+                    .unwrap_or(body_hash);
+
+                let valid_body = validate_body(&tree, &request_path, &decoded_body_hash)
+                    || (encoded && validate_body(&tree, &request_path, &body_hash));
+
+                if !valid_body {
+                    return Err(ResponseVerificationError::InvalidResponseBody {
+                        body_hash: hex::encode(decoded_body_hash),
+                        certified_body_hash: certified_body_hash(&tree, &request_path)
+                            .map(hex::encode),
+                        request_path,
+                    });
+                }
+
+                Ok(VerificationInfo {
+                    response: Some(VerifiedResponse {
+                        status_code: None,
+                        headers: Vec::new(),
+                        body: Vec::new(),
+                    }),
+                    verification_version: 1,
+                    certification: VerifiedCertification { certificate, tree },
+                })
+            }
+            PendingVerification::V2 {
+                certificate,
+                tree,
+                expr_path,
+                expr_hash,
+                request_hash,
+                response_headers_hash,
+                response,
+            } => {
+                let response_hash = hash([response_headers_hash, body_hash].concat().as_slice());
+
+                match validate_hashes(&expr_hash, &request_hash, &response_hash, &expr_path, &tree)
+                {
+                    Ok(()) => Ok(VerificationInfo {
+                        response: Some(response),
+                        verification_version: 2,
+                        certification: VerifiedCertification { certificate, tree },
+                    }),
+                    Err(mismatch) => Err(ResponseVerificationError::InvalidResponseHashes {
+                        provided_expr_path: expr_path,
+                        mismatch,
+                        response_headers_hash: hex::encode(response_headers_hash),
+                        body_hash: hex::encode(body_hash),
+                    }),
+                }
+            }
+        }
+    }
+}
@@ -6,8 +6,15 @@ use crate::{
 use base64::Engine as _;
 use ic_cbor::{parse_cbor_string_array, CertificateToCbor, HashTreeToCbor};
 use ic_certification::{Certificate, HashTree};
+#[cfg(feature = "log")]
 use log::warn;
 
+/// Discards warnings when the `log` feature is disabled.
+#[cfg(not(feature = "log"))]
+macro_rules! warn {
+    ($($arg:tt)*) => {};
+}
+
 /// Parsed `Ic-Certificate` header, containing a certificate and tree.
 #[derive(Debug, PartialEq, Eq)]
 pub struct CertificateHeader {
@@ -28,6 +35,29 @@ pub struct CertificateHeader {
 impl CertificateHeader {
     /// Parses the given header and returns a new CertificateHeader.
     pub fn from(header_value: &str) -> ResponseVerificationResult<CertificateHeader> {
+        Self::parse(header_value, |certificate_bytes| {
+            Ok(Certificate::from_cbor(certificate_bytes)?)
+        })
+    }
+
+    /// Parses only the verification version of the given header, without decoding the
+    /// certificate and tree.
+    pub(crate) fn parse_version(header_value: &str) -> ResponseVerificationResult<u8> {
+        let version = header_value
+            .split(',')
+            .filter_map(CertificateHeaderField::from)
+            .find(|CertificateHeaderField(name, _)| *name == "version")
+            .map(|CertificateHeaderField(_, value)| parse_int_header(value))
+            .transpose()?;
+
+        Ok(version.unwrap_or(MIN_VERIFICATION_VERSION))
+    }
+
+    /// Parses the given header, using `parse_certificate` to parse the CBOR encoded certificate.
+    pub(crate) fn parse(
+        header_value: &str,
+        mut parse_certificate: impl FnMut(&[u8]) -> ResponseVerificationResult<Certificate>,
+    ) -> ResponseVerificationResult<CertificateHeader> {
         let mut certificate = None;
         let mut tree = None;
         let mut version = None;
@@ -40,7 +70,7 @@ impl CertificateHeader {
                         certificate = match certificate {
                             None => {
                                 let certificate_bytes = decode_base64_header(value)?;
-                                let certificate = Certificate::from_cbor(&certificate_bytes)?;
+                                let certificate = parse_certificate(&certificate_bytes)?;
 
                                 Some(certificate)
                             }
@@ -0,0 +1,470 @@
+use crate::{
+    error::{ResponseChunkMismatch, ResponseVerificationError, ResponseVerificationResult},
+    types::{VerifiedResponse, VerifiedResponseChunk},
+};
+use http::{header, StatusCode};
+use ic_http_certification::CERTIFICATE_HEADER_NAME;
+
+/// Reassembles the full response body from a sequence of verified `206 Partial Content`
+/// responses, such as the chunks that are served for large assets by the `AssetRouter` of the
+/// `ic-asset-certification` crate.
+///
+/// Each chunk must have been verified with
+/// [verify_request_response_pair](crate::verify_request_response_pair) beforehand, and must
+/// certify both its status code and its `Content-Range` header. The chunks must be given in
+/// order, starting at the first byte of the response body, and their ranges must be contiguous
+/// and cover the full response body.
+///
+/// To prevent chunks of different responses from being mixed, every chunk must have been
+/// verified for the same request path, and must have the same certified `ETag` header, if any,
+/// and the same complete length in its `Content-Range` header as the first chunk. Without a
+/// certified `ETag` header, chunks of two versions of a response at the same path can only be
+/// told apart by their complete length, so canisters should certify an `ETag` header for
+/// responses that are served in chunks.
+///
+/// The returned response has a `200 OK` status code and the certified headers of the first chunk,
+/// except for the `Content-Range` and `IC-Certificate` headers, which only apply to the first
+/// chunk. If present, the `Content-Length` header is replaced with the length of the full
+/// response body.
+///
+/// # Examples
+///
+/// ```
+/// use ic_response_verification::{
+///     reassemble_chunked_response,
+///     types::{VerifiedResponse, VerifiedResponseChunk},
+/// };
+///
+/// let chunks = vec![
+///     VerifiedResponseChunk {
+///         request_path: "/video.mp4".into(),
+///         response: VerifiedResponse {
+///             status_code: Some(206),
+///             headers: vec![
+///                 ("etag".into(), "\"1\"".into()),
+///                 ("content-range".into(), "bytes 0-2/5".into()),
+///             ],
+///             body: vec![1, 2, 3],
+///         },
+///     },
+///     VerifiedResponseChunk {
+///         request_path: "/video.mp4".into(),
+///         response: VerifiedResponse {
+///             status_code: Some(206),
+///             headers: vec![
+///                 ("etag".into(), "\"1\"".into()),
+///                 ("content-range".into(), "bytes 3-4/5".into()),
+///             ],
+///             body: vec![4, 5],
+///         },
+///     },
+/// ];
+///
+/// let response = reassemble_chunked_response(chunks).unwrap();
+///
+/// assert_eq!(response.status_code, Some(200));
+/// assert_eq!(response.body, vec![1, 2, 3, 4, 5]);
+/// ```
+pub fn reassemble_chunked_response(
+    chunks: Vec<VerifiedResponseChunk>,
+) -> ResponseVerificationResult<VerifiedResponse> {
+    let Some((first_chunk, remaining_chunks)) = chunks.split_first() else {
+        return Err(ResponseVerificationError::MissingResponseChunks);
+    };
+
+    let total_length = chunk_range(0, &first_chunk.response, 0, None)?.total_length;
+    let mut length = first_chunk.response.body.len();
+
+    for (chunk_index, chunk) in (1..).zip(remaining_chunks) {
+        validate_chunk_origin(chunk_index, first_chunk, chunk)?;
+        chunk_range(chunk_index, &chunk.response, length, Some(total_length))?;
+        length += chunk.response.body.len();
+    }
+
+    // the total length is taken from an untrusted header, so it is only used to allocate the
+    // body once the chunks have been confirmed to add up to it
+    if length != total_length {
+        return Err(ResponseVerificationError::IncompleteChunkedResponse {
+            length,
+            total_length,
+        });
+    }
+
+    let mut body = Vec::with_capacity(length);
+    for chunk in &chunks {
+        body.extend_from_slice(&chunk.response.body);
+    }
+
+    let headers = first_chunk
+        .response
+        .headers
+        .iter()
+        .filter(|(name, _)| {
+            !name.eq_ignore_ascii_case(header::CONTENT_RANGE.as_str())
+                && !name.eq_ignore_ascii_case(CERTIFICATE_HEADER_NAME)
+        })
+        .map(|(name, value)| {
+            if name.eq_ignore_ascii_case(header::CONTENT_LENGTH.as_str()) {
+                (name.clone(), total_length.to_string())
+            } else {
+                (name.clone(), value.clone())
+            }
+        })
+        .collect();
+
+    Ok(VerifiedResponse {
+        status_code: Some(StatusCode::OK.as_u16()),
+        headers,
+        body,
+    })
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct ContentRange {
+    range_begin: usize,
+    range_end: usize,
+    total_length: usize,
+}
+
+impl ContentRange {
+    /// Parses the value of a `Content-Range` header of the form `bytes <begin>-<end>/<length>`.
+    fn parse(content_range: &str) -> Option<Self> {
+        let (range, total_length) = content_range
+            .trim()
+            .strip_prefix("bytes ")?
+            .split_once('/')?;
+        let (range_begin, range_end) = range.split_once('-')?;
+
+        let content_range = Self {
+            range_begin: range_begin.trim().parse().ok()?,
+            range_end: range_end.trim().parse().ok()?,
+            total_length: total_length.trim().parse().ok()?,
+        };
+        if content_range.range_begin > content_range.range_end
+            || content_range.range_end >= content_range.total_length
+        {
+            return None;
+        }
+
+        Some(content_range)
+    }
+}
+
+fn validate_chunk_origin(
+    chunk_index: usize,
+    first_chunk: &VerifiedResponseChunk,
+    chunk: &VerifiedResponseChunk,
+) -> ResponseVerificationResult<()> {
+    let invalid_chunk = |mismatch| ResponseVerificationError::InvalidResponseChunk {
+        chunk_index,
+        mismatch,
+    };
+
+    if chunk.request_path != first_chunk.request_path {
+        return Err(invalid_chunk(ResponseChunkMismatch::RequestPathMismatch {
+            expected_request_path: first_chunk.request_path.clone(),
+            request_path: chunk.request_path.clone(),
+        }));
+    }
+
+    let expected_etag = chunk_etag(&first_chunk.response);
+    let etag = chunk_etag(&chunk.response);
+    if etag != expected_etag {
+        return Err(invalid_chunk(ResponseChunkMismatch::ETagMismatch {
+            expected_etag: expected_etag.map(str::to_string),
+            etag: etag.map(str::to_string),
+        }));
+    }
+
+    Ok(())
+}
+
+fn chunk_etag(chunk: &VerifiedResponse) -> Option<&str> {
+    chunk
+        .headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(header::ETAG.as_str()))
+        .map(|(_, etag)| etag.as_str())
+}
+
+fn chunk_range(
+    chunk_index: usize,
+    chunk: &VerifiedResponse,
+    expected_range_begin: usize,
+    expected_total_length: Option<usize>,
+) -> ResponseVerificationResult<ContentRange> {
+    let invalid_chunk = |mismatch| ResponseVerificationError::InvalidResponseChunk {
+        chunk_index,
+        mismatch,
+    };
+
+    if chunk.status_code != Some(StatusCode::PARTIAL_CONTENT.as_u16()) {
+        return Err(invalid_chunk(ResponseChunkMismatch::UnexpectedStatusCode {
+            status_code: chunk.status_code,
+        }));
+    }
+
+    let Some((_, content_range)) = chunk
+        .headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(header::CONTENT_RANGE.as_str()))
+    else {
+        return Err(invalid_chunk(ResponseChunkMismatch::MissingContentRange));
+    };
+
+    let Some(range) = ContentRange::parse(content_range) else {
+        return Err(invalid_chunk(
+            ResponseChunkMismatch::MalformedContentRange {
+                content_range: content_range.clone(),
+            },
+        ));
+    };
+
+    if range.range_begin != expected_range_begin {
+        return Err(invalid_chunk(ResponseChunkMismatch::DiscontinuousRange {
+            expected_range_begin,
+            range_begin: range.range_begin,
+        }));
+    }
+
+    if let Some(expected_total_length) = expected_total_length {
+        if range.total_length != expected_total_length {
+            return Err(invalid_chunk(ResponseChunkMismatch::TotalLengthMismatch {
+                expected_total_length,
+                total_length: range.total_length,
+            }));
+        }
+    }
+
+    let range_length = range.range_end - range.range_begin + 1;
+    if chunk.body.len() != range_length {
+        return Err(invalid_chunk(ResponseChunkMismatch::BodyLengthMismatch {
+            range_length,
+            body_length: chunk.body.len(),
+        }));
+    }
+
+    Ok(range)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    const REQUEST_PATH: &str = "/video.mp4";
+    const ETAG: &str = "\"1\"";
+
+    fn response(content_range: &str, body: &[u8]) -> VerifiedResponse {
+        VerifiedResponse {
+            status_code: Some(206),
+            headers: vec![
+                ("content-type".into(), "text/plain".into()),
+                ("etag".into(), ETAG.into()),
+                ("content-length".into(), body.len().to_string()),
+                ("content-range".into(), content_range.into()),
+                (CERTIFICATE_HEADER_NAME.into(), "certificate=:AA==:".into()),
+            ],
+            body: body.to_vec(),
+        }
+    }
+
+    fn chunk(content_range: &str, body: &[u8]) -> VerifiedResponseChunk {
+        VerifiedResponseChunk {
+            request_path: REQUEST_PATH.into(),
+            response: response(content_range, body),
+        }
+    }
+
+    #[rstest]
+    #[case::single_chunk(vec![chunk("bytes 0-4/5", &[1, 2, 3, 4, 5])])]
+    #[case::multiple_chunks(vec![
+        chunk("bytes 0-1/5", &[1, 2]),
+        chunk("bytes 2-3/5", &[3, 4]),
+        chunk("bytes 4-4/5", &[5]),
+    ])]
+    fn reassemble_chunks(#[case] chunks: Vec<VerifiedResponseChunk>) {
+        let response = reassemble_chunked_response(chunks).unwrap();
+
+        assert_eq!(
+            response,
+            VerifiedResponse {
+                status_code: Some(200),
+                headers: vec![
+                    ("content-type".into(), "text/plain".into()),
+                    ("etag".into(), ETAG.into()),
+                    ("content-length".into(), "5".into()),
+                ],
+                body: vec![1, 2, 3, 4, 5],
+            }
+        );
+    }
+
+    #[test]
+    fn reassemble_no_chunks() {
+        let result = reassemble_chunked_response(vec![]);
+
+        assert!(matches!(
+            result,
+            Err(ResponseVerificationError::MissingResponseChunks)
+        ));
+    }
+
+    #[test]
+    fn reassemble_incomplete_chunks() {
+        let result = reassemble_chunked_response(vec![
+            chunk("bytes 0-1/5", &[1, 2]),
+            chunk("bytes 2-3/5", &[3, 4]),
+        ]);
+
+        assert!(matches!(
+            result,
+            Err(ResponseVerificationError::IncompleteChunkedResponse {
+                length: 4,
+                total_length: 5,
+            })
+        ));
+    }
+
+    #[test]
+    fn reassemble_chunks_with_huge_total_length() {
+        let result =
+            reassemble_chunked_response(vec![chunk(&format!("bytes 0-0/{}", usize::MAX), &[1])]);
+
+        assert!(matches!(
+            result,
+            Err(ResponseVerificationError::IncompleteChunkedResponse {
+                length: 1,
+                total_length: usize::MAX,
+            })
+        ));
+    }
+
+    #[rstest]
+    #[case::uncertified_status_code(
+        VerifiedResponse { status_code: None, ..response("bytes 2-4/5", &[3, 4, 5]) },
+        ResponseChunkMismatch::UnexpectedStatusCode { status_code: None },
+    )]
+    #[case::ok_status_code(
+        VerifiedResponse { status_code: Some(200), ..response("bytes 2-4/5", &[3, 4, 5]) },
+        ResponseChunkMismatch::UnexpectedStatusCode { status_code: Some(200) },
+    )]
+    #[case::missing_content_range(
+        VerifiedResponse {
+            headers: vec![("etag".into(), ETAG.into())],
+            ..response("bytes 2-4/5", &[3, 4, 5])
+        },
+        ResponseChunkMismatch::MissingContentRange,
+    )]
+    #[case::malformed_content_range(
+        response("bytes 2-/5", &[3, 4, 5]),
+        ResponseChunkMismatch::MalformedContentRange { content_range: "bytes 2-/5".into() },
+    )]
+    #[case::out_of_bounds_content_range(
+        response("bytes 2-5/5", &[3, 4, 5]),
+        ResponseChunkMismatch::MalformedContentRange { content_range: "bytes 2-5/5".into() },
+    )]
+    #[case::overlapping_range(
+        response("bytes 1-4/5", &[2, 3, 4, 5]),
+        ResponseChunkMismatch::DiscontinuousRange { expected_range_begin: 2, range_begin: 1 },
+    )]
+    #[case::gap_in_range(
+        response("bytes 3-4/5", &[4, 5]),
+        ResponseChunkMismatch::DiscontinuousRange { expected_range_begin: 2, range_begin: 3 },
+    )]
+    #[case::total_length_mismatch(
+        response("bytes 2-4/6", &[3, 4, 5]),
+        ResponseChunkMismatch::TotalLengthMismatch { expected_total_length: 5, total_length: 6 },
+    )]
+    #[case::body_length_mismatch(
+        response("bytes 2-4/5", &[3, 4]),
+        ResponseChunkMismatch::BodyLengthMismatch { range_length: 3, body_length: 2 },
+    )]
+    fn reassemble_invalid_chunk(
+        #[case] invalid_chunk: VerifiedResponse,
+        #[case] expected_mismatch: ResponseChunkMismatch,
+    ) {
+        let invalid_chunk = VerifiedResponseChunk {
+            request_path: REQUEST_PATH.into(),
+            response: invalid_chunk,
+        };
+        let result =
+            reassemble_chunked_response(vec![chunk("bytes 0-1/5", &[1, 2]), invalid_chunk]);
+
+        assert!(matches!(
+            result,
+            Err(ResponseVerificationError::InvalidResponseChunk {
+                chunk_index: 1,
+                mismatch,
+            }) if mismatch == expected_mismatch
+        ));
+    }
+
+    #[test]
+    fn reassemble_first_chunk_not_at_start() {
+        let result = reassemble_chunked_response(vec![chunk("bytes 2-4/5", &[3, 4, 5])]);
+
+        assert!(matches!(
+            result,
+            Err(ResponseVerificationError::InvalidResponseChunk {
+                chunk_index: 0,
+                mismatch: ResponseChunkMismatch::DiscontinuousRange {
+                    expected_range_begin: 0,
+                    range_begin: 2,
+                },
+            })
+        ));
+    }
+
+    #[rstest]
+    #[case::other_request_path(
+        VerifiedResponseChunk { request_path: "/audio.mp3".into(), ..chunk("bytes 2-4/5", &[3, 4, 5]) },
+        ResponseChunkMismatch::RequestPathMismatch {
+            expected_request_path: REQUEST_PATH.into(),
+            request_path: "/audio.mp3".into(),
+        },
+    )]
+    #[case::other_etag(
+        VerifiedResponseChunk {
+            request_path: REQUEST_PATH.into(),
+            response: VerifiedResponse {
+                headers: vec![
+                    ("etag".into(), "\"2\"".into()),
+                    ("content-range".into(), "bytes 2-4/5".into()),
+                ],
+                ..response("bytes 2-4/5", &[3, 4, 5])
+            },
+        },
+        ResponseChunkMismatch::ETagMismatch {
+            expected_etag: Some(ETAG.into()),
+            etag: Some("\"2\"".into()),
+        },
+    )]
+    #[case::missing_etag(
+        VerifiedResponseChunk {
+            request_path: REQUEST_PATH.into(),
+            response: VerifiedResponse {
+                headers: vec![("content-range".into(), "bytes 2-4/5".into())],
+                ..response("bytes 2-4/5", &[3, 4, 5])
+            },
+        },
+        ResponseChunkMismatch::ETagMismatch {
+            expected_etag: Some(ETAG.into()),
+            etag: None,
+        },
+    )]
+    fn reassemble_chunks_of_different_responses(
+        #[case] other_chunk: VerifiedResponseChunk,
+        #[case] expected_mismatch: ResponseChunkMismatch,
+    ) {
+        let result = reassemble_chunked_response(vec![chunk("bytes 0-1/5", &[1, 2]), other_chunk]);
+
+        assert!(matches!(
+            result,
+            Err(ResponseVerificationError::InvalidResponseChunk {
+                chunk_index: 1,
+                mismatch,
+            }) if mismatch == expected_mismatch
+        ));
+    }
+}
@@ -1,10 +1,20 @@
 //! The primary entry point for the repsonse verification API.
 
 mod body;
+
+mod body_verifier;
+pub use body_verifier::*;
+
 mod certificate_header_field;
 
+mod chunked_response;
+pub use chunked_response::*;
+
 mod certificate_header;
 pub use certificate_header::*;
 
 mod verify_request_response_pair;
 pub use verify_request_response_pair::*;
+
+mod version_negotiation;
+pub use version_negotiation::*;
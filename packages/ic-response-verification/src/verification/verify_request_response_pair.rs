@@ -1,13 +1,18 @@
-use super::{body::decode_body, certificate_header::CertificateHeader};
+use super::{
+    body::BodyDecoder,
+    body_verifier::{BodyVerifier, PendingVerification},
+    certificate_header::CertificateHeader,
+    version_negotiation::validate_verification_version,
+};
 use crate::{
-    cel::{map_cel_ast, parse_cel_expression},
+    cel::{parse_cel_expression_header, CelParserError},
     error::{ResponseVerificationError, ResponseVerificationResult},
-    types::{VerificationInfo, VerifiedResponse},
-    validation::{
-        validate_body, validate_expr_hash, validate_expr_path, validate_hashes, validate_tree,
+    types::{
+        CertificateCache, TimeConfig, TimeSource, VerificationInfo, VerificationPolicy,
+        VerifiedCertification, VerifiedResponse,
     },
+    validation::{certified_data, validate_expr_hash, validate_expr_path, validate_tree},
 };
-use ic_certificate_verification::VerifyCertificate;
 use ic_certification::{hash_tree::Hash, Certificate, HashTree};
 use ic_http_certification::{
     cel::{
@@ -27,15 +32,243 @@ pub const MAX_VERIFICATION_VERSION: u8 = 2;
 
 /// The primary entry point for verifying a request and response pair. This will verify the response
 /// with respect to the request, according the [Response Verification Spec]().
+///
+/// The time of the certificate is verified against the current time of the given [TimeSource],
+/// within the bounds of the given [TimeConfig].
+///
+/// The response must additionally meet the requirements of the given [VerificationPolicy], use
+/// [VerificationPolicy::default] to only perform the checks of the spec.
+#[allow(clippy::too_many_arguments)]
 pub fn verify_request_response_pair(
+    request: HttpRequest,
+    response: HttpResponse,
+    canister_id: &[u8],
+    time_source: &dyn TimeSource,
+    time_config: &TimeConfig,
+    ic_public_key: &[u8],
+    min_requested_verification_version: u8,
+    policy: &VerificationPolicy,
+) -> ResponseVerificationResult<VerificationInfo> {
+    verify_with_certificates(
+        request,
+        response,
+        canister_id,
+        time_source.current_time_ns(),
+        time_config,
+        ic_public_key,
+        min_requested_verification_version,
+        policy,
+        &mut CertificateCache::unbounded(),
+    )
+}
+
+/// Same as [verify_request_response_pair], but reuses the certificates of previous verifications
+/// from the given [CertificateCache], and adds the certificate of the response to it.
+///
+/// A certificate is only decoded and its signature is only verified the first time that it is
+/// encountered, as long as it remains in the cache. Its time is checked for every response.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ic_http_certification::{HttpRequest, HttpResponse};
+/// use ic_response_verification::{
+///     types::{CertificateCache, SystemTimeSource, TimeConfig, VerificationPolicy},
+///     verify_request_response_pair_with_cache,
+/// };
+///
+/// # fn receive() -> (HttpRequest<'static>, HttpResponse<'static>) { unimplemented!() }
+/// # let canister_id: Vec<u8> = vec![];
+/// # let ic_public_key: Vec<u8> = vec![];
+/// let mut cache = CertificateCache::new(1_000);
+///
+/// loop {
+///     let (request, response) = receive();
+///
+///     let verification_info = verify_request_response_pair_with_cache(
+///         request,
+///         response,
+///         &canister_id,
+///         &SystemTimeSource,
+///         &TimeConfig::default(),
+///         &ic_public_key,
+///         2,
+///         &VerificationPolicy::default(),
+///         &mut cache,
+///     );
+/// }
+/// ```
+#[allow(clippy::too_many_arguments)]
+pub fn verify_request_response_pair_with_cache(
+    request: HttpRequest,
+    response: HttpResponse,
+    canister_id: &[u8],
+    time_source: &dyn TimeSource,
+    time_config: &TimeConfig,
+    ic_public_key: &[u8],
+    min_requested_verification_version: u8,
+    policy: &VerificationPolicy,
+    cache: &mut CertificateCache,
+) -> ResponseVerificationResult<VerificationInfo> {
+    verify_with_certificates(
+        request,
+        response,
+        canister_id,
+        time_source.current_time_ns(),
+        time_config,
+        ic_public_key,
+        min_requested_verification_version,
+        policy,
+        cache,
+    )
+}
+
+/// Verifies a batch of request and response pairs of the same canister, in the same way as
+/// [verify_request_response_pair], and returns the result of each pair in the same order.
+///
+/// Responses of a batch commonly share the same certificate, for example when they were certified
+/// in the same round. Each distinct certificate of the batch is only decoded once, and its signature
+/// is only verified once for all pairs whose `IC-Certificate` header contains it.
+#[allow(clippy::too_many_arguments)]
+pub fn verify_request_response_pairs<'a>(
+    pairs: impl IntoIterator<Item = (HttpRequest<'a>, HttpResponse<'a>)>,
+    canister_id: &[u8],
+    time_source: &dyn TimeSource,
+    time_config: &TimeConfig,
+    ic_public_key: &[u8],
+    min_requested_verification_version: u8,
+    policy: &VerificationPolicy,
+) -> Vec<ResponseVerificationResult<VerificationInfo>> {
+    let mut certificates = CertificateCache::unbounded();
+
+    pairs
+        .into_iter()
+        .map(|(request, response)| {
+            verify_with_certificates(
+                request,
+                response,
+                canister_id,
+                time_source.current_time_ns(),
+                time_config,
+                ic_public_key,
+                min_requested_verification_version,
+                policy,
+                &mut certificates,
+            )
+        })
+        .collect()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn verify_with_certificates(
     request: HttpRequest,
     response: HttpResponse,
     canister_id: &[u8],
     current_time_ns: u128,
-    max_cert_time_offset_ns: u128,
+    time_config: &TimeConfig,
     ic_public_key: &[u8],
     min_requested_verification_version: u8,
+    policy: &VerificationPolicy,
+    certificates: &mut CertificateCache,
 ) -> ResponseVerificationResult<VerificationInfo> {
+    let mut body_verifier = verify_streaming_with_certificates(
+        &request,
+        &response,
+        canister_id,
+        current_time_ns,
+        time_config,
+        ic_public_key,
+        min_requested_verification_version,
+        policy,
+        certificates,
+    )?;
+    body_verifier.update(response.body())?;
+
+    let mut verification_info = body_verifier.finalize()?;
+    if let Some(verified_response) = verification_info.response.as_mut() {
+        verified_response.body = response.body().to_vec();
+    }
+
+    Ok(verification_info)
+}
+
+/// Same as [verify_request_response_pair], but for responses whose body is streamed. Everything
+/// except for the response body is verified immediately, and the body of the given response is
+/// ignored.
+///
+/// The returned [BodyVerifier] is fed the chunks of the response body as they arrive, and
+/// completes verification once the body is complete, so that the body does not need to be
+/// buffered before verification can start.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ic_http_certification::{HttpRequest, HttpResponse};
+/// use ic_response_verification::{
+///     types::{SystemTimeSource, TimeConfig, VerificationPolicy},
+///     verify_request_response_pair_streaming,
+/// };
+///
+/// # fn chunks() -> Vec<Vec<u8>> { vec![] }
+/// # let canister_id: Vec<u8> = vec![];
+/// # let ic_public_key: Vec<u8> = vec![];
+/// let request = HttpRequest::get("/video.mp4").build();
+/// // the status code and headers of the response, without its body
+/// let response = HttpResponse::builder().build();
+///
+/// let mut body_verifier = verify_request_response_pair_streaming(
+///     &request,
+///     &response,
+///     &canister_id,
+///     &SystemTimeSource,
+///     &TimeConfig::default(),
+///     &ic_public_key,
+///     2,
+///     &VerificationPolicy::default(),
+/// )
+/// .unwrap();
+///
+/// for chunk in chunks() {
+///     body_verifier.update(&chunk).unwrap();
+/// }
+/// let verification_info = body_verifier.finalize().unwrap();
+/// ```
+#[allow(clippy::too_many_arguments)]
+pub fn verify_request_response_pair_streaming(
+    request: &HttpRequest,
+    response: &HttpResponse,
+    canister_id: &[u8],
+    time_source: &dyn TimeSource,
+    time_config: &TimeConfig,
+    ic_public_key: &[u8],
+    min_requested_verification_version: u8,
+    policy: &VerificationPolicy,
+) -> ResponseVerificationResult<BodyVerifier> {
+    verify_streaming_with_certificates(
+        request,
+        response,
+        canister_id,
+        time_source.current_time_ns(),
+        time_config,
+        ic_public_key,
+        min_requested_verification_version,
+        policy,
+        &mut CertificateCache::unbounded(),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn verify_streaming_with_certificates(
+    request: &HttpRequest,
+    response: &HttpResponse,
+    canister_id: &[u8],
+    current_time_ns: u128,
+    time_config: &TimeConfig,
+    ic_public_key: &[u8],
+    min_requested_verification_version: u8,
+    policy: &VerificationPolicy,
+    certificates: &mut CertificateCache,
+) -> ResponseVerificationResult<BodyVerifier> {
     let headers: HashMap<_, _> = response
         .headers()
         .iter()
@@ -46,15 +279,19 @@ pub fn verify_request_response_pair(
         return Err(ResponseVerificationError::MissingCertification);
     };
 
-    let certificate_header = CertificateHeader::from(certificate_header_str)?;
+    let mut certificate_bytes = Vec::new();
+    let certificate_header = CertificateHeader::parse(certificate_header_str, |bytes| {
+        certificate_bytes = bytes.to_vec();
+        certificates.parse(bytes)
+    })?;
+
+    validate_verification_version(
+        certificate_header.version,
+        min_requested_verification_version,
+        policy,
+    )?;
 
     match certificate_header.version {
-        version if version < min_requested_verification_version => Err(
-            ResponseVerificationError::RequestedVerificationVersionMismatch {
-                requested_version: version,
-                min_requested_verification_version,
-            },
-        ),
         1 => {
             let encoding = headers
                 .get("content-encoding")
@@ -62,14 +299,17 @@ pub fn verify_request_response_pair(
 
             v1_verification(V1VerificationOpts {
                 request,
-                response,
                 canister_id,
                 current_time_ns,
-                max_cert_time_offset_ns,
+                time_config,
                 tree: certificate_header.tree,
                 certificate: certificate_header.certificate,
+                certificate_bytes,
+                certificates,
                 encoding,
+                status_code: response.status_code().as_u16(),
                 ic_public_key,
+                policy,
             })
         }
         2 => match headers.get(&CERTIFICATE_EXPRESSION_HEADER_NAME.to_lowercase()) {
@@ -78,8 +318,7 @@ pub fn verify_request_response_pair(
                     return Err(ResponseVerificationError::MissingCertificateExpressionPath);
                 };
 
-                let cel_ast = parse_cel_expression(certificate_expression_header)?;
-                let certification = map_cel_ast(&cel_ast)?;
+                let certification = parse_cel_expression_header(certificate_expression_header)?;
                 let expr_hash = hash(certificate_expression_header.as_bytes());
 
                 v2_verification(V2VerificationOpts {
@@ -87,13 +326,16 @@ pub fn verify_request_response_pair(
                     response,
                     canister_id,
                     current_time_ns,
-                    max_cert_time_offset_ns,
+                    time_config,
                     tree: certificate_header.tree,
                     certificate: certificate_header.certificate,
+                    certificate_bytes,
+                    certificates,
                     expr_path,
                     expr_hash,
                     certification,
                     ic_public_key,
+                    policy,
                 })
             }
             None => Err(ResponseVerificationError::MissingCertification),
@@ -107,77 +349,81 @@ pub fn verify_request_response_pair(
 }
 
 struct V1VerificationOpts<'a> {
-    request: HttpRequest<'a>,
-    response: HttpResponse<'a>,
+    request: &'a HttpRequest<'a>,
     canister_id: &'a [u8],
     current_time_ns: u128,
-    max_cert_time_offset_ns: u128,
+    time_config: &'a TimeConfig,
     tree: HashTree,
     certificate: Certificate,
+    certificate_bytes: Vec<u8>,
+    certificates: &'a mut CertificateCache,
     encoding: Option<&'a str>,
+    status_code: u16,
     ic_public_key: &'a [u8],
+    policy: &'a VerificationPolicy,
 }
 
 fn v1_verification(
     V1VerificationOpts {
         request,
-        response,
         canister_id,
         current_time_ns,
-        max_cert_time_offset_ns,
+        time_config,
         tree,
         certificate,
+        certificate_bytes,
+        certificates,
         encoding,
+        status_code,
         ic_public_key,
+        policy,
     }: V1VerificationOpts<'_>,
-) -> ResponseVerificationResult<VerificationInfo> {
-    certificate.verify(
+) -> ResponseVerificationResult<BodyVerifier> {
+    certificates.verify(
+        &certificate_bytes,
+        &certificate,
         canister_id,
         ic_public_key,
-        &current_time_ns,
-        &max_cert_time_offset_ns,
+        current_time_ns,
+        time_config,
     )?;
 
     let request_path = request.get_path()?;
-    let decoded_body = decode_body(response.body(), encoding)?;
-    let decoded_body_sha = hash(decoded_body.as_slice());
 
     if !validate_tree(canister_id, &certificate, &tree) {
-        return Err(ResponseVerificationError::InvalidTree);
+        return Err(invalid_tree_error(canister_id, &certificate, &tree));
     }
 
-    let mut valid_body = validate_body(&tree, &request_path, &decoded_body_sha);
-    if encoding.is_some() && !valid_body {
-        let body_sha = hash(response.body());
-        valid_body = validate_body(&tree, &request_path, &body_sha);
-    }
-
-    if !valid_body {
-        return Err(ResponseVerificationError::InvalidResponseBody);
-    }
+    // version 1 of response verification does not certify response headers
+    policy.validate(None)?;
+    policy.validate_redirect(status_code, None, &[])?;
 
-    Ok(VerificationInfo {
-        response: Some(VerifiedResponse {
-            status_code: None,
-            headers: Vec::new(),
-            body: response.body().to_vec(),
-        }),
-        verification_version: 1,
-    })
+    Ok(BodyVerifier::new(
+        PendingVerification::V1 {
+            certificate,
+            tree,
+            request_path,
+            encoded: encoding.is_some(),
+        },
+        BodyDecoder::new(encoding, policy),
+    ))
 }
 
 struct V2VerificationOpts<'a> {
-    request: HttpRequest<'a>,
-    response: HttpResponse<'a>,
+    request: &'a HttpRequest<'a>,
+    response: &'a HttpResponse<'a>,
     canister_id: &'a [u8],
     current_time_ns: u128,
-    max_cert_time_offset_ns: u128,
+    time_config: &'a TimeConfig,
     tree: HashTree,
     certificate: Certificate,
+    certificate_bytes: Vec<u8>,
+    certificates: &'a mut CertificateCache,
     expr_path: Vec<String>,
     expr_hash: Hash,
     certification: CelExpression<'a>,
     ic_public_key: &'a [u8],
+    policy: &'a VerificationPolicy,
 }
 
 fn v2_verification(
@@ -186,26 +432,31 @@ fn v2_verification(
         response,
         canister_id,
         current_time_ns,
-        max_cert_time_offset_ns,
+        time_config,
         tree,
         certificate,
+        certificate_bytes,
+        certificates,
         expr_path,
         expr_hash,
         certification,
         ic_public_key,
+        policy,
     }: V2VerificationOpts<'_>,
-) -> ResponseVerificationResult<VerificationInfo> {
+) -> ResponseVerificationResult<BodyVerifier> {
     let request_path = request.get_path()?;
 
-    certificate.verify(
+    certificates.verify(
+        &certificate_bytes,
+        &certificate,
         canister_id,
         ic_public_key,
-        &current_time_ns,
-        &max_cert_time_offset_ns,
+        current_time_ns,
+        time_config,
     )?;
 
     if !validate_tree(canister_id, &certificate, &tree) {
-        return Err(ResponseVerificationError::InvalidTree);
+        return Err(invalid_tree_error(canister_id, &certificate, &tree));
     }
 
     validate_expr_path(&expr_path, &request_path, &tree)?;
@@ -213,10 +464,19 @@ fn v2_verification(
     let (request_certification, response_certification) = match &certification {
         CelExpression::Default(DefaultCelExpression::Skip) => {
             return match validate_expr_hash(&expr_path, &expr_hash, &tree).is_some() {
-                true => Ok(VerificationInfo {
-                    response: None,
-                    verification_version: 2,
-                }),
+                true => {
+                    policy.validate(None)?;
+                    policy.validate_redirect(response.status_code().as_u16(), None, &expr_path)?;
+
+                    Ok(BodyVerifier::new(
+                        PendingVerification::Verified(VerificationInfo {
+                            response: None,
+                            verification_version: 2,
+                            certification: VerifiedCertification { certificate, tree },
+                        }),
+                        None,
+                    ))
+                }
                 false => Err(ResponseVerificationError::InvalidExpressionPath),
             };
         }
@@ -227,46 +487,63 @@ fn v2_verification(
             request,
             response,
         })) => (Some(request), response),
+        unsupported_certification => {
+            return Err(CelParserError::UnrecognizedFunction(
+                unsupported_certification.to_string(),
+            )
+            .into());
+        }
     };
 
     let request_hash = request_certification
         .as_ref()
-        .map(|request_certification| request_hash(&request, request_certification))
+        .map(|request_certification| request_hash(request, request_certification))
         .transpose()?;
 
-    let body_hash = hash(response.body());
-    let response_headers = filter_response_headers(&response, response_certification);
+    let mut response_headers = filter_response_headers(response, response_certification);
+    policy.remove_ignored_response_headers(&mut response_headers.headers);
     let response_headers_hash =
         response_headers_hash(&response.status_code().as_u16().into(), &response_headers);
-    let response_hash = hash([response_headers_hash, body_hash].concat().as_slice());
 
-    let are_hashes_valid = validate_hashes(
-        &expr_hash,
-        &request_hash,
-        &response_hash,
+    policy.validate(Some(response_certification))?;
+    policy.validate_redirect(
+        response.status_code().as_u16(),
+        Some(response_certification),
         &expr_path,
-        &tree,
-        &certification,
-    );
-
-    match are_hashes_valid {
-        true => {
-            let mut all_headers = response_headers.headers;
-            // add the certificate header back to the response
-            let Some(certificate_header_str) = response_headers.certificate else {
-                return Err(ResponseVerificationError::MissingCertification);
-            };
-            all_headers.push((CERTIFICATE_HEADER_NAME.to_string(), certificate_header_str));
-
-            Ok(VerificationInfo {
-                response: Some(VerifiedResponse {
-                    status_code: Some(response.status_code().into()),
-                    headers: all_headers,
-                    body: response.body().to_vec(),
-                }),
-                verification_version: 2,
-            })
-        }
-        false => Err(ResponseVerificationError::InvalidResponseHashes),
+    )?;
+
+    let mut all_headers = response_headers.headers;
+    // add the certificate header back to the response
+    let Some(certificate_header_str) = response_headers.certificate else {
+        return Err(ResponseVerificationError::MissingCertification);
+    };
+    all_headers.push((CERTIFICATE_HEADER_NAME.to_string(), certificate_header_str));
+
+    Ok(BodyVerifier::new(
+        PendingVerification::V2 {
+            certificate,
+            tree,
+            expr_path,
+            expr_hash,
+            request_hash,
+            response_headers_hash,
+            response: VerifiedResponse {
+                status_code: Some(response.status_code().into()),
+                headers: all_headers,
+                body: Vec::new(),
+            },
+        },
+        None,
+    ))
+}
+
+fn invalid_tree_error(
+    canister_id: &[u8],
+    certificate: &Certificate,
+    tree: &HashTree,
+) -> ResponseVerificationError {
+    ResponseVerificationError::InvalidTree {
+        tree_root_hash: hex::encode(tree.digest()),
+        certified_data: certified_data(canister_id, certificate).map(hex::encode),
     }
 }
@@ -0,0 +1,313 @@
+use super::{
+    certificate_header::CertificateHeader, MAX_VERIFICATION_VERSION, MIN_VERIFICATION_VERSION,
+};
+use crate::{
+    error::{ResponseVerificationError, ResponseVerificationResult},
+    types::VerificationPolicy,
+};
+use ic_http_certification::{
+    HttpResponse, CERTIFICATE_EXPRESSION_HEADER_NAME, CERTIFICATE_HEADER_NAME,
+};
+use std::ops::RangeInclusive;
+
+/// Returns the range of verification versions that are supported by this package.
+///
+/// # Examples
+///
+/// ```
+/// use ic_response_verification::supported_verification_versions;
+///
+/// assert!(supported_verification_versions().contains(&2));
+/// ```
+pub fn supported_verification_versions() -> RangeInclusive<u8> {
+    MIN_VERIFICATION_VERSION..=MAX_VERIFICATION_VERSION
+}
+
+/// Returns the highest verification version that is supported by this package and accepted by
+/// the given [VerificationPolicy]. This is the version to request from the canister, for example
+/// as the `certificate_version` of an HTTP request.
+///
+/// # Examples
+///
+/// ```
+/// use ic_response_verification::{max_accepted_verification_version, types::VerificationPolicy};
+///
+/// let policy = VerificationPolicy::new().with_max_verification_version(1);
+///
+/// assert_eq!(max_accepted_verification_version(&VerificationPolicy::default()), 2);
+/// assert_eq!(max_accepted_verification_version(&policy), 1);
+/// ```
+pub fn max_accepted_verification_version(policy: &VerificationPolicy) -> u8 {
+    policy
+        .max_verification_version()
+        .map_or(MAX_VERIFICATION_VERSION, |max_verification_version| {
+            max_verification_version.min(MAX_VERIFICATION_VERSION)
+        })
+}
+
+/// Returns the highest verification version that is offered by the canister, supported by this
+/// package, accepted by the given [VerificationPolicy] and not lower than
+/// `min_requested_verification_version`. This is the version to request from a canister that
+/// certifies its responses with any of the `offered_versions`.
+///
+/// If the canister offers higher versions than this package supports or the policy accepts, the
+/// highest version that is accepted is negotiated instead. If none of the offered versions is
+/// accepted, [NoCommonVerificationVersion](ResponseVerificationError::NoCommonVerificationVersion)
+/// is returned.
+///
+/// # Examples
+///
+/// ```
+/// use ic_response_verification::{negotiate_verification_version, types::VerificationPolicy};
+///
+/// let policy = VerificationPolicy::new().with_max_verification_version(1);
+///
+/// assert_eq!(
+///     negotiate_verification_version(&[1, 2], 1, &VerificationPolicy::default()).unwrap(),
+///     2
+/// );
+/// assert_eq!(negotiate_verification_version(&[1, 2], 1, &policy).unwrap(), 1);
+/// assert!(negotiate_verification_version(&[1], 2, &VerificationPolicy::default()).is_err());
+/// ```
+pub fn negotiate_verification_version(
+    offered_versions: &[u8],
+    min_requested_verification_version: u8,
+    policy: &VerificationPolicy,
+) -> ResponseVerificationResult<u8> {
+    let min_accepted_version = min_requested_verification_version.max(MIN_VERIFICATION_VERSION);
+    let max_accepted_version = max_accepted_verification_version(policy);
+
+    offered_versions
+        .iter()
+        .copied()
+        .filter(|version| (min_accepted_version..=max_accepted_version).contains(version))
+        .max()
+        .ok_or_else(|| ResponseVerificationError::NoCommonVerificationVersion {
+            offered_versions: offered_versions.to_vec(),
+            min_accepted_version,
+            max_accepted_version,
+        })
+}
+
+/// Returns the verification version that the given response will be verified with by
+/// [verify_request_response_pair](crate::verify_request_response_pair), based on the version of
+/// its `IC-Certificate` header, without verifying the response.
+///
+/// The version must be supported by this package, must not be lower than
+/// `min_requested_verification_version` and must be accepted by the given [VerificationPolicy],
+/// otherwise the same error is returned that verification of the response would fail with.
+pub fn response_verification_version(
+    response: &HttpResponse,
+    min_requested_verification_version: u8,
+    policy: &VerificationPolicy,
+) -> ResponseVerificationResult<u8> {
+    let Some((_, certificate_header)) = response
+        .headers()
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(CERTIFICATE_HEADER_NAME))
+    else {
+        return Err(ResponseVerificationError::MissingCertification);
+    };
+
+    let version = CertificateHeader::parse_version(certificate_header)?;
+    validate_verification_version(version, min_requested_verification_version, policy)?;
+
+    let has_certificate_expression = response
+        .headers()
+        .iter()
+        .any(|(name, _)| name.eq_ignore_ascii_case(CERTIFICATE_EXPRESSION_HEADER_NAME));
+    if version >= 2 && !has_certificate_expression {
+        return Err(ResponseVerificationError::MissingCertification);
+    }
+
+    Ok(version)
+}
+
+/// Validates that the given verification version is supported by this package and by the
+/// [VerificationPolicy], and is not lower than the minimum requested verification version.
+pub(super) fn validate_verification_version(
+    version: u8,
+    min_requested_verification_version: u8,
+    policy: &VerificationPolicy,
+) -> ResponseVerificationResult {
+    if let Some(max_verification_version) = policy.max_verification_version() {
+        if version > max_verification_version {
+            return Err(
+                ResponseVerificationError::RequestedVerificationVersionAboveMaximum {
+                    max_verification_version,
+                    requested_version: version,
+                },
+            );
+        }
+    }
+
+    if version < min_requested_verification_version {
+        return Err(
+            ResponseVerificationError::RequestedVerificationVersionMismatch {
+                requested_version: version,
+                min_requested_verification_version,
+            },
+        );
+    }
+
+    if !supported_verification_versions().contains(&version) {
+        return Err(ResponseVerificationError::UnsupportedVerificationVersion {
+            min_supported_version: MIN_VERIFICATION_VERSION,
+            max_supported_version: MAX_VERIFICATION_VERSION,
+            requested_version: version,
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::create_header_field;
+
+    fn response_with_version(version: Option<&str>) -> HttpResponse<'static> {
+        let mut certificate_header = vec![
+            create_header_field("certificate", "AA=="),
+            create_header_field("tree", "AA=="),
+        ];
+        if let Some(version) = version {
+            certificate_header.push(create_header_field("version", version));
+        }
+
+        HttpResponse::ok(
+            vec![],
+            vec![
+                (
+                    CERTIFICATE_HEADER_NAME.to_string(),
+                    certificate_header.join(","),
+                ),
+                (
+                    CERTIFICATE_EXPRESSION_HEADER_NAME.to_string(),
+                    "default_certification(ValidationArgs{no_certification:Empty{}})".to_string(),
+                ),
+            ],
+        )
+        .build()
+    }
+
+    #[test]
+    fn response_version() {
+        let policy = VerificationPolicy::default();
+
+        assert_eq!(
+            response_verification_version(&response_with_version(None), 1, &policy).unwrap(),
+            1
+        );
+        assert_eq!(
+            response_verification_version(&response_with_version(Some("2")), 1, &policy).unwrap(),
+            2
+        );
+        assert_eq!(
+            response_verification_version(&response_with_version(Some("2")), 2, &policy).unwrap(),
+            2
+        );
+    }
+
+    #[test]
+    fn invalid_response_version() {
+        let policy = VerificationPolicy::new().with_max_verification_version(1);
+
+        assert!(matches!(
+            response_verification_version(
+                &HttpResponse::ok(vec![], vec![]).build(),
+                1,
+                &VerificationPolicy::default()
+            ),
+            Err(ResponseVerificationError::MissingCertification)
+        ));
+        assert!(matches!(
+            response_verification_version(&response_with_version(None), 2, &policy),
+            Err(
+                ResponseVerificationError::RequestedVerificationVersionMismatch {
+                    requested_version: 1,
+                    min_requested_verification_version: 2,
+                }
+            )
+        ));
+        assert!(matches!(
+            response_verification_version(&response_with_version(Some("2")), 1, &policy),
+            Err(
+                ResponseVerificationError::RequestedVerificationVersionAboveMaximum {
+                    max_verification_version: 1,
+                    requested_version: 2,
+                }
+            )
+        ));
+        assert!(matches!(
+            response_verification_version(
+                &response_with_version(Some("3")),
+                1,
+                &VerificationPolicy::default()
+            ),
+            Err(ResponseVerificationError::UnsupportedVerificationVersion {
+                requested_version: 3,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn negotiate_highest_common_version() {
+        let policy = VerificationPolicy::default();
+
+        assert_eq!(negotiate_verification_version(&[1], 1, &policy).unwrap(), 1);
+        assert_eq!(
+            negotiate_verification_version(&[1, 2], 1, &policy).unwrap(),
+            2
+        );
+        assert_eq!(
+            negotiate_verification_version(&[2, 1], 1, &policy).unwrap(),
+            2
+        );
+        assert_eq!(
+            negotiate_verification_version(&[1, 2, 3], 1, &policy).unwrap(),
+            2
+        );
+    }
+
+    #[test]
+    fn negotiate_downgraded_version() {
+        let policy = VerificationPolicy::new().with_max_verification_version(1);
+
+        assert_eq!(
+            negotiate_verification_version(&[1, 2], 1, &policy).unwrap(),
+            1
+        );
+        assert_eq!(
+            negotiate_verification_version(&[1, 2, 3], 1, &VerificationPolicy::default()).unwrap(),
+            2
+        );
+    }
+
+    #[test]
+    fn negotiate_without_common_version() {
+        let policy = VerificationPolicy::new().with_max_verification_version(1);
+
+        assert!(matches!(
+            negotiate_verification_version(&[1], 2, &VerificationPolicy::default()),
+            Err(ResponseVerificationError::NoCommonVerificationVersion {
+                offered_versions,
+                min_accepted_version: 2,
+                max_accepted_version: 2,
+            }) if offered_versions == vec![1]
+        ));
+        assert!(matches!(
+            negotiate_verification_version(&[2, 3], 1, &policy),
+            Err(ResponseVerificationError::NoCommonVerificationVersion {
+                min_accepted_version: 1,
+                max_accepted_version: 1,
+                ..
+            })
+        ));
+        assert!(matches!(
+            negotiate_verification_version(&[], 1, &VerificationPolicy::default()),
+            Err(ResponseVerificationError::NoCommonVerificationVersion { .. })
+        ));
+    }
+}